@@ -8,5 +8,5 @@ pub mod types;
 
 pub use types::{
     AccountSummary, ActionDetail, ActionListFilter, ActionListItem, LabelColors, LabelSummary,
-    MessageSummary, PaginatedResponse, UndoActionResponse,
+    MessageSummary, PaginatedResponse, ThreadDetail, UndoActionResponse,
 };
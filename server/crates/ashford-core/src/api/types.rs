@@ -10,6 +10,7 @@ use ts_rs::TS;
 
 use crate::accounts::SyncStatus;
 use crate::decisions::{ActionStatus, Decision};
+use crate::threads::ThreadSummary;
 
 /// Summary of an account for API responses.
 /// Excludes sensitive OAuth tokens and configuration details.
@@ -130,6 +131,25 @@ pub struct ActionDetail {
     pub has_been_undone: bool,
     /// If undone, the ID of the undo action
     pub undo_action_id: Option<String>,
+    /// Rolling summary of the message's thread, if one has been generated
+    pub thread_summary: Option<ThreadSummary>,
+}
+
+/// A thread with its rolling summary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ThreadDetail {
+    pub id: String,
+    pub account_id: String,
+    pub provider_thread_id: String,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+    pub last_message_at: Option<DateTime<Utc>>,
+    /// Messages of the thread stored locally
+    #[ts(type = "number")]
+    pub message_count: i64,
+    /// The summary, if one has been generated
+    pub summary: Option<ThreadSummary>,
 }
 
 /// Response for the undo action endpoint.
@@ -0,0 +1,151 @@
+use ashford_core::eval::{dataset_from_feedback, load_dataset, write_dataset};
+use ashford_core::llm::Redactor;
+use ashford_core::{
+    Config, DEFAULT_ORG_ID, DEFAULT_USER_ID, Database, EvalRunner, GenaiLLMClient, LLMClient,
+    PolicyConfig, PricingConfig, ReplayLLMClient, migrations,
+};
+use std::env;
+use std::error::Error;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+type AnyError = Box<dyn Error + Send + Sync>;
+
+const USAGE: &str = "Usage:
+  classifier-eval run <dataset.jsonl> [--replay] [--json] [--min-accuracy <0-1>]
+  classifier-eval export <output.jsonl> [--limit <n>]
+
+Reads the config from CONFIG_PATH (default config.toml). `run --replay` answers with the
+responses recorded in the dataset and only uses the config for policy and pricing, if present.
+`export` writes a dataset built from corrected decisions.";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]).await,
+        Some("export") => export(&args[1..]).await,
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: &[String]) -> Result<ExitCode, AnyError> {
+    let dataset_path = positional(args)?;
+    let replay = args.iter().any(|arg| arg == "--replay");
+    let json = args.iter().any(|arg| arg == "--json");
+    let min_accuracy: Option<f64> = flag_value(args, "--min-accuracy")
+        .map(str::parse)
+        .transpose()?;
+
+    let cases = load_dataset(dataset_path)?;
+    let mut redactor = None;
+    let (llm, policy, pricing): (Arc<dyn LLMClient>, PolicyConfig, PricingConfig) = if replay {
+        let (policy, pricing) = match Config::load(config_path()) {
+            Ok(config) => (config.policy, config.pricing),
+            Err(_) => (PolicyConfig::default(), PricingConfig::default()),
+        };
+        (
+            Arc::new(ReplayLLMClient::from_cases(&cases)),
+            policy,
+            pricing,
+        )
+    } else {
+        let config = Config::load(config_path())?;
+        let db = open_database(&config).await?;
+        let client = GenaiLLMClient::new(db, config.model.clone())
+            .with_pricing(config.pricing.clone())
+            .with_routing(&config.routing);
+        if config.redaction.enabled {
+            redactor = Some(Redactor::new(&config.redaction)?);
+        }
+        (Arc::new(client), config.policy, config.pricing)
+    };
+
+    let mut runner = EvalRunner::new(llm, policy).with_pricing(pricing);
+    if let Some(redactor) = redactor {
+        runner = runner.with_redactor(redactor);
+    }
+    let report = runner.run(&cases).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report.summary());
+    }
+
+    if let Some(min_accuracy) = min_accuracy
+        && report.accuracy < min_accuracy
+    {
+        eprintln!(
+            "accuracy {:.3} is below the required {:.3}",
+            report.accuracy, min_accuracy
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn export(args: &[String]) -> Result<ExitCode, AnyError> {
+    let output_path = positional(args)?;
+    let limit: i64 = flag_value(args, "--limit")
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or(500);
+
+    let config = Config::load(config_path())?;
+    let db = open_database(&config).await?;
+    let cases = dataset_from_feedback(&db, DEFAULT_ORG_ID, DEFAULT_USER_ID, limit).await?;
+    write_dataset(output_path, &cases)?;
+    let recorded = cases
+        .iter()
+        .filter(|case| case.recorded_response.is_some())
+        .count();
+    println!(
+        "wrote {} cases ({} with recorded responses) to {}",
+        cases.len(),
+        recorded,
+        output_path
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn config_path() -> String {
+    env::var("CONFIG_PATH").unwrap_or_else(|_| "config.toml".to_string())
+}
+
+async fn open_database(config: &Config) -> Result<Database, AnyError> {
+    let db = Database::new(&config.paths.database).await?;
+    migrations::run_migrations(&db).await?;
+    Ok(db)
+}
+
+/// The first argument that is neither a flag nor a flag's value.
+fn positional(args: &[String]) -> Result<&str, AnyError> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--min-accuracy" | "--limit" => {
+                iter.next();
+            }
+            flag if flag.starts_with("--") => {}
+            value => return Ok(value),
+        }
+    }
+    Err(USAGE.into())
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
@@ -0,0 +1,952 @@
+//! Bulk undo.
+//!
+//! Selects completed actions by rule, decision source, account, action type and time window,
+//! so a bad rule's work can be reverted at once. Each undoable action gets its own
+//! `undo.action` job; a stored bulk undo keeps the selection so progress can be read back
+//! from those jobs and the undo actions they create.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use libsql::{Row, params};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::db::{Database, DbError};
+use crate::decisions::{ActionStatus, DecisionSource};
+use crate::jobs::JOB_TYPE_UNDO_ACTION;
+
+const BULK_UNDO_COLUMNS: &str =
+    "id, filter_json, action_ids_json, skipped_json, created_at, org_id, user_id";
+
+/// Most actions a single bulk undo may enqueue.
+pub const MAX_BULK_UNDO_ACTIONS: usize = 2000;
+
+#[derive(Debug, Error)]
+pub enum BulkUndoError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("datetime parse error: {0}")]
+    DateTimeParse(#[from] chrono::ParseError),
+    #[error("bulk undo not found: {0}")]
+    NotFound(String),
+    #[error("{0} actions selected; a bulk undo covers at most {MAX_BULK_UNDO_ACTIONS}")]
+    TooManyActions(usize),
+    #[error("until must be after since")]
+    InvalidWindow,
+    #[error("until is required; pass the until returned by the preview")]
+    UntilRequired,
+}
+
+/// Which actions to undo. Unset fields match everything; `action_types` empty matches
+/// every type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkUndoFilter {
+    /// Deterministic rule that chose the actions.
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    #[serde(default)]
+    pub source: Option<DecisionSource>,
+    #[serde(default)]
+    pub account_id: Option<String>,
+    #[serde(default)]
+    pub action_types: Vec<String>,
+    /// Start of the window, on action creation time.
+    pub since: DateTime<Utc>,
+    /// End of the window; defaults to now when previewing. Required to create a bulk undo.
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// An action the filter selected that can be undone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkUndoCandidate {
+    pub action_id: String,
+    pub account_id: String,
+    pub action_type: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Why a selected action cannot be undone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum BulkUndoSkipReason {
+    /// The action never completed, so there is nothing to revert.
+    NotCompleted,
+    /// The action records no inverse.
+    NotSupported,
+    /// The action cannot be reverted, e.g. a permanent delete.
+    Irreversible,
+    AlreadyUndone,
+    /// An earlier undo failed or was canceled.
+    UndoAttempted,
+    UndoInProgress,
+}
+
+/// An action the filter selected that was left alone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkUndoSkip {
+    pub action_id: String,
+    pub action_type: String,
+    pub reason: BulkUndoSkipReason,
+    /// Human readable explanation, e.g. `not completed (status failed)`.
+    pub detail: String,
+}
+
+/// The actions a filter selects, split by whether they can be undone. Oldest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkUndoPreview {
+    /// End of the window the preview used. Creating the bulk undo with this `until` covers
+    /// the same actions, not ones created since.
+    pub until: DateTime<Utc>,
+    pub undoable: Vec<BulkUndoCandidate>,
+    pub not_undoable: Vec<BulkUndoSkip>,
+}
+
+/// A stored bulk undo request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkUndo {
+    pub id: String,
+    #[ts(type = "number")]
+    pub org_id: i64,
+    #[ts(type = "number")]
+    pub user_id: i64,
+    pub filter: BulkUndoFilter,
+    /// Actions an undo job was enqueued for.
+    pub action_ids: Vec<String>,
+    /// Actions the filter selected but that could not be undone.
+    pub skipped: Vec<BulkUndoSkip>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BulkUndo {
+    /// Idempotency key of the undo job enqueued for `action_id`.
+    pub fn job_idempotency_key(&self, action_id: &str) -> String {
+        job_idempotency_key(&self.id, action_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum BulkUndoItemStatus {
+    Pending,
+    Completed,
+    Failed,
+}
+
+/// Progress of one action's undo.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkUndoItem {
+    pub action_id: String,
+    pub status: BulkUndoItemStatus,
+    /// The `undo_*` action recording the revert, once the job created it.
+    pub undo_action_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A bulk undo with the state of each of its undo jobs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkUndoProgress {
+    pub bulk_undo: BulkUndo,
+    #[ts(type = "number")]
+    pub total: i64,
+    #[ts(type = "number")]
+    pub pending: i64,
+    #[ts(type = "number")]
+    pub completed: i64,
+    #[ts(type = "number")]
+    pub failed: i64,
+    pub items: Vec<BulkUndoItem>,
+}
+
+#[derive(Clone)]
+pub struct BulkUndoRepository {
+    db: Database,
+}
+
+impl BulkUndoRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Select the actions matching `filter` and sort out which can be undone. Undo actions
+    /// themselves are never selected.
+    pub async fn preview(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        filter: &BulkUndoFilter,
+    ) -> Result<BulkUndoPreview, BulkUndoError> {
+        let until = filter.until.unwrap_or_else(Utc::now);
+        if until <= filter.since {
+            return Err(BulkUndoError::InvalidWindow);
+        }
+
+        // Undo links come in two directions, and `undo_status` checks both:
+        // - `POST /api/actions/{id}/undo` links the original as cause and its undo as effect;
+        //   that undo is a plain inverse action (e.g. `apply_label`), so the last NOT EXISTS
+        //   keeps it out of the selection.
+        // - The `undo.action` job links its `undo_*` action as cause and the original as effect.
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                r"SELECT a.id, a.account_id, a.action_type, a.status, a.undo_hint_json, a.created_at,
+                    COALESCE(
+                        (SELECT u.status FROM action_links al
+                         JOIN actions u ON u.id = al.effect_action_id
+                         WHERE al.cause_action_id = a.id AND al.relation_type = 'undo_of'
+                         LIMIT 1),
+                        (SELECT u.status FROM action_links al
+                         JOIN actions u ON u.id = al.cause_action_id
+                         WHERE al.effect_action_id = a.id AND al.relation_type = 'undo_of'
+                           AND u.action_type LIKE 'undo\_%' ESCAPE '\'
+                         LIMIT 1)
+                    ) AS undo_status
+                 FROM actions a
+                 LEFT JOIN decisions d ON d.id = a.decision_id
+                 WHERE a.org_id = ?1 AND a.user_id = ?2
+                   AND a.created_at >= ?3 AND a.created_at < ?4
+                   AND (?5 IS NULL OR a.account_id = ?5)
+                   AND (?6 IS NULL OR d.source = ?6)
+                   AND (?7 IS NULL OR json_extract(d.telemetry_json, '$.rule.id') = ?7)
+                   AND (json_array_length(?8) = 0
+                        OR a.action_type IN (SELECT value FROM json_each(?8)))
+                   AND a.action_type NOT LIKE 'undo\_%' ESCAPE '\'
+                   AND NOT EXISTS (
+                       SELECT 1 FROM action_links al
+                       JOIN actions o ON o.id = al.cause_action_id
+                       WHERE al.effect_action_id = a.id AND al.relation_type = 'undo_of'
+                         AND o.action_type NOT LIKE 'undo\_%' ESCAPE '\'
+                   )
+                 ORDER BY a.created_at, a.id",
+                params![
+                    org_id,
+                    user_id,
+                    timestamp(filter.since),
+                    timestamp(until),
+                    filter.account_id.as_deref(),
+                    filter.source.as_ref().map(DecisionSource::as_str),
+                    filter.rule_id.as_deref(),
+                    serde_json::to_string(&filter.action_types)?
+                ],
+            )
+            .await?;
+
+        let mut preview = BulkUndoPreview {
+            until,
+            undoable: Vec::new(),
+            not_undoable: Vec::new(),
+        };
+        while let Some(row) = rows.next().await? {
+            let action_id: String = row.get(0)?;
+            let action_type: String = row.get(2)?;
+            let status: String = row.get(3)?;
+            let undo_hint_json: String = row.get(4)?;
+            let created_at: String = row.get(5)?;
+            let undo_status: Option<String> = row.get(6)?;
+
+            let undo_hint: Value = serde_json::from_str(&undo_hint_json)?;
+            match skip_reason(&action_type, &status, &undo_hint, undo_status.as_deref()) {
+                Some((reason, detail)) => preview.not_undoable.push(BulkUndoSkip {
+                    action_id,
+                    action_type,
+                    reason,
+                    detail,
+                }),
+                None => preview.undoable.push(BulkUndoCandidate {
+                    action_id,
+                    account_id: row.get(1)?,
+                    action_type,
+                    created_at: parse_timestamp(&created_at)?,
+                }),
+            }
+        }
+        Ok(preview)
+    }
+
+    /// Store a bulk undo for a previewed selection. `filter.until` must be set so the stored
+    /// filter names exactly the window that was previewed. Enqueueing the undo jobs is up to
+    /// the caller, with [`BulkUndo::job_idempotency_key`].
+    pub async fn create(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        filter: &BulkUndoFilter,
+        preview: &BulkUndoPreview,
+    ) -> Result<BulkUndo, BulkUndoError> {
+        if filter.until.is_none() {
+            return Err(BulkUndoError::UntilRequired);
+        }
+        if preview.undoable.len() > MAX_BULK_UNDO_ACTIONS {
+            return Err(BulkUndoError::TooManyActions(preview.undoable.len()));
+        }
+        let action_ids: Vec<&str> = preview
+            .undoable
+            .iter()
+            .map(|candidate| candidate.action_id.as_str())
+            .collect();
+
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "INSERT INTO bulk_undos (
+                        id, filter_json, action_ids_json, skipped_json, created_at, org_id, user_id
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                    RETURNING {BULK_UNDO_COLUMNS}"
+                ),
+                params![
+                    Uuid::new_v4().to_string(),
+                    serde_json::to_string(filter)?,
+                    serde_json::to_string(&action_ids)?,
+                    serde_json::to_string(&preview.not_undoable)?,
+                    timestamp(Utc::now()),
+                    org_id,
+                    user_id
+                ],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => row_to_bulk_undo(row),
+            None => Err(BulkUndoError::NotFound("insert failed".into())),
+        }
+    }
+
+    pub async fn get_by_id(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<BulkUndo, BulkUndoError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {BULK_UNDO_COLUMNS} FROM bulk_undos
+                     WHERE id = ?1 AND org_id = ?2 AND user_id = ?3"
+                ),
+                params![id, org_id, user_id],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => row_to_bulk_undo(row),
+            None => Err(BulkUndoError::NotFound(id.to_string())),
+        }
+    }
+
+    /// List bulk undos, newest first.
+    pub async fn list(&self, org_id: i64, user_id: i64) -> Result<Vec<BulkUndo>, BulkUndoError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {BULK_UNDO_COLUMNS} FROM bulk_undos
+                     WHERE org_id = ?1 AND user_id = ?2
+                     ORDER BY created_at DESC"
+                ),
+                params![org_id, user_id],
+            )
+            .await?;
+        let mut bulk_undos = Vec::new();
+        while let Some(row) = rows.next().await? {
+            bulk_undos.push(row_to_bulk_undo(row)?);
+        }
+        Ok(bulk_undos)
+    }
+
+    /// Read each action's undo state from its undo action and, until that exists, its job.
+    pub async fn progress(&self, bulk_undo: BulkUndo) -> Result<BulkUndoProgress, BulkUndoError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                r"SELECT ids.value, u.id, u.status, u.error_message, j.state, j.last_error
+                 FROM json_each(?1) ids
+                 LEFT JOIN actions u ON u.id = (
+                     SELECT al.cause_action_id FROM action_links al
+                     JOIN actions c ON c.id = al.cause_action_id
+                     WHERE al.effect_action_id = ids.value AND al.relation_type = 'undo_of'
+                       AND c.action_type LIKE 'undo\_%' ESCAPE '\'
+                     LIMIT 1
+                 )
+                 LEFT JOIN jobs j ON j.idempotency_key = ?2 || ids.value
+                 ORDER BY ids.key",
+                params![
+                    serde_json::to_string(&bulk_undo.action_ids)?,
+                    job_idempotency_key(&bulk_undo.id, "")
+                ],
+            )
+            .await?;
+
+        let mut items = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let undo_status: Option<String> = row.get(2)?;
+            let undo_error: Option<String> = row.get(3)?;
+            let job_state: Option<String> = row.get(4)?;
+            let job_error: Option<String> = row.get(5)?;
+            let (status, error) = item_status(
+                undo_status.as_deref(),
+                undo_error,
+                job_state.as_deref(),
+                job_error,
+            );
+            items.push(BulkUndoItem {
+                action_id: row.get(0)?,
+                status,
+                undo_action_id: row.get(1)?,
+                error,
+            });
+        }
+
+        let count = |status: BulkUndoItemStatus| {
+            items.iter().filter(|item| item.status == status).count() as i64
+        };
+        Ok(BulkUndoProgress {
+            total: items.len() as i64,
+            pending: count(BulkUndoItemStatus::Pending),
+            completed: count(BulkUndoItemStatus::Completed),
+            failed: count(BulkUndoItemStatus::Failed),
+            items,
+            bulk_undo,
+        })
+    }
+}
+
+fn job_idempotency_key(bulk_undo_id: &str, action_id: &str) -> String {
+    format!("{JOB_TYPE_UNDO_ACTION}:bulk:{bulk_undo_id}:{action_id}")
+}
+
+/// Mirrors the checks `undo.action` makes before reverting an action.
+fn skip_reason(
+    action_type: &str,
+    status: &str,
+    undo_hint: &Value,
+    undo_status: Option<&str>,
+) -> Option<(BulkUndoSkipReason, String)> {
+    if status != ActionStatus::Completed.as_str() {
+        return Some((
+            BulkUndoSkipReason::NotCompleted,
+            format!("not completed (status {status})"),
+        ));
+    }
+    let inverse_action = undo_hint
+        .get("inverse_action")
+        .and_then(Value::as_str)
+        .filter(|inverse| !inverse.trim().is_empty());
+    let Some(inverse_action) = inverse_action else {
+        return Some((
+            BulkUndoSkipReason::NotSupported,
+            "does not support undo".to_string(),
+        ));
+    };
+    if undo_hint
+        .get("irreversible")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    {
+        return Some((BulkUndoSkipReason::Irreversible, "irreversible".to_string()));
+    }
+    if inverse_action == "none" && action_type != "snooze" {
+        return Some((
+            BulkUndoSkipReason::NotSupported,
+            "does not support undo".to_string(),
+        ));
+    }
+    match undo_status {
+        None => None,
+        Some("completed") => Some((
+            BulkUndoSkipReason::AlreadyUndone,
+            "already undone".to_string(),
+        )),
+        Some(status @ ("failed" | "canceled" | "rejected")) => Some((
+            BulkUndoSkipReason::UndoAttempted,
+            format!("undo already attempted (status {status})"),
+        )),
+        Some(status) => Some((
+            BulkUndoSkipReason::UndoInProgress,
+            format!("undo already in progress (status {status})"),
+        )),
+    }
+}
+
+fn item_status(
+    undo_status: Option<&str>,
+    undo_error: Option<String>,
+    job_state: Option<&str>,
+    job_error: Option<String>,
+) -> (BulkUndoItemStatus, Option<String>) {
+    match (undo_status, job_state) {
+        (Some("completed"), _) => (BulkUndoItemStatus::Completed, None),
+        (Some("failed" | "canceled" | "rejected"), _) => {
+            (BulkUndoItemStatus::Failed, undo_error.or(job_error))
+        }
+        (_, Some("failed" | "canceled")) => (BulkUndoItemStatus::Failed, job_error),
+        (_, Some("completed")) => (BulkUndoItemStatus::Completed, None),
+        (None, None) => (
+            BulkUndoItemStatus::Failed,
+            Some("undo job was not enqueued".to_string()),
+        ),
+        _ => (BulkUndoItemStatus::Pending, None),
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, BulkUndoError> {
+    Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc))
+}
+
+fn row_to_bulk_undo(row: Row) -> Result<BulkUndo, BulkUndoError> {
+    let filter_json: String = row.get(1)?;
+    let action_ids_json: String = row.get(2)?;
+    let skipped_json: String = row.get(3)?;
+    let created_at: String = row.get(4)?;
+
+    Ok(BulkUndo {
+        id: row.get(0)?,
+        filter: serde_json::from_str(&filter_json)?,
+        action_ids: serde_json::from_str(&action_ids_json)?,
+        skipped: serde_json::from_str(&skipped_json)?,
+        created_at: parse_timestamp(&created_at)?,
+        org_id: row.get(5)?,
+        user_id: row.get(6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::decisions::{
+        ActionLinkRelationType, ActionLinkRepository, ActionRepository, DecisionRepository,
+        NewAction, NewActionLink, NewDecision,
+    };
+    use crate::gmail::OAuthTokens;
+    use crate::messages::{MessageRepository, NewMessage};
+    use crate::migrations::run_migrations;
+    use crate::queue::JobQueue;
+    use crate::threads::ThreadRepository;
+    use chrono::Duration;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    async fn setup() -> (Database, TempDir, String, String) {
+        let dir = TempDir::new().expect("temp dir");
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+        let thread = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account.id,
+                "thr-1",
+                Some("Sale".into()),
+                None,
+                None,
+                json!({}),
+            )
+            .await
+            .expect("thread");
+        let message = MessageRepository::new(db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account.id.clone(),
+                thread_id: thread.id,
+                provider_message_id: "msg-1".into(),
+                from_email: Some("deals@shop.example".into()),
+                from_name: None,
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some("Sale".into()),
+                snippet: None,
+                received_at: None,
+                internal_date: None,
+                labels: vec![],
+                headers: vec![],
+                body_plain: None,
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("message");
+        (db, dir, account.id, message.id)
+    }
+
+    /// Record an action chosen by `rule_id`, completed with `undo_hint` when given.
+    async fn add_action(
+        db: &Database,
+        account_id: &str,
+        message_id: &str,
+        action_type: &str,
+        rule_id: &str,
+        undo_hint: Option<Value>,
+    ) -> String {
+        let decision = DecisionRepository::new(db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.to_string(),
+                message_id: message_id.to_string(),
+                source: DecisionSource::Deterministic,
+                decision_json: json!({}),
+                action_type: Some(action_type.to_string()),
+                confidence: Some(1.0),
+                needs_approval: false,
+                rationale: None,
+                telemetry_json: json!({"rule": {"id": rule_id, "name": rule_id}}),
+            })
+            .await
+            .expect("decision");
+        let repo = ActionRepository::new(db.clone());
+        let action = repo
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.to_string(),
+                message_id: message_id.to_string(),
+                decision_id: Some(decision.id),
+                action_type: action_type.to_string(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("action");
+        if let Some(undo_hint) = undo_hint {
+            repo.mark_executing(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+                .await
+                .expect("executing");
+            repo.mark_completed_with_undo_hint(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &action.id,
+                undo_hint,
+            )
+            .await
+            .expect("completed");
+        }
+        action.id
+    }
+
+    /// Record the `undo_*` action an `undo.action` job creates for `original_id`.
+    async fn add_undo(
+        db: &Database,
+        account_id: &str,
+        message_id: &str,
+        original_id: &str,
+        complete: bool,
+    ) -> String {
+        let repo = ActionRepository::new(db.clone());
+        let undo = repo
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.to_string(),
+                message_id: message_id.to_string(),
+                decision_id: None,
+                action_type: "undo_archive".into(),
+                parameters_json: json!({"original_action_id": original_id}),
+                status: ActionStatus::Executing,
+                error_message: None,
+                executed_at: Some(Utc::now()),
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("undo action");
+        ActionLinkRepository::new(db.clone())
+            .create(NewActionLink {
+                cause_action_id: undo.id.clone(),
+                effect_action_id: original_id.to_string(),
+                relation_type: ActionLinkRelationType::UndoOf,
+            })
+            .await
+            .expect("undo link");
+        if complete {
+            repo.mark_completed(DEFAULT_ORG_ID, DEFAULT_USER_ID, &undo.id)
+                .await
+                .expect("complete undo");
+        }
+        undo.id
+    }
+
+    fn archive_hint() -> Value {
+        json!({"inverse_action": "apply_label", "inverse_parameters": {"label": "INBOX"}})
+    }
+
+    fn filter(rule_id: Option<&str>) -> BulkUndoFilter {
+        BulkUndoFilter {
+            rule_id: rule_id.map(str::to_string),
+            source: None,
+            account_id: None,
+            action_types: vec![],
+            since: Utc::now() - Duration::hours(1),
+            until: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn preview_selects_by_filter_and_explains_skips() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let archived = add_action(
+            &db,
+            &account_id,
+            &message_id,
+            "archive",
+            "bad",
+            Some(archive_hint()),
+        )
+        .await;
+        let failed = add_action(&db, &account_id, &message_id, "archive", "bad", None).await;
+        let deleted = add_action(
+            &db,
+            &account_id,
+            &message_id,
+            "delete",
+            "bad",
+            Some(json!({"inverse_action": "none", "irreversible": true})),
+        )
+        .await;
+        let undone = add_action(
+            &db,
+            &account_id,
+            &message_id,
+            "archive",
+            "bad",
+            Some(archive_hint()),
+        )
+        .await;
+        add_undo(&db, &account_id, &message_id, &undone, true).await;
+        add_action(
+            &db,
+            &account_id,
+            &message_id,
+            "archive",
+            "good",
+            Some(archive_hint()),
+        )
+        .await;
+
+        let repo = BulkUndoRepository::new(db.clone());
+        let preview = repo
+            .preview(DEFAULT_ORG_ID, DEFAULT_USER_ID, &filter(Some("bad")))
+            .await
+            .expect("preview");
+        let undoable: Vec<&str> = preview
+            .undoable
+            .iter()
+            .map(|candidate| candidate.action_id.as_str())
+            .collect();
+        assert_eq!(undoable, vec![archived.as_str()]);
+        let skipped: Vec<(&str, BulkUndoSkipReason)> = preview
+            .not_undoable
+            .iter()
+            .map(|skip| (skip.action_id.as_str(), skip.reason))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                (failed.as_str(), BulkUndoSkipReason::NotCompleted),
+                (deleted.as_str(), BulkUndoSkipReason::Irreversible),
+                (undone.as_str(), BulkUndoSkipReason::AlreadyUndone),
+            ]
+        );
+        assert_eq!(
+            preview.not_undoable[0].detail,
+            "not completed (status queued)"
+        );
+
+        // Without a rule every rule's actions match, but never the undo actions
+        let all = repo
+            .preview(DEFAULT_ORG_ID, DEFAULT_USER_ID, &filter(None))
+            .await
+            .expect("preview all");
+        assert_eq!(all.undoable.len(), 2);
+        assert_eq!(all.not_undoable.len(), 3);
+
+        let deletes = repo
+            .preview(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &BulkUndoFilter {
+                    action_types: vec!["delete".into()],
+                    source: Some(DecisionSource::Deterministic),
+                    ..filter(None)
+                },
+            )
+            .await
+            .expect("preview deletes");
+        assert!(deletes.undoable.is_empty());
+        assert_eq!(deletes.not_undoable.len(), 1);
+
+        let earlier = repo
+            .preview(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &BulkUndoFilter {
+                    until: Some(Utc::now() - Duration::minutes(30)),
+                    ..filter(None)
+                },
+            )
+            .await
+            .expect("preview earlier window");
+        assert!(earlier.undoable.is_empty() && earlier.not_undoable.is_empty());
+
+        let err = repo
+            .preview(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &BulkUndoFilter {
+                    until: Some(Utc::now() - Duration::hours(2)),
+                    ..filter(None)
+                },
+            )
+            .await
+            .expect_err("inverted window");
+        assert!(matches!(err, BulkUndoError::InvalidWindow));
+    }
+
+    #[tokio::test]
+    async fn progress_follows_undo_actions_and_jobs() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let mut ids = Vec::new();
+        for _ in 0..4 {
+            ids.push(
+                add_action(
+                    &db,
+                    &account_id,
+                    &message_id,
+                    "archive",
+                    "bad",
+                    Some(archive_hint()),
+                )
+                .await,
+            );
+        }
+        let repo = BulkUndoRepository::new(db.clone());
+        let bulk_filter = filter(Some("bad"));
+        let preview = repo
+            .preview(DEFAULT_ORG_ID, DEFAULT_USER_ID, &bulk_filter)
+            .await
+            .expect("preview");
+        let err = repo
+            .create(DEFAULT_ORG_ID, DEFAULT_USER_ID, &bulk_filter, &preview)
+            .await
+            .expect_err("until is required");
+        assert!(matches!(err, BulkUndoError::UntilRequired));
+        let bulk_filter = BulkUndoFilter {
+            until: Some(preview.until),
+            ..bulk_filter
+        };
+        let bulk_undo = repo
+            .create(DEFAULT_ORG_ID, DEFAULT_USER_ID, &bulk_filter, &preview)
+            .await
+            .expect("create");
+        assert_eq!(bulk_undo.action_ids, ids);
+
+        // The last action's job was never enqueued
+        let queue = JobQueue::new(db.clone());
+        let mut job_ids = Vec::new();
+        for id in &ids[..3] {
+            job_ids.push(
+                queue
+                    .enqueue(
+                        JOB_TYPE_UNDO_ACTION,
+                        json!({"account_id": account_id, "original_action_id": id}),
+                        Some(bulk_undo.job_idempotency_key(id)),
+                        0,
+                    )
+                    .await
+                    .expect("enqueue"),
+            );
+        }
+        let undo_id = add_undo(&db, &account_id, &message_id, &ids[0], true).await;
+        queue.cancel(&job_ids[1]).await.expect("cancel");
+
+        let progress = repo
+            .progress(
+                repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &bulk_undo.id)
+                    .await
+                    .expect("get"),
+            )
+            .await
+            .expect("progress");
+        assert_eq!(
+            (
+                progress.total,
+                progress.pending,
+                progress.completed,
+                progress.failed
+            ),
+            (4, 1, 1, 2)
+        );
+        let statuses: Vec<BulkUndoItemStatus> =
+            progress.items.iter().map(|item| item.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                BulkUndoItemStatus::Completed,
+                BulkUndoItemStatus::Failed,
+                BulkUndoItemStatus::Pending,
+                BulkUndoItemStatus::Failed,
+            ]
+        );
+        assert_eq!(
+            progress.items[0].undo_action_id.as_deref(),
+            Some(undo_id.as_str())
+        );
+        assert_eq!(
+            progress.items[3].error.as_deref(),
+            Some("undo job was not enqueued")
+        );
+
+        let listed = repo
+            .list(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+            .await
+            .expect("list");
+        assert_eq!(listed, vec![bulk_undo]);
+    }
+}
@@ -0,0 +1,1004 @@
+//! Automation circuit breaker.
+//!
+//! Every tracked action about to run automatically is checked against sliding windows of
+//! recent automatic actions, per account and per deterministic rule. Exceeding a window trips
+//! the breaker: a trip is recorded and, until someone resets it, new tracked actions of that
+//! account (or rule) wait for approval instead of running.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use libsql::{Row, params};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::config::CircuitBreakerConfig;
+use crate::db::{Database, DbError};
+use crate::decisions::ActionStatus;
+
+const TRIP_COLUMNS: &str = "id, account_id, scope, rule_id, rule_name, action_types_json, window_seconds, threshold, action_count, tripped_at, reset_at, org_id, user_id";
+
+/// Action statuses that count as run automatically.
+const RUN_STATUSES: &str = "'queued', 'pending_execution', 'executing', 'completed', 'failed'";
+
+#[derive(Debug, Error)]
+pub enum CircuitBreakerError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("datetime parse error: {0}")]
+    DateTimeParse(#[from] chrono::ParseError),
+    #[error("circuit breaker trip not found: {0}")]
+    NotFound(String),
+    #[error("circuit breaker trip already reset: {0}")]
+    AlreadyReset(String),
+    #[error("invalid scope value {0}")]
+    InvalidScope(String),
+    #[error("invalid status value {0}")]
+    InvalidStatus(String),
+}
+
+/// What a trip pauses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum CircuitBreakerScope {
+    /// Every tracked action of the account.
+    Account,
+    /// Tracked actions chosen by one deterministic rule.
+    Rule,
+}
+
+impl CircuitBreakerScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitBreakerScope::Account => "account",
+            CircuitBreakerScope::Rule => "rule",
+        }
+    }
+}
+
+impl FromStr for CircuitBreakerScope {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "account" => Ok(Self::Account),
+            "rule" => Ok(Self::Rule),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A recorded trip. It stays open, holding actions for approval, until `reset_at` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CircuitBreakerTrip {
+    pub id: String,
+    #[ts(type = "number")]
+    pub org_id: i64,
+    #[ts(type = "number")]
+    pub user_id: i64,
+    pub account_id: String,
+    pub scope: CircuitBreakerScope,
+    /// The rule that was paused, for rule trips.
+    pub rule_id: Option<String>,
+    pub rule_name: Option<String>,
+    /// Action types tracked when the breaker tripped.
+    pub action_types: Vec<String>,
+    /// The window that was exceeded.
+    #[ts(type = "number")]
+    pub window_seconds: i64,
+    #[ts(type = "number")]
+    pub threshold: i64,
+    /// Actions that had run within the window, including the one that tripped it.
+    #[ts(type = "number")]
+    pub action_count: i64,
+    pub tripped_at: DateTime<Utc>,
+    pub reset_at: Option<DateTime<Utc>>,
+}
+
+impl CircuitBreakerTrip {
+    pub fn is_open(&self) -> bool {
+        self.reset_at.is_none()
+    }
+
+    /// Short description of what was paused, e.g. `rule 'Bulk cleanup'`.
+    pub fn target(&self) -> String {
+        match (self.scope, &self.rule_name) {
+            (CircuitBreakerScope::Rule, Some(name)) => format!("rule '{name}'"),
+            (CircuitBreakerScope::Rule, None) => {
+                format!("rule {}", self.rule_id.as_deref().unwrap_or("unknown"))
+            }
+            (CircuitBreakerScope::Account, _) => "account".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CircuitBreakerTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ran {} tracked actions within {} (limit {})",
+            self.target(),
+            self.action_count,
+            format_window(self.window_seconds),
+            self.threshold
+        )
+    }
+}
+
+/// An action listed in a burst report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BurstAction {
+    pub action_id: String,
+    pub message_id: String,
+    pub subject: Option<String>,
+    pub from_email: Option<String>,
+    pub action_type: String,
+    pub status: ActionStatus,
+    pub rule_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What ran during the burst that tripped a breaker, and what it has held since.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BurstReport {
+    pub trip: CircuitBreakerTrip,
+    /// Tracked actions run automatically in the exceeded window, oldest first.
+    pub executed: Vec<BurstAction>,
+    /// Actions sent to approval because the breaker was open, oldest first.
+    pub held: Vec<BurstAction>,
+}
+
+/// An action about to run automatically.
+#[derive(Debug, Clone, Copy)]
+pub struct TrackedAction<'a> {
+    pub account_id: &'a str,
+    pub action_type: &'a str,
+    /// The deterministic rule that chose the action, as `(id, name)`.
+    pub rule: Option<(&'a str, &'a str)>,
+}
+
+/// Outcome of a check that found an open breaker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreakerHold {
+    pub trip: CircuitBreakerTrip,
+    /// True when this check tripped the breaker, so it should be alerted on.
+    pub newly_tripped: bool,
+}
+
+#[derive(Clone)]
+pub struct CircuitBreakerRepository {
+    db: Database,
+}
+
+impl CircuitBreakerRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Check `action` against open trips and the configured windows, tripping a breaker when
+    /// the action would exceed a window. Returns the trip holding the action, if any.
+    pub async fn check(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        config: &CircuitBreakerConfig,
+        action: &TrackedAction<'_>,
+    ) -> Result<Option<CircuitBreakerHold>, CircuitBreakerError> {
+        if !config.enabled
+            || !config
+                .action_types
+                .iter()
+                .any(|tracked| tracked.eq_ignore_ascii_case(action.action_type))
+        {
+            return Ok(None);
+        }
+
+        let rule_id = action.rule.map(|(id, _)| id);
+        if let Some(trip) = self
+            .open_for(org_id, user_id, action.account_id, rule_id)
+            .await?
+            .into_iter()
+            .next()
+        {
+            return Ok(Some(CircuitBreakerHold {
+                trip,
+                newly_tripped: false,
+            }));
+        }
+
+        let now = Utc::now();
+        for window in &config.windows {
+            let since = now - Duration::seconds(window.seconds);
+            let scopes = [
+                (CircuitBreakerScope::Account, None, window.max_per_account),
+                (CircuitBreakerScope::Rule, rule_id, window.max_per_rule),
+            ];
+            for (scope, scope_rule_id, threshold) in scopes {
+                if scope == CircuitBreakerScope::Rule && scope_rule_id.is_none() {
+                    continue;
+                }
+                let count = self
+                    .count_run(
+                        org_id,
+                        user_id,
+                        action.account_id,
+                        scope_rule_id,
+                        &config.action_types,
+                        since,
+                    )
+                    .await?
+                    + 1;
+                if count > threshold {
+                    let (trip, newly_tripped) = self
+                        .trip(NewTrip {
+                            org_id,
+                            user_id,
+                            account_id: action.account_id,
+                            scope,
+                            rule: action.rule.filter(|_| scope == CircuitBreakerScope::Rule),
+                            action_types: &config.action_types,
+                            window_seconds: window.seconds,
+                            threshold,
+                            action_count: count,
+                        })
+                        .await?;
+                    return Ok(Some(CircuitBreakerHold {
+                        trip,
+                        newly_tripped,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Open trips covering an account's actions: the account's own trip and, when `rule_id`
+    /// is given, that rule's trip.
+    pub async fn open_for(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        rule_id: Option<&str>,
+    ) -> Result<Vec<CircuitBreakerTrip>, CircuitBreakerError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {TRIP_COLUMNS}
+                     FROM circuit_breaker_trips
+                     WHERE org_id = ?1 AND user_id = ?2 AND account_id = ?3 AND reset_at IS NULL
+                       AND (scope = 'account' OR rule_id = ?4)
+                     ORDER BY scope, tripped_at"
+                ),
+                params![org_id, user_id, account_id, rule_id],
+            )
+            .await?;
+        let mut trips = Vec::new();
+        while let Some(row) = rows.next().await? {
+            trips.push(row_to_trip(row)?);
+        }
+        Ok(trips)
+    }
+
+    /// Count tracked actions run automatically since `since`, optionally only those chosen
+    /// by one rule. Actions from before the latest reset of the account's breaker (or, for a
+    /// rule, of the account's or that rule's breaker) are not counted, so a reset breaker does
+    /// not trip again on the burst that was just reviewed.
+    async fn count_run(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        rule_id: Option<&str>,
+        action_types: &[String],
+        since: DateTime<Utc>,
+    ) -> Result<i64, CircuitBreakerError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT COUNT(*)
+                     FROM actions a
+                     JOIN decisions d ON d.id = a.decision_id
+                     WHERE a.org_id = ?1 AND a.user_id = ?2 AND a.account_id = ?3
+                       AND a.created_at >= ?4 AND d.needs_approval = 0
+                       AND a.status IN ({RUN_STATUSES})
+                       AND a.action_type IN (SELECT value FROM json_each(?5))
+                       AND (?6 IS NULL OR json_extract(d.telemetry_json, '$.rule.id') = ?6)
+                       AND a.created_at > COALESCE((
+                           SELECT MAX(t.reset_at) FROM circuit_breaker_trips t
+                           WHERE t.org_id = ?1 AND t.user_id = ?2 AND t.account_id = ?3
+                             AND (t.scope = 'account' OR t.rule_id = ?6)
+                       ), '')"
+                ),
+                params![
+                    org_id,
+                    user_id,
+                    account_id,
+                    timestamp(since),
+                    serde_json::to_string(action_types)?,
+                    rule_id
+                ],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => Ok(row.get(0)?),
+            None => Ok(0),
+        }
+    }
+
+    /// Record a trip. When the same breaker is already open, returns that trip instead and
+    /// `false`.
+    async fn trip(
+        &self,
+        new_trip: NewTrip<'_>,
+    ) -> Result<(CircuitBreakerTrip, bool), CircuitBreakerError> {
+        let conn = self.db.connection().await?;
+        let inserted = conn
+            .query(
+                &format!(
+                    "INSERT INTO circuit_breaker_trips (
+                        id, account_id, scope, rule_id, rule_name, action_types_json,
+                        window_seconds, threshold, action_count, tripped_at, org_id, user_id
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                    RETURNING {TRIP_COLUMNS}"
+                ),
+                params![
+                    Uuid::new_v4().to_string(),
+                    new_trip.account_id,
+                    new_trip.scope.as_str(),
+                    new_trip.rule.map(|(id, _)| id),
+                    new_trip.rule.map(|(_, name)| name),
+                    serde_json::to_string(new_trip.action_types)?,
+                    new_trip.window_seconds,
+                    new_trip.threshold,
+                    new_trip.action_count,
+                    timestamp(Utc::now()),
+                    new_trip.org_id,
+                    new_trip.user_id
+                ],
+            )
+            .await;
+
+        match inserted {
+            Ok(mut rows) => match rows.next().await? {
+                Some(row) => Ok((row_to_trip(row)?, true)),
+                None => Err(CircuitBreakerError::NotFound("insert failed".into())),
+            },
+            Err(err)
+                if err
+                    .to_string()
+                    .to_ascii_lowercase()
+                    .contains("unique constraint failed") =>
+            {
+                // Another job tripped the same breaker first
+                let rule_id = new_trip.rule.map(|(id, _)| id);
+                self.open_for(
+                    new_trip.org_id,
+                    new_trip.user_id,
+                    new_trip.account_id,
+                    rule_id,
+                )
+                .await?
+                .into_iter()
+                .find(|trip| trip.scope == new_trip.scope)
+                .map(|trip| (trip, false))
+                .ok_or(CircuitBreakerError::Sql(err))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn get_by_id(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<CircuitBreakerTrip, CircuitBreakerError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {TRIP_COLUMNS} FROM circuit_breaker_trips
+                     WHERE id = ?1 AND org_id = ?2 AND user_id = ?3"
+                ),
+                params![id, org_id, user_id],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => row_to_trip(row),
+            None => Err(CircuitBreakerError::NotFound(id.to_string())),
+        }
+    }
+
+    /// List trips, newest first. Reset trips are included only when `include_reset`.
+    pub async fn list(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        include_reset: bool,
+    ) -> Result<Vec<CircuitBreakerTrip>, CircuitBreakerError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {TRIP_COLUMNS} FROM circuit_breaker_trips
+                     WHERE org_id = ?1 AND user_id = ?2 AND (?3 OR reset_at IS NULL)
+                     ORDER BY tripped_at DESC"
+                ),
+                params![org_id, user_id, include_reset as i64],
+            )
+            .await?;
+        let mut trips = Vec::new();
+        while let Some(row) = rows.next().await? {
+            trips.push(row_to_trip(row)?);
+        }
+        Ok(trips)
+    }
+
+    /// Close an open trip so its account or rule runs automatically again.
+    pub async fn reset(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<CircuitBreakerTrip, CircuitBreakerError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "UPDATE circuit_breaker_trips SET reset_at = ?4
+                     WHERE id = ?1 AND org_id = ?2 AND user_id = ?3 AND reset_at IS NULL
+                     RETURNING {TRIP_COLUMNS}"
+                ),
+                params![id, org_id, user_id, timestamp(Utc::now())],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => row_to_trip(row),
+            None => {
+                // Distinguish a missing trip from one that was already reset
+                self.get_by_id(org_id, user_id, id).await?;
+                Err(CircuitBreakerError::AlreadyReset(id.to_string()))
+            }
+        }
+    }
+
+    /// Report the tracked actions run in the window that tripped `trip` and the actions it
+    /// has held for approval.
+    pub async fn report(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        trip: &CircuitBreakerTrip,
+    ) -> Result<BurstReport, CircuitBreakerError> {
+        let since = trip.tripped_at - Duration::seconds(trip.window_seconds);
+        let conn = self.db.connection().await?;
+
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT a.id, a.message_id, m.subject, m.from_email, a.action_type, a.status,
+                            json_extract(d.telemetry_json, '$.rule.name'), a.created_at
+                     FROM actions a
+                     JOIN decisions d ON d.id = a.decision_id
+                     LEFT JOIN messages m ON m.id = a.message_id
+                     WHERE a.org_id = ?1 AND a.user_id = ?2 AND a.account_id = ?3
+                       AND a.created_at >= ?4 AND a.created_at <= ?5 AND d.needs_approval = 0
+                       AND a.status IN ({RUN_STATUSES})
+                       AND a.action_type IN (SELECT value FROM json_each(?6))
+                       AND (?7 IS NULL OR json_extract(d.telemetry_json, '$.rule.id') = ?7)
+                     ORDER BY a.created_at"
+                ),
+                params![
+                    org_id,
+                    user_id,
+                    trip.account_id.clone(),
+                    timestamp(since),
+                    timestamp(trip.tripped_at),
+                    serde_json::to_string(&trip.action_types)?,
+                    trip.rule_id.clone()
+                ],
+            )
+            .await?;
+        let mut executed = Vec::new();
+        while let Some(row) = rows.next().await? {
+            executed.push(row_to_burst_action(row)?);
+        }
+
+        let mut rows = conn
+            .query(
+                "SELECT a.id, a.message_id, m.subject, m.from_email, a.action_type, a.status,
+                        json_extract(d.telemetry_json, '$.rule.name'), a.created_at
+                 FROM actions a
+                 JOIN decisions d ON d.id = a.decision_id
+                 LEFT JOIN messages m ON m.id = a.message_id
+                 WHERE a.org_id = ?1 AND a.user_id = ?2
+                   AND json_extract(d.telemetry_json, '$.circuit_breaker.trip_id') = ?3
+                 ORDER BY a.created_at",
+                params![org_id, user_id, trip.id.clone()],
+            )
+            .await?;
+        let mut held = Vec::new();
+        while let Some(row) = rows.next().await? {
+            held.push(row_to_burst_action(row)?);
+        }
+
+        Ok(BurstReport {
+            trip: trip.clone(),
+            executed,
+            held,
+        })
+    }
+}
+
+struct NewTrip<'a> {
+    org_id: i64,
+    user_id: i64,
+    account_id: &'a str,
+    scope: CircuitBreakerScope,
+    rule: Option<(&'a str, &'a str)>,
+    action_types: &'a [String],
+    window_seconds: i64,
+    threshold: i64,
+    action_count: i64,
+}
+
+/// Format a window length as whole hours, minutes or seconds.
+fn format_window(seconds: i64) -> String {
+    let (value, unit) = if seconds % 3600 == 0 {
+        (seconds / 3600, "hour")
+    } else if seconds % 60 == 0 {
+        (seconds / 60, "minute")
+    } else {
+        (seconds, "second")
+    };
+    if value == 1 {
+        format!("1 {unit}")
+    } else {
+        format!("{value} {unit}s")
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, CircuitBreakerError> {
+    Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc))
+}
+
+fn row_to_trip(row: Row) -> Result<CircuitBreakerTrip, CircuitBreakerError> {
+    let scope: String = row.get(2)?;
+    let action_types_json: String = row.get(5)?;
+    let tripped_at: String = row.get(9)?;
+    let reset_at: Option<String> = row.get(10)?;
+
+    Ok(CircuitBreakerTrip {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        scope: scope
+            .parse()
+            .map_err(|_| CircuitBreakerError::InvalidScope(scope.clone()))?,
+        rule_id: row.get(3)?,
+        rule_name: row.get(4)?,
+        action_types: serde_json::from_str(&action_types_json)?,
+        window_seconds: row.get(6)?,
+        threshold: row.get(7)?,
+        action_count: row.get(8)?,
+        tripped_at: parse_timestamp(&tripped_at)?,
+        reset_at: reset_at.as_deref().map(parse_timestamp).transpose()?,
+        org_id: row.get(11)?,
+        user_id: row.get(12)?,
+    })
+}
+
+fn row_to_burst_action(row: Row) -> Result<BurstAction, CircuitBreakerError> {
+    let status: String = row.get(5)?;
+    let created_at: String = row.get(7)?;
+    Ok(BurstAction {
+        action_id: row.get(0)?,
+        message_id: row.get(1)?,
+        subject: row.get(2)?,
+        from_email: row.get(3)?,
+        action_type: row.get(4)?,
+        status: ActionStatus::from_str(&status)
+            .ok_or_else(|| CircuitBreakerError::InvalidStatus(status.clone()))?,
+        rule_name: row.get(6)?,
+        created_at: parse_timestamp(&created_at)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::config::CircuitBreakerWindow;
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::decisions::{
+        ActionRepository, DecisionRepository, DecisionSource, NewAction, NewDecision,
+    };
+    use crate::gmail::OAuthTokens;
+    use crate::messages::{MessageRepository, NewMessage};
+    use crate::migrations::run_migrations;
+    use crate::threads::ThreadRepository;
+    use serde_json::{Value, json};
+    use tempfile::TempDir;
+
+    async fn setup() -> (Database, TempDir, String, String) {
+        let dir = TempDir::new().expect("temp dir");
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+        let thread = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account.id,
+                "thr-1",
+                Some("Sale".into()),
+                None,
+                None,
+                json!({}),
+            )
+            .await
+            .expect("thread");
+        let message = MessageRepository::new(db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account.id.clone(),
+                thread_id: thread.id,
+                provider_message_id: "msg-1".into(),
+                from_email: Some("deals@shop.example".into()),
+                from_name: None,
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some("Sale".into()),
+                snippet: None,
+                received_at: None,
+                internal_date: None,
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: None,
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("message");
+        (db, dir, account.id, message.id)
+    }
+
+    /// Record an action the way classification does, with its decision.
+    async fn add_action(
+        db: &Database,
+        account_id: &str,
+        message_id: &str,
+        action_type: &str,
+        needs_approval: bool,
+        telemetry: Value,
+    ) {
+        let decision = DecisionRepository::new(db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.to_string(),
+                message_id: message_id.to_string(),
+                source: DecisionSource::Deterministic,
+                decision_json: json!({}),
+                action_type: Some(action_type.to_string()),
+                confidence: Some(1.0),
+                needs_approval,
+                rationale: None,
+                telemetry_json: telemetry,
+            })
+            .await
+            .expect("decision");
+        ActionRepository::new(db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.to_string(),
+                message_id: message_id.to_string(),
+                decision_id: Some(decision.id),
+                action_type: action_type.to_string(),
+                parameters_json: json!({}),
+                status: if needs_approval {
+                    ActionStatus::ApprovedPending
+                } else {
+                    ActionStatus::Queued
+                },
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("action");
+    }
+
+    fn config(max_per_account: i64, max_per_rule: i64) -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            windows: vec![CircuitBreakerWindow {
+                seconds: 300,
+                max_per_account,
+                max_per_rule,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn tracked<'a>(
+        account_id: &'a str,
+        action_type: &'a str,
+        rule: Option<(&'a str, &'a str)>,
+    ) -> TrackedAction<'a> {
+        TrackedAction {
+            account_id,
+            action_type,
+            rule,
+        }
+    }
+
+    #[tokio::test]
+    async fn account_breaker_trips_past_the_limit_and_stays_open() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let repo = CircuitBreakerRepository::new(db.clone());
+        let config = config(2, 100);
+
+        for _ in 0..2 {
+            add_action(&db, &account_id, &message_id, "trash", false, json!({})).await;
+        }
+        // Untracked types and actions that waited for approval do not count
+        add_action(
+            &db,
+            &account_id,
+            &message_id,
+            "apply_label",
+            false,
+            json!({}),
+        )
+        .await;
+        add_action(&db, &account_id, &message_id, "trash", true, json!({})).await;
+
+        let label = repo
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "apply_label", None),
+            )
+            .await
+            .expect("check");
+        assert_eq!(label, None);
+
+        let hold = repo
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "trash", None),
+            )
+            .await
+            .expect("check")
+            .expect("tripped");
+        assert!(hold.newly_tripped);
+        assert_eq!(hold.trip.scope, CircuitBreakerScope::Account);
+        assert_eq!(hold.trip.action_count, 3);
+        assert_eq!(hold.trip.threshold, 2);
+        assert_eq!(
+            hold.trip.to_string(),
+            "account ran 3 tracked actions within 5 minutes (limit 2)"
+        );
+
+        // Later tracked actions are held by the same trip, even for a rule
+        let again = repo
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "archive", Some(("r1", "Cleanup"))),
+            )
+            .await
+            .expect("check")
+            .expect("still open");
+        assert!(!again.newly_tripped);
+        assert_eq!(again.trip.id, hold.trip.id);
+
+        repo.reset(DEFAULT_ORG_ID, DEFAULT_USER_ID, &hold.trip.id)
+            .await
+            .expect("reset");
+        let err = repo
+            .reset(DEFAULT_ORG_ID, DEFAULT_USER_ID, &hold.trip.id)
+            .await
+            .expect_err("already reset");
+        assert!(matches!(err, CircuitBreakerError::AlreadyReset(_)));
+        assert!(
+            repo.list(DEFAULT_ORG_ID, DEFAULT_USER_ID, false)
+                .await
+                .expect("list")
+                .is_empty()
+        );
+        assert_eq!(
+            repo.list(DEFAULT_ORG_ID, DEFAULT_USER_ID, true)
+                .await
+                .expect("list")
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn reset_restarts_the_count() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let repo = CircuitBreakerRepository::new(db.clone());
+        let config = config(2, 1);
+        let rule = json!({"rule": {"id": "r1", "name": "Cleanup"}});
+
+        for _ in 0..3 {
+            add_action(&db, &account_id, &message_id, "trash", false, rule.clone()).await;
+        }
+        let hold = repo
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "trash", None),
+            )
+            .await
+            .expect("check")
+            .expect("tripped");
+        repo.reset(DEFAULT_ORG_ID, DEFAULT_USER_ID, &hold.trip.id)
+            .await
+            .expect("reset");
+
+        // The burst is still inside the window, but it was reviewed when the trip was reset
+        let after_reset = repo
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "trash", Some(("r1", "Cleanup"))),
+            )
+            .await
+            .expect("check");
+        assert_eq!(after_reset, None);
+
+        // Actions run after the reset count again
+        add_action(&db, &account_id, &message_id, "trash", false, rule.clone()).await;
+        let hold = repo
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "trash", Some(("r1", "Cleanup"))),
+            )
+            .await
+            .expect("check")
+            .expect("tripped again");
+        assert_eq!(hold.trip.scope, CircuitBreakerScope::Rule);
+        assert_eq!(hold.trip.action_count, 2);
+    }
+
+    #[tokio::test]
+    async fn rule_breaker_pauses_only_that_rule_and_reports_the_burst() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let repo = CircuitBreakerRepository::new(db.clone());
+        let config = config(100, 1);
+        let rule = json!({"rule": {"id": "r1", "name": "Cleanup"}});
+
+        add_action(&db, &account_id, &message_id, "trash", false, rule.clone()).await;
+        add_action(&db, &account_id, &message_id, "trash", false, json!({})).await;
+
+        let hold = repo
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "trash", Some(("r1", "Cleanup"))),
+            )
+            .await
+            .expect("check")
+            .expect("tripped");
+        assert_eq!(hold.trip.scope, CircuitBreakerScope::Rule);
+        assert_eq!(hold.trip.rule_id.as_deref(), Some("r1"));
+        assert_eq!(hold.trip.target(), "rule 'Cleanup'");
+        assert_eq!(hold.trip.action_count, 2);
+
+        let other_rule = repo
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "trash", Some(("r2", "Other"))),
+            )
+            .await
+            .expect("check");
+        assert_eq!(other_rule, None);
+
+        // An action held by the trip, as classification records it
+        add_action(
+            &db,
+            &account_id,
+            &message_id,
+            "trash",
+            true,
+            json!({
+                "rule": {"id": "r1", "name": "Cleanup"},
+                "circuit_breaker": {"trip_id": hold.trip.id},
+            }),
+        )
+        .await;
+
+        let report = repo
+            .report(DEFAULT_ORG_ID, DEFAULT_USER_ID, &hold.trip)
+            .await
+            .expect("report");
+        assert_eq!(report.executed.len(), 1);
+        assert_eq!(report.executed[0].rule_name.as_deref(), Some("Cleanup"));
+        assert_eq!(report.executed[0].subject.as_deref(), Some("Sale"));
+        assert_eq!(report.held.len(), 1);
+        assert_eq!(report.held[0].status, ActionStatus::ApprovedPending);
+    }
+
+    #[tokio::test]
+    async fn disabled_breaker_never_trips() {
+        let (db, _dir, account_id, _message_id) = setup().await;
+        let config = CircuitBreakerConfig {
+            enabled: false,
+            ..config(0, 0)
+        };
+        let hold = CircuitBreakerRepository::new(db)
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &tracked(&account_id, "trash", None),
+            )
+            .await
+            .expect("check");
+        assert_eq!(hold, None);
+    }
+
+    #[test]
+    fn format_window_uses_largest_whole_unit() {
+        assert_eq!(format_window(3600), "1 hour");
+        assert_eq!(format_window(7200), "2 hours");
+        assert_eq!(format_window(300), "5 minutes");
+        assert_eq!(format_window(90), "90 seconds");
+    }
+}
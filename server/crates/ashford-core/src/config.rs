@@ -1,7 +1,12 @@
+use chrono::{Duration, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::{env, path::Path, path::PathBuf};
 use thiserror::Error;
 
+use crate::digest::next_digest_at;
+use crate::rules::{ScheduleDay, ScheduleError};
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Config {
@@ -13,6 +18,32 @@ pub struct Config {
     pub gmail: GmailConfig,
     pub imap: ImapConfig,
     pub policy: PolicyConfig,
+    #[serde(default)]
+    pub decision_cache: DecisionCacheConfig,
+    #[serde(default)]
+    pub pricing: PricingConfig,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    #[serde(default)]
+    pub direction_check: DirectionCheckConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub similar_messages: SimilarMessagesConfig,
+    #[serde(default)]
+    pub backfill: BackfillConfig,
+    #[serde(default)]
+    pub thread_summaries: ThreadSummaryConfig,
+    #[serde(default)]
+    pub digest: DigestConfig,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub approval: ApprovalConfig,
+    #[serde(default)]
+    pub execution_delay: ExecutionDelayConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -43,6 +74,485 @@ pub struct ModelConfig {
     pub model: String,
     pub temperature: f32,
     pub max_output_tokens: u32,
+    /// Base URL of a self-hosted OpenAI-compatible server, e.g. "http://localhost:11434/v1/".
+    /// Applies to every call routed to this provider; unset uses the provider's default.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// API key sent to the provider instead of the provider's default environment variable.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Extra HTTP headers sent with every call to this provider.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Whether the model supports tool calling. When false, tool requests are sent in JSON
+    /// mode with the tool's schema in the prompt.
+    #[serde(default = "default_supports_tools")]
+    pub supports_tools: bool,
+}
+
+/// A provider/model pair used by model routing.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ModelRef {
+    pub provider: String,
+    pub model: String,
+}
+
+/// Model routing on top of the default `[model]`.
+///
+/// Features without an override use the default model. Classification can be re-run on
+/// `escalation_model` when the first answer is unconvincing.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct RoutingConfig {
+    /// Model per LLM call feature, e.g. "classify" or "rules_assistant".
+    pub features: HashMap<String, ModelRef>,
+    /// Stronger model for classification re-runs. Escalation is off when unset.
+    pub escalation_model: Option<ModelRef>,
+    /// Re-run when the first decision's confidence is below this.
+    pub escalation_confidence: f64,
+    /// Re-run when the first decision picks a dangerous action.
+    pub escalate_dangerous: bool,
+    /// Re-run when the first response has no parseable decision.
+    pub escalate_on_parse_failure: bool,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            features: HashMap::new(),
+            escalation_model: None,
+            escalation_confidence: 0.7,
+            escalate_dangerous: true,
+            escalate_on_parse_failure: true,
+        }
+    }
+}
+
+/// Checks that LLM decisions follow the enabled directions. A decision that contradicts a
+/// direction requires approval.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct DirectionCheckConfig {
+    pub enabled: bool,
+    /// Ask the LLM about directions the pattern checks cannot decide. Costs one extra call
+    /// per classification that has such directions.
+    pub llm_check: bool,
+}
+
+impl Default for DirectionCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            llm_check: false,
+        }
+    }
+}
+
+/// Masks personal data in email content before it is sent to the LLM provider. Placeholders
+/// in generated outbound text are restored before the action runs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    pub credit_cards: bool,
+    pub ibans: bool,
+    pub ssns: bool,
+    pub phone_numbers: bool,
+    /// Verification codes and PINs next to words like "code" or "OTP".
+    pub one_time_codes: bool,
+    /// Extra patterns, applied after the built-in ones.
+    pub custom: Vec<CustomRedaction>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            credit_cards: true,
+            ibans: true,
+            ssns: true,
+            phone_numbers: true,
+            one_time_codes: true,
+            custom: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CustomRedaction {
+    /// Placeholder name, e.g. "employee_id" produces `[EMPLOYEE_ID_1]`.
+    pub name: String,
+    /// Regex to mask. If it has a group named `value`, only that group is masked.
+    pub pattern: String,
+}
+
+/// Adds the most similar earlier messages and what happened to them to classification
+/// prompts. Messages are embedded either on-box or by an embedding provider.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct SimilarMessagesConfig {
+    pub enabled: bool,
+    /// "local" for the built-in hashing embedder, or a genai provider such as "openai",
+    /// "gemini" or "ollama".
+    pub provider: String,
+    /// Embedding model of the provider, e.g. "text-embedding-3-small". Unused for "local".
+    pub model: String,
+    /// OpenAI-compatible endpoint for self-hosted embedding servers.
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    /// Vector size of the local embedder.
+    pub dimensions: usize,
+    /// Similar messages included in the prompt.
+    pub top_k: usize,
+    /// Cosine similarity a past message needs to be included.
+    pub min_similarity: f32,
+    /// Most recent embeddings compared per classification.
+    pub max_candidates: usize,
+}
+
+impl Default for SimilarMessagesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: "local".to_string(),
+            model: String::new(),
+            base_url: None,
+            api_key: None,
+            dimensions: 256,
+            top_k: 3,
+            min_similarity: 0.5,
+            max_candidates: 2_000,
+        }
+    }
+}
+
+/// How messages ingested by a backfill are classified.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct BackfillConfig {
+    /// Messages classified per LLM request. 1 classifies each message on its own.
+    pub batch_size: usize,
+    /// How long the per-message classify job waits for the batch before running itself.
+    pub fallback_delay_minutes: i64,
+    /// What happens to historical mail, unless overridden for the account.
+    pub mode: BackfillMode,
+    /// Per-account overrides of `mode`, keyed by account email.
+    pub accounts: HashMap<String, BackfillMode>,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 1,
+            fallback_delay_minutes: 10,
+            mode: BackfillMode::Full,
+            accounts: HashMap::new(),
+        }
+    }
+}
+
+impl BackfillConfig {
+    pub fn is_batching(&self) -> bool {
+        self.batch_size > 1
+    }
+
+    /// The mode for the account with `email`.
+    pub fn mode_for(&self, email: &str) -> BackfillMode {
+        self.accounts
+            .iter()
+            .find(|(account, _)| account.eq_ignore_ascii_case(email))
+            .map(|(_, mode)| *mode)
+            .unwrap_or(self.mode)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillMode {
+    /// Classify and act on historical mail like new mail.
+    #[default]
+    Full,
+    /// Only apply labels; every other action is recorded as a no-op.
+    LabelOnly,
+    /// Ingest without classifying.
+    Skip,
+}
+
+impl BackfillMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackfillMode::Full => "full",
+            BackfillMode::LabelOnly => "label_only",
+            BackfillMode::Skip => "skip",
+        }
+    }
+}
+
+/// Rolling LLM summaries of long threads.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct ThreadSummaryConfig {
+    /// Summarize threads as messages arrive and use the summaries in classification prompts.
+    pub enabled: bool,
+    /// Messages a thread needs before it is summarized.
+    pub min_messages: usize,
+    /// How long after a new message the summary is updated, so a burst of replies is folded
+    /// in with one call.
+    pub delay_seconds: i64,
+    /// Most new messages sent to the LLM per update; older unsummarized ones are dropped.
+    pub max_new_messages: usize,
+}
+
+impl Default for ThreadSummaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_messages: 3,
+            delay_seconds: 120,
+            max_new_messages: 20,
+        }
+    }
+}
+
+/// Periodic digest of what was done automatically, sent to each account.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct DigestConfig {
+    pub enabled: bool,
+    pub frequency: DigestFrequency,
+    /// `HH:MM` wall-clock time in `timezone` the digest is sent at.
+    pub time: String,
+    /// Day a weekly digest is sent on.
+    pub day: ScheduleDay,
+    /// IANA timezone name.
+    pub timezone: String,
+    pub channels: Vec<DigestChannel>,
+    /// Sections included, in order.
+    pub sections: Vec<DigestSection>,
+    /// Email recipient; defaults to the account's own address.
+    pub recipient: Option<String>,
+    /// Send the digest even when every section is empty.
+    pub send_empty: bool,
+    /// Decisions below this confidence are listed as low-confidence.
+    pub low_confidence_threshold: f64,
+    /// Most items listed per section; counts always cover everything.
+    pub max_items: usize,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            frequency: DigestFrequency::Daily,
+            time: "08:00".to_string(),
+            day: ScheduleDay::Mon,
+            timezone: "UTC".to_string(),
+            channels: vec![DigestChannel::Email],
+            sections: vec![
+                DigestSection::Actions,
+                DigestSection::Approvals,
+                DigestSection::Snoozes,
+                DigestSection::LowConfidence,
+                DigestSection::Failures,
+            ],
+            recipient: None,
+            send_empty: false,
+            low_confidence_threshold: 0.7,
+            max_items: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    #[default]
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    /// Length of the period a digest covers.
+    pub fn period(&self) -> Duration {
+        match self {
+            DigestFrequency::Daily => Duration::days(1),
+            DigestFrequency::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestChannel {
+    /// Email to the account through Gmail.
+    Email,
+    /// Message in the configured Discord channel.
+    Discord,
+}
+
+impl DigestChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestChannel::Email => "email",
+            DigestChannel::Discord => "discord",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestSection {
+    /// Completed actions by type and by rule.
+    Actions,
+    /// Actions waiting for approval.
+    Approvals,
+    /// Snoozed messages returning before the next digest.
+    Snoozes,
+    /// Decisions below `low_confidence_threshold`.
+    LowConfidence,
+    /// Actions that failed.
+    Failures,
+}
+
+/// Pauses automation when an account or a rule runs too many tracked actions in a short time.
+/// New tracked actions then wait for approval until the breaker is reset by hand.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    /// Action types counted towards the limits and held once a breaker trips.
+    pub action_types: Vec<String>,
+    /// Sliding windows checked on every tracked action; exceeding any one trips the breaker.
+    pub windows: Vec<CircuitBreakerWindow>,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action_types: [
+                "archive",
+                "trash",
+                "delete",
+                "move",
+                "forward",
+                "auto_reply",
+                "snooze",
+                "mute_thread",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            windows: vec![
+                CircuitBreakerWindow {
+                    seconds: 300,
+                    max_per_account: 100,
+                    max_per_rule: 50,
+                },
+                CircuitBreakerWindow {
+                    seconds: 3600,
+                    max_per_account: 500,
+                    max_per_rule: 250,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CircuitBreakerWindow {
+    /// Length of the window.
+    pub seconds: i64,
+    /// Most tracked actions an account may run automatically within the window.
+    pub max_per_account: i64,
+    /// Most tracked actions one deterministic rule may run automatically within the window.
+    pub max_per_rule: i64,
+}
+
+/// Deadlines for actions waiting in `approved_pending`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct ApprovalConfig {
+    /// Timeouts by action type. Action types without a matching entry wait indefinitely.
+    pub timeouts: Vec<ApprovalTimeout>,
+}
+
+impl ApprovalConfig {
+    /// The timeout listing `action_type`, else the first one without action types.
+    pub fn timeout_for(&self, action_type: &str) -> Option<&ApprovalTimeout> {
+        self.timeouts
+            .iter()
+            .find(|timeout| timeout.action_types.iter().any(|a| a == action_type))
+            .or_else(|| {
+                self.timeouts
+                    .iter()
+                    .find(|timeout| timeout.action_types.is_empty())
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ApprovalTimeout {
+    /// Action types covered; empty covers every type without its own entry.
+    #[serde(default)]
+    pub action_types: Vec<String>,
+    /// Minutes an action may wait for approval.
+    pub after_minutes: i64,
+    /// What happens once the action has waited `after_minutes`.
+    pub outcome: ApprovalTimeoutOutcome,
+    /// Send a reminder this many minutes before the timeout, once per entry.
+    #[serde(default)]
+    pub remind_before_minutes: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalTimeoutOutcome {
+    /// Reject the action.
+    Reject,
+    /// Run the action, unless it is dangerous, which escalates instead.
+    Approve,
+    /// Alert that the approval is overdue and keep the action waiting.
+    Escalate,
+}
+
+impl ApprovalTimeoutOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalTimeoutOutcome::Reject => "reject",
+            ApprovalTimeoutOutcome::Approve => "approve",
+            ApprovalTimeoutOutcome::Escalate => "escalate",
+        }
+    }
+}
+
+/// Minutes an automatically run action waits in `pending_execution`, cancelable, before its
+/// job runs. Zero runs it right away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct ExecutionDelayConfig {
+    pub trash_minutes: i64,
+    pub delete_minutes: i64,
+    pub forward_minutes: i64,
+    pub auto_reply_minutes: i64,
+}
+
+impl ExecutionDelayConfig {
+    /// The delay for `action_type`, if it has one.
+    pub fn delay_for(&self, action_type: &str) -> Option<Duration> {
+        let minutes = match action_type {
+            "trash" => self.trash_minutes,
+            "delete" => self.delete_minutes,
+            "forward" => self.forward_minutes,
+            "auto_reply" => self.auto_reply_minutes,
+            _ => 0,
+        };
+        (minutes > 0).then(|| Duration::minutes(minutes))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -106,6 +616,128 @@ impl Default for PolicyConfig {
     }
 }
 
+/// Sender-level decision cache. When enabled, a message skips the LLM if the last
+/// `min_agreeing` LLM decisions for the same sender and subject pattern agree.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct DecisionCacheConfig {
+    pub enabled: bool,
+    /// Number of most recent decisions that must agree.
+    pub min_agreeing: usize,
+    /// Minimum confidence each of those decisions must have.
+    pub min_confidence: f64,
+    /// Decisions older than this are not reused.
+    pub ttl_hours: i64,
+}
+
+impl Default for DecisionCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_agreeing: 3,
+            min_confidence: 0.9,
+            ttl_hours: 168,
+        }
+    }
+}
+
+/// Per-model token prices used to turn `llm_calls` token counts into spend.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct PricingConfig {
+    pub models: Vec<ModelPricing>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ModelPricing {
+    /// Model name as logged, e.g. "openai::gpt-4o-mini". A name without a provider
+    /// prefix matches that model from any provider.
+    pub model: String,
+    /// USD per million input tokens.
+    pub input_per_million: f64,
+    /// USD per million output tokens.
+    pub output_per_million: f64,
+}
+
+impl PricingConfig {
+    /// Find the price entry for a logged model name.
+    ///
+    /// Exact (case-insensitive) matches win; otherwise the model part after `::` is compared.
+    pub fn price_for(&self, model: &str) -> Option<&ModelPricing> {
+        let exact = self
+            .models
+            .iter()
+            .find(|entry| entry.model.eq_ignore_ascii_case(model));
+        exact.or_else(|| {
+            let bare = model_without_provider(model);
+            self.models
+                .iter()
+                .find(|entry| model_without_provider(&entry.model).eq_ignore_ascii_case(bare))
+        })
+    }
+
+    /// Cost in USD of a call, or `None` when the model has no configured price.
+    pub fn cost_usd(&self, model: &str, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+        self.price_for(model).map(|price| {
+            (input_tokens as f64 * price.input_per_million
+                + output_tokens as f64 * price.output_per_million)
+                / 1_000_000.0
+        })
+    }
+}
+
+fn model_without_provider(model: &str) -> &str {
+    model.rsplit_once("::").map_or(model, |(_, name)| name)
+}
+
+/// LLM spend limits. Once any limit that applies to a classification is reached, the
+/// classifier stops calling the LLM until the window rolls over.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct BudgetConfig {
+    /// Total USD per UTC day across all features and accounts.
+    pub daily_usd: Option<f64>,
+    /// Total USD per UTC calendar month across all features and accounts.
+    pub monthly_usd: Option<f64>,
+    /// What classification does once a budget is exceeded.
+    pub when_exceeded: BudgetExceededAction,
+    /// Narrower limits for a feature, an account, or both.
+    pub limits: Vec<BudgetLimit>,
+}
+
+impl BudgetConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.daily_usd.is_some()
+            || self.monthly_usd.is_some()
+            || self
+                .limits
+                .iter()
+                .any(|limit| limit.daily_usd.is_some() || limit.monthly_usd.is_some())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetExceededAction {
+    /// Leave messages without a deterministic match unclassified.
+    #[default]
+    Skip,
+    /// Record a no-op decision that waits for manual approval.
+    Approval,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct BudgetLimit {
+    /// LLM call feature, e.g. "classify". Unset matches every feature.
+    pub feature: Option<String>,
+    /// Account ID. Unset matches every account.
+    pub account_id: Option<String>,
+    pub daily_usd: Option<f64>,
+    pub monthly_usd: Option<f64>,
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("failed to read configuration file: {0}")]
@@ -116,6 +748,10 @@ pub enum ConfigError {
     MissingEnvVar(String),
     #[error("invalid APP_PORT override: {0}")]
     InvalidPort(std::num::ParseIntError),
+    #[error("invalid redaction pattern {name}: {source}")]
+    InvalidRedactionPattern { name: String, source: regex::Error },
+    #[error("invalid digest schedule: {0}")]
+    InvalidDigestSchedule(ScheduleError),
 }
 
 impl Config {
@@ -131,9 +767,25 @@ impl Config {
         cfg.apply_env_overrides()?;
         cfg.resolve_env_markers()?;
         cfg.expand_paths();
+        cfg.validate_redaction()?;
+        if cfg.digest.enabled {
+            next_digest_at(&cfg.digest, Utc::now()).map_err(ConfigError::InvalidDigestSchedule)?;
+        }
         Ok(cfg)
     }
 
+    fn validate_redaction(&self) -> Result<(), ConfigError> {
+        for custom in &self.redaction.custom {
+            regex::Regex::new(&custom.pattern).map_err(|source| {
+                ConfigError::InvalidRedactionPattern {
+                    name: custom.name.clone(),
+                    source,
+                }
+            })?;
+        }
+        Ok(())
+    }
+
     fn apply_env_overrides(&mut self) -> Result<(), ConfigError> {
         if let Ok(port) = env::var("APP_PORT") {
             let port: u16 = port.parse().map_err(ConfigError::InvalidPort)?;
@@ -160,6 +812,21 @@ impl Config {
         apply_env_marker(&mut self.app.env)?;
         apply_env_marker(&mut self.model.provider)?;
         apply_env_marker(&mut self.model.model)?;
+        if let Some(base_url) = &mut self.model.base_url {
+            apply_env_marker(base_url)?;
+        }
+        if let Some(api_key) = &mut self.model.api_key {
+            apply_env_marker(api_key)?;
+        }
+        for value in self.model.headers.values_mut() {
+            apply_env_marker(value)?;
+        }
+        if let Some(base_url) = &mut self.similar_messages.base_url {
+            apply_env_marker(base_url)?;
+        }
+        if let Some(api_key) = &mut self.similar_messages.api_key {
+            apply_env_marker(api_key)?;
+        }
         apply_env_marker(&mut self.discord.bot_token)?;
         apply_env_marker(&mut self.discord.channel_id)?;
         for entry in &mut self.discord.whitelist {
@@ -203,6 +870,10 @@ fn default_snooze_label() -> String {
     "Ashford/Snoozed".to_string()
 }
 
+fn default_supports_tools() -> bool {
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,6 +934,12 @@ provider = "vercel"
 model = "gemini-1.5-pro"
 temperature = 0.2
 max_output_tokens = 1024
+base_url = "http://localhost:11434/v1/"
+api_key = "env:LOCAL_LLM_KEY"
+supports_tools = false
+
+[model.headers]
+x-tenant = "env:LOCAL_LLM_TENANT"
 
 [discord]
 bot_token = "env:DISCORD_BOT_TOKEN"
@@ -283,6 +960,95 @@ snooze_folder = "Snoozed"
 [policy]
 approval_always = ["delete","forward"]
 confidence_default = 0.7
+
+[decision_cache]
+enabled = true
+ttl_hours = 24
+
+[[pricing.models]]
+model = "vercel::gemini-1.5-pro"
+input_per_million = 1.25
+output_per_million = 5.0
+
+[budget]
+monthly_usd = 20.0
+when_exceeded = "approval"
+
+[[budget.limits]]
+feature = "classify"
+daily_usd = 1.5
+
+[routing]
+escalation_confidence = 0.8
+
+[routing.features.classify]
+provider = "openai"
+model = "gpt-4o-mini"
+
+[routing.escalation_model]
+provider = "openai"
+model = "gpt-4o"
+
+[direction_check]
+llm_check = true
+
+[redaction]
+enabled = true
+phone_numbers = false
+
+[[redaction.custom]]
+name = "employee_id"
+pattern = "EMP-\\d{{6}}"
+
+[similar_messages]
+enabled = true
+provider = "openai"
+model = "nomic-embed-text"
+base_url = "http://localhost:11434/v1"
+api_key = "env:LOCAL_LLM_KEY"
+top_k = 5
+
+[backfill]
+batch_size = 10
+mode = "label_only"
+
+[backfill.accounts]
+"Archive@Example.com" = "skip"
+
+[thread_summaries]
+enabled = true
+min_messages = 4
+
+[digest]
+enabled = true
+frequency = "weekly"
+day = "fri"
+time = "17:30"
+timezone = "Europe/Berlin"
+channels = ["email", "discord"]
+sections = ["actions", "failures"]
+
+[circuit_breaker]
+action_types = ["trash", "delete"]
+
+[[circuit_breaker.windows]]
+seconds = 60
+max_per_account = 20
+max_per_rule = 10
+
+[[approval.timeouts]]
+action_types = ["archive", "trash"]
+after_minutes = 1440
+outcome = "approve"
+remind_before_minutes = [60]
+
+[[approval.timeouts]]
+after_minutes = 2880
+outcome = "reject"
+
+[execution_delay]
+delete_minutes = 30
+auto_reply_minutes = 5
 "#
         )
     }
@@ -306,6 +1072,8 @@ confidence_default = 0.7
                 ("WHITELIST_USER", Some("user#1")),
                 ("GMAIL_PROJECT", Some("project-1")),
                 ("GMAIL_SUB", Some("sub-1")),
+                ("LOCAL_LLM_KEY", Some("local-key")),
+                ("LOCAL_LLM_TENANT", Some("ashford")),
             ],
             || {
                 let cfg = Config::load(&path).expect("config loads");
@@ -319,9 +1087,98 @@ confidence_default = 0.7
                 assert_eq!(cfg.discord.bot_token, "secret-token");
                 assert_eq!(cfg.discord.channel_id, "channel-123");
                 assert_eq!(cfg.discord.whitelist, vec!["user#1".to_string()]);
+                assert_eq!(
+                    cfg.model.base_url.as_deref(),
+                    Some("http://localhost:11434/v1/")
+                );
+                assert_eq!(cfg.model.api_key.as_deref(), Some("local-key"));
+                assert_eq!(cfg.model.headers["x-tenant"], "ashford");
+                assert!(!cfg.model.supports_tools);
                 assert_eq!(cfg.gmail.project_id, "project-1");
                 assert_eq!(cfg.gmail.subscription, "sub-1");
                 assert_eq!(cfg.gmail.snooze_label, "Ashford/Snoozed");
+                assert!(cfg.decision_cache.enabled);
+                assert_eq!(cfg.decision_cache.ttl_hours, 24);
+                assert_eq!(cfg.decision_cache.min_agreeing, 3);
+                assert_eq!(cfg.pricing.models.len(), 1);
+                assert_eq!(cfg.budget.monthly_usd, Some(20.0));
+                assert_eq!(cfg.budget.daily_usd, None);
+                assert_eq!(cfg.budget.when_exceeded, BudgetExceededAction::Approval);
+                assert_eq!(cfg.budget.limits.len(), 1);
+                assert_eq!(cfg.budget.limits[0].feature.as_deref(), Some("classify"));
+                assert_eq!(cfg.budget.limits[0].daily_usd, Some(1.5));
+                assert!(cfg.budget.is_enabled());
+                assert_eq!(cfg.routing.features["classify"].model, "gpt-4o-mini");
+                assert_eq!(
+                    cfg.routing
+                        .escalation_model
+                        .as_ref()
+                        .map(|m| m.model.as_str()),
+                    Some("gpt-4o")
+                );
+                assert_eq!(cfg.routing.escalation_confidence, 0.8);
+                assert!(cfg.routing.escalate_dangerous);
+                assert!(cfg.direction_check.enabled);
+                assert!(cfg.direction_check.llm_check);
+                assert!(cfg.redaction.enabled);
+                assert!(cfg.redaction.credit_cards);
+                assert!(!cfg.redaction.phone_numbers);
+                assert_eq!(cfg.redaction.custom[0].name, "employee_id");
+                assert_eq!(cfg.redaction.custom[0].pattern, r"EMP-\d{6}");
+                assert!(cfg.similar_messages.enabled);
+                assert_eq!(cfg.similar_messages.model, "nomic-embed-text");
+                assert_eq!(cfg.similar_messages.api_key.as_deref(), Some("local-key"));
+                assert_eq!(cfg.similar_messages.top_k, 5);
+                assert_eq!(cfg.similar_messages.min_similarity, 0.5);
+                assert_eq!(cfg.backfill.batch_size, 10);
+                assert_eq!(cfg.backfill.fallback_delay_minutes, 10);
+                assert_eq!(
+                    cfg.backfill.mode_for("me@example.com"),
+                    BackfillMode::LabelOnly
+                );
+                assert_eq!(
+                    cfg.backfill.mode_for("archive@example.com"),
+                    BackfillMode::Skip
+                );
+                assert!(cfg.thread_summaries.enabled);
+                assert_eq!(cfg.thread_summaries.min_messages, 4);
+                assert_eq!(cfg.thread_summaries.delay_seconds, 120);
+                assert!(cfg.digest.enabled);
+                assert_eq!(cfg.digest.frequency, DigestFrequency::Weekly);
+                assert_eq!(cfg.digest.day, ScheduleDay::Fri);
+                assert_eq!(
+                    cfg.digest.channels,
+                    vec![DigestChannel::Email, DigestChannel::Discord]
+                );
+                assert_eq!(
+                    cfg.digest.sections,
+                    vec![DigestSection::Actions, DigestSection::Failures]
+                );
+                assert_eq!(cfg.digest.max_items, 10);
+                assert!(cfg.circuit_breaker.enabled);
+                assert_eq!(cfg.circuit_breaker.action_types, vec!["trash", "delete"]);
+                assert_eq!(
+                    cfg.circuit_breaker.windows,
+                    vec![CircuitBreakerWindow {
+                        seconds: 60,
+                        max_per_account: 20,
+                        max_per_rule: 10,
+                    }]
+                );
+                let trash = cfg.approval.timeout_for("trash").expect("trash timeout");
+                assert_eq!(trash.outcome, ApprovalTimeoutOutcome::Approve);
+                assert_eq!(trash.remind_before_minutes, vec![60]);
+                let delete = cfg
+                    .approval
+                    .timeout_for("delete")
+                    .expect("fallback timeout");
+                assert_eq!(delete.outcome, ApprovalTimeoutOutcome::Reject);
+                assert_eq!(delete.after_minutes, 2880);
+                assert_eq!(
+                    cfg.execution_delay.delay_for("delete"),
+                    Some(Duration::minutes(30))
+                );
+                assert_eq!(cfg.execution_delay.delay_for("trash"), None);
             },
         );
     }
@@ -385,11 +1242,61 @@ confidence_default = 0.5
                     Some("http://override.local:4318")
                 );
                 assert_eq!(cfg.model.model, "env-model");
+                assert!(cfg.model.base_url.is_none());
+                assert!(cfg.model.headers.is_empty());
+                assert!(cfg.model.supports_tools);
                 assert_eq!(cfg.discord.bot_token, "env-token");
+                assert!(cfg.pricing.models.is_empty());
+                assert!(!cfg.budget.is_enabled());
+                assert!(cfg.routing.features.is_empty());
+                assert!(cfg.routing.escalation_model.is_none());
+                assert!(cfg.direction_check.enabled);
+                assert!(!cfg.direction_check.llm_check);
+                assert!(!cfg.redaction.enabled);
+                assert!(cfg.redaction.custom.is_empty());
+                assert!(!cfg.similar_messages.enabled);
+                assert_eq!(cfg.similar_messages.provider, "local");
+                assert!(!cfg.backfill.is_batching());
+                assert_eq!(cfg.backfill.mode, BackfillMode::Full);
+                assert!(!cfg.thread_summaries.enabled);
+                assert!(!cfg.digest.enabled);
+                assert_eq!(cfg.digest.channels, vec![DigestChannel::Email]);
+                assert!(cfg.circuit_breaker.enabled);
+                assert_eq!(cfg.circuit_breaker.windows.len(), 2);
+                assert!(cfg.approval.timeout_for("delete").is_none());
+                assert_eq!(cfg.execution_delay, ExecutionDelayConfig::default());
             },
         );
     }
 
+    #[test]
+    fn pricing_matches_exact_name_then_bare_model() {
+        let pricing = PricingConfig {
+            models: vec![
+                ModelPricing {
+                    model: "gpt-4o-mini".into(),
+                    input_per_million: 0.15,
+                    output_per_million: 0.6,
+                },
+                ModelPricing {
+                    model: "azure::gpt-4o-mini".into(),
+                    input_per_million: 0.2,
+                    output_per_million: 0.8,
+                },
+            ],
+        };
+
+        let cost = pricing
+            .cost_usd("OpenAI::gpt-4o-mini", 1_000_000, 500_000)
+            .expect("priced");
+        assert!((cost - 0.45).abs() < 1e-9);
+        let azure = pricing
+            .price_for("Azure::gpt-4o-mini")
+            .expect("exact match");
+        assert_eq!(azure.input_per_million, 0.2);
+        assert!(pricing.cost_usd("anthropic::claude", 10, 10).is_none());
+    }
+
     #[test]
     fn env_marker_without_variable_errors() {
         let (_dir, path) = write_config(
@@ -452,6 +1359,39 @@ confidence_default = 0.5
         );
     }
 
+    #[test]
+    fn invalid_redaction_pattern_is_reported() {
+        let body = format!(
+            "{}\n[[redaction.custom]]\nname = \"broken\"\npattern = \"(unclosed\"\n",
+            full_config_body("/tmp/db.sqlite")
+        );
+        let (_dir, path) = write_config(&body);
+
+        with_env(
+            &[
+                ("APP_PORT", None),
+                ("OTLP_ENDPOINT", None),
+                ("MODEL", None),
+                ("DISCORD_BOT_TOKEN", Some("secret-token")),
+                ("DISCORD_CHANNEL", Some("channel-123")),
+                ("WHITELIST_USER", Some("user#1")),
+                ("GMAIL_PROJECT", Some("project-1")),
+                ("GMAIL_SUB", Some("sub-1")),
+                ("LOCAL_LLM_KEY", Some("local-key")),
+                ("LOCAL_LLM_TENANT", Some("ashford")),
+            ],
+            || {
+                let err = Config::load(&path).expect_err("invalid pattern should error");
+                match err {
+                    ConfigError::InvalidRedactionPattern { name, .. } => {
+                        assert_eq!(name, "broken")
+                    }
+                    other => panic!("unexpected error: {other}"),
+                }
+            },
+        );
+    }
+
     #[test]
     fn invalid_port_override_is_reported() {
         let (_dir, path) = write_config(
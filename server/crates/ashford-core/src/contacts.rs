@@ -0,0 +1,444 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use libsql::{Row, params};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::db::{Database, DbError};
+use crate::messages::Mailbox;
+
+const CONTACT_COLUMNS: &str = "id, account_id, email, name, sent_count, reply_count, received_count, last_sent_at, last_interaction_at, created_at, updated_at, org_id, user_id";
+
+/// A known correspondent: someone the account has sent mail to.
+///
+/// Contacts are only created from sent mail, so cold outreach and newsletters never
+/// become contacts on their own. Incoming mail from an existing contact updates
+/// `received_count` and `last_interaction_at`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contact {
+    pub id: String,
+    pub account_id: String,
+    /// Lowercased email address.
+    pub email: String,
+    pub name: Option<String>,
+    /// Number of sent messages addressed to this contact.
+    pub sent_count: i64,
+    /// Number of those sent messages that were replies.
+    pub reply_count: i64,
+    /// Number of messages received from this contact since it became known.
+    pub received_count: i64,
+    pub last_sent_at: Option<DateTime<Utc>>,
+    pub last_interaction_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub org_id: i64,
+    pub user_id: i64,
+}
+
+/// How strong the relationship with a contact is, derived from how often the account
+/// has written to and replied to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ContactStrength {
+    /// Written to once or twice, never replied to.
+    Weak,
+    /// Written to at least 3 times, or replied to at least once.
+    Moderate,
+    /// Written to at least 10 times, or replied to at least 3 times.
+    Strong,
+}
+
+impl ContactStrength {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContactStrength::Weak => "weak",
+            ContactStrength::Moderate => "moderate",
+            ContactStrength::Strong => "strong",
+        }
+    }
+}
+
+impl Contact {
+    pub fn strength(&self) -> ContactStrength {
+        if self.reply_count >= 3 || self.sent_count >= 10 {
+            ContactStrength::Strong
+        } else if self.reply_count >= 1 || self.sent_count >= 3 {
+            ContactStrength::Moderate
+        } else {
+            ContactStrength::Weak
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ContactError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("datetime parse error: {0}")]
+    DateTimeParse(#[from] chrono::ParseError),
+}
+
+#[derive(Clone)]
+pub struct ContactRepository {
+    db: Database,
+}
+
+impl ContactRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Record a message the account sent. Every recipient becomes (or stays) a contact with its
+    /// sent count bumped, and its reply count too when `is_reply`.
+    ///
+    /// Callers must record each sent message once and leave the account's own address out of
+    /// `recipients`; counts are not deduplicated across calls.
+    pub async fn record_sent(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        recipients: &[Mailbox],
+        is_reply: bool,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), ContactError> {
+        let now = now_rfc3339();
+        let sent_at = to_rfc3339(sent_at);
+        let reply_increment: i64 = if is_reply { 1 } else { 0 };
+
+        let mut seen = Vec::new();
+        let conn = self.db.connection().await?;
+        for recipient in recipients {
+            let email = recipient.email.trim().to_lowercase();
+            if email.is_empty() || seen.contains(&email) {
+                continue;
+            }
+
+            conn.execute(
+                "INSERT INTO contacts (id, account_id, email, name, sent_count, reply_count, received_count, last_sent_at, last_interaction_at, created_at, updated_at, org_id, user_id)
+                 VALUES (?1, ?2, ?3, ?4, 1, ?5, 0, ?6, ?6, ?7, ?7, ?8, ?9)
+                 ON CONFLICT(account_id, email) DO UPDATE SET
+                    name = COALESCE(excluded.name, contacts.name),
+                    sent_count = contacts.sent_count + 1,
+                    reply_count = contacts.reply_count + excluded.reply_count,
+                    last_sent_at = MAX(COALESCE(contacts.last_sent_at, ''), excluded.last_sent_at),
+                    last_interaction_at = MAX(COALESCE(contacts.last_interaction_at, ''), excluded.last_interaction_at),
+                    updated_at = excluded.updated_at",
+                params![
+                    Uuid::new_v4().to_string(),
+                    account_id,
+                    email.as_str(),
+                    recipient.name.clone().filter(|name| !name.trim().is_empty()),
+                    reply_increment,
+                    sent_at.as_str(),
+                    now.as_str(),
+                    org_id,
+                    user_id
+                ],
+            )
+            .await?;
+            seen.push(email);
+        }
+
+        Ok(())
+    }
+
+    /// Record a message received from `email`. Only existing contacts are updated; returns
+    /// whether the sender is a contact.
+    pub async fn record_received(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        email: &str,
+        received_at: DateTime<Utc>,
+    ) -> Result<bool, ContactError> {
+        let conn = self.db.connection().await?;
+        let affected = conn
+            .execute(
+                "UPDATE contacts
+                 SET received_count = received_count + 1,
+                     last_interaction_at = MAX(COALESCE(last_interaction_at, ''), ?1),
+                     updated_at = ?2
+                 WHERE org_id = ?3 AND user_id = ?4 AND account_id = ?5 AND email = ?6",
+                params![
+                    to_rfc3339(received_at),
+                    now_rfc3339(),
+                    org_id,
+                    user_id,
+                    account_id,
+                    email.trim().to_lowercase()
+                ],
+            )
+            .await?;
+        Ok(affected > 0)
+    }
+
+    /// Look up a contact by email address (case-insensitive).
+    pub async fn get_by_email(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        email: &str,
+    ) -> Result<Option<Contact>, ContactError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {CONTACT_COLUMNS}
+                     FROM contacts
+                     WHERE org_id = ?1 AND user_id = ?2 AND account_id = ?3 AND email = ?4"
+                ),
+                params![org_id, user_id, account_id, email.trim().to_lowercase()],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(row_to_contact(row)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn row_to_contact(row: Row) -> Result<Contact, ContactError> {
+    let last_sent_at: Option<String> = row.get(7)?;
+    let last_interaction_at: Option<String> = row.get(8)?;
+    let created_at: String = row.get(9)?;
+    let updated_at: String = row.get(10)?;
+
+    Ok(Contact {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        email: row.get(2)?,
+        name: row.get(3)?,
+        sent_count: row.get(4)?,
+        reply_count: row.get(5)?,
+        received_count: row.get(6)?,
+        last_sent_at: parse_optional_datetime(last_sent_at)?,
+        last_interaction_at: parse_optional_datetime(last_interaction_at)?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+        org_id: row.get(11)?,
+        user_id: row.get(12)?,
+    })
+}
+
+fn parse_optional_datetime(value: Option<String>) -> Result<Option<DateTime<Utc>>, ContactError> {
+    match value {
+        Some(value) => Ok(Some(
+            DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc),
+        )),
+        None => Ok(None),
+    }
+}
+
+fn now_rfc3339() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn to_rfc3339(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::gmail::OAuthTokens;
+    use crate::migrations::run_migrations;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    async fn setup_repo() -> (ContactRepository, String, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_name = format!("db_{}.sqlite", Uuid::new_v4());
+        let db = Database::new(&dir.path().join(db_name))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "me@example.com",
+                Some("Me".into()),
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+
+        (ContactRepository::new(db), account.id, dir)
+    }
+
+    fn mailbox(email: &str, name: Option<&str>) -> Mailbox {
+        Mailbox {
+            email: email.to_string(),
+            name: name.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn record_sent_creates_and_updates_contacts() {
+        let (repo, account_id, _dir) = setup_repo().await;
+        let earlier = Utc::now() - Duration::days(2);
+        let later = Utc::now();
+
+        repo.record_sent(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &account_id,
+            &[
+                mailbox("Alice@Example.com", Some("Alice")),
+                mailbox("alice@example.com", None),
+            ],
+            false,
+            later,
+        )
+        .await
+        .expect("record first");
+        repo.record_sent(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &account_id,
+            &[mailbox("alice@example.com", None)],
+            true,
+            earlier,
+        )
+        .await
+        .expect("record reply");
+
+        let alice = repo
+            .get_by_email(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "ALICE@example.com",
+            )
+            .await
+            .expect("get")
+            .expect("alice is a contact");
+        assert_eq!(alice.email, "alice@example.com");
+        assert_eq!(alice.name.as_deref(), Some("Alice"));
+        assert_eq!(
+            alice.sent_count, 2,
+            "duplicates within a message count once"
+        );
+        assert_eq!(alice.reply_count, 1);
+        assert_eq!(
+            alice.last_sent_at.map(|at| at.timestamp_millis()),
+            Some(later.timestamp_millis()),
+            "older messages must not move last_sent_at backwards"
+        );
+        assert_eq!(alice.strength(), ContactStrength::Moderate);
+    }
+
+    #[tokio::test]
+    async fn record_received_only_updates_existing_contacts() {
+        let (repo, account_id, _dir) = setup_repo().await;
+        let now = Utc::now();
+
+        assert!(
+            !repo
+                .record_received(
+                    DEFAULT_ORG_ID,
+                    DEFAULT_USER_ID,
+                    &account_id,
+                    "cold@example.com",
+                    now
+                )
+                .await
+                .expect("record unknown")
+        );
+        assert!(
+            repo.get_by_email(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "cold@example.com"
+            )
+            .await
+            .expect("get")
+            .is_none()
+        );
+
+        repo.record_sent(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &account_id,
+            &[mailbox("bob@example.com", None)],
+            false,
+            now - Duration::days(1),
+        )
+        .await
+        .expect("record sent");
+        assert!(
+            repo.record_received(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "Bob@example.com",
+                now
+            )
+            .await
+            .expect("record known")
+        );
+
+        let bob = repo
+            .get_by_email(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "bob@example.com",
+            )
+            .await
+            .expect("get")
+            .expect("bob");
+        assert_eq!(bob.received_count, 1);
+        assert_eq!(
+            bob.last_interaction_at.map(|at| at.timestamp_millis()),
+            Some(now.timestamp_millis())
+        );
+    }
+
+    #[test]
+    fn strength_thresholds() {
+        let contact = |sent_count, reply_count| Contact {
+            id: "c".into(),
+            account_id: "a".into(),
+            email: "x@example.com".into(),
+            name: None,
+            sent_count,
+            reply_count,
+            received_count: 0,
+            last_sent_at: None,
+            last_interaction_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            org_id: DEFAULT_ORG_ID,
+            user_id: DEFAULT_USER_ID,
+        };
+
+        assert_eq!(contact(1, 0).strength(), ContactStrength::Weak);
+        assert_eq!(contact(3, 0).strength(), ContactStrength::Moderate);
+        assert_eq!(contact(1, 1).strength(), ContactStrength::Moderate);
+        assert_eq!(contact(10, 0).strength(), ContactStrength::Strong);
+        assert_eq!(contact(3, 3).strength(), ContactStrength::Strong);
+        assert!(ContactStrength::Strong > ContactStrength::Moderate);
+    }
+}
@@ -0,0 +1,632 @@
+//! Sender-level decision cache.
+//!
+//! Automated senders tend to get the same decision for every message. When the most recent
+//! LLM decisions for a sender and subject pattern all agree, the classifier can reuse that
+//! decision instead of calling the LLM again.
+//!
+//! Every LLM decision records a fingerprint of the directions and LLM rules that were in the
+//! prompt (`rules_fingerprint` in `telemetry_json`). Only decisions made under the current
+//! fingerprint are reused, so editing, adding, disabling or deleting a rule or direction
+//! invalidates the cache without any bookkeeping.
+
+use chrono::{Duration, SecondsFormat, Utc};
+use libsql::params;
+use thiserror::Error;
+
+use crate::config::DecisionCacheConfig;
+use crate::db::{Database, DbError};
+use crate::llm::decision::{DecisionOutput, MessageRef};
+use crate::messages::Message;
+use crate::rules::types::{Direction, LlmRule};
+
+/// Key under which LLM decisions store the rules fingerprint in `telemetry_json`.
+pub const RULES_FINGERPRINT_KEY: &str = "rules_fingerprint";
+
+/// Upper bound on decisions fetched per sender before filtering by subject pattern.
+const CANDIDATE_LIMIT: i64 = 50;
+
+#[derive(Debug, Error)]
+pub enum DecisionCacheError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A decision reused from earlier LLM decisions.
+#[derive(Debug, Clone)]
+pub struct CachedDecision {
+    /// The reused decision, re-targeted at the message being classified.
+    pub output: DecisionOutput,
+    /// The agreeing decisions, most recent first.
+    pub source_decision_ids: Vec<String>,
+    pub subject_pattern: String,
+}
+
+#[derive(Clone)]
+pub struct DecisionCache {
+    db: Database,
+    config: DecisionCacheConfig,
+}
+
+struct Candidate {
+    id: String,
+    subject: Option<String>,
+    decision_json: String,
+    confidence: Option<f64>,
+    corrected: bool,
+}
+
+impl DecisionCache {
+    pub fn new(db: Database, config: DecisionCacheConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled && self.config.min_agreeing > 0
+    }
+
+    /// Look for a reusable decision for `message`.
+    ///
+    /// Hits only when the last `min_agreeing` LLM decisions for the same sender (compared
+    /// case-insensitively) and subject pattern, made within the TTL under `rules_fingerprint`,
+    /// all have at least `min_confidence`, were never undone or rejected, and chose the same
+    /// action with the same parameters.
+    pub async fn lookup(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        message: &Message,
+        rules_fingerprint: &str,
+    ) -> Result<Option<CachedDecision>, DecisionCacheError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let Some(from_email) = message.from_email.as_deref() else {
+            return Ok(None);
+        };
+
+        let cutoff = (Utc::now() - Duration::hours(self.config.ttl_hours))
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "SELECT d.id, m.subject, d.decision_json, d.confidence,
+                        EXISTS (
+                            SELECT 1 FROM actions a
+                            WHERE a.decision_id = d.id
+                              AND (a.status = 'rejected' OR EXISTS (
+                                  SELECT 1 FROM action_links l
+                                  WHERE l.effect_action_id = a.id AND l.relation_type = 'undo_of'
+                              ))
+                        ) AS corrected
+                 FROM decisions d
+                 JOIN messages m ON m.id = d.message_id
+                 WHERE d.org_id = ?1 AND d.user_id = ?2
+                   AND m.account_id = ?3 AND lower(m.from_email) = lower(?4)
+                   AND d.message_id != ?5
+                   AND d.source = 'llm'
+                   AND d.created_at >= ?6
+                   AND json_extract(d.telemetry_json, '$.rules_fingerprint') = ?7
+                 ORDER BY d.created_at DESC, d.rowid DESC
+                 LIMIT ?8",
+                params![
+                    org_id,
+                    user_id,
+                    message.account_id.as_str(),
+                    from_email,
+                    message.id.as_str(),
+                    cutoff,
+                    rules_fingerprint,
+                    CANDIDATE_LIMIT
+                ],
+            )
+            .await?;
+
+        let pattern = subject_pattern(message.subject.as_deref());
+        let mut agreeing = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let candidate = Candidate {
+                id: row.get(0)?,
+                subject: row.get(1)?,
+                decision_json: row.get(2)?,
+                confidence: row.get(3)?,
+                corrected: row.get::<i64>(4)? != 0,
+            };
+            if subject_pattern(candidate.subject.as_deref()) != pattern {
+                continue;
+            }
+            agreeing.push(candidate);
+            if agreeing.len() == self.config.min_agreeing {
+                break;
+            }
+        }
+
+        if agreeing.len() < self.config.min_agreeing {
+            return Ok(None);
+        }
+        if agreeing.iter().any(|candidate| {
+            candidate.corrected || candidate.confidence.unwrap_or(0.0) < self.config.min_confidence
+        }) {
+            return Ok(None);
+        }
+
+        let outputs = agreeing
+            .iter()
+            .map(|candidate| serde_json::from_str::<DecisionOutput>(&candidate.decision_json))
+            .collect::<Result<Vec<_>, _>>()?;
+        let latest = &outputs[0];
+        let all_agree = outputs.iter().all(|output| {
+            output.decision.action == latest.decision.action
+                && output.decision.parameters == latest.decision.parameters
+        });
+        if !all_agree {
+            return Ok(None);
+        }
+
+        let mut output = latest.clone();
+        output.message_ref = MessageRef {
+            provider: output.message_ref.provider,
+            account_id: message.account_id.clone(),
+            thread_id: message.thread_id.clone(),
+            message_id: message.id.clone(),
+        };
+        output.decision.rationale = format!(
+            "Reused the decision from {} agreeing earlier messages from this sender: {}",
+            agreeing.len(),
+            output.decision.rationale
+        );
+
+        Ok(Some(CachedDecision {
+            output,
+            source_decision_ids: agreeing.into_iter().map(|candidate| candidate.id).collect(),
+            subject_pattern: pattern,
+        }))
+    }
+}
+
+/// Normalise a subject so that messages from the same template compare equal: lowercased,
+/// reply/forward prefixes removed, digit runs replaced by `#`, whitespace collapsed.
+pub fn subject_pattern(subject: Option<&str>) -> String {
+    let mut rest = subject.unwrap_or("").trim().to_lowercase();
+    loop {
+        let stripped = ["re:", "fw:", "fwd:"]
+            .iter()
+            .find_map(|prefix| rest.strip_prefix(prefix))
+            .map(|value| value.trim_start().to_string());
+        match stripped {
+            Some(value) => rest = value,
+            None => break,
+        }
+    }
+
+    let mut pattern = String::with_capacity(rest.len());
+    let mut last = None;
+    for ch in rest.chars() {
+        let mapped = if ch.is_ascii_digit() {
+            '#'
+        } else if ch.is_whitespace() {
+            ' '
+        } else {
+            ch
+        };
+        if (mapped == '#' || mapped == ' ') && last == Some(mapped) {
+            continue;
+        }
+        pattern.push(mapped);
+        last = Some(mapped);
+    }
+    pattern
+}
+
+/// Stable fingerprint of the directions and LLM rules that shape a prompt.
+///
+/// Uses 64-bit FNV-1a so the value is identical across builds and processes.
+pub fn rules_fingerprint(directions: &[Direction], llm_rules: &[LlmRule]) -> String {
+    let mut parts: Vec<String> = directions
+        .iter()
+        .map(|direction| format!("d:{}:{}", direction.id, direction.content))
+        .chain(llm_rules.iter().map(|rule| {
+            format!(
+                "r:{}:{}:{}",
+                rule.id,
+                rule.description.as_deref().unwrap_or(""),
+                rule.rule_text
+            )
+        }))
+        .collect();
+    parts.sort();
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in parts.join("\n").bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::decisions::{
+        ActionLinkRelationType, ActionLinkRepository, ActionRepository, ActionStatus,
+        DecisionRepository, DecisionSource, NewAction, NewActionLink, NewDecision,
+    };
+    use crate::gmail::OAuthTokens;
+    use crate::llm::decision::{
+        ActionType, DecisionDetails, Explanations, TelemetryPlaceholder, UndoHint,
+    };
+    use crate::messages::{MessageRepository, NewMessage};
+    use crate::migrations::run_migrations;
+    use crate::threads::ThreadRepository;
+    use serde_json::{Value, json};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    const FINGERPRINT: &str = "fp-1";
+
+    struct Fixture {
+        db: Database,
+        account_id: String,
+        thread_id: String,
+        _dir: TempDir,
+    }
+
+    async fn setup() -> Fixture {
+        let dir = TempDir::new().expect("temp dir");
+        let db_name = format!("db_{}.sqlite", Uuid::new_v4());
+        let db = Database::new(&dir.path().join(db_name))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account_id = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account")
+            .id;
+        let thread_id = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thread-1",
+                None,
+                None,
+                Some(Utc::now()),
+                json!({}),
+            )
+            .await
+            .expect("create thread")
+            .id;
+
+        Fixture {
+            db,
+            account_id,
+            thread_id,
+            _dir: dir,
+        }
+    }
+
+    fn cache(fx: &Fixture) -> DecisionCache {
+        DecisionCache::new(
+            fx.db.clone(),
+            DecisionCacheConfig {
+                enabled: true,
+                ..DecisionCacheConfig::default()
+            },
+        )
+    }
+
+    async fn seed_message(fx: &Fixture, from: &str, subject: &str) -> Message {
+        MessageRepository::new(fx.db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fx.account_id.clone(),
+                thread_id: fx.thread_id.clone(),
+                provider_message_id: Uuid::new_v4().to_string(),
+                from_email: Some(from.to_string()),
+                from_name: None,
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some(subject.to_string()),
+                snippet: None,
+                received_at: Some(Utc::now()),
+                internal_date: Some(Utc::now()),
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: None,
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("create message")
+    }
+
+    /// Seed an LLM decision (and its action) for a new message from `from`.
+    async fn seed_decision(
+        fx: &Fixture,
+        from: &str,
+        subject: &str,
+        action: ActionType,
+        confidence: f64,
+    ) -> (String, String) {
+        let message = seed_message(fx, from, subject).await;
+        let output = DecisionOutput {
+            message_ref: MessageRef {
+                provider: "gmail".into(),
+                account_id: fx.account_id.clone(),
+                thread_id: fx.thread_id.clone(),
+                message_id: message.id.clone(),
+            },
+            decision: DecisionDetails {
+                action,
+                parameters: json!({}),
+                confidence,
+                needs_approval: false,
+                rationale: "Newsletter".into(),
+            },
+            explanations: Explanations {
+                salient_features: vec![],
+                matched_directions: vec![],
+                considered_alternatives: vec![],
+            },
+            undo_hint: UndoHint {
+                inverse_action: ActionType::None,
+                inverse_parameters: Value::Null,
+            },
+            telemetry: TelemetryPlaceholder::default(),
+        };
+        let decision = DecisionRepository::new(fx.db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fx.account_id.clone(),
+                message_id: message.id.clone(),
+                source: DecisionSource::Llm,
+                decision_json: serde_json::to_value(&output).unwrap(),
+                action_type: Some(action.as_str().into()),
+                confidence: Some(confidence),
+                needs_approval: false,
+                rationale: None,
+                telemetry_json: json!({ RULES_FINGERPRINT_KEY: FINGERPRINT }),
+            })
+            .await
+            .expect("create decision");
+        let action = ActionRepository::new(fx.db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fx.account_id.clone(),
+                message_id: message.id.clone(),
+                decision_id: Some(decision.id.clone()),
+                action_type: action.as_str().into(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("create action");
+        (decision.id, action.id)
+    }
+
+    #[test]
+    fn subject_pattern_normalises_prefixes_digits_and_whitespace() {
+        assert_eq!(
+            subject_pattern(Some("RE: Fwd:  Order #12345   shipped")),
+            "order # shipped"
+        );
+        assert_eq!(
+            subject_pattern(Some("Order #987 shipped")),
+            subject_pattern(Some("re: order #1 shipped"))
+        );
+        assert_eq!(subject_pattern(None), "");
+    }
+
+    #[test]
+    fn rules_fingerprint_changes_with_rules_but_not_order() {
+        let now = Utc::now();
+        let direction = |id: &str, content: &str| Direction {
+            id: id.into(),
+            org_id: DEFAULT_ORG_ID,
+            user_id: Some(DEFAULT_USER_ID),
+            content: content.into(),
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+        };
+        let a = direction("d1", "Never delete invoices");
+        let b = direction("d2", "Archive newsletters");
+
+        let base = rules_fingerprint(&[a.clone(), b.clone()], &[]);
+        assert_eq!(base, rules_fingerprint(&[b.clone(), a.clone()], &[]));
+        assert_ne!(base, rules_fingerprint(std::slice::from_ref(&a), &[]));
+
+        let edited = direction("d2", "Archive all newsletters");
+        assert_ne!(base, rules_fingerprint(&[a, edited], &[]));
+    }
+
+    #[tokio::test]
+    async fn lookup_hits_when_recent_decisions_agree() {
+        let fx = setup().await;
+        let mut ids = Vec::new();
+        for n in 1..=3 {
+            let subject = format!("Your receipt #{n}");
+            let (id, _) =
+                seed_decision(&fx, "shop@example.com", &subject, ActionType::Archive, 0.95).await;
+            ids.push(id);
+        }
+        // Different subject pattern and different sender do not count
+        seed_decision(&fx, "shop@example.com", "Sale", ActionType::Delete, 0.99).await;
+        seed_decision(&fx, "other@example.com", "Receipt", ActionType::Star, 0.99).await;
+
+        let message = seed_message(&fx, "shop@example.com", "Your receipt #4").await;
+        let hit = cache(&fx)
+            .lookup(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message, FINGERPRINT)
+            .await
+            .expect("lookup")
+            .expect("cache hit");
+        assert_eq!(hit.output.decision.action, ActionType::Archive);
+        assert_eq!(hit.output.message_ref.message_id, message.id);
+        assert_eq!(hit.subject_pattern, "your receipt #");
+        ids.reverse();
+        assert_eq!(hit.source_decision_ids, ids);
+
+        // A different rules fingerprint invalidates every cached decision
+        let miss = cache(&fx)
+            .lookup(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message, "fp-2")
+            .await
+            .expect("lookup");
+        assert!(miss.is_none());
+
+        // Disabled cache never hits
+        let disabled = DecisionCache::new(fx.db.clone(), DecisionCacheConfig::default());
+        assert!(
+            disabled
+                .lookup(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message, FINGERPRINT)
+                .await
+                .expect("lookup")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_matches_sender_case_insensitively() {
+        let fx = setup().await;
+        for sender in ["Shop@Example.com", "shop@example.com", "SHOP@EXAMPLE.COM"] {
+            seed_decision(&fx, sender, "Your receipt", ActionType::Archive, 0.95).await;
+        }
+
+        let message = seed_message(&fx, "shop@Example.COM", "Your receipt").await;
+        let hit = cache(&fx)
+            .lookup(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message, FINGERPRINT)
+            .await
+            .expect("lookup")
+            .expect("cache hit");
+        assert_eq!(hit.source_decision_ids.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn lookup_misses_on_disagreement_low_confidence_or_undo() {
+        let fx = setup().await;
+        let message = seed_message(&fx, "news@example.com", "Weekly digest").await;
+
+        seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::Archive,
+            0.95,
+        )
+        .await;
+        seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::Archive,
+            0.95,
+        )
+        .await;
+        seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::MarkRead,
+            0.95,
+        )
+        .await;
+        let cache = cache(&fx);
+        let lookup = || cache.lookup(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message, FINGERPRINT);
+        assert!(lookup().await.expect("lookup").is_none(), "disagreement");
+
+        // Three newer agreeing decisions take over, but one has low confidence
+        seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::Archive,
+            0.95,
+        )
+        .await;
+        seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::Archive,
+            0.5,
+        )
+        .await;
+        seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::Archive,
+            0.95,
+        )
+        .await;
+        assert!(lookup().await.expect("lookup").is_none(), "low confidence");
+
+        seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::Archive,
+            0.95,
+        )
+        .await;
+        seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::Archive,
+            0.95,
+        )
+        .await;
+        assert!(lookup().await.expect("lookup").is_some());
+
+        // Undoing one of the last three decisions breaks the agreement
+        let (_, newest_action) = seed_decision(
+            &fx,
+            "news@example.com",
+            "Weekly digest",
+            ActionType::Archive,
+            0.95,
+        )
+        .await;
+        let (_, undo_action) =
+            seed_decision(&fx, "user@example.com", "unrelated", ActionType::None, 1.0).await;
+        ActionLinkRepository::new(fx.db.clone())
+            .create(NewActionLink {
+                cause_action_id: undo_action,
+                effect_action_id: newest_action,
+                relation_type: ActionLinkRelationType::UndoOf,
+            })
+            .await
+            .expect("create undo link");
+        assert!(lookup().await.expect("lookup").is_none(), "undone");
+    }
+}
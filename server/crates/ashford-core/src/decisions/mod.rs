@@ -1,14 +1,20 @@
+pub mod cache;
+pub mod direction_check;
 pub mod policy;
 pub mod repositories;
 pub mod safety;
 pub mod types;
 
-pub use policy::{ActionDangerLevel, SafetyOverride, SafetyResult};
+pub use cache::{CachedDecision, DecisionCache, DecisionCacheError};
+pub use direction_check::{DirectionCheck, DirectionVerifier, DirectionViolation, ViolationMethod};
+pub use policy::{
+    ActionDangerLevel, NewPolicyClause, PolicyClause, PolicyEffect, SafetyOverride, SafetyResult,
+};
 pub use repositories::{
     ActionDetailRow, ActionError, ActionLinkError, ActionLinkRepository, ActionListItemRow,
-    ActionRepository, DecisionError, DecisionRepository,
+    ActionRepository, DecisionError, DecisionRepository, PolicyClauseError, PolicyClauseRepository,
 };
-pub use safety::SafetyEnforcer;
+pub use safety::{PolicySubject, SafetyEnforcer};
 pub use types::{
     Action, ActionLink, ActionLinkRelationType, ActionStatus, Decision, DecisionSource, NewAction,
     NewActionLink, NewDecision,
@@ -1,7 +1,10 @@
 //! Safety policy definitions for action danger levels and override tracking.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
+use ts_rs::TS;
 
 /// Classification of how dangerous an action is.
 /// Used to determine approval requirements.
@@ -48,11 +51,71 @@ pub enum SafetyOverride {
         confidence: f64,
         /// The configured threshold that was not met.
         threshold: f32,
+        /// Name of the `min_confidence` policy clause that set the threshold, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        clause: Option<String>,
     },
     /// Action type is in the approval_always configuration list.
     InApprovalAlwaysList,
     /// The LLM explicitly requested approval via needs_approval=true.
     LlmRequestedApproval,
+    /// The decision contradicts an enabled direction.
+    DirectionViolation {
+        /// ID of the contradicted direction.
+        direction_id: String,
+    },
+    /// The message contains text that tries to instruct the model.
+    SuspectedPromptInjection {
+        /// Names of the matching injection patterns.
+        patterns: Vec<String>,
+    },
+    /// An outbound action targets an address found only in the message content.
+    UntrustedRecipient {
+        /// The blocked recipient address.
+        address: String,
+    },
+    /// A `require_approval` policy clause matched the decision.
+    PolicyClause {
+        /// ID of the matching clause.
+        clause_id: String,
+        /// Name of the matching clause.
+        clause: String,
+    },
+    /// A `protect` policy clause covers the message and the action is not labeling.
+    ProtectedMessage {
+        /// ID of the matching clause.
+        clause_id: String,
+        /// Name of the matching clause.
+        clause: String,
+    },
+    /// A tripped circuit breaker holds the account's or rule's tracked actions.
+    CircuitBreakerOpen {
+        /// ID of the open trip.
+        trip_id: String,
+        /// What the trip paused, e.g. `account` or `rule 'Bulk cleanup'`.
+        target: String,
+    },
+}
+
+impl SafetyOverride {
+    /// Name of the policy clause that produced this override. Stored clauses are named by
+    /// their `name`; built-in checks use the config key or check they come from.
+    pub fn clause(&self) -> &str {
+        match self {
+            SafetyOverride::DangerousAction => "danger_level",
+            SafetyOverride::LowConfidence { clause, .. } => {
+                clause.as_deref().unwrap_or("confidence_default")
+            }
+            SafetyOverride::InApprovalAlwaysList => "approval_always",
+            SafetyOverride::LlmRequestedApproval => "llm_advisory",
+            SafetyOverride::DirectionViolation { .. } => "direction_check",
+            SafetyOverride::SuspectedPromptInjection { .. } => "prompt_injection",
+            SafetyOverride::UntrustedRecipient { .. } => "untrusted_recipient",
+            SafetyOverride::PolicyClause { clause, .. }
+            | SafetyOverride::ProtectedMessage { clause, .. } => clause,
+            SafetyOverride::CircuitBreakerOpen { .. } => "circuit_breaker",
+        }
+    }
 }
 
 impl fmt::Display for SafetyOverride {
@@ -64,12 +127,17 @@ impl fmt::Display for SafetyOverride {
             SafetyOverride::LowConfidence {
                 confidence,
                 threshold,
+                clause,
             } => {
                 write!(
                     f,
                     "confidence {:.2} is below threshold {:.2}",
                     confidence, threshold
-                )
+                )?;
+                if let Some(clause) = clause {
+                    write!(f, " set by policy clause '{}'", clause)?;
+                }
+                Ok(())
             }
             SafetyOverride::InApprovalAlwaysList => {
                 write!(f, "action type is in approval_always list")
@@ -77,6 +145,32 @@ impl fmt::Display for SafetyOverride {
             SafetyOverride::LlmRequestedApproval => {
                 write!(f, "LLM explicitly requested approval")
             }
+            SafetyOverride::DirectionViolation { direction_id } => {
+                write!(f, "decision contradicts direction {}", direction_id)
+            }
+            SafetyOverride::SuspectedPromptInjection { patterns } => {
+                write!(
+                    f,
+                    "message content looks like injected instructions ({})",
+                    patterns.join(", ")
+                )
+            }
+            SafetyOverride::UntrustedRecipient { address } => {
+                write!(
+                    f,
+                    "recipient {} appears only in the message content",
+                    address
+                )
+            }
+            SafetyOverride::PolicyClause { clause, .. } => {
+                write!(f, "policy clause '{}' requires approval", clause)
+            }
+            SafetyOverride::ProtectedMessage { clause, .. } => {
+                write!(f, "message is protected by policy clause '{}'", clause)
+            }
+            SafetyOverride::CircuitBreakerOpen { target, .. } => {
+                write!(f, "circuit breaker is open for {}", target)
+            }
         }
     }
 }
@@ -90,6 +184,12 @@ pub struct SafetyResult {
     /// Final determination of whether approval is required.
     /// True if any override was applied (OR logic).
     pub requires_approval: bool,
+    /// Overrides dropped by a `skip_approval` policy clause.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waived: Vec<SafetyOverride>,
+    /// Name of the `skip_approval` clause that waived them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waived_by: Option<String>,
 }
 
 impl SafetyResult {
@@ -100,6 +200,8 @@ impl SafetyResult {
         Self {
             overrides_applied: overrides,
             requires_approval,
+            waived: Vec::new(),
+            waived_by: None,
         }
     }
 
@@ -108,18 +210,33 @@ impl SafetyResult {
         Self {
             overrides_applied: Vec::new(),
             requires_approval: false,
+            waived: Vec::new(),
+            waived_by: None,
         }
     }
 
     /// Convert the safety result to a JSON value for telemetry storage.
     pub fn to_telemetry_json(&self) -> serde_json::Value {
-        serde_json::json!({
+        let mut json = serde_json::json!({
             "safety_overrides": self.overrides_applied.iter()
                 .map(|o| o.to_string())
                 .collect::<Vec<_>>(),
             "requires_approval": self.requires_approval,
             "override_details": self.overrides_applied,
-        })
+            "policy_clauses": self.overrides_applied.iter()
+                .map(|o| o.clause())
+                .collect::<Vec<_>>(),
+        });
+        if let Some(waived_by) = &self.waived_by {
+            json["waived_by"] = serde_json::json!(waived_by);
+            json["waived_overrides"] = serde_json::json!(
+                self.waived
+                    .iter()
+                    .map(|o| o.to_string())
+                    .collect::<Vec<_>>()
+            );
+        }
+        json
     }
 }
 
@@ -129,6 +246,94 @@ impl Default for SafetyResult {
     }
 }
 
+/// What a policy clause does to the decisions it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum PolicyEffect {
+    /// Always require approval. Cannot be waived by `skip_approval`.
+    RequireApproval,
+    /// Waive the low-confidence and `approval_always` checks. Dangerous actions and the
+    /// LLM's own approval request still apply.
+    SkipApproval,
+    /// Replace `confidence_default` with `min_confidence`.
+    MinConfidence,
+    /// Require approval for every action except labeling. Also matches labels elsewhere in
+    /// the thread, cannot be waived, and applies to rules with a `safe_mode` override.
+    Protect,
+}
+
+impl PolicyEffect {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyEffect::RequireApproval => "require_approval",
+            PolicyEffect::SkipApproval => "skip_approval",
+            PolicyEffect::MinConfidence => "min_confidence",
+            PolicyEffect::Protect => "protect",
+        }
+    }
+}
+
+impl FromStr for PolicyEffect {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "require_approval" => Ok(Self::RequireApproval),
+            "skip_approval" => Ok(Self::SkipApproval),
+            "min_confidence" => Ok(Self::MinConfidence),
+            "protect" => Ok(Self::Protect),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A stored safety policy clause, applied by the `SafetyEnforcer` on top of `PolicyConfig`.
+///
+/// A clause matches a decision when every non-empty filter matches: its account, one of
+/// its action types, one of its senders, and one of its labels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PolicyClause {
+    pub id: String,
+    #[ts(type = "number")]
+    pub org_id: i64,
+    #[ts(type = "number | null")]
+    pub user_id: Option<i64>,
+    pub name: String,
+    pub description: Option<String>,
+    /// Limits the clause to one account. Account clauses take precedence over global
+    /// clauses when both set a confidence threshold.
+    pub account_id: Option<String>,
+    pub effect: PolicyEffect,
+    /// Threshold used by `min_confidence` clauses.
+    pub min_confidence: Option<f64>,
+    /// Action types (snake_case) the clause covers. Empty covers every action.
+    pub action_types: Vec<String>,
+    /// Sender addresses, `*@domain` wildcards, or bare domains. Empty covers every sender.
+    pub senders: Vec<String>,
+    /// Label names or IDs the message must carry. Empty covers every message.
+    pub labels: Vec<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewPolicyClause {
+    pub org_id: i64,
+    pub user_id: Option<i64>,
+    pub name: String,
+    pub description: Option<String>,
+    pub account_id: Option<String>,
+    pub effect: PolicyEffect,
+    pub min_confidence: Option<f64>,
+    pub action_types: Vec<String>,
+    pub senders: Vec<String>,
+    pub labels: Vec<String>,
+    pub enabled: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,7 +362,8 @@ mod tests {
         assert_eq!(
             SafetyOverride::LowConfidence {
                 confidence: 0.45,
-                threshold: 0.7
+                threshold: 0.7,
+                clause: None,
             }
             .to_string(),
             "confidence 0.45 is below threshold 0.70"
@@ -172,6 +378,50 @@ mod tests {
             SafetyOverride::LlmRequestedApproval.to_string(),
             "LLM explicitly requested approval"
         );
+
+        assert_eq!(
+            SafetyOverride::DirectionViolation {
+                direction_id: "dir_1".into()
+            }
+            .to_string(),
+            "decision contradicts direction dir_1"
+        );
+
+        assert_eq!(
+            SafetyOverride::SuspectedPromptInjection {
+                patterns: vec!["ignore_instructions".into(), "tool_reference".into()]
+            }
+            .to_string(),
+            "message content looks like injected instructions (ignore_instructions, tool_reference)"
+        );
+
+        assert_eq!(
+            SafetyOverride::UntrustedRecipient {
+                address: "x@evil.test".into()
+            }
+            .to_string(),
+            "recipient x@evil.test appears only in the message content"
+        );
+
+        let breaker = SafetyOverride::CircuitBreakerOpen {
+            trip_id: "cb_1".into(),
+            target: "rule 'Bulk cleanup'".into(),
+        };
+        assert_eq!(
+            breaker.to_string(),
+            "circuit breaker is open for rule 'Bulk cleanup'"
+        );
+        assert_eq!(breaker.clause(), "circuit_breaker");
+
+        let protected = SafetyOverride::ProtectedMessage {
+            clause_id: "pc_2".into(),
+            clause: "family".into(),
+        };
+        assert_eq!(
+            protected.to_string(),
+            "message is protected by policy clause 'family'"
+        );
+        assert_eq!(protected.clause(), "family");
     }
 
     #[test]
@@ -199,6 +449,7 @@ mod tests {
             SafetyOverride::LowConfidence {
                 confidence: 0.5,
                 threshold: 0.7,
+                clause: None,
             },
         ]);
 
@@ -225,6 +476,7 @@ mod tests {
         let low_conf = SafetyOverride::LowConfidence {
             confidence: 0.5,
             threshold: 0.7,
+            clause: None,
         };
         let low_conf_json = serde_json::to_value(&low_conf).unwrap();
         assert_eq!(low_conf_json["type"], "low_confidence");
@@ -232,5 +484,69 @@ mod tests {
         // f32 threshold may have precision differences when serialized
         let threshold = low_conf_json["threshold"].as_f64().unwrap();
         assert!((threshold - 0.7).abs() < 0.001, "threshold should be ~0.7");
+
+        let violation_json = serde_json::to_value(&SafetyOverride::DirectionViolation {
+            direction_id: "dir_1".into(),
+        })
+        .unwrap();
+        assert_eq!(violation_json["type"], "direction_violation");
+        assert_eq!(violation_json["direction_id"], "dir_1");
+
+        let recipient_json = serde_json::to_value(&SafetyOverride::UntrustedRecipient {
+            address: "x@evil.test".into(),
+        })
+        .unwrap();
+        assert_eq!(recipient_json["type"], "untrusted_recipient");
+        assert_eq!(recipient_json["address"], "x@evil.test");
+    }
+
+    #[test]
+    fn safety_override_names_policy_clause() {
+        assert_eq!(SafetyOverride::DangerousAction.clause(), "danger_level");
+        assert_eq!(
+            SafetyOverride::InApprovalAlwaysList.clause(),
+            "approval_always"
+        );
+
+        let low_conf = SafetyOverride::LowConfidence {
+            confidence: 0.8,
+            threshold: 0.9,
+            clause: Some("strict trash".into()),
+        };
+        assert_eq!(low_conf.clause(), "strict trash");
+        assert_eq!(
+            low_conf.to_string(),
+            "confidence 0.80 is below threshold 0.90 set by policy clause 'strict trash'"
+        );
+
+        let clause = SafetyOverride::PolicyClause {
+            clause_id: "pc_1".into(),
+            clause: "protect finance".into(),
+        };
+        assert_eq!(clause.clause(), "protect finance");
+        assert_eq!(
+            clause.to_string(),
+            "policy clause 'protect finance' requires approval"
+        );
+        let json = serde_json::to_value(&clause).unwrap();
+        assert_eq!(json["type"], "policy_clause");
+        assert_eq!(json["clause_id"], "pc_1");
+
+        let result = SafetyResult::new(vec![SafetyOverride::DangerousAction, clause]);
+        assert_eq!(
+            result.to_telemetry_json()["policy_clauses"],
+            serde_json::json!(["danger_level", "protect finance"])
+        );
+    }
+
+    #[test]
+    fn low_confidence_without_clause_deserializes() {
+        let parsed: SafetyOverride = serde_json::from_value(serde_json::json!({
+            "type": "low_confidence",
+            "confidence": 0.5,
+            "threshold": 0.7
+        }))
+        .unwrap();
+        assert_eq!(parsed.clause(), "confidence_default");
     }
 }
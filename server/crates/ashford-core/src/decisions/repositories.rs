@@ -6,6 +6,7 @@ use uuid::Uuid;
 
 use crate::db::{Database, DbError};
 
+use super::policy::{NewPolicyClause, PolicyClause, PolicyEffect};
 use super::types::{
     Action, ActionLink, ActionLinkRelationType, ActionStatus, Decision, DecisionSource, NewAction,
     NewActionLink, NewDecision,
@@ -14,6 +15,7 @@ use super::types::{
 const DECISION_COLUMNS: &str = "id, account_id, message_id, source, decision_json, action_type, confidence, needs_approval, rationale, telemetry_json, created_at, updated_at, org_id, user_id";
 const ACTION_COLUMNS: &str = "id, account_id, message_id, decision_id, action_type, parameters_json, status, error_message, executed_at, undo_hint_json, trace_id, created_at, updated_at, org_id, user_id";
 const ACTION_LINK_COLUMNS: &str = "id, cause_action_id, effect_action_id, relation_type";
+const POLICY_CLAUSE_COLUMNS: &str = "id, name, description, account_id, effect, min_confidence, action_types_json, senders_json, labels_json, enabled, created_at, updated_at, org_id, user_id";
 const RECENT_DECISION_LIMIT: i64 = 50;
 
 #[derive(Debug, Error)]
@@ -67,6 +69,26 @@ pub enum ActionLinkError {
     InvalidRelationType(String),
 }
 
+#[derive(Debug, Error)]
+pub enum PolicyClauseError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("datetime parse error: {0}")]
+    DateTimeParse(#[from] chrono::ParseError),
+    #[error("policy clause not found: {0}")]
+    NotFound(String),
+    #[error("policy clause already exists: {0}")]
+    DuplicateName(String),
+    #[error("invalid policy clause: {0}")]
+    Invalid(String),
+    #[error("invalid effect value {0}")]
+    InvalidEffect(String),
+}
+
 #[derive(Clone)]
 pub struct DecisionRepository {
     db: Database,
@@ -440,6 +462,53 @@ impl ActionRepository {
         }
     }
 
+    /// Move the action to `next_status` only while it is still in `expected`.
+    /// Returns `None` when another writer changed its status first.
+    pub async fn update_status_if(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+        expected: ActionStatus,
+        next_status: ActionStatus,
+        error_message: Option<String>,
+    ) -> Result<Option<Action>, ActionError> {
+        if !is_valid_transition(&expected, &next_status) {
+            return Err(ActionError::InvalidStatusTransition {
+                from: expected,
+                to: next_status,
+            });
+        }
+
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "UPDATE actions
+                     SET status = ?1,
+                         error_message = ?2,
+                         updated_at = ?3
+                     WHERE id = ?4 AND status = ?5 AND org_id = ?6 AND user_id = ?7
+                     RETURNING {ACTION_COLUMNS}"
+                ),
+                params![
+                    next_status.as_str(),
+                    error_message,
+                    now_rfc3339(),
+                    id,
+                    expected.as_str(),
+                    org_id,
+                    user_id,
+                ],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(row_to_action(row)?)),
+            None => Ok(None),
+        }
+    }
+
     pub async fn mark_executing(
         &self,
         org_id: i64,
@@ -1122,6 +1191,287 @@ impl ActionLinkRepository {
     }
 }
 
+#[derive(Clone)]
+pub struct PolicyClauseRepository {
+    db: Database,
+}
+
+impl PolicyClauseRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(
+        &self,
+        new_clause: NewPolicyClause,
+    ) -> Result<PolicyClause, PolicyClauseError> {
+        let clause = normalize_policy_clause(new_clause)?;
+        let id = Uuid::new_v4().to_string();
+        let now = now_rfc3339();
+
+        let conn = self.db.connection().await?;
+        let result = conn
+            .query(
+                &format!(
+                    "INSERT INTO policy_clauses (id, name, description, account_id, effect, min_confidence, action_types_json, senders_json, labels_json, enabled, created_at, updated_at, org_id, user_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11, ?12, ?13)
+                     RETURNING {POLICY_CLAUSE_COLUMNS}"
+                ),
+                params![
+                    id,
+                    clause.name.as_str(),
+                    clause.description,
+                    clause.account_id,
+                    clause.effect.as_str(),
+                    clause.min_confidence,
+                    serde_json::to_string(&clause.action_types)?,
+                    serde_json::to_string(&clause.senders)?,
+                    serde_json::to_string(&clause.labels)?,
+                    clause.enabled as i64,
+                    now,
+                    clause.org_id,
+                    clause.user_id
+                ],
+            )
+            .await;
+
+        let mut rows = result.map_err(|err| policy_clause_write_error(err, &clause.name))?;
+        match rows
+            .next()
+            .await
+            .map_err(|err| policy_clause_write_error(err, &clause.name))?
+        {
+            Some(row) => row_to_policy_clause(row),
+            None => Err(PolicyClauseError::NotFound("insert failed".into())),
+        }
+    }
+
+    pub async fn get_by_id(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<PolicyClause, PolicyClauseError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {POLICY_CLAUSE_COLUMNS}
+                     FROM policy_clauses
+                     WHERE id = ?1
+                       AND org_id = ?2
+                       AND (user_id IS NULL OR user_id = ?3)"
+                ),
+                params![id, org_id, user_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_policy_clause(row),
+            None => Err(PolicyClauseError::NotFound(id.to_string())),
+        }
+    }
+
+    pub async fn list_all(
+        &self,
+        org_id: i64,
+        user_id: i64,
+    ) -> Result<Vec<PolicyClause>, PolicyClauseError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {POLICY_CLAUSE_COLUMNS}
+                     FROM policy_clauses
+                     WHERE org_id = ?1 AND (user_id IS NULL OR user_id = ?2)
+                     ORDER BY name"
+                ),
+                params![org_id, user_id],
+            )
+            .await?;
+
+        let mut clauses = Vec::new();
+        while let Some(row) = rows.next().await? {
+            clauses.push(row_to_policy_clause(row)?);
+        }
+        Ok(clauses)
+    }
+
+    /// Enabled clauses that apply to an account: global clauses and the account's own.
+    pub async fn list_enabled_for_account(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+    ) -> Result<Vec<PolicyClause>, PolicyClauseError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {POLICY_CLAUSE_COLUMNS}
+                     FROM policy_clauses
+                     WHERE org_id = ?1 AND (user_id IS NULL OR user_id = ?2)
+                       AND enabled = 1
+                       AND (account_id IS NULL OR account_id = ?3)
+                     ORDER BY name"
+                ),
+                params![org_id, user_id, account_id],
+            )
+            .await?;
+
+        let mut clauses = Vec::new();
+        while let Some(row) = rows.next().await? {
+            clauses.push(row_to_policy_clause(row)?);
+        }
+        Ok(clauses)
+    }
+
+    pub async fn update(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+        updated: NewPolicyClause,
+    ) -> Result<PolicyClause, PolicyClauseError> {
+        let clause = normalize_policy_clause(updated)?;
+        let now = now_rfc3339();
+        let conn = self.db.connection().await?;
+        let result = conn
+            .query(
+                &format!(
+                    "UPDATE policy_clauses
+                     SET name = ?1,
+                         description = ?2,
+                         account_id = ?3,
+                         effect = ?4,
+                         min_confidence = ?5,
+                         action_types_json = ?6,
+                         senders_json = ?7,
+                         labels_json = ?8,
+                         enabled = ?9,
+                         updated_at = ?10
+                     WHERE id = ?11
+                       AND org_id = ?12
+                       AND (user_id IS NULL OR user_id = ?13)
+                     RETURNING {POLICY_CLAUSE_COLUMNS}"
+                ),
+                params![
+                    clause.name.as_str(),
+                    clause.description,
+                    clause.account_id,
+                    clause.effect.as_str(),
+                    clause.min_confidence,
+                    serde_json::to_string(&clause.action_types)?,
+                    serde_json::to_string(&clause.senders)?,
+                    serde_json::to_string(&clause.labels)?,
+                    clause.enabled as i64,
+                    now,
+                    id,
+                    org_id,
+                    user_id
+                ],
+            )
+            .await;
+
+        let mut rows = result.map_err(|err| policy_clause_write_error(err, &clause.name))?;
+        match rows
+            .next()
+            .await
+            .map_err(|err| policy_clause_write_error(err, &clause.name))?
+        {
+            Some(row) => row_to_policy_clause(row),
+            None => Err(PolicyClauseError::NotFound(id.to_string())),
+        }
+    }
+
+    pub async fn delete(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<(), PolicyClauseError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "DELETE FROM policy_clauses WHERE id = ?1 AND org_id = ?2 AND (user_id IS NULL OR user_id = ?3) RETURNING id",
+                params![id, org_id, user_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(_) => Ok(()),
+            None => Err(PolicyClauseError::NotFound(id.to_string())),
+        }
+    }
+}
+
+/// Validate a clause and normalize its filters: trimmed name, known action types,
+/// lowercased senders, and no blank or duplicate entries.
+fn normalize_policy_clause(
+    mut clause: NewPolicyClause,
+) -> Result<NewPolicyClause, PolicyClauseError> {
+    clause.name = clause.name.trim().to_string();
+    if clause.name.is_empty() {
+        return Err(PolicyClauseError::Invalid("name is required".into()));
+    }
+
+    match (clause.effect, clause.min_confidence) {
+        (PolicyEffect::MinConfidence, Some(min)) if (0.0..=1.0).contains(&min) => {}
+        (PolicyEffect::MinConfidence, _) => {
+            return Err(PolicyClauseError::Invalid(
+                "min_confidence clauses need a min_confidence between 0.0 and 1.0".into(),
+            ));
+        }
+        _ => clause.min_confidence = None,
+    }
+    if clause.effect == PolicyEffect::Protect {
+        // Protection covers every action except labeling
+        clause.action_types.clear();
+    }
+
+    let dedup = |entries: Vec<String>, lowercase: bool| {
+        let mut normalized: Vec<String> = Vec::new();
+        for entry in entries {
+            let entry = entry.trim();
+            let entry = if lowercase {
+                entry.to_lowercase()
+            } else {
+                entry.to_string()
+            };
+            if !entry.is_empty() && !normalized.iter().any(|e| e.eq_ignore_ascii_case(&entry)) {
+                normalized.push(entry);
+            }
+        }
+        normalized
+    };
+    clause.action_types = dedup(clause.action_types, true);
+    if let Some(unknown) = clause
+        .action_types
+        .iter()
+        .find(|a| a.parse::<crate::llm::decision::ActionType>().is_err())
+    {
+        return Err(PolicyClauseError::Invalid(format!(
+            "unknown action type: {unknown}"
+        )));
+    }
+    clause.senders = dedup(clause.senders, true);
+    clause.labels = dedup(clause.labels, false);
+    Ok(clause)
+}
+
+/// Map a write error, turning unique name violations into `DuplicateName`.
+fn policy_clause_write_error(err: libsql::Error, name: &str) -> PolicyClauseError {
+    if err
+        .to_string()
+        .to_ascii_lowercase()
+        .contains("unique constraint failed")
+    {
+        PolicyClauseError::DuplicateName(name.to_string())
+    } else {
+        PolicyClauseError::Sql(err)
+    }
+}
+
 fn is_valid_transition(current: &ActionStatus, next: &ActionStatus) -> bool {
     use ActionStatus::*;
     match current {
@@ -1130,7 +1480,8 @@ fn is_valid_transition(current: &ActionStatus, next: &ActionStatus) -> bool {
             Executing | Canceled | Rejected | ApprovedPending | Failed
         ),
         Executing => matches!(next, Completed | Failed | Canceled),
-        ApprovedPending => matches!(next, Queued | Canceled | Rejected),
+        ApprovedPending => matches!(next, Queued | PendingExecution | Canceled | Rejected),
+        PendingExecution => matches!(next, Executing | Canceled | Failed),
         Completed | Failed | Canceled | Rejected => false,
     }
 }
@@ -1138,7 +1489,10 @@ fn is_valid_transition(current: &ActionStatus, next: &ActionStatus) -> bool {
 fn is_valid_initial_status(status: &ActionStatus) -> bool {
     matches!(
         status,
-        ActionStatus::Queued | ActionStatus::Executing | ActionStatus::ApprovedPending
+        ActionStatus::Queued
+            | ActionStatus::Executing
+            | ActionStatus::ApprovedPending
+            | ActionStatus::PendingExecution
     )
 }
 
@@ -1229,6 +1583,37 @@ fn row_to_action_link(row: Row) -> Result<ActionLink, ActionLinkError> {
     })
 }
 
+fn row_to_policy_clause(row: Row) -> Result<PolicyClause, PolicyClauseError> {
+    let effect: String = row.get(4)?;
+    let action_types_json: String = row.get(6)?;
+    let senders_json: String = row.get(7)?;
+    let labels_json: String = row.get(8)?;
+    let enabled: i64 = row.get(9)?;
+    let created_at: String = row.get(10)?;
+    let updated_at: String = row.get(11)?;
+
+    let effect = effect
+        .parse::<PolicyEffect>()
+        .map_err(|_| PolicyClauseError::InvalidEffect(effect.clone()))?;
+
+    Ok(PolicyClause {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        account_id: row.get(3)?,
+        effect,
+        min_confidence: row.get(5)?,
+        action_types: serde_json::from_str(&action_types_json)?,
+        senders: serde_json::from_str(&senders_json)?,
+        labels: serde_json::from_str(&labels_json)?,
+        enabled: enabled != 0,
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+        org_id: row.get(12)?,
+        user_id: row.get(13)?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2404,4 +2789,164 @@ mod tests {
             .expect_err("queued -> completed should be rejected");
         assert!(matches!(err, ActionError::InvalidStatusTransition { .. }));
     }
+
+    fn sample_new_policy_clause(name: &str, effect: PolicyEffect) -> NewPolicyClause {
+        NewPolicyClause {
+            org_id: DEFAULT_ORG_ID,
+            user_id: Some(DEFAULT_USER_ID),
+            name: name.to_string(),
+            description: None,
+            account_id: None,
+            effect,
+            min_confidence: None,
+            action_types: vec![],
+            senders: vec![],
+            labels: vec![],
+            enabled: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn policy_clause_create_normalizes_filters() {
+        let (db, _dir) = setup_db().await;
+        let repo = PolicyClauseRepository::new(db);
+
+        let clause = repo
+            .create(NewPolicyClause {
+                action_types: vec!["Trash".into(), "trash".into(), "delete".into()],
+                senders: vec![" Boss@Corp.com ".into(), "".into()],
+                labels: vec!["Finance".into(), "finance".into()],
+                min_confidence: Some(0.5),
+                ..sample_new_policy_clause(" protect finance ", PolicyEffect::RequireApproval)
+            })
+            .await
+            .expect("create clause");
+
+        assert_eq!(clause.name, "protect finance");
+        assert_eq!(clause.action_types, vec!["trash", "delete"]);
+        assert_eq!(clause.senders, vec!["boss@corp.com"]);
+        assert_eq!(clause.labels, vec!["Finance"]);
+        // Only min_confidence clauses keep a threshold
+        assert_eq!(clause.min_confidence, None);
+
+        let fetched = repo
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &clause.id)
+            .await
+            .expect("get clause");
+        assert_eq!(fetched, clause);
+
+        let err = repo
+            .create(sample_new_policy_clause(
+                "PROTECT FINANCE",
+                PolicyEffect::SkipApproval,
+            ))
+            .await
+            .expect_err("duplicate name");
+        assert!(matches!(err, PolicyClauseError::DuplicateName(_)));
+
+        // Protection always covers every non-label action
+        let protect = repo
+            .create(NewPolicyClause {
+                action_types: vec!["archive".into()],
+                senders: vec!["mom@family.example".into()],
+                ..sample_new_policy_clause("family", PolicyEffect::Protect)
+            })
+            .await
+            .expect("create protect clause");
+        assert_eq!(protect.effect, PolicyEffect::Protect);
+        assert!(protect.action_types.is_empty());
+    }
+
+    #[tokio::test]
+    async fn policy_clause_rejects_invalid_clauses() {
+        let (db, _dir) = setup_db().await;
+        let repo = PolicyClauseRepository::new(db);
+
+        for invalid in [
+            sample_new_policy_clause("  ", PolicyEffect::SkipApproval),
+            sample_new_policy_clause("no threshold", PolicyEffect::MinConfidence),
+            NewPolicyClause {
+                min_confidence: Some(1.5),
+                ..sample_new_policy_clause("too high", PolicyEffect::MinConfidence)
+            },
+            NewPolicyClause {
+                action_types: vec!["shred".into()],
+                ..sample_new_policy_clause("unknown action", PolicyEffect::RequireApproval)
+            },
+        ] {
+            let err = repo.create(invalid).await.expect_err("invalid clause");
+            assert!(matches!(err, PolicyClauseError::Invalid(_)), "got {err:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn policy_clause_list_enabled_for_account_includes_global_clauses() {
+        let (db, _dir) = setup_db().await;
+        let account_a = seed_account(&db).await;
+        let account_b = seed_account_with_email(&db, "other@example.com").await;
+        let repo = PolicyClauseRepository::new(db);
+
+        repo.create(sample_new_policy_clause(
+            "global",
+            PolicyEffect::SkipApproval,
+        ))
+        .await
+        .expect("global");
+        repo.create(NewPolicyClause {
+            account_id: Some(account_a.clone()),
+            ..sample_new_policy_clause("account a", PolicyEffect::RequireApproval)
+        })
+        .await
+        .expect("account a");
+        let disabled = repo
+            .create(NewPolicyClause {
+                enabled: false,
+                ..sample_new_policy_clause("disabled", PolicyEffect::RequireApproval)
+            })
+            .await
+            .expect("disabled");
+
+        let names = |clauses: Vec<PolicyClause>| {
+            clauses
+                .into_iter()
+                .map(|clause| clause.name)
+                .collect::<Vec<_>>()
+        };
+        let for_a = repo
+            .list_enabled_for_account(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_a)
+            .await
+            .expect("list a");
+        assert_eq!(names(for_a), vec!["account a", "global"]);
+        let for_b = repo
+            .list_enabled_for_account(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_b)
+            .await
+            .expect("list b");
+        assert_eq!(names(for_b), vec!["global"]);
+
+        let mut update = sample_new_policy_clause("disabled", PolicyEffect::MinConfidence);
+        update.min_confidence = Some(0.8);
+        let updated = repo
+            .update(DEFAULT_ORG_ID, DEFAULT_USER_ID, &disabled.id, update)
+            .await
+            .expect("update");
+        assert!(updated.enabled);
+        assert_eq!(updated.effect, PolicyEffect::MinConfidence);
+        assert_eq!(updated.min_confidence, Some(0.8));
+
+        repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &disabled.id)
+            .await
+            .expect("delete");
+        let err = repo
+            .delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &disabled.id)
+            .await
+            .expect_err("already deleted");
+        assert!(matches!(err, PolicyClauseError::NotFound(_)));
+        assert_eq!(
+            repo.list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+                .await
+                .expect("list all")
+                .len(),
+            2
+        );
+    }
 }
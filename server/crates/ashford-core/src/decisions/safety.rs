@@ -5,8 +5,20 @@
 
 use crate::config::PolicyConfig;
 use crate::llm::decision::{ActionType, DecisionOutput};
-
-use super::policy::{SafetyOverride, SafetyResult};
+use crate::rules::conditions::matches_sender_list_entry;
+
+use super::policy::{PolicyClause, PolicyEffect, SafetyOverride, SafetyResult};
+
+/// Facts about the message a decision is for, matched against policy clause filters.
+#[derive(Debug, Clone, Default)]
+pub struct PolicySubject {
+    pub account_id: String,
+    pub sender_email: Option<String>,
+    /// Label IDs and names on the message.
+    pub labels: Vec<String>,
+    /// Label IDs and names on other messages of the thread, matched by `protect` clauses only.
+    pub thread_labels: Vec<String>,
+}
 
 /// Enforces safety policies on LLM decisions.
 ///
@@ -15,37 +27,67 @@ use super::policy::{SafetyOverride, SafetyResult};
 #[derive(Debug, Clone)]
 pub struct SafetyEnforcer {
     policy: PolicyConfig,
+    clauses: Vec<PolicyClause>,
 }
 
 impl SafetyEnforcer {
     /// Create a new SafetyEnforcer with the given policy configuration.
     pub fn new(policy: PolicyConfig) -> Self {
-        Self { policy }
+        Self {
+            policy,
+            clauses: Vec::new(),
+        }
+    }
+
+    /// Apply stored policy clauses on top of the configuration. Disabled clauses are ignored.
+    pub fn with_clauses(mut self, clauses: Vec<PolicyClause>) -> Self {
+        self.clauses = clauses.into_iter().filter(|c| c.enabled).collect();
+        self
     }
 
     /// Enforce safety policies on a decision.
     ///
+    /// Only clauses without account, sender, or label filters can match, since there is no
+    /// message to match them against. Use [`enforce_for`](Self::enforce_for) when there is.
+    pub fn enforce(&self, decision: &DecisionOutput) -> SafetyResult {
+        self.enforce_for(decision, &PolicySubject::default())
+    }
+
+    /// Enforce safety policies on a decision for a message.
+    ///
     /// Checks all policy conditions and returns a SafetyResult indicating
     /// whether approval is required and why.
     ///
     /// The following conditions are checked (OR logic - any triggers approval):
     /// 1. Action is classified as Dangerous
-    /// 2. Confidence is below the configured threshold
+    /// 2. Confidence is below the threshold (a `min_confidence` clause or the default)
     /// 3. Action type is in the approval_always list
     /// 4. LLM explicitly requested approval (needs_approval = true)
-    pub fn enforce(&self, decision: &DecisionOutput) -> SafetyResult {
+    /// 5. A `require_approval` clause matches
+    /// 6. A `protect` clause matches and the action is not labeling
+    ///
+    /// A matching `skip_approval` clause then waives checks 2 and 3.
+    pub fn enforce_for(&self, decision: &DecisionOutput, subject: &PolicySubject) -> SafetyResult {
+        let action = decision.decision.action;
+        let matching: Vec<&PolicyClause> = self
+            .clauses
+            .iter()
+            .filter(|clause| clause_matches(clause, action, subject))
+            .collect();
         let mut overrides = Vec::new();
 
         // Check each condition and collect all applicable overrides
-        if let Some(override_reason) = self.check_danger_level(decision.decision.action) {
+        if let Some(override_reason) = self.check_danger_level(action) {
             overrides.push(override_reason);
         }
 
-        if let Some(override_reason) = self.check_confidence(decision.decision.confidence) {
+        if let Some(override_reason) =
+            self.check_confidence(decision.decision.confidence, &matching)
+        {
             overrides.push(override_reason);
         }
 
-        if let Some(override_reason) = self.check_approval_always(decision.decision.action) {
+        if let Some(override_reason) = self.check_approval_always(action) {
             overrides.push(override_reason);
         }
 
@@ -53,7 +95,53 @@ impl SafetyEnforcer {
             overrides.push(override_reason);
         }
 
-        SafetyResult::new(overrides)
+        overrides.extend(
+            matching
+                .iter()
+                .filter(|clause| clause.effect == PolicyEffect::RequireApproval)
+                .map(|clause| SafetyOverride::PolicyClause {
+                    clause_id: clause.id.clone(),
+                    clause: clause.name.clone(),
+                }),
+        );
+
+        overrides.extend(protected_overrides(action, &matching));
+
+        let skip = matching
+            .iter()
+            .find(|clause| clause.effect == PolicyEffect::SkipApproval);
+        let (waived, kept): (Vec<_>, Vec<_>) = overrides.into_iter().partition(|o| {
+            skip.is_some()
+                && matches!(
+                    o,
+                    SafetyOverride::LowConfidence { .. } | SafetyOverride::InApprovalAlwaysList
+                )
+        });
+
+        let mut result = SafetyResult::new(kept);
+        if let Some(skip) = skip.filter(|_| !waived.is_empty()) {
+            result.waived = waived;
+            result.waived_by = Some(skip.name.clone());
+        }
+        result
+    }
+
+    /// Overrides from matching `protect` clauses only.
+    ///
+    /// Used for rules whose `safe_mode` skips the rest of enforcement: protection applies to
+    /// them all the same.
+    pub fn protected_overrides(
+        &self,
+        decision: &DecisionOutput,
+        subject: &PolicySubject,
+    ) -> Vec<SafetyOverride> {
+        let action = decision.decision.action;
+        let matching: Vec<&PolicyClause> = self
+            .clauses
+            .iter()
+            .filter(|clause| clause_matches(clause, action, subject))
+            .collect();
+        protected_overrides(action, &matching)
     }
 
     /// Check if the action is classified as dangerous.
@@ -65,14 +153,36 @@ impl SafetyEnforcer {
         }
     }
 
-    /// Check if confidence is below the configured threshold.
-    fn check_confidence(&self, confidence: f64) -> Option<SafetyOverride> {
-        let threshold = self.policy.confidence_default;
+    /// Check if confidence is below the threshold.
+    ///
+    /// Matching `min_confidence` clauses replace the configured default: account clauses
+    /// win over global ones, and the strictest threshold wins within each.
+    fn check_confidence(
+        &self,
+        confidence: f64,
+        matching: &[&PolicyClause],
+    ) -> Option<SafetyOverride> {
+        let strictest = |account_scoped: bool| {
+            matching
+                .iter()
+                .filter(|clause| {
+                    clause.effect == PolicyEffect::MinConfidence
+                        && clause.account_id.is_some() == account_scoped
+                })
+                .filter_map(|clause| clause.min_confidence.map(|min| (min, clause)))
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+        };
+        let (threshold, clause) = match strictest(true).or_else(|| strictest(false)) {
+            Some((min, clause)) => (min as f32, Some(clause.name.clone())),
+            None => (self.policy.confidence_default, None),
+        };
+
         // Convert f32 threshold to f64 for comparison
         if confidence < threshold as f64 {
             Some(SafetyOverride::LowConfidence {
                 confidence,
                 threshold,
+                clause,
             })
         } else {
             None
@@ -99,6 +209,66 @@ impl SafetyEnforcer {
     }
 }
 
+/// A `ProtectedMessage` override for each matching `protect` clause, unless the action only
+/// labels the message.
+fn protected_overrides(action: ActionType, matching: &[&PolicyClause]) -> Vec<SafetyOverride> {
+    if matches!(
+        action,
+        ActionType::ApplyLabel | ActionType::RemoveLabel | ActionType::None
+    ) {
+        return Vec::new();
+    }
+    matching
+        .iter()
+        .filter(|clause| clause.effect == PolicyEffect::Protect)
+        .map(|clause| SafetyOverride::ProtectedMessage {
+            clause_id: clause.id.clone(),
+            clause: clause.name.clone(),
+        })
+        .collect()
+}
+
+/// Whether every non-empty filter of a clause matches the decision and message.
+fn clause_matches(clause: &PolicyClause, action: ActionType, subject: &PolicySubject) -> bool {
+    if clause
+        .account_id
+        .as_deref()
+        .is_some_and(|account_id| account_id != subject.account_id)
+    {
+        return false;
+    }
+    if !clause.action_types.is_empty() && !clause.action_types.iter().any(|a| a == action.as_str())
+    {
+        return false;
+    }
+    if !clause.senders.is_empty() {
+        let Some(sender) = subject.sender_email.as_deref() else {
+            return false;
+        };
+        if !clause
+            .senders
+            .iter()
+            .any(|entry| matches_sender_list_entry(entry, sender))
+        {
+            return false;
+        }
+    }
+    // Protection extends to the whole thread
+    let thread_labels: &[String] = if clause.effect == PolicyEffect::Protect {
+        &subject.thread_labels
+    } else {
+        &[]
+    };
+    clause.labels.is_empty()
+        || clause.labels.iter().any(|label| {
+            subject
+                .labels
+                .iter()
+                .chain(thread_labels)
+                .any(|have| have.eq_ignore_ascii_case(label))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,7 +407,8 @@ mod tests {
             o,
             SafetyOverride::LowConfidence {
                 confidence: c,
-                threshold: t
+                threshold: t,
+                clause: None,
             } if (*c - 0.5).abs() < f64::EPSILON && (*t - 0.7).abs() < f32::EPSILON
         )));
     }
@@ -516,6 +687,7 @@ mod tests {
             ActionType::MarkUnread,
             ActionType::Archive,
             ActionType::Move,
+            ActionType::Summarize,
             ActionType::None,
         ] {
             let decision = sample_decision_output(action, 0.9, false);
@@ -541,6 +713,7 @@ mod tests {
             ActionType::Star,
             ActionType::Unstar,
             ActionType::Snooze,
+            ActionType::MuteThread,
             ActionType::AddNote,
             ActionType::CreateTask,
         ] {
@@ -633,6 +806,7 @@ mod tests {
             SafetyOverride::LowConfidence {
                 confidence: 0.5,
                 threshold: 0.7,
+                clause: None,
             },
             SafetyOverride::InApprovalAlwaysList,
             SafetyOverride::LlmRequestedApproval,
@@ -679,4 +853,246 @@ mod tests {
         assert!(!result.requires_approval);
         assert!(result.overrides_applied.is_empty());
     }
+
+    // ===================
+    // Policy clause tests
+    // ===================
+
+    fn clause(name: &str, effect: PolicyEffect) -> PolicyClause {
+        PolicyClause {
+            id: format!("id-{name}"),
+            org_id: 1,
+            user_id: Some(1),
+            name: name.into(),
+            description: None,
+            account_id: None,
+            effect,
+            min_confidence: None,
+            action_types: vec![],
+            senders: vec![],
+            labels: vec![],
+            enabled: true,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn min_confidence(name: &str, min: f64, action: &str) -> PolicyClause {
+        PolicyClause {
+            min_confidence: Some(min),
+            action_types: vec![action.into()],
+            ..clause(name, PolicyEffect::MinConfidence)
+        }
+    }
+
+    fn subject(sender: &str, labels: &[&str]) -> PolicySubject {
+        PolicySubject {
+            account_id: "acc_1".into(),
+            sender_email: Some(sender.into()),
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            thread_labels: vec![],
+        }
+    }
+
+    #[test]
+    fn min_confidence_clause_sets_threshold_per_action_type() {
+        let enforcer = SafetyEnforcer::new(default_policy()).with_clauses(vec![min_confidence(
+            "strict trash",
+            0.95,
+            "trash",
+        )]);
+        let sender = subject("a@example.com", &[]);
+
+        let trash = sample_decision_output(ActionType::Trash, 0.9, false);
+        let result = enforcer.enforce_for(&trash, &sender);
+        assert_eq!(
+            result.overrides_applied,
+            vec![SafetyOverride::LowConfidence {
+                confidence: 0.9,
+                threshold: 0.95,
+                clause: Some("strict trash".into()),
+            }]
+        );
+        assert_eq!(result.overrides_applied[0].clause(), "strict trash");
+
+        // Other actions keep the configured default
+        let archive = sample_decision_output(ActionType::Archive, 0.9, false);
+        assert!(!enforcer.enforce_for(&archive, &sender).requires_approval);
+    }
+
+    #[test]
+    fn account_min_confidence_clause_overrides_global_clause() {
+        let mut account = min_confidence("lenient account", 0.5, "archive");
+        account.account_id = Some("acc_1".into());
+        let enforcer = SafetyEnforcer::new(default_policy())
+            .with_clauses(vec![min_confidence("strict", 0.9, "archive"), account]);
+        let decision = sample_decision_output(ActionType::Archive, 0.6, false);
+
+        let result = enforcer.enforce_for(&decision, &subject("a@example.com", &[]));
+        assert!(!result.requires_approval);
+
+        // Another account only sees the global clause
+        let mut other = subject("a@example.com", &[]);
+        other.account_id = "acc_2".into();
+        let result = enforcer.enforce_for(&decision, &other);
+        assert_eq!(result.overrides_applied[0].clause(), "strict");
+    }
+
+    #[test]
+    fn require_approval_clause_matches_label_and_names_clause() {
+        let protect = PolicyClause {
+            action_types: vec!["trash".into(), "delete".into()],
+            labels: vec!["finance".into()],
+            ..clause("protect finance", PolicyEffect::RequireApproval)
+        };
+        let enforcer = SafetyEnforcer::new(default_policy()).with_clauses(vec![protect]);
+        let decision = sample_decision_output(ActionType::Trash, 0.99, false);
+
+        let result = enforcer.enforce_for(&decision, &subject("a@example.com", &["Finance"]));
+        assert!(result.requires_approval);
+        assert_eq!(
+            result.overrides_applied,
+            vec![SafetyOverride::PolicyClause {
+                clause_id: "id-protect finance".into(),
+                clause: "protect finance".into(),
+            }]
+        );
+
+        let result = enforcer.enforce_for(&decision, &subject("a@example.com", &["Receipts"]));
+        assert!(!result.requires_approval);
+    }
+
+    #[test]
+    fn skip_approval_clause_waives_configurable_checks_only() {
+        let trusted = PolicyClause {
+            senders: vec!["*@trusted.com".into()],
+            ..clause("trusted senders", PolicyEffect::SkipApproval)
+        };
+        let enforcer = SafetyEnforcer::new(policy_with_approval_always(vec!["archive", "delete"]))
+            .with_clauses(vec![trusted]);
+
+        let archive = sample_decision_output(ActionType::Archive, 0.3, false);
+        let result = enforcer.enforce_for(&archive, &subject("bob@trusted.com", &[]));
+        assert!(!result.requires_approval);
+        assert_eq!(result.waived.len(), 2);
+        assert_eq!(result.waived_by.as_deref(), Some("trusted senders"));
+        let telemetry = result.to_telemetry_json();
+        assert_eq!(telemetry["waived_by"], "trusted senders");
+        assert_eq!(telemetry["waived_overrides"].as_array().unwrap().len(), 2);
+
+        // Dangerous actions and the LLM's own request are never waived
+        let delete = sample_decision_output(ActionType::Delete, 0.9, true);
+        let result = enforcer.enforce_for(&delete, &subject("bob@trusted.com", &[]));
+        assert_eq!(
+            result.overrides_applied,
+            vec![
+                SafetyOverride::DangerousAction,
+                SafetyOverride::LlmRequestedApproval
+            ]
+        );
+
+        // Other senders are not covered
+        let result = enforcer.enforce_for(&archive, &subject("eve@other.com", &[]));
+        assert!(result.requires_approval);
+        assert!(result.waived_by.is_none());
+    }
+
+    #[test]
+    fn require_approval_clause_is_not_waived_by_skip_clause() {
+        let enforcer = SafetyEnforcer::new(default_policy()).with_clauses(vec![
+            PolicyClause {
+                senders: vec!["trusted.com".into()],
+                ..clause("trusted", PolicyEffect::SkipApproval)
+            },
+            PolicyClause {
+                labels: vec!["Finance".into()],
+                ..clause("finance", PolicyEffect::RequireApproval)
+            },
+        ]);
+        let decision = sample_decision_output(ActionType::Archive, 0.9, false);
+
+        let result = enforcer.enforce_for(&decision, &subject("x@trusted.com", &["Finance"]));
+        assert!(result.requires_approval);
+        assert_eq!(result.overrides_applied[0].clause(), "finance");
+    }
+
+    #[test]
+    fn disabled_and_filtered_clauses_are_ignored() {
+        let disabled = PolicyClause {
+            enabled: false,
+            ..clause("disabled", PolicyEffect::RequireApproval)
+        };
+        let sender_only = PolicyClause {
+            senders: vec!["boss@corp.com".into()],
+            ..clause("boss", PolicyEffect::RequireApproval)
+        };
+        let enforcer =
+            SafetyEnforcer::new(default_policy()).with_clauses(vec![disabled, sender_only]);
+        let decision = sample_decision_output(ActionType::Archive, 0.9, false);
+
+        // Without a message, sender filters cannot match
+        assert!(!enforcer.enforce(&decision).requires_approval);
+        assert!(
+            enforcer
+                .enforce_for(&decision, &subject("Boss@Corp.com", &[]))
+                .requires_approval
+        );
+    }
+
+    #[test]
+    fn protect_clause_holds_everything_but_labeling() {
+        let family = PolicyClause {
+            senders: vec!["family.org".into()],
+            ..clause("family", PolicyEffect::Protect)
+        };
+        let enforcer = SafetyEnforcer::new(default_policy()).with_clauses(vec![family]);
+        let mom = subject("mom@family.org", &[]);
+
+        let archive = sample_decision_output(ActionType::Archive, 0.99, false);
+        let result = enforcer.enforce_for(&archive, &mom);
+        assert!(result.requires_approval);
+        assert_eq!(
+            result.overrides_applied,
+            vec![SafetyOverride::ProtectedMessage {
+                clause_id: "id-family".into(),
+                clause: "family".into(),
+            }]
+        );
+        assert_eq!(enforcer.protected_overrides(&archive, &mom).len(), 1);
+
+        let label = sample_decision_output(ActionType::ApplyLabel, 0.99, false);
+        assert!(!enforcer.enforce_for(&label, &mom).requires_approval);
+        assert!(enforcer.protected_overrides(&label, &mom).is_empty());
+    }
+
+    #[test]
+    fn protect_clause_is_not_waived_and_matches_thread_labels() {
+        let enforcer = SafetyEnforcer::new(default_policy()).with_clauses(vec![
+            PolicyClause {
+                senders: vec!["client.com".into()],
+                ..clause("trusted", PolicyEffect::SkipApproval)
+            },
+            PolicyClause {
+                labels: vec!["Key Clients".into()],
+                ..clause("key clients", PolicyEffect::Protect)
+            },
+        ]);
+        let decision = sample_decision_output(ActionType::Archive, 0.3, false);
+
+        // A reply without the label is protected through the rest of its thread
+        let mut reply = subject("ceo@client.com", &[]);
+        assert!(!enforcer.enforce_for(&decision, &reply).requires_approval);
+        reply.thread_labels = vec!["key clients".into()];
+        let result = enforcer.enforce_for(&decision, &reply);
+        assert!(result.requires_approval);
+        assert_eq!(result.overrides_applied[0].clause(), "key clients");
+        assert_eq!(
+            result.waived,
+            vec![SafetyOverride::LowConfidence {
+                confidence: 0.3,
+                threshold: 0.7,
+                clause: None,
+            }]
+        );
+    }
 }
@@ -9,6 +9,10 @@ use ts_rs::TS;
 pub enum DecisionSource {
     Llm,
     Deterministic,
+    /// Reused from earlier LLM decisions for the same sender by the decision cache.
+    Cached,
+    /// Placeholder for a message the LLM was not asked about because a budget ran out.
+    Budget,
 }
 
 impl DecisionSource {
@@ -16,6 +20,8 @@ impl DecisionSource {
         match self {
             DecisionSource::Llm => "llm",
             DecisionSource::Deterministic => "deterministic",
+            DecisionSource::Cached => "cached",
+            DecisionSource::Budget => "budget",
         }
     }
 
@@ -23,6 +29,8 @@ impl DecisionSource {
         match value {
             "llm" => Some(Self::Llm),
             "deterministic" => Some(Self::Deterministic),
+            "cached" => Some(Self::Cached),
+            "budget" => Some(Self::Budget),
             _ => None,
         }
     }
@@ -39,6 +47,7 @@ pub enum ActionStatus {
     Canceled,
     Rejected,
     ApprovedPending,
+    PendingExecution,
 }
 
 impl ActionStatus {
@@ -51,6 +60,7 @@ impl ActionStatus {
             ActionStatus::Canceled => "canceled",
             ActionStatus::Rejected => "rejected",
             ActionStatus::ApprovedPending => "approved_pending",
+            ActionStatus::PendingExecution => "pending_execution",
         }
     }
 
@@ -63,6 +73,7 @@ impl ActionStatus {
             "canceled" => Some(Self::Canceled),
             "rejected" => Some(Self::Rejected),
             "approved_pending" => Some(Self::ApprovedPending),
+            "pending_execution" => Some(Self::PendingExecution),
             _ => None,
         }
     }
@@ -0,0 +1,795 @@
+//! Periodic digest of automated activity.
+//!
+//! A digest covers one period of an account: the actions taken by type and by rule, actions
+//! waiting for approval, snoozed messages coming back, low-confidence decisions and failures.
+//! It is rendered as plain text for both email and Discord.
+
+use chrono::{DateTime, Datelike, Duration, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+use libsql::{Row, params};
+use thiserror::Error;
+
+use crate::config::{DigestConfig, DigestFrequency, DigestSection};
+use crate::db::{Database, DbError};
+use crate::rules::schedule::parse_time;
+use crate::rules::{ScheduleDay, ScheduleError};
+
+const DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M %Z";
+
+#[derive(Debug, Error)]
+pub enum DigestError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("datetime parse error: {0}")]
+    DateTimeParse(#[from] chrono::ParseError),
+}
+
+/// Number of actions sharing a type or a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestCount {
+    pub label: String,
+    pub count: i64,
+}
+
+/// A message listed in a digest section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestItem {
+    pub message_id: String,
+    pub subject: Option<String>,
+    pub from_email: Option<String>,
+    /// What happened to the message, e.g. the action awaiting approval.
+    pub detail: String,
+    pub at: DateTime<Utc>,
+}
+
+/// The first `max_items` entries of a section and how many there are in total.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DigestList {
+    pub total: i64,
+    pub items: Vec<DigestItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Digest {
+    pub account_id: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Completed actions by action type, most frequent first.
+    pub actions_by_type: Vec<DigestCount>,
+    /// Completed actions by the rule or classifier that chose them, most frequent first.
+    pub actions_by_rule: Vec<DigestCount>,
+    /// Actions waiting for approval, whenever they were created.
+    pub awaiting_approval: DigestList,
+    /// Snoozed messages returning to the inbox before the next digest.
+    pub snoozes_due: DigestList,
+    pub low_confidence: DigestList,
+    pub failures: DigestList,
+}
+
+impl Digest {
+    /// Whether none of `sections` has anything to report.
+    pub fn is_empty(&self, sections: &[DigestSection]) -> bool {
+        sections.iter().all(|section| match section {
+            DigestSection::Actions => self.actions_by_type.is_empty(),
+            DigestSection::Approvals => self.awaiting_approval.total == 0,
+            DigestSection::Snoozes => self.snoozes_due.total == 0,
+            DigestSection::LowConfidence => self.low_confidence.total == 0,
+            DigestSection::Failures => self.failures.total == 0,
+        })
+    }
+
+    pub fn subject(&self, account_email: &str, tz: Tz) -> String {
+        format!(
+            "Ashford digest for {account_email}: {}",
+            self.period_end.with_timezone(&tz).format("%Y-%m-%d")
+        )
+    }
+
+    /// Plain-text rendering of `sections`, with times shown in `tz`.
+    pub fn render_text(&self, account_email: &str, sections: &[DigestSection], tz: Tz) -> String {
+        let local = |at: DateTime<Utc>| at.with_timezone(&tz).format(DISPLAY_FORMAT).to_string();
+        let mut blocks = vec![format!(
+            "Ashford digest for {account_email}\n{} to {}",
+            local(self.period_start),
+            local(self.period_end)
+        )];
+
+        for section in sections {
+            let block = match section {
+                DigestSection::Actions => {
+                    let total: i64 = self.actions_by_type.iter().map(|c| c.count).sum();
+                    let mut lines = vec![format!("ACTIONS TAKEN ({total})")];
+                    if total == 0 {
+                        lines.push("None.".to_string());
+                    } else {
+                        lines.push("By type:".to_string());
+                        lines.extend(format_counts(&self.actions_by_type));
+                        lines.push("By rule:".to_string());
+                        lines.extend(format_counts(&self.actions_by_rule));
+                    }
+                    lines.join("\n")
+                }
+                DigestSection::Approvals => {
+                    format_list("AWAITING APPROVAL", &self.awaiting_approval, &local)
+                }
+                DigestSection::Snoozes => {
+                    format_list("SNOOZES COMING DUE", &self.snoozes_due, &local)
+                }
+                DigestSection::LowConfidence => {
+                    format_list("LOW-CONFIDENCE DECISIONS", &self.low_confidence, &local)
+                }
+                DigestSection::Failures => format_list("FAILURES", &self.failures, &local),
+            };
+            blocks.push(block);
+        }
+
+        blocks.join("\n\n")
+    }
+}
+
+fn format_counts(counts: &[DigestCount]) -> Vec<String> {
+    counts
+        .iter()
+        .map(|c| format!("  {}: {}", c.label, c.count))
+        .collect()
+}
+
+fn format_list(
+    heading: &str,
+    list: &DigestList,
+    local: &dyn Fn(DateTime<Utc>) -> String,
+) -> String {
+    let mut lines = vec![format!("{heading} ({})", list.total)];
+    if list.total == 0 {
+        lines.push("None.".to_string());
+    }
+    for item in &list.items {
+        let subject = item.subject.as_deref().unwrap_or("(no subject)");
+        let from = item.from_email.as_deref().unwrap_or("unknown sender");
+        lines.push(format!(
+            "- \"{subject}\" from {from}: {} ({})",
+            item.detail,
+            local(item.at)
+        ));
+    }
+    let hidden = list.total - list.items.len() as i64;
+    if hidden > 0 {
+        lines.push(format!("...and {hidden} more"));
+    }
+    lines.join("\n")
+}
+
+/// The first scheduled digest time strictly after `after`.
+///
+/// A time skipped by a daylight-saving change is sent an hour later.
+pub fn next_digest_at(
+    config: &DigestConfig,
+    after: DateTime<Utc>,
+) -> Result<DateTime<Utc>, ScheduleError> {
+    let time = parse_time(&config.time)?;
+    let tz = digest_timezone(config)?;
+    let mut date = after.with_timezone(&tz).date_naive();
+
+    // A weekly digest whose time today has passed is due in seven days
+    for _ in 0..8 {
+        let due = match config.frequency {
+            DigestFrequency::Daily => true,
+            DigestFrequency::Weekly => ScheduleDay::from(date.weekday()) == config.day,
+        };
+        if due {
+            let local = date.and_time(time);
+            let at = tz
+                .from_local_datetime(&local)
+                .earliest()
+                .or_else(|| {
+                    tz.from_local_datetime(&(local + Duration::hours(1)))
+                        .earliest()
+                })
+                .map(|at| at.with_timezone(&Utc));
+            if let Some(at) = at.filter(|at| *at > after) {
+                return Ok(at);
+            }
+        }
+        date = date.succ_opt().expect("date within range");
+    }
+    unreachable!("a digest is due within eight days")
+}
+
+pub fn digest_timezone(config: &DigestConfig) -> Result<Tz, ScheduleError> {
+    config
+        .timezone
+        .parse::<Tz>()
+        .map_err(|_| ScheduleError::UnknownTimezone(config.timezone.clone()))
+}
+
+#[derive(Clone)]
+pub struct DigestRepository {
+    db: Database,
+}
+
+impl DigestRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Compile the digest of an account for `[period_start, period_end)`.
+    pub async fn compile(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        config: &DigestConfig,
+    ) -> Result<Digest, DigestError> {
+        let start = timestamp(period_start);
+        let end = timestamp(period_end);
+        let limit = config.max_items as i64;
+        let conn = self.db.connection().await?;
+
+        let mut rows = conn
+            .query(
+                "SELECT action_type, COUNT(*) AS count
+                 FROM actions
+                 WHERE org_id = ?1 AND user_id = ?2 AND account_id = ?3 AND status = 'completed'
+                   AND executed_at >= ?4 AND executed_at < ?5
+                 GROUP BY action_type
+                 ORDER BY count DESC, action_type",
+                params![org_id, user_id, account_id, start.clone(), end.clone()],
+            )
+            .await?;
+        let mut actions_by_type = Vec::new();
+        while let Some(row) = rows.next().await? {
+            actions_by_type.push(DigestCount {
+                label: row.get(0)?,
+                count: row.get(1)?,
+            });
+        }
+
+        let mut rows = conn
+            .query(
+                "SELECT json_extract(d.telemetry_json, '$.rule.name') AS rule_name,
+                        d.source,
+                        COUNT(*) AS count
+                 FROM actions a
+                 LEFT JOIN decisions d ON d.id = a.decision_id
+                 WHERE a.org_id = ?1 AND a.user_id = ?2 AND a.account_id = ?3
+                   AND a.status = 'completed' AND a.executed_at >= ?4 AND a.executed_at < ?5
+                 GROUP BY rule_name, d.source
+                 ORDER BY count DESC, rule_name",
+                params![org_id, user_id, account_id, start.clone(), end.clone()],
+            )
+            .await?;
+        let mut actions_by_rule = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let rule_name: Option<String> = row.get(0)?;
+            let source: Option<String> = row.get(1)?;
+            actions_by_rule.push(DigestCount {
+                label: rule_label(rule_name, source.as_deref()),
+                count: row.get(2)?,
+            });
+        }
+
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) OVER (), a.message_id, m.subject, m.from_email, a.created_at,
+                        a.action_type
+                 FROM actions a
+                 JOIN messages m ON m.id = a.message_id
+                 WHERE a.org_id = ?1 AND a.user_id = ?2 AND a.account_id = ?3
+                   AND a.status = 'approved_pending'
+                 ORDER BY a.created_at
+                 LIMIT ?4",
+                params![org_id, user_id, account_id, limit],
+            )
+            .await?;
+        let awaiting_approval = collect_list(&mut rows, |row| {
+            let action_type: String = row.get(5)?;
+            Ok(action_type)
+        })
+        .await?;
+
+        // Snoozes returning before the next digest is due
+        let horizon = timestamp(period_end + config.frequency.period());
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) OVER (), m.id, m.subject, m.from_email, j.not_before
+                 FROM jobs j
+                 JOIN messages m ON m.id = json_extract(j.payload_json, '$.message_id')
+                 WHERE j.type = 'unsnooze.gmail' AND j.state = 'queued'
+                   AND json_extract(j.payload_json, '$.account_id') = ?3
+                   AND m.org_id = ?1 AND m.user_id = ?2 AND j.not_before < ?4
+                 ORDER BY j.not_before
+                 LIMIT ?5",
+                params![org_id, user_id, account_id, horizon, limit],
+            )
+            .await?;
+        let snoozes_due = collect_list(&mut rows, |_| Ok("returns to inbox".to_string())).await?;
+
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) OVER (), d.message_id, m.subject, m.from_email, d.created_at,
+                        d.action_type, d.confidence
+                 FROM decisions d
+                 JOIN messages m ON m.id = d.message_id
+                 WHERE d.org_id = ?1 AND d.user_id = ?2 AND d.account_id = ?3
+                   AND d.source IN ('llm', 'cached') AND d.confidence < ?4
+                   AND d.created_at >= ?5 AND d.created_at < ?6
+                 ORDER BY d.confidence, d.created_at
+                 LIMIT ?7",
+                params![
+                    org_id,
+                    user_id,
+                    account_id,
+                    config.low_confidence_threshold,
+                    start.clone(),
+                    end.clone(),
+                    limit
+                ],
+            )
+            .await?;
+        let low_confidence = collect_list(&mut rows, |row| {
+            let action_type: Option<String> = row.get(5)?;
+            let confidence: f64 = row.get(6)?;
+            Ok(format!(
+                "{} at {confidence:.2}",
+                action_type.as_deref().unwrap_or("none")
+            ))
+        })
+        .await?;
+
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) OVER (), a.message_id, m.subject, m.from_email, a.updated_at,
+                        a.action_type, a.error_message
+                 FROM actions a
+                 JOIN messages m ON m.id = a.message_id
+                 WHERE a.org_id = ?1 AND a.user_id = ?2 AND a.account_id = ?3
+                   AND a.status = 'failed' AND a.updated_at >= ?4 AND a.updated_at < ?5
+                 ORDER BY a.updated_at
+                 LIMIT ?6",
+                params![org_id, user_id, account_id, start, end, limit],
+            )
+            .await?;
+        let failures = collect_list(&mut rows, |row| {
+            let action_type: String = row.get(5)?;
+            let error: Option<String> = row.get(6)?;
+            Ok(match error {
+                Some(error) => format!("{action_type}: {error}"),
+                None => action_type,
+            })
+        })
+        .await?;
+
+        Ok(Digest {
+            account_id: account_id.to_string(),
+            period_start,
+            period_end,
+            actions_by_type,
+            actions_by_rule,
+            awaiting_approval,
+            snoozes_due,
+            low_confidence,
+            failures,
+        })
+    }
+}
+
+/// Who chose an action: the matched rule, or the kind of decision behind it.
+fn rule_label(rule_name: Option<String>, source: Option<&str>) -> String {
+    match (rule_name, source) {
+        (Some(name), _) => name,
+        (None, Some("llm")) => "LLM classifier".to_string(),
+        (None, Some("cached")) => "Decision cache".to_string(),
+        (None, Some("budget")) => "LLM budget exceeded".to_string(),
+        (None, Some(_)) => "Deterministic rule".to_string(),
+        (None, None) => "Other".to_string(),
+    }
+}
+
+/// Reads rows of `total, message_id, subject, from_email, at, ...`; `detail` reads the rest.
+async fn collect_list(
+    rows: &mut libsql::Rows,
+    detail: impl Fn(&Row) -> Result<String, libsql::Error>,
+) -> Result<DigestList, DigestError> {
+    let mut list = DigestList::default();
+    while let Some(row) = rows.next().await? {
+        let at: String = row.get(4)?;
+        list.total = row.get(0)?;
+        list.items.push(DigestItem {
+            message_id: row.get(1)?,
+            subject: row.get(2)?,
+            from_email: row.get(3)?,
+            detail: detail(&row)?,
+            at: DateTime::parse_from_rfc3339(&at)?.with_timezone(&Utc),
+        });
+    }
+    Ok(list)
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::decisions::{
+        ActionRepository, ActionStatus, DecisionRepository, DecisionSource, NewAction, NewDecision,
+    };
+    use crate::gmail::OAuthTokens;
+    use crate::messages::{MessageRepository, NewMessage};
+    use crate::migrations::run_migrations;
+    use crate::queue::JobQueue;
+    use crate::threads::ThreadRepository;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn daily_digest_runs_at_the_next_configured_time() {
+        let config = DigestConfig {
+            time: "08:00".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            next_digest_at(&config, utc(2024, 6, 3, 7, 59)).unwrap(),
+            utc(2024, 6, 3, 8, 0)
+        );
+        assert_eq!(
+            next_digest_at(&config, utc(2024, 6, 3, 8, 0)).unwrap(),
+            utc(2024, 6, 4, 8, 0)
+        );
+    }
+
+    #[test]
+    fn weekly_digest_uses_configured_day_and_timezone() {
+        let config = DigestConfig {
+            frequency: DigestFrequency::Weekly,
+            day: ScheduleDay::Mon,
+            time: "09:00".into(),
+            timezone: "America/New_York".into(),
+            ..Default::default()
+        };
+        // 2024-06-03 is a Monday; 09:00 EDT is 13:00 UTC.
+        assert_eq!(
+            next_digest_at(&config, utc(2024, 6, 1, 0, 0)).unwrap(),
+            utc(2024, 6, 3, 13, 0)
+        );
+        assert_eq!(
+            next_digest_at(&config, utc(2024, 6, 3, 13, 0)).unwrap(),
+            utc(2024, 6, 10, 13, 0)
+        );
+
+        let invalid = DigestConfig {
+            timezone: "Mars/Olympus".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            next_digest_at(&invalid, utc(2024, 6, 1, 0, 0)),
+            Err(ScheduleError::UnknownTimezone("Mars/Olympus".into()))
+        );
+    }
+
+    async fn setup() -> (Database, TempDir, String, String) {
+        let dir = TempDir::new().expect("temp dir");
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+        let thread = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account.id,
+                "thr-1",
+                Some("Invoice".into()),
+                None,
+                None,
+                json!({}),
+            )
+            .await
+            .expect("thread");
+        let message = MessageRepository::new(db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account.id.clone(),
+                thread_id: thread.id,
+                provider_message_id: "msg-1".into(),
+                from_email: Some("billing@acme.example".into()),
+                from_name: None,
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some("Invoice".into()),
+                snippet: None,
+                received_at: None,
+                internal_date: None,
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: None,
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("message");
+        (db, dir, account.id, message.id)
+    }
+
+    async fn add_decision(
+        db: &Database,
+        account_id: &str,
+        message_id: &str,
+        source: DecisionSource,
+        confidence: f64,
+        telemetry: serde_json::Value,
+    ) -> String {
+        DecisionRepository::new(db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.to_string(),
+                message_id: message_id.to_string(),
+                source,
+                decision_json: json!({}),
+                action_type: Some("archive".into()),
+                confidence: Some(confidence),
+                needs_approval: false,
+                rationale: None,
+                telemetry_json: telemetry,
+            })
+            .await
+            .expect("decision")
+            .id
+    }
+
+    /// Creates an action and forces its status and timestamps.
+    async fn add_action(
+        db: &Database,
+        account_id: &str,
+        message_id: &str,
+        decision_id: Option<String>,
+        action_type: &str,
+        status: &str,
+        at: DateTime<Utc>,
+    ) {
+        let action = ActionRepository::new(db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.to_string(),
+                message_id: message_id.to_string(),
+                decision_id,
+                action_type: action_type.to_string(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("action");
+        let at = timestamp(at);
+        db.connection()
+            .await
+            .expect("conn")
+            .execute(
+                "UPDATE actions SET status = ?2, executed_at = ?3, updated_at = ?3, created_at = ?3,
+                        error_message = CASE WHEN ?2 = 'failed' THEN 'gmail said no' END
+                 WHERE id = ?1",
+                params![action.id, status, at],
+            )
+            .await
+            .expect("update action");
+    }
+
+    #[tokio::test]
+    async fn compile_collects_each_section_for_the_period() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let period_end = Utc::now() + Duration::minutes(1);
+        let period_start = period_end - Duration::days(1);
+        let inside = period_end - Duration::hours(2);
+
+        let rule_decision = add_decision(
+            &db,
+            &account_id,
+            &message_id,
+            DecisionSource::Deterministic,
+            1.0,
+            json!({"rule": {"id": "r1", "name": "Invoices"}}),
+        )
+        .await;
+        let llm_decision = add_decision(
+            &db,
+            &account_id,
+            &message_id,
+            DecisionSource::Llm,
+            0.4,
+            json!({}),
+        )
+        .await;
+        for decision in [
+            Some(rule_decision.clone()),
+            Some(rule_decision),
+            Some(llm_decision),
+        ] {
+            add_action(
+                &db,
+                &account_id,
+                &message_id,
+                decision,
+                "archive",
+                "completed",
+                inside,
+            )
+            .await;
+        }
+        // Completed before the period
+        add_action(
+            &db,
+            &account_id,
+            &message_id,
+            None,
+            "star",
+            "completed",
+            period_start - Duration::hours(1),
+        )
+        .await;
+        add_action(
+            &db,
+            &account_id,
+            &message_id,
+            None,
+            "delete",
+            "approved_pending",
+            period_start - Duration::days(3),
+        )
+        .await;
+        add_action(
+            &db,
+            &account_id,
+            &message_id,
+            None,
+            "forward",
+            "failed",
+            inside,
+        )
+        .await;
+        JobQueue::new(db.clone())
+            .enqueue_scheduled(
+                "unsnooze.gmail",
+                json!({"account_id": account_id, "message_id": message_id}),
+                None,
+                0,
+                period_end + Duration::hours(3),
+            )
+            .await
+            .expect("unsnooze job");
+
+        let config = DigestConfig {
+            low_confidence_threshold: 0.5,
+            ..Default::default()
+        };
+        let digest = DigestRepository::new(db.clone())
+            .compile(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                period_start,
+                period_end,
+                &config,
+            )
+            .await
+            .expect("compile");
+
+        assert_eq!(
+            digest.actions_by_type,
+            vec![DigestCount {
+                label: "archive".into(),
+                count: 3
+            }]
+        );
+        assert_eq!(
+            digest.actions_by_rule,
+            vec![
+                DigestCount {
+                    label: "Invoices".into(),
+                    count: 2
+                },
+                DigestCount {
+                    label: "LLM classifier".into(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(digest.awaiting_approval.total, 1);
+        assert_eq!(digest.awaiting_approval.items[0].detail, "delete");
+        assert_eq!(digest.snoozes_due.total, 1);
+        assert_eq!(digest.low_confidence.total, 1);
+        assert_eq!(digest.low_confidence.items[0].detail, "archive at 0.40");
+        assert_eq!(digest.failures.items[0].detail, "forward: gmail said no");
+        assert!(!digest.is_empty(&config.sections));
+
+        let text = digest.render_text("user@example.com", &config.sections, Tz::UTC);
+        assert!(text.contains("ACTIONS TAKEN (3)"));
+        assert!(text.contains("  Invoices: 2"));
+        assert!(
+            text.contains("AWAITING APPROVAL (1)\n- \"Invoice\" from billing@acme.example: delete")
+        );
+        assert!(text.contains("FAILURES (1)"));
+
+        let only_snoozes = [DigestSection::Snoozes];
+        let text = digest.render_text("user@example.com", &only_snoozes, Tz::UTC);
+        assert!(text.contains("SNOOZES COMING DUE (1)"));
+        assert!(!text.contains("FAILURES"));
+    }
+
+    #[tokio::test]
+    async fn compile_limits_listed_items_but_counts_all() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let now = Utc::now();
+        for _ in 0..3 {
+            add_action(
+                &db,
+                &account_id,
+                &message_id,
+                None,
+                "archive",
+                "failed",
+                now - Duration::minutes(5),
+            )
+            .await;
+        }
+
+        let config = DigestConfig {
+            max_items: 2,
+            ..Default::default()
+        };
+        let digest = DigestRepository::new(db.clone())
+            .compile(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                now - Duration::days(1),
+                now,
+                &config,
+            )
+            .await
+            .expect("compile");
+        assert_eq!(digest.failures.total, 3);
+        assert_eq!(digest.failures.items.len(), 2);
+        assert!(digest.is_empty(&[DigestSection::Actions, DigestSection::Approvals]));
+
+        let text = digest.render_text("user@example.com", &[DigestSection::Failures], Tz::UTC);
+        assert!(text.ends_with("...and 1 more"));
+    }
+}
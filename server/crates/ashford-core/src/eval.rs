@@ -0,0 +1,983 @@
+//! Offline evaluation of the classifier against a golden dataset.
+//!
+//! A dataset is a JSON Lines file of [`EvalCase`]s: a message fixture, the expected action
+//! and labels, and the actions that are acceptable alternatives. [`EvalRunner`] sends each
+//! case through the same pipeline as `classify` (prompt, LLM, decision parsing and safety
+//! enforcement) and produces an [`EvalReport`].
+//!
+//! Cases may carry the LLM response recorded in `llm_calls`. [`ReplayLLMClient`] answers
+//! with those recordings so CI runs are deterministic and free.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::config::{PolicyConfig, PricingConfig};
+use crate::db::Database;
+use crate::decisions::SafetyEnforcer;
+use crate::feedback::{FeedbackError, FeedbackRepository};
+use crate::gmail::types::Header;
+use crate::llm::decision::{ActionType, DecisionOutput};
+use crate::llm::prompt::{DECISION_TOOL_NAME, PromptBuilder, PromptContext, build_decision_tool};
+use crate::llm::redaction::Redactor;
+use crate::llm::{
+    CompletionRequest, CompletionResponse, LLMClient, LLMError, LlmCallContext, LlmCallError,
+    LlmCallRepository, recorded_completion,
+};
+use crate::messages::{Mailbox, Message, MessageError, MessageRepository};
+
+/// `llm_calls` feature name for evaluation runs.
+pub const EVAL_FEATURE: &str = "eval";
+
+/// Feature whose recorded calls are replayed when building a dataset.
+const CLASSIFY_FEATURE: &str = "classify";
+
+/// Number of equal-width confidence buckets in the calibration table.
+const CALIBRATION_BUCKETS: usize = 10;
+
+/// Confusion matrix column for cases that produced no decision.
+const NO_DECISION: &str = "no_decision";
+
+#[derive(Debug, Error)]
+pub enum EvalError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid case on line {line}: {source}")]
+    InvalidCase {
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("feedback error: {0}")]
+    Feedback(#[from] FeedbackError),
+    #[error("message error: {0}")]
+    Message(#[from] MessageError),
+    #[error("llm call error: {0}")]
+    LlmCall(#[from] LlmCallError),
+}
+
+/// The parts of a message the classifier sees.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalMessage {
+    pub from_email: Option<String>,
+    pub from_name: Option<String>,
+    pub to: Vec<Mailbox>,
+    pub cc: Vec<Mailbox>,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+    pub received_at: Option<DateTime<Utc>>,
+    pub labels: Vec<String>,
+    pub headers: Vec<Header>,
+    pub body_plain: Option<String>,
+    pub body_html: Option<String>,
+}
+
+impl EvalMessage {
+    pub fn from_message(message: &Message) -> Self {
+        Self {
+            from_email: message.from_email.clone(),
+            from_name: message.from_name.clone(),
+            to: message.to.clone(),
+            cc: message.cc.clone(),
+            subject: message.subject.clone(),
+            snippet: message.snippet.clone(),
+            received_at: message.received_at,
+            labels: message.labels.clone(),
+            headers: message.headers.clone(),
+            body_plain: message.body_plain.clone(),
+            body_html: message.body_html.clone(),
+        }
+    }
+
+    /// Build a stored-message stand-in for the prompt builder. `case_id` is used for every ID.
+    fn to_message(&self, case_id: &str) -> Message {
+        let now = Utc::now();
+        Message {
+            id: case_id.to_string(),
+            account_id: "eval".into(),
+            thread_id: case_id.to_string(),
+            provider_message_id: case_id.to_string(),
+            from_email: self.from_email.clone(),
+            from_name: self.from_name.clone(),
+            to: self.to.clone(),
+            cc: self.cc.clone(),
+            bcc: Vec::new(),
+            subject: self.subject.clone(),
+            snippet: self.snippet.clone(),
+            received_at: self.received_at,
+            internal_date: self.received_at,
+            labels: self.labels.clone(),
+            headers: self.headers.clone(),
+            body_plain: self.body_plain.clone(),
+            body_html: self.body_html.clone(),
+            raw_json: json!({}),
+            created_at: now,
+            updated_at: now,
+            org_id: 0,
+            user_id: 0,
+        }
+    }
+}
+
+/// One golden example.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalCase {
+    pub id: String,
+    pub message: EvalMessage,
+    pub expected_action: ActionType,
+    /// Labels an `apply_label` decision should name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_labels: Vec<String>,
+    /// Other actions that count as acceptable, though not exact.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub acceptable_actions: Vec<ActionType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// LLM response to replay instead of calling a model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recorded_response: Option<CompletionResponse>,
+}
+
+/// Parse a JSON Lines dataset. Blank lines and lines starting with `#` are skipped.
+pub fn parse_dataset(input: &str) -> Result<Vec<EvalCase>, EvalError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|source| EvalError::InvalidCase {
+                line: index + 1,
+                source,
+            })
+        })
+        .collect()
+}
+
+pub fn load_dataset(path: impl AsRef<Path>) -> Result<Vec<EvalCase>, EvalError> {
+    parse_dataset(&std::fs::read_to_string(path)?)
+}
+
+pub fn write_dataset(path: impl AsRef<Path>, cases: &[EvalCase]) -> Result<(), EvalError> {
+    let mut output = String::new();
+    for case in cases {
+        output.push_str(&serde_json::to_string(case)?);
+        output.push('\n');
+    }
+    std::fs::write(path, output)?;
+    Ok(())
+}
+
+/// Build cases from decisions the user corrected after an undo or rejection.
+///
+/// The corrected action is the expected one. Each case carries the response the classifier
+/// gave at the time, when it is still in `llm_calls`, so replaying it reproduces the mistake.
+pub async fn dataset_from_feedback(
+    db: &Database,
+    org_id: i64,
+    user_id: i64,
+    limit: i64,
+) -> Result<Vec<EvalCase>, EvalError> {
+    let feedback = FeedbackRepository::new(db.clone())
+        .list_recent(org_id, user_id, limit, 0)
+        .await?;
+    let messages = MessageRepository::new(db.clone());
+    let llm_calls = LlmCallRepository::new(db.clone());
+
+    let mut cases = Vec::new();
+    let mut seen = BTreeSet::new();
+    for entry in feedback {
+        let Some(expected_action) = entry
+            .corrected_action_type
+            .as_deref()
+            .and_then(|action| action.parse::<ActionType>().ok())
+        else {
+            continue;
+        };
+        // list_recent is newest first, so the latest correction wins
+        if !seen.insert(entry.message_id.clone()) {
+            continue;
+        }
+
+        let message = match messages.get_by_id(org_id, user_id, &entry.message_id).await {
+            Ok(message) => message,
+            Err(MessageError::NotFound(_)) => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let recorded_response = llm_calls
+            .latest_for_message(org_id, user_id, CLASSIFY_FEATURE, &entry.message_id)
+            .await?
+            .as_ref()
+            .and_then(recorded_completion);
+        let expected_labels = match expected_action {
+            ActionType::ApplyLabel => entry
+                .corrected_parameters_json
+                .as_ref()
+                .map(labels_in_parameters)
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        cases.push(EvalCase {
+            id: entry.message_id.clone(),
+            message: EvalMessage::from_message(&message),
+            expected_action,
+            expected_labels,
+            acceptable_actions: Vec::new(),
+            notes: Some(format!(
+                "{} of {}",
+                entry.source.as_str(),
+                entry.wrong_action_type
+            )),
+            recorded_response,
+        });
+    }
+    Ok(cases)
+}
+
+/// Answers with each case's recorded response, matched by the message ID in the call context.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayLLMClient {
+    responses: HashMap<String, CompletionResponse>,
+}
+
+impl ReplayLLMClient {
+    pub fn from_cases(cases: &[EvalCase]) -> Self {
+        let responses = cases
+            .iter()
+            .filter_map(|case| {
+                case.recorded_response
+                    .clone()
+                    .map(|response| (case.id.clone(), response))
+            })
+            .collect();
+        Self { responses }
+    }
+}
+
+#[async_trait]
+impl LLMClient for ReplayLLMClient {
+    async fn complete(
+        &self,
+        _request: CompletionRequest,
+        context: LlmCallContext,
+    ) -> Result<CompletionResponse, LLMError> {
+        let case_id = context.message_id.unwrap_or_default();
+        self.responses.get(&case_id).cloned().ok_or_else(|| {
+            LLMError::ProviderError(format!("no recorded response for case {case_id}"))
+        })
+    }
+}
+
+/// Outcome of a single case.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalCaseResult {
+    pub case_id: String,
+    pub expected_action: ActionType,
+    pub predicted_action: Option<ActionType>,
+    pub confidence: Option<f64>,
+    pub correct: bool,
+    /// Correct, or one of the case's acceptable actions.
+    pub acceptable: bool,
+    /// `None` when the case expects no labels.
+    pub labels_correct: Option<bool>,
+    pub needs_approval: bool,
+    pub model: Option<String>,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cost_usd: Option<f64>,
+    /// LLM or parse error when no decision was produced.
+    pub error: Option<String>,
+}
+
+/// Accuracy of the decisions whose confidence falls in `[lower, upper)`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalibrationBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub mean_confidence: f64,
+    pub accuracy: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalReport {
+    pub total: usize,
+    pub correct: usize,
+    pub acceptable: usize,
+    /// Cases that produced no decision.
+    pub failed: usize,
+    pub needs_approval: usize,
+    pub accuracy: f64,
+    pub acceptable_accuracy: f64,
+    /// Over cases with expected labels; `None` when there are none.
+    pub label_accuracy: Option<f64>,
+    /// Expected action -> predicted action (or `no_decision`) -> count.
+    pub confusion: BTreeMap<String, BTreeMap<String, usize>>,
+    /// Non-empty buckets, lowest confidence first.
+    pub calibration: Vec<CalibrationBucket>,
+    pub expected_calibration_error: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    pub unpriced_models: Vec<String>,
+    pub cases: Vec<EvalCaseResult>,
+}
+
+impl EvalReport {
+    pub fn from_results(cases: Vec<EvalCaseResult>) -> Self {
+        let total = cases.len();
+        let count = |f: fn(&EvalCaseResult) -> bool| cases.iter().filter(|c| f(c)).count();
+        let correct = count(|c| c.correct);
+        let acceptable = count(|c| c.acceptable);
+        let failed = count(|c| c.predicted_action.is_none());
+        let needs_approval = count(|c| c.needs_approval);
+
+        let labelled: Vec<bool> = cases.iter().filter_map(|c| c.labels_correct).collect();
+        let label_accuracy = (!labelled.is_empty())
+            .then(|| ratio(labelled.iter().filter(|ok| **ok).count(), labelled.len()));
+
+        let mut confusion: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+        for case in &cases {
+            let predicted = case
+                .predicted_action
+                .map_or(NO_DECISION, |action| action.as_str());
+            *confusion
+                .entry(case.expected_action.as_str().to_string())
+                .or_default()
+                .entry(predicted.to_string())
+                .or_default() += 1;
+        }
+
+        let (calibration, expected_calibration_error) = calibrate(&cases);
+
+        let mut unpriced_models = BTreeSet::new();
+        for case in &cases {
+            if case.cost_usd.is_none()
+                && let Some(model) = &case.model
+            {
+                unpriced_models.insert(model.clone());
+            }
+        }
+
+        Self {
+            total,
+            correct,
+            acceptable,
+            failed,
+            needs_approval,
+            accuracy: ratio(correct, total),
+            acceptable_accuracy: ratio(acceptable, total),
+            label_accuracy,
+            confusion,
+            calibration,
+            expected_calibration_error,
+            input_tokens: cases.iter().map(|c| c.input_tokens as u64).sum(),
+            output_tokens: cases.iter().map(|c| c.output_tokens as u64).sum(),
+            cost_usd: cases
+                .iter()
+                .filter_map(|c| c.cost_usd)
+                .fold(0.0, |sum, cost| sum + cost),
+            unpriced_models: unpriced_models.into_iter().collect(),
+            cases,
+        }
+    }
+
+    /// Plain-text summary for terminals and CI logs.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "cases: {}  correct: {} ({:.1}%)  acceptable: {} ({:.1}%)  no decision: {}",
+                self.total,
+                self.correct,
+                self.accuracy * 100.0,
+                self.acceptable,
+                self.acceptable_accuracy * 100.0,
+                self.failed
+            ),
+            format!("needs approval: {}", self.needs_approval),
+        ];
+        if let Some(label_accuracy) = self.label_accuracy {
+            lines.push(format!("label accuracy: {:.1}%", label_accuracy * 100.0));
+        }
+        lines.push(format!(
+            "tokens: {} in / {} out  cost: ${:.4}",
+            self.input_tokens, self.output_tokens, self.cost_usd
+        ));
+        if !self.unpriced_models.is_empty() {
+            lines.push(format!(
+                "unpriced models: {}",
+                self.unpriced_models.join(", ")
+            ));
+        }
+
+        lines.push("confusion (expected -> predicted):".into());
+        for (expected, predicted) in &self.confusion {
+            let row: Vec<String> = predicted
+                .iter()
+                .map(|(action, count)| format!("{action}={count}"))
+                .collect();
+            lines.push(format!("  {expected}: {}", row.join(" ")));
+        }
+
+        lines.push(format!(
+            "calibration (ECE {:.3}):",
+            self.expected_calibration_error
+        ));
+        for bucket in &self.calibration {
+            lines.push(format!(
+                "  {:.1}-{:.1}: n={} confidence={:.2} accuracy={:.2}",
+                bucket.lower, bucket.upper, bucket.count, bucket.mean_confidence, bucket.accuracy
+            ));
+        }
+
+        let misses: Vec<&EvalCaseResult> = self.cases.iter().filter(|c| !c.correct).collect();
+        if !misses.is_empty() {
+            lines.push("misses:".into());
+            for case in misses {
+                let predicted = case
+                    .predicted_action
+                    .map_or(NO_DECISION, |action| action.as_str());
+                let detail = case
+                    .error
+                    .as_deref()
+                    .map(|err| format!(" ({err})"))
+                    .unwrap_or_default();
+                lines.push(format!(
+                    "  {}: expected {}, got {predicted}{detail}",
+                    case.case_id,
+                    case.expected_action.as_str()
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Runs cases through the classification pipeline.
+#[derive(Clone)]
+pub struct EvalRunner {
+    llm: Arc<dyn LLMClient>,
+    prompt_builder: PromptBuilder,
+    enforcer: SafetyEnforcer,
+    pricing: PricingConfig,
+}
+
+impl EvalRunner {
+    pub fn new(llm: Arc<dyn LLMClient>, policy: PolicyConfig) -> Self {
+        Self {
+            llm,
+            prompt_builder: PromptBuilder::new(),
+            enforcer: SafetyEnforcer::new(policy),
+            pricing: PricingConfig::default(),
+        }
+    }
+
+    pub fn with_pricing(mut self, pricing: PricingConfig) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    /// Mask personal data in prompts, as classify does when redaction is enabled.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.prompt_builder = self.prompt_builder.with_redactor(redactor);
+        self
+    }
+
+    pub async fn run(&self, cases: &[EvalCase]) -> EvalReport {
+        let mut results = Vec::with_capacity(cases.len());
+        for case in cases {
+            results.push(self.run_case(case).await);
+        }
+        EvalReport::from_results(results)
+    }
+
+    pub async fn run_case(&self, case: &EvalCase) -> EvalCaseResult {
+        let message = case.message.to_message(&case.id);
+        // Same request as classify, without account-specific context
+        let (messages, redactions) = self
+            .prompt_builder
+            .build_with_redactions(&message, &PromptContext::default());
+        let request = CompletionRequest {
+            messages,
+            temperature: 0.2,
+            max_tokens: 2048,
+            json_mode: false,
+            model: None,
+            tools: vec![build_decision_tool()],
+        };
+        let mut context = LlmCallContext::new(EVAL_FEATURE);
+        context.message_id = Some(case.id.clone());
+        context.redactions = redactions.counts().clone();
+
+        let mut result = EvalCaseResult {
+            case_id: case.id.clone(),
+            expected_action: case.expected_action,
+            predicted_action: None,
+            confidence: None,
+            correct: false,
+            acceptable: false,
+            labels_correct: (!case.expected_labels.is_empty()).then_some(false),
+            needs_approval: false,
+            model: None,
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd: None,
+            error: None,
+        };
+
+        let response = match self.llm.complete(request, context).await {
+            Ok(response) => response,
+            Err(err) => {
+                result.error = Some(err.to_string());
+                return result;
+            }
+        };
+        result.cost_usd = self.pricing.cost_usd(
+            &response.model,
+            response.input_tokens as u64,
+            response.output_tokens as u64,
+        );
+        result.model = Some(response.model.clone());
+        result.input_tokens = response.input_tokens;
+        result.output_tokens = response.output_tokens;
+
+        let decision = match DecisionOutput::parse_from_completion(&response, DECISION_TOOL_NAME) {
+            Ok(decision) => decision,
+            Err(err) => {
+                result.error = Some(err.to_string());
+                return result;
+            }
+        };
+        let action = decision.decision.action;
+        result.predicted_action = Some(action);
+        result.confidence = Some(decision.decision.confidence);
+        result.correct = action == case.expected_action;
+        result.acceptable = result.correct || case.acceptable_actions.contains(&action);
+        if !case.expected_labels.is_empty() {
+            let predicted = labels_in_parameters(&decision.decision.parameters);
+            result.labels_correct = Some(
+                action == ActionType::ApplyLabel
+                    && case
+                        .expected_labels
+                        .iter()
+                        .all(|label| predicted.iter().any(|p| p.eq_ignore_ascii_case(label))),
+            );
+        }
+        result.needs_approval = self.enforcer.enforce(&decision).requires_approval;
+        result
+    }
+}
+
+/// Labels named by `label` or `labels` in action parameters.
+fn labels_in_parameters(parameters: &Value) -> Vec<String> {
+    let mut labels = Vec::new();
+    if let Some(label) = parameters.get("label").and_then(Value::as_str) {
+        labels.push(label.to_string());
+    }
+    if let Some(list) = parameters.get("labels").and_then(Value::as_array) {
+        labels.extend(list.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+    labels
+}
+
+fn calibrate(cases: &[EvalCaseResult]) -> (Vec<CalibrationBucket>, f64) {
+    let mut buckets: Vec<(usize, f64, usize)> = vec![(0, 0.0, 0); CALIBRATION_BUCKETS];
+    let mut scored = 0;
+    for case in cases {
+        let Some(confidence) = case.confidence else {
+            continue;
+        };
+        let index =
+            ((confidence * CALIBRATION_BUCKETS as f64) as usize).min(CALIBRATION_BUCKETS - 1);
+        let bucket = &mut buckets[index];
+        bucket.0 += 1;
+        bucket.1 += confidence;
+        bucket.2 += usize::from(case.correct);
+        scored += 1;
+    }
+
+    let mut calibration = Vec::new();
+    let mut error = 0.0;
+    for (index, (count, confidence_sum, correct)) in buckets.into_iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let mean_confidence = confidence_sum / count as f64;
+        let accuracy = ratio(correct, count);
+        error += (count as f64 / scored as f64) * (accuracy - mean_confidence).abs();
+        calibration.push(CalibrationBucket {
+            lower: index as f64 / CALIBRATION_BUCKETS as f64,
+            upper: (index + 1) as f64 / CALIBRATION_BUCKETS as f64,
+            count,
+            mean_confidence,
+            accuracy,
+        });
+    }
+    (calibration, error)
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelPricing;
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::llm::decision::{
+        DecisionDetails, Explanations, MessageRef, TelemetryPlaceholder, UndoHint,
+    };
+    use crate::llm::types::ToolCallResult;
+
+    fn decision_response(action: ActionType, parameters: Value, confidence: f64) -> Value {
+        let output = DecisionOutput {
+            message_ref: MessageRef {
+                provider: "gmail".into(),
+                account_id: "eval".into(),
+                thread_id: "t".into(),
+                message_id: "m".into(),
+            },
+            decision: DecisionDetails {
+                action,
+                parameters,
+                confidence,
+                needs_approval: false,
+                rationale: "test".into(),
+            },
+            explanations: Explanations {
+                salient_features: vec![],
+                matched_directions: vec![],
+                considered_alternatives: vec![],
+            },
+            undo_hint: UndoHint {
+                inverse_action: ActionType::None,
+                inverse_parameters: json!({}),
+            },
+            telemetry: TelemetryPlaceholder {},
+        };
+        serde_json::to_value(output).expect("serialize")
+    }
+
+    fn case(id: &str, expected: ActionType, recorded: Option<Value>) -> EvalCase {
+        EvalCase {
+            id: id.into(),
+            message: EvalMessage {
+                from_email: Some("news@example.com".into()),
+                subject: Some(format!("Subject {id}")),
+                ..Default::default()
+            },
+            expected_action: expected,
+            expected_labels: Vec::new(),
+            acceptable_actions: Vec::new(),
+            notes: None,
+            recorded_response: recorded.map(|arguments| CompletionResponse {
+                content: String::new(),
+                model: "openai::gpt-4o-mini".into(),
+                input_tokens: 1_000,
+                output_tokens: 100,
+                latency_ms: 10,
+                llm_call_id: None,
+                tool_calls: vec![ToolCallResult {
+                    call_id: "call".into(),
+                    fn_name: DECISION_TOOL_NAME.into(),
+                    fn_arguments: arguments,
+                }],
+            }),
+        }
+    }
+
+    #[test]
+    fn parse_dataset_skips_comments_and_reports_line() {
+        let input = r#"
+# newsletters
+{"id": "a", "message": {"subject": "Weekly digest"}, "expected_action": "archive", "acceptable_actions": ["mark_read"]}
+"#;
+        let cases = parse_dataset(input).expect("parse");
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].acceptable_actions, vec![ActionType::MarkRead]);
+
+        let err = parse_dataset("{\"id\": \"a\"}\n{").unwrap_err();
+        assert!(matches!(err, EvalError::InvalidCase { line: 1, .. }));
+    }
+
+    #[tokio::test]
+    async fn replay_run_reports_accuracy_confusion_calibration_and_cost() {
+        let mut labelled = case(
+            "label",
+            ActionType::ApplyLabel,
+            Some(decision_response(
+                ActionType::ApplyLabel,
+                json!({"label": "Receipts"}),
+                0.85,
+            )),
+        );
+        labelled.expected_labels = vec!["receipts".into()];
+        let mut acceptable = case(
+            "acceptable",
+            ActionType::Archive,
+            Some(decision_response(ActionType::MarkRead, json!({}), 0.95)),
+        );
+        acceptable.acceptable_actions = vec![ActionType::MarkRead];
+        let cases = vec![
+            case(
+                "exact",
+                ActionType::Archive,
+                Some(decision_response(ActionType::Archive, json!({}), 0.95)),
+            ),
+            acceptable,
+            labelled,
+            case(
+                "dangerous",
+                ActionType::Archive,
+                Some(decision_response(ActionType::Delete, json!({}), 0.9)),
+            ),
+            case("unrecorded", ActionType::Star, None),
+        ];
+
+        let runner = EvalRunner::new(
+            Arc::new(ReplayLLMClient::from_cases(&cases)),
+            PolicyConfig::default(),
+        )
+        .with_pricing(PricingConfig {
+            models: vec![ModelPricing {
+                model: "gpt-4o-mini".into(),
+                input_per_million: 1.0,
+                output_per_million: 10.0,
+            }],
+        });
+        let report = runner.run(&cases).await;
+
+        assert_eq!(report.total, 5);
+        assert_eq!(report.correct, 2);
+        assert_eq!(report.acceptable, 3);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.needs_approval, 1);
+        assert!((report.accuracy - 0.4).abs() < 1e-9);
+        assert_eq!(report.label_accuracy, Some(1.0));
+        assert_eq!(report.confusion["archive"]["archive"], 1);
+        assert_eq!(report.confusion["archive"]["mark_read"], 1);
+        assert_eq!(report.confusion["archive"]["delete"], 1);
+        assert_eq!(report.confusion["star"][NO_DECISION], 1);
+        assert!(
+            report.cases[4]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("no recorded response")
+        );
+
+        // 0.8-0.9 holds the labelled case, 0.9-1.0 the other three decisions
+        assert_eq!(report.calibration.len(), 2);
+        assert_eq!(report.calibration[0].count, 1);
+        assert_eq!(report.calibration[0].accuracy, 1.0);
+        assert_eq!(report.calibration[1].count, 3);
+        assert!((report.calibration[1].accuracy - 1.0 / 3.0).abs() < 1e-9);
+        assert!(report.expected_calibration_error > 0.0);
+
+        assert_eq!(report.input_tokens, 4_000);
+        assert!((report.cost_usd - 4.0 * 0.002).abs() < 1e-9);
+        assert!(report.unpriced_models.is_empty());
+        assert!(report.summary().contains("star: no_decision=1"));
+    }
+
+    #[tokio::test]
+    async fn dataset_from_feedback_uses_corrections_and_recorded_calls() {
+        use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+        use crate::decisions::{
+            ActionRepository, ActionStatus, DecisionRepository, DecisionSource, NewAction,
+            NewDecision,
+        };
+        use crate::feedback::FeedbackSource;
+        use crate::gmail::OAuthTokens;
+        use crate::llm::NewLlmCall;
+        use crate::messages::NewMessage;
+        use crate::migrations::run_migrations;
+        use crate::threads::ThreadRepository;
+        use genai::ModelIden;
+        use genai::adapter::AdapterKind;
+        use genai::chat::{ChatResponse, MessageContent, Usage};
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().expect("temp dir");
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account_id = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + chrono::Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account")
+            .id;
+        let thread_id = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thread-1",
+                None,
+                None,
+                Some(Utc::now()),
+                json!({}),
+            )
+            .await
+            .expect("create thread")
+            .id;
+        let message = MessageRepository::new(db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.clone(),
+                thread_id,
+                provider_message_id: "m1".into(),
+                from_email: Some("boss@example.com".into()),
+                from_name: None,
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some("Quarterly numbers".into()),
+                snippet: Some("see attached".into()),
+                received_at: Some(Utc::now()),
+                internal_date: Some(Utc::now()),
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: Some("Numbers inside".into()),
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("create message");
+        let decision = DecisionRepository::new(db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.clone(),
+                message_id: message.id.clone(),
+                source: DecisionSource::Llm,
+                decision_json: json!({}),
+                action_type: Some("archive".into()),
+                confidence: Some(0.9),
+                needs_approval: false,
+                rationale: None,
+                telemetry_json: json!({}),
+            })
+            .await
+            .expect("create decision");
+        let action = ActionRepository::new(db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id,
+                message_id: message.id.clone(),
+                decision_id: Some(decision.id),
+                action_type: "archive".into(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("create action");
+
+        let feedback = FeedbackRepository::new(db.clone());
+        let recorded = feedback
+            .record_from_action(&action, FeedbackSource::Undo)
+            .await
+            .expect("record")
+            .expect("feedback");
+        feedback
+            .set_correction(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &recorded.id,
+                Some("apply_label"),
+                Some(&json!({"label": "Finance"})),
+            )
+            .await
+            .expect("correct");
+
+        let mut context = LlmCallContext::new(CLASSIFY_FEATURE);
+        context.message_id = Some(message.id.clone());
+        let model = ModelIden::new(AdapterKind::OpenAI, "gpt-4o-mini");
+        let chat_response = ChatResponse {
+            content: MessageContent::from_text("done"),
+            reasoning_content: None,
+            model_iden: model.clone(),
+            provider_model_iden: model,
+            usage: Usage {
+                prompt_tokens: Some(120),
+                completion_tokens: Some(30),
+                ..Default::default()
+            },
+            captured_raw_body: None,
+        };
+        LlmCallRepository::new(db.clone())
+            .create(NewLlmCall {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                context,
+                model: "openai::gpt-4o-mini".into(),
+                request_json: json!({}),
+                response_json: Some(serde_json::to_value(&chat_response).expect("serialize")),
+                input_tokens: Some(120),
+                output_tokens: Some(30),
+                latency_ms: Some(80),
+                error: None,
+                trace_id: None,
+            })
+            .await
+            .expect("log call");
+
+        let cases = dataset_from_feedback(&db, DEFAULT_ORG_ID, DEFAULT_USER_ID, 50)
+            .await
+            .expect("dataset");
+        assert_eq!(cases.len(), 1);
+        let case = &cases[0];
+        assert_eq!(case.id, message.id);
+        assert_eq!(case.expected_action, ActionType::ApplyLabel);
+        assert_eq!(case.expected_labels, vec!["Finance".to_string()]);
+        assert_eq!(case.message.subject.as_deref(), Some("Quarterly numbers"));
+        assert_eq!(case.notes.as_deref(), Some("undo of archive"));
+        let response = case.recorded_response.as_ref().expect("recorded");
+        assert_eq!(response.content, "done");
+        assert_eq!(response.input_tokens, 120);
+        assert_eq!(response.model, "openai::gpt-4o-mini");
+
+        let path = dir.path().join("golden.jsonl");
+        write_dataset(&path, &cases).expect("write");
+        assert_eq!(load_dataset(&path).expect("load"), cases);
+    }
+}
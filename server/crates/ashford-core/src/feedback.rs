@@ -0,0 +1,722 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use libsql::{Row, params};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::db::{Database, DbError};
+use crate::decisions::Action;
+use crate::messages::{Message, MessageError, MessageRepository};
+use crate::rules::conditions::extract_domain;
+
+const FEEDBACK_COLUMNS: &str = "id, org_id, user_id, account_id, message_id, action_id, decision_id, source, sender_email, sender_domain, subject, snippet, wrong_action_type, wrong_parameters_json, corrected_action_type, corrected_parameters_json, created_at, updated_at";
+
+/// Number of recent corrections scored when looking for relevant examples.
+const CANDIDATE_LIMIT: i64 = 200;
+
+/// Minimum subject/snippet similarity for a correction from another sender to count as relevant.
+const MIN_SIMILARITY: f64 = 0.3;
+
+/// How the user signalled that a decision was wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum FeedbackSource {
+    /// The executed action was undone.
+    Undo,
+    /// The action was rejected while awaiting approval.
+    Rejection,
+}
+
+impl FeedbackSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackSource::Undo => "undo",
+            FeedbackSource::Rejection => "rejection",
+        }
+    }
+}
+
+impl std::str::FromStr for FeedbackSource {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "undo" => Ok(FeedbackSource::Undo),
+            "rejection" => Ok(FeedbackSource::Rejection),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+/// A correction to a classifier decision, kept as a few-shot example for later prompts.
+///
+/// Stores the message features the decision was based on, the action that turned out to be
+/// wrong and, when known, the action the user wanted instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ClassificationFeedback {
+    pub id: String,
+    #[ts(type = "number")]
+    pub org_id: i64,
+    #[ts(type = "number")]
+    pub user_id: i64,
+    pub account_id: String,
+    pub message_id: String,
+    /// The undone or rejected action.
+    pub action_id: String,
+    pub decision_id: Option<String>,
+    pub source: FeedbackSource,
+    pub sender_email: Option<String>,
+    pub sender_domain: Option<String>,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+    pub wrong_action_type: String,
+    #[ts(type = "Record<string, unknown>")]
+    pub wrong_parameters_json: Value,
+    pub corrected_action_type: Option<String>,
+    #[ts(type = "Record<string, unknown> | null")]
+    pub corrected_parameters_json: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Error)]
+pub enum FeedbackError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("datetime parse error: {0}")]
+    DateTimeParse(#[from] chrono::ParseError),
+    #[error("message error: {0}")]
+    Message(#[from] MessageError),
+    #[error("invalid feedback source {0}")]
+    InvalidSource(String),
+    #[error("feedback not found: {0}")]
+    NotFound(String),
+}
+
+#[derive(Clone)]
+pub struct FeedbackRepository {
+    db: Database,
+}
+
+impl FeedbackRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Record that `action` was wrong. Only actions that came from a decision are recorded;
+    /// returns `None` for others. Recording the same action twice keeps the first entry.
+    pub async fn record_from_action(
+        &self,
+        action: &Action,
+        source: FeedbackSource,
+    ) -> Result<Option<ClassificationFeedback>, FeedbackError> {
+        if action.decision_id.is_none() {
+            return Ok(None);
+        }
+
+        let message = MessageRepository::new(self.db.clone())
+            .get_by_id(action.org_id, action.user_id, &action.message_id)
+            .await?;
+        let sender_email = message.from_email.as_deref().map(str::to_lowercase);
+        let sender_domain = sender_email
+            .as_deref()
+            .and_then(extract_domain)
+            .map(str::to_string);
+        let now = now_rfc3339();
+
+        let conn = self.db.connection().await?;
+        conn.execute(
+            "INSERT INTO classification_feedback (id, org_id, user_id, account_id, message_id, action_id, decision_id, source, sender_email, sender_domain, subject, snippet, wrong_action_type, wrong_parameters_json, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?15)
+             ON CONFLICT(action_id) DO NOTHING",
+            params![
+                Uuid::new_v4().to_string(),
+                action.org_id,
+                action.user_id,
+                action.account_id.as_str(),
+                action.message_id.as_str(),
+                action.id.as_str(),
+                action.decision_id.as_deref(),
+                source.as_str(),
+                sender_email,
+                sender_domain,
+                message.subject.clone(),
+                message.snippet.clone(),
+                action.action_type.as_str(),
+                serde_json::to_string(&action.parameters_json)?,
+                now
+            ],
+        )
+        .await?;
+
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {FEEDBACK_COLUMNS} FROM classification_feedback
+                     WHERE org_id = ?1 AND user_id = ?2 AND action_id = ?3"
+                ),
+                params![action.org_id, action.user_id, action.id.as_str()],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => Ok(Some(row_to_feedback(row)?)),
+            None => Err(FeedbackError::NotFound(action.id.clone())),
+        }
+    }
+
+    pub async fn get_by_id(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<ClassificationFeedback, FeedbackError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {FEEDBACK_COLUMNS} FROM classification_feedback
+                     WHERE org_id = ?1 AND user_id = ?2 AND id = ?3"
+                ),
+                params![org_id, user_id, id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_feedback(row),
+            None => Err(FeedbackError::NotFound(id.to_string())),
+        }
+    }
+
+    /// List corrections, newest first.
+    pub async fn list_recent(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ClassificationFeedback>, FeedbackError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {FEEDBACK_COLUMNS} FROM classification_feedback
+                     WHERE org_id = ?1 AND user_id = ?2
+                     ORDER BY created_at DESC, id
+                     LIMIT ?3 OFFSET ?4"
+                ),
+                params![org_id, user_id, limit, offset],
+            )
+            .await?;
+
+        let mut feedback = Vec::new();
+        while let Some(row) = rows.next().await? {
+            feedback.push(row_to_feedback(row)?);
+        }
+        Ok(feedback)
+    }
+
+    pub async fn count(&self, org_id: i64, user_id: i64) -> Result<i64, FeedbackError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) FROM classification_feedback WHERE org_id = ?1 AND user_id = ?2",
+                params![org_id, user_id],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => Ok(row.get(0)?),
+            None => Ok(0),
+        }
+    }
+
+    /// Set or clear the action the user wanted instead of the wrong one.
+    pub async fn set_correction(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+        corrected_action_type: Option<&str>,
+        corrected_parameters: Option<&Value>,
+    ) -> Result<ClassificationFeedback, FeedbackError> {
+        let corrected_parameters = corrected_parameters
+            .map(serde_json::to_string)
+            .transpose()?;
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "UPDATE classification_feedback
+                     SET corrected_action_type = ?1, corrected_parameters_json = ?2, updated_at = ?3
+                     WHERE org_id = ?4 AND user_id = ?5 AND id = ?6
+                     RETURNING {FEEDBACK_COLUMNS}"
+                ),
+                params![
+                    corrected_action_type,
+                    corrected_parameters,
+                    now_rfc3339(),
+                    org_id,
+                    user_id,
+                    id
+                ],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_feedback(row),
+            None => Err(FeedbackError::NotFound(id.to_string())),
+        }
+    }
+
+    pub async fn delete(&self, org_id: i64, user_id: i64, id: &str) -> Result<(), FeedbackError> {
+        let conn = self.db.connection().await?;
+        let affected = conn
+            .execute(
+                "DELETE FROM classification_feedback WHERE org_id = ?1 AND user_id = ?2 AND id = ?3",
+                params![org_id, user_id, id],
+            )
+            .await?;
+        if affected == 0 {
+            return Err(FeedbackError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Delete corrections recorded before `cutoff`. Returns how many were removed.
+    pub async fn prune_older_than(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, FeedbackError> {
+        let conn = self.db.connection().await?;
+        let affected = conn
+            .execute(
+                "DELETE FROM classification_feedback
+                 WHERE org_id = ?1 AND user_id = ?2 AND created_at < ?3",
+                params![org_id, user_id, to_rfc3339(cutoff)],
+            )
+            .await?;
+        Ok(affected)
+    }
+
+    /// Find the corrections most relevant to `message`, best first.
+    ///
+    /// Corrections for the same sender rank highest, then the same domain; corrections from
+    /// other senders are only used when their subject and snippet are similar enough. Ties go
+    /// to the newest correction.
+    pub async fn find_relevant(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        message: &Message,
+        limit: usize,
+    ) -> Result<Vec<ClassificationFeedback>, FeedbackError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let sender_email = message.from_email.as_deref().map(str::to_lowercase);
+        let sender_domain = sender_email
+            .as_deref()
+            .and_then(extract_domain)
+            .map(str::to_string);
+
+        // Same-sender and same-domain corrections are always candidates, however old
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {FEEDBACK_COLUMNS} FROM classification_feedback
+                     WHERE org_id = ?1 AND user_id = ?2
+                     ORDER BY (sender_email IS ?3) DESC, (sender_domain IS ?4) DESC, created_at DESC
+                     LIMIT ?5"
+                ),
+                params![
+                    org_id,
+                    user_id,
+                    sender_email.clone(),
+                    sender_domain.clone(),
+                    CANDIDATE_LIMIT
+                ],
+            )
+            .await?;
+
+        let message_tokens = tokens(message.subject.as_deref(), message.snippet.as_deref());
+        let mut scored = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let feedback = row_to_feedback(row)?;
+            let similarity = jaccard(
+                &message_tokens,
+                &tokens(feedback.subject.as_deref(), feedback.snippet.as_deref()),
+            );
+            let score = if sender_email.is_some() && feedback.sender_email == sender_email {
+                3.0 + similarity
+            } else if sender_domain.is_some() && feedback.sender_domain == sender_domain {
+                2.0 + similarity
+            } else if similarity >= MIN_SIMILARITY {
+                similarity
+            } else {
+                continue;
+            };
+            scored.push((score, feedback));
+        }
+
+        scored.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| b.created_at.cmp(&a.created_at))
+        });
+        Ok(scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, feedback)| feedback)
+            .collect())
+    }
+}
+
+/// Lowercased words of at least 3 characters from the subject and snippet.
+fn tokens(subject: Option<&str>, snippet: Option<&str>) -> HashSet<String> {
+    [subject, snippet]
+        .into_iter()
+        .flatten()
+        .flat_map(|text| text.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| word.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let intersection = a.intersection(b).count() as f64;
+    let union = a.union(b).count() as f64;
+    intersection / union
+}
+
+fn row_to_feedback(row: Row) -> Result<ClassificationFeedback, FeedbackError> {
+    let source: String = row.get(7)?;
+    let wrong_parameters_json: String = row.get(13)?;
+    let corrected_parameters_json: Option<String> = row.get(15)?;
+    let created_at: String = row.get(16)?;
+    let updated_at: String = row.get(17)?;
+
+    Ok(ClassificationFeedback {
+        id: row.get(0)?,
+        org_id: row.get(1)?,
+        user_id: row.get(2)?,
+        account_id: row.get(3)?,
+        message_id: row.get(4)?,
+        action_id: row.get(5)?,
+        decision_id: row.get(6)?,
+        source: source.parse().map_err(FeedbackError::InvalidSource)?,
+        sender_email: row.get(8)?,
+        sender_domain: row.get(9)?,
+        subject: row.get(10)?,
+        snippet: row.get(11)?,
+        wrong_action_type: row.get(12)?,
+        wrong_parameters_json: serde_json::from_str(&wrong_parameters_json)?,
+        corrected_action_type: row.get(14)?,
+        corrected_parameters_json: corrected_parameters_json
+            .map(|value| serde_json::from_str(&value))
+            .transpose()?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+    })
+}
+
+fn now_rfc3339() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn to_rfc3339(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::decisions::{
+        ActionRepository, ActionStatus, DecisionRepository, DecisionSource, NewAction, NewDecision,
+    };
+    use crate::gmail::OAuthTokens;
+    use crate::messages::NewMessage;
+    use crate::migrations::run_migrations;
+    use crate::threads::ThreadRepository;
+    use chrono::Duration;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        db: Database,
+        account_id: String,
+        thread_id: String,
+        _dir: TempDir,
+    }
+
+    async fn setup() -> Fixture {
+        let dir = TempDir::new().expect("temp dir");
+        let db_name = format!("db_{}.sqlite", Uuid::new_v4());
+        let db = Database::new(&dir.path().join(db_name))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account_id = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account")
+            .id;
+        let thread_id = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thread-1",
+                None,
+                None,
+                Some(Utc::now()),
+                json!({}),
+            )
+            .await
+            .expect("create thread")
+            .id;
+
+        Fixture {
+            db,
+            account_id,
+            thread_id,
+            _dir: dir,
+        }
+    }
+
+    async fn seed_message(fx: &Fixture, provider_id: &str, from: &str, subject: &str) -> Message {
+        MessageRepository::new(fx.db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fx.account_id.clone(),
+                thread_id: fx.thread_id.clone(),
+                provider_message_id: provider_id.to_string(),
+                from_email: Some(from.to_string()),
+                from_name: None,
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some(subject.to_string()),
+                snippet: None,
+                received_at: Some(Utc::now()),
+                internal_date: Some(Utc::now()),
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: None,
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("create message")
+    }
+
+    async fn seed_action(fx: &Fixture, message: &Message, with_decision: bool) -> Action {
+        let decision_id = if with_decision {
+            let decision = DecisionRepository::new(fx.db.clone())
+                .create(NewDecision {
+                    org_id: DEFAULT_ORG_ID,
+                    user_id: DEFAULT_USER_ID,
+                    account_id: fx.account_id.clone(),
+                    message_id: message.id.clone(),
+                    source: DecisionSource::Llm,
+                    decision_json: json!({}),
+                    action_type: Some("archive".into()),
+                    confidence: Some(0.9),
+                    needs_approval: false,
+                    rationale: None,
+                    telemetry_json: json!({}),
+                })
+                .await
+                .expect("create decision");
+            Some(decision.id)
+        } else {
+            None
+        };
+
+        ActionRepository::new(fx.db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fx.account_id.clone(),
+                message_id: message.id.clone(),
+                decision_id,
+                action_type: "archive".into(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("create action")
+    }
+
+    async fn record(fx: &Fixture, from: &str, subject: &str) -> ClassificationFeedback {
+        let message = seed_message(fx, &Uuid::new_v4().to_string(), from, subject).await;
+        let action = seed_action(fx, &message, true).await;
+        FeedbackRepository::new(fx.db.clone())
+            .record_from_action(&action, FeedbackSource::Undo)
+            .await
+            .expect("record")
+            .expect("feedback recorded")
+    }
+
+    #[tokio::test]
+    async fn record_from_action_captures_features_once() {
+        let fx = setup().await;
+        let repo = FeedbackRepository::new(fx.db.clone());
+        let message = seed_message(&fx, "m1", "Alice@Example.com", "Quarterly report").await;
+        let action = seed_action(&fx, &message, true).await;
+
+        let feedback = repo
+            .record_from_action(&action, FeedbackSource::Rejection)
+            .await
+            .expect("record")
+            .expect("feedback");
+        assert_eq!(feedback.source, FeedbackSource::Rejection);
+        assert_eq!(feedback.sender_email.as_deref(), Some("alice@example.com"));
+        assert_eq!(feedback.sender_domain.as_deref(), Some("example.com"));
+        assert_eq!(feedback.subject.as_deref(), Some("Quarterly report"));
+        assert_eq!(feedback.wrong_action_type, "archive");
+        assert!(feedback.corrected_action_type.is_none());
+
+        let again = repo
+            .record_from_action(&action, FeedbackSource::Undo)
+            .await
+            .expect("record again")
+            .expect("feedback");
+        assert_eq!(again.id, feedback.id);
+        assert_eq!(again.source, FeedbackSource::Rejection);
+        assert_eq!(
+            repo.count(DEFAULT_ORG_ID, DEFAULT_USER_ID).await.unwrap(),
+            1
+        );
+
+        let manual = seed_action(&fx, &message, false).await;
+        assert!(
+            repo.record_from_action(&manual, FeedbackSource::Undo)
+                .await
+                .expect("record manual")
+                .is_none(),
+            "actions without a decision are not classifier mistakes"
+        );
+    }
+
+    #[tokio::test]
+    async fn set_correction_delete_and_prune() {
+        let fx = setup().await;
+        let repo = FeedbackRepository::new(fx.db.clone());
+        let first = record(&fx, "alice@example.com", "Invoice").await;
+        let second = record(&fx, "bob@example.com", "Newsletter").await;
+
+        let corrected = repo
+            .set_correction(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &first.id,
+                Some("apply_label"),
+                Some(&json!({"label": "Finance"})),
+            )
+            .await
+            .expect("set correction");
+        assert_eq!(
+            corrected.corrected_action_type.as_deref(),
+            Some("apply_label")
+        );
+        assert_eq!(
+            corrected.corrected_parameters_json,
+            Some(json!({"label": "Finance"}))
+        );
+
+        repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &second.id)
+            .await
+            .expect("delete");
+        assert!(matches!(
+            repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &second.id)
+                .await,
+            Err(FeedbackError::NotFound(_))
+        ));
+
+        let pruned = repo
+            .prune_older_than(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                Utc::now() + Duration::seconds(1),
+            )
+            .await
+            .expect("prune");
+        assert_eq!(pruned, 1);
+        assert!(
+            repo.list_recent(DEFAULT_ORG_ID, DEFAULT_USER_ID, 10, 0)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn find_relevant_ranks_sender_then_domain_then_similarity() {
+        let fx = setup().await;
+        let repo = FeedbackRepository::new(fx.db.clone());
+        let similar = record(&fx, "news@other.com", "Weekly project status update").await;
+        let domain = record(&fx, "billing@example.com", "Receipt").await;
+        let sender = record(&fx, "alice@example.com", "Lunch").await;
+        record(&fx, "spam@unrelated.com", "Win a prize").await;
+
+        let message = seed_message(
+            &fx,
+            "incoming",
+            "alice@example.com",
+            "Project status update for this week",
+        )
+        .await;
+        let relevant = repo
+            .find_relevant(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message, 5)
+            .await
+            .expect("find relevant");
+        let ids: Vec<&str> = relevant.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec![&sender.id, &domain.id, &similar.id]);
+
+        let top = repo
+            .find_relevant(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message, 1)
+            .await
+            .expect("find top");
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].id, sender.id);
+    }
+}
@@ -1,22 +1,30 @@
+use std::str::FromStr;
 use std::sync::Arc;
 
 use chrono::{DateTime, Duration, Utc};
 use serde::Deserialize;
 use serde_json::{Value, json};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::accounts::{Account, AccountRepository};
 use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
-use crate::decisions::{Action, ActionRepository, ActionStatus};
+use crate::decisions::{
+    Action, ActionLinkRelationType, ActionLinkRepository, ActionRepository, ActionStatus,
+};
+use crate::feedback::{FeedbackRepository, FeedbackSource};
 use crate::gmail::types::{Header, MessagePart};
 use crate::gmail::{GmailClient, GmailClientError, NoopTokenStore};
 use crate::labels::{LabelError, LabelRepository, NewLabel};
 use crate::llm::decision::ActionType;
 use crate::messages::{Mailbox, Message, MessageRepository};
 use crate::queue::{JobQueue, QueueError};
-use crate::threads::{ThreadError, ThreadRepository};
+use crate::reply_templates::{
+    RenderedReply, ReplyTemplateError, ReplyTemplateRepository, template_variables,
+};
+use crate::threads::{MutedThreadRepository, ThreadError, ThreadRepository};
 use crate::{Job, JobError};
 
+use super::summarize_thread::{SummaryOutcome, summarize_thread};
 use super::{
     JOB_TYPE_OUTBOUND_SEND, JOB_TYPE_UNSNOOZE_GMAIL, JobDispatcher, map_account_error,
     map_action_error, map_gmail_error,
@@ -125,6 +133,26 @@ impl PreImageState {
         }
     }
 
+    /// Whether applying `action_type` would change a message in this state.
+    ///
+    /// Used for thread-targeted actions so that only the messages an action actually changes
+    /// are recorded for undo.
+    pub fn changed_by(&self, action_type: ActionType, label: Option<&str>) -> bool {
+        let has_label = |label: &str| self.labels.iter().any(|l| l == label);
+        match action_type {
+            ActionType::Archive => self.is_in_inbox,
+            ActionType::ApplyLabel => label.is_some_and(|l| !has_label(l)),
+            ActionType::RemoveLabel => label.is_some_and(has_label),
+            ActionType::MarkRead => self.is_unread,
+            ActionType::MarkUnread => !self.is_unread,
+            ActionType::Star => !self.is_starred,
+            ActionType::Unstar => self.is_starred,
+            ActionType::Trash => !self.is_in_trash,
+            ActionType::Restore => self.is_in_trash,
+            _ => false,
+        }
+    }
+
     /// Builds an undo hint JSON value with the pre-image state and inverse action info.
     ///
     /// # Arguments
@@ -846,38 +874,54 @@ async fn execute_auto_reply(
     let bcc = parse_recipients(&action.parameters_json, "bcc")?;
     let references = parse_recipients(&action.parameters_json, "references")?;
 
-    let body_plain = action
+    let explicit_subject = action
         .parameters_json
-        .get("body")
+        .get("subject")
         .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
-        .or_else(|| {
-            action
-                .parameters_json
-                .get("body_plain")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string())
-        });
-    let body_html = action
+        .map(|s| s.to_string());
+
+    let template_name = action
         .parameters_json
-        .get("body_html")
+        .get("template")
         .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let (body_plain, body_html, template_subject) = if let Some(name) = template_name {
+        let rendered = render_reply_template(dispatcher, action, message, name).await?;
+        (
+            Some(rendered.body_plain),
+            Some(rendered.body_html),
+            rendered.subject,
+        )
+    } else {
+        let body_plain = action
+            .parameters_json
+            .get("body")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .or_else(|| {
+                action
+                    .parameters_json
+                    .get("body_plain")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            });
+        let body_html = action
+            .parameters_json
+            .get("body_html")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        (body_plain, body_html, None)
+    };
 
     if body_plain.is_none() && body_html.is_none() {
         return Err(JobError::Fatal(
-            "auto_reply requires 'body' or 'body_html' content".to_string(),
+            "auto_reply requires 'body', 'body_html' or 'template' content".to_string(),
         ));
     }
 
-    let subject = reply_subject(
-        message,
-        action
-            .parameters_json
-            .get("subject")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string()),
-    );
+    // An explicit subject wins over the template's; both fall back to "Re: <subject>"
+    let subject = reply_subject(message, explicit_subject.or(template_subject));
 
     let thread_id = lookup_provider_thread_id(dispatcher, &message.thread_id).await?;
 
@@ -900,6 +944,37 @@ async fn execute_auto_reply(
     enqueue_outbound_send(dispatcher, action, payload).await
 }
 
+/// Render the stored reply template `name` for `message`, filling slots from the action's
+/// `slots` parameter.
+async fn render_reply_template(
+    dispatcher: &JobDispatcher,
+    action: &Action,
+    message: &Message,
+    name: &str,
+) -> Result<RenderedReply, JobError> {
+    let template = ReplyTemplateRepository::new(dispatcher.db.clone())
+        .get_by_name(action.org_id, action.user_id, name)
+        .await
+        .map_err(|err| match err {
+            ReplyTemplateError::NotFound(name) => {
+                JobError::Fatal(format!("auto_reply: unknown reply template '{name}'"))
+            }
+            ReplyTemplateError::Database(_) | ReplyTemplateError::Sql(_) => {
+                JobError::retryable(format!("load reply template: {err}"))
+            }
+            other => JobError::Fatal(format!("load reply template: {other}")),
+        })?;
+    let account = AccountRepository::new(dispatcher.db.clone())
+        .get_by_id(action.org_id, action.user_id, &action.account_id)
+        .await
+        .map_err(|err| map_account_error("load account for reply template", err))?;
+
+    let variables = template_variables(message, &account, action.parameters_json.get("slots"));
+    template
+        .render(&variables)
+        .map_err(|err| JobError::Fatal(format!("auto_reply: {err}")))
+}
+
 /// Execute the archive action: removes the INBOX label from the message.
 async fn execute_archive(
     gmail_client: &GmailClient<NoopTokenStore>,
@@ -1173,39 +1248,363 @@ async fn execute_action(
         return execute_delete(gmail_client, provider_message_id).await;
     }
 
+    let action_type = ActionType::from_str(&action.action_type)
+        .map_err(|_| GmailClientError::UnsupportedAction(action.action_type.clone()))?;
+
     // Capture pre-image for all other actions
     let pre_image = capture_pre_image(gmail_client, provider_message_id).await?;
 
-    match action.action_type.as_str() {
-        "archive" => execute_archive(gmail_client, provider_message_id, &pre_image).await,
-        "apply_label" => {
-            execute_apply_label(
-                gmail_client,
-                provider_message_id,
-                &pre_image,
-                &action.parameters_json,
-            )
-            .await
+    execute_on_message(
+        gmail_client,
+        provider_message_id,
+        &pre_image,
+        action_type,
+        &action.parameters_json,
+    )
+    .await
+}
+
+/// Execute a label-style action on a single message whose pre-image is already known.
+async fn execute_on_message(
+    gmail_client: &GmailClient<NoopTokenStore>,
+    provider_message_id: &str,
+    pre_image: &PreImageState,
+    action_type: ActionType,
+    parameters: &Value,
+) -> Result<ActionExecutionResult, GmailClientError> {
+    match action_type {
+        ActionType::Archive => execute_archive(gmail_client, provider_message_id, pre_image).await,
+        ActionType::ApplyLabel => {
+            execute_apply_label(gmail_client, provider_message_id, pre_image, parameters).await
         }
-        "remove_label" => {
-            execute_remove_label(
-                gmail_client,
-                provider_message_id,
-                &pre_image,
-                &action.parameters_json,
-            )
-            .await
+        ActionType::RemoveLabel => {
+            execute_remove_label(gmail_client, provider_message_id, pre_image, parameters).await
+        }
+        ActionType::MarkRead => {
+            execute_mark_read(gmail_client, provider_message_id, pre_image).await
         }
-        "mark_read" => execute_mark_read(gmail_client, provider_message_id, &pre_image).await,
-        "mark_unread" => execute_mark_unread(gmail_client, provider_message_id, &pre_image).await,
-        "star" => execute_star(gmail_client, provider_message_id, &pre_image).await,
-        "unstar" => execute_unstar(gmail_client, provider_message_id, &pre_image).await,
-        "trash" => execute_trash(gmail_client, provider_message_id, &pre_image).await,
-        "restore" => execute_restore(gmail_client, provider_message_id, &pre_image).await,
+        ActionType::MarkUnread => {
+            execute_mark_unread(gmail_client, provider_message_id, pre_image).await
+        }
+        ActionType::Star => execute_star(gmail_client, provider_message_id, pre_image).await,
+        ActionType::Unstar => execute_unstar(gmail_client, provider_message_id, pre_image).await,
+        ActionType::Trash => execute_trash(gmail_client, provider_message_id, pre_image).await,
+        ActionType::Restore => execute_restore(gmail_client, provider_message_id, pre_image).await,
         other => {
             // Unsupported action types should fail, not silently succeed
-            Err(GmailClientError::UnsupportedAction(other.to_string()))
+            Err(GmailClientError::UnsupportedAction(
+                other.as_str().to_string(),
+            ))
+        }
+    }
+}
+
+/// Which messages an action applies to, from the optional `target` parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ActionTarget {
+    /// Only the action's own message (the default).
+    Message,
+    /// Every message in the action's Gmail thread.
+    Thread,
+}
+
+fn parse_action_target(parameters: &Value) -> Result<ActionTarget, JobError> {
+    match parameters.get("target") {
+        None | Some(Value::Null) => Ok(ActionTarget::Message),
+        Some(Value::String(target)) if target == "message" => Ok(ActionTarget::Message),
+        Some(Value::String(target)) if target == "thread" => Ok(ActionTarget::Thread),
+        Some(other) => Err(JobError::Fatal(format!(
+            "invalid target {other}, expected 'message' or 'thread'"
+        ))),
+    }
+}
+
+/// Returns the action type, inverse action and inverse parameters for an action that
+/// supports `target: "thread"`.
+fn thread_action_inverse(action: &Action) -> Result<(ActionType, ActionType, Value), JobError> {
+    let label = || {
+        action
+            .parameters_json
+            .get("label")
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                JobError::Fatal(format!(
+                    "{} action requires a non-empty 'label' parameter",
+                    action.action_type
+                ))
+            })
+    };
+
+    Ok(match action.action_type.as_str() {
+        "archive" => (
+            ActionType::Archive,
+            ActionType::ApplyLabel,
+            json!({"label": "INBOX"}),
+        ),
+        "apply_label" => (
+            ActionType::ApplyLabel,
+            ActionType::RemoveLabel,
+            json!({"label": label()?}),
+        ),
+        "remove_label" => (
+            ActionType::RemoveLabel,
+            ActionType::ApplyLabel,
+            json!({"label": label()?}),
+        ),
+        "mark_read" => (ActionType::MarkRead, ActionType::MarkUnread, json!({})),
+        "mark_unread" => (ActionType::MarkUnread, ActionType::MarkRead, json!({})),
+        "star" => (ActionType::Star, ActionType::Unstar, json!({})),
+        "unstar" => (ActionType::Unstar, ActionType::Star, json!({})),
+        "trash" => (ActionType::Trash, ActionType::Restore, json!({})),
+        "restore" => (ActionType::Restore, ActionType::Trash, json!({})),
+        other => {
+            return Err(JobError::Fatal(format!(
+                "action {other} does not support target 'thread'"
+            )));
+        }
+    })
+}
+
+/// Applies `action_type` to every message in the message's Gmail thread that it would change.
+///
+/// Returns the provider thread id and the thread undo hint described by `undo`, listing each
+/// changed message's provider id and the labels it had beforehand. Messages recorded by an
+/// earlier attempt of the same action are kept, and if a message fails the hint for the messages
+/// already changed is stored before the error is returned, so a retry never loses them.
+async fn apply_to_thread(
+    dispatcher: &JobDispatcher,
+    gmail_client: &GmailClient<NoopTokenStore>,
+    message: &Message,
+    action: &Action,
+    action_type: ActionType,
+    parameters: &Value,
+    undo: (ActionType, ActionType, Value),
+) -> Result<(String, Value), JobError> {
+    let (hint_action, inverse_action, inverse_parameters) = undo;
+    let provider_thread_id = lookup_provider_thread_id(dispatcher, &message.thread_id)
+        .await?
+        .ok_or_else(|| JobError::Fatal(format!("thread not found: {}", message.thread_id)))?;
+
+    let thread = gmail_client
+        .get_thread(&provider_thread_id)
+        .await
+        .map_err(|err| map_gmail_error("load thread", err))?;
+
+    let mut affected = recorded_thread_messages(action, &provider_thread_id);
+    let label = parameters.get("label").and_then(Value::as_str);
+    for thread_message in &thread.messages {
+        let pre_image = PreImageState::from_labels(&thread_message.label_ids);
+        if !pre_image.changed_by(action_type, label) {
+            continue;
+        }
+
+        let result = execute_on_message(
+            gmail_client,
+            &thread_message.id,
+            &pre_image,
+            action_type,
+            parameters,
+        )
+        .await;
+        if let Err(err) = result {
+            let job_error = map_gmail_error("execute gmail thread action", err);
+            if !affected.is_empty() {
+                let partial = build_thread_undo_hint(
+                    hint_action,
+                    inverse_action,
+                    inverse_parameters,
+                    &provider_thread_id,
+                    affected,
+                );
+                if let Err(store_err) = ActionRepository::new(dispatcher.db.clone())
+                    .update_undo_hint(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id, partial)
+                    .await
+                {
+                    warn!(
+                        action_id = %action.id,
+                        error = %store_err,
+                        "failed to store partial thread undo hint"
+                    );
+                }
+            }
+            return Err(job_error);
+        }
+
+        let already_recorded = affected
+            .iter()
+            .any(|m| m["provider_message_id"].as_str() == Some(thread_message.id.as_str()));
+        if !already_recorded {
+            affected.push(json!({
+                "provider_message_id": thread_message.id,
+                "pre_labels": pre_image.labels,
+            }));
+        }
+    }
+
+    let undo_hint = build_thread_undo_hint(
+        hint_action,
+        inverse_action,
+        inverse_parameters,
+        &provider_thread_id,
+        affected,
+    );
+    Ok((provider_thread_id, undo_hint))
+}
+
+/// Messages a previous attempt of `action` already changed in the thread, from the partial
+/// undo hint it stored before failing.
+fn recorded_thread_messages(action: &Action, provider_thread_id: &str) -> Vec<Value> {
+    let hint = &action.undo_hint_json;
+    if hint["target"] != "thread" || hint["provider_thread_id"] != provider_thread_id {
+        return Vec::new();
+    }
+    hint["messages"].as_array().cloned().unwrap_or_default()
+}
+
+fn build_thread_undo_hint(
+    action_type: ActionType,
+    inverse_action: ActionType,
+    inverse_parameters: Value,
+    provider_thread_id: &str,
+    messages: Vec<Value>,
+) -> Value {
+    json!({
+        "action": action_type.as_str(),
+        "target": "thread",
+        "provider_thread_id": provider_thread_id,
+        "messages": messages,
+        "inverse_action": inverse_action.as_str(),
+        "inverse_parameters": inverse_parameters
+    })
+}
+
+/// Execute an action with `target: "thread"` on every message in the thread.
+///
+/// Messages the action would not change are skipped, so undo only reverts the
+/// messages this action actually touched.
+async fn execute_thread_action(
+    dispatcher: &JobDispatcher,
+    gmail_client: &GmailClient<NoopTokenStore>,
+    message: &Message,
+    action: &Action,
+) -> Result<ActionExecutionResult, JobError> {
+    let undo = thread_action_inverse(action)?;
+
+    let (_, undo_hint) = apply_to_thread(
+        dispatcher,
+        gmail_client,
+        message,
+        action,
+        undo.0,
+        &action.parameters_json,
+        undo,
+    )
+    .await?;
+
+    Ok(ActionExecutionResult { undo_hint })
+}
+
+/// Execute the mute_thread action: archives the thread and records the mute so that
+/// messages arriving in it later are archived during ingest.
+async fn execute_mute_thread(
+    dispatcher: &JobDispatcher,
+    gmail_client: &GmailClient<NoopTokenStore>,
+    message: &Message,
+    action: &Action,
+) -> Result<ActionExecutionResult, JobError> {
+    let (provider_thread_id, undo_hint) = apply_to_thread(
+        dispatcher,
+        gmail_client,
+        message,
+        action,
+        ActionType::Archive,
+        &json!({}),
+        (
+            ActionType::MuteThread,
+            ActionType::ApplyLabel,
+            json!({"label": "INBOX"}),
+        ),
+    )
+    .await?;
+
+    MutedThreadRepository::new(dispatcher.db.clone())
+        .mute(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &message.account_id,
+            &provider_thread_id,
+            Some(&message.thread_id),
+            Some(&action.id),
+        )
+        .await
+        .map_err(|err| JobError::retryable(format!("store thread mute: {err}")))?;
+
+    Ok(ActionExecutionResult { undo_hint })
+}
+
+/// Execute the summarize action: brings the rolling summary of the message's thread up to
+/// date, however short the thread. There is nothing to undo.
+async fn execute_summarize(
+    dispatcher: &JobDispatcher,
+    message: &Message,
+) -> Result<ActionExecutionResult, JobError> {
+    let message_count =
+        match summarize_thread(dispatcher, &message.account_id, &message.thread_id, true).await? {
+            SummaryOutcome::Updated(summary) => Some(summary.message_count),
+            SummaryOutcome::UpToDate => None,
+            SummaryOutcome::Skipped(reason) => {
+                return Err(JobError::Fatal(format!("summarize thread: {reason}")));
+            }
+        };
+
+    let undo_hint = json!({
+        "action": ActionType::Summarize.as_str(),
+        "thread_id": message.thread_id,
+        "summarized_message_count": message_count,
+    });
+    Ok(ActionExecutionResult { undo_hint })
+}
+
+/// When `action` is an undo requested through `POST /api/actions/{id}/undo`, record the
+/// original action as classifier feedback now that the undo has actually run. Such undos link
+/// the original as cause and the undo as effect; failures are only logged.
+async fn record_undo_feedback(dispatcher: &JobDispatcher, action: &Action) {
+    let links = match ActionLinkRepository::new(dispatcher.db.clone())
+        .get_by_effect_action_id(&action.id)
+        .await
+    {
+        Ok(links) => links,
+        Err(err) => {
+            warn!(action_id = %action.id, error = %err, "failed to load undo links");
+            return;
         }
+    };
+    let Some(link) = links
+        .into_iter()
+        .find(|link| link.relation_type == ActionLinkRelationType::UndoOf)
+    else {
+        return;
+    };
+
+    let result = match ActionRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &link.cause_action_id)
+        .await
+    {
+        Ok(original) => FeedbackRepository::new(dispatcher.db.clone())
+            .record_from_action(&original, FeedbackSource::Undo)
+            .await
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+    if let Err(err) = result {
+        warn!(
+            original_action_id = %link.cause_action_id,
+            error = %err,
+            "failed to record undo feedback"
+        );
     }
 }
 
@@ -1259,8 +1658,8 @@ pub async fn handle_action_gmail(dispatcher: &JobDispatcher, job: Job) -> Result
             );
             return Ok(());
         }
-        ActionStatus::Queued => {
-            // Mark as executing before we start
+        ActionStatus::Queued | ActionStatus::PendingExecution => {
+            // Mark as executing before we start; a delayed action's window has passed
             repo.mark_executing(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
                 .await
                 .map_err(|err| {
@@ -1275,13 +1674,20 @@ pub async fn handle_action_gmail(dispatcher: &JobDispatcher, job: Job) -> Result
     // Get the provider message ID from our internal message record
     let message = get_provider_message_id(dispatcher, &action.message_id).await?;
     let provider_message_id = &message.provider_message_id;
+    let target = parse_action_target(&action.parameters_json);
 
     // Forward and auto_reply enqueue outbound.send and leave completion to that job.
     if action.action_type == "forward" || action.action_type == "auto_reply" {
-        let result = if action.action_type == "forward" {
-            execute_forward(dispatcher, &action, &message).await
-        } else {
-            execute_auto_reply(dispatcher, &action, &message).await
+        let result = match target {
+            Ok(ActionTarget::Message) if action.action_type == "forward" => {
+                execute_forward(dispatcher, &action, &message).await
+            }
+            Ok(ActionTarget::Message) => execute_auto_reply(dispatcher, &action, &message).await,
+            Ok(ActionTarget::Thread) => Err(JobError::Fatal(format!(
+                "action {} does not support target 'thread'",
+                action.action_type
+            ))),
+            Err(err) => Err(err),
         };
 
         return match result {
@@ -1310,17 +1716,30 @@ pub async fn handle_action_gmail(dispatcher: &JobDispatcher, job: Job) -> Result
         };
     }
 
-    // Create Gmail client
-    let gmail_client = create_gmail_client(dispatcher, &payload.account_id).await?;
-
     // Execute the action and get the result
     let execution_result: Result<ActionExecutionResult, JobError> =
-        if action.action_type == "snooze" {
-            execute_snooze(dispatcher, &gmail_client, &message, &action).await
+        if action.action_type == "summarize" {
+            // Summaries are stored locally; Gmail is not touched
+            execute_summarize(dispatcher, &message).await
         } else {
-            execute_action(&gmail_client, provider_message_id, &action)
-                .await
-                .map_err(|err| map_gmail_error("execute gmail action", err))
+            let gmail_client = create_gmail_client(dispatcher, &payload.account_id).await?;
+            match target {
+                Err(err) => Err(err),
+                Ok(_) if action.action_type == "mute_thread" => {
+                    execute_mute_thread(dispatcher, &gmail_client, &message, &action).await
+                }
+                Ok(ActionTarget::Thread) => {
+                    execute_thread_action(dispatcher, &gmail_client, &message, &action).await
+                }
+                Ok(ActionTarget::Message) if action.action_type == "snooze" => {
+                    execute_snooze(dispatcher, &gmail_client, &message, &action).await
+                }
+                Ok(ActionTarget::Message) => {
+                    execute_action(&gmail_client, provider_message_id, &action)
+                        .await
+                        .map_err(|err| map_gmail_error("execute gmail action", err))
+                }
+            }
         };
 
     match execution_result {
@@ -1337,6 +1756,8 @@ pub async fn handle_action_gmail(dispatcher: &JobDispatcher, job: Job) -> Result
                 JobError::retryable(format!("failed to mark action completed: {err}"))
             })?;
 
+            record_undo_feedback(dispatcher, &action).await;
+
             info!(
                 account_id = %payload.account_id,
                 action_id = %payload.action_id,
@@ -1649,6 +2070,19 @@ mod tests {
 
     // ===== Snooze parameter parsing =====
 
+    #[test]
+    fn pre_image_state_changed_by_only_reports_real_changes() {
+        let pre_image = PreImageState::from_labels(&["INBOX".to_string(), "Work".to_string()]);
+        assert!(pre_image.changed_by(ActionType::Archive, None));
+        assert!(pre_image.changed_by(ActionType::MarkUnread, None));
+        assert!(!pre_image.changed_by(ActionType::MarkRead, None));
+        assert!(!pre_image.changed_by(ActionType::ApplyLabel, Some("Work")));
+        assert!(pre_image.changed_by(ActionType::RemoveLabel, Some("Work")));
+        assert!(!pre_image.changed_by(ActionType::RemoveLabel, None));
+        assert!(!pre_image.changed_by(ActionType::Restore, None));
+        assert!(!pre_image.changed_by(ActionType::Delete, None));
+    }
+
     #[test]
     fn parse_snooze_with_absolute_until() {
         let until = (Utc::now() + chrono::Duration::minutes(10))
@@ -3234,27 +3668,95 @@ mod tests {
         }
 
         #[tokio::test]
-        async fn handle_action_gmail_marks_failed_on_gmail_error() {
+        async fn handle_action_gmail_records_undo_feedback_once_the_undo_completes() {
             let server = MockServer::start().await;
             let api_base = format!("{}/gmail/v1/users", &server.uri());
 
-            // Return 404 for the message - it doesn't exist
             Mock::given(method("GET"))
                 .and(path("/gmail/v1/users/user@example.com/messages/msg-123"))
-                .respond_with(ResponseTemplate::new(404))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(build_gmail_message_response("msg-123", vec!["UNREAD"])),
+                )
+                .mount(&server)
+                .await;
+
+            Mock::given(method("POST"))
+                .and(path(
+                    "/gmail/v1/users/user@example.com/messages/msg-123/modify",
+                ))
+                .respond_with(ResponseTemplate::new(200).set_body_json(
+                    build_gmail_message_response("msg-123", vec!["INBOX", "UNREAD"]),
+                ))
                 .mount(&server)
                 .await;
 
             let (db, _dir) = setup_db().await;
             let (_, account_id) = setup_account(&db).await;
             let message_id = setup_message(&db, &account_id, "msg-123").await;
-            let action_id = setup_action(&db, &account_id, &message_id, "archive", json!({})).await;
+            let decision = crate::decisions::DecisionRepository::new(db.clone())
+                .create(crate::decisions::NewDecision {
+                    org_id: DEFAULT_ORG_ID,
+                    user_id: DEFAULT_USER_ID,
+                    account_id: account_id.clone(),
+                    message_id: message_id.clone(),
+                    source: crate::decisions::DecisionSource::Llm,
+                    decision_json: json!({}),
+                    action_type: Some("archive".into()),
+                    confidence: Some(0.9),
+                    needs_approval: false,
+                    rationale: None,
+                    telemetry_json: json!({}),
+                })
+                .await
+                .expect("create decision");
+            let original = ActionRepository::new(db.clone())
+                .create(NewAction {
+                    org_id: DEFAULT_ORG_ID,
+                    user_id: DEFAULT_USER_ID,
+                    account_id: account_id.clone(),
+                    message_id: message_id.clone(),
+                    decision_id: Some(decision.id),
+                    action_type: "archive".to_string(),
+                    parameters_json: json!({}),
+                    status: ActionStatus::Queued,
+                    error_message: None,
+                    executed_at: None,
+                    undo_hint_json: json!({}),
+                    trace_id: None,
+                })
+                .await
+                .expect("create original action");
+            // The API undo links the original as cause and the undo as effect
+            let undo_id = setup_action(
+                &db,
+                &account_id,
+                &message_id,
+                "apply_label",
+                json!({"label": "INBOX"}),
+            )
+            .await;
+            ActionLinkRepository::new(db.clone())
+                .create(crate::decisions::NewActionLink {
+                    cause_action_id: original.id.clone(),
+                    effect_action_id: undo_id.clone(),
+                    relation_type: ActionLinkRelationType::UndoOf,
+                })
+                .await
+                .expect("create link");
+
+            let feedback_repo = FeedbackRepository::new(db.clone());
+            let before = feedback_repo
+                .list_recent(DEFAULT_ORG_ID, DEFAULT_USER_ID, 10, 0)
+                .await
+                .expect("list feedback");
+            assert!(before.is_empty());
 
             let queue = JobQueue::new(db.clone());
             let job_id = queue
                 .enqueue(
                     JOB_TYPE,
-                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    json!({"account_id": account_id.clone(), "action_id": undo_id.clone()}),
                     None,
                     1,
                 )
@@ -3270,38 +3772,159 @@ mod tests {
             )
             .with_gmail_api_base(api_base);
 
-            // Should return a Fatal error for 404
-            let result = handle_action_gmail(&dispatcher, job).await;
-            assert!(matches!(result, Err(JobError::Fatal(_))));
+            handle_action_gmail(&dispatcher, job).await.expect("handle");
 
-            // Verify action was marked as failed
-            let action_repo = ActionRepository::new(db.clone());
-            let action = action_repo
-                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+            let feedback = feedback_repo
+                .list_recent(DEFAULT_ORG_ID, DEFAULT_USER_ID, 10, 0)
                 .await
-                .expect("get action");
-
-            assert_eq!(action.status, ActionStatus::Failed);
-            assert!(action.error_message.is_some());
+                .expect("list feedback");
+            assert_eq!(feedback.len(), 1);
+            assert_eq!(feedback[0].action_id, original.id);
+            assert_eq!(feedback[0].source, FeedbackSource::Undo);
+            assert_eq!(feedback[0].wrong_action_type, "archive");
         }
 
         #[tokio::test]
-        async fn handle_action_gmail_skips_completed_action() {
+        async fn handle_action_gmail_summarize_stores_thread_summary_without_gmail() {
             let (db, _dir) = setup_db().await;
             let (_, account_id) = setup_account(&db).await;
             let message_id = setup_message(&db, &account_id, "msg-123").await;
-            let action_id = setup_action(&db, &account_id, &message_id, "archive", json!({})).await;
+            let action_id =
+                setup_action(&db, &account_id, &message_id, "summarize", json!({})).await;
 
-            // Mark action as completed
-            let action_repo = ActionRepository::new(db.clone());
-            action_repo
-                .mark_executing(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
-                .await
-                .expect("mark executing");
-            action_repo
-                .mark_completed(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
-                .await
-                .expect("mark completed");
+            let queue = JobQueue::new(db.clone());
+            let job_id = queue
+                .enqueue(
+                    JOB_TYPE,
+                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+            let llm = Arc::new(MockLLMClient::new());
+            llm.enqueue_response(Ok(crate::llm::CompletionResponse {
+                content: String::new(),
+                model: "summary-model".into(),
+                input_tokens: 10,
+                output_tokens: 5,
+                latency_ms: 1,
+                llm_call_id: None,
+                tool_calls: vec![crate::llm::ToolCallResult {
+                    call_id: "tool-1".into(),
+                    fn_name: crate::llm::SUMMARY_TOOL_NAME.into(),
+                    fn_arguments: json!({"summary": "Sender asks for a test."}),
+                }],
+            }));
+            // No Gmail mocks: summarizing must not call the Gmail API
+            let dispatcher = JobDispatcher::new(
+                db.clone(),
+                reqwest::Client::new(),
+                llm.clone(),
+                PolicyConfig::default(),
+            )
+            .with_gmail_api_base("http://127.0.0.1:9/gmail/v1/users");
+
+            handle_action_gmail(&dispatcher, job).await.expect("handle");
+
+            let action = ActionRepository::new(db.clone())
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("get action");
+            assert_eq!(action.status, ActionStatus::Completed);
+            assert_eq!(action.undo_hint_json["action"], "summarize");
+            assert_eq!(action.undo_hint_json["summarized_message_count"], 1);
+            assert!(action.undo_hint_json.get("inverse_action").is_none());
+
+            let message = MessageRepository::new(db.clone())
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message_id)
+                .await
+                .expect("message");
+            let summary = crate::threads::ThreadSummaryRepository::new(db.clone())
+                .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message.thread_id)
+                .await
+                .expect("get summary")
+                .expect("summary stored");
+            assert_eq!(summary.summary, "Sender asks for a test.");
+            assert_eq!(llm.calls()[0].1.feature, "summarize");
+            let for_message = crate::threads::ThreadSummaryRepository::new(db.clone())
+                .get_for_message(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message_id)
+                .await
+                .expect("get summary for message");
+            assert_eq!(for_message, Some(summary));
+        }
+
+        #[tokio::test]
+        async fn handle_action_gmail_marks_failed_on_gmail_error() {
+            let server = MockServer::start().await;
+            let api_base = format!("{}/gmail/v1/users", &server.uri());
+
+            // Return 404 for the message - it doesn't exist
+            Mock::given(method("GET"))
+                .and(path("/gmail/v1/users/user@example.com/messages/msg-123"))
+                .respond_with(ResponseTemplate::new(404))
+                .mount(&server)
+                .await;
+
+            let (db, _dir) = setup_db().await;
+            let (_, account_id) = setup_account(&db).await;
+            let message_id = setup_message(&db, &account_id, "msg-123").await;
+            let action_id = setup_action(&db, &account_id, &message_id, "archive", json!({})).await;
+
+            let queue = JobQueue::new(db.clone());
+            let job_id = queue
+                .enqueue(
+                    JOB_TYPE,
+                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+            let dispatcher = JobDispatcher::new(
+                db.clone(),
+                reqwest::Client::new(),
+                Arc::new(MockLLMClient::new()),
+                PolicyConfig::default(),
+            )
+            .with_gmail_api_base(api_base);
+
+            // Should return a Fatal error for 404
+            let result = handle_action_gmail(&dispatcher, job).await;
+            assert!(matches!(result, Err(JobError::Fatal(_))));
+
+            // Verify action was marked as failed
+            let action_repo = ActionRepository::new(db.clone());
+            let action = action_repo
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("get action");
+
+            assert_eq!(action.status, ActionStatus::Failed);
+            assert!(action.error_message.is_some());
+        }
+
+        #[tokio::test]
+        async fn handle_action_gmail_skips_completed_action() {
+            let (db, _dir) = setup_db().await;
+            let (_, account_id) = setup_account(&db).await;
+            let message_id = setup_message(&db, &account_id, "msg-123").await;
+            let action_id = setup_action(&db, &account_id, &message_id, "archive", json!({})).await;
+
+            // Mark action as completed
+            let action_repo = ActionRepository::new(db.clone());
+            action_repo
+                .mark_executing(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("mark executing");
+            action_repo
+                .mark_completed(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("mark completed");
 
             let queue = JobQueue::new(db.clone());
             let job_id = queue
@@ -4561,6 +5184,128 @@ mod tests {
             assert_eq!(payload["body_plain"], json!("Thanks!"));
         }
 
+        #[tokio::test]
+        async fn handle_action_gmail_auto_reply_renders_template() {
+            let (db, _dir) = setup_db().await;
+            let (_, account_id) = setup_account(&db).await;
+            let message_id = setup_message(&db, &account_id, "msg-template").await;
+            ReplyTemplateRepository::new(db.clone())
+                .create(crate::reply_templates::NewReplyTemplate {
+                    org_id: DEFAULT_ORG_ID,
+                    user_id: DEFAULT_USER_ID,
+                    name: "Out of office".to_string(),
+                    description: None,
+                    subject: None,
+                    body: "Hi {{sender_name}},\n\nI'm away{{#if return_date}} until {{return_date}}{{/if}}.".to_string(),
+                    body_html: None,
+                    slots: vec![crate::reply_templates::TemplateSlot {
+                        name: "return_date".to_string(),
+                        description: None,
+                        required: false,
+                    }],
+                })
+                .await
+                .expect("create template");
+            let action_id = setup_action(
+                &db,
+                &account_id,
+                &message_id,
+                "auto_reply",
+                json!({
+                    "template": "out of office",
+                    "slots": {"return_date": "<Monday>"}
+                }),
+            )
+            .await;
+
+            let queue = JobQueue::new(db.clone());
+            let job_id = queue
+                .enqueue(
+                    JOB_TYPE,
+                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+            let dispatcher = JobDispatcher::new(
+                db.clone(),
+                reqwest::Client::new(),
+                Arc::new(MockLLMClient::new()),
+                PolicyConfig::default(),
+            );
+
+            handle_action_gmail(&dispatcher, job)
+                .await
+                .expect("auto reply handled");
+
+            let conn = db.connection().await.expect("conn");
+            let mut rows = conn
+                .query(
+                    "SELECT payload_json FROM jobs WHERE type = ?1",
+                    params!["outbound.send"],
+                )
+                .await
+                .expect("query jobs");
+            let row = rows.next().await.expect("row").expect("outbound send job");
+            let payload_json: String = row.get(0).expect("payload");
+            let payload: Value = serde_json::from_str(&payload_json).expect("payload json");
+
+            assert_eq!(payload["subject"], json!("Re: Test Subject"));
+            assert_eq!(
+                payload["body_plain"],
+                json!("Hi Sender,\n\nI'm away until <Monday>.")
+            );
+            assert_eq!(
+                payload["body_html"],
+                json!("<p>Hi Sender,</p>\n<p>I'm away until &lt;Monday&gt;.</p>")
+            );
+        }
+
+        #[tokio::test]
+        async fn handle_action_gmail_auto_reply_unknown_template_is_fatal() {
+            let (db, _dir) = setup_db().await;
+            let (_, account_id) = setup_account(&db).await;
+            let message_id = setup_message(&db, &account_id, "msg-no-template").await;
+            let action_id = setup_action(
+                &db,
+                &account_id,
+                &message_id,
+                "auto_reply",
+                json!({"template": "missing"}),
+            )
+            .await;
+
+            let queue = JobQueue::new(db.clone());
+            let job_id = queue
+                .enqueue(
+                    JOB_TYPE,
+                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+            let dispatcher = JobDispatcher::new(
+                db.clone(),
+                reqwest::Client::new(),
+                Arc::new(MockLLMClient::new()),
+                PolicyConfig::default(),
+            );
+
+            let err = handle_action_gmail(&dispatcher, job)
+                .await
+                .expect_err("unknown template fails");
+            assert!(
+                matches!(err, JobError::Fatal(ref msg) if msg.contains("unknown reply template")),
+                "unexpected error: {err:?}"
+            );
+        }
+
         #[tokio::test]
         async fn handle_action_gmail_auto_reply_prefers_reply_to() {
             let (db, _dir) = setup_db().await;
@@ -4770,5 +5515,345 @@ mod tests {
             assert_eq!(action.status, ActionStatus::Failed);
             assert!(action.error_message.as_ref().unwrap().contains("recipient"));
         }
+
+        fn build_gmail_thread_response(messages: Vec<(&str, Vec<&str>)>) -> serde_json::Value {
+            json!({
+                "id": "thread-123",
+                "messages": messages
+                    .into_iter()
+                    .map(|(id, labels)| build_gmail_message_response(id, labels))
+                    .collect::<Vec<_>>(),
+            })
+        }
+
+        #[tokio::test]
+        async fn handle_action_gmail_thread_target_only_changes_affected_messages() {
+            let server = MockServer::start().await;
+            let api_base = format!("{}/gmail/v1/users", &server.uri());
+
+            Mock::given(method("GET"))
+                .and(path("/gmail/v1/users/user@example.com/threads/thread-123"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(
+                    build_gmail_thread_response(vec![
+                        ("msg-123", vec!["INBOX", "UNREAD"]),
+                        ("msg-456", vec!["SENT"]),
+                        ("msg-789", vec!["INBOX"]),
+                    ]),
+                ))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            for (id, times) in [("msg-123", 1), ("msg-456", 0), ("msg-789", 1)] {
+                Mock::given(method("POST"))
+                    .and(path(format!(
+                        "/gmail/v1/users/user@example.com/messages/{id}/modify"
+                    )))
+                    .and(body_json(json!({"removeLabelIds": ["INBOX"]})))
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .set_body_json(build_gmail_message_response(id, vec![])),
+                    )
+                    .expect(times)
+                    .mount(&server)
+                    .await;
+            }
+
+            let (db, _dir) = setup_db().await;
+            let (_, account_id) = setup_account(&db).await;
+            let message_id = setup_message(&db, &account_id, "msg-123").await;
+            let action_id = setup_action(
+                &db,
+                &account_id,
+                &message_id,
+                "archive",
+                json!({"target": "thread"}),
+            )
+            .await;
+
+            let queue = JobQueue::new(db.clone());
+            let job_id = queue
+                .enqueue(
+                    JOB_TYPE,
+                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+            let dispatcher = JobDispatcher::new(
+                db.clone(),
+                reqwest::Client::new(),
+                Arc::new(MockLLMClient::new()),
+                PolicyConfig::default(),
+            )
+            .with_gmail_api_base(api_base);
+
+            handle_action_gmail(&dispatcher, job).await.expect("handle");
+
+            let action = ActionRepository::new(db.clone())
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("get action");
+            assert_eq!(action.status, ActionStatus::Completed);
+            assert_eq!(action.undo_hint_json["target"], "thread");
+            assert_eq!(action.undo_hint_json["provider_thread_id"], "thread-123");
+            assert_eq!(action.undo_hint_json["inverse_action"], "apply_label");
+            let ids: Vec<&str> = action.undo_hint_json["messages"]
+                .as_array()
+                .expect("messages")
+                .iter()
+                .map(|m| m["provider_message_id"].as_str().unwrap())
+                .collect();
+            assert_eq!(ids, vec!["msg-123", "msg-789"]);
+        }
+
+        #[tokio::test]
+        async fn handle_action_gmail_thread_target_retry_keeps_messages_changed_before_failure() {
+            let server = MockServer::start().await;
+            let api_base = format!("{}/gmail/v1/users", &server.uri());
+
+            async fn mount_thread(server: &MockServer, messages: Vec<(&str, Vec<&str>)>) {
+                Mock::given(method("GET"))
+                    .and(path("/gmail/v1/users/user@example.com/threads/thread-123"))
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .set_body_json(build_gmail_thread_response(messages)),
+                    )
+                    .mount(server)
+                    .await;
+            }
+
+            async fn mount_modify(server: &MockServer, id: &str, status: u16, times: u64) {
+                Mock::given(method("POST"))
+                    .and(path(format!(
+                        "/gmail/v1/users/user@example.com/messages/{id}/modify"
+                    )))
+                    .respond_with(
+                        ResponseTemplate::new(status)
+                            .set_body_json(build_gmail_message_response(id, vec![])),
+                    )
+                    .expect(times)
+                    .mount(server)
+                    .await;
+            }
+
+            // First attempt: msg-123 is archived, then Gmail fails on msg-456.
+            mount_thread(
+                &server,
+                vec![
+                    ("msg-123", vec!["INBOX", "UNREAD"]),
+                    ("msg-456", vec!["INBOX"]),
+                    ("msg-789", vec!["INBOX"]),
+                ],
+            )
+            .await;
+            mount_modify(&server, "msg-123", 200, 1).await;
+            mount_modify(&server, "msg-456", 500, 1).await;
+            mount_modify(&server, "msg-789", 200, 0).await;
+
+            let (db, _dir) = setup_db().await;
+            let (_, account_id) = setup_account(&db).await;
+            let message_id = setup_message(&db, &account_id, "msg-123").await;
+            let action_id = setup_action(
+                &db,
+                &account_id,
+                &message_id,
+                "archive",
+                json!({"target": "thread"}),
+            )
+            .await;
+
+            let queue = JobQueue::new(db.clone());
+            let job_id = queue
+                .enqueue(
+                    JOB_TYPE,
+                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+            let dispatcher = JobDispatcher::new(
+                db.clone(),
+                reqwest::Client::new(),
+                Arc::new(MockLLMClient::new()),
+                PolicyConfig::default(),
+            )
+            .with_gmail_api_base(api_base);
+
+            let err = handle_action_gmail(&dispatcher, job.clone())
+                .await
+                .expect_err("msg-456 should fail");
+            assert!(err.is_retryable());
+
+            let repo = ActionRepository::new(db.clone());
+            let action = repo
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("get action");
+            assert_eq!(action.status, ActionStatus::Executing);
+            assert_eq!(
+                action.undo_hint_json["messages"][0]["pre_labels"],
+                json!(["INBOX", "UNREAD"])
+            );
+            server.verify().await;
+
+            // Retry: msg-123 no longer looks affected, but undo must still restore it.
+            server.reset().await;
+            mount_thread(
+                &server,
+                vec![
+                    ("msg-123", vec!["UNREAD"]),
+                    ("msg-456", vec!["INBOX"]),
+                    ("msg-789", vec!["INBOX"]),
+                ],
+            )
+            .await;
+            mount_modify(&server, "msg-123", 200, 0).await;
+            mount_modify(&server, "msg-456", 200, 1).await;
+            mount_modify(&server, "msg-789", 200, 1).await;
+
+            handle_action_gmail(&dispatcher, job).await.expect("retry");
+
+            let action = repo
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("get action");
+            assert_eq!(action.status, ActionStatus::Completed);
+            let ids: Vec<&str> = action.undo_hint_json["messages"]
+                .as_array()
+                .expect("messages")
+                .iter()
+                .map(|m| m["provider_message_id"].as_str().unwrap())
+                .collect();
+            assert_eq!(ids, vec!["msg-123", "msg-456", "msg-789"]);
+            assert_eq!(
+                action.undo_hint_json["messages"][0]["pre_labels"],
+                json!(["INBOX", "UNREAD"])
+            );
+        }
+
+        #[tokio::test]
+        async fn handle_action_gmail_mute_thread_archives_and_records_mute() {
+            let server = MockServer::start().await;
+            let api_base = format!("{}/gmail/v1/users", &server.uri());
+
+            Mock::given(method("GET"))
+                .and(path("/gmail/v1/users/user@example.com/threads/thread-123"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(
+                    build_gmail_thread_response(vec![
+                        ("msg-123", vec!["INBOX"]),
+                        ("msg-456", vec!["INBOX", "UNREAD"]),
+                    ]),
+                ))
+                .mount(&server)
+                .await;
+
+            Mock::given(method("POST"))
+                .and(body_json(json!({"removeLabelIds": ["INBOX"]})))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(build_gmail_message_response("msg-123", vec![])),
+                )
+                .expect(2)
+                .mount(&server)
+                .await;
+
+            let (db, _dir) = setup_db().await;
+            let (_, account_id) = setup_account(&db).await;
+            let message_id = setup_message(&db, &account_id, "msg-123").await;
+            let action_id =
+                setup_action(&db, &account_id, &message_id, "mute_thread", json!({})).await;
+
+            let queue = JobQueue::new(db.clone());
+            let job_id = queue
+                .enqueue(
+                    JOB_TYPE,
+                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+            let dispatcher = JobDispatcher::new(
+                db.clone(),
+                reqwest::Client::new(),
+                Arc::new(MockLLMClient::new()),
+                PolicyConfig::default(),
+            )
+            .with_gmail_api_base(api_base);
+
+            handle_action_gmail(&dispatcher, job).await.expect("handle");
+
+            let action = ActionRepository::new(db.clone())
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("get action");
+            assert_eq!(action.status, ActionStatus::Completed);
+            assert_eq!(action.undo_hint_json["action"], "mute_thread");
+            assert_eq!(
+                action.undo_hint_json["messages"].as_array().map(Vec::len),
+                Some(2)
+            );
+
+            let mute = MutedThreadRepository::new(db.clone())
+                .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, "thread-123")
+                .await
+                .expect("load mute")
+                .expect("thread should be muted");
+            assert_eq!(mute.action_id.as_deref(), Some(action_id.as_str()));
+        }
+
+        #[tokio::test]
+        async fn handle_action_gmail_rejects_thread_target_for_forward() {
+            let (db, _dir) = setup_db().await;
+            let (_, account_id) = setup_account(&db).await;
+            let message_id = setup_message(&db, &account_id, "msg-123").await;
+            let action_id = setup_action(
+                &db,
+                &account_id,
+                &message_id,
+                "forward",
+                json!({"to": "someone@example.com", "target": "thread"}),
+            )
+            .await;
+
+            let queue = JobQueue::new(db.clone());
+            let job_id = queue
+                .enqueue(
+                    JOB_TYPE,
+                    json!({"account_id": account_id.clone(), "action_id": action_id.clone()}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+            let dispatcher = JobDispatcher::new(
+                db.clone(),
+                reqwest::Client::new(),
+                Arc::new(MockLLMClient::new()),
+                PolicyConfig::default(),
+            );
+
+            let err = handle_action_gmail(&dispatcher, job)
+                .await
+                .expect_err("forward cannot target a thread");
+            assert!(matches!(err, JobError::Fatal(ref msg) if msg.contains("target 'thread'")));
+
+            let action = ActionRepository::new(db.clone())
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action_id)
+                .await
+                .expect("action");
+            assert_eq!(action.status, ActionStatus::Failed);
+        }
     }
 }
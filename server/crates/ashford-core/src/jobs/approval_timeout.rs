@@ -0,0 +1,708 @@
+//! Deadlines for actions waiting for approval.
+//!
+//! When `classify` holds an action for approval and `[approval]` has a timeout for its type,
+//! an `approval.timeout` job is scheduled for the deadline and an `approval.remind` job for
+//! each reminder before it. Both do nothing once the action has left `approved_pending`.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config::{ApprovalTimeout, ApprovalTimeoutOutcome};
+use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+use crate::decisions::{
+    Action, ActionRepository, ActionStatus, DecisionError, DecisionRepository, SafetyOverride,
+};
+use crate::llm::decision::ActionType;
+use crate::queue::{JobQueue, QueueError};
+use crate::{Job, JobError};
+
+use super::classify::enqueue_follow_up_job;
+use super::digest::{describe_action, notify_discord};
+use super::pending_execution_notify::enqueue_pending_execution_notify;
+use super::{JobDispatcher, map_action_error};
+
+pub const JOB_TYPE: &str = "approval.timeout";
+pub const REMIND_JOB_TYPE: &str = "approval.remind";
+
+#[derive(Debug, Deserialize)]
+struct ApprovalTimeoutPayload {
+    action_id: String,
+}
+
+/// Apply the configured outcome to an action still waiting when its timeout expires.
+pub async fn handle_approval_timeout(dispatcher: &JobDispatcher, job: Job) -> Result<(), JobError> {
+    let Some((action, timeout)) = load_pending(dispatcher, &job).await? else {
+        return Ok(());
+    };
+    let repo = ActionRepository::new(dispatcher.db.clone());
+    let waited = format_minutes(timeout.after_minutes);
+    let held = held_for_review(dispatcher, &action).await?;
+
+    match effective_outcome(&action, timeout.outcome, held) {
+        ApprovalTimeoutOutcome::Reject => {
+            let rejected = repo
+                .update_status_if(
+                    DEFAULT_ORG_ID,
+                    DEFAULT_USER_ID,
+                    &action.id,
+                    ActionStatus::ApprovedPending,
+                    ActionStatus::Rejected,
+                    Some(format!("Approval timed out after {waited}")),
+                )
+                .await
+                .map_err(|err| map_action_error("reject timed out action", err))?;
+            if rejected.is_some() {
+                info!(action_id = %action.id, "approval timed out; action rejected");
+            }
+        }
+        ApprovalTimeoutOutcome::Approve => {
+            // Approved actions honor the execution delay like any other automatic run
+            let execute_at = dispatcher
+                .execution_delay_config
+                .delay_for(&action.action_type)
+                .map(|delay| Utc::now() + delay);
+            let next_status = if execute_at.is_some() {
+                ActionStatus::PendingExecution
+            } else {
+                ActionStatus::Queued
+            };
+            let approved = repo
+                .update_status_if(
+                    DEFAULT_ORG_ID,
+                    DEFAULT_USER_ID,
+                    &action.id,
+                    ActionStatus::ApprovedPending,
+                    next_status,
+                    None,
+                )
+                .await
+                .map_err(|err| map_action_error("approve timed out action", err))?;
+            if approved.is_none() {
+                // A person decided while the timeout was running
+                return Ok(());
+            }
+            enqueue_follow_up_job(
+                dispatcher,
+                false,
+                &action.account_id,
+                &action.message_id,
+                &action.id,
+                execute_at,
+            )
+            .await?;
+            if let Some(execute_at) = execute_at {
+                enqueue_pending_execution_notify(dispatcher, &action.id, execute_at).await?;
+            }
+            info!(action_id = %action.id, "approval timed out; action approved");
+        }
+        ApprovalTimeoutOutcome::Escalate => {
+            warn!(
+                action_id = %action.id,
+                action_type = %action.action_type,
+                "approval overdue after {waited}; escalating"
+            );
+            let subject = format!("Ashford approval overdue: {}", action.action_type);
+            let body = format!(
+                "{} has waited {waited} for approval and still needs a decision (action {}).",
+                describe_action(dispatcher, &action).await,
+                action.id
+            );
+            notify_discord(dispatcher, &job, &subject, &body).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Remind approvers that an action's timeout is coming up.
+pub async fn handle_approval_remind(dispatcher: &JobDispatcher, job: Job) -> Result<(), JobError> {
+    let Some((action, timeout)) = load_pending(dispatcher, &job).await? else {
+        return Ok(());
+    };
+    let remaining = (deadline(&action, timeout) - Utc::now())
+        .num_minutes()
+        .max(1);
+    let held = held_for_review(dispatcher, &action).await?;
+    let outcome = match effective_outcome(&action, timeout.outcome, held) {
+        ApprovalTimeoutOutcome::Reject => "be rejected",
+        ApprovalTimeoutOutcome::Approve => "run",
+        ApprovalTimeoutOutcome::Escalate => "be escalated",
+    };
+
+    info!(action_id = %action.id, remaining_minutes = remaining, "approval reminder");
+    let subject = format!("Ashford approval reminder: {}", action.action_type);
+    let body = format!(
+        "{} waits for approval and will {outcome} in {} (action {}).",
+        describe_action(dispatcher, &action).await,
+        format_minutes(remaining),
+        action.id
+    );
+    notify_discord(dispatcher, &job, &subject, &body).await
+}
+
+/// Schedule the timeout and reminders for an action that was just held for approval.
+pub(super) async fn schedule_approval_timeout(
+    dispatcher: &JobDispatcher,
+    action: &Action,
+) -> Result<(), JobError> {
+    let Some(timeout) = dispatcher.approval_config.timeout_for(&action.action_type) else {
+        return Ok(());
+    };
+    let queue = JobQueue::new(dispatcher.db.clone());
+    let payload = json!({ "action_id": action.id });
+    let deadline = deadline(action, timeout);
+
+    let mut jobs = vec![(JOB_TYPE, format!("{JOB_TYPE}:{}", action.id), deadline)];
+    for minutes in &timeout.remind_before_minutes {
+        let remind_at = deadline - Duration::minutes(*minutes);
+        if *minutes > 0 && remind_at > action.created_at {
+            jobs.push((
+                REMIND_JOB_TYPE,
+                format!("{REMIND_JOB_TYPE}:{}:{minutes}", action.id),
+                remind_at,
+            ));
+        }
+    }
+
+    for (job_type, key, not_before) in jobs {
+        match queue
+            .enqueue_scheduled(job_type, payload.clone(), Some(key), 0, not_before)
+            .await
+        {
+            Ok(_) | Err(QueueError::DuplicateIdempotency { .. }) => {}
+            Err(err) => {
+                return Err(JobError::retryable(format!("enqueue {job_type}: {err}")));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The action and its timeout, or `None` when it no longer waits or has no timeout.
+async fn load_pending<'a>(
+    dispatcher: &'a JobDispatcher,
+    job: &Job,
+) -> Result<Option<(Action, &'a ApprovalTimeout)>, JobError> {
+    let payload: ApprovalTimeoutPayload = serde_json::from_value(job.payload.clone())
+        .map_err(|err| JobError::Fatal(format!("invalid {} payload: {err}", job.job_type)))?;
+    let action = ActionRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &payload.action_id)
+        .await
+        .map_err(|err| map_action_error("load action", err))?;
+
+    if action.status != ActionStatus::ApprovedPending {
+        return Ok(None);
+    }
+    // The timeout may have been removed from the configuration since scheduling
+    Ok(dispatcher
+        .approval_config
+        .timeout_for(&action.action_type)
+        .map(|timeout| (action, timeout)))
+}
+
+/// Dangerous actions, and actions held for a reason a person has to judge, are never
+/// approved without a human, so they escalate instead.
+fn effective_outcome(
+    action: &Action,
+    outcome: ApprovalTimeoutOutcome,
+    held_for_review: bool,
+) -> ApprovalTimeoutOutcome {
+    let dangerous = ActionType::from_str(&action.action_type).map_or(true, |action_type| {
+        action_type.danger_level().requires_approval()
+    });
+    if outcome == ApprovalTimeoutOutcome::Approve && (dangerous || held_for_review) {
+        ApprovalTimeoutOutcome::Escalate
+    } else {
+        outcome
+    }
+}
+
+/// Whether the action's decision was held for more than low confidence or the
+/// `approval_always` list: a protect clause, an open circuit breaker, a suspected
+/// injection, a direction violation and the like. Unreadable overrides count as held.
+async fn held_for_review(dispatcher: &JobDispatcher, action: &Action) -> Result<bool, JobError> {
+    let Some(decision_id) = action.decision_id.as_deref() else {
+        return Ok(false);
+    };
+    let decision = match DecisionRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, decision_id)
+        .await
+    {
+        Ok(decision) => decision,
+        Err(DecisionError::NotFound(_)) => return Ok(false),
+        Err(err) => return Err(JobError::retryable(format!("load decision: {err}"))),
+    };
+    let overrides = match decision.telemetry_json.get("override_details") {
+        Some(details) => serde_json::from_value::<Vec<SafetyOverride>>(details.clone()),
+        None => Ok(Vec::new()),
+    };
+    Ok(overrides.map_or(true, |overrides| {
+        overrides.iter().any(|o| {
+            !matches!(
+                o,
+                SafetyOverride::LowConfidence { .. } | SafetyOverride::InApprovalAlwaysList
+            )
+        })
+    }))
+}
+
+fn deadline(action: &Action, timeout: &ApprovalTimeout) -> DateTime<Utc> {
+    action.created_at + Duration::minutes(timeout.after_minutes)
+}
+
+fn format_minutes(minutes: i64) -> String {
+    let (value, unit) = if minutes % 1440 == 0 {
+        (minutes / 1440, "day")
+    } else if minutes % 60 == 0 {
+        (minutes / 60, "hour")
+    } else {
+        (minutes, "minute")
+    };
+    if value == 1 {
+        format!("1 {unit}")
+    } else {
+        format!("{value} {unit}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::config::{ApprovalConfig, ExecutionDelayConfig};
+    use crate::decisions::{DecisionSource, NewAction, NewDecision};
+    use crate::gmail::OAuthTokens;
+    use crate::jobs::JOB_TYPE_ACTION_GMAIL;
+    use crate::messages::{Mailbox, MessageRepository, NewMessage};
+    use crate::migrations::run_migrations;
+    use crate::threads::ThreadRepository;
+    use crate::{Database, llm::MockLLMClient};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    async fn setup() -> (Database, TempDir, String, String) {
+        let dir = TempDir::new().expect("temp dir");
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+        let thread = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account.id,
+                "t1",
+                Some("Invoice".into()),
+                None,
+                None,
+                json!({}),
+            )
+            .await
+            .expect("thread");
+        let message = MessageRepository::new(db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account.id.clone(),
+                thread_id: thread.id,
+                provider_message_id: "m1".into(),
+                from_email: Some("billing@vendor.com".into()),
+                from_name: None,
+                to: vec![Mailbox {
+                    email: "user@example.com".into(),
+                    name: None,
+                }],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some("Invoice".into()),
+                snippet: None,
+                received_at: Some(Utc::now()),
+                internal_date: Some(Utc::now()),
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: None,
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("message");
+        (db, dir, account.id, message.id)
+    }
+
+    async fn pending_action(
+        db: &Database,
+        account_id: &str,
+        message_id: &str,
+        action_type: &str,
+    ) -> Action {
+        ActionRepository::new(db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.into(),
+                message_id: message_id.into(),
+                decision_id: None,
+                action_type: action_type.into(),
+                parameters_json: json!({}),
+                status: ActionStatus::ApprovedPending,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("action")
+    }
+
+    fn dispatcher(db: &Database, outcome: ApprovalTimeoutOutcome) -> JobDispatcher {
+        JobDispatcher::new(
+            db.clone(),
+            reqwest::Client::new(),
+            Arc::new(MockLLMClient::new()),
+            crate::config::PolicyConfig::default(),
+        )
+        .with_approval_config(ApprovalConfig {
+            timeouts: vec![ApprovalTimeout {
+                action_types: vec![],
+                after_minutes: 120,
+                outcome,
+                remind_before_minutes: vec![30, 500],
+            }],
+        })
+    }
+
+    async fn run_timeout(dispatcher: &JobDispatcher, action: &Action) {
+        schedule_approval_timeout(dispatcher, action)
+            .await
+            .expect("schedule");
+        let job = JobQueue::new(dispatcher.db.clone())
+            .find_by_idempotency_key(&format!("{JOB_TYPE}:{}", action.id))
+            .await
+            .expect("find job")
+            .expect("timeout job");
+        handle_approval_timeout(dispatcher, job)
+            .await
+            .expect("timeout");
+    }
+
+    #[tokio::test]
+    async fn schedule_enqueues_timeout_and_reminders_before_it() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let action = pending_action(&db, &account_id, &message_id, "archive").await;
+        let dispatcher = dispatcher(&db, ApprovalTimeoutOutcome::Reject);
+
+        schedule_approval_timeout(&dispatcher, &action)
+            .await
+            .expect("schedule");
+
+        let queue = JobQueue::new(db.clone());
+        let timeout = queue
+            .find_by_idempotency_key(&format!("{JOB_TYPE}:{}", action.id))
+            .await
+            .expect("find")
+            .expect("timeout job");
+        assert_eq!(
+            timeout.not_before,
+            Some(action.created_at + Duration::minutes(120))
+        );
+        let reminder = queue
+            .find_by_idempotency_key(&format!("{REMIND_JOB_TYPE}:{}:30", action.id))
+            .await
+            .expect("find")
+            .expect("reminder job");
+        assert_eq!(
+            reminder.not_before,
+            Some(action.created_at + Duration::minutes(90))
+        );
+        // A reminder before the action existed is dropped
+        assert!(
+            queue
+                .find_by_idempotency_key(&format!("{REMIND_JOB_TYPE}:{}:500", action.id))
+                .await
+                .expect("find")
+                .is_none()
+        );
+
+        handle_approval_remind(&dispatcher, reminder)
+            .await
+            .expect("reminder");
+    }
+
+    #[tokio::test]
+    async fn timeout_rejects_pending_action() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let action = pending_action(&db, &account_id, &message_id, "archive").await;
+
+        run_timeout(&dispatcher(&db, ApprovalTimeoutOutcome::Reject), &action).await;
+
+        let action = ActionRepository::new(db.clone())
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+            .await
+            .expect("action");
+        assert_eq!(action.status, ActionStatus::Rejected);
+        assert_eq!(
+            action.error_message.as_deref(),
+            Some("Approval timed out after 2 hours")
+        );
+    }
+
+    #[tokio::test]
+    async fn timeout_approves_only_reversible_actions() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let archive = pending_action(&db, &account_id, &message_id, "archive").await;
+        let delete = pending_action(&db, &account_id, &message_id, "delete").await;
+        let dispatcher = dispatcher(&db, ApprovalTimeoutOutcome::Approve);
+
+        run_timeout(&dispatcher, &archive).await;
+        run_timeout(&dispatcher, &delete).await;
+
+        let repo = ActionRepository::new(db.clone());
+        let archive = repo
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &archive.id)
+            .await
+            .expect("archive");
+        assert_eq!(archive.status, ActionStatus::Queued);
+        assert!(
+            JobQueue::new(db.clone())
+                .find_by_idempotency_key(&format!(
+                    "{JOB_TYPE_ACTION_GMAIL}:{account_id}:{message_id}:{}",
+                    archive.id
+                ))
+                .await
+                .expect("find")
+                .is_some()
+        );
+
+        // Delete escalates and keeps waiting
+        let delete = repo
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &delete.id)
+            .await
+            .expect("delete");
+        assert_eq!(delete.status, ActionStatus::ApprovedPending);
+    }
+
+    async fn held_action(
+        db: &Database,
+        account_id: &str,
+        message_id: &str,
+        action_type: &str,
+        hold: SafetyOverride,
+    ) -> Action {
+        let decision = DecisionRepository::new(db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.into(),
+                message_id: message_id.into(),
+                source: DecisionSource::Llm,
+                decision_json: json!({}),
+                action_type: Some(action_type.into()),
+                confidence: Some(0.9),
+                needs_approval: true,
+                rationale: None,
+                telemetry_json: json!({ "override_details": [hold] }),
+            })
+            .await
+            .expect("decision");
+        ActionRepository::new(db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.into(),
+                message_id: message_id.into(),
+                decision_id: Some(decision.id),
+                action_type: action_type.into(),
+                parameters_json: json!({}),
+                status: ActionStatus::ApprovedPending,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("action")
+    }
+
+    #[tokio::test]
+    async fn timeout_escalates_actions_held_for_review() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let dispatcher = dispatcher(&db, ApprovalTimeoutOutcome::Approve);
+        let holds = [
+            SafetyOverride::ProtectedMessage {
+                clause_id: "clause-1".into(),
+                clause: "vips".into(),
+            },
+            SafetyOverride::CircuitBreakerOpen {
+                trip_id: "trip-1".into(),
+                target: "account".into(),
+            },
+            SafetyOverride::SuspectedPromptInjection {
+                patterns: vec!["ignore_instructions".into()],
+            },
+            SafetyOverride::DirectionViolation {
+                direction_id: "direction-1".into(),
+            },
+        ];
+
+        for hold in holds {
+            let action = held_action(&db, &account_id, &message_id, "trash", hold.clone()).await;
+            run_timeout(&dispatcher, &action).await;
+            let action = ActionRepository::new(db.clone())
+                .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+                .await
+                .expect("action");
+            assert_eq!(
+                action.status,
+                ActionStatus::ApprovedPending,
+                "{hold:?} should escalate"
+            );
+        }
+
+        // Low confidence alone still approves
+        let action = held_action(
+            &db,
+            &account_id,
+            &message_id,
+            "archive",
+            SafetyOverride::LowConfidence {
+                confidence: 0.4,
+                threshold: 0.7,
+                clause: None,
+            },
+        )
+        .await;
+        run_timeout(&dispatcher, &action).await;
+        let action = ActionRepository::new(db.clone())
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+            .await
+            .expect("action");
+        assert_eq!(action.status, ActionStatus::Queued);
+    }
+
+    #[tokio::test]
+    async fn timeout_approval_honors_execution_delay() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let action = pending_action(&db, &account_id, &message_id, "trash").await;
+        let dispatcher = dispatcher(&db, ApprovalTimeoutOutcome::Approve)
+            .with_execution_delay_config(ExecutionDelayConfig {
+                trash_minutes: 15,
+                ..Default::default()
+            });
+
+        run_timeout(&dispatcher, &action).await;
+
+        let action = ActionRepository::new(db.clone())
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+            .await
+            .expect("action");
+        assert_eq!(action.status, ActionStatus::PendingExecution);
+        let queue = JobQueue::new(db.clone());
+        let job = queue
+            .find_by_idempotency_key(&format!(
+                "{JOB_TYPE_ACTION_GMAIL}:{account_id}:{message_id}:{}",
+                action.id
+            ))
+            .await
+            .expect("find")
+            .expect("action job");
+        assert!(job.not_before.expect("scheduled") > Utc::now() + Duration::minutes(14));
+        assert!(
+            queue
+                .find_by_idempotency_key(&format!("pending_execution.notify:{}", action.id))
+                .await
+                .expect("find")
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn timeout_does_not_override_a_concurrent_decision() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let action = pending_action(&db, &account_id, &message_id, "archive").await;
+        let repo = ActionRepository::new(db.clone());
+        repo.update_status(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &action.id,
+            ActionStatus::Rejected,
+            None,
+            None,
+        )
+        .await
+        .expect("reject");
+
+        // The timeout read the action before the rejection landed
+        let updated = repo
+            .update_status_if(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &action.id,
+                ActionStatus::ApprovedPending,
+                ActionStatus::Queued,
+                None,
+            )
+            .await
+            .expect("conditional update");
+        assert!(updated.is_none());
+        let action = repo
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+            .await
+            .expect("action");
+        assert_eq!(action.status, ActionStatus::Rejected);
+    }
+
+    #[tokio::test]
+    async fn timeout_ignores_action_that_was_handled() {
+        let (db, _dir, account_id, message_id) = setup().await;
+        let action = pending_action(&db, &account_id, &message_id, "archive").await;
+        let dispatcher = dispatcher(&db, ApprovalTimeoutOutcome::Reject);
+        ActionRepository::new(db.clone())
+            .update_status(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &action.id,
+                ActionStatus::Queued,
+                None,
+                None,
+            )
+            .await
+            .expect("approve");
+
+        run_timeout(&dispatcher, &action).await;
+
+        let action = ActionRepository::new(db.clone())
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+            .await
+            .expect("action");
+        assert_eq!(action.status, ActionStatus::Queued);
+    }
+
+    #[test]
+    fn format_minutes_uses_largest_whole_unit() {
+        assert_eq!(format_minutes(1440), "1 day");
+        assert_eq!(format_minutes(120), "2 hours");
+        assert_eq!(format_minutes(90), "90 minutes");
+    }
+}
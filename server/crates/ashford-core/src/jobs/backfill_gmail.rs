@@ -1,14 +1,18 @@
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use serde::Deserialize;
 use tracing::{debug, info};
 
 use crate::Job;
 use crate::accounts::{AccountRepository, SyncStatus};
+use crate::config::BackfillMode;
 use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
 use crate::gmail::{GmailClient, NoopTokenStore};
-use crate::jobs::{JOB_TYPE_INGEST_GMAIL, JobDispatcher, map_account_error, map_gmail_error};
+use crate::jobs::{
+    JOB_TYPE_CLASSIFY_BATCH, JOB_TYPE_INGEST_GMAIL, JobDispatcher, map_account_error,
+    map_gmail_error,
+};
 use crate::queue::{JobQueue, QueueError};
 use crate::worker::JobError;
 
@@ -20,6 +24,9 @@ const BACKFILL_PRIORITY: i64 = -10;
 /// Priority for ingest jobs created during backfill (high priority)
 const INGEST_PRIORITY: i64 = 1;
 
+/// How long a batch classification waits for the page's ingest jobs to finish.
+const BATCH_DELAY_SECONDS: i64 = 60;
+
 #[derive(Debug, Deserialize)]
 struct BackfillPayload {
     account_id: String,
@@ -78,6 +85,16 @@ pub async fn handle_backfill_gmail(dispatcher: &JobDispatcher, job: Job) -> Resu
         enqueue_ingest_job(&queue, &payload.account_id, &msg.id).await?;
     }
 
+    // Classify the page with as few LLM requests as possible once it is ingested
+    let backfill_config = &dispatcher.backfill_config;
+    if backfill_config.is_batching()
+        && backfill_config.mode_for(&account.email) != BackfillMode::Skip
+        && !response.messages.is_empty()
+    {
+        let message_ids: Vec<&str> = response.messages.iter().map(|m| m.id.as_str()).collect();
+        enqueue_batch_job(&queue, &payload.account_id, &job.id, &message_ids).await?;
+    }
+
     info!(
         account_id = %payload.account_id,
         query = %payload.query,
@@ -113,6 +130,7 @@ async fn enqueue_ingest_job(
     let payload = serde_json::json!({
         "account_id": account_id,
         "message_id": message_id,
+        "backfill": true,
     });
     let idempotency = format!("{JOB_TYPE_INGEST_GMAIL}:{account_id}:{message_id}");
 
@@ -136,6 +154,42 @@ async fn enqueue_ingest_job(
     }
 }
 
+async fn enqueue_batch_job(
+    queue: &JobQueue,
+    account_id: &str,
+    backfill_job_id: &str,
+    message_ids: &[&str],
+) -> Result<(), JobError> {
+    let payload = serde_json::json!({
+        "account_id": account_id,
+        "provider_message_ids": message_ids,
+    });
+    let idempotency = format!("{JOB_TYPE_CLASSIFY_BATCH}:{account_id}:{backfill_job_id}");
+
+    match queue
+        .enqueue_scheduled(
+            JOB_TYPE_CLASSIFY_BATCH,
+            payload,
+            Some(idempotency),
+            0,
+            Utc::now() + Duration::seconds(BATCH_DELAY_SECONDS),
+        )
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(QueueError::DuplicateIdempotency { .. }) => {
+            debug!(
+                account_id,
+                backfill_job_id, "batch classify job already enqueued"
+            );
+            Ok(())
+        }
+        Err(err) => Err(JobError::retryable(format!(
+            "enqueue batch classify job failed: {err}"
+        ))),
+    }
+}
+
 async fn enqueue_next_page(
     queue: &JobQueue,
     account_id: &str,
@@ -399,6 +453,66 @@ mod tests {
         assert_eq!(priority, BACKFILL_PRIORITY);
     }
 
+    #[tokio::test]
+    async fn backfill_enqueues_delayed_batch_classification_for_page() {
+        let (_repo, dispatcher, queue, _dir, account_id) = setup_account().await;
+
+        let server = MockServer::start().await;
+        let api_base = format!("{}/gmail/v1/users", &server.uri());
+        let dispatcher = dispatcher
+            .with_gmail_api_base(api_base)
+            .with_backfill_config(crate::config::BackfillConfig {
+                batch_size: 10,
+                ..Default::default()
+            });
+
+        Mock::given(method("GET"))
+            .and(path("/gmail/v1/users/user@example.com/messages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "messages": [
+                    { "id": "msg-1", "threadId": "thr-1" },
+                    { "id": "msg-2", "threadId": "thr-2" }
+                ],
+                "nextPageToken": "page2token",
+                "resultSizeEstimate": 100
+            })))
+            .mount(&server)
+            .await;
+
+        let job_id = queue
+            .enqueue(
+                JOB_TYPE,
+                json!({"account_id": account_id.clone(), "query": "newer_than:7d"}),
+                None,
+                0,
+            )
+            .await
+            .expect("enqueue");
+        let job = queue.fetch_job(&job_id).await.expect("fetch job");
+
+        handle_backfill_gmail(&dispatcher, job)
+            .await
+            .expect("backfill succeeds");
+
+        let ingest = queue
+            .find_by_idempotency_key(&format!("{JOB_TYPE_INGEST_GMAIL}:{account_id}:msg-1"))
+            .await
+            .expect("lookup")
+            .expect("ingest job");
+        assert_eq!(ingest.payload["backfill"], true);
+
+        let batch = queue
+            .find_by_idempotency_key(&format!("{JOB_TYPE_CLASSIFY_BATCH}:{account_id}:{job_id}"))
+            .await
+            .expect("lookup")
+            .expect("batch job");
+        assert_eq!(
+            batch.payload,
+            json!({"account_id": account_id, "provider_message_ids": ["msg-1", "msg-2"]})
+        );
+        assert!(batch.not_before.expect("scheduled") > Utc::now());
+    }
+
     #[tokio::test]
     async fn backfill_with_page_token_uses_token() {
         let (repo, dispatcher, queue, _dir, account_id) = setup_account().await;
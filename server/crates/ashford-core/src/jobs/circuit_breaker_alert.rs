@@ -0,0 +1,204 @@
+//! Alert sent when the circuit breaker trips.
+//!
+//! The alert is always logged and, when a Discord bot is configured, posted to its channel.
+
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::accounts::AccountRepository;
+use crate::circuit_breaker::{CircuitBreakerError, CircuitBreakerRepository};
+use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+use crate::queue::{JobQueue, QueueError};
+use crate::{Job, JobError};
+
+use super::digest::notify_discord;
+use super::{JobDispatcher, map_account_error};
+
+pub const JOB_TYPE: &str = "circuit_breaker.alert";
+
+#[derive(Debug, Deserialize)]
+struct AlertPayload {
+    trip_id: String,
+}
+
+pub async fn handle_circuit_breaker_alert(
+    dispatcher: &JobDispatcher,
+    job: Job,
+) -> Result<(), JobError> {
+    let payload: AlertPayload = serde_json::from_value(job.payload.clone())
+        .map_err(|err| JobError::Fatal(format!("invalid circuit_breaker.alert payload: {err}")))?;
+
+    let trip = match CircuitBreakerRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &payload.trip_id)
+        .await
+    {
+        Ok(trip) => trip,
+        Err(CircuitBreakerError::NotFound(id)) => {
+            return Err(JobError::Fatal(format!(
+                "circuit breaker trip not found: {id}"
+            )));
+        }
+        Err(err) => {
+            return Err(JobError::retryable(format!(
+                "load circuit breaker trip: {err}"
+            )));
+        }
+    };
+    if !trip.is_open() {
+        debug!(trip_id = %trip.id, "circuit breaker already reset; not alerting");
+        return Ok(());
+    }
+
+    let account = AccountRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &trip.account_id)
+        .await
+        .map_err(|err| map_account_error("load account", err))?;
+
+    warn!(
+        trip_id = %trip.id,
+        account_id = %trip.account_id,
+        scope = trip.scope.as_str(),
+        rule_id = ?trip.rule_id,
+        action_count = trip.action_count,
+        "circuit breaker tripped: {trip}"
+    );
+
+    let subject = format!("Ashford circuit breaker tripped for {}", account.email);
+    let body = format!(
+        "The {trip}.\nNew {} actions of this {} wait for approval until the breaker is reset (trip {}).",
+        trip.action_types.join("/"),
+        trip.scope.as_str(),
+        trip.id
+    );
+    notify_discord(dispatcher, &job, &subject, &body).await
+}
+
+/// Enqueue the alert for a trip that was just recorded.
+pub(super) async fn enqueue_circuit_breaker_alert(
+    dispatcher: &JobDispatcher,
+    trip_id: &str,
+) -> Result<(), JobError> {
+    let queue = JobQueue::new(dispatcher.db.clone());
+    match queue
+        .enqueue(
+            JOB_TYPE,
+            json!({ "trip_id": trip_id }),
+            Some(format!("{JOB_TYPE}:{trip_id}")),
+            0,
+        )
+        .await
+    {
+        Ok(_) | Err(QueueError::DuplicateIdempotency { .. }) => Ok(()),
+        Err(err) => Err(JobError::retryable(format!(
+            "enqueue circuit breaker alert: {err}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, PubsubConfig};
+    use crate::circuit_breaker::TrackedAction;
+    use crate::config::{CircuitBreakerConfig, CircuitBreakerWindow, DiscordConfig};
+    use crate::gmail::OAuthTokens;
+    use crate::migrations::run_migrations;
+    use chrono::{Duration, Utc};
+    use tempfile::TempDir;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn alert_posts_trip_to_discord() {
+        let dir = TempDir::new().expect("temp dir");
+        let db = crate::Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+
+        // A zero limit trips on the first tracked action
+        let config = CircuitBreakerConfig {
+            windows: vec![CircuitBreakerWindow {
+                seconds: 60,
+                max_per_account: 0,
+                max_per_rule: 0,
+            }],
+            ..Default::default()
+        };
+        let hold = CircuitBreakerRepository::new(db.clone())
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &TrackedAction {
+                    account_id: &account.id,
+                    action_type: "trash",
+                    rule: None,
+                },
+            )
+            .await
+            .expect("check")
+            .expect("tripped");
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/channels/chan-1/messages"))
+            .and(header("authorization", "Bot bot-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "m1"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let dispatcher = JobDispatcher::new(
+            db.clone(),
+            reqwest::Client::new(),
+            std::sync::Arc::new(crate::llm::MockLLMClient::new()),
+            crate::config::PolicyConfig::default(),
+        )
+        .with_discord_api_base(server.uri())
+        .with_discord_config(DiscordConfig {
+            bot_token: "bot-token".into(),
+            channel_id: "chan-1".into(),
+            whitelist: vec![],
+        });
+
+        enqueue_circuit_breaker_alert(&dispatcher, &hold.trip.id)
+            .await
+            .expect("enqueue");
+        let queue = JobQueue::new(db.clone());
+        let job = queue
+            .find_by_idempotency_key(&format!("{JOB_TYPE}:{}", hold.trip.id))
+            .await
+            .expect("find job")
+            .expect("alert job");
+        assert_eq!(job.job_type, JOB_TYPE);
+        handle_circuit_breaker_alert(&dispatcher, job)
+            .await
+            .expect("alert posted");
+
+        let requests = server.received_requests().await.expect("requests");
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).expect("json");
+        let content = body["content"].as_str().expect("content");
+        assert!(content.starts_with("**Ashford circuit breaker tripped for user@example.com**"));
+        assert!(content.contains("account ran 1 tracked actions within 1 minute (limit 0)"));
+    }
+}
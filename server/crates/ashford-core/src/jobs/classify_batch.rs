@@ -0,0 +1,665 @@
+//! Batch classification of backfilled messages.
+//!
+//! Each backfill page enqueues one `classify.batch` job next to the per-message `classify`
+//! jobs, which wait `backfill.fallback_delay_minutes` before running. The batch job asks the
+//! LLM about up to `backfill.batch_size` messages per request and cancels the per-message job
+//! of every message it classifies. Anything it cannot classify (not ingested yet, missing or
+//! invalid in the response, a failed request) keeps its per-message job as the fallback.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, info, warn};
+
+use crate::accounts::AccountRepository;
+use crate::config::BackfillMode;
+use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+use crate::decisions::{DecisionError, DecisionRepository, DecisionSource};
+use crate::labels::{Label, LabelRepository};
+use crate::llm::LlmCallContext;
+use crate::llm::decision::{ActionType, BatchDecisionOutput, MessageRef};
+use crate::llm::prompt::{
+    BATCH_DECISION_TOOL_NAME, PromptBuilder, PromptContext, build_batch_decision_tool,
+};
+use crate::llm::redaction::Redactor;
+use crate::llm::types::CompletionRequest;
+use crate::messages::{Message, MessageError, MessageRepository};
+use crate::queue::{JobQueue, JobState};
+use crate::reply_templates::{ReplyTemplate, ReplyTemplateRepository};
+use crate::{Job, JobError};
+
+use super::classify::{
+    LLM_FEATURE, LlmInputs, SlowPathDecision, SlowPathPlan, classify_message, escalation_reason,
+    evaluate_rules, plan_slow_path, redactor, translate_label_name_in_decision,
+};
+use super::{JOB_TYPE_CLASSIFY, JobDispatcher, map_account_error};
+
+pub const JOB_TYPE: &str = "classify.batch";
+
+/// Output tokens allowed per message in a batch request.
+const MAX_TOKENS_PER_MESSAGE: u32 = 1024;
+
+#[derive(Debug, Deserialize)]
+struct BatchPayload {
+    account_id: String,
+    /// Gmail message ids of one backfill page.
+    provider_message_ids: Vec<String>,
+}
+
+/// A message waiting for an LLM decision, and its per-message classify job.
+struct PendingMessage {
+    message: Message,
+    fallback_job_id: String,
+    inputs: LlmInputs,
+}
+
+pub async fn handle_classify_batch(dispatcher: &JobDispatcher, job: Job) -> Result<(), JobError> {
+    let payload: BatchPayload = serde_json::from_value(job.payload.clone())
+        .map_err(|err| JobError::Fatal(format!("invalid classify.batch payload: {err}")))?;
+
+    let account = AccountRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &payload.account_id)
+        .await
+        .map_err(|err| map_account_error("load account", err))?;
+    let mode = dispatcher.backfill_config.mode_for(&account.email);
+    if mode == BackfillMode::Skip {
+        return Ok(());
+    }
+
+    let queue = JobQueue::new(dispatcher.db.clone());
+    let msg_repo = MessageRepository::new(dispatcher.db.clone());
+    let decision_repo = DecisionRepository::new(dispatcher.db.clone());
+
+    let mut classified = 0usize;
+    // Messages that need the LLM, grouped by the directions and rules they are judged by
+    let mut groups: Vec<(String, Vec<PendingMessage>)> = Vec::new();
+    for provider_message_id in &payload.provider_message_ids {
+        let message = match msg_repo
+            .get_by_provider_id(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &payload.account_id,
+                provider_message_id,
+            )
+            .await
+        {
+            Ok(message) => message,
+            Err(MessageError::NotFound(_)) => {
+                debug!(
+                    provider_message_id,
+                    "message not ingested yet, leaving it to fallback"
+                );
+                continue;
+            }
+            Err(err) => {
+                return Err(JobError::retryable(format!(
+                    "failed to load message: {err}"
+                )));
+            }
+        };
+
+        // Only messages whose own classify job has not started and that have no decision
+        let key = format!("{JOB_TYPE_CLASSIFY}:{}:{}", payload.account_id, message.id);
+        let fallback = queue
+            .find_by_idempotency_key(&key)
+            .await
+            .map_err(|err| JobError::retryable(format!("failed to load classify job: {err}")))?;
+        let Some(fallback) = fallback.filter(|fallback| fallback.state == JobState::Queued) else {
+            continue;
+        };
+        match decision_repo
+            .get_by_message_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message.id)
+            .await
+        {
+            Ok(_) => continue,
+            Err(DecisionError::NotFound(_)) => {}
+            Err(err) => {
+                return Err(JobError::retryable(format!(
+                    "failed to load decision: {err}"
+                )));
+            }
+        }
+
+        // Deterministic rules and cached decisions need no LLM request
+        if evaluate_rules(dispatcher, &message).await?.is_some() {
+            classify_message(dispatcher, &message, Some(mode), None).await?;
+            cancel_fallback(&queue, &fallback.id).await;
+            classified += 1;
+            continue;
+        }
+        match plan_slow_path(dispatcher, &message, &payload.account_id).await? {
+            SlowPathPlan::Decided(decision) => {
+                classify_message(dispatcher, &message, Some(mode), Some(*decision)).await?;
+                cancel_fallback(&queue, &fallback.id).await;
+                classified += 1;
+            }
+            // The per-message job checks the budget again when it runs
+            SlowPathPlan::Skip => {}
+            SlowPathPlan::CallLlm(inputs) => {
+                let pending = PendingMessage {
+                    message,
+                    fallback_job_id: fallback.id,
+                    inputs,
+                };
+                match groups
+                    .iter_mut()
+                    .find(|(fingerprint, _)| *fingerprint == pending.inputs.fingerprint)
+                {
+                    Some((_, group)) => group.push(pending),
+                    None => groups.push((pending.inputs.fingerprint.clone(), vec![pending])),
+                }
+            }
+        }
+    }
+
+    let mut batched = 0usize;
+    if !groups.is_empty() {
+        let available_labels = LabelRepository::new(dispatcher.db.clone())
+            .get_available_for_classifier(DEFAULT_ORG_ID, DEFAULT_USER_ID, &payload.account_id)
+            .await
+            .map_err(|err| JobError::retryable(format!("failed to load labels: {err}")))?;
+        let reply_templates = ReplyTemplateRepository::new(dispatcher.db.clone())
+            .list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+            .await
+            .map_err(|err| JobError::retryable(format!("failed to load reply templates: {err}")))?;
+        let redactor = redactor(dispatcher)?;
+        let batch_size = dispatcher.backfill_config.batch_size.max(1);
+
+        for (_, group) in groups {
+            let mut group = group.into_iter().peekable();
+            while group.peek().is_some() {
+                let chunk: Vec<PendingMessage> = group.by_ref().take(batch_size).collect();
+                batched += classify_chunk(
+                    dispatcher,
+                    &queue,
+                    mode,
+                    chunk,
+                    &available_labels,
+                    &reply_templates,
+                    redactor.as_ref(),
+                )
+                .await?;
+            }
+        }
+    }
+
+    info!(
+        account_id = %payload.account_id,
+        messages = payload.provider_message_ids.len(),
+        classified = classified + batched,
+        batched,
+        "batch classification finished"
+    );
+
+    Ok(())
+}
+
+/// Classify `chunk` with one LLM request. Returns how many messages were classified; the
+/// rest are left to their per-message jobs.
+async fn classify_chunk(
+    dispatcher: &JobDispatcher,
+    queue: &JobQueue,
+    mode: BackfillMode,
+    chunk: Vec<PendingMessage>,
+    available_labels: &[Label],
+    reply_templates: &[ReplyTemplate],
+    redactor: Option<&Redactor>,
+) -> Result<usize, JobError> {
+    let Some(first) = chunk.first() else {
+        return Ok(0);
+    };
+    let account_id = first.message.account_id.clone();
+
+    let mut prompt_builder = PromptBuilder::new();
+    if let Some(redactor) = redactor {
+        prompt_builder = prompt_builder.with_redactor(redactor.clone());
+    }
+    let messages: Vec<&Message> = chunk.iter().map(|pending| &pending.message).collect();
+    let (prompt, redactions) = prompt_builder.build_batch_with_redactions(
+        &messages,
+        &PromptContext {
+            directions: &first.inputs.directions,
+            llm_rules: &first.inputs.llm_rules,
+            available_labels,
+            reply_templates,
+            ..Default::default()
+        },
+    );
+
+    let request = CompletionRequest {
+        messages: prompt,
+        temperature: 0.2,
+        max_tokens: MAX_TOKENS_PER_MESSAGE * chunk.len() as u32,
+        json_mode: false,
+        model: None,
+        tools: vec![build_batch_decision_tool()],
+    };
+    let context = LlmCallContext {
+        feature: LLM_FEATURE.into(),
+        org_id: Some(DEFAULT_ORG_ID),
+        user_id: Some(DEFAULT_USER_ID),
+        account_id: Some(account_id.clone()),
+        message_id: None,
+        thread_id: None,
+        rule_name: None,
+        rule_id: None,
+        redactions: redactions.counts().clone(),
+    };
+
+    let response = match dispatcher.llm_client.complete(request, context).await {
+        Ok(response) => response,
+        Err(err) => {
+            warn!(account_id, size = chunk.len(), error = %err, "batch classification request failed");
+            return Ok(0);
+        }
+    };
+    let parsed = match BatchDecisionOutput::parse_from_completion(
+        &response,
+        BATCH_DECISION_TOOL_NAME,
+    ) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            warn!(account_id, size = chunk.len(), error = %err, "failed to parse batch decisions");
+            return Ok(0);
+        }
+    };
+
+    let mut decisions = HashMap::new();
+    for decision in parsed {
+        match decision {
+            Ok(decision) => {
+                decisions
+                    .entry(decision.message_ref.message_id.clone())
+                    .or_insert(decision);
+            }
+            Err(err) => warn!(account_id, error = %err, "invalid decision in batch response"),
+        }
+    }
+
+    let size = chunk.len();
+    let mut classified = 0;
+    for pending in chunk {
+        let message = &pending.message;
+        let Some(mut decision) = decisions.remove(&message.id) else {
+            debug!(message_id = %message.id, "no decision in batch response, leaving it to fallback");
+            continue;
+        };
+        // Unconvincing answers get the per-message path, which can escalate
+        if dispatcher.routing_config.escalation_model.is_some()
+            && let Some(reason) =
+                escalation_reason(&dispatcher.routing_config, &Ok(decision.clone()))
+        {
+            debug!(message_id = %message.id, reason, "batch decision needs escalation, leaving it to fallback");
+            continue;
+        }
+
+        decision.message_ref = MessageRef {
+            provider: "gmail".into(),
+            account_id: message.account_id.clone(),
+            thread_id: message.thread_id.clone(),
+            message_id: message.id.clone(),
+        };
+        // Outbound text is generated from the redacted prompt; put the real values back
+        redactions.restore_value(&mut decision.decision.parameters);
+        if decision.decision.action == ActionType::ApplyLabel {
+            translate_label_name_in_decision(&mut decision, available_labels);
+        }
+
+        // Same shape as the per-message path's attempts, plus the batch it came from
+        let mut telemetry = pending.inputs.telemetry;
+        telemetry.insert(
+            "llm_attempts".to_string(),
+            json!([{
+                "model": response.model,
+                "llm_call_id": response.llm_call_id,
+                "action": decision.decision.action.as_str(),
+                "confidence": decision.decision.confidence,
+            }]),
+        );
+        telemetry.insert(
+            "batch".to_string(),
+            json!({
+                "size": size,
+                "model": response.model,
+                "llm_call_id": response.llm_call_id,
+            }),
+        );
+
+        classify_message(
+            dispatcher,
+            message,
+            Some(mode),
+            Some(SlowPathDecision {
+                output: decision,
+                source: DecisionSource::Llm,
+                telemetry,
+                directions: pending.inputs.directions,
+                llm_rules: pending.inputs.llm_rules,
+            }),
+        )
+        .await?;
+        cancel_fallback(queue, &pending.fallback_job_id).await;
+        classified += 1;
+    }
+
+    Ok(classified)
+}
+
+/// Cancel a message's per-message classify job after the batch classified it. If that fails
+/// the job still finds the decision and stops.
+async fn cancel_fallback(queue: &JobQueue, job_id: &str) {
+    if let Err(err) = queue.cancel(job_id).await {
+        warn!(job_id, error = %err, "failed to cancel per-message classify job");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use crate::accounts::{AccountConfig, PubsubConfig};
+    use crate::config::{BackfillConfig, PolicyConfig};
+    use crate::decisions::ActionRepository;
+    use crate::gmail::OAuthTokens;
+    use crate::llm::decision::{
+        DecisionDetails, DecisionOutput, Explanations, TelemetryPlaceholder, UndoHint,
+    };
+    use crate::llm::types::{CompletionResponse, ToolCallResult};
+    use crate::llm::{LLMError, MockLLMClient};
+    use crate::messages::{Mailbox, NewMessage};
+    use crate::migrations::run_migrations;
+    use crate::reply_templates::NewReplyTemplate;
+    use crate::threads::ThreadRepository;
+    use chrono::{Duration, Utc};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        db: Database,
+        queue: JobQueue,
+        account_id: String,
+        _dir: TempDir,
+    }
+
+    async fn setup() -> Fixture {
+        let dir = TempDir::new().expect("temp dir");
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("db");
+        run_migrations(&db).await.expect("migrations");
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("account");
+        Fixture {
+            queue: JobQueue::new(db.clone()),
+            db,
+            account_id: account.id,
+            _dir: dir,
+        }
+    }
+
+    /// Ingest a message and enqueue its delayed per-message classify job, as a backfill does.
+    async fn ingest(fixture: &Fixture, provider_message_id: &str, subject: &str) -> Message {
+        let thread = ThreadRepository::new(fixture.db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &fixture.account_id,
+                &format!("thr-{provider_message_id}"),
+                Some(subject.into()),
+                None,
+                Some(Utc::now()),
+                json!({}),
+            )
+            .await
+            .expect("thread");
+        let message = MessageRepository::new(fixture.db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fixture.account_id.clone(),
+                thread_id: thread.id,
+                provider_message_id: provider_message_id.into(),
+                from_email: Some("news@example.com".into()),
+                from_name: None,
+                to: vec![Mailbox {
+                    email: "user@example.com".into(),
+                    name: None,
+                }],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some(subject.into()),
+                snippet: None,
+                received_at: Some(Utc::now()),
+                internal_date: Some(Utc::now()),
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: Some(format!("{subject} body")),
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("message");
+        fixture
+            .queue
+            .enqueue_scheduled(
+                JOB_TYPE_CLASSIFY,
+                json!({"account_id": fixture.account_id, "message_id": message.id, "backfill": true}),
+                Some(classify_key(fixture, &message)),
+                0,
+                Utc::now() + Duration::minutes(10),
+            )
+            .await
+            .expect("enqueue classify");
+        message
+    }
+
+    fn classify_key(fixture: &Fixture, message: &Message) -> String {
+        format!("{JOB_TYPE_CLASSIFY}:{}:{}", fixture.account_id, message.id)
+    }
+
+    async fn fallback_state(fixture: &Fixture, message: &Message) -> JobState {
+        fixture
+            .queue
+            .find_by_idempotency_key(&classify_key(fixture, message))
+            .await
+            .expect("lookup")
+            .expect("classify job")
+            .state
+    }
+
+    fn decision(message: &Message, action: ActionType, confidence: f64) -> DecisionOutput {
+        DecisionOutput {
+            message_ref: MessageRef {
+                provider: "gmail".into(),
+                account_id: message.account_id.clone(),
+                thread_id: message.thread_id.clone(),
+                message_id: message.id.clone(),
+            },
+            decision: DecisionDetails {
+                action,
+                parameters: json!({}),
+                confidence,
+                needs_approval: false,
+                rationale: "Bulk newsletter".into(),
+            },
+            explanations: Explanations {
+                salient_features: vec![],
+                matched_directions: vec![],
+                considered_alternatives: vec![],
+            },
+            undo_hint: UndoHint {
+                inverse_action: ActionType::None,
+                inverse_parameters: json!({}),
+            },
+            telemetry: TelemetryPlaceholder::default(),
+        }
+    }
+
+    async fn run_batch(fixture: &Fixture, dispatcher: &JobDispatcher, provider_ids: &[&str]) {
+        let job_id = fixture
+            .queue
+            .enqueue(
+                JOB_TYPE,
+                json!({"account_id": fixture.account_id, "provider_message_ids": provider_ids}),
+                None,
+                0,
+            )
+            .await
+            .expect("enqueue batch");
+        let job = fixture.queue.fetch_job(&job_id).await.expect("fetch");
+        handle_classify_batch(dispatcher, job).await.expect("batch");
+    }
+
+    #[tokio::test]
+    async fn batch_classifies_messages_in_one_request_and_leaves_failures_to_fallback() {
+        let fixture = setup().await;
+        let archived = ingest(&fixture, "msg-1", "Weekly digest").await;
+        let starred = ingest(&fixture, "msg-2", "Team offsite").await;
+        let invalid = ingest(&fixture, "msg-3", "Quarterly report").await;
+        let missing = ingest(&fixture, "msg-4", "Lunch?").await;
+        ReplyTemplateRepository::new(fixture.db.clone())
+            .create(NewReplyTemplate {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                name: "lunch-decline".into(),
+                description: Some("Politely decline a lunch invite".into()),
+                subject: None,
+                body: "Thanks, {{sender_first_name}}, but I can't make it.".into(),
+                body_html: None,
+                slots: vec![],
+            })
+            .await
+            .expect("create template");
+
+        let mut bad = decision(&invalid, ActionType::Archive, 0.9);
+        bad.decision.confidence = 2.0;
+        let mock_llm = Arc::new(MockLLMClient::new());
+        mock_llm.enqueue_response(Ok(CompletionResponse {
+            content: String::new(),
+            model: "openai::gpt-4o-mini".into(),
+            input_tokens: 400,
+            output_tokens: 200,
+            latency_ms: 900,
+            llm_call_id: Some("call-batch".into()),
+            tool_calls: vec![ToolCallResult {
+                call_id: "call_1".into(),
+                fn_name: BATCH_DECISION_TOOL_NAME.into(),
+                fn_arguments: json!({"decisions": [
+                    decision(&archived, ActionType::Archive, 0.95),
+                    decision(&starred, ActionType::Star, 0.9),
+                    bad,
+                ]}),
+            }],
+        }));
+        let dispatcher = JobDispatcher::new(
+            fixture.db.clone(),
+            reqwest::Client::new(),
+            mock_llm.clone(),
+            PolicyConfig::default(),
+        )
+        .with_backfill_config(BackfillConfig {
+            batch_size: 10,
+            ..Default::default()
+        });
+
+        // msg-5 was listed but is not ingested yet
+        run_batch(
+            &fixture,
+            &dispatcher,
+            &["msg-1", "msg-2", "msg-3", "msg-4", "msg-5"],
+        )
+        .await;
+
+        let calls = mock_llm.calls();
+        assert_eq!(calls.len(), 1);
+        let (request, context) = &calls[0];
+        assert_eq!(context.feature, LLM_FEATURE);
+        assert_eq!(context.message_id, None);
+        let prompt = &request.messages[1].content;
+        for message in [&archived, &starred, &invalid, &missing] {
+            assert!(prompt.contains(&format!("message_id: {}", message.id)));
+        }
+        assert!(prompt.contains("- lunch-decline: Politely decline a lunch invite"));
+
+        let decisions = DecisionRepository::new(fixture.db.clone());
+        let first = decisions
+            .get_by_message_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &archived.id)
+            .await
+            .expect("decision");
+        assert_eq!(first.source, DecisionSource::Llm);
+        assert_eq!(first.action_type.as_deref(), Some("archive"));
+        assert_eq!(
+            first.telemetry_json["batch"],
+            json!({"size": 4, "model": "openai::gpt-4o-mini", "llm_call_id": "call-batch"})
+        );
+        assert!(first.telemetry_json["rules_fingerprint"].is_string());
+        assert_eq!(first.telemetry_json["backfill"]["mode"], "full");
+        assert_eq!(
+            fallback_state(&fixture, &archived).await,
+            JobState::Canceled
+        );
+
+        let actions = ActionRepository::new(fixture.db.clone())
+            .list_by_message_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &starred.id)
+            .await
+            .expect("actions");
+        assert_eq!(actions[0].action_type, "star");
+        assert_eq!(fallback_state(&fixture, &starred).await, JobState::Canceled);
+
+        // Invalid and missing decisions keep their own classify job
+        for message in [&invalid, &missing] {
+            assert!(matches!(
+                decisions
+                    .get_by_message_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message.id)
+                    .await,
+                Err(DecisionError::NotFound(_))
+            ));
+            assert_eq!(fallback_state(&fixture, message).await, JobState::Queued);
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_failure_leaves_every_message_to_fallback() {
+        let fixture = setup().await;
+        let first = ingest(&fixture, "msg-1", "Weekly digest").await;
+        let second = ingest(&fixture, "msg-2", "Team offsite").await;
+
+        let mock_llm = Arc::new(MockLLMClient::new());
+        mock_llm.enqueue_response(Err(LLMError::ServerError("overloaded".into())));
+        let dispatcher = JobDispatcher::new(
+            fixture.db.clone(),
+            reqwest::Client::new(),
+            mock_llm.clone(),
+            PolicyConfig::default(),
+        )
+        .with_backfill_config(BackfillConfig {
+            batch_size: 10,
+            mode: BackfillMode::LabelOnly,
+            ..Default::default()
+        });
+
+        run_batch(&fixture, &dispatcher, &["msg-1", "msg-2"]).await;
+
+        assert_eq!(mock_llm.call_count(), 1);
+        assert_eq!(fallback_state(&fixture, &first).await, JobState::Queued);
+        assert_eq!(fallback_state(&fixture, &second).await, JobState::Queued);
+    }
+}
@@ -0,0 +1,622 @@
+//! Scheduled activity digests.
+//!
+//! Each account gets one `digest.send` job per configured channel and period. A job schedules
+//! the next period's job before delivering, so one failed delivery does not end the chain.
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use reqwest::header::AUTHORIZATION;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, info};
+
+use crate::accounts::AccountRepository;
+use crate::config::DigestChannel;
+use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+use crate::decisions::Action;
+use crate::digest::{DigestRepository, digest_timezone, next_digest_at};
+use crate::gmail::{EmailAddress, MimeMessage};
+use crate::messages::MessageRepository;
+use crate::queue::{JobQueue, QueueError};
+use crate::{Job, JobError};
+
+use super::action_gmail::create_gmail_client_with_account;
+use super::outbound_send::map_mime_error;
+use super::{JobDispatcher, map_account_error, map_gmail_error};
+
+pub const JOB_TYPE: &str = "digest.send";
+
+pub const DISCORD_API_BASE: &str = "https://discord.com/api/v10";
+
+/// Discord rejects messages longer than this many characters.
+const DISCORD_MESSAGE_LIMIT: usize = 2_000;
+
+/// Job step recorded for each Discord message that was delivered.
+const DISCORD_MESSAGE_STEP: &str = "discord.message";
+
+#[derive(Debug, Deserialize)]
+struct DigestPayload {
+    account_id: String,
+    channel: DigestChannel,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+}
+
+pub async fn handle_digest_send(dispatcher: &JobDispatcher, job: Job) -> Result<(), JobError> {
+    let payload: DigestPayload = serde_json::from_value(job.payload.clone())
+        .map_err(|err| JobError::Fatal(format!("invalid digest.send payload: {err}")))?;
+
+    let config = &dispatcher.digest_config;
+    if !config.enabled || !config.channels.contains(&payload.channel) {
+        debug!(
+            account_id = %payload.account_id,
+            channel = payload.channel.as_str(),
+            "digest channel disabled; skipping"
+        );
+        return Ok(());
+    }
+
+    let next_end = next_digest_at(config, payload.period_end)
+        .map_err(|err| JobError::Fatal(format!("invalid digest schedule: {err}")))?;
+    enqueue_digest(
+        &JobQueue::new(dispatcher.db.clone()),
+        &payload.account_id,
+        payload.channel,
+        payload.period_end,
+        next_end,
+    )
+    .await?;
+
+    let account = AccountRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &payload.account_id)
+        .await
+        .map_err(|err| map_account_error("load account", err))?;
+    let tz = digest_timezone(config)
+        .map_err(|err| JobError::Fatal(format!("invalid digest schedule: {err}")))?;
+
+    let digest = DigestRepository::new(dispatcher.db.clone())
+        .compile(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &payload.account_id,
+            payload.period_start,
+            payload.period_end,
+            config,
+        )
+        .await
+        .map_err(|err| JobError::retryable(format!("compile digest: {err}")))?;
+    if digest.is_empty(&config.sections) && !config.send_empty {
+        debug!(account_id = %payload.account_id, "digest is empty; not sending");
+        return Ok(());
+    }
+
+    let subject = digest.subject(&account.email, tz);
+    let body = digest.render_text(&account.email, &config.sections, tz);
+    match payload.channel {
+        DigestChannel::Email => send_email(dispatcher, &payload.account_id, subject, body).await?,
+        DigestChannel::Discord => post_to_discord(dispatcher, &job, &subject, &body).await?,
+    }
+
+    info!(
+        account_id = %payload.account_id,
+        channel = payload.channel.as_str(),
+        period_end = %payload.period_end,
+        "sent digest"
+    );
+    Ok(())
+}
+
+/// Schedule the next digest of every account and channel, for a server that just started.
+/// Periods that already have a job are left alone.
+pub async fn schedule_digests(
+    dispatcher: &JobDispatcher,
+    now: DateTime<Utc>,
+) -> Result<(), JobError> {
+    let config = &dispatcher.digest_config;
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let period_end = next_digest_at(config, now)
+        .map_err(|err| JobError::Fatal(format!("invalid digest schedule: {err}")))?;
+    let period_start = period_end - config.frequency.period();
+    let accounts = AccountRepository::new(dispatcher.db.clone())
+        .list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+        .await
+        .map_err(|err| map_account_error("list accounts", err))?;
+    let queue = JobQueue::new(dispatcher.db.clone());
+    for account in accounts {
+        for channel in &config.channels {
+            enqueue_digest(&queue, &account.id, *channel, period_start, period_end).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn enqueue_digest(
+    queue: &JobQueue,
+    account_id: &str,
+    channel: DigestChannel,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+) -> Result<(), JobError> {
+    let payload = json!({
+        "account_id": account_id,
+        "channel": channel.as_str(),
+        "period_start": period_start,
+        "period_end": period_end,
+    });
+    let idempotency_key = format!(
+        "{JOB_TYPE}:{account_id}:{}:{}",
+        channel.as_str(),
+        period_end.timestamp()
+    );
+    match queue
+        .enqueue_scheduled(JOB_TYPE, payload, Some(idempotency_key), 0, period_end)
+        .await
+    {
+        Ok(_) | Err(QueueError::DuplicateIdempotency { .. }) => Ok(()),
+        Err(err) => Err(JobError::retryable(format!("enqueue digest job: {err}"))),
+    }
+}
+
+async fn send_email(
+    dispatcher: &JobDispatcher,
+    account_id: &str,
+    subject: String,
+    body: String,
+) -> Result<(), JobError> {
+    let (account, gmail_client) = create_gmail_client_with_account(dispatcher, account_id).await?;
+    let recipient = dispatcher
+        .digest_config
+        .recipient
+        .clone()
+        .unwrap_or_else(|| account.email.clone());
+
+    let raw_message = MimeMessage {
+        from: EmailAddress::new(account.display_name.clone(), account.email.clone()),
+        to: vec![EmailAddress::from(recipient)],
+        cc: Vec::new(),
+        bcc: Vec::new(),
+        subject: Some(subject),
+        body_plain: Some(body),
+        body_html: None,
+        in_reply_to: None,
+        references: Vec::new(),
+        attachments: Vec::new(),
+    }
+    .to_base64_url()
+    .map_err(|err| map_mime_error("build digest email", err))?;
+
+    gmail_client
+        .send_message(raw_message, None)
+        .await
+        .map_err(|err| map_gmail_error("send digest email", err))?;
+    Ok(())
+}
+
+/// Post `subject` and `body` to the configured Discord channel, split into as many messages
+/// as needed.
+///
+/// Each delivered message is recorded as a finished step of `job`, so a retry after a failure
+/// part-way through resumes with the first message Discord has not accepted yet.
+pub(super) async fn post_to_discord(
+    dispatcher: &JobDispatcher,
+    job: &Job,
+    subject: &str,
+    body: &str,
+) -> Result<(), JobError> {
+    let discord = dispatcher
+        .discord_config
+        .as_ref()
+        .filter(|discord| !discord.bot_token.is_empty() && !discord.channel_id.is_empty())
+        .ok_or_else(|| JobError::Fatal("discord channel is not configured".into()))?;
+    let api_base = dispatcher
+        .discord_api_base
+        .as_deref()
+        .unwrap_or(DISCORD_API_BASE);
+    let url = format!("{api_base}/channels/{}/messages", discord.channel_id);
+
+    let queue = JobQueue::new(dispatcher.db.clone());
+    let delivered = queue
+        .count_finished_steps(&job.id, DISCORD_MESSAGE_STEP)
+        .await
+        .map_err(|err| JobError::retryable(format!("load delivered discord messages: {err}")))?;
+
+    let chunks = split_message(&format!("**{subject}**\n{body}"), DISCORD_MESSAGE_LIMIT);
+    for (index, chunk) in chunks.into_iter().enumerate().skip(delivered) {
+        let response = dispatcher
+            .http
+            .post(&url)
+            .header(AUTHORIZATION, format!("Bot {}", discord.bot_token))
+            .json(&json!({ "content": chunk }))
+            .send()
+            .await
+            .map_err(|err| JobError::retryable(format!("post to discord: {err}")))?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(JobError::retryable(format!(
+                "post to discord: http status {status}"
+            )));
+        }
+        if !status.is_success() {
+            return Err(JobError::Fatal(format!(
+                "post to discord: http status {status}"
+            )));
+        }
+
+        let record_error =
+            |err: QueueError| JobError::retryable(format!("record discord message: {err}"));
+        let step_id = queue
+            .start_step(&job.id, DISCORD_MESSAGE_STEP)
+            .await
+            .map_err(record_error)?;
+        queue
+            .finish_step(&step_id, Some(json!({ "index": index })))
+            .await
+            .map_err(record_error)?;
+    }
+    Ok(())
+}
+
+/// "archive of 'Subject' from sender", falling back to the action type alone.
+pub(super) async fn describe_action(dispatcher: &JobDispatcher, action: &Action) -> String {
+    match MessageRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.message_id)
+        .await
+    {
+        Ok(message) => format!(
+            "{} of '{}' from {}",
+            action.action_type,
+            message.subject.as_deref().unwrap_or("(no subject)"),
+            message.from_email.as_deref().unwrap_or("unknown sender")
+        ),
+        Err(_) => action.action_type.clone(),
+    }
+}
+
+/// Post to Discord when a bot is configured; the log line is the only record otherwise.
+pub(super) async fn notify_discord(
+    dispatcher: &JobDispatcher,
+    job: &Job,
+    subject: &str,
+    body: &str,
+) -> Result<(), JobError> {
+    let discord_configured = dispatcher
+        .discord_config
+        .as_ref()
+        .is_some_and(|discord| !discord.bot_token.is_empty() && !discord.channel_id.is_empty());
+    if discord_configured {
+        post_to_discord(dispatcher, job, subject, body).await?;
+    }
+    Ok(())
+}
+
+/// Split `text` into messages of at most `limit` characters, breaking between lines where
+/// possible.
+fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for line in text.lines() {
+        let mut line: Vec<char> = line.chars().collect();
+        // Lines longer than a whole message are cut at the limit
+        while line.len() > limit {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+                current_len = 0;
+            }
+            chunks.push(line.drain(..limit).collect());
+        }
+
+        let separator = usize::from(!current.is_empty());
+        if current_len + separator + line.len() > limit {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        } else if separator == 1 {
+            current.push('\n');
+            current_len += 1;
+        }
+        current_len += line.len();
+        current.extend(line);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, PubsubConfig};
+    use crate::config::{DigestConfig, DiscordConfig};
+    use crate::gmail::OAuthTokens;
+    use crate::migrations::run_migrations;
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use chrono::Duration;
+    use tempfile::TempDir;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn setup() -> (crate::Database, TempDir, String) {
+        let dir = TempDir::new().expect("temp dir");
+        let db = crate::Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                Some("User".into()),
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+        (db, dir, account.id)
+    }
+
+    fn digest_config() -> DigestConfig {
+        DigestConfig {
+            enabled: true,
+            channels: vec![DigestChannel::Email, DigestChannel::Discord],
+            send_empty: true,
+            ..Default::default()
+        }
+    }
+
+    fn dispatcher(db: crate::Database, server: &MockServer) -> JobDispatcher {
+        JobDispatcher::new(
+            db,
+            reqwest::Client::new(),
+            std::sync::Arc::new(crate::llm::MockLLMClient::new()),
+            crate::config::PolicyConfig::default(),
+        )
+        .with_gmail_api_base(format!("{}/gmail/v1/users", server.uri()))
+        .with_discord_api_base(server.uri())
+        .with_digest_config(digest_config())
+        .with_discord_config(DiscordConfig {
+            bot_token: "bot-token".into(),
+            channel_id: "chan-1".into(),
+            whitelist: vec![],
+        })
+    }
+
+    async fn digest_job(
+        db: &crate::Database,
+        account_id: &str,
+        channel: &str,
+        period_end: DateTime<Utc>,
+    ) -> Job {
+        let queue = JobQueue::new(db.clone());
+        let job_id = queue
+            .enqueue(
+                JOB_TYPE,
+                json!({
+                    "account_id": account_id,
+                    "channel": channel,
+                    "period_start": period_end - Duration::days(1),
+                    "period_end": period_end,
+                }),
+                None,
+                0,
+            )
+            .await
+            .expect("enqueue job");
+        queue.fetch_job(&job_id).await.expect("fetch job")
+    }
+
+    #[tokio::test]
+    async fn email_digest_is_sent_and_next_period_scheduled() {
+        let (db, _dir, account_id) = setup().await;
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/gmail/v1/users/user@example.com/messages/send"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "sent-1",
+                "threadId": "thr-1",
+                "labelIds": ["SENT"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let period_end = Utc::now();
+        let job = digest_job(&db, &account_id, "email", period_end).await;
+        handle_digest_send(&dispatcher(db.clone(), &server), job)
+            .await
+            .expect("digest sent");
+
+        let requests = server.received_requests().await.expect("requests");
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).expect("json");
+        let raw = URL_SAFE_NO_PAD
+            .decode(body["raw"].as_str().expect("raw"))
+            .expect("decode raw");
+        let raw = String::from_utf8(raw).expect("utf8");
+        assert!(raw.contains("To: <user@example.com>"), "got: {raw}");
+        assert!(raw.contains("Subject: Ashford digest for user@example.com"));
+        assert!(raw.contains("ACTIONS TAKEN (0)"));
+
+        let next_end = next_digest_at(&digest_config(), period_end).expect("next");
+        let key = format!("{JOB_TYPE}:{account_id}:email:{}", next_end.timestamp());
+        let next = JobQueue::new(db.clone())
+            .find_by_idempotency_key(&key)
+            .await
+            .expect("lookup")
+            .expect("next digest scheduled");
+        assert_eq!(next.not_before, Some(next_end));
+        assert_eq!(next.payload["period_start"], json!(period_end));
+    }
+
+    #[tokio::test]
+    async fn discord_digest_posts_to_channel() {
+        let (db, _dir, account_id) = setup().await;
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/channels/chan-1/messages"))
+            .and(header("authorization", "Bot bot-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "m1"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let job = digest_job(&db, &account_id, "discord", Utc::now()).await;
+        handle_digest_send(&dispatcher(db.clone(), &server), job)
+            .await
+            .expect("digest posted");
+
+        let requests = server.received_requests().await.expect("requests");
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).expect("json");
+        assert!(
+            body["content"]
+                .as_str()
+                .expect("content")
+                .starts_with("**Ashford digest for user@example.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn discord_rate_limit_is_retryable() {
+        let (db, _dir, account_id) = setup().await;
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/channels/chan-1/messages"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&server)
+            .await;
+
+        let job = digest_job(&db, &account_id, "discord", Utc::now()).await;
+        let err = handle_digest_send(&dispatcher(db.clone(), &server), job)
+            .await
+            .expect_err("rate limited");
+        assert!(matches!(err, JobError::Retryable { .. }), "got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn discord_retry_resumes_after_delivered_messages() {
+        let (db, _dir, account_id) = setup().await;
+        let server = MockServer::start().await;
+        // The first message goes through, the second is rate limited, then Discord recovers
+        Mock::given(method("POST"))
+            .and(path("/channels/chan-1/messages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "m1"})))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/channels/chan-1/messages"))
+            .respond_with(ResponseTemplate::new(429))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/channels/chan-1/messages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "m2"})))
+            .mount(&server)
+            .await;
+
+        let dispatcher = dispatcher(db.clone(), &server);
+        let job = digest_job(&db, &account_id, "discord", Utc::now()).await;
+        let body = ["a", "b", "c"].map(|c| c.repeat(1_500)).join("\n");
+        let err = post_to_discord(&dispatcher, &job, "Digest", &body)
+            .await
+            .expect_err("rate limited");
+        assert!(matches!(err, JobError::Retryable { .. }), "got {err:?}");
+        post_to_discord(&dispatcher, &job, "Digest", &body)
+            .await
+            .expect("retry posts the rest");
+
+        let contents: Vec<String> = server
+            .received_requests()
+            .await
+            .expect("requests")
+            .iter()
+            .map(|request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).expect("json");
+                body["content"].as_str().expect("content").to_string()
+            })
+            .collect();
+        assert_eq!(contents.len(), 4);
+        assert!(contents[0].starts_with("**Digest**\naaa"));
+        assert!(contents[1].starts_with('b'));
+        assert!(
+            contents[2].starts_with('b'),
+            "retry resends the rejected message"
+        );
+        assert!(contents[3].starts_with('c'));
+    }
+
+    #[tokio::test]
+    async fn removed_channel_is_skipped() {
+        let (db, _dir, account_id) = setup().await;
+        let server = MockServer::start().await;
+        let dispatcher = dispatcher(db.clone(), &server).with_digest_config(DigestConfig {
+            enabled: true,
+            channels: vec![DigestChannel::Email],
+            ..Default::default()
+        });
+
+        let job = digest_job(&db, &account_id, "discord", Utc::now()).await;
+        handle_digest_send(&dispatcher, job).await.expect("skipped");
+        assert!(
+            server
+                .received_requests()
+                .await
+                .expect("requests")
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn schedule_digests_seeds_each_account_and_channel_once() {
+        let (db, _dir, account_id) = setup().await;
+        let server = MockServer::start().await;
+        let dispatcher = dispatcher(db.clone(), &server);
+        let now = Utc::now();
+
+        schedule_digests(&dispatcher, now).await.expect("schedule");
+        schedule_digests(&dispatcher, now)
+            .await
+            .expect("schedule again");
+
+        let period_end = next_digest_at(&dispatcher.digest_config, now).expect("next");
+        let queue = JobQueue::new(db.clone());
+        for channel in ["email", "discord"] {
+            let key = format!(
+                "{JOB_TYPE}:{account_id}:{channel}:{}",
+                period_end.timestamp()
+            );
+            let job = queue
+                .find_by_idempotency_key(&key)
+                .await
+                .expect("lookup")
+                .expect("digest scheduled");
+            assert_eq!(
+                job.payload["period_start"],
+                json!(period_end - Duration::days(1))
+            );
+        }
+    }
+
+    #[test]
+    fn split_message_breaks_between_lines() {
+        assert_eq!(split_message("ab\ncd\nef", 5), vec!["ab\ncd", "ef"]);
+        assert_eq!(split_message("abcdefg", 3), vec!["abc", "def", "g"]);
+        assert_eq!(split_message("ab\n\ncd", 10), vec!["ab\n\ncd"]);
+    }
+}
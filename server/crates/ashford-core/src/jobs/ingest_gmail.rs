@@ -1,22 +1,37 @@
 use std::sync::Arc;
 
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use serde::Deserialize;
+use serde_json::json;
 use tracing::{debug, info, warn};
 
 use crate::accounts::AccountRepository;
+use crate::config::BackfillMode;
 use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+use crate::contacts::ContactRepository;
+use crate::decisions::{
+    ActionLinkRelationType, ActionLinkRepository, ActionRepository, ActionStatus, NewAction,
+    NewActionLink,
+};
 use crate::gmail::{GmailClient, NoopTokenStore, parse_message};
-use crate::jobs::{JOB_TYPE_CLASSIFY, JobDispatcher, map_account_error, map_gmail_error};
-use crate::messages::{Mailbox, MessageRepository, NewMessage};
+use crate::jobs::action_gmail::PreImageState;
+use crate::jobs::{
+    JOB_TYPE_CLASSIFY, JOB_TYPE_SUMMARIZE_THREAD, JobDispatcher, map_account_error,
+    map_action_error, map_gmail_error,
+};
+use crate::llm::decision::ActionType;
+use crate::messages::{Mailbox, Message, MessageRepository, NewMessage};
 use crate::queue::{JobQueue, QueueError};
-use crate::threads::ThreadRepository;
+use crate::threads::{MutedThread, MutedThreadRepository, ThreadRepository};
 use crate::{Job, JobError};
 
 #[derive(Debug, Deserialize)]
 struct IngestPayload {
     account_id: String,
     message_id: String,
+    /// Set for messages listed by a backfill rather than delivered by sync.
+    #[serde(default)]
+    backfill: bool,
 }
 
 pub async fn handle_ingest_gmail(dispatcher: &JobDispatcher, job: Job) -> Result<(), JobError> {
@@ -85,6 +100,15 @@ pub async fn handle_ingest_gmail(dispatcher: &JobDispatcher, job: Job) -> Result
         .map_err(|err| JobError::retryable(format!("upsert thread failed: {err}")))?;
 
     let msg_repo = MessageRepository::new(dispatcher.db.clone());
+    let already_ingested = msg_repo
+        .exists(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &payload.account_id,
+            &message.id,
+        )
+        .await
+        .map_err(|err| JobError::retryable(format!("check message exists failed: {err}")))?;
     let new_msg = NewMessage {
         org_id: DEFAULT_ORG_ID,
         user_id: DEFAULT_USER_ID,
@@ -133,8 +157,54 @@ pub async fn handle_ingest_gmail(dispatcher: &JobDispatcher, job: Job) -> Result
         .await
         .map_err(|err| JobError::retryable(format!("upsert message failed: {err}")))?;
 
+    // Only count each message once towards contact statistics
+    if !already_ingested {
+        record_contact_interaction(dispatcher, &account.email, &persisted_msg).await?;
+    }
+
+    // Messages in muted threads are archived instead of classified
+    let muted = MutedThreadRepository::new(dispatcher.db.clone())
+        .get(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &payload.account_id,
+            &thread_id,
+        )
+        .await
+        .map_err(|err| JobError::retryable(format!("load thread mute failed: {err}")))?;
+    if let Some(mute) = muted {
+        archive_muted_message(dispatcher, &client, &mute, &persisted_msg).await?;
+        info!(
+            account_id = %payload.account_id,
+            message_id = %payload.message_id,
+            thread_id = %thread_id,
+            "ingested message in muted thread, skipping classification"
+        );
+        return Ok(());
+    }
+
+    if payload.backfill && dispatcher.backfill_config.mode_for(&account.email) == BackfillMode::Skip
+    {
+        info!(
+            account_id = %payload.account_id,
+            message_id = %payload.message_id,
+            "ingested backfilled message, classification disabled for account"
+        );
+        return Ok(());
+    }
+
+    if dispatcher.thread_summary_config.enabled {
+        enqueue_summary_job(dispatcher, &persisted_msg).await?;
+    }
+
     // Enqueue classify job for the persisted message
-    enqueue_classify_job(dispatcher, &payload.account_id, &persisted_msg.id).await?;
+    enqueue_classify_job(
+        dispatcher,
+        &payload.account_id,
+        &persisted_msg.id,
+        payload.backfill,
+    )
+    .await?;
 
     info!(
         account_id = %payload.account_id,
@@ -165,22 +235,176 @@ fn parse_internal_date(internal_date: &Option<String>) -> Result<Option<DateTime
     }
 }
 
+/// Updates the known-contacts table: sent mail makes its recipients contacts, received mail
+/// bumps the sender's counters if they are already a contact.
+async fn record_contact_interaction(
+    dispatcher: &JobDispatcher,
+    account_email: &str,
+    message: &Message,
+) -> Result<(), JobError> {
+    let contact_repo = ContactRepository::new(dispatcher.db.clone());
+    let at = message.received_at.unwrap_or_else(Utc::now);
+
+    let result = if message.labels.iter().any(|label| label == "SENT") {
+        let is_reply = message
+            .headers
+            .iter()
+            .any(|header| header.name.eq_ignore_ascii_case("In-Reply-To"));
+        let recipients: Vec<Mailbox> = message
+            .to
+            .iter()
+            .chain(&message.cc)
+            .chain(&message.bcc)
+            .filter(|mailbox| !mailbox.email.eq_ignore_ascii_case(account_email))
+            .cloned()
+            .collect();
+        contact_repo
+            .record_sent(
+                message.org_id,
+                message.user_id,
+                &message.account_id,
+                &recipients,
+                is_reply,
+                at,
+            )
+            .await
+    } else if let Some(from_email) = message.from_email.as_deref() {
+        contact_repo
+            .record_received(
+                message.org_id,
+                message.user_id,
+                &message.account_id,
+                from_email,
+                at,
+            )
+            .await
+            .map(|_| ())
+    } else {
+        Ok(())
+    };
+
+    result.map_err(|err| JobError::retryable(format!("record contact failed: {err}")))
+}
+
+/// Archives a message that arrived in a muted thread and records it as a completed `archive`
+/// action spawned by the thread's `mute_thread` action, so undoing the mute restores it too.
+async fn archive_muted_message(
+    dispatcher: &JobDispatcher,
+    client: &GmailClient<NoopTokenStore>,
+    mute: &MutedThread,
+    message: &Message,
+) -> Result<(), JobError> {
+    let pre_image = PreImageState::from_labels(&message.labels);
+    if !pre_image.is_in_inbox {
+        return Ok(());
+    }
+
+    let action_repo = ActionRepository::new(dispatcher.db.clone());
+    let existing = action_repo
+        .list_by_message_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message.id)
+        .await
+        .map_err(|err| map_action_error("load message actions", err))?;
+    if existing.iter().any(|action| {
+        action.action_type == "archive"
+            && action.parameters_json.get("muted_thread_id") == Some(&json!(mute.id))
+    }) {
+        debug!(message_id = %message.id, "muted thread message already archived");
+        return Ok(());
+    }
+
+    client
+        .modify_message(
+            &message.provider_message_id,
+            None,
+            Some(vec!["INBOX".to_string()]),
+        )
+        .await
+        .map_err(|err| map_gmail_error("archive muted thread message", err))?;
+
+    let action = action_repo
+        .create(NewAction {
+            org_id: DEFAULT_ORG_ID,
+            user_id: DEFAULT_USER_ID,
+            account_id: message.account_id.clone(),
+            message_id: message.id.clone(),
+            decision_id: None,
+            action_type: ActionType::Archive.as_str().to_string(),
+            parameters_json: json!({
+                "muted_thread_id": mute.id,
+                "reason": "thread muted",
+            }),
+            status: ActionStatus::Executing,
+            error_message: None,
+            executed_at: Some(Utc::now()),
+            undo_hint_json: json!({}),
+            trace_id: None,
+        })
+        .await
+        .map_err(|err| map_action_error("record muted thread archive", err))?;
+    let action = action_repo
+        .mark_completed_with_undo_hint(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &action.id,
+            pre_image.build_undo_hint(
+                ActionType::Archive,
+                ActionType::ApplyLabel,
+                json!({"label": "INBOX"}),
+            ),
+        )
+        .await
+        .map_err(|err| map_action_error("complete muted thread archive", err))?;
+
+    if let Some(mute_action_id) = mute.action_id.as_ref() {
+        ActionLinkRepository::new(dispatcher.db.clone())
+            .create(NewActionLink {
+                cause_action_id: mute_action_id.clone(),
+                effect_action_id: action.id,
+                relation_type: ActionLinkRelationType::Spawned,
+            })
+            .await
+            .map_err(|err| JobError::retryable(format!("link muted thread archive: {err}")))?;
+    }
+
+    Ok(())
+}
+
+/// Enqueue the message's classify job. With batching on, backfilled messages wait for the
+/// page's `classify.batch` job and only run on their own if the batch left them out.
 async fn enqueue_classify_job(
     dispatcher: &JobDispatcher,
     account_id: &str,
     message_id: &str,
+    backfill: bool,
 ) -> Result<(), JobError> {
     let queue = JobQueue::new(dispatcher.db.clone());
-    let payload = serde_json::json!({
+    let mut payload = serde_json::json!({
         "account_id": account_id,
         "message_id": message_id,
     });
+    if backfill {
+        payload["backfill"] = json!(true);
+    }
     let idempotency_key = format!("{JOB_TYPE_CLASSIFY}:{account_id}:{message_id}");
 
-    match queue
-        .enqueue(JOB_TYPE_CLASSIFY, payload, Some(idempotency_key), 0)
-        .await
-    {
+    let result = if backfill && dispatcher.backfill_config.is_batching() {
+        let not_before =
+            Utc::now() + Duration::minutes(dispatcher.backfill_config.fallback_delay_minutes);
+        queue
+            .enqueue_scheduled(
+                JOB_TYPE_CLASSIFY,
+                payload,
+                Some(idempotency_key),
+                0,
+                not_before,
+            )
+            .await
+    } else {
+        queue
+            .enqueue(JOB_TYPE_CLASSIFY, payload, Some(idempotency_key), 0)
+            .await
+    };
+    match result {
         Ok(_) => Ok(()),
         Err(QueueError::DuplicateIdempotency { .. }) => {
             debug!(account_id, message_id, "classify job already enqueued");
@@ -192,6 +416,50 @@ async fn enqueue_classify_job(
     }
 }
 
+/// Schedule an update of the thread's summary once it is long enough. The delay lets a burst
+/// of replies share one update; runs that find nothing new are no-ops.
+async fn enqueue_summary_job(
+    dispatcher: &JobDispatcher,
+    message: &Message,
+) -> Result<(), JobError> {
+    let config = &dispatcher.thread_summary_config;
+    let message_count = MessageRepository::new(dispatcher.db.clone())
+        .count_by_thread(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message.thread_id)
+        .await
+        .map_err(|err| JobError::retryable(format!("count thread messages failed: {err}")))?;
+    if message_count < config.min_messages as i64 {
+        return Ok(());
+    }
+
+    let payload = json!({
+        "account_id": message.account_id,
+        "thread_id": message.thread_id,
+    });
+    let idempotency_key = format!(
+        "{JOB_TYPE_SUMMARIZE_THREAD}:{}:{}",
+        message.thread_id, message.id
+    );
+    match JobQueue::new(dispatcher.db.clone())
+        .enqueue_scheduled(
+            JOB_TYPE_SUMMARIZE_THREAD,
+            payload,
+            Some(idempotency_key),
+            0,
+            Utc::now() + Duration::seconds(config.delay_seconds),
+        )
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(QueueError::DuplicateIdempotency { .. }) => {
+            debug!(message_id = %message.id, "thread summary job already enqueued");
+            Ok(())
+        }
+        Err(err) => Err(JobError::retryable(format!(
+            "enqueue thread summary job failed: {err}"
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,7 +472,7 @@ mod tests {
     use base64::Engine;
     use serde_json::json;
     use tempfile::TempDir;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{body_json, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     async fn setup_account() -> (AccountRepository, JobDispatcher, TempDir, String) {
@@ -356,6 +624,179 @@ mod tests {
         assert_eq!(priority, 0, "classify job should have priority 0");
     }
 
+    #[tokio::test]
+    async fn ingest_records_contacts_from_sent_and_received_mail() {
+        let (_repo, dispatcher, _dir, account_id) = setup_account().await;
+        let queue = JobQueue::new(dispatcher.db.clone());
+
+        let server = MockServer::start().await;
+        let api_base = format!("{}/gmail/v1/users", &server.uri());
+        let dispatcher = dispatcher.with_gmail_api_base(api_base);
+
+        let mut sent = build_message_response();
+        sent["id"] = json!("msg-sent");
+        sent["labelIds"] = json!(["SENT"]);
+        sent["payload"]["headers"] = json!([
+            {"name": "From", "value": "User <user@example.com>"},
+            {"name": "To", "value": "Bob <bob@example.com>"},
+            {"name": "Cc", "value": "user@example.com"},
+            {"name": "In-Reply-To", "value": "<original@example.com>"},
+            {"name": "Subject", "value": "Re: Greetings"}
+        ]);
+        let mut received = build_message_response();
+        received["id"] = json!("msg-received");
+        received["payload"]["headers"] = json!([
+            {"name": "From", "value": "Bob <bob@example.com>"},
+            {"name": "To", "value": "user@example.com"},
+            {"name": "Subject", "value": "Re: Greetings"}
+        ]);
+
+        Mock::given(method("GET"))
+            .and(path("/gmail/v1/users/user@example.com/messages/msg-sent"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(sent))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/gmail/v1/users/user@example.com/messages/msg-received",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(received))
+            .mount(&server)
+            .await;
+
+        // The sent message is ingested twice to check it only counts once
+        for message_id in ["msg-sent", "msg-sent", "msg-received"] {
+            let job_id = queue
+                .enqueue(
+                    crate::jobs::JOB_TYPE_INGEST_GMAIL,
+                    json!({"account_id": account_id, "message_id": message_id}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+            handle_ingest_gmail(&dispatcher, job).await.expect("ingest");
+        }
+
+        let contacts = ContactRepository::new(dispatcher.db.clone());
+        let bob = contacts
+            .get_by_email(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "bob@example.com",
+            )
+            .await
+            .expect("get contact")
+            .expect("bob is a contact");
+        assert_eq!(bob.name.as_deref(), Some("Bob"));
+        assert_eq!(bob.sent_count, 1);
+        assert_eq!(bob.reply_count, 1);
+        assert_eq!(bob.received_count, 1);
+        assert!(
+            contacts
+                .get_by_email(
+                    DEFAULT_ORG_ID,
+                    DEFAULT_USER_ID,
+                    &account_id,
+                    "user@example.com"
+                )
+                .await
+                .expect("get self")
+                .is_none(),
+            "the account's own address is never a contact"
+        );
+    }
+
+    #[tokio::test]
+    async fn ingest_archives_messages_in_muted_threads_without_classifying() {
+        let (_repo, dispatcher, _dir, account_id) = setup_account().await;
+        let queue = JobQueue::new(dispatcher.db.clone());
+
+        let server = MockServer::start().await;
+        let api_base = format!("{}/gmail/v1/users", &server.uri());
+        let dispatcher = dispatcher.with_gmail_api_base(api_base);
+
+        Mock::given(method("GET"))
+            .and(path("/gmail/v1/users/user@example.com/messages/msg-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(build_message_response()))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(
+                "/gmail/v1/users/user@example.com/messages/msg-1/modify",
+            ))
+            .and(body_json(json!({"removeLabelIds": ["INBOX"]})))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"id": "msg-1", "labelIds": []})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mute = MutedThreadRepository::new(dispatcher.db.clone())
+            .mute(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thr-1",
+                None,
+                None,
+            )
+            .await
+            .expect("mute thread");
+
+        // Ingesting twice (a retry) must not archive or record the message twice
+        for _ in 0..2 {
+            let job_id = queue
+                .enqueue(
+                    crate::jobs::JOB_TYPE_INGEST_GMAIL,
+                    json!({"account_id": account_id, "message_id": "msg-1"}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+            handle_ingest_gmail(&dispatcher, job).await.expect("ingest");
+        }
+
+        let stored = MessageRepository::new(dispatcher.db.clone())
+            .get_by_provider_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, "msg-1")
+            .await
+            .expect("message");
+        let actions = ActionRepository::new(dispatcher.db.clone())
+            .list_by_message_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &stored.id)
+            .await
+            .expect("actions");
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "archive");
+        assert_eq!(actions[0].status, ActionStatus::Completed);
+        assert_eq!(
+            actions[0].parameters_json["muted_thread_id"],
+            json!(mute.id)
+        );
+        assert_eq!(actions[0].undo_hint_json["inverse_action"], "apply_label");
+
+        let conn = dispatcher.db.connection().await.expect("conn");
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) FROM jobs WHERE type = ?1",
+                libsql::params![crate::jobs::JOB_TYPE_CLASSIFY],
+            )
+            .await
+            .expect("query");
+        let count: i64 = rows
+            .next()
+            .await
+            .expect("row")
+            .expect("count")
+            .get(0)
+            .expect("count");
+        assert_eq!(count, 0, "muted thread messages should not be classified");
+    }
+
     #[tokio::test]
     async fn ingest_returns_fatal_on_not_found() {
         let (_repo, dispatcher, _dir, account_id) = setup_account().await;
@@ -588,4 +1029,148 @@ mod tests {
             "only one classify job should exist due to idempotency"
         );
     }
+
+    #[tokio::test]
+    async fn ingest_defers_or_skips_classification_of_backfilled_messages() {
+        let (_repo, dispatcher, _dir, account_id) = setup_account().await;
+        let queue = JobQueue::new(dispatcher.db.clone());
+
+        let server = MockServer::start().await;
+        let api_base = format!("{}/gmail/v1/users", &server.uri());
+        Mock::given(method("GET"))
+            .and(path("/gmail/v1/users/user@example.com/messages/msg-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(build_message_response()))
+            .mount(&server)
+            .await;
+
+        let ingest = |dispatcher: JobDispatcher| {
+            let queue = queue.clone();
+            let account_id = account_id.clone();
+            async move {
+                let job_id = queue
+                    .enqueue(
+                        crate::jobs::JOB_TYPE_INGEST_GMAIL,
+                        json!({"account_id": account_id, "message_id": "msg-1", "backfill": true}),
+                        None,
+                        1,
+                    )
+                    .await
+                    .expect("enqueue job");
+                let job = queue.fetch_job(&job_id).await.expect("fetch job");
+                handle_ingest_gmail(&dispatcher, job).await.expect("ingest");
+            }
+        };
+
+        // Classification disabled for the account's historical mail
+        let skip = crate::config::BackfillConfig {
+            batch_size: 10,
+            accounts: [("User@Example.com".to_string(), BackfillMode::Skip)].into(),
+            ..Default::default()
+        };
+        ingest(
+            dispatcher
+                .clone()
+                .with_gmail_api_base(api_base.clone())
+                .with_backfill_config(skip),
+        )
+        .await;
+        let stored = MessageRepository::new(dispatcher.db.clone())
+            .get_by_provider_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, "msg-1")
+            .await
+            .expect("message");
+        let key = format!("{JOB_TYPE_CLASSIFY}:{account_id}:{}", stored.id);
+        assert!(
+            queue
+                .find_by_idempotency_key(&key)
+                .await
+                .expect("lookup")
+                .is_none()
+        );
+
+        // With batching, the per-message job waits for the batch
+        let batching = crate::config::BackfillConfig {
+            batch_size: 10,
+            fallback_delay_minutes: 10,
+            ..Default::default()
+        };
+        ingest(
+            dispatcher
+                .with_gmail_api_base(api_base)
+                .with_backfill_config(batching),
+        )
+        .await;
+        let classify = queue
+            .find_by_idempotency_key(&key)
+            .await
+            .expect("lookup")
+            .expect("classify job");
+        assert_eq!(classify.payload["backfill"], true);
+        assert!(classify.not_before.expect("scheduled") > Utc::now() + Duration::minutes(9));
+    }
+
+    #[tokio::test]
+    async fn ingest_schedules_thread_summary_once_thread_is_long_enough() {
+        let (_repo, dispatcher, _dir, account_id) = setup_account().await;
+        let queue = JobQueue::new(dispatcher.db.clone());
+
+        let server = MockServer::start().await;
+        for message_id in ["msg-1", "msg-2"] {
+            let mut response = build_message_response();
+            response["id"] = json!(message_id);
+            Mock::given(method("GET"))
+                .and(path(format!(
+                    "/gmail/v1/users/user@example.com/messages/{message_id}"
+                )))
+                .respond_with(ResponseTemplate::new(200).set_body_json(response))
+                .mount(&server)
+                .await;
+        }
+        let dispatcher = dispatcher
+            .with_gmail_api_base(format!("{}/gmail/v1/users", &server.uri()))
+            .with_thread_summary_config(crate::config::ThreadSummaryConfig {
+                enabled: true,
+                min_messages: 2,
+                delay_seconds: 300,
+                ..Default::default()
+            });
+
+        let mut stored_ids = Vec::new();
+        for message_id in ["msg-1", "msg-2"] {
+            let job_id = queue
+                .enqueue(
+                    crate::jobs::JOB_TYPE_INGEST_GMAIL,
+                    json!({"account_id": account_id, "message_id": message_id}),
+                    None,
+                    1,
+                )
+                .await
+                .expect("enqueue job");
+            let job = queue.fetch_job(&job_id).await.expect("fetch job");
+            handle_ingest_gmail(&dispatcher, job).await.expect("ingest");
+            let stored = MessageRepository::new(dispatcher.db.clone())
+                .get_by_provider_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, message_id)
+                .await
+                .expect("message");
+            stored_ids.push((stored.thread_id, stored.id));
+        }
+
+        let key = |(thread_id, message_id): &(String, String)| {
+            format!("{JOB_TYPE_SUMMARIZE_THREAD}:{thread_id}:{message_id}")
+        };
+        assert!(
+            queue
+                .find_by_idempotency_key(&key(&stored_ids[0]))
+                .await
+                .expect("lookup")
+                .is_none(),
+            "a single message is not summarized"
+        );
+        let job = queue
+            .find_by_idempotency_key(&key(&stored_ids[1]))
+            .await
+            .expect("lookup")
+            .expect("summary job scheduled for the second message");
+        assert_eq!(job.payload["thread_id"], json!(stored_ids[1].0));
+        assert!(job.not_before.expect("scheduled") > Utc::now() + Duration::seconds(200));
+    }
 }
@@ -507,6 +507,9 @@ mod tests {
                 action_type: "apply_label".to_string(),
                 action_parameters_json: json!({"label_id": "Label_DELETED"}),
                 safe_mode: SafeMode::Default,
+                active_from: None,
+                active_until: None,
+                schedule: None,
             })
             .await
             .expect("create rule");
@@ -702,6 +705,9 @@ mod tests {
                 action_type: "archive".to_string(),
                 action_parameters_json: json!({}),
                 safe_mode: SafeMode::Default,
+                active_from: None,
+                active_until: None,
+                schedule: None,
             })
             .await
             .expect("create rule1");
@@ -721,6 +727,9 @@ mod tests {
                 action_type: "apply_label".to_string(),
                 action_parameters_json: json!({"label_id": "Label_SHARED"}),
                 safe_mode: SafeMode::Default,
+                active_from: None,
+                active_until: None,
+                schedule: None,
             })
             .await
             .expect("create rule2");
@@ -873,6 +882,9 @@ mod tests {
                 action_type: "apply_label".to_string(),
                 action_parameters_json: json!({"label_id": "Label_DUAL"}),
                 safe_mode: SafeMode::Default,
+                active_from: None,
+                active_until: None,
+                schedule: None,
             })
             .await
             .expect("create rule");
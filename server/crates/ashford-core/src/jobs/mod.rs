@@ -4,45 +4,70 @@ use async_trait::async_trait;
 use reqwest::StatusCode;
 
 use crate::accounts::AccountError;
-use crate::config::{GmailConfig, PolicyConfig};
+use crate::config::{
+    ApprovalConfig, BackfillConfig, BudgetConfig, CircuitBreakerConfig, DecisionCacheConfig,
+    DigestConfig, DirectionCheckConfig, DiscordConfig, ExecutionDelayConfig, GmailConfig,
+    PolicyConfig, PricingConfig, RedactionConfig, RoutingConfig, SimilarMessagesConfig,
+    ThreadSummaryConfig,
+};
 use crate::decisions::ActionError;
 use crate::gmail::GmailClientError;
 use crate::gmail::oauth::OAuthError;
-use crate::llm::{LLMClient, LLMError};
+use crate::llm::{EmbeddingClient, LLMClient, LLMError, build_embedding_client};
 use crate::rules::ExecutorError;
 use crate::worker::{JobError, JobExecutor};
 use crate::{Database, Job, JobContext};
 
 mod action_gmail;
 mod approval_notify;
+mod approval_timeout;
 mod backfill_gmail;
+mod circuit_breaker_alert;
 mod classify;
+mod classify_batch;
+mod digest;
 mod history_sync_gmail;
 mod ingest_gmail;
 mod labels_sync_gmail;
 mod outbound_send;
+mod pending_execution_notify;
+mod summarize_thread;
 mod undo_action;
 mod unsnooze_gmail;
 
 use action_gmail::handle_action_gmail;
 use approval_notify::handle_approval_notify;
+use approval_timeout::{handle_approval_remind, handle_approval_timeout};
 use backfill_gmail::handle_backfill_gmail;
+use circuit_breaker_alert::handle_circuit_breaker_alert;
 use classify::handle_classify;
+use classify_batch::handle_classify_batch;
+use digest::handle_digest_send;
+pub use digest::schedule_digests;
 use history_sync_gmail::handle_history_sync_gmail;
 use ingest_gmail::handle_ingest_gmail;
 use labels_sync_gmail::handle_labels_sync_gmail;
 use outbound_send::handle_outbound_send;
+use pending_execution_notify::handle_pending_execution_notify;
+use summarize_thread::handle_summarize_thread;
 use undo_action::handle_undo_action;
 use unsnooze_gmail::handle_unsnooze_gmail;
 
 pub const JOB_TYPE_ACTION_GMAIL: &str = action_gmail::JOB_TYPE;
 pub const JOB_TYPE_APPROVAL_NOTIFY: &str = approval_notify::JOB_TYPE;
+pub const JOB_TYPE_APPROVAL_REMIND: &str = approval_timeout::REMIND_JOB_TYPE;
+pub const JOB_TYPE_APPROVAL_TIMEOUT: &str = approval_timeout::JOB_TYPE;
 pub const JOB_TYPE_BACKFILL_GMAIL: &str = backfill_gmail::JOB_TYPE;
+pub const JOB_TYPE_CIRCUIT_BREAKER_ALERT: &str = circuit_breaker_alert::JOB_TYPE;
 pub const JOB_TYPE_CLASSIFY: &str = "classify";
+pub const JOB_TYPE_CLASSIFY_BATCH: &str = classify_batch::JOB_TYPE;
+pub const JOB_TYPE_DIGEST_SEND: &str = digest::JOB_TYPE;
 pub const JOB_TYPE_INGEST_GMAIL: &str = "ingest.gmail";
 pub const JOB_TYPE_HISTORY_SYNC_GMAIL: &str = "history.sync.gmail";
 pub const JOB_TYPE_LABELS_SYNC_GMAIL: &str = labels_sync_gmail::JOB_TYPE;
 pub const JOB_TYPE_OUTBOUND_SEND: &str = outbound_send::JOB_TYPE;
+pub const JOB_TYPE_PENDING_EXECUTION_NOTIFY: &str = pending_execution_notify::JOB_TYPE;
+pub const JOB_TYPE_SUMMARIZE_THREAD: &str = summarize_thread::JOB_TYPE;
 pub const JOB_TYPE_UNSNOOZE_GMAIL: &str = unsnooze_gmail::JOB_TYPE;
 pub const JOB_TYPE_UNDO_ACTION: &str = undo_action::JOB_TYPE;
 
@@ -54,6 +79,24 @@ pub struct JobDispatcher {
     pub gmail_config: GmailConfig,
     pub llm_client: Arc<dyn LLMClient>,
     pub policy_config: PolicyConfig,
+    pub decision_cache_config: DecisionCacheConfig,
+    pub pricing_config: PricingConfig,
+    pub budget_config: BudgetConfig,
+    pub routing_config: RoutingConfig,
+    pub direction_check_config: DirectionCheckConfig,
+    pub redaction_config: RedactionConfig,
+    pub similar_messages_config: SimilarMessagesConfig,
+    /// Set when similar-message retrieval is enabled.
+    pub embedding_client: Option<Arc<dyn EmbeddingClient>>,
+    pub backfill_config: BackfillConfig,
+    pub thread_summary_config: ThreadSummaryConfig,
+    pub digest_config: DigestConfig,
+    pub circuit_breaker_config: CircuitBreakerConfig,
+    pub approval_config: ApprovalConfig,
+    pub execution_delay_config: ExecutionDelayConfig,
+    /// Bot credentials for posting digests and alerts to Discord.
+    pub discord_config: Option<DiscordConfig>,
+    pub discord_api_base: Option<String>,
 }
 
 impl JobDispatcher {
@@ -70,6 +113,22 @@ impl JobDispatcher {
             gmail_config: GmailConfig::default(),
             llm_client,
             policy_config,
+            decision_cache_config: DecisionCacheConfig::default(),
+            pricing_config: PricingConfig::default(),
+            budget_config: BudgetConfig::default(),
+            routing_config: RoutingConfig::default(),
+            direction_check_config: DirectionCheckConfig::default(),
+            redaction_config: RedactionConfig::default(),
+            similar_messages_config: SimilarMessagesConfig::default(),
+            embedding_client: None,
+            backfill_config: BackfillConfig::default(),
+            thread_summary_config: ThreadSummaryConfig::default(),
+            digest_config: DigestConfig::default(),
+            circuit_breaker_config: CircuitBreakerConfig::default(),
+            approval_config: ApprovalConfig::default(),
+            execution_delay_config: ExecutionDelayConfig::default(),
+            discord_config: None,
+            discord_api_base: None,
         }
     }
 
@@ -82,6 +141,86 @@ impl JobDispatcher {
         self.gmail_config = gmail_config;
         self
     }
+
+    pub fn with_decision_cache_config(mut self, config: DecisionCacheConfig) -> Self {
+        self.decision_cache_config = config;
+        self
+    }
+
+    pub fn with_pricing_config(mut self, config: PricingConfig) -> Self {
+        self.pricing_config = config;
+        self
+    }
+
+    pub fn with_budget_config(mut self, config: BudgetConfig) -> Self {
+        self.budget_config = config;
+        self
+    }
+
+    pub fn with_routing_config(mut self, config: RoutingConfig) -> Self {
+        self.routing_config = config;
+        self
+    }
+
+    pub fn with_direction_check_config(mut self, config: DirectionCheckConfig) -> Self {
+        self.direction_check_config = config;
+        self
+    }
+
+    pub fn with_redaction_config(mut self, config: RedactionConfig) -> Self {
+        self.redaction_config = config;
+        self
+    }
+
+    /// Enable similar-message retrieval when `config.enabled`, embedding with the configured
+    /// provider.
+    pub fn with_similar_messages_config(mut self, config: SimilarMessagesConfig) -> Self {
+        self.embedding_client = config
+            .enabled
+            .then(|| build_embedding_client(self.db.clone(), &config));
+        self.similar_messages_config = config;
+        self
+    }
+
+    pub fn with_backfill_config(mut self, config: BackfillConfig) -> Self {
+        self.backfill_config = config;
+        self
+    }
+
+    pub fn with_thread_summary_config(mut self, config: ThreadSummaryConfig) -> Self {
+        self.thread_summary_config = config;
+        self
+    }
+
+    pub fn with_digest_config(mut self, config: DigestConfig) -> Self {
+        self.digest_config = config;
+        self
+    }
+
+    pub fn with_circuit_breaker_config(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker_config = config;
+        self
+    }
+
+    pub fn with_approval_config(mut self, config: ApprovalConfig) -> Self {
+        self.approval_config = config;
+        self
+    }
+
+    pub fn with_execution_delay_config(mut self, config: ExecutionDelayConfig) -> Self {
+        self.execution_delay_config = config;
+        self
+    }
+
+    pub fn with_discord_config(mut self, config: DiscordConfig) -> Self {
+        self.discord_config = Some(config);
+        self
+    }
+
+    pub fn with_discord_api_base(mut self, base: impl Into<String>) -> Self {
+        self.discord_api_base = Some(base.into());
+        self
+    }
 }
 
 #[async_trait]
@@ -91,11 +230,18 @@ impl JobExecutor for JobDispatcher {
             JOB_TYPE_BACKFILL_GMAIL => handle_backfill_gmail(self, job).await,
             JOB_TYPE_ACTION_GMAIL => handle_action_gmail(self, job).await,
             JOB_TYPE_APPROVAL_NOTIFY => handle_approval_notify(self, job).await,
+            JOB_TYPE_APPROVAL_REMIND => handle_approval_remind(self, job).await,
+            JOB_TYPE_APPROVAL_TIMEOUT => handle_approval_timeout(self, job).await,
+            JOB_TYPE_CIRCUIT_BREAKER_ALERT => handle_circuit_breaker_alert(self, job).await,
             JOB_TYPE_CLASSIFY => handle_classify(self, job).await,
+            JOB_TYPE_CLASSIFY_BATCH => handle_classify_batch(self, job).await,
             JOB_TYPE_INGEST_GMAIL => handle_ingest_gmail(self, job).await,
+            JOB_TYPE_DIGEST_SEND => handle_digest_send(self, job).await,
             JOB_TYPE_HISTORY_SYNC_GMAIL => handle_history_sync_gmail(self, job).await,
             JOB_TYPE_LABELS_SYNC_GMAIL => handle_labels_sync_gmail(self, job).await,
             JOB_TYPE_OUTBOUND_SEND => handle_outbound_send(self, job).await,
+            JOB_TYPE_PENDING_EXECUTION_NOTIFY => handle_pending_execution_notify(self, job).await,
+            JOB_TYPE_SUMMARIZE_THREAD => handle_summarize_thread(self, job).await,
             JOB_TYPE_UNSNOOZE_GMAIL => handle_unsnooze_gmail(self, job).await,
             JOB_TYPE_UNDO_ACTION => handle_undo_action(self, job).await,
             other => Err(JobError::Fatal(format!("unknown job type: {other}"))),
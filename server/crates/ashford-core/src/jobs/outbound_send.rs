@@ -92,7 +92,7 @@ pub async fn handle_outbound_send(dispatcher: &JobDispatcher, job: Job) -> Resul
             );
             return Ok(());
         }
-        ActionStatus::Queued => {
+        ActionStatus::Queued | ActionStatus::PendingExecution => {
             action_repo
                 .mark_executing(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
                 .await
@@ -253,7 +253,7 @@ pub async fn handle_outbound_send(dispatcher: &JobDispatcher, job: Job) -> Resul
     }
 }
 
-fn map_mime_error(context: &str, err: MimeBuildError) -> JobError {
+pub(super) fn map_mime_error(context: &str, err: MimeBuildError) -> JobError {
     match err {
         MimeBuildError::MissingRecipients | MimeBuildError::MissingBody => {
             JobError::Fatal(format!("{context}: {err}"))
@@ -0,0 +1,106 @@
+//! Notice that a delayed action is about to run.
+//!
+//! Sent when `classify` creates an action in `pending_execution`, so it can still be canceled
+//! before its job runs. The notice points at the action's page in the web app, whose Cancel
+//! Action button calls `POST /api/actions/{id}/cancel`.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::info;
+
+use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+use crate::decisions::{ActionRepository, ActionStatus};
+use crate::queue::{JobQueue, QueueError};
+use crate::{Job, JobError};
+
+use super::digest::{describe_action, notify_discord};
+use super::{JobDispatcher, map_action_error};
+
+pub const JOB_TYPE: &str = "pending_execution.notify";
+
+#[derive(Debug, Deserialize)]
+struct PendingExecutionPayload {
+    action_id: String,
+    execute_at: DateTime<Utc>,
+}
+
+pub async fn handle_pending_execution_notify(
+    dispatcher: &JobDispatcher,
+    job: Job,
+) -> Result<(), JobError> {
+    let payload: PendingExecutionPayload =
+        serde_json::from_value(job.payload.clone()).map_err(|err| {
+            JobError::Fatal(format!("invalid pending_execution.notify payload: {err}"))
+        })?;
+
+    let action = ActionRepository::new(dispatcher.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &payload.action_id)
+        .await
+        .map_err(|err| map_action_error("load action", err))?;
+    if action.status != ActionStatus::PendingExecution {
+        // Already canceled or running
+        return Ok(());
+    }
+
+    let execute_at = payload
+        .execute_at
+        .to_rfc3339_opts(SecondsFormat::Secs, true);
+    info!(action_id = %action.id, %execute_at, "delayed action pending execution");
+    let subject = format!("Ashford will run {} at {execute_at}", action.action_type);
+    let body = notice_body(
+        &describe_action(dispatcher, &action).await,
+        &action.id,
+        &execute_at,
+    );
+    notify_discord(dispatcher, &job, &subject, &body).await
+}
+
+/// The notice text, with the web app route where the action can be canceled.
+fn notice_body(description: &str, action_id: &str, execute_at: &str) -> String {
+    format!(
+        "{description} runs at {execute_at}. To stop it, open /actions/{action_id} in the \
+         Ashford web app and press Cancel Action before then."
+    )
+}
+
+/// Enqueue the notice for an action that was just delayed.
+pub(super) async fn enqueue_pending_execution_notify(
+    dispatcher: &JobDispatcher,
+    action_id: &str,
+    execute_at: DateTime<Utc>,
+) -> Result<(), JobError> {
+    match JobQueue::new(dispatcher.db.clone())
+        .enqueue(
+            JOB_TYPE,
+            json!({ "action_id": action_id, "execute_at": execute_at }),
+            Some(format!("{JOB_TYPE}:{action_id}")),
+            0,
+        )
+        .await
+    {
+        Ok(_) | Err(QueueError::DuplicateIdempotency { .. }) => Ok(()),
+        Err(err) => Err(JobError::retryable(format!(
+            "enqueue pending execution notice: {err}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notice_body_points_at_the_action_page() {
+        let body = notice_body(
+            "Delete \"Invoice\" from billing@example.com",
+            "act-1",
+            "2024-06-01T12:00:00Z",
+        );
+        assert_eq!(
+            body,
+            "Delete \"Invoice\" from billing@example.com runs at 2024-06-01T12:00:00Z. To stop it, \
+             open /actions/act-1 in the Ashford web app and press Cancel Action before then."
+        );
+    }
+}
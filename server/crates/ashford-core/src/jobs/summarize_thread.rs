@@ -0,0 +1,436 @@
+//! Rolling thread summaries.
+//!
+//! Each run folds the messages that arrived since the stored summary into it with one LLM
+//! call, so a thread is never re-read from the start once summarized.
+
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+use crate::llm::{
+    CompletionRequest, LlmCallContext, PromptBuilder, SpendTracker, ThreadContext,
+    ThreadSummaryOutput, build_summary_tool,
+};
+use crate::messages::MessageRepository;
+use crate::threads::{NewThreadSummary, ThreadSummary, ThreadSummaryRepository};
+use crate::{Job, JobError};
+
+use super::classify::redactor;
+use super::{JobDispatcher, map_llm_error};
+
+pub const JOB_TYPE: &str = "summarize.thread";
+
+/// Feature name recorded on summary LLM calls and matched by budget limits and routing.
+pub const LLM_FEATURE: &str = "summarize";
+
+#[derive(Debug, Deserialize)]
+struct SummarizePayload {
+    account_id: String,
+    /// Internal thread id.
+    thread_id: String,
+}
+
+/// What a summary update did.
+#[derive(Debug)]
+pub(super) enum SummaryOutcome {
+    Updated(Box<ThreadSummary>),
+    /// The summary already covers the newest message.
+    UpToDate,
+    /// Left alone without calling the LLM, for the given reason.
+    Skipped(&'static str),
+}
+
+pub async fn handle_summarize_thread(dispatcher: &JobDispatcher, job: Job) -> Result<(), JobError> {
+    let payload: SummarizePayload = serde_json::from_value(job.payload.clone())
+        .map_err(|err| JobError::Fatal(format!("invalid summarize.thread payload: {err}")))?;
+
+    if !dispatcher.thread_summary_config.enabled {
+        debug!(thread_id = %payload.thread_id, "thread summaries disabled; skipping");
+        return Ok(());
+    }
+
+    match summarize_thread(dispatcher, &payload.account_id, &payload.thread_id, false).await? {
+        SummaryOutcome::Updated(summary) => info!(
+            account_id = %payload.account_id,
+            thread_id = %payload.thread_id,
+            message_count = summary.message_count,
+            "updated thread summary"
+        ),
+        SummaryOutcome::UpToDate => debug!(
+            thread_id = %payload.thread_id,
+            "thread summary already up to date"
+        ),
+        SummaryOutcome::Skipped(reason) => debug!(
+            thread_id = %payload.thread_id,
+            reason,
+            "thread summary not updated"
+        ),
+    }
+    Ok(())
+}
+
+/// Fold the thread's unsummarized messages into its summary.
+///
+/// Threads shorter than `min_messages` are skipped unless `force` is set, as for an explicit
+/// `summarize` action. The update is also skipped while an LLM budget covering the
+/// `summarize` feature is exhausted.
+pub(super) async fn summarize_thread(
+    dispatcher: &JobDispatcher,
+    account_id: &str,
+    thread_id: &str,
+    force: bool,
+) -> Result<SummaryOutcome, JobError> {
+    let config = &dispatcher.thread_summary_config;
+    let messages = MessageRepository::new(dispatcher.db.clone())
+        .list_by_thread(DEFAULT_ORG_ID, DEFAULT_USER_ID, thread_id)
+        .await
+        .map_err(|err| JobError::retryable(format!("failed to load thread messages: {err}")))?;
+    if let Some(message) = messages.iter().find(|m| m.account_id != account_id) {
+        return Err(JobError::Fatal(format!(
+            "thread {thread_id} message {} does not belong to account {account_id}",
+            message.id
+        )));
+    }
+    let Some(newest) = messages.last() else {
+        return Ok(SummaryOutcome::Skipped("thread has no messages"));
+    };
+    if !force && messages.len() < config.min_messages {
+        return Ok(SummaryOutcome::Skipped("thread too short"));
+    }
+
+    let summary_repo = ThreadSummaryRepository::new(dispatcher.db.clone());
+    let previous = summary_repo
+        .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, thread_id)
+        .await
+        .map_err(|err| JobError::retryable(format!("failed to load thread summary: {err}")))?;
+
+    // Messages after the newest one the summary covers; all of them if it is gone
+    let unsummarized = match &previous {
+        Some(summary) if summary.last_message_id == newest.id => {
+            return Ok(SummaryOutcome::UpToDate);
+        }
+        Some(summary) => match messages
+            .iter()
+            .position(|m| m.id == summary.last_message_id)
+        {
+            Some(index) => &messages[index + 1..],
+            None => &messages[..],
+        },
+        None => &messages[..],
+    };
+    let start = unsummarized
+        .len()
+        .saturating_sub(config.max_new_messages.max(1));
+    let new_messages: Vec<_> = unsummarized[start..].iter().collect();
+
+    let spend = SpendTracker::new(
+        dispatcher.db.clone(),
+        dispatcher.pricing_config.clone(),
+        dispatcher.budget_config.clone(),
+    );
+    match spend
+        .check_budget(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            LLM_FEATURE,
+            account_id,
+            Utc::now(),
+        )
+        .await
+    {
+        Ok(Some(exceeded)) => {
+            warn!(
+                thread_id,
+                window = ?exceeded.window,
+                limit_usd = exceeded.limit_usd,
+                spent_usd = exceeded.spent_usd,
+                "llm budget exceeded; skipping thread summary"
+            );
+            return Ok(SummaryOutcome::Skipped("llm budget exceeded"));
+        }
+        Ok(None) => {}
+        Err(err) => warn!(thread_id, error = %err, "llm budget check failed"),
+    }
+
+    let mut prompt_builder = PromptBuilder::new();
+    if let Some(redactor) = redactor(dispatcher)? {
+        prompt_builder = prompt_builder.with_redactor(redactor);
+    }
+    let previous_context = previous.as_ref().map(ThreadContext::from);
+    let (prompt, redactions) = prompt_builder
+        .build_thread_summary_with_redactions(previous_context.as_ref(), &new_messages);
+
+    let request = CompletionRequest {
+        messages: prompt,
+        temperature: 0.2,
+        max_tokens: 1024,
+        json_mode: false,
+        model: None,
+        tools: vec![build_summary_tool()],
+    };
+    let context = LlmCallContext {
+        feature: LLM_FEATURE.into(),
+        org_id: Some(DEFAULT_ORG_ID),
+        user_id: Some(DEFAULT_USER_ID),
+        account_id: Some(account_id.to_string()),
+        message_id: Some(newest.id.clone()),
+        thread_id: Some(thread_id.to_string()),
+        rule_name: None,
+        rule_id: None,
+        redactions: redactions.counts().clone(),
+    };
+    let response = dispatcher
+        .llm_client
+        .complete(request, context)
+        .await
+        .map_err(|err| map_llm_error("thread summary", err))?;
+    let mut output = ThreadSummaryOutput::parse_from_completion(&response)
+        .map_err(|err| JobError::Fatal(format!("thread summary: parse error {err}")))?;
+    output.restore(&redactions);
+
+    let summary = summary_repo
+        .upsert(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            NewThreadSummary {
+                thread_id: thread_id.to_string(),
+                account_id: account_id.to_string(),
+                summary: output.summary,
+                participants: output.participants,
+                open_questions: output.open_questions,
+                commitments: output.commitments,
+                message_count: messages.len() as i64,
+                last_message_id: newest.id.clone(),
+                model: response.model,
+                llm_call_id: response.llm_call_id,
+            },
+        )
+        .await
+        .map_err(|err| JobError::retryable(format!("failed to store thread summary: {err}")))?;
+    Ok(SummaryOutcome::Updated(Box::new(summary)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::config::{PolicyConfig, ThreadSummaryConfig};
+    use crate::gmail::OAuthTokens;
+    use crate::llm::{
+        CompletionResponse, LLMClient, MockLLMClient, SUMMARY_TOOL_NAME, ToolCallResult,
+    };
+    use crate::messages::{Mailbox, NewMessage};
+    use crate::migrations::run_migrations;
+    use crate::queue::{JobQueue, JobState};
+    use crate::threads::ThreadRepository;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    async fn setup() -> (JobDispatcher, Arc<MockLLMClient>, TempDir, String, String) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir
+            .path()
+            .join(format!("db_{}.sqlite", uuid::Uuid::new_v4()));
+        let db = crate::Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + chrono::Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+        let thread = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account.id,
+                "thr-1",
+                Some("Offsite".into()),
+                None,
+                None,
+                json!({}),
+            )
+            .await
+            .expect("thread");
+
+        let llm = Arc::new(MockLLMClient::new());
+        let dispatcher = JobDispatcher::new(
+            db,
+            reqwest::Client::new(),
+            llm.clone() as Arc<dyn LLMClient>,
+            PolicyConfig::default(),
+        )
+        .with_thread_summary_config(ThreadSummaryConfig {
+            enabled: true,
+            min_messages: 2,
+            ..Default::default()
+        });
+        (dispatcher, llm, dir, account.id, thread.id)
+    }
+
+    async fn add_message(
+        dispatcher: &JobDispatcher,
+        account_id: &str,
+        thread_id: &str,
+        index: i64,
+    ) -> String {
+        MessageRepository::new(dispatcher.db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.to_string(),
+                thread_id: thread_id.to_string(),
+                provider_message_id: format!("msg-{index}"),
+                from_email: Some("alice@example.com".into()),
+                from_name: Some("Alice".into()),
+                to: vec![Mailbox {
+                    email: "user@example.com".into(),
+                    name: None,
+                }],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some("Offsite".into()),
+                snippet: None,
+                received_at: None,
+                internal_date: Some(Utc::now() + chrono::Duration::seconds(index)),
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: Some(format!("Message number {index}")),
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("insert message")
+            .id
+    }
+
+    fn summary_response(summary: &str) -> CompletionResponse {
+        CompletionResponse {
+            content: String::new(),
+            model: "summary-model".into(),
+            input_tokens: 100,
+            output_tokens: 20,
+            latency_ms: 5,
+            llm_call_id: Some("call-1".into()),
+            tool_calls: vec![ToolCallResult {
+                call_id: "tool-1".into(),
+                fn_name: SUMMARY_TOOL_NAME.into(),
+                fn_arguments: json!({
+                    "summary": summary,
+                    "participants": ["Alice <alice@example.com>"],
+                    "open_questions": ["Which venue?"],
+                    "commitments": [],
+                }),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn summary_rolls_forward_with_only_new_messages() {
+        let (dispatcher, llm, _dir, account_id, thread_id) = setup().await;
+        add_message(&dispatcher, &account_id, &thread_id, 1).await;
+
+        let outcome = summarize_thread(&dispatcher, &account_id, &thread_id, false)
+            .await
+            .expect("summarize");
+        assert!(matches!(
+            outcome,
+            SummaryOutcome::Skipped("thread too short")
+        ));
+        assert_eq!(llm.call_count(), 0);
+
+        add_message(&dispatcher, &account_id, &thread_id, 2).await;
+        llm.enqueue_response(Ok(summary_response("Alice proposes an offsite.")));
+        let SummaryOutcome::Updated(first) =
+            summarize_thread(&dispatcher, &account_id, &thread_id, false)
+                .await
+                .expect("summarize")
+        else {
+            panic!("expected a new summary");
+        };
+        assert_eq!(first.message_count, 2);
+        assert_eq!(first.model, "summary-model");
+        assert_eq!(first.open_questions, vec!["Which venue?"]);
+
+        let outcome = summarize_thread(&dispatcher, &account_id, &thread_id, false)
+            .await
+            .expect("summarize again");
+        assert!(matches!(outcome, SummaryOutcome::UpToDate));
+        assert_eq!(llm.call_count(), 1);
+
+        let third = add_message(&dispatcher, &account_id, &thread_id, 3).await;
+        llm.enqueue_response(Ok(summary_response("The offsite is in Lisbon.")));
+        let SummaryOutcome::Updated(second) =
+            summarize_thread(&dispatcher, &account_id, &thread_id, false)
+                .await
+                .expect("summarize")
+        else {
+            panic!("expected an updated summary");
+        };
+        assert_eq!(second.message_count, 3);
+        assert_eq!(second.last_message_id, third);
+
+        let calls = llm.calls();
+        let (request, context) = &calls[1];
+        assert_eq!(context.feature, LLM_FEATURE);
+        assert_eq!(context.thread_id.as_deref(), Some(thread_id.as_str()));
+        let user_content = &request.messages[1].content;
+        assert!(user_content.contains("PREVIOUS SUMMARY (covers 2 messages)"));
+        assert!(user_content.contains("Alice proposes an offsite."));
+        assert!(user_content.contains("Message number 3"));
+        assert!(
+            !user_content.contains("Message number 2"),
+            "summarized messages are not sent again"
+        );
+    }
+
+    #[tokio::test]
+    async fn handler_is_a_no_op_when_summaries_are_disabled() {
+        let (dispatcher, llm, _dir, account_id, thread_id) = setup().await;
+        let dispatcher = dispatcher.with_thread_summary_config(ThreadSummaryConfig::default());
+        add_message(&dispatcher, &account_id, &thread_id, 1).await;
+        add_message(&dispatcher, &account_id, &thread_id, 2).await;
+        add_message(&dispatcher, &account_id, &thread_id, 3).await;
+
+        let queue = JobQueue::new(dispatcher.db.clone());
+        let job_id = queue
+            .enqueue(
+                JOB_TYPE,
+                json!({"account_id": account_id, "thread_id": thread_id}),
+                None,
+                0,
+            )
+            .await
+            .expect("enqueue");
+        let job = queue.fetch_job(&job_id).await.expect("job");
+        assert_eq!(job.state, JobState::Queued);
+
+        handle_summarize_thread(&dispatcher, job)
+            .await
+            .expect("handler succeeds");
+        assert_eq!(llm.call_count(), 0);
+        assert!(
+            ThreadSummaryRepository::new(dispatcher.db.clone())
+                .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &thread_id)
+                .await
+                .expect("get")
+                .is_none()
+        );
+    }
+}
@@ -12,10 +12,12 @@ use crate::decisions::{
     Action, ActionLinkError, ActionLinkRelationType, ActionLinkRepository, ActionRepository,
     ActionStatus, NewAction, NewActionLink,
 };
+use crate::feedback::{FeedbackRepository, FeedbackSource};
 use crate::gmail::{GmailClientError, NoopTokenStore};
 use crate::llm::decision::ActionType;
 use crate::messages::{MessageError, MessageRepository};
 use crate::queue::{JobQueue, QueueError};
+use crate::threads::MutedThreadRepository;
 use crate::{Job, JobError};
 
 use super::action_gmail::create_gmail_client;
@@ -147,14 +149,34 @@ pub async fn handle_undo_action(dispatcher: &JobDispatcher, job: Job) -> Result<
             &inverse_parameters,
         )
         .await
-    } else if let Some(inverse_action) = inverse_action {
-        execute_inverse_action(
+    } else if original_action.action_type == "mute_thread" {
+        undo_mute_thread(
+            dispatcher,
             &gmail_client,
-            &message.provider_message_id,
+            &original_action,
             inverse_action,
             &inverse_parameters,
         )
         .await
+    } else if let Some(inverse_action) = inverse_action {
+        if is_thread_undo_hint(&original_action.undo_hint_json) {
+            let provider_message_ids = thread_message_ids(&original_action.undo_hint_json)?;
+            revert_messages(
+                &gmail_client,
+                &provider_message_ids,
+                inverse_action,
+                &inverse_parameters,
+            )
+            .await
+        } else {
+            execute_inverse_action(
+                &gmail_client,
+                &message.provider_message_id,
+                inverse_action,
+                &inverse_parameters,
+            )
+            .await
+        }
     } else {
         Err(JobError::Fatal(
             "no inverse action available for undo".to_string(),
@@ -174,6 +196,18 @@ pub async fn handle_undo_action(dispatcher: &JobDispatcher, job: Job) -> Result<
                 .await
                 .map_err(|err| map_action_error("mark undo action completed", err))?;
 
+            // Undoing a decision's action is a correction the classifier should learn from
+            if let Err(err) = FeedbackRepository::new(dispatcher.db.clone())
+                .record_from_action(&original_action, FeedbackSource::Undo)
+                .await
+            {
+                warn!(
+                    original_action_id = %original_action.id,
+                    error = %err,
+                    "failed to record undo feedback"
+                );
+            }
+
             info!(
                 account_id = %payload.account_id,
                 undo_action_id = %undo_action.id,
@@ -349,9 +383,10 @@ fn validate_action_undoable(
                     undo_action.status.as_str()
                 )))
             }
-            ActionStatus::Executing | ActionStatus::Queued | ActionStatus::ApprovedPending => {
-                Ok(())
-            }
+            ActionStatus::Executing
+            | ActionStatus::Queued
+            | ActionStatus::ApprovedPending
+            | ActionStatus::PendingExecution => Ok(()),
         };
     }
 
@@ -471,6 +506,131 @@ async fn undo_snooze(
     }
 }
 
+fn is_thread_undo_hint(undo_hint: &Value) -> bool {
+    undo_hint.get("target").and_then(|v| v.as_str()) == Some("thread")
+}
+
+/// Provider message ids recorded in a thread-targeted undo hint.
+fn thread_message_ids(undo_hint: &Value) -> Result<Vec<String>, JobError> {
+    let messages = undo_hint
+        .get("messages")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| JobError::Fatal("thread undo_hint missing messages".to_string()))?;
+
+    messages
+        .iter()
+        .map(|entry| {
+            entry
+                .get("provider_message_id")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    JobError::Fatal("thread undo_hint entry missing provider_message_id".into())
+                })
+        })
+        .collect()
+}
+
+/// Applies the inverse action to each message. Messages that no longer exist are skipped;
+/// the undo only reports NotFound when none of them could be reverted.
+async fn revert_messages(
+    gmail_client: &crate::gmail::GmailClient<NoopTokenStore>,
+    provider_message_ids: &[String],
+    inverse_action: ActionType,
+    inverse_parameters: &Value,
+) -> Result<UndoExecutionResult, JobError> {
+    let mut not_found = 0;
+    for provider_message_id in provider_message_ids {
+        if let UndoExecutionResult::NotFound(_) = execute_inverse_action(
+            gmail_client,
+            provider_message_id,
+            inverse_action,
+            inverse_parameters,
+        )
+        .await?
+        {
+            not_found += 1;
+        }
+    }
+
+    if !provider_message_ids.is_empty() && not_found == provider_message_ids.len() {
+        return Ok(UndoExecutionResult::NotFound(
+            "gmail resources not found (404) for every thread message during undo".to_string(),
+        ));
+    }
+
+    Ok(UndoExecutionResult::Completed)
+}
+
+/// Undo a mute_thread action: removes the mute and returns to the inbox both the messages
+/// the mute archived and those archived on ingest while the thread was muted.
+async fn undo_mute_thread(
+    dispatcher: &JobDispatcher,
+    gmail_client: &crate::gmail::GmailClient<NoopTokenStore>,
+    original_action: &Action,
+    inverse_action: Option<ActionType>,
+    inverse_parameters: &Value,
+) -> Result<UndoExecutionResult, JobError> {
+    let inverse_action = inverse_action
+        .ok_or_else(|| JobError::Fatal("no inverse action available for undo".to_string()))?;
+    let provider_thread_id = original_action
+        .undo_hint_json
+        .get("provider_thread_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            JobError::Fatal("mute_thread undo_hint missing provider_thread_id".into())
+        })?;
+
+    MutedThreadRepository::new(dispatcher.db.clone())
+        .unmute(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &original_action.account_id,
+            provider_thread_id,
+        )
+        .await
+        .map_err(|err| JobError::retryable(format!("remove thread mute: {err}")))?;
+
+    let mut provider_message_ids = thread_message_ids(&original_action.undo_hint_json)?;
+
+    let link_repo = ActionLinkRepository::new(dispatcher.db.clone());
+    let action_repo = ActionRepository::new(dispatcher.db.clone());
+    let message_repo = MessageRepository::new(dispatcher.db.clone());
+    let links = link_repo
+        .get_by_cause_action_id(&original_action.id)
+        .await
+        .map_err(|err| map_action_link_error("load spawned actions", err))?;
+
+    for link in links
+        .into_iter()
+        .filter(|link| link.relation_type == ActionLinkRelationType::Spawned)
+    {
+        let spawned = action_repo
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &link.effect_action_id)
+            .await
+            .map_err(|err| map_action_error("load spawned action", err))?;
+        if spawned.action_type != "archive" || spawned.status != ActionStatus::Completed {
+            continue;
+        }
+
+        let message = message_repo
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &spawned.message_id)
+            .await
+            .map_err(|err| map_message_error("load spawned action message", err))?;
+        if !provider_message_ids.contains(&message.provider_message_id) {
+            provider_message_ids.push(message.provider_message_id);
+        }
+    }
+
+    revert_messages(
+        gmail_client,
+        &provider_message_ids,
+        inverse_action,
+        inverse_parameters,
+    )
+    .await
+}
+
 fn labels_from_array(value: Option<&Value>) -> Result<Option<Vec<String>>, JobError> {
     match value {
         None => Ok(None),
@@ -740,6 +900,155 @@ mod tests {
         assert_eq!(links[0].cause_action_id, undo_action.id);
     }
 
+    async fn run_undo_job(
+        db: &crate::Database,
+        server: &MockServer,
+        account_id: &str,
+        action_id: &str,
+    ) {
+        let queue = JobQueue::new(db.clone());
+        let job_id = queue
+            .enqueue(
+                JOB_TYPE,
+                json!({"account_id": account_id, "original_action_id": action_id}),
+                None,
+                0,
+            )
+            .await
+            .expect("enqueue undo job");
+        let job = queue.fetch_job(&job_id).await.expect("fetch job");
+        let dispatcher = make_dispatcher(db.clone())
+            .with_gmail_api_base(format!("{}/gmail/v1/users", server.uri()));
+        let ctx = JobContext::new(queue.clone(), job.clone());
+
+        dispatcher.execute(job, ctx).await.expect("undo succeeds");
+    }
+
+    async fn mock_add_inbox(server: &MockServer, provider_message_id: &str) {
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/gmail/v1/users/user@example.com/messages/{provider_message_id}/modify"
+            )))
+            .and(body_json(json!({"addLabelIds": ["INBOX"]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": provider_message_id,
+                "labelIds": ["INBOX"],
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn undo_thread_target_reverts_every_affected_message() {
+        let (db, _dir, account_id) = setup_account().await;
+        let message_id = seed_message(&db, &account_id, "msg-1").await;
+
+        let undo_hint = json!({
+            "action": "archive",
+            "target": "thread",
+            "provider_thread_id": "thread-1",
+            "messages": [
+                {"provider_message_id": "msg-1", "pre_labels": ["INBOX"]},
+                {"provider_message_id": "msg-2", "pre_labels": ["INBOX", "UNREAD"]},
+            ],
+            "inverse_action": "apply_label",
+            "inverse_parameters": {"label": "INBOX"},
+        });
+        let original_action =
+            seed_completed_action(&db, &account_id, &message_id, "archive", undo_hint).await;
+
+        let server = MockServer::start().await;
+        mock_add_inbox(&server, "msg-1").await;
+        mock_add_inbox(&server, "msg-2").await;
+
+        run_undo_job(&db, &server, &account_id, &original_action.id).await;
+
+        let actions = ActionRepository::new(db.clone())
+            .list_by_message_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message_id)
+            .await
+            .expect("list actions");
+        let undo_action = actions
+            .iter()
+            .find(|a| a.action_type == "undo_archive")
+            .expect("undo action");
+        assert_eq!(undo_action.status, ActionStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn undo_mute_thread_unmutes_and_restores_auto_archived_messages() {
+        let (db, _dir, account_id) = setup_account().await;
+        let message_id = seed_message(&db, &account_id, "msg-1").await;
+        let later_message_id = seed_message(&db, &account_id, "msg-3").await;
+
+        let undo_hint = json!({
+            "action": "mute_thread",
+            "target": "thread",
+            "provider_thread_id": "thread-1",
+            "messages": [{"provider_message_id": "msg-1", "pre_labels": ["INBOX"]}],
+            "inverse_action": "apply_label",
+            "inverse_parameters": {"label": "INBOX"},
+        });
+        let mute_action =
+            seed_completed_action(&db, &account_id, &message_id, "mute_thread", undo_hint).await;
+
+        let muted_repo = MutedThreadRepository::new(db.clone());
+        muted_repo
+            .mute(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thread-1",
+                None,
+                Some(&mute_action.id),
+            )
+            .await
+            .expect("mute");
+
+        // A message archived on ingest while the thread was muted
+        let auto_archive = seed_completed_action(
+            &db,
+            &account_id,
+            &later_message_id,
+            "archive",
+            json!({"inverse_action": "apply_label", "inverse_parameters": {"label": "INBOX"}}),
+        )
+        .await;
+        ActionLinkRepository::new(db.clone())
+            .create(NewActionLink {
+                cause_action_id: mute_action.id.clone(),
+                effect_action_id: auto_archive.id.clone(),
+                relation_type: ActionLinkRelationType::Spawned,
+            })
+            .await
+            .expect("link spawned archive");
+
+        let server = MockServer::start().await;
+        mock_add_inbox(&server, "msg-1").await;
+        mock_add_inbox(&server, "msg-3").await;
+
+        run_undo_job(&db, &server, &account_id, &mute_action.id).await;
+
+        assert!(
+            muted_repo
+                .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, "thread-1")
+                .await
+                .expect("load mute")
+                .is_none(),
+            "undo should remove the mute"
+        );
+
+        let actions = ActionRepository::new(db.clone())
+            .list_by_message_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message_id)
+            .await
+            .expect("list actions");
+        let undo_action = actions
+            .iter()
+            .find(|a| a.action_type == "undo_mute_thread")
+            .expect("undo action");
+        assert_eq!(undo_action.status, ActionStatus::Completed);
+    }
+
     #[tokio::test]
     async fn irreversible_actions_rejected() {
         let (db, _dir, account_id) = setup_account().await;
@@ -165,6 +165,27 @@ impl LabelRepository {
         Ok(labels)
     }
 
+    /// Get all labels across every account for the org/user.
+    pub async fn list_all(&self, org_id: i64, user_id: i64) -> Result<Vec<Label>, LabelError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {LABEL_COLUMNS} FROM labels
+                     WHERE org_id = ?1 AND user_id = ?2
+                     ORDER BY account_id, name"
+                ),
+                params![org_id, user_id],
+            )
+            .await?;
+
+        let mut labels = Vec::new();
+        while let Some(row) = rows.next().await? {
+            labels.push(row_to_label(row)?);
+        }
+        Ok(labels)
+    }
+
     /// Lookup a label by account_id + provider_label_id.
     pub async fn get_by_provider_id(
         &self,
@@ -1,9 +1,15 @@
 pub mod accounts;
 pub mod api;
+pub mod bulk_undo;
+pub mod circuit_breaker;
 pub mod config;
 pub mod constants;
+pub mod contacts;
 pub mod db;
 pub mod decisions;
+pub mod digest;
+pub mod eval;
+pub mod feedback;
 pub mod gmail;
 pub mod jobs;
 pub mod labels;
@@ -13,7 +19,9 @@ pub mod migrations;
 pub mod pubsub;
 pub mod pubsub_listener;
 pub mod queue;
+pub mod reply_templates;
 pub mod rules;
+pub mod similar_messages;
 pub mod telemetry;
 pub mod threads;
 pub mod worker;
@@ -23,43 +31,82 @@ pub use accounts::{
 };
 pub use api::{
     AccountSummary, ActionDetail, ActionListFilter, ActionListItem, LabelColors, LabelSummary,
-    MessageSummary, PaginatedResponse, UndoActionResponse,
+    MessageSummary, PaginatedResponse, ThreadDetail, UndoActionResponse,
+};
+pub use bulk_undo::{
+    BulkUndo, BulkUndoCandidate, BulkUndoError, BulkUndoFilter, BulkUndoItem, BulkUndoItemStatus,
+    BulkUndoPreview, BulkUndoProgress, BulkUndoRepository, BulkUndoSkip, BulkUndoSkipReason,
+    MAX_BULK_UNDO_ACTIONS,
+};
+pub use circuit_breaker::{
+    BurstAction, BurstReport, CircuitBreakerError, CircuitBreakerHold, CircuitBreakerRepository,
+    CircuitBreakerScope, CircuitBreakerTrip, TrackedAction,
+};
+pub use config::{
+    ApprovalConfig, ApprovalTimeout, ApprovalTimeoutOutcome, BackfillConfig, BackfillMode,
+    BudgetConfig, BudgetExceededAction, BudgetLimit, CircuitBreakerConfig, CircuitBreakerWindow,
+    Config, DecisionCacheConfig, DigestChannel, DigestConfig, DigestFrequency, DigestSection,
+    DirectionCheckConfig, ExecutionDelayConfig, ModelPricing, ModelRef, PolicyConfig,
+    PricingConfig, RedactionConfig, RoutingConfig, SimilarMessagesConfig, ThreadSummaryConfig,
 };
-pub use config::{Config, PolicyConfig};
 pub use constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+pub use contacts::{Contact, ContactError, ContactRepository, ContactStrength};
 pub use db::Database;
 pub use decisions::{
     Action, ActionDangerLevel, ActionDetailRow, ActionError, ActionLink, ActionLinkError,
-    ActionLinkRelationType, ActionListItemRow, ActionRepository, ActionStatus, Decision,
-    DecisionError, DecisionRepository, DecisionSource, NewAction, NewActionLink, NewDecision,
-    SafetyEnforcer, SafetyOverride, SafetyResult,
+    ActionLinkRelationType, ActionListItemRow, ActionRepository, ActionStatus, CachedDecision,
+    Decision, DecisionCache, DecisionCacheError, DecisionError, DecisionRepository, DecisionSource,
+    NewAction, NewActionLink, NewDecision, NewPolicyClause, PolicyClause, PolicyClauseError,
+    PolicyClauseRepository, PolicyEffect, PolicySubject, SafetyEnforcer, SafetyOverride,
+    SafetyResult,
+};
+pub use digest::{Digest, DigestError, DigestRepository, next_digest_at};
+pub use eval::{
+    EvalCase, EvalCaseResult, EvalError, EvalMessage, EvalReport, EvalRunner, ReplayLLMClient,
 };
+pub use feedback::{ClassificationFeedback, FeedbackError, FeedbackRepository, FeedbackSource};
 pub use gmail::{
     DEFAULT_REFRESH_BUFFER, GmailClient, GmailClientError, NoopTokenStore, OAuthError, OAuthTokens,
     TokenStore,
 };
 pub use jobs::{
-    JOB_TYPE_ACTION_GMAIL, JOB_TYPE_APPROVAL_NOTIFY, JOB_TYPE_CLASSIFY,
-    JOB_TYPE_HISTORY_SYNC_GMAIL, JOB_TYPE_INGEST_GMAIL, JOB_TYPE_UNSNOOZE_GMAIL, JobDispatcher,
+    JOB_TYPE_ACTION_GMAIL, JOB_TYPE_APPROVAL_NOTIFY, JOB_TYPE_APPROVAL_REMIND,
+    JOB_TYPE_APPROVAL_TIMEOUT, JOB_TYPE_CIRCUIT_BREAKER_ALERT, JOB_TYPE_CLASSIFY,
+    JOB_TYPE_CLASSIFY_BATCH, JOB_TYPE_DIGEST_SEND, JOB_TYPE_HISTORY_SYNC_GMAIL,
+    JOB_TYPE_INGEST_GMAIL, JOB_TYPE_PENDING_EXECUTION_NOTIFY, JOB_TYPE_SUMMARIZE_THREAD,
+    JOB_TYPE_UNDO_ACTION, JOB_TYPE_UNSNOOZE_GMAIL, JobDispatcher, schedule_digests,
 };
 pub use labels::{Label, LabelError, LabelRepository, NewLabel};
 pub use llm::{
-    ChatMessage, ChatRole, CompletionRequest, CompletionResponse, GenaiLLMClient, LLMClient,
-    LLMError, LlmCall, LlmCallContext, LlmCallError, LlmCallRepository, MockLLMClient, NewLlmCall,
-    RateLimitInfo,
+    BudgetExceeded, ChatMessage, ChatRole, CompletionRequest, CompletionResponse, GenaiLLMClient,
+    LLMClient, LLMError, LlmCall, LlmCallContext, LlmCallError, LlmCallRepository, MockLLMClient,
+    NewLlmCall, RateLimitInfo, SpendError, SpendReport, SpendSummary, SpendTotals, SpendTracker,
+    SpendWindow,
 };
 pub use messages::{
     Mailbox, Message as StoredMessage, MessageError, MessageRepository, NewMessage,
 };
 pub use pubsub::{GmailNotification, PubsubError};
 pub use queue::{Job, JobContext, JobQueue, JobState};
+pub use reply_templates::{
+    BUILTIN_VARIABLES, NewReplyTemplate, RenderedReply, ReplyTemplate, ReplyTemplateError,
+    ReplyTemplateRepository, TemplateSlot, template_variables,
+};
 pub use rules::{
     DeterministicRule, DeterministicRuleError, DeterministicRuleRepository, Direction,
-    DirectionError, DirectionsRepository, LlmRule, LlmRuleError, LlmRuleRepository,
-    NewDeterministicRule, NewDirection, NewLlmRule, NewRulesChatMessage, NewRulesChatSession,
-    RuleScope, RulesChatMessage, RulesChatMessageError, RulesChatMessageRepository, RulesChatRole,
-    RulesChatSession, RulesChatSessionError, RulesChatSessionRepository, SafeMode,
+    DirectionError, DirectionsRepository, LintError, LintKind, LintWarning, LlmRule, LlmRuleError,
+    LlmRuleRepository, NewDeterministicRule, NewDirection, NewLlmRule, NewRulesChatMessage,
+    NewRulesChatSession, NewSenderList, RuleLinter, RuleSchedule, RuleScope, RulesChatMessage,
+    RulesChatMessageError, RulesChatMessageRepository, RulesChatRole, RulesChatSession,
+    RulesChatSessionError, RulesChatSessionRepository, SafeMode, ScheduleDay, ScheduleError,
+    SenderList, SenderListError, SenderListRepository,
+};
+pub use similar_messages::{
+    MessageEmbeddingRepository, SimilarMessage, SimilarMessagesError, SimilarityQuery,
 };
 pub use telemetry::{TelemetryError, TelemetryGuard, init_logging, init_telemetry};
-pub use threads::{Thread, ThreadError, ThreadRepository};
+pub use threads::{
+    MutedThread, MutedThreadRepository, NewThreadSummary, Thread, ThreadError, ThreadRepository,
+    ThreadSummary, ThreadSummaryRepository,
+};
 pub use worker::{JobError, JobExecutor, NoopExecutor, WorkerConfig, run_worker};
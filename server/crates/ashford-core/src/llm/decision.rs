@@ -4,7 +4,7 @@ use serde_json::Value;
 use std::str::FromStr;
 use thiserror::Error;
 
-use super::types::ToolCallResult;
+use super::types::{CompletionResponse, ToolCallResult};
 use crate::decisions::policy::ActionDangerLevel;
 
 /// Supported actions that the LLM may return.
@@ -26,7 +26,9 @@ pub enum ActionType {
     AutoReply,
     CreateTask,
     Snooze,
+    MuteThread,
     AddNote,
+    Summarize,
     Escalate,
     None,
 }
@@ -49,7 +51,9 @@ impl ActionType {
             ActionType::AutoReply => "auto_reply",
             ActionType::CreateTask => "create_task",
             ActionType::Snooze => "snooze",
+            ActionType::MuteThread => "mute_thread",
             ActionType::AddNote => "add_note",
+            ActionType::Summarize => "summarize",
             ActionType::Escalate => "escalate",
             ActionType::None => "none",
         }
@@ -57,8 +61,9 @@ impl ActionType {
 
     /// Returns the danger level classification for this action type.
     ///
-    /// - Safe: ApplyLabel, RemoveLabel, MarkRead, MarkUnread, Archive, Move, Trash, Restore, None
-    /// - Reversible: Star, Unstar, Snooze, AddNote, CreateTask
+    /// - Safe: ApplyLabel, RemoveLabel, MarkRead, MarkUnread, Archive, Move, Trash, Restore,
+    ///   Summarize, None
+    /// - Reversible: Star, Unstar, Snooze, MuteThread, AddNote, CreateTask
     /// - Dangerous: Delete, Forward, AutoReply, Escalate
     pub fn danger_level(&self) -> ActionDangerLevel {
         match self {
@@ -71,12 +76,14 @@ impl ActionType {
             | ActionType::Trash
             | ActionType::Restore
             | ActionType::Move
+            | ActionType::Summarize
             | ActionType::None => ActionDangerLevel::Safe,
 
             // Reversible actions - can be easily undone
             ActionType::Star
             | ActionType::Unstar
             | ActionType::Snooze
+            | ActionType::MuteThread
             | ActionType::AddNote
             | ActionType::CreateTask => ActionDangerLevel::Reversible,
 
@@ -109,7 +116,9 @@ impl FromStr for ActionType {
             "auto_reply" => Ok(Self::AutoReply),
             "create_task" => Ok(Self::CreateTask),
             "snooze" => Ok(Self::Snooze),
+            "mute_thread" => Ok(Self::MuteThread),
             "add_note" => Ok(Self::AddNote),
+            "summarize" => Ok(Self::Summarize),
             "escalate" => Ok(Self::Escalate),
             "none" => Ok(Self::None),
             _ => Err(()),
@@ -294,6 +303,70 @@ impl DecisionOutput {
         parsed.validate()?;
         Ok(parsed)
     }
+
+    /// Parse a decision from a completion response.
+    ///
+    /// Uses the tool call when there is one. Models without tool support answer with the
+    /// decision JSON as plain content, which is parsed with [`DecisionOutput::parse`].
+    pub fn parse_from_completion(
+        response: &CompletionResponse,
+        expected_tool_name: &str,
+    ) -> Result<Self, DecisionParseError> {
+        match Self::parse_from_tool_calls(&response.tool_calls, expected_tool_name) {
+            Err(DecisionParseError::NoToolCall) if !response.content.trim().is_empty() => {
+                Self::parse(&response.content)
+            }
+            other => other,
+        }
+    }
+}
+
+/// Arguments of the batch decision tool: one decision per message of the batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BatchDecisionOutput {
+    pub decisions: Vec<DecisionOutput>,
+}
+
+/// The batch as returned, before each decision is checked on its own.
+#[derive(Deserialize)]
+struct RawBatchDecisions {
+    decisions: Vec<Value>,
+}
+
+impl BatchDecisionOutput {
+    /// Parse the decisions of a batch response, from its tool call or, for models without
+    /// tool support, its content.
+    ///
+    /// Each decision is parsed and validated on its own, so one bad item does not discard the
+    /// rest; the caller falls back to classifying that message alone.
+    pub fn parse_from_completion(
+        response: &CompletionResponse,
+        expected_tool_name: &str,
+    ) -> Result<Vec<Result<DecisionOutput, DecisionParseError>>, DecisionParseError> {
+        let raw: RawBatchDecisions = match response.tool_calls.first() {
+            Some(tool_call) if tool_call.fn_name != expected_tool_name => {
+                return Err(DecisionParseError::WrongToolName {
+                    expected: expected_tool_name.to_string(),
+                    actual: tool_call.fn_name.clone(),
+                });
+            }
+            Some(tool_call) => serde_json::from_value(tool_call.fn_arguments.clone())?,
+            None if !response.content.trim().is_empty() => {
+                serde_json::from_str(extract_json_from_response(&response.content)?)?
+            }
+            None => return Err(DecisionParseError::NoToolCall),
+        };
+
+        Ok(raw
+            .decisions
+            .into_iter()
+            .map(|value| {
+                let decision: DecisionOutput = serde_json::from_value(value)?;
+                decision.validate()?;
+                Ok(decision)
+            })
+            .collect())
+    }
 }
 
 /// Extracts the JSON slice from an LLM response that may contain extra text or code fences.
@@ -418,7 +491,9 @@ mod tests {
             ActionType::AutoReply,
             ActionType::CreateTask,
             ActionType::Snooze,
+            ActionType::MuteThread,
             ActionType::AddNote,
+            ActionType::Summarize,
             ActionType::Escalate,
             ActionType::None,
         ] {
@@ -645,6 +720,72 @@ mod tests {
         assert!(matches!(err, DecisionParseError::Json(_)));
     }
 
+    #[test]
+    fn parse_from_completion_falls_back_to_content() {
+        let decision = sample_decision();
+        let mut response = CompletionResponse {
+            content: format!(
+                "```json\n{}\n```",
+                serde_json::to_string(&decision).unwrap()
+            ),
+            model: "openai::llama3".into(),
+            input_tokens: 0,
+            output_tokens: 0,
+            latency_ms: 0,
+            llm_call_id: None,
+            tool_calls: vec![],
+        };
+        let parsed = DecisionOutput::parse_from_completion(&response, "record_decision")
+            .expect("content should parse");
+        assert_eq!(parsed, decision);
+
+        response.content = "  ".into();
+        let err = DecisionOutput::parse_from_completion(&response, "record_decision").unwrap_err();
+        assert_eq!(err, DecisionParseError::NoToolCall);
+    }
+
+    #[test]
+    fn batch_parse_keeps_valid_decisions_when_others_fail() {
+        let valid = sample_decision();
+        let mut invalid = sample_decision();
+        invalid.decision.confidence = 1.5;
+        let mut response = CompletionResponse {
+            content: String::new(),
+            model: "openai::gpt-4o-mini".into(),
+            input_tokens: 0,
+            output_tokens: 0,
+            latency_ms: 0,
+            llm_call_id: None,
+            tool_calls: vec![ToolCallResult {
+                call_id: "call_1".into(),
+                fn_name: "record_decisions".into(),
+                fn_arguments: serde_json::json!({
+                    "decisions": [valid, invalid, {"message_ref": "broken"}]
+                }),
+            }],
+        };
+
+        let parsed = BatchDecisionOutput::parse_from_completion(&response, "record_decisions")
+            .expect("batch parses");
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].as_ref().unwrap(), &valid);
+        assert_eq!(
+            parsed[1].as_ref().unwrap_err(),
+            &DecisionParseError::Validation(DecisionValidationError::InvalidConfidence(1.5))
+        );
+        assert!(matches!(parsed[2], Err(DecisionParseError::Json(_))));
+
+        let err =
+            BatchDecisionOutput::parse_from_completion(&response, "record_decision").unwrap_err();
+        assert!(matches!(err, DecisionParseError::WrongToolName { .. }));
+
+        response.tool_calls.clear();
+        response.content = serde_json::json!({"decisions": [valid]}).to_string();
+        let parsed = BatchDecisionOutput::parse_from_completion(&response, "record_decisions")
+            .expect("content parses");
+        assert_eq!(parsed.len(), 1);
+    }
+
     #[test]
     fn action_type_danger_level_classifications() {
         // Safe actions
@@ -657,6 +798,7 @@ mod tests {
             ActionType::Trash,
             ActionType::Restore,
             ActionType::Move,
+            ActionType::Summarize,
             ActionType::None,
         ] {
             assert_eq!(
@@ -672,6 +814,7 @@ mod tests {
             ActionType::Star,
             ActionType::Unstar,
             ActionType::Snooze,
+            ActionType::MuteThread,
             ActionType::AddNote,
             ActionType::CreateTask,
         ] {
@@ -719,13 +862,15 @@ mod tests {
             ActionType::AutoReply,
             ActionType::CreateTask,
             ActionType::Snooze,
+            ActionType::MuteThread,
             ActionType::AddNote,
+            ActionType::Summarize,
             ActionType::Escalate,
             ActionType::None,
         ];
 
-        // All 18 action types should have a danger level
-        assert_eq!(all_actions.len(), 18);
+        // All 20 action types should have a danger level
+        assert_eq!(all_actions.len(), 20);
         for action in all_actions {
             // This should not panic - just confirm we get a valid danger level
             let _ = action.danger_level();
@@ -0,0 +1,316 @@
+//! Text embeddings for similar-message retrieval.
+//!
+//! [`GenaiEmbeddingClient`] calls a provider's embedding endpoint and records the call in
+//! `llm_calls` like chat completions. [`HashingEmbedder`] runs on-box: it hashes words and word
+//! pairs into a fixed-size vector, so nothing leaves the machine and nothing needs downloading.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use genai::{Client as GenaiClient, ServiceTarget, resolver::AuthData, resolver::Endpoint};
+use serde_json::json;
+use tracing::warn;
+
+use super::repository::{LlmCallContext, LlmCallRepository, NewLlmCall};
+use super::{LLMError, endpoint_url, map_genai_error, namespace_model};
+use crate::config::SimilarMessagesConfig;
+use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+use crate::db::Database;
+
+/// Turns text into a vector. Vectors are only comparable when they come from the same model.
+#[async_trait]
+pub trait EmbeddingClient: Send + Sync {
+    /// Identifier stored with each vector.
+    fn model(&self) -> &str;
+
+    async fn embed(&self, text: &str, context: LlmCallContext) -> Result<Vec<f32>, LLMError>;
+}
+
+/// The embedding client configured in `[similar_messages]`: the on-box embedder for the
+/// "local" provider, otherwise the provider's embedding model.
+pub fn build_embedding_client(
+    db: Database,
+    config: &SimilarMessagesConfig,
+) -> Arc<dyn EmbeddingClient> {
+    if config.provider.eq_ignore_ascii_case("local") {
+        Arc::new(HashingEmbedder::new(config.dimensions))
+    } else {
+        Arc::new(GenaiEmbeddingClient::new(db, config))
+    }
+}
+
+/// Embedding client backed by the genai crate.
+pub struct GenaiEmbeddingClient {
+    client: GenaiClient,
+    model: String,
+    repo: LlmCallRepository,
+}
+
+impl GenaiEmbeddingClient {
+    pub fn new(db: Database, config: &SimilarMessagesConfig) -> Self {
+        let client = if config.base_url.is_some() || config.api_key.is_some() {
+            let base_url = config.base_url.as_deref().map(endpoint_url);
+            let api_key = config.api_key.clone();
+            GenaiClient::builder()
+                .with_service_target_resolver_fn(move |mut target: ServiceTarget| {
+                    if let Some(url) = &base_url {
+                        target.endpoint = Endpoint::from_owned(url.clone());
+                    }
+                    if let Some(key) = &api_key {
+                        target.auth = AuthData::from_single(key.clone());
+                    }
+                    Ok(target)
+                })
+                .build()
+        } else {
+            GenaiClient::default()
+        };
+        Self {
+            client,
+            model: namespace_model(&config.provider, &config.model),
+            repo: LlmCallRepository::new(db),
+        }
+    }
+
+    async fn log_call(
+        &self,
+        context: LlmCallContext,
+        input_chars: usize,
+        dimensions: Option<usize>,
+        input_tokens: Option<u32>,
+        latency_ms: u64,
+        error: Option<String>,
+    ) {
+        let org_id = context.org_id.unwrap_or(DEFAULT_ORG_ID);
+        let user_id = context.user_id.unwrap_or(DEFAULT_USER_ID);
+        let new_call = NewLlmCall {
+            org_id,
+            user_id,
+            context,
+            model: self.model.clone(),
+            // The text itself is not kept; it is the message content again
+            request_json: json!({ "input_chars": input_chars }),
+            response_json: dimensions.map(|dimensions| json!({ "dimensions": dimensions })),
+            input_tokens,
+            output_tokens: input_tokens.map(|_| 0),
+            latency_ms: Some(latency_ms),
+            error,
+            trace_id: None,
+        };
+        if let Err(err) = self.repo.create(new_call).await {
+            warn!(error = ?err, "failed to record embedding call");
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingClient for GenaiEmbeddingClient {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, text: &str, context: LlmCallContext) -> Result<Vec<f32>, LLMError> {
+        let start = Instant::now();
+        let result = self.client.embed(&self.model, text, None).await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(response) => {
+                let input_tokens = response
+                    .usage
+                    .prompt_tokens
+                    .map(|tokens| tokens.max(0) as u32);
+                let Some(vector) = response.into_vectors().into_iter().next() else {
+                    let err = LLMError::ParseError("embedding response has no vector".into());
+                    self.log_call(
+                        context,
+                        text.len(),
+                        None,
+                        input_tokens,
+                        latency_ms,
+                        Some(err.to_string()),
+                    )
+                    .await;
+                    return Err(err);
+                };
+                self.log_call(
+                    context,
+                    text.len(),
+                    Some(vector.len()),
+                    input_tokens,
+                    latency_ms,
+                    None,
+                )
+                .await;
+                Ok(vector)
+            }
+            Err(err) => {
+                let mapped = map_genai_error(err);
+                self.log_call(
+                    context,
+                    text.len(),
+                    None,
+                    None,
+                    latency_ms,
+                    Some(mapped.to_string()),
+                )
+                .await;
+                Err(mapped)
+            }
+        }
+    }
+}
+
+/// On-box embedder using feature hashing of lowercased words and adjacent word pairs.
+///
+/// Much weaker than a trained model, but it finds messages that share wording, such as
+/// repeated notifications from the same service.
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    dimensions: usize,
+    model: String,
+}
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        let dimensions = dimensions.max(1);
+        Self {
+            dimensions,
+            model: format!("local-hash-{dimensions}"),
+        }
+    }
+
+    /// The L2-normalized vector for `text`; all zeros when it has no words.
+    pub fn vector(&self, text: &str) -> Vec<f32> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.chars().count() >= 2)
+            .map(str::to_lowercase)
+            .collect();
+
+        let mut vector = vec![0f32; self.dimensions];
+        let pairs = words
+            .windows(2)
+            .map(|pair| format!("{} {}", pair[0], pair[1]));
+        for feature in words.iter().cloned().chain(pairs) {
+            let hash = fnv1a(feature.as_bytes());
+            let index = (hash % self.dimensions as u64) as usize;
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[index] += sign;
+        }
+
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        vector
+    }
+}
+
+#[async_trait]
+impl EmbeddingClient for HashingEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, text: &str, _context: LlmCallContext) -> Result<Vec<f32>, LLMError> {
+        Ok(self.vector(text))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Cosine similarity of two vectors; 0 when their lengths differ or either is all zeros.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::run_migrations;
+    use tempfile::TempDir;
+
+    #[test]
+    fn hashing_embedder_scores_shared_wording_higher() {
+        let embedder = HashingEmbedder::new(256);
+        assert_eq!(embedder.model(), "local-hash-256");
+
+        let invoice = embedder.vector("Your invoice #1042 from Acme Hosting is ready");
+        let similar = embedder.vector("Your invoice #1077 from Acme Hosting is ready to view");
+        let unrelated = embedder.vector("Lunch on Friday? The team is going to the new place");
+
+        assert_eq!(invoice.len(), 256);
+        assert!((cosine_similarity(&invoice, &invoice) - 1.0).abs() < 1e-5);
+        assert!(cosine_similarity(&invoice, &similar) > 0.6);
+        assert!(cosine_similarity(&invoice, &similar) > cosine_similarity(&invoice, &unrelated));
+        assert_eq!(embedder.vector("!!!"), vec![0.0; 256]);
+        assert_eq!(cosine_similarity(&invoice, &[1.0]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn genai_embedding_client_calls_custom_endpoint_and_logs_call() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .and(header("authorization", "Bearer local-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "object": "list",
+                "data": [{"object": "embedding", "index": 0, "embedding": [0.6, 0.8]}],
+                "model": "nomic-embed-text",
+                "usage": {"prompt_tokens": 7, "total_tokens": 7}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("db");
+        run_migrations(&db).await.expect("migrations");
+
+        let config = SimilarMessagesConfig {
+            provider: "openai".into(),
+            model: "nomic-embed-text".into(),
+            base_url: Some(format!("{}/v1", server.uri())),
+            api_key: Some("local-key".into()),
+            ..Default::default()
+        };
+        let client = build_embedding_client(db.clone(), &config);
+        assert_eq!(client.model(), "openai::nomic-embed-text");
+
+        let vector = client
+            .embed("Invoice from Acme", LlmCallContext::new("similar_messages"))
+            .await
+            .expect("embedding");
+        assert_eq!(vector, vec![0.6, 0.8]);
+
+        let calls = LlmCallRepository::new(db)
+            .list(DEFAULT_ORG_ID, DEFAULT_USER_ID, None, None)
+            .await
+            .expect("calls");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].feature, "similar_messages");
+        assert_eq!(calls[0].input_tokens, Some(7));
+        assert_eq!(calls[0].response_json, Some(json!({"dimensions": 2})));
+    }
+}
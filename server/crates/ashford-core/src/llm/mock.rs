@@ -10,6 +10,7 @@ use super::{CompletionRequest, CompletionResponse, LLMClient, LLMError, LlmCallC
 pub struct MockLLMClient {
     responses: Arc<Mutex<VecDeque<Result<CompletionResponse, LLMError>>>>,
     call_count: Arc<AtomicUsize>,
+    calls: Arc<Mutex<Vec<(CompletionRequest, LlmCallContext)>>>,
 }
 
 impl MockLLMClient {
@@ -26,16 +27,25 @@ impl MockLLMClient {
     pub fn call_count(&self) -> usize {
         self.call_count.load(Ordering::SeqCst)
     }
+
+    /// Returns the request and context of every `complete` call, oldest first.
+    pub fn calls(&self) -> Vec<(CompletionRequest, LlmCallContext)> {
+        self.calls.lock().expect("lock calls").clone()
+    }
 }
 
 #[async_trait]
 impl LLMClient for MockLLMClient {
     async fn complete(
         &self,
-        _request: CompletionRequest,
-        _context: LlmCallContext,
+        request: CompletionRequest,
+        context: LlmCallContext,
     ) -> Result<CompletionResponse, LLMError> {
         self.call_count.fetch_add(1, Ordering::SeqCst);
+        self.calls
+            .lock()
+            .expect("lock calls")
+            .push((request, context));
         let mut guard = self.responses.lock().expect("lock responses");
         guard.pop_front().unwrap_or_else(|| {
             Err(LLMError::ProviderError(
@@ -58,6 +68,7 @@ mod tests {
             input_tokens: 10,
             output_tokens: 2,
             latency_ms: 50,
+            llm_call_id: None,
             tool_calls: vec![],
         };
         let response_two = CompletionResponse {
@@ -66,6 +77,7 @@ mod tests {
             input_tokens: 20,
             output_tokens: 4,
             latency_ms: 75,
+            llm_call_id: None,
             tool_calls: vec![],
         };
 
@@ -78,6 +90,7 @@ mod tests {
             temperature: 0.0,
             max_tokens: 0,
             json_mode: false,
+            model: None,
             tools: vec![],
         };
         let context = LlmCallContext::new("test");
@@ -103,6 +116,7 @@ mod tests {
             temperature: 0.0,
             max_tokens: 0,
             json_mode: false,
+            model: None,
             tools: vec![],
         };
         let context = LlmCallContext::new("test");
@@ -122,6 +136,7 @@ mod tests {
             input_tokens: 1,
             output_tokens: 1,
             latency_ms: 10,
+            llm_call_id: None,
             tool_calls: vec![],
         };
         mock.enqueue_response(Ok(response.clone()));
@@ -132,6 +147,7 @@ mod tests {
             temperature: 0.0,
             max_tokens: 0,
             json_mode: false,
+            model: None,
             tools: vec![],
         };
         let context = LlmCallContext::new("test");
@@ -141,5 +157,7 @@ mod tests {
         assert_eq!(mock.call_count(), 1);
         let _ = mock.complete(request, context).await;
         assert_eq!(mock.call_count(), 2);
+        assert_eq!(mock.calls().len(), 2);
+        assert_eq!(mock.calls()[0].1.feature, "test");
     }
 }
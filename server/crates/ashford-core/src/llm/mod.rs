@@ -1,44 +1,63 @@
 pub mod decision;
+pub mod embedding;
 pub mod error;
+pub mod injection;
 pub mod mock;
 pub mod prompt;
+pub mod redaction;
 pub mod repository;
+pub mod spend;
+pub mod summary;
 pub mod types;
 
 pub use decision::{
-    ActionType, ConsideredAlternative, DecisionDetails, DecisionOutput, DecisionParseError,
-    DecisionValidationError, Explanations, MessageRef, TelemetryPlaceholder, UndoHint,
+    ActionType, BatchDecisionOutput, ConsideredAlternative, DecisionDetails, DecisionOutput,
+    DecisionParseError, DecisionValidationError, Explanations, MessageRef, TelemetryPlaceholder,
+    UndoHint,
+};
+pub use embedding::{
+    EmbeddingClient, GenaiEmbeddingClient, HashingEmbedder, build_embedding_client,
 };
 pub use error::{LLMError, RateLimitInfo};
+pub use injection::{InjectionSignal, UntrustedContent, detect_injection, untrusted_recipients};
 pub use mock::MockLLMClient;
 pub use prompt::{
-    DECISION_TOOL_NAME, PromptBuilder, PromptBuilderConfig, ThreadContext, build_decision_tool,
+    BATCH_DECISION_TOOL_NAME, ContentFence, DECISION_TOOL_NAME, PromptBuilder, PromptBuilderConfig,
+    PromptContext, ThreadContext, build_batch_decision_tool, build_decision_tool,
 };
+pub use redaction::{RedactionError, Redactions, Redactor};
 pub use repository::{LlmCall, LlmCallContext, LlmCallError, LlmCallRepository, NewLlmCall};
+pub use spend::{
+    BudgetExceeded, SpendError, SpendReport, SpendSummary, SpendTotals, SpendTracker, SpendWindow,
+};
+pub use summary::{SUMMARY_TOOL_NAME, ThreadSummaryOutput, build_summary_tool};
 pub use types::{
     ChatMessage, ChatRole, CompletionRequest, CompletionResponse, Tool, ToolCall, ToolCallResult,
 };
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use genai::{
-    Client as GenaiClient, Error as GenaiError,
+    Client as GenaiClient, Error as GenaiError, ServiceTarget,
+    adapter::AdapterKind,
     chat::{
         ChatMessage as GenaiChatMessage, ChatOptions, ChatRequest, ChatResponse,
         ChatResponseFormat, MessageContent,
     },
+    resolver::{AuthData, Endpoint},
     webc,
 };
 use reqwest::{
     StatusCode,
     header::{HeaderMap, HeaderValue, RETRY_AFTER},
 };
-use tracing::warn;
+use tracing::{info, warn};
 
-use crate::config::ModelConfig;
+use crate::config::{ModelConfig, PricingConfig, RoutingConfig};
 use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
 use crate::db::Database;
 
@@ -74,16 +93,94 @@ impl ChatExecutor for GenaiClient {
     }
 }
 
+/// Custom endpoint settings from `[model]`.
+///
+/// They apply to every call whose model resolves to the same genai adapter as the configured
+/// model, so routed models on that provider share the endpoint.
+#[derive(Debug, Clone)]
+struct ProviderEndpoint {
+    adapter_kind: AdapterKind,
+    base_url: Option<String>,
+    api_key: Option<String>,
+    headers: Vec<(String, String)>,
+    supports_tools: bool,
+}
+
+impl ProviderEndpoint {
+    fn from_config(cfg: &ModelConfig) -> Option<Self> {
+        let customized = cfg.base_url.is_some()
+            || cfg.api_key.is_some()
+            || !cfg.headers.is_empty()
+            || !cfg.supports_tools;
+        if !customized {
+            return None;
+        }
+        let adapter_kind = AdapterKind::from_model(&namespaced_model(cfg)).ok()?;
+        Some(Self {
+            adapter_kind,
+            base_url: cfg.base_url.as_deref().map(endpoint_url),
+            api_key: cfg.api_key.clone(),
+            headers: cfg
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            supports_tools: cfg.supports_tools,
+        })
+    }
+
+    fn applies_to(&self, model: &str) -> bool {
+        AdapterKind::from_model(model).is_ok_and(|kind| kind == self.adapter_kind)
+    }
+
+    fn resolve_target(&self, mut target: ServiceTarget) -> ServiceTarget {
+        if target.model.adapter_kind != self.adapter_kind {
+            return target;
+        }
+        if let Some(url) = &self.base_url {
+            target.endpoint = Endpoint::from_owned(url.clone());
+        }
+        if let Some(key) = &self.api_key {
+            target.auth = AuthData::from_single(key.clone());
+        }
+        target
+    }
+}
+
+/// `url` as a genai endpoint. Endpoint URLs are joined with the API path, which drops a last
+/// segment without a trailing slash.
+fn endpoint_url(url: &str) -> String {
+    if url.ends_with('/') {
+        url.to_string()
+    } else {
+        format!("{url}/")
+    }
+}
+
 /// Default LLM client backed by the genai crate.
 pub struct GenaiLLMClient {
     chat: Arc<dyn ChatExecutor>,
     model: String,
+    endpoint: Option<ProviderEndpoint>,
+    /// Namespaced model per feature, from `[routing.features]`.
+    feature_models: HashMap<String, String>,
     repo: LlmCallRepository,
+    pricing: PricingConfig,
 }
 
 impl GenaiLLMClient {
     pub fn new(db: Database, model_config: ModelConfig) -> Self {
-        let chat: Arc<dyn ChatExecutor> = Arc::new(GenaiClient::default());
+        let genai = match ProviderEndpoint::from_config(&model_config) {
+            Some(endpoint) if endpoint.base_url.is_some() || endpoint.api_key.is_some() => {
+                GenaiClient::builder()
+                    .with_service_target_resolver_fn(move |target: ServiceTarget| {
+                        Ok(endpoint.resolve_target(target))
+                    })
+                    .build()
+            }
+            _ => GenaiClient::default(),
+        };
+        let chat: Arc<dyn ChatExecutor> = Arc::new(genai);
         Self::with_executor(db, model_config, chat)
     }
 
@@ -96,10 +193,44 @@ impl GenaiLLMClient {
         Self {
             chat,
             model,
+            endpoint: ProviderEndpoint::from_config(&model_config),
+            feature_models: HashMap::new(),
             repo: LlmCallRepository::new(db),
+            pricing: PricingConfig::default(),
         }
     }
 
+    /// Use the per-feature model overrides from the routing config.
+    pub fn with_routing(mut self, routing: &RoutingConfig) -> Self {
+        self.feature_models = routing
+            .features
+            .iter()
+            .map(|(feature, model)| {
+                (
+                    feature.clone(),
+                    namespace_model(&model.provider, &model.model),
+                )
+            })
+            .collect();
+        self
+    }
+
+    /// Model for a call: the request's explicit model, then the feature override, then the
+    /// default model.
+    fn route_model<'a>(&'a self, request: &'a CompletionRequest, feature: &str) -> &'a str {
+        request
+            .model
+            .as_deref()
+            .or_else(|| self.feature_models.get(feature).map(String::as_str))
+            .unwrap_or(&self.model)
+    }
+
+    /// Price completed calls so their cost is emitted with the usage event.
+    pub fn with_pricing(mut self, pricing: PricingConfig) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
     fn build_chat_request(&self, request: &CompletionRequest) -> ChatRequest {
         let messages = request
             .messages
@@ -137,12 +268,27 @@ impl GenaiLLMClient {
         output_tokens: Option<u32>,
         latency_ms: Option<u64>,
         error: Option<String>,
-    ) {
+    ) -> Option<String> {
         let org_id = context.org_id.unwrap_or(DEFAULT_ORG_ID);
         let user_id = context.user_id.unwrap_or(DEFAULT_USER_ID);
         let mut context = context.clone();
         context.org_id = Some(org_id);
         context.user_id = Some(user_id);
+
+        if let (Some(input), Some(output)) = (input_tokens, output_tokens) {
+            let cost_usd = self.pricing.cost_usd(model, input.into(), output.into());
+            info!(
+                feature = %context.feature,
+                account_id = context.account_id.as_deref().unwrap_or_default(),
+                model,
+                input_tokens = input,
+                output_tokens = output,
+                cost_usd = cost_usd.unwrap_or_default(),
+                priced = cost_usd.is_some(),
+                "llm call usage"
+            );
+        }
+
         let new_call = NewLlmCall {
             org_id,
             user_id,
@@ -157,8 +303,12 @@ impl GenaiLLMClient {
             trace_id: None,
         };
 
-        if let Err(log_err) = self.repo.create(new_call).await {
-            warn!(error = ?log_err, "failed to record llm call");
+        match self.repo.create(new_call).await {
+            Ok(call) => Some(call.id),
+            Err(log_err) => {
+                warn!(error = ?log_err, "failed to record llm call");
+                None
+            }
         }
     }
 }
@@ -170,8 +320,25 @@ impl LLMClient for GenaiLLMClient {
         request: CompletionRequest,
         context: LlmCallContext,
     ) -> Result<CompletionResponse, LLMError> {
+        let model = self.route_model(&request, &context.feature).to_string();
+        let endpoint = self
+            .endpoint
+            .as_ref()
+            .filter(|endpoint| endpoint.applies_to(&model));
+        let request = match endpoint {
+            Some(endpoint) if !endpoint.supports_tools && !request.tools.is_empty() => {
+                tools_as_json_mode(request)
+            }
+            _ => request,
+        };
+
         let chat_request = self.build_chat_request(&request);
-        let options = self.build_chat_options(&request);
+        let mut options = self.build_chat_options(&request);
+        if let Some(endpoint) = endpoint
+            && !endpoint.headers.is_empty()
+        {
+            options = options.with_extra_headers(endpoint.headers.clone());
+        }
 
         let request_json = serde_json::to_value(&request)
             .unwrap_or_else(|err| serde_json::json!({"error": err.to_string()}));
@@ -179,7 +346,7 @@ impl LLMClient for GenaiLLMClient {
         let start = Instant::now();
         let result = self
             .chat
-            .exec_chat(&self.model, chat_request, Some(&options))
+            .exec_chat(&model, chat_request, Some(&options))
             .await;
         let latency_ms = start.elapsed().as_millis() as u64;
 
@@ -190,28 +357,20 @@ impl LLMClient for GenaiLLMClient {
                 let (input_tokens, output_tokens) = usage_tokens(&response.usage);
                 let response_json = serde_json::to_value(&response).ok();
 
-                // Extract tool calls from the response
-                let tool_calls = response
-                    .tool_calls()
-                    .iter()
-                    .map(|tc| types::ToolCallResult {
-                        call_id: tc.call_id.clone(),
-                        fn_name: tc.fn_name.clone(),
-                        fn_arguments: tc.fn_arguments.clone(),
-                    })
-                    .collect();
-
-                self.log_call(
-                    &context,
-                    &provider_model,
-                    request_json,
-                    response_json.clone(),
-                    Some(input_tokens),
-                    Some(output_tokens),
-                    Some(latency_ms),
-                    None,
-                )
-                .await;
+                let tool_calls = tool_call_results(&response);
+
+                let llm_call_id = self
+                    .log_call(
+                        &context,
+                        &provider_model,
+                        request_json,
+                        response_json.clone(),
+                        Some(input_tokens),
+                        Some(output_tokens),
+                        Some(latency_ms),
+                        None,
+                    )
+                    .await;
 
                 Ok(CompletionResponse {
                     content,
@@ -219,6 +378,7 @@ impl LLMClient for GenaiLLMClient {
                     input_tokens,
                     output_tokens,
                     latency_ms,
+                    llm_call_id,
                     tool_calls,
                 })
             }
@@ -226,7 +386,7 @@ impl LLMClient for GenaiLLMClient {
                 let mapped = map_genai_error(err);
                 self.log_call(
                     &context,
-                    &self.model,
+                    &model,
                     request_json,
                     None,
                     None,
@@ -241,6 +401,35 @@ impl LLMClient for GenaiLLMClient {
     }
 }
 
+/// Rewrite a tool request for a model without tool calling.
+///
+/// The tools' schemas move into a final user message and the model answers with a JSON
+/// object in JSON mode, which callers parse from the response content.
+fn tools_as_json_mode(mut request: CompletionRequest) -> CompletionRequest {
+    let tools = std::mem::take(&mut request.tools);
+    let instructions = tools
+        .iter()
+        .map(|tool| {
+            let schema = tool
+                .schema
+                .as_ref()
+                .and_then(|schema| serde_json::to_string_pretty(schema).ok())
+                .unwrap_or_else(|| "{}".to_string());
+            format!(
+                "Instead of calling the `{}` tool, reply with only a JSON object of its arguments, matching this JSON schema:\n{schema}",
+                tool.name
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    request.messages.push(ChatMessage {
+        role: ChatRole::User,
+        content: format!("Tool calling is not available.\n{instructions}"),
+    });
+    request.json_mode = true;
+    request
+}
+
 fn to_genai_message(message: &ChatMessage) -> GenaiChatMessage {
     match message.role {
         ChatRole::System => GenaiChatMessage::system(text_content(&message.content)),
@@ -254,10 +443,15 @@ fn text_content(content: &str) -> MessageContent {
 }
 
 fn namespaced_model(cfg: &ModelConfig) -> String {
-    if cfg.provider.is_empty() {
-        cfg.model.clone()
+    namespace_model(&cfg.provider, &cfg.model)
+}
+
+/// Model name in genai's "provider::model" form; the provider is lowercased.
+pub fn namespace_model(provider: &str, model: &str) -> String {
+    if provider.is_empty() {
+        model.to_string()
     } else {
-        format!("{}::{}", cfg.provider.to_lowercase(), cfg.model)
+        format!("{}::{}", provider.to_lowercase(), model)
     }
 }
 
@@ -267,6 +461,35 @@ fn usage_tokens(usage: &genai::chat::Usage) -> (u32, u32) {
     (input, output)
 }
 
+fn tool_call_results(response: &ChatResponse) -> Vec<types::ToolCallResult> {
+    response
+        .tool_calls()
+        .iter()
+        .map(|tc| types::ToolCallResult {
+            call_id: tc.call_id.clone(),
+            fn_name: tc.fn_name.clone(),
+            fn_arguments: tc.fn_arguments.clone(),
+        })
+        .collect()
+}
+
+/// Rebuild the response of a logged call from its `response_json`.
+///
+/// Returns `None` for failed calls and rows whose response cannot be read back.
+pub fn recorded_completion(call: &LlmCall) -> Option<CompletionResponse> {
+    let response: ChatResponse = serde_json::from_value(call.response_json.clone()?).ok()?;
+    let (input_tokens, output_tokens) = usage_tokens(&response.usage);
+    Some(CompletionResponse {
+        content: response.first_text().unwrap_or("").to_string(),
+        model: call.model.clone(),
+        input_tokens,
+        output_tokens,
+        latency_ms: call.latency_ms.unwrap_or_default(),
+        llm_call_id: Some(call.id.clone()),
+        tool_calls: tool_call_results(&response),
+    })
+}
+
 fn map_genai_error(err: GenaiError) -> LLMError {
     match err {
         GenaiError::RequiresApiKey { .. }
@@ -386,6 +609,10 @@ mod tests {
             model: "gpt-4o-mini".into(),
             temperature: 0.2,
             max_output_tokens: 256,
+            base_url: None,
+            api_key: None,
+            headers: HashMap::new(),
+            supports_tools: true,
         }
     }
 
@@ -421,7 +648,10 @@ mod tests {
         let client = GenaiLLMClient {
             chat: Arc::new(GenaiClient::default()),
             model: "openai::gpt-4o-mini".into(),
+            endpoint: None,
+            feature_models: HashMap::new(),
             repo: LlmCallRepository::new(db),
+            pricing: PricingConfig::default(),
         };
 
         let request = CompletionRequest {
@@ -438,6 +668,7 @@ mod tests {
             temperature: 0.1,
             max_tokens: 32,
             json_mode: false,
+            model: None,
             tools: vec![],
         };
 
@@ -463,7 +694,10 @@ mod tests {
         let client = GenaiLLMClient {
             chat: Arc::new(GenaiClient::default()),
             model: "openai::gpt-4o-mini".into(),
+            endpoint: None,
+            feature_models: HashMap::new(),
             repo: LlmCallRepository::new(db),
+            pricing: PricingConfig::default(),
         };
 
         let request = CompletionRequest {
@@ -471,6 +705,7 @@ mod tests {
             temperature: 0.42,
             max_tokens: 128,
             json_mode: true,
+            model: None,
             tools: vec![],
         };
 
@@ -693,6 +928,7 @@ mod tests {
             temperature: 0.5,
             max_tokens: 64,
             json_mode: true,
+            model: None,
             tools: vec![],
         };
         let context = LlmCallContext {
@@ -724,6 +960,7 @@ mod tests {
         assert_eq!(call.context.org_id, Some(123));
         assert_eq!(call.context.user_id, Some(456));
         assert_eq!(call.model, expected_model, "should log provider model iden");
+        assert_eq!(completion.llm_call_id.as_deref(), Some(call.id.as_str()));
         assert_eq!(call.request_json["messages"][0]["content"], "hello");
 
         let recorded = stub.calls.lock().expect("calls");
@@ -741,6 +978,84 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn complete_routes_model_by_request_then_feature() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("db.sqlite");
+        let db = Database::new(&db_path).await.expect("db");
+        run_migrations(&db).await.expect("migrations");
+
+        let stub = Arc::new(StubChatExecutor {
+            responses: Mutex::new(vec![
+                Err(GenaiError::Internal("third".into())),
+                Err(GenaiError::Internal("second".into())),
+                Err(GenaiError::Internal("first".into())),
+            ]),
+            calls: Mutex::new(Vec::new()),
+        });
+        let mut routing = RoutingConfig::default();
+        routing.features.insert(
+            "classify".into(),
+            crate::config::ModelRef {
+                provider: "Anthropic".into(),
+                model: "claude-haiku".into(),
+            },
+        );
+        let client = GenaiLLMClient::with_executor(db.clone(), test_model_config(), stub.clone())
+            .with_routing(&routing);
+
+        let request = CompletionRequest {
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: "hi".into(),
+            }],
+            temperature: 0.0,
+            max_tokens: 16,
+            json_mode: false,
+            model: None,
+            tools: vec![],
+        };
+        let escalated = CompletionRequest {
+            model: Some("openai::gpt-4o".into()),
+            ..request.clone()
+        };
+
+        let _ = client
+            .complete(request.clone(), LlmCallContext::new("classify"))
+            .await;
+        let _ = client
+            .complete(escalated, LlmCallContext::new("classify"))
+            .await;
+        let _ = client
+            .complete(request, LlmCallContext::new("rules_assistant"))
+            .await;
+
+        let models: Vec<String> = stub
+            .calls
+            .lock()
+            .expect("calls")
+            .iter()
+            .map(|call| call.0.clone())
+            .collect();
+        assert_eq!(
+            models,
+            vec![
+                "anthropic::claude-haiku",
+                "openai::gpt-4o",
+                "openai::gpt-4o-mini"
+            ]
+        );
+
+        let calls = client
+            .repo
+            .list(DEFAULT_ORG_ID, DEFAULT_USER_ID, Some("classify"), Some(10))
+            .await
+            .expect("list");
+        let mut logged: Vec<&str> = calls.iter().map(|call| call.model.as_str()).collect();
+        logged.sort();
+        assert_eq!(logged, vec!["anthropic::claude-haiku", "openai::gpt-4o"]);
+    }
+
     #[tokio::test]
     async fn complete_logs_rate_limit_with_retry_after() {
         let dir = TempDir::new().unwrap();
@@ -770,6 +1085,7 @@ mod tests {
             temperature: 0.0,
             max_tokens: 16,
             json_mode: false,
+            model: None,
             tools: vec![],
         };
         let context = LlmCallContext::new("classification");
@@ -805,4 +1121,80 @@ mod tests {
         let recorded = stub.calls.lock().expect("calls");
         assert_eq!(recorded.len(), 1, "stub should capture call");
     }
+
+    #[tokio::test]
+    async fn custom_endpoint_without_tool_support_uses_json_mode() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer local-key"))
+            .and(header("x-tenant", "ashford"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "chatcmpl-local",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "llama3",
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": "{\"label\": \"news\"}"},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 12, "completion_tokens": 4, "total_tokens": 16}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("db");
+        run_migrations(&db).await.expect("migrations");
+
+        let mut config = test_model_config();
+        config.model = "llama3".into();
+        config.base_url = Some(format!("{}/v1", server.uri()));
+        config.api_key = Some("local-key".into());
+        config.headers.insert("x-tenant".into(), "ashford".into());
+        config.supports_tools = false;
+        let client = GenaiLLMClient::new(db, config);
+
+        let request = CompletionRequest {
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: "label this".into(),
+            }],
+            temperature: 0.0,
+            max_tokens: 64,
+            json_mode: false,
+            model: None,
+            tools: vec![Tool::new("record_label").with_schema(serde_json::json!({
+                "type": "object",
+                "properties": {"label": {"type": "string"}}
+            }))],
+        };
+
+        let response = client
+            .complete(request, LlmCallContext::new("classify"))
+            .await
+            .expect("completion");
+        assert_eq!(response.content, "{\"label\": \"news\"}");
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(response.input_tokens, 12);
+
+        let received = server.received_requests().await.expect("requests");
+        let body: serde_json::Value = received[0].body_json().expect("json body");
+        assert!(body.get("tools").is_none());
+        assert_eq!(body["response_format"]["type"], "json_object");
+        let last = body["messages"]
+            .as_array()
+            .and_then(|messages| messages.last())
+            .expect("messages");
+        let content = last["content"].as_str().expect("content");
+        assert!(content.contains("Tool calling is not available"));
+        assert!(content.contains("record_label"));
+    }
 }
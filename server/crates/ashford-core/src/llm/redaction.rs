@@ -0,0 +1,338 @@
+//! Masking of personal data in prompts.
+//!
+//! A [`Redactor`] replaces credit-card numbers, IBANs, SSNs, phone numbers, one-time codes and
+//! custom patterns with placeholders such as `[CREDIT_CARD_1]`. Placeholders are stable within
+//! one prompt: the same value always gets the same placeholder, so the model can still tell
+//! values apart. The [`Redactions`] collected while building a prompt restore the originals
+//! in generated text, e.g. the body of an auto-reply.
+
+use std::collections::{BTreeMap, HashMap};
+
+use regex::{Captures, Regex};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::config::RedactionConfig;
+
+#[derive(Debug, Error)]
+pub enum RedactionError {
+    #[error("invalid redaction pattern {name}: {source}")]
+    InvalidPattern { name: String, source: regex::Error },
+}
+
+/// A pattern to mask. When the regex has a group named `value`, only that group is masked.
+#[derive(Debug, Clone)]
+struct RedactionRule {
+    kind: String,
+    regex: Regex,
+    /// Extra check on the masked text, e.g. a checksum.
+    validate: Option<fn(&str) -> bool>,
+}
+
+/// Compiled redaction patterns from [`RedactionConfig`].
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    rules: Vec<RedactionRule>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig) -> Result<Self, RedactionError> {
+        let mut rules = Vec::new();
+        let mut builtin = |kind: &str, patterns: &[&str], validate: Option<fn(&str) -> bool>| {
+            for pattern in patterns {
+                rules.push(RedactionRule {
+                    kind: kind.to_string(),
+                    regex: Regex::new(pattern).expect("valid built-in redaction regex"),
+                    validate,
+                });
+            }
+        };
+        // IBANs first: their digits alone can look like a card number
+        if config.ibans {
+            builtin(
+                "iban",
+                &[r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,3})?\b"],
+                Some(is_iban_valid),
+            );
+        }
+        if config.credit_cards {
+            builtin(
+                "credit_card",
+                &[r"\b(?:\d[ -]?){12,18}\d\b"],
+                Some(is_luhn_valid),
+            );
+        }
+        if config.ssns {
+            builtin("ssn", &[r"\b\d{3}-\d{2}-\d{4}\b"], Some(is_ssn_valid));
+        }
+        if config.one_time_codes {
+            builtin(
+                "one_time_code",
+                &[
+                    r"(?i)\b(?:code|otp|passcode|pin|one-time password)\b[^\n\d]{0,20}\b(?P<value>\d{4,8})\b",
+                    r"(?i)\b(?P<value>\d{4,8})\b\s+is your\b[^\n\d]{0,30}\b(?:code|otp|passcode|pin)\b",
+                ],
+                None,
+            );
+        }
+        if config.phone_numbers {
+            builtin(
+                "phone",
+                &[
+                    r"\+\d{1,3}(?:[ .-]?\(?\d{1,4}\)?){2,5}\b",
+                    r"(?:\(\d{3}\) ?|\b\d{3}[ .-])\d{3}[ .-]\d{4}\b",
+                ],
+                Some(is_phone_length),
+            );
+        }
+        for custom in &config.custom {
+            let regex =
+                Regex::new(&custom.pattern).map_err(|source| RedactionError::InvalidPattern {
+                    name: custom.name.clone(),
+                    source,
+                })?;
+            rules.push(RedactionRule {
+                kind: custom.name.clone(),
+                regex,
+                validate: None,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// Mask every match in `text`, recording placeholders and counts in `redactions`.
+    pub fn redact(&self, text: &str, redactions: &mut Redactions) -> String {
+        let mut text = text.to_string();
+        for rule in &self.rules {
+            text = rule
+                .regex
+                .replace_all(&text, |caps: &Captures<'_>| {
+                    let whole = caps.get(0).expect("group 0 always matches");
+                    let value = caps.name("value").unwrap_or(whole);
+                    if rule
+                        .validate
+                        .is_some_and(|validate| !validate(value.as_str()))
+                    {
+                        return whole.as_str().to_string();
+                    }
+                    let placeholder = redactions.placeholder(&rule.kind, value.as_str());
+                    let start = value.start() - whole.start();
+                    let end = value.end() - whole.start();
+                    format!(
+                        "{}{}{}",
+                        &whole.as_str()[..start],
+                        placeholder,
+                        &whole.as_str()[end..]
+                    )
+                })
+                .into_owned();
+        }
+        text
+    }
+}
+
+/// Placeholders handed out while redacting one prompt.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Redactions {
+    /// Placeholder for each masked value.
+    by_value: HashMap<String, String>,
+    /// Original value for each placeholder.
+    by_placeholder: BTreeMap<String, String>,
+    /// Masked occurrences per kind.
+    counts: BTreeMap<String, usize>,
+    /// Placeholders handed out per label.
+    issued: HashMap<String, usize>,
+}
+
+impl Redactions {
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Masked occurrences per kind, e.g. `{"credit_card": 2}`.
+    pub fn counts(&self) -> &BTreeMap<String, usize> {
+        &self.counts
+    }
+
+    /// Put the original values back in place of any placeholders in `text`.
+    pub fn restore(&self, text: &str) -> String {
+        if self.by_placeholder.is_empty() || !text.contains('[') {
+            return text.to_string();
+        }
+        self.by_placeholder
+            .iter()
+            .fold(text.to_string(), |text, (placeholder, original)| {
+                text.replace(placeholder, original)
+            })
+    }
+
+    /// [`restore`](Self::restore) every string inside `value`.
+    pub fn restore_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.restore(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.restore_value(item)),
+            Value::Object(map) => map.values_mut().for_each(|item| self.restore_value(item)),
+            _ => {}
+        }
+    }
+
+    fn placeholder(&mut self, kind: &str, value: &str) -> String {
+        *self.counts.entry(kind.to_string()).or_default() += 1;
+        if let Some(existing) = self.by_value.get(value) {
+            return existing.clone();
+        }
+        let label: String = kind
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let index = self.issued.entry(label.clone()).or_default();
+        *index += 1;
+        let placeholder = format!("[{label}_{index}]");
+        self.by_value.insert(value.to_string(), placeholder.clone());
+        self.by_placeholder
+            .insert(placeholder.clone(), value.to_string());
+        placeholder
+    }
+}
+
+fn digits(value: &str) -> Vec<u32> {
+    value.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+fn is_luhn_valid(value: &str) -> bool {
+    let digits = digits(value);
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| {
+            if index % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn is_iban_valid(value: &str) -> bool {
+    let compact: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(15..=34).contains(&compact.len()) {
+        return false;
+    }
+    let (head, tail) = compact.split_at(4);
+    let remainder = tail.chars().chain(head.chars()).try_fold(0u32, |acc, c| {
+        let value = c.to_digit(36)?;
+        Some(if value > 9 {
+            (acc * 100 + value) % 97
+        } else {
+            (acc * 10 + value) % 97
+        })
+    });
+    remainder == Some(1)
+}
+
+fn is_ssn_valid(value: &str) -> bool {
+    let mut parts = value.split('-');
+    let (Some(area), Some(group), Some(serial)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    area != "000" && area != "666" && !area.starts_with('9') && group != "00" && serial != "0000"
+}
+
+fn is_phone_length(value: &str) -> bool {
+    (7..=15).contains(&digits(value).len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CustomRedaction;
+    use serde_json::json;
+
+    fn redactor() -> Redactor {
+        Redactor::new(&RedactionConfig {
+            enabled: true,
+            custom: vec![CustomRedaction {
+                name: "employee_id".into(),
+                pattern: r"EMP-\d{6}".into(),
+            }],
+            ..Default::default()
+        })
+        .expect("redactor")
+    }
+
+    #[test]
+    fn redact_masks_builtin_kinds_with_stable_placeholders() {
+        let mut redactions = Redactions::default();
+        let text = "Card 4111 1111 1111 1111 (again: 4111 1111 1111 1111), \
+                    IBAN DE89 3704 0044 0532 0130 00, SSN 123-45-6789, \
+                    call +1 415-555-0123. Your verification code is 482913. \
+                    Employee EMP-123456.";
+
+        let redacted = redactor().redact(text, &mut redactions);
+        assert_eq!(
+            redacted,
+            "Card [CREDIT_CARD_1] (again: [CREDIT_CARD_1]), \
+             IBAN [IBAN_1], SSN [SSN_1], \
+             call [PHONE_1]. Your verification code is [ONE_TIME_CODE_1]. \
+             Employee [EMPLOYEE_ID_1]."
+        );
+        assert_eq!(redactions.counts()["credit_card"], 2);
+        assert_eq!(redactions.counts()["iban"], 1);
+        assert_eq!(redactions.counts()["one_time_code"], 1);
+        assert_eq!(redactions.counts()["employee_id"], 1);
+        assert_eq!(redactor().redact(&redacted, &mut redactions), redacted);
+    }
+
+    #[test]
+    fn redact_leaves_invalid_numbers_and_dates_alone() {
+        let mut redactions = Redactions::default();
+        let text = "Order 1234 5678 9012 3456 shipped on 2024-01-15, ref 000-12-3456, total 1,250.";
+        assert_eq!(redactor().redact(text, &mut redactions), text);
+        assert!(redactions.is_empty());
+    }
+
+    #[test]
+    fn restore_puts_original_values_back_in_generated_text() {
+        let mut redactions = Redactions::default();
+        redactor().redact("Call me at (415) 555-0123 or 415.555.0199", &mut redactions);
+
+        let mut parameters = json!({
+            "body": "We will call [PHONE_1] tomorrow, not [PHONE_2].",
+            "to": ["a@example.com"],
+            "nested": {"note": "[PHONE_1]"},
+        });
+        redactions.restore_value(&mut parameters);
+        assert_eq!(
+            parameters["body"],
+            "We will call (415) 555-0123 tomorrow, not 415.555.0199."
+        );
+        assert_eq!(parameters["nested"]["note"], "(415) 555-0123");
+        assert_eq!(parameters["to"][0], "a@example.com");
+    }
+
+    #[test]
+    fn new_rejects_invalid_custom_pattern() {
+        let err = Redactor::new(&RedactionConfig {
+            custom: vec![CustomRedaction {
+                name: "broken".into(),
+                pattern: "(unclosed".into(),
+            }],
+            ..Default::default()
+        })
+        .expect_err("invalid pattern");
+        assert!(matches!(err, RedactionError::InvalidPattern { name, .. } if name == "broken"));
+    }
+}
@@ -2,6 +2,7 @@ use chrono::{DateTime, SecondsFormat, Utc};
 use libsql::{Row, params};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -35,6 +36,9 @@ pub struct LlmCallContext {
     pub thread_id: Option<String>,
     pub rule_name: Option<String>,
     pub rule_id: Option<String>,
+    /// Values masked in the prompt per kind, e.g. `{"credit_card": 1}`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub redactions: BTreeMap<String, usize>,
 }
 
 impl LlmCallContext {
@@ -198,6 +202,35 @@ impl LlmCallRepository {
         }
         Ok(calls)
     }
+    /// The newest successful call for `feature` about `message_id`, if any.
+    pub async fn latest_for_message(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        feature: &str,
+        message_id: &str,
+    ) -> Result<Option<LlmCall>, LlmCallError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {LLM_CALL_COLUMNS}
+                     FROM llm_calls
+                     WHERE org_id = ?1 AND user_id = ?2 AND feature = ?3
+                       AND json_extract(context_json, '$.message_id') = ?4
+                       AND error IS NULL
+                     ORDER BY created_at DESC
+                     LIMIT 1"
+                ),
+                params![org_id, user_id, feature, message_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(row_to_llm_call(row)?)),
+            None => Ok(None),
+        }
+    }
 }
 
 fn row_to_llm_call(row: Row) -> Result<LlmCall, LlmCallError> {
@@ -259,6 +292,7 @@ mod tests {
             thread_id: None,
             rule_name: Some("rule-a".into()),
             rule_id: None,
+            redactions: BTreeMap::from([("phone".to_string(), 2)]),
         }
     }
 
@@ -301,6 +335,7 @@ mod tests {
         assert_eq!(list.len(), 1);
         assert_eq!(list[0].id, created.id);
         assert_eq!(list[0].context.account_id, Some("acc-1".into()));
+        assert_eq!(list[0].context.redactions["phone"], 2);
     }
 
     #[tokio::test]
@@ -351,6 +386,48 @@ mod tests {
         assert_eq!(rules[0].feature, "rules_assistant");
     }
 
+    #[tokio::test]
+    async fn latest_for_message_skips_failed_and_other_messages() {
+        let (repo, _dir) = setup_repo().await;
+        let call = |message_id: &str, error: Option<&str>| NewLlmCall {
+            org_id: DEFAULT_ORG_ID,
+            user_id: DEFAULT_USER_ID,
+            context: LlmCallContext {
+                message_id: Some(message_id.into()),
+                ..sample_context()
+            },
+            model: "openai::gpt-4o-mini".into(),
+            request_json: serde_json::json!({}),
+            response_json: None,
+            input_tokens: None,
+            output_tokens: None,
+            latency_ms: None,
+            error: error.map(str::to_string),
+            trace_id: None,
+        };
+
+        let ok = repo.create(call("msg-1", None)).await.expect("create ok");
+        repo.create(call("msg-1", Some("timeout")))
+            .await
+            .expect("create failed");
+        repo.create(call("msg-2", None))
+            .await
+            .expect("create other");
+
+        let latest = repo
+            .latest_for_message(DEFAULT_ORG_ID, DEFAULT_USER_ID, "classification", "msg-1")
+            .await
+            .expect("latest")
+            .expect("call");
+        assert_eq!(latest.id, ok.id);
+
+        let missing = repo
+            .latest_for_message(DEFAULT_ORG_ID, DEFAULT_USER_ID, "classification", "msg-3")
+            .await
+            .expect("latest");
+        assert!(missing.is_none());
+    }
+
     #[tokio::test]
     async fn list_by_org_returns_all_users_within_org() {
         let (repo, _dir) = setup_repo().await;
@@ -0,0 +1,540 @@
+//! LLM spend aggregation and budget checks.
+//!
+//! Spend is derived from the token counts in `llm_calls` and the configured per-model
+//! pricing, so changing a price re-prices the whole window.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Datelike, SecondsFormat, TimeZone, Utc};
+use libsql::params;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+
+use crate::config::{BudgetConfig, BudgetLimit, PricingConfig};
+use crate::db::{Database, DbError};
+
+#[derive(Debug, Error)]
+pub enum SpendError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+}
+
+/// Budget window. Both windows start at midnight UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SpendWindow {
+    Daily,
+    Monthly,
+}
+
+impl SpendWindow {
+    /// Start of the window containing `now`.
+    pub fn start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            SpendWindow::Daily => now
+                .date_naive()
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight is valid")
+                .and_utc(),
+            SpendWindow::Monthly => Utc
+                .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+                .single()
+                .expect("first of month is valid"),
+        }
+    }
+}
+
+/// Call count, tokens and cost for a group of LLM calls.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SpendTotals {
+    #[ts(type = "number")]
+    pub calls: u64,
+    #[ts(type = "number")]
+    pub input_tokens: u64,
+    #[ts(type = "number")]
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl SpendTotals {
+    fn add(&mut self, row: &SpendRow) {
+        self.calls += row.calls;
+        self.input_tokens += row.input_tokens;
+        self.output_tokens += row.output_tokens;
+        self.cost_usd += row.cost_usd;
+    }
+}
+
+/// Spend within one window, broken down by feature and by account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SpendSummary {
+    pub window: SpendWindow,
+    pub since: DateTime<Utc>,
+    pub total: SpendTotals,
+    pub by_feature: BTreeMap<String, SpendTotals>,
+    /// Calls made outside an account (e.g. the rules assistant) only appear in the total.
+    pub by_account: BTreeMap<String, SpendTotals>,
+    /// Models with calls in the window but no configured price; their cost counts as zero.
+    pub unpriced_models: Vec<String>,
+}
+
+/// A budget limit that has been reached.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BudgetExceeded {
+    pub window: SpendWindow,
+    /// Feature the limit applies to; `None` for limits across all features.
+    pub feature: Option<String>,
+    /// Account the limit applies to; `None` for limits across all accounts.
+    pub account_id: Option<String>,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+}
+
+/// Daily and monthly spend plus every budget limit currently exceeded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SpendReport {
+    pub daily: SpendSummary,
+    pub monthly: SpendSummary,
+    pub exceeded: Vec<BudgetExceeded>,
+}
+
+/// Priced aggregate of calls sharing a feature, account and model.
+struct SpendRow {
+    feature: String,
+    account_id: Option<String>,
+    model: String,
+    calls: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cost_usd: f64,
+    priced: bool,
+}
+
+#[derive(Clone)]
+pub struct SpendTracker {
+    db: Database,
+    pricing: PricingConfig,
+    budget: BudgetConfig,
+}
+
+impl SpendTracker {
+    pub fn new(db: Database, pricing: PricingConfig, budget: BudgetConfig) -> Self {
+        Self {
+            db,
+            pricing,
+            budget,
+        }
+    }
+
+    /// Spend in the window containing `now`.
+    pub async fn summary(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        window: SpendWindow,
+        now: DateTime<Utc>,
+    ) -> Result<SpendSummary, SpendError> {
+        let since = window.start(now);
+        let rows = self.load_rows(org_id, user_id, since).await?;
+        Ok(summarize(window, since, &rows))
+    }
+
+    /// Daily and monthly spend with all exceeded limits.
+    pub async fn report(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        now: DateTime<Utc>,
+    ) -> Result<SpendReport, SpendError> {
+        let daily_since = SpendWindow::Daily.start(now);
+        let monthly_since = SpendWindow::Monthly.start(now);
+        let daily_rows = self.load_rows(org_id, user_id, daily_since).await?;
+        let monthly_rows = self.load_rows(org_id, user_id, monthly_since).await?;
+
+        let mut exceeded = Vec::new();
+        for limit in self.limits() {
+            exceeded.extend(exceeded_in_window(
+                &limit,
+                SpendWindow::Daily,
+                limit.daily_usd,
+                &daily_rows,
+            ));
+            exceeded.extend(exceeded_in_window(
+                &limit,
+                SpendWindow::Monthly,
+                limit.monthly_usd,
+                &monthly_rows,
+            ));
+        }
+
+        Ok(SpendReport {
+            daily: summarize(SpendWindow::Daily, daily_since, &daily_rows),
+            monthly: summarize(SpendWindow::Monthly, monthly_since, &monthly_rows),
+            exceeded,
+        })
+    }
+
+    /// First exceeded limit that applies to an LLM call for `feature` on `account_id`.
+    ///
+    /// Returns `None` without querying when no budget is configured.
+    pub async fn check_budget(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        feature: &str,
+        account_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<BudgetExceeded>, SpendError> {
+        if !self.budget.is_enabled() {
+            return Ok(None);
+        }
+
+        let limits: Vec<BudgetLimit> = self
+            .limits()
+            .into_iter()
+            .filter(|limit| {
+                limit.feature.as_deref().is_none_or(|f| f == feature)
+                    && limit.account_id.as_deref().is_none_or(|a| a == account_id)
+            })
+            .collect();
+
+        for window in [SpendWindow::Daily, SpendWindow::Monthly] {
+            let window_limits: Vec<(&BudgetLimit, f64)> = limits
+                .iter()
+                .filter_map(|limit| {
+                    let usd = match window {
+                        SpendWindow::Daily => limit.daily_usd,
+                        SpendWindow::Monthly => limit.monthly_usd,
+                    };
+                    usd.map(|usd| (limit, usd))
+                })
+                .collect();
+            if window_limits.is_empty() {
+                continue;
+            }
+
+            let rows = self.load_rows(org_id, user_id, window.start(now)).await?;
+            for (limit, usd) in window_limits {
+                if let Some(exceeded) = exceeded_in_window(limit, window, Some(usd), &rows) {
+                    return Ok(Some(exceeded));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Configured limits, with the global daily/monthly budget first.
+    fn limits(&self) -> Vec<BudgetLimit> {
+        let mut limits = Vec::with_capacity(self.budget.limits.len() + 1);
+        if self.budget.daily_usd.is_some() || self.budget.monthly_usd.is_some() {
+            limits.push(BudgetLimit {
+                feature: None,
+                account_id: None,
+                daily_usd: self.budget.daily_usd,
+                monthly_usd: self.budget.monthly_usd,
+            });
+        }
+        limits.extend(self.budget.limits.iter().cloned());
+        limits
+    }
+
+    async fn load_rows(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<SpendRow>, SpendError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "SELECT feature,
+                        json_extract(context_json, '$.account_id') AS account_id,
+                        model,
+                        COUNT(*),
+                        COALESCE(SUM(input_tokens), 0),
+                        COALESCE(SUM(output_tokens), 0)
+                 FROM llm_calls
+                 WHERE org_id = ?1 AND user_id = ?2 AND created_at >= ?3
+                 GROUP BY feature, account_id, model",
+                params![
+                    org_id,
+                    user_id,
+                    since.to_rfc3339_opts(SecondsFormat::Millis, true)
+                ],
+            )
+            .await?;
+
+        let mut spend_rows = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let model: String = row.get(2)?;
+            let calls: i64 = row.get(3)?;
+            let input_tokens: i64 = row.get(4)?;
+            let output_tokens: i64 = row.get(5)?;
+            let input_tokens = input_tokens.max(0) as u64;
+            let output_tokens = output_tokens.max(0) as u64;
+            let cost = self.pricing.cost_usd(&model, input_tokens, output_tokens);
+            spend_rows.push(SpendRow {
+                feature: row.get(0)?,
+                account_id: row.get(1)?,
+                calls: calls.max(0) as u64,
+                input_tokens,
+                output_tokens,
+                cost_usd: cost.unwrap_or(0.0),
+                priced: cost.is_some() || input_tokens + output_tokens == 0,
+                model,
+            });
+        }
+        Ok(spend_rows)
+    }
+}
+
+fn summarize(window: SpendWindow, since: DateTime<Utc>, rows: &[SpendRow]) -> SpendSummary {
+    let mut total = SpendTotals::default();
+    let mut by_feature: BTreeMap<String, SpendTotals> = BTreeMap::new();
+    let mut by_account: BTreeMap<String, SpendTotals> = BTreeMap::new();
+    let mut unpriced = BTreeSet::new();
+
+    for row in rows {
+        total.add(row);
+        by_feature.entry(row.feature.clone()).or_default().add(row);
+        if let Some(account_id) = &row.account_id {
+            by_account.entry(account_id.clone()).or_default().add(row);
+        }
+        if !row.priced {
+            unpriced.insert(row.model.clone());
+        }
+    }
+
+    SpendSummary {
+        window,
+        since,
+        total,
+        by_feature,
+        by_account,
+        unpriced_models: unpriced.into_iter().collect(),
+    }
+}
+
+fn exceeded_in_window(
+    limit: &BudgetLimit,
+    window: SpendWindow,
+    limit_usd: Option<f64>,
+    rows: &[SpendRow],
+) -> Option<BudgetExceeded> {
+    let limit_usd = limit_usd?;
+    let spent_usd: f64 = rows
+        .iter()
+        .filter(|row| {
+            limit.feature.as_deref().is_none_or(|f| f == row.feature)
+                && limit
+                    .account_id
+                    .as_deref()
+                    .is_none_or(|a| row.account_id.as_deref() == Some(a))
+        })
+        .map(|row| row.cost_usd)
+        .sum();
+
+    (spent_usd >= limit_usd).then(|| BudgetExceeded {
+        window,
+        feature: limit.feature.clone(),
+        account_id: limit.account_id.clone(),
+        limit_usd,
+        spent_usd,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelPricing;
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::llm::{LlmCallContext, LlmCallRepository, NewLlmCall};
+    use crate::migrations::run_migrations;
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("db.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    fn pricing() -> PricingConfig {
+        PricingConfig {
+            models: vec![ModelPricing {
+                model: "gpt-4o-mini".into(),
+                input_per_million: 1.0,
+                output_per_million: 4.0,
+            }],
+        }
+    }
+
+    async fn log_call(
+        db: &Database,
+        feature: &str,
+        account_id: Option<&str>,
+        model: &str,
+        input_tokens: u32,
+        output_tokens: u32,
+    ) {
+        let mut context = LlmCallContext::new(feature);
+        context.account_id = account_id.map(str::to_string);
+        LlmCallRepository::new(db.clone())
+            .create(NewLlmCall {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                context,
+                model: model.into(),
+                request_json: serde_json::json!({}),
+                response_json: None,
+                input_tokens: Some(input_tokens),
+                output_tokens: Some(output_tokens),
+                latency_ms: None,
+                error: None,
+                trace_id: None,
+            })
+            .await
+            .expect("log call");
+    }
+
+    #[test]
+    fn windows_start_at_utc_midnight() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 17, 15, 42, 7).unwrap();
+        assert_eq!(
+            SpendWindow::Daily.start(now),
+            Utc.with_ymd_and_hms(2026, 3, 17, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            SpendWindow::Monthly.start(now),
+            Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn report_breaks_down_spend_by_feature_and_account() {
+        let (db, _dir) = setup_db().await;
+        log_call(
+            &db,
+            "classify",
+            Some("acc-1"),
+            "openai::gpt-4o-mini",
+            500_000,
+            100_000,
+        )
+        .await;
+        log_call(
+            &db,
+            "classify",
+            Some("acc-2"),
+            "openai::gpt-4o-mini",
+            250_000,
+            0,
+        )
+        .await;
+        log_call(
+            &db,
+            "rules_assistant",
+            None,
+            "openai::gpt-4o-mini",
+            0,
+            250_000,
+        )
+        .await;
+        log_call(&db, "classify", Some("acc-1"), "local::llama", 1_000, 1_000).await;
+
+        let tracker = SpendTracker::new(db, pricing(), BudgetConfig::default());
+        let report = tracker
+            .report(DEFAULT_ORG_ID, DEFAULT_USER_ID, Utc::now())
+            .await
+            .expect("report");
+
+        let daily = &report.daily;
+        assert_eq!(daily.total.calls, 4);
+        assert!((daily.total.cost_usd - 2.15).abs() < 1e-9);
+        assert!((daily.by_feature["classify"].cost_usd - 1.15).abs() < 1e-9);
+        assert!((daily.by_feature["rules_assistant"].cost_usd - 1.0).abs() < 1e-9);
+        assert!((daily.by_account["acc-1"].cost_usd - 0.9).abs() < 1e-9);
+        assert_eq!(daily.by_account["acc-1"].calls, 2);
+        assert!(!daily.by_account.contains_key(""));
+        assert_eq!(daily.unpriced_models, vec!["local::llama".to_string()]);
+        assert_eq!(report.monthly.total, daily.total);
+        assert!(report.exceeded.is_empty());
+    }
+
+    #[tokio::test]
+    async fn check_budget_applies_matching_limits_only() {
+        let (db, _dir) = setup_db().await;
+        // $0.90 of classification spend on acc-1
+        log_call(
+            &db,
+            "classify",
+            Some("acc-1"),
+            "openai::gpt-4o-mini",
+            500_000,
+            100_000,
+        )
+        .await;
+
+        let budget = BudgetConfig {
+            monthly_usd: Some(10.0),
+            limits: vec![
+                BudgetLimit {
+                    account_id: Some("acc-1".into()),
+                    daily_usd: Some(0.5),
+                    ..Default::default()
+                },
+                BudgetLimit {
+                    feature: Some("rules_assistant".into()),
+                    daily_usd: Some(0.1),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let tracker = SpendTracker::new(db, pricing(), budget);
+
+        let exceeded = tracker
+            .check_budget(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "classify",
+                "acc-1",
+                Utc::now(),
+            )
+            .await
+            .expect("check")
+            .expect("acc-1 over its daily limit");
+        assert_eq!(exceeded.window, SpendWindow::Daily);
+        assert_eq!(exceeded.account_id.as_deref(), Some("acc-1"));
+        assert_eq!(exceeded.limit_usd, 0.5);
+        assert!((exceeded.spent_usd - 0.9).abs() < 1e-9);
+
+        let other_account = tracker
+            .check_budget(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "classify",
+                "acc-2",
+                Utc::now(),
+            )
+            .await
+            .expect("check");
+        assert!(other_account.is_none());
+
+        let report = tracker
+            .report(DEFAULT_ORG_ID, DEFAULT_USER_ID, Utc::now())
+            .await
+            .expect("report");
+        assert_eq!(report.exceeded.len(), 1);
+    }
+}
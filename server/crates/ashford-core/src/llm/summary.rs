@@ -0,0 +1,154 @@
+use schemars::JsonSchema;
+use schemars::schema_for;
+use serde::{Deserialize, Serialize};
+
+use super::decision::{DecisionParseError, DecisionValidationError, extract_json_from_response};
+use super::redaction::Redactions;
+use super::types::{CompletionResponse, Tool};
+
+/// The name of the tool the LLM calls with an updated thread summary.
+pub const SUMMARY_TOOL_NAME: &str = "record_thread_summary";
+
+/// Arguments of the thread summary tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ThreadSummaryOutput {
+    /// A few sentences covering what the thread is about and where it stands.
+    pub summary: String,
+    /// People taking part, as "Name <email>" or just the email address.
+    #[serde(default)]
+    pub participants: Vec<String>,
+    /// Questions asked in the thread that have not been answered yet.
+    #[serde(default)]
+    pub open_questions: Vec<String>,
+    /// Things someone promised to do, with who and by when when stated.
+    #[serde(default)]
+    pub commitments: Vec<String>,
+}
+
+impl ThreadSummaryOutput {
+    /// Parse the summary from the tool call or, for models without tool support, the content.
+    pub fn parse_from_completion(
+        response: &CompletionResponse,
+    ) -> Result<Self, DecisionParseError> {
+        let parsed: ThreadSummaryOutput = match response.tool_calls.first() {
+            Some(tool_call) if tool_call.fn_name != SUMMARY_TOOL_NAME => {
+                return Err(DecisionParseError::WrongToolName {
+                    expected: SUMMARY_TOOL_NAME.to_string(),
+                    actual: tool_call.fn_name.clone(),
+                });
+            }
+            Some(tool_call) => serde_json::from_value(tool_call.fn_arguments.clone())?,
+            None if !response.content.trim().is_empty() => {
+                serde_json::from_str(extract_json_from_response(&response.content)?)?
+            }
+            None => return Err(DecisionParseError::NoToolCall),
+        };
+
+        if parsed.summary.trim().is_empty() {
+            return Err(DecisionValidationError::EmptyField("summary").into());
+        }
+        Ok(parsed)
+    }
+
+    /// Put back the personal data masked in the prompt.
+    pub fn restore(&mut self, redactions: &Redactions) {
+        if redactions.is_empty() {
+            return;
+        }
+        self.summary = redactions.restore(&self.summary);
+        for item in self
+            .participants
+            .iter_mut()
+            .chain(self.open_questions.iter_mut())
+            .chain(self.commitments.iter_mut())
+        {
+            *item = redactions.restore(item);
+        }
+    }
+}
+
+/// Builds the thread summary tool with the [`ThreadSummaryOutput`] JSON schema.
+pub fn build_summary_tool() -> Tool {
+    let schema = schema_for!(ThreadSummaryOutput);
+    let schema_value = serde_json::to_value(schema).expect("schema should serialize");
+
+    Tool::new(SUMMARY_TOOL_NAME)
+        .with_description(
+            "Record the updated summary of this email thread. \
+             You MUST call this tool to provide the summary.",
+        )
+        .with_schema(schema_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::types::ToolCallResult;
+    use serde_json::json;
+
+    fn response(tool_calls: Vec<ToolCallResult>, content: &str) -> CompletionResponse {
+        CompletionResponse {
+            content: content.to_string(),
+            model: "test-model".into(),
+            input_tokens: 0,
+            output_tokens: 0,
+            latency_ms: 0,
+            llm_call_id: None,
+            tool_calls,
+        }
+    }
+
+    #[test]
+    fn parses_summary_from_tool_call_and_content() {
+        let args = json!({
+            "summary": "Alice and Bob are planning the offsite.",
+            "participants": ["Alice <alice@example.com>", "bob@example.com"],
+            "open_questions": ["Which venue?"],
+        });
+        let from_tool = ThreadSummaryOutput::parse_from_completion(&response(
+            vec![ToolCallResult {
+                call_id: "call-1".into(),
+                fn_name: SUMMARY_TOOL_NAME.into(),
+                fn_arguments: args.clone(),
+            }],
+            "",
+        ))
+        .expect("tool call parses");
+        assert_eq!(from_tool.participants.len(), 2);
+        assert!(from_tool.commitments.is_empty());
+
+        let from_content = ThreadSummaryOutput::parse_from_completion(&response(
+            Vec::new(),
+            &format!("```json\n{args}\n```"),
+        ))
+        .expect("content parses");
+        assert_eq!(from_content, from_tool);
+    }
+
+    #[test]
+    fn rejects_wrong_tool_and_empty_summary() {
+        let wrong_tool = ThreadSummaryOutput::parse_from_completion(&response(
+            vec![ToolCallResult {
+                call_id: "call-1".into(),
+                fn_name: "record_decision".into(),
+                fn_arguments: json!({}),
+            }],
+            "",
+        ));
+        assert!(matches!(
+            wrong_tool,
+            Err(DecisionParseError::WrongToolName { .. })
+        ));
+
+        let empty = ThreadSummaryOutput::parse_from_completion(&response(
+            Vec::new(),
+            r#"{"summary": "  "}"#,
+        ));
+        assert_eq!(
+            empty,
+            Err(DecisionParseError::Validation(
+                DecisionValidationError::EmptyField("summary")
+            ))
+        );
+    }
+}
@@ -24,6 +24,9 @@ pub struct CompletionRequest {
     pub temperature: f32,
     pub max_tokens: u32,
     pub json_mode: bool,
+    /// Namespaced model ("provider::model") to use instead of the client's routed model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
     /// Optional tools for the LLM to call. When provided, the LLM may return
     /// tool calls instead of or in addition to text content.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -37,6 +40,7 @@ impl PartialEq for CompletionRequest {
             && (self.temperature - other.temperature).abs() < f32::EPSILON
             && self.max_tokens == other.max_tokens
             && self.json_mode == other.json_mode
+            && self.model == other.model
             && self.tools.len() == other.tools.len()
     }
 }
@@ -48,6 +52,9 @@ pub struct CompletionResponse {
     pub input_tokens: u32,
     pub output_tokens: u32,
     pub latency_ms: u64,
+    /// ID of the `llm_calls` row recording this call, when it was logged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_call_id: Option<String>,
     /// Tool calls returned by the LLM, if any.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tool_calls: Vec<ToolCallResult>,
@@ -113,6 +120,7 @@ mod tests {
             temperature: 0.7,
             max_tokens: 256,
             json_mode: true,
+            model: None,
             tools: vec![],
         };
 
@@ -140,6 +148,7 @@ mod tests {
             input_tokens: 42,
             output_tokens: 7,
             latency_ms: 1234,
+            llm_call_id: None,
             tool_calls: vec![],
         };
 
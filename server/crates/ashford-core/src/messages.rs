@@ -250,6 +250,53 @@ impl MessageRepository {
             None => Err(MessageError::NotFound(message_id.to_string())),
         }
     }
+
+    /// Messages of a thread, oldest first.
+    pub async fn list_by_thread(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        thread_id: &str,
+    ) -> Result<Vec<Message>, MessageError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {MESSAGE_COLUMNS} FROM messages
+                     WHERE org_id = ?1 AND user_id = ?2 AND thread_id = ?3
+                     ORDER BY COALESCE(internal_date, received_at, created_at), created_at, id"
+                ),
+                params![org_id, user_id, thread_id],
+            )
+            .await?;
+
+        let mut messages = Vec::new();
+        while let Some(row) = rows.next().await? {
+            messages.push(row_to_message(row)?);
+        }
+        Ok(messages)
+    }
+
+    /// Number of messages stored for a thread.
+    pub async fn count_by_thread(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        thread_id: &str,
+    ) -> Result<i64, MessageError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) FROM messages WHERE org_id = ?1 AND user_id = ?2 AND thread_id = ?3",
+                params![org_id, user_id, thread_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(row.get(0)?),
+            None => Ok(0),
+        }
+    }
 }
 
 fn row_to_message(row: Row) -> Result<Message, MessageError> {
@@ -579,4 +626,40 @@ mod tests {
             .expect_err("wrong org should not fetch");
         assert!(matches!(wrong_org, MessageError::NotFound(_)));
     }
+
+    #[tokio::test]
+    async fn list_by_thread_returns_messages_oldest_first() {
+        let (repo, db, _dir) = setup_repo().await;
+        let account_id = seed_account(&db).await;
+        let thread_id = seed_thread(&db, &account_id, "thread1").await;
+        let other_thread_id = seed_thread(&db, &account_id, "thread2").await;
+
+        let now = Utc::now();
+        for (provider_id, thread, minutes_ago) in [
+            ("late", &thread_id, 1),
+            ("early", &thread_id, 10),
+            ("elsewhere", &other_thread_id, 5),
+        ] {
+            let mut msg = sample_new_message(&account_id, thread);
+            msg.provider_message_id = provider_id.into();
+            msg.internal_date = Some(now - chrono::Duration::minutes(minutes_ago));
+            repo.upsert(msg).await.expect("insert");
+        }
+
+        let messages = repo
+            .list_by_thread(DEFAULT_ORG_ID, DEFAULT_USER_ID, &thread_id)
+            .await
+            .expect("list");
+        let ids: Vec<_> = messages
+            .iter()
+            .map(|m| m.provider_message_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["early", "late"]);
+        assert_eq!(
+            repo.count_by_thread(DEFAULT_ORG_ID, DEFAULT_USER_ID, &thread_id)
+                .await
+                .expect("count"),
+            2
+        );
+    }
 }
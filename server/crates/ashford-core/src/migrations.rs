@@ -39,6 +39,62 @@ static MIGRATIONS: &[Migration] = &[
         version: "007_add_unique_undo_links",
         sql: include_str!("../../../migrations/007_add_unique_undo_links.sql"),
     },
+    Migration {
+        version: "008_add_sender_lists",
+        sql: include_str!("../../../migrations/008_add_sender_lists.sql"),
+    },
+    Migration {
+        version: "009_add_rule_active_windows",
+        sql: include_str!("../../../migrations/009_add_rule_active_windows.sql"),
+    },
+    Migration {
+        version: "010_add_muted_threads",
+        sql: include_str!("../../../migrations/010_add_muted_threads.sql"),
+    },
+    Migration {
+        version: "011_add_contacts",
+        sql: include_str!("../../../migrations/011_add_contacts.sql"),
+    },
+    Migration {
+        version: "012_add_classification_feedback",
+        sql: include_str!("../../../migrations/012_add_classification_feedback.sql"),
+    },
+    Migration {
+        version: "013_add_cached_decision_source",
+        sql: include_str!("../../../migrations/013_add_cached_decision_source.sql"),
+    },
+    Migration {
+        version: "014_add_message_embeddings",
+        sql: include_str!("../../../migrations/014_add_message_embeddings.sql"),
+    },
+    Migration {
+        version: "015_add_thread_summaries",
+        sql: include_str!("../../../migrations/015_add_thread_summaries.sql"),
+    },
+    Migration {
+        version: "016_add_policy_clauses",
+        sql: include_str!("../../../migrations/016_add_policy_clauses.sql"),
+    },
+    Migration {
+        version: "017_add_circuit_breaker_trips",
+        sql: include_str!("../../../migrations/017_add_circuit_breaker_trips.sql"),
+    },
+    Migration {
+        version: "018_add_pending_execution_status",
+        sql: include_str!("../../../migrations/018_add_pending_execution_status.sql"),
+    },
+    Migration {
+        version: "019_add_bulk_undos",
+        sql: include_str!("../../../migrations/019_add_bulk_undos.sql"),
+    },
+    Migration {
+        version: "020_add_reply_templates",
+        sql: include_str!("../../../migrations/020_add_reply_templates.sql"),
+    },
+    Migration {
+        version: "021_add_budget_decision_source",
+        sql: include_str!("../../../migrations/021_add_budget_decision_source.sql"),
+    },
 ];
 
 #[derive(Error, Debug)]
@@ -161,7 +217,7 @@ mod tests {
             .expect("row value")
             .get(0)
             .expect("count");
-        assert_eq!(count, 7, "migrations should only record once each");
+        assert_eq!(count, 21, "migrations should only record once each");
     }
 
     #[tokio::test]
@@ -409,4 +465,79 @@ mod tests {
             "nullable user_id tables should leave existing rows NULL"
         );
     }
+
+    #[tokio::test]
+    async fn cached_source_migration_keeps_decisions_referenced_by_actions() {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("db.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        let conn = db.connection().await.expect("open connection");
+
+        apply_migrations(&conn, &MIGRATIONS[..12])
+            .await
+            .expect("earlier migrations");
+
+        let now = "2024-01-01T00:00:00Z";
+        conn.execute(
+            "INSERT INTO accounts (id, provider, email, display_name, config_json, state_json, created_at, updated_at)
+             VALUES ('acc1', 'gmail', 'one@example.com', 'One', '{}', '{}', ?1, ?1)",
+            params![now],
+        )
+        .await
+        .expect("insert account");
+        conn.execute(
+            "INSERT INTO threads (id, account_id, provider_thread_id, subject, snippet, last_message_at, metadata_json, raw_json, created_at, updated_at)
+             VALUES ('thr1', 'acc1', 'pt1', 'Subject', 'Snippet', ?1, '{}', '{}', ?1, ?1)",
+            params![now],
+        )
+        .await
+        .expect("insert thread");
+        conn.execute(
+            "INSERT INTO messages (id, account_id, thread_id, provider_message_id, from_email, from_name, to_json, cc_json, bcc_json, subject, snippet, received_at, internal_date, labels_json, headers_json, body_plain, body_html, raw_json, created_at, updated_at)
+             VALUES ('msg1', 'acc1', 'thr1', 'pm1', 'a@example.com', 'A', '[]', '[]', '[]', 'Subject', 'Snippet', ?1, ?1, '[]', '[]', NULL, NULL, '{}', ?1, ?1)",
+            params![now],
+        )
+        .await
+        .expect("insert message");
+        conn.execute(
+            "INSERT INTO decisions (id, account_id, message_id, source, decision_json, telemetry_json, created_at, updated_at, org_id, user_id)
+             VALUES ('dec1', 'acc1', 'msg1', 'llm', '{}', '{}', ?1, ?1, 1, 1)",
+            params![now],
+        )
+        .await
+        .expect("insert decision");
+        conn.execute(
+            "INSERT INTO actions (id, account_id, message_id, decision_id, action_type, parameters_json, status, created_at, updated_at)
+             VALUES ('act1', 'acc1', 'msg1', 'dec1', 'archive', '{}', 'queued', ?1, ?1)",
+            params![now],
+        )
+        .await
+        .expect("insert action");
+
+        apply_migrations(&conn, &MIGRATIONS[12..])
+            .await
+            .expect("cached source migration");
+
+        let mut rows = conn
+            .query("SELECT source FROM decisions WHERE id = 'dec1'", ())
+            .await
+            .expect("query decision");
+        let source: String = rows
+            .next()
+            .await
+            .expect("row result")
+            .expect("decision row")
+            .get(0)
+            .expect("source");
+        assert_eq!(source, "llm");
+        assert!(index_exists(&conn, "decisions", "decisions_org_user_idx").await);
+
+        conn.execute(
+            "INSERT INTO decisions (id, account_id, message_id, source, decision_json, telemetry_json, created_at, updated_at)
+             VALUES ('dec2', 'acc1', 'msg1', 'cached', '{}', '{}', ?1, ?1)",
+            params![now],
+        )
+        .await
+        .expect("cached source is allowed");
+    }
 }
@@ -370,6 +370,27 @@ impl JobQueue {
         Ok(())
     }
 
+    /// How many steps named `name` the job has finished, across all of its attempts.
+    pub async fn count_finished_steps(
+        &self,
+        job_id: &str,
+        name: &str,
+    ) -> Result<usize, QueueError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) FROM job_steps
+                 WHERE job_id = ?1 AND name = ?2 AND finished_at IS NOT NULL",
+                params![job_id, name],
+            )
+            .await?;
+        let count: i64 = match rows.next().await? {
+            Some(row) => row.get(0)?,
+            None => 0,
+        };
+        Ok(count as usize)
+    }
+
     pub async fn fetch_job(&self, job_id: &str) -> Result<Job, QueueError> {
         let conn = self.db.connection().await?;
         let mut rows = conn
@@ -384,6 +405,22 @@ impl JobQueue {
             None => Err(QueueError::JobNotFound(job_id.to_string())),
         }
     }
+
+    /// The job enqueued with `key`, in whatever state it is.
+    pub async fn find_by_idempotency_key(&self, key: &str) -> Result<Option<Job>, QueueError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!("SELECT {JOB_COLUMNS} FROM jobs WHERE idempotency_key = ?1"),
+                params![key],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_job(row).map(Some),
+            None => Ok(None),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -701,6 +738,31 @@ mod tests {
         assert_eq!(stored, Some(r#"{"ok":true}"#.to_string()));
     }
 
+    #[tokio::test]
+    async fn count_finished_steps_skips_unfinished_and_other_names() {
+        let (queue, _dir) = setup_queue().await;
+        let id = queue
+            .enqueue("digest.send", json!({}), None, 0)
+            .await
+            .expect("enqueue");
+
+        for name in ["post", "post", "render"] {
+            let step_id = queue.start_step(&id, name).await.expect("start step");
+            queue
+                .finish_step(&step_id, None)
+                .await
+                .expect("finish step");
+        }
+        queue.start_step(&id, "post").await.expect("start step");
+
+        assert_eq!(queue.count_finished_steps(&id, "post").await.unwrap(), 2);
+        assert_eq!(queue.count_finished_steps(&id, "render").await.unwrap(), 1);
+        assert_eq!(
+            queue.count_finished_steps("other", "post").await.unwrap(),
+            0
+        );
+    }
+
     #[tokio::test]
     async fn concurrent_claim_allows_single_runner() {
         let (queue, _dir) = setup_queue().await;
@@ -796,6 +858,31 @@ mod tests {
         assert!(matches!(job.state, JobState::Canceled));
     }
 
+    #[tokio::test]
+    async fn find_by_idempotency_key_returns_job_in_any_state() {
+        let (queue, _dir) = setup_queue().await;
+        let job_id = queue
+            .enqueue("keyed", json!({}), Some("keyed:1".into()), 0)
+            .await
+            .expect("enqueue");
+        queue.cancel(&job_id).await.expect("cancel");
+
+        let job = queue
+            .find_by_idempotency_key("keyed:1")
+            .await
+            .expect("lookup")
+            .expect("job");
+        assert_eq!(job.id, job_id);
+        assert!(matches!(job.state, JobState::Canceled));
+        assert!(
+            queue
+                .find_by_idempotency_key("keyed:2")
+                .await
+                .expect("lookup")
+                .is_none()
+        );
+    }
+
     #[tokio::test]
     async fn heartbeat_errors_when_job_not_running() {
         let (queue, _dir) = setup_queue().await;
@@ -0,0 +1,834 @@
+//! Stored reply templates for `auto_reply`.
+//!
+//! A template is referenced by name from an action's parameters
+//! (`{"template": "out_of_office", "slots": {...}}`). Templates use `{{variable}}`
+//! placeholders and `{{#if variable}}...{{else}}...{{/if}}` blocks. Variables are either
+//! built in, filled from the original message and account, or slots the template declares,
+//! which a rule or the LLM fills. A template renders to plain text and HTML; without an HTML
+//! body, the HTML part is derived from the plain text.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use libsql::{Row, params};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::accounts::Account;
+use crate::db::{Database, DbError};
+use crate::messages::Message;
+
+const REPLY_TEMPLATE_COLUMNS: &str = "id, name, description, subject, body, body_html, slots_json, created_at, updated_at, org_id, user_id";
+
+/// Variables every template can use, filled from the original message and the account.
+pub const BUILTIN_VARIABLES: &[&str] = &[
+    "sender_name",
+    "sender_first_name",
+    "sender_email",
+    "original_subject",
+    "original_date",
+    "account_email",
+    "account_name",
+];
+
+#[derive(Debug, Error)]
+pub enum ReplyTemplateError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("datetime parse error: {0}")]
+    DateTimeParse(#[from] chrono::ParseError),
+    #[error("reply template not found: {0}")]
+    NotFound(String),
+    #[error("reply template already exists: {0}")]
+    DuplicateName(String),
+    #[error("invalid reply template: {0}")]
+    Invalid(String),
+    #[error("reply template '{template}' needs a value for slot '{slot}'")]
+    MissingSlot { template: String, slot: String },
+}
+
+/// A value the template leaves for a rule or the LLM to fill.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TemplateSlot {
+    pub name: String,
+    /// Tells the LLM what to put in the slot.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Rendering fails when a required slot is missing or blank.
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ReplyTemplate {
+    pub id: String,
+    #[ts(type = "number")]
+    pub org_id: i64,
+    #[ts(type = "number")]
+    pub user_id: i64,
+    /// Unique, case-insensitive; what rules and the LLM refer to.
+    pub name: String,
+    /// Tells the LLM when the template fits.
+    pub description: Option<String>,
+    /// Subject template. Without one, replies use `Re: <original subject>`.
+    pub subject: Option<String>,
+    /// Plain text body template.
+    pub body: String,
+    /// HTML body template. Without one, the HTML part is the escaped plain text.
+    pub body_html: Option<String>,
+    pub slots: Vec<TemplateSlot>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewReplyTemplate {
+    pub org_id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub subject: Option<String>,
+    pub body: String,
+    pub body_html: Option<String>,
+    pub slots: Vec<TemplateSlot>,
+}
+
+/// A rendered template, ready for a `MimeMessage`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedReply {
+    pub subject: Option<String>,
+    pub body_plain: String,
+    pub body_html: String,
+}
+
+impl ReplyTemplate {
+    /// Render the template. `variables` holds built-in variables and slot values; missing
+    /// optional ones render empty and count as false in `{{#if}}` blocks.
+    pub fn render(
+        &self,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<RenderedReply, ReplyTemplateError> {
+        for slot in self.slots.iter().filter(|slot| slot.required) {
+            if !is_truthy(variables, &slot.name) {
+                return Err(ReplyTemplateError::MissingSlot {
+                    template: self.name.clone(),
+                    slot: slot.name.clone(),
+                });
+            }
+        }
+
+        let render = |source: &str, escape: bool| -> Result<String, ReplyTemplateError> {
+            let nodes = parse_template(source).map_err(ReplyTemplateError::Invalid)?;
+            let mut out = String::new();
+            render_nodes(&nodes, variables, escape, &mut out);
+            Ok(out)
+        };
+
+        let body_plain = render(&self.body, false)?;
+        let body_html = match self.body_html.as_deref() {
+            Some(body_html) => render(body_html, true)?,
+            None => html_from_plain(&body_plain),
+        };
+        let subject = self
+            .subject
+            .as_deref()
+            .map(|subject| render(subject, false))
+            .transpose()?
+            .map(|subject| subject.trim().to_string())
+            .filter(|subject| !subject.is_empty());
+
+        Ok(RenderedReply {
+            subject,
+            body_plain,
+            body_html,
+        })
+    }
+}
+
+/// Variables for replying to `message` from `account`, with `slots` (a JSON object from the
+/// action parameters) layered on top. Non-string slot values are rendered as JSON.
+pub fn template_variables(
+    message: &Message,
+    account: &Account,
+    slots: Option<&Value>,
+) -> BTreeMap<String, String> {
+    let mut variables = BTreeMap::new();
+    let sender_email = message.from_email.clone().unwrap_or_default();
+    let sender_name = message
+        .from_name
+        .clone()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| sender_email.clone());
+    let first_name = message
+        .from_name
+        .as_deref()
+        .and_then(|name| name.split_whitespace().next())
+        .unwrap_or_default()
+        .to_string();
+    let date = message
+        .received_at
+        .or(message.internal_date)
+        .map(|at| at.format("%B %-d, %Y").to_string())
+        .unwrap_or_default();
+
+    variables.insert("sender_name".to_string(), sender_name);
+    variables.insert("sender_first_name".to_string(), first_name);
+    variables.insert("sender_email".to_string(), sender_email);
+    variables.insert(
+        "original_subject".to_string(),
+        message.subject.clone().unwrap_or_default(),
+    );
+    variables.insert("original_date".to_string(), date);
+    variables.insert("account_email".to_string(), account.email.clone());
+    variables.insert(
+        "account_name".to_string(),
+        account
+            .display_name
+            .clone()
+            .unwrap_or_else(|| account.email.clone()),
+    );
+
+    if let Some(Value::Object(slots)) = slots {
+        for (name, value) in slots {
+            if BUILTIN_VARIABLES.contains(&name.as_str()) {
+                continue;
+            }
+            let value = match value {
+                Value::String(value) => value.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            variables.insert(name.clone(), value);
+        }
+    }
+    variables
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Variable(String),
+    If {
+        variable: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// An `{{#if}}` block being parsed.
+struct OpenBlock {
+    variable: String,
+    then: Vec<Node>,
+    otherwise: Option<Vec<Node>>,
+}
+
+impl OpenBlock {
+    fn nodes(&mut self) -> &mut Vec<Node> {
+        self.otherwise.as_mut().unwrap_or(&mut self.then)
+    }
+}
+
+fn parse_template(source: &str) -> Result<Vec<Node>, String> {
+    let mut root = Vec::new();
+    let mut open: Vec<OpenBlock> = Vec::new();
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        let nodes = match open.last_mut() {
+            Some(block) => block.nodes(),
+            None => &mut root,
+        };
+        let Some(start) = rest.find("{{") else {
+            nodes.push(Node::Text(rest.to_string()));
+            break;
+        };
+        if start > 0 {
+            nodes.push(Node::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "unclosed '{{'".to_string())?;
+        let tag = after[..end].trim();
+        rest = &after[end + 2..];
+
+        if let Some(variable) = tag.strip_prefix("#if") {
+            open.push(OpenBlock {
+                variable: variable_name(variable.trim())?,
+                then: Vec::new(),
+                otherwise: None,
+            });
+        } else if tag == "else" {
+            match open.last_mut() {
+                Some(block) if block.otherwise.is_none() => block.otherwise = Some(Vec::new()),
+                _ => return Err("'{{else}}' outside an '{{#if}}' block".to_string()),
+            }
+        } else if tag == "/if" {
+            let block = open
+                .pop()
+                .ok_or_else(|| "'{{/if}}' without an '{{#if}}'".to_string())?;
+            let node = Node::If {
+                variable: block.variable,
+                then: block.then,
+                otherwise: block.otherwise.unwrap_or_default(),
+            };
+            match open.last_mut() {
+                Some(parent) => parent.nodes().push(node),
+                None => root.push(node),
+            }
+        } else {
+            nodes.push(Node::Variable(variable_name(tag)?));
+        }
+    }
+
+    match open.last() {
+        Some(block) => Err(format!("'{{{{#if {}}}}}' is never closed", block.variable)),
+        None => Ok(root),
+    }
+}
+
+fn variable_name(name: &str) -> Result<String, String> {
+    if is_variable_name(name) {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "invalid variable '{name}'; use lowercase letters, digits and underscores"
+        ))
+    }
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn referenced_variables(nodes: &[Node], out: &mut BTreeSet<String>) {
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Variable(name) => {
+                out.insert(name.clone());
+            }
+            Node::If {
+                variable,
+                then,
+                otherwise,
+            } => {
+                out.insert(variable.clone());
+                referenced_variables(then, out);
+                referenced_variables(otherwise, out);
+            }
+        }
+    }
+}
+
+fn is_truthy(variables: &BTreeMap<String, String>, name: &str) -> bool {
+    variables
+        .get(name)
+        .is_some_and(|value| !value.trim().is_empty())
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    variables: &BTreeMap<String, String>,
+    escape: bool,
+    out: &mut String,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Variable(name) => {
+                let value = variables.get(name).map(String::as_str).unwrap_or_default();
+                if escape {
+                    out.push_str(&escape_html(value));
+                } else {
+                    out.push_str(value);
+                }
+            }
+            Node::If {
+                variable,
+                then,
+                otherwise,
+            } => {
+                let branch = if is_truthy(variables, variable) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, variables, escape, out);
+            }
+        }
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Paragraphs for blank-line separated blocks, `<br>` for single line breaks.
+fn html_from_plain(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| {
+            let lines: Vec<String> = paragraph.lines().map(escape_html).collect();
+            format!("<p>{}</p>", lines.join("<br>\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Clone)]
+pub struct ReplyTemplateRepository {
+    db: Database,
+}
+
+impl ReplyTemplateRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(
+        &self,
+        new_template: NewReplyTemplate,
+    ) -> Result<ReplyTemplate, ReplyTemplateError> {
+        let template = normalize_reply_template(new_template)?;
+        let now = timestamp(Utc::now());
+
+        let conn = self.db.connection().await?;
+        let result = conn
+            .query(
+                &format!(
+                    "INSERT INTO reply_templates (id, name, description, subject, body, body_html, slots_json, created_at, updated_at, org_id, user_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8, ?9, ?10)
+                     RETURNING {REPLY_TEMPLATE_COLUMNS}"
+                ),
+                params![
+                    Uuid::new_v4().to_string(),
+                    template.name.as_str(),
+                    template.description,
+                    template.subject,
+                    template.body,
+                    template.body_html,
+                    serde_json::to_string(&template.slots)?,
+                    now,
+                    template.org_id,
+                    template.user_id
+                ],
+            )
+            .await;
+
+        let mut rows = result.map_err(|err| write_error(err, &template.name))?;
+        match rows
+            .next()
+            .await
+            .map_err(|err| write_error(err, &template.name))?
+        {
+            Some(row) => row_to_reply_template(row),
+            None => Err(ReplyTemplateError::NotFound("insert failed".into())),
+        }
+    }
+
+    pub async fn get_by_id(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<ReplyTemplate, ReplyTemplateError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {REPLY_TEMPLATE_COLUMNS} FROM reply_templates
+                     WHERE id = ?1 AND org_id = ?2 AND user_id = ?3"
+                ),
+                params![id, org_id, user_id],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => row_to_reply_template(row),
+            None => Err(ReplyTemplateError::NotFound(id.to_string())),
+        }
+    }
+
+    /// Look a template up by name, ignoring case.
+    pub async fn get_by_name(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        name: &str,
+    ) -> Result<ReplyTemplate, ReplyTemplateError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {REPLY_TEMPLATE_COLUMNS} FROM reply_templates
+                     WHERE LOWER(name) = LOWER(?1) AND org_id = ?2 AND user_id = ?3"
+                ),
+                params![name.trim(), org_id, user_id],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => row_to_reply_template(row),
+            None => Err(ReplyTemplateError::NotFound(name.to_string())),
+        }
+    }
+
+    pub async fn list_all(
+        &self,
+        org_id: i64,
+        user_id: i64,
+    ) -> Result<Vec<ReplyTemplate>, ReplyTemplateError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {REPLY_TEMPLATE_COLUMNS} FROM reply_templates
+                     WHERE org_id = ?1 AND user_id = ?2
+                     ORDER BY name"
+                ),
+                params![org_id, user_id],
+            )
+            .await?;
+        let mut templates = Vec::new();
+        while let Some(row) = rows.next().await? {
+            templates.push(row_to_reply_template(row)?);
+        }
+        Ok(templates)
+    }
+
+    pub async fn update(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+        updated: NewReplyTemplate,
+    ) -> Result<ReplyTemplate, ReplyTemplateError> {
+        let template = normalize_reply_template(updated)?;
+        let conn = self.db.connection().await?;
+        let result = conn
+            .query(
+                &format!(
+                    "UPDATE reply_templates
+                     SET name = ?1,
+                         description = ?2,
+                         subject = ?3,
+                         body = ?4,
+                         body_html = ?5,
+                         slots_json = ?6,
+                         updated_at = ?7
+                     WHERE id = ?8 AND org_id = ?9 AND user_id = ?10
+                     RETURNING {REPLY_TEMPLATE_COLUMNS}"
+                ),
+                params![
+                    template.name.as_str(),
+                    template.description,
+                    template.subject,
+                    template.body,
+                    template.body_html,
+                    serde_json::to_string(&template.slots)?,
+                    timestamp(Utc::now()),
+                    id,
+                    org_id,
+                    user_id
+                ],
+            )
+            .await;
+
+        let mut rows = result.map_err(|err| write_error(err, &template.name))?;
+        match rows
+            .next()
+            .await
+            .map_err(|err| write_error(err, &template.name))?
+        {
+            Some(row) => row_to_reply_template(row),
+            None => Err(ReplyTemplateError::NotFound(id.to_string())),
+        }
+    }
+
+    pub async fn delete(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<(), ReplyTemplateError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "DELETE FROM reply_templates WHERE id = ?1 AND org_id = ?2 AND user_id = ?3 RETURNING id",
+                params![id, org_id, user_id],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(_) => Ok(()),
+            None => Err(ReplyTemplateError::NotFound(id.to_string())),
+        }
+    }
+}
+
+/// Validate a template: trimmed name, well-formed slots, templates that parse and only use
+/// built-in variables or declared slots.
+fn normalize_reply_template(
+    mut template: NewReplyTemplate,
+) -> Result<NewReplyTemplate, ReplyTemplateError> {
+    template.name = template.name.trim().to_string();
+    if template.name.is_empty() {
+        return Err(ReplyTemplateError::Invalid("name is required".into()));
+    }
+    if template.body.trim().is_empty() {
+        return Err(ReplyTemplateError::Invalid("body is required".into()));
+    }
+    template.subject = template
+        .subject
+        .filter(|subject| !subject.trim().is_empty());
+    template.body_html = template
+        .body_html
+        .filter(|body_html| !body_html.trim().is_empty());
+
+    let mut declared = BTreeSet::new();
+    for slot in &mut template.slots {
+        slot.name = slot.name.trim().to_string();
+        if !is_variable_name(&slot.name) {
+            return Err(ReplyTemplateError::Invalid(format!(
+                "invalid slot name '{}'; use lowercase letters, digits and underscores",
+                slot.name
+            )));
+        }
+        if BUILTIN_VARIABLES.contains(&slot.name.as_str()) {
+            return Err(ReplyTemplateError::Invalid(format!(
+                "slot '{}' clashes with a built-in variable",
+                slot.name
+            )));
+        }
+        if !declared.insert(slot.name.clone()) {
+            return Err(ReplyTemplateError::Invalid(format!(
+                "slot '{}' is declared twice",
+                slot.name
+            )));
+        }
+    }
+
+    let sources = [
+        ("subject", template.subject.as_deref()),
+        ("body", Some(template.body.as_str())),
+        ("body_html", template.body_html.as_deref()),
+    ];
+    for (field, source) in sources {
+        let Some(source) = source else {
+            continue;
+        };
+        let nodes = parse_template(source)
+            .map_err(|err| ReplyTemplateError::Invalid(format!("{field}: {err}")))?;
+        let mut used = BTreeSet::new();
+        referenced_variables(&nodes, &mut used);
+        if let Some(unknown) = used
+            .iter()
+            .find(|name| !BUILTIN_VARIABLES.contains(&name.as_str()) && !declared.contains(*name))
+        {
+            return Err(ReplyTemplateError::Invalid(format!(
+                "{field}: unknown variable '{unknown}'; declare it as a slot"
+            )));
+        }
+    }
+    Ok(template)
+}
+
+/// Map a write error, turning unique name violations into `DuplicateName`.
+fn write_error(err: libsql::Error, name: &str) -> ReplyTemplateError {
+    if err
+        .to_string()
+        .to_ascii_lowercase()
+        .contains("unique constraint failed")
+    {
+        ReplyTemplateError::DuplicateName(name.to_string())
+    } else {
+        ReplyTemplateError::Sql(err)
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, ReplyTemplateError> {
+    Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc))
+}
+
+fn row_to_reply_template(row: Row) -> Result<ReplyTemplate, ReplyTemplateError> {
+    let slots_json: String = row.get(6)?;
+    let created_at: String = row.get(7)?;
+    let updated_at: String = row.get(8)?;
+
+    Ok(ReplyTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        subject: row.get(3)?,
+        body: row.get(4)?,
+        body_html: row.get(5)?,
+        slots: serde_json::from_str(&slots_json)?,
+        created_at: parse_timestamp(&created_at)?,
+        updated_at: parse_timestamp(&updated_at)?,
+        org_id: row.get(9)?,
+        user_id: row.get(10)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::migrations::run_migrations;
+    use tempfile::TempDir;
+
+    fn slot(name: &str, required: bool) -> TemplateSlot {
+        TemplateSlot {
+            name: name.to_string(),
+            description: None,
+            required,
+        }
+    }
+
+    fn new_template(name: &str, body: &str, slots: Vec<TemplateSlot>) -> NewReplyTemplate {
+        NewReplyTemplate {
+            org_id: DEFAULT_ORG_ID,
+            user_id: DEFAULT_USER_ID,
+            name: name.to_string(),
+            description: None,
+            subject: None,
+            body: body.to_string(),
+            body_html: None,
+            slots,
+        }
+    }
+
+    fn variables(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    #[tokio::test]
+    async fn renders_variables_conditionals_and_html() {
+        let (db, _dir) = setup_db().await;
+        let repo = ReplyTemplateRepository::new(db);
+        let mut new = new_template(
+            "meeting",
+            "Hi {{sender_first_name}},\n\n{{#if meeting_time}}See you {{meeting_time}}.{{else}}I'll send a time soon.{{/if}}\nRe: {{original_subject}}",
+            vec![slot("meeting_time", false), slot("note", true)],
+        );
+        new.subject = Some("{{#if note}}{{note}}{{/if}}".to_string());
+        new.body = format!("{}\n{{{{note}}}}", new.body);
+        let template = repo.create(new).await.expect("create");
+
+        let rendered = template
+            .render(&variables(&[
+                ("sender_first_name", "Ada"),
+                ("original_subject", "Sync <q3>"),
+                ("meeting_time", "Tuesday at 10"),
+                ("note", "Agenda attached"),
+            ]))
+            .expect("render");
+        assert_eq!(rendered.subject.as_deref(), Some("Agenda attached"));
+        assert_eq!(
+            rendered.body_plain,
+            "Hi Ada,\n\nSee you Tuesday at 10.\nRe: Sync <q3>\nAgenda attached"
+        );
+        assert_eq!(
+            rendered.body_html,
+            "<p>Hi Ada,</p>\n<p>See you Tuesday at 10.<br>\nRe: Sync &lt;q3&gt;<br>\nAgenda attached</p>"
+        );
+
+        let rendered = template
+            .render(&variables(&[("meeting_time", " "), ("note", "n")]))
+            .expect("render");
+        assert!(rendered.body_plain.contains("I'll send a time soon."));
+
+        let err = template
+            .render(&variables(&[("meeting_time", "noon")]))
+            .expect_err("missing required slot");
+        assert!(matches!(
+            err,
+            ReplyTemplateError::MissingSlot { ref slot, .. } if slot == "note"
+        ));
+    }
+
+    #[tokio::test]
+    async fn validates_templates_and_names() {
+        let (db, _dir) = setup_db().await;
+        let repo = ReplyTemplateRepository::new(db);
+
+        for (body, slots) in [
+            ("Hi {{#if sender_name}}there", vec![]),
+            ("Hi {{/if}}", vec![]),
+            ("Hi {{ Sender }}", vec![]),
+            ("See you {{when}}", vec![]),
+            ("Hi", vec![slot("sender_name", false)]),
+            ("Hi", vec![slot("when", false), slot("when", true)]),
+        ] {
+            let err = repo
+                .create(new_template("bad", body, slots))
+                .await
+                .expect_err("invalid template");
+            assert!(
+                matches!(err, ReplyTemplateError::Invalid(_)),
+                "{body}: {err}"
+            );
+        }
+
+        let mut html = new_template("Thanks", "Thanks!", vec![]);
+        html.body_html = Some("<b>Thanks, {{sender_name}}!</b>".to_string());
+        let created = repo.create(html).await.expect("create");
+        let found = repo
+            .get_by_name(DEFAULT_ORG_ID, DEFAULT_USER_ID, " thanks ")
+            .await
+            .expect("get by name");
+        assert_eq!(found.id, created.id);
+        let rendered = found
+            .render(&variables(&[("sender_name", "Tom & Jerry")]))
+            .expect("render");
+        assert_eq!(rendered.body_html, "<b>Thanks, Tom &amp; Jerry!</b>");
+
+        let err = repo
+            .create(new_template("THANKS", "Thank you!", vec![]))
+            .await
+            .expect_err("duplicate");
+        assert!(matches!(err, ReplyTemplateError::DuplicateName(_)));
+
+        repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &created.id)
+            .await
+            .expect("delete");
+        assert!(
+            repo.list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+                .await
+                .expect("list")
+                .is_empty()
+        );
+    }
+}
@@ -6,8 +6,11 @@ use serde_json::Value;
 use thiserror::Error;
 use ts_rs::TS;
 
+use crate::contacts::{Contact, ContactStrength};
 use crate::messages::Message;
 
+use super::types::SenderList;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 #[ts(export)]
@@ -33,12 +36,36 @@ pub struct LogicalCondition {
 #[serde(tag = "type", rename_all = "snake_case")]
 #[ts(export)]
 pub enum LeafCondition {
-    SenderEmail { value: String },
-    SenderDomain { value: String },
-    SubjectContains { value: String },
-    SubjectRegex { value: String },
-    HeaderMatch { header: String, pattern: String },
-    LabelPresent { value: String },
+    SenderEmail {
+        value: String,
+    },
+    SenderDomain {
+        value: String,
+    },
+    SubjectContains {
+        value: String,
+    },
+    SubjectRegex {
+        value: String,
+    },
+    HeaderMatch {
+        header: String,
+        pattern: String,
+    },
+    LabelPresent {
+        value: String,
+    },
+    /// Matches when the sender is in the named sender list. Unknown lists never match.
+    InList {
+        list: String,
+    },
+    /// Matches when the sender is a known contact (someone the account has sent mail to) with
+    /// at least `min_strength`. Without `min_strength` any known contact matches.
+    KnownContact {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        min_strength: Option<ContactStrength>,
+    },
 }
 
 /// A condition that can be either a logical operation (AND/OR/NOT) or a leaf condition.
@@ -103,15 +130,45 @@ fn validate_condition(condition: &Condition) -> Result<(), ConditionError> {
 #[derive(Debug, Default)]
 pub struct EvaluationContext {
     regex_cache: HashMap<String, Regex>,
+    /// Sender list entries keyed by lowercased list name.
+    sender_lists: HashMap<String, Vec<String>>,
+    /// Known-contact record of the message sender, if any.
+    sender_contact: Option<Contact>,
 }
 
 impl EvaluationContext {
     pub fn new() -> Self {
         Self {
             regex_cache: HashMap::new(),
+            sender_lists: HashMap::new(),
+            sender_contact: None,
         }
     }
 
+    /// Create a context that resolves `in_list` conditions against the given lists.
+    pub fn with_sender_lists(lists: &[SenderList]) -> Self {
+        Self {
+            regex_cache: HashMap::new(),
+            sender_lists: lists
+                .iter()
+                .map(|list| (list.name.to_lowercase(), list.entries.clone()))
+                .collect(),
+            sender_contact: None,
+        }
+    }
+
+    /// Set the sender's contact record that `known_contact` conditions resolve against.
+    pub fn set_sender_contact(&mut self, contact: Option<Contact>) {
+        self.sender_contact = contact;
+    }
+
+    /// Entries of a sender list, or `None` if the list is not known.
+    pub fn sender_list(&self, name: &str) -> Option<&[String]> {
+        self.sender_lists
+            .get(&name.to_lowercase())
+            .map(Vec::as_slice)
+    }
+
     pub fn get_or_compile_regex(&mut self, pattern: &str) -> Result<&Regex, ConditionError> {
         if !self.regex_cache.contains_key(pattern) {
             let compiled = Regex::new(pattern).map_err(|source| ConditionError::InvalidRegex {
@@ -218,9 +275,65 @@ fn evaluate_leaf(
         LeafCondition::LabelPresent { value } => {
             Ok(message.labels.iter().any(|label| label == value))
         }
+        LeafCondition::InList { list } => {
+            let (Some(from), Some(entries)) =
+                (message.from_email.as_deref(), ctx.sender_list(list))
+            else {
+                return Ok(false);
+            };
+            Ok(entries
+                .iter()
+                .any(|entry| matches_sender_list_entry(entry, from)))
+        }
+        LeafCondition::KnownContact { min_strength } => {
+            Ok(ctx.sender_contact.as_ref().is_some_and(|contact| {
+                contact.strength() >= min_strength.unwrap_or(ContactStrength::Weak)
+            }))
+        }
+    }
+}
+
+/// Entries with an `@` match like `sender_email`; anything else is a domain.
+pub(crate) fn matches_sender_list_entry(entry: &str, email: &str) -> bool {
+    if entry.contains('@') {
+        matches_sender_email(entry, email)
+    } else {
+        extract_domain(email).is_some_and(|domain| domain.eq_ignore_ascii_case(entry))
+    }
+}
+
+/// Visit every leaf in a condition tree.
+pub(crate) fn for_each_leaf<'a>(condition: &'a Condition, f: &mut impl FnMut(&'a LeafCondition)) {
+    match condition {
+        Condition::Leaf(leaf) => f(leaf),
+        Condition::Logical(logical) => {
+            for child in &logical.children {
+                for_each_leaf(child, f);
+            }
+        }
     }
 }
 
+/// Whether any leaf needs the sender's contact record.
+pub fn references_known_contact(condition: &Condition) -> bool {
+    let mut found = false;
+    for_each_leaf(condition, &mut |leaf| {
+        found |= matches!(leaf, LeafCondition::KnownContact { .. });
+    });
+    found
+}
+
+/// Names of the sender lists referenced by `in_list` leaves.
+pub fn referenced_sender_lists(condition: &Condition) -> Vec<&str> {
+    let mut lists = Vec::new();
+    for_each_leaf(condition, &mut |leaf| {
+        if let LeafCondition::InList { list } = leaf {
+            lists.push(list.as_str());
+        }
+    });
+    lists
+}
+
 fn matches_sender_email(pattern: &str, email: &str) -> bool {
     if let Some(domain) = pattern.strip_prefix("*@") {
         return match extract_domain(email) {
@@ -550,6 +663,110 @@ mod tests {
         assert!(!evaluate_simple(condition, &msg).unwrap());
     }
 
+    fn list_context(entries: &[&str]) -> EvaluationContext {
+        EvaluationContext::with_sender_lists(&[SenderList {
+            id: "list1".into(),
+            org_id: 1,
+            user_id: None,
+            name: "VIPs".into(),
+            description: None,
+            entries: entries.iter().map(|e| e.to_string()).collect(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }])
+    }
+
+    #[test]
+    fn in_list_matches_email_wildcard_and_domain_entries() {
+        let msg = sample_message();
+        let condition = Condition::Leaf(LeafCondition::InList {
+            list: "vips".into(),
+        });
+
+        for entries in [
+            vec!["ALICE@amazon.com"],
+            vec!["*@amazon.com"],
+            vec!["other.com", "amazon.com"],
+        ] {
+            let mut ctx = list_context(&entries);
+            assert!(
+                evaluate(&condition, &msg, &mut ctx).unwrap(),
+                "entries {entries:?} should match"
+            );
+        }
+
+        let mut ctx = list_context(&["bob@amazon.com", "mail.amazon.com"]);
+        assert!(!evaluate(&condition, &msg, &mut ctx).unwrap());
+    }
+
+    #[test]
+    fn in_list_unknown_list_does_not_match() {
+        let msg = sample_message();
+        let condition = Condition::Leaf(LeafCondition::InList {
+            list: "family".into(),
+        });
+        let mut ctx = list_context(&["amazon.com"]);
+        assert!(!evaluate(&condition, &msg, &mut ctx).unwrap());
+    }
+
+    fn contact(sent_count: i64, reply_count: i64) -> Contact {
+        Contact {
+            id: "contact1".into(),
+            account_id: "acc1".into(),
+            email: "alice@amazon.com".into(),
+            name: None,
+            sent_count,
+            reply_count,
+            received_count: 0,
+            last_sent_at: None,
+            last_interaction_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            org_id: 1,
+            user_id: 1,
+        }
+    }
+
+    #[test]
+    fn known_contact_respects_min_strength() {
+        let msg = sample_message();
+        let any: Condition =
+            parse_condition(&serde_json::json!({"type": "known_contact"})).unwrap();
+        let moderate: Condition = parse_condition(
+            &serde_json::json!({"type": "known_contact", "min_strength": "moderate"}),
+        )
+        .unwrap();
+
+        let mut ctx = EvaluationContext::new();
+        assert!(!evaluate(&any, &msg, &mut ctx).unwrap());
+
+        ctx.set_sender_contact(Some(contact(1, 0)));
+        assert!(evaluate(&any, &msg, &mut ctx).unwrap());
+        assert!(!evaluate(&moderate, &msg, &mut ctx).unwrap());
+
+        ctx.set_sender_contact(Some(contact(1, 1)));
+        assert!(evaluate(&moderate, &msg, &mut ctx).unwrap());
+        assert!(references_known_contact(&moderate));
+        assert!(!references_known_contact(&Condition::Leaf(
+            LeafCondition::LabelPresent {
+                value: "INBOX".into()
+            }
+        )));
+    }
+
+    #[test]
+    fn referenced_sender_lists_walks_the_tree() {
+        let condition = parse_condition(&serde_json::json!({
+            "op": "and",
+            "children": [
+                {"type": "in_list", "list": "vips"},
+                {"op": "not", "children": [{"type": "in_list", "list": "vendors"}]}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(referenced_sender_lists(&condition), vec!["vips", "vendors"]);
+    }
+
     #[test]
     fn and_all_true() {
         let msg = sample_message();
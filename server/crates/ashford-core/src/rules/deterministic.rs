@@ -1,13 +1,19 @@
+use chrono::Utc;
 use serde_json::Value;
 use thiserror::Error;
 
+use crate::contacts::{Contact, ContactError, ContactRepository};
 use crate::messages::Message;
 
 use super::conditions::{
     ConditionError, EvaluationContext, evaluate, extract_domain, parse_condition,
+    references_known_contact,
 };
-use super::repositories::{DeterministicRuleError, DeterministicRuleRepository};
-use super::types::{DeterministicRule, RuleScope, SafeMode};
+use super::repositories::{
+    DeterministicRuleError, DeterministicRuleRepository, SenderListError, SenderListRepository,
+};
+use super::schedule::retain_active;
+use super::types::{DeterministicRule, RuleScope, SafeMode, SenderList};
 
 #[derive(Debug, Clone)]
 pub struct RuleMatch {
@@ -21,18 +27,62 @@ pub struct RuleMatch {
 pub enum RuleLoaderError {
     #[error("failed to load rules: {0}")]
     Repository(#[from] DeterministicRuleError),
+    #[error("failed to load sender lists: {0}")]
+    SenderLists(#[from] SenderListError),
+    #[error("failed to load contact: {0}")]
+    Contacts(#[from] ContactError),
 }
 
 #[derive(Clone)]
 pub struct RuleLoader {
     repo: DeterministicRuleRepository,
+    sender_lists: SenderListRepository,
+    contacts: ContactRepository,
 }
 
 impl RuleLoader {
-    pub fn new(repo: DeterministicRuleRepository) -> Self {
-        Self { repo }
+    pub fn new(
+        repo: DeterministicRuleRepository,
+        sender_lists: SenderListRepository,
+        contacts: ContactRepository,
+    ) -> Self {
+        Self {
+            repo,
+            sender_lists,
+            contacts,
+        }
+    }
+
+    /// Load the sender lists that `in_list` conditions resolve against.
+    pub async fn load_sender_lists(
+        &self,
+        org_id: i64,
+        user_id: i64,
+    ) -> Result<Vec<SenderList>, RuleLoaderError> {
+        Ok(self.sender_lists.list_all(org_id, user_id).await?)
     }
 
+    /// Load the known-contact record for the message sender that `known_contact` conditions
+    /// resolve against.
+    pub async fn load_sender_contact(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        message: &Message,
+    ) -> Result<Option<Contact>, RuleLoaderError> {
+        let Some(from) = message.from_email.as_deref() else {
+            return Ok(None);
+        };
+        Ok(self
+            .contacts
+            .get_by_email(org_id, user_id, &message.account_id, from)
+            .await?)
+    }
+
+    /// Load the enabled rules that can apply to a message, in evaluation order.
+    ///
+    /// Rules outside their active window or schedule are left out. Rules whose `active_until`
+    /// has passed are auto-disabled with a `disabled_reason` so they stop being loaded.
     pub async fn load_applicable_rules(
         &self,
         org_id: i64,
@@ -70,17 +120,29 @@ impl RuleLoader {
             );
         }
 
-        rules.sort_by(|a, b| {
-            a.priority
-                .cmp(&b.priority)
-                .then_with(|| a.created_at.cmp(&b.created_at))
-                .then_with(|| a.id.cmp(&b.id))
-        });
+        let repo = &self.repo;
+        let mut rules = retain_active(rules, Utc::now(), |id, reason| async move {
+            repo.disable_rule_with_reason(org_id, user_id, &id, &reason)
+                .await
+                .map(|_| ())
+        })
+        .await;
+        sort_by_evaluation_order(&mut rules);
 
         Ok(rules)
     }
 }
 
+/// Sort rules into the order the executor evaluates them: priority, then creation time, then id.
+pub(crate) fn sort_by_evaluation_order(rules: &mut [DeterministicRule]) {
+    rules.sort_by(|a, b| {
+        a.priority
+            .cmp(&b.priority)
+            .then_with(|| a.created_at.cmp(&b.created_at))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
 #[derive(Debug, Error)]
 pub enum ExecutorError {
     #[error("rule loading failed: {0}")]
@@ -95,9 +157,13 @@ pub struct RuleExecutor {
 }
 
 impl RuleExecutor {
-    pub fn new(repo: DeterministicRuleRepository) -> Self {
+    pub fn new(
+        repo: DeterministicRuleRepository,
+        sender_lists: SenderListRepository,
+        contacts: ContactRepository,
+    ) -> Self {
         Self {
-            loader: RuleLoader::new(repo),
+            loader: RuleLoader::new(repo, sender_lists, contacts),
         }
     }
 
@@ -111,7 +177,6 @@ impl RuleExecutor {
         user_id: i64,
         message: &Message,
     ) -> Result<Option<RuleMatch>, ExecutorError> {
-        let mut ctx = EvaluationContext::new();
         let rules = self
             .loader
             .load_applicable_rules(
@@ -121,9 +186,24 @@ impl RuleExecutor {
                 message.from_email.as_deref(),
             )
             .await?;
+        if rules.is_empty() {
+            return Ok(None);
+        }
+
+        let sender_lists = self.loader.load_sender_lists(org_id, user_id).await?;
+        let mut ctx = EvaluationContext::with_sender_lists(&sender_lists);
+        let mut contact_loaded = false;
 
         for rule in rules {
             let condition = parse_condition(&rule.conditions_json)?;
+            if !contact_loaded && references_known_contact(&condition) {
+                let contact = self
+                    .loader
+                    .load_sender_contact(org_id, user_id, message)
+                    .await?;
+                ctx.set_sender_contact(contact);
+                contact_loaded = true;
+            }
             if evaluate(&condition, message, &mut ctx)? {
                 return Ok(Some(RuleMatch {
                     action_type: rule.action_type.clone(),
@@ -146,7 +226,7 @@ mod tests {
     use crate::gmail::types::Header;
     use crate::messages::{Mailbox, Message};
     use crate::migrations::run_migrations;
-    use crate::rules::types::NewDeterministicRule;
+    use crate::rules::types::{NewDeterministicRule, NewSenderList};
     use libsql::params;
     use tempfile::TempDir;
     use uuid::Uuid;
@@ -159,7 +239,11 @@ mod tests {
         run_migrations(&db).await.expect("migrations");
 
         let repo = DeterministicRuleRepository::new(db.clone());
-        let executor = RuleExecutor::new(repo.clone());
+        let executor = RuleExecutor::new(
+            repo.clone(),
+            SenderListRepository::new(db.clone()),
+            ContactRepository::new(db.clone()),
+        );
 
         (executor, repo, db, dir)
     }
@@ -218,6 +302,9 @@ mod tests {
             action_type: "label".into(),
             action_parameters_json: serde_json::json!({"label": "Applied"}),
             safe_mode: SafeMode::Default,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         }
     }
 
@@ -682,4 +769,117 @@ mod tests {
         let matched = result.expect("should match account rule");
         assert_eq!(matched.rule.scope, RuleScope::Account);
     }
+
+    #[tokio::test]
+    async fn executor_resolves_sender_lists_and_sees_edits() {
+        let (executor, repo, db, _dir) = setup_executor().await;
+        let lists = SenderListRepository::new(db.clone());
+        let message = sample_message("acct1", "alice@amazon.com");
+
+        let list = lists
+            .create(NewSenderList {
+                org_id: DEFAULT_ORG_ID,
+                user_id: Some(DEFAULT_USER_ID),
+                name: "vips".into(),
+                description: None,
+                entries: vec!["bob@example.com".into()],
+            })
+            .await
+            .expect("create list");
+
+        repo.create(new_rule(
+            RuleScope::Global,
+            None,
+            10,
+            true,
+            serde_json::json!({"type": "in_list", "list": "vips"}),
+        ))
+        .await
+        .expect("create rule");
+
+        let result = executor
+            .evaluate(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message)
+            .await
+            .expect("execute");
+        assert!(result.is_none());
+
+        lists
+            .update(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &list.id,
+                NewSenderList {
+                    org_id: DEFAULT_ORG_ID,
+                    user_id: Some(DEFAULT_USER_ID),
+                    name: "vips".into(),
+                    description: None,
+                    entries: vec!["bob@example.com".into(), "amazon.com".into()],
+                },
+            )
+            .await
+            .expect("update list");
+
+        let result = executor
+            .evaluate(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message)
+            .await
+            .expect("execute");
+        assert!(result.is_some());
+    }
+
+    #[tokio::test]
+    async fn executor_skips_inactive_rules_and_disables_expired_ones() {
+        let (executor, repo, _db, _dir) = setup_executor().await;
+        let message = sample_message("acct1", "alice@example.com");
+        let now = chrono::Utc::now();
+        let matches_all = serde_json::json!({"type": "sender_domain", "value": "example.com"});
+
+        let mut expired = new_rule(RuleScope::Global, None, 1, true, matches_all.clone());
+        expired.active_until = Some(now - chrono::Duration::hours(1));
+        let expired = repo.create(expired).await.expect("create expired");
+
+        let mut upcoming = new_rule(RuleScope::Global, None, 2, true, matches_all.clone());
+        upcoming.active_from = Some(now + chrono::Duration::days(1));
+        repo.create(upcoming).await.expect("create upcoming");
+
+        let mut never_scheduled = new_rule(RuleScope::Global, None, 3, true, matches_all.clone());
+        never_scheduled.schedule = Some(crate::rules::schedule::RuleSchedule {
+            days: vec![],
+            start: "00:00".into(),
+            end: "00:00".into(),
+            timezone: "Invalid/Zone".into(),
+        });
+        repo.create(never_scheduled)
+            .await
+            .expect("create scheduled");
+
+        let result = executor
+            .evaluate(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message)
+            .await
+            .expect("execute");
+        assert!(result.is_none());
+
+        let expired = repo
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &expired.id)
+            .await
+            .expect("fetch expired");
+        assert!(!expired.enabled);
+        assert!(
+            expired
+                .disabled_reason
+                .as_deref()
+                .is_some_and(|reason| reason.starts_with("Active window ended at"))
+        );
+
+        let mut current = new_rule(RuleScope::Global, None, 4, true, matches_all);
+        current.active_from = Some(now - chrono::Duration::hours(1));
+        current.active_until = Some(now + chrono::Duration::hours(1));
+        let current = repo.create(current).await.expect("create current");
+
+        let result = executor
+            .evaluate(DEFAULT_ORG_ID, DEFAULT_USER_ID, &message)
+            .await
+            .expect("execute")
+            .expect("current rule matches");
+        assert_eq!(result.rule.id, current.id);
+    }
 }
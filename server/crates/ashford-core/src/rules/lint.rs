@@ -0,0 +1,1428 @@
+//! Static analysis for deterministic rules.
+//!
+//! The linter looks at the enabled rule set the way `RuleLoader` sees it for a message:
+//! rules are ordered by priority, and two rules only interact when their scopes can be
+//! loaded together (a global rule meets everything, two account rules only meet when they
+//! target the same account, and so on). Condition analysis is conservative: a warning is
+//! only raised when it can be proven from the condition trees, so some problems that depend
+//! on message contents will go unreported. A rule with an active window or schedule never
+//! shadows later rules, and only conflicts with rules that share the same window.
+
+use std::collections::{HashMap, HashSet};
+
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+
+use crate::contacts::ContactStrength;
+use crate::labels::{Label, LabelError, LabelRepository};
+use crate::reply_templates::{ReplyTemplate, ReplyTemplateError, ReplyTemplateRepository};
+
+use super::conditions::{
+    Condition, LeafCondition, LogicalCondition, LogicalOperator, extract_domain, for_each_leaf,
+    parse_condition, referenced_sender_lists,
+};
+use super::deterministic::sort_by_evaluation_order;
+use super::repositories::{
+    DeterministicRuleError, DeterministicRuleRepository, SenderListError, SenderListRepository,
+};
+use super::schedule::validate_window;
+use super::types::{DeterministicRule, RuleScope, SenderList};
+
+/// Compiled program size above which a regex is reported as too expensive to evaluate.
+/// The regex crate's own limit is 10 MiB; patterns between the two compile but are slow.
+const REGEX_SIZE_LIMIT: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum LintKind {
+    /// The stored conditions do not parse into a valid condition tree.
+    InvalidCondition,
+    /// An earlier rule with a broader or equal scope matches every message this rule matches.
+    Shadowed,
+    /// Rules with identical conditions and overlapping scopes take different actions.
+    ConflictingActions,
+    /// The rule's scope and conditions can never be satisfied by the same message.
+    Unreachable,
+    /// A regex pattern fails to compile.
+    InvalidRegex,
+    /// A regex pattern compiles to a program large enough to make evaluation slow.
+    SlowRegex,
+    /// The rule references a label id that no synced account knows about.
+    UnknownLabel,
+    /// The rule references a sender list that does not exist, so `in_list` never matches.
+    UnknownSenderList,
+    /// An `auto_reply` rule names a reply template that does not exist.
+    UnknownReplyTemplate,
+}
+
+impl LintKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintKind::InvalidCondition => "invalid_condition",
+            LintKind::Shadowed => "shadowed",
+            LintKind::ConflictingActions => "conflicting_actions",
+            LintKind::Unreachable => "unreachable",
+            LintKind::InvalidRegex => "invalid_regex",
+            LintKind::SlowRegex => "slow_regex",
+            LintKind::UnknownLabel => "unknown_label",
+            LintKind::UnknownSenderList => "unknown_sender_list",
+            LintKind::UnknownReplyTemplate => "unknown_reply_template",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LintWarning {
+    pub kind: LintKind,
+    pub rule_id: String,
+    pub rule_name: String,
+    /// The other rule involved, for shadowing and conflict warnings.
+    pub related_rule_id: Option<String>,
+    pub message: String,
+}
+
+impl LintWarning {
+    /// Whether this warning is about the given rule, either directly or as the related rule.
+    pub fn involves(&self, rule_id: &str) -> bool {
+        self.rule_id == rule_id || self.related_rule_id.as_deref() == Some(rule_id)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LintError {
+    #[error("failed to load rules: {0}")]
+    Rules(#[from] DeterministicRuleError),
+    #[error("failed to load labels: {0}")]
+    Labels(#[from] LabelError),
+    #[error("failed to load sender lists: {0}")]
+    SenderLists(#[from] SenderListError),
+    #[error("failed to load reply templates: {0}")]
+    ReplyTemplates(#[from] ReplyTemplateError),
+}
+
+/// Provider label ids known for each account.
+#[derive(Debug, Clone, Default)]
+pub struct KnownLabels {
+    by_account: HashMap<String, HashSet<String>>,
+}
+
+impl KnownLabels {
+    pub fn from_labels(labels: &[Label]) -> Self {
+        let mut by_account: HashMap<String, HashSet<String>> = HashMap::new();
+        for label in labels {
+            by_account
+                .entry(label.account_id.clone())
+                .or_default()
+                .insert(label.provider_label_id.clone());
+        }
+        Self { by_account }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_account.is_empty()
+    }
+
+    /// Check a label id against one account, or against every account when the rule is not
+    /// tied to one (or the account has not synced labels yet).
+    fn contains(&self, account_id: Option<&str>, label_id: &str) -> bool {
+        if let Some(labels) = account_id.and_then(|id| self.by_account.get(id)) {
+            return labels.contains(label_id);
+        }
+        self.by_account
+            .values()
+            .any(|labels| labels.contains(label_id))
+    }
+}
+
+#[derive(Clone)]
+pub struct RuleLinter {
+    rules: DeterministicRuleRepository,
+    labels: LabelRepository,
+    sender_lists: SenderListRepository,
+    reply_templates: ReplyTemplateRepository,
+}
+
+impl RuleLinter {
+    pub fn new(
+        rules: DeterministicRuleRepository,
+        labels: LabelRepository,
+        sender_lists: SenderListRepository,
+        reply_templates: ReplyTemplateRepository,
+    ) -> Self {
+        Self {
+            rules,
+            labels,
+            sender_lists,
+            reply_templates,
+        }
+    }
+
+    /// Lint every enabled deterministic rule visible to the org/user.
+    pub async fn lint(&self, org_id: i64, user_id: i64) -> Result<Vec<LintWarning>, LintError> {
+        let rules = self.rules.list_all(org_id, user_id).await?;
+        let labels = self.labels.list_all(org_id, user_id).await?;
+        let sender_lists = self.sender_lists.list_all(org_id, user_id).await?;
+        let reply_templates = self.reply_templates.list_all(org_id, user_id).await?;
+        Ok(lint_rules(
+            &rules,
+            &KnownLabels::from_labels(&labels),
+            &sender_lists,
+            &reply_templates,
+        ))
+    }
+
+    /// Lint the full rule set but only return warnings that involve the given rule.
+    pub async fn lint_rule(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        rule_id: &str,
+    ) -> Result<Vec<LintWarning>, LintError> {
+        let warnings = self.lint(org_id, user_id).await?;
+        Ok(warnings
+            .into_iter()
+            .filter(|warning| warning.involves(rule_id))
+            .collect())
+    }
+}
+
+/// Lint a set of rules. Disabled rules are ignored, since the loader never sees them.
+/// Label references are only checked when `known_labels` is non-empty, so a fresh install
+/// without synced labels does not flag every label rule. `in_list` conditions are expanded
+/// into their entries before conditions are compared.
+pub fn lint_rules(
+    rules: &[DeterministicRule],
+    known_labels: &KnownLabels,
+    sender_lists: &[SenderList],
+    reply_templates: &[ReplyTemplate],
+) -> Vec<LintWarning> {
+    let mut enabled: Vec<DeterministicRule> = rules.iter().filter(|r| r.enabled).cloned().collect();
+    sort_by_evaluation_order(&mut enabled);
+
+    let mut warnings = Vec::new();
+    let mut parsed: Vec<(&DeterministicRule, Condition, Option<Applicability>)> = Vec::new();
+
+    for rule in &enabled {
+        let condition = match parse_condition(&rule.conditions_json) {
+            Ok(condition) => condition,
+            Err(err) => {
+                warnings.push(warning(
+                    LintKind::InvalidCondition,
+                    rule,
+                    None,
+                    format!("conditions are invalid: {err}"),
+                ));
+                continue;
+            }
+        };
+
+        check_regexes(rule, &condition, &mut warnings);
+        if !known_labels.is_empty() {
+            check_labels(rule, &condition, known_labels, &mut warnings);
+        }
+        check_sender_lists(rule, &condition, sender_lists, &mut warnings);
+        check_reply_template(rule, reply_templates, &mut warnings);
+        if let Err(err) =
+            validate_window(rule.active_from, rule.active_until, rule.schedule.as_ref())
+        {
+            warnings.push(warning(
+                LintKind::Unreachable,
+                rule,
+                None,
+                format!("active window can never match: {err}"),
+            ));
+        }
+        let condition = expand_sender_lists(condition, sender_lists);
+
+        let applicability = Applicability::of(rule);
+        match &applicability {
+            None => warnings.push(warning(
+                LintKind::Unreachable,
+                rule,
+                None,
+                format!(
+                    "{} scope requires a scope_ref, so the rule is never loaded",
+                    rule.scope.as_str()
+                ),
+            )),
+            Some(applies) => {
+                if let Some(reason) = unsatisfiable_sender(applies, &condition) {
+                    warnings.push(warning(LintKind::Unreachable, rule, None, reason));
+                }
+            }
+        }
+
+        if let Some(applies) = &applicability {
+            check_against_earlier(rule, &condition, applies, &parsed, &mut warnings);
+        }
+
+        parsed.push((rule, condition, applicability));
+    }
+
+    warnings
+}
+
+/// Compare a rule with every rule evaluated before it. Reports at most one shadowing rule.
+fn check_against_earlier(
+    rule: &DeterministicRule,
+    condition: &Condition,
+    applies: &Applicability,
+    earlier: &[(&DeterministicRule, Condition, Option<Applicability>)],
+    warnings: &mut Vec<LintWarning>,
+) {
+    for (other, other_condition, other_applies) in earlier {
+        let Some(other_applies) = other_applies else {
+            continue;
+        };
+        if !other_applies.overlaps(applies) {
+            continue;
+        }
+
+        let covers = other_applies.covers(applies);
+        let same_action = other.action_type == rule.action_type
+            && other.action_parameters_json == rule.action_parameters_json;
+
+        if other_condition == condition && !same_action && same_window(other, rule) {
+            let message = if covers {
+                format!(
+                    "has the same conditions as '{}' but a different action; '{}' always runs first, so this rule never fires",
+                    other.name, other.name
+                )
+            } else {
+                format!(
+                    "has the same conditions as '{}' but a different action; which one fires depends on the account and sender",
+                    other.name
+                )
+            };
+            warnings.push(warning(
+                LintKind::ConflictingActions,
+                rule,
+                Some(other),
+                message,
+            ));
+            if covers {
+                return;
+            }
+            continue;
+        }
+
+        if covers && always_active(other) && implies(condition, other_condition) {
+            let message = if same_action {
+                format!(
+                    "duplicates '{}', which runs first with the same action",
+                    other.name
+                )
+            } else {
+                format!(
+                    "is shadowed by '{}', which runs first and matches every message this rule matches",
+                    other.name
+                )
+            };
+            warnings.push(warning(LintKind::Shadowed, rule, Some(other), message));
+            return;
+        }
+    }
+}
+
+fn always_active(rule: &DeterministicRule) -> bool {
+    rule.active_from.is_none() && rule.active_until.is_none() && rule.schedule.is_none()
+}
+
+fn same_window(a: &DeterministicRule, b: &DeterministicRule) -> bool {
+    a.active_from == b.active_from && a.active_until == b.active_until && a.schedule == b.schedule
+}
+
+fn warning(
+    kind: LintKind,
+    rule: &DeterministicRule,
+    related: Option<&DeterministicRule>,
+    message: String,
+) -> LintWarning {
+    LintWarning {
+        kind,
+        rule_id: rule.id.clone(),
+        rule_name: rule.name.clone(),
+        related_rule_id: related.map(|r| r.id.clone()),
+        message,
+    }
+}
+
+/// The set of messages a rule is loaded for, derived from its scope.
+#[derive(Debug, Clone, PartialEq)]
+enum Applicability {
+    Global,
+    Account(String),
+    Domain(String),
+    Sender(String),
+}
+
+impl Applicability {
+    /// Returns `None` for scoped rules without a scope_ref, which the loader never returns.
+    fn of(rule: &DeterministicRule) -> Option<Self> {
+        let scope_ref = rule.scope_ref.as_deref().map(str::to_lowercase);
+        match rule.scope {
+            RuleScope::Global => Some(Self::Global),
+            RuleScope::Account => rule.scope_ref.clone().map(Self::Account),
+            RuleScope::Domain => scope_ref.map(Self::Domain),
+            RuleScope::Sender => scope_ref.map(Self::Sender),
+        }
+    }
+
+    /// Whether both rules can be loaded for the same message.
+    fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Global, _) | (_, Self::Global) => true,
+            (Self::Account(a), Self::Account(b)) => a == b,
+            (Self::Account(_), _) | (_, Self::Account(_)) => true,
+            (Self::Domain(a), Self::Domain(b)) => a == b,
+            (Self::Sender(a), Self::Sender(b)) => a == b,
+            (Self::Domain(domain), Self::Sender(sender))
+            | (Self::Sender(sender), Self::Domain(domain)) => {
+                extract_domain(sender) == Some(domain.as_str())
+            }
+        }
+    }
+
+    /// Whether this rule is loaded for every message the other rule is loaded for.
+    fn covers(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Global, _) => true,
+            (Self::Account(a), Self::Account(b)) => a == b,
+            (Self::Domain(a), Self::Domain(b)) => a == b,
+            (Self::Domain(domain), Self::Sender(sender)) => {
+                extract_domain(sender) == Some(domain.as_str())
+            }
+            (Self::Sender(a), Self::Sender(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Returns true when every message matching `narrow` also matches `broad`.
+/// False negatives are expected; false positives are not.
+fn implies(narrow: &Condition, broad: &Condition) -> bool {
+    if narrow == broad {
+        return true;
+    }
+
+    if let Condition::Logical(logical) = broad
+        && logical.op == LogicalOperator::And
+    {
+        return logical.children.iter().all(|child| implies(narrow, child));
+    }
+    if let Condition::Logical(logical) = narrow
+        && logical.op == LogicalOperator::Or
+    {
+        return logical.children.iter().all(|child| implies(child, broad));
+    }
+    if let Condition::Logical(logical) = broad
+        && logical.op == LogicalOperator::Or
+        && logical.children.iter().any(|child| implies(narrow, child))
+    {
+        return true;
+    }
+    if let Condition::Logical(logical) = narrow
+        && logical.op == LogicalOperator::And
+        && logical.children.iter().any(|child| implies(child, broad))
+    {
+        return true;
+    }
+
+    match (narrow, broad) {
+        (Condition::Logical(n), Condition::Logical(b))
+            if n.op == LogicalOperator::Not && b.op == LogicalOperator::Not =>
+        {
+            match (n.children.first(), b.children.first()) {
+                (Some(n_child), Some(b_child)) => implies(b_child, n_child),
+                _ => false,
+            }
+        }
+        (Condition::Leaf(n), Condition::Leaf(b)) => leaf_implies(n, b),
+        _ => false,
+    }
+}
+
+fn leaf_implies(narrow: &LeafCondition, broad: &LeafCondition) -> bool {
+    match (narrow, broad) {
+        (LeafCondition::SenderEmail { value: n }, LeafCondition::SenderEmail { value: b }) => {
+            if n.eq_ignore_ascii_case(b) {
+                return true;
+            }
+            match b.strip_prefix("*@") {
+                Some(domain) => {
+                    sender_pattern_domain(n).is_some_and(|d| d.eq_ignore_ascii_case(domain))
+                }
+                None => false,
+            }
+        }
+        (LeafCondition::SenderEmail { value: n }, LeafCondition::SenderDomain { value: b }) => {
+            sender_pattern_domain(n).is_some_and(|d| d.eq_ignore_ascii_case(b))
+        }
+        (LeafCondition::SenderDomain { value: n }, LeafCondition::SenderEmail { value: b }) => b
+            .strip_prefix("*@")
+            .is_some_and(|domain| domain.eq_ignore_ascii_case(n)),
+        (LeafCondition::SenderDomain { value: n }, LeafCondition::SenderDomain { value: b }) => {
+            n.eq_ignore_ascii_case(b)
+        }
+        (
+            LeafCondition::SubjectContains { value: n },
+            LeafCondition::SubjectContains { value: b },
+        ) => n.to_lowercase().contains(&b.to_lowercase()),
+        (
+            LeafCondition::HeaderMatch {
+                header: n_header,
+                pattern: n_pattern,
+            },
+            LeafCondition::HeaderMatch {
+                header: b_header,
+                pattern: b_pattern,
+            },
+        ) => n_header.eq_ignore_ascii_case(b_header) && n_pattern == b_pattern,
+        (
+            LeafCondition::KnownContact { min_strength: n },
+            LeafCondition::KnownContact { min_strength: b },
+        ) => n.unwrap_or(ContactStrength::Weak) >= b.unwrap_or(ContactStrength::Weak),
+        _ => narrow == broad,
+    }
+}
+
+/// Domain matched by a `SenderEmail` pattern, for both exact addresses and `*@domain`.
+fn sender_pattern_domain(pattern: &str) -> Option<&str> {
+    pattern
+        .strip_prefix("*@")
+        .or_else(|| extract_domain(pattern))
+}
+
+/// Detect rules whose scope and top-level AND conditions require two different senders
+/// or sender domains at once.
+fn unsatisfiable_sender(applies: &Applicability, condition: &Condition) -> Option<String> {
+    let mut emails: Vec<String> = Vec::new();
+    let mut domains: Vec<String> = Vec::new();
+
+    match applies {
+        Applicability::Domain(domain) => domains.push(domain.clone()),
+        Applicability::Sender(sender) => {
+            emails.push(sender.clone());
+            if let Some(domain) = extract_domain(sender) {
+                domains.push(domain.to_string());
+            }
+        }
+        Applicability::Global | Applicability::Account(_) => {}
+    }
+
+    collect_required_senders(condition, &mut emails, &mut domains);
+
+    emails.sort();
+    emails.dedup();
+    domains.sort();
+    domains.dedup();
+
+    if emails.len() > 1 {
+        Some(format!(
+            "requires the sender to be each of {} at once, so it can never match",
+            emails.join(", ")
+        ))
+    } else if domains.len() > 1 {
+        Some(format!(
+            "requires the sender domain to be each of {} at once, so it can never match",
+            domains.join(", ")
+        ))
+    } else {
+        None
+    }
+}
+
+fn collect_required_senders(
+    condition: &Condition,
+    emails: &mut Vec<String>,
+    domains: &mut Vec<String>,
+) {
+    match condition {
+        Condition::Logical(logical) if logical.op == LogicalOperator::And => {
+            for child in &logical.children {
+                collect_required_senders(child, emails, domains);
+            }
+        }
+        Condition::Leaf(LeafCondition::SenderEmail { value }) => {
+            let value = value.to_lowercase();
+            if let Some(domain) = sender_pattern_domain(&value) {
+                domains.push(domain.to_string());
+            }
+            if !value.starts_with("*@") {
+                emails.push(value);
+            }
+        }
+        Condition::Leaf(LeafCondition::SenderDomain { value }) => {
+            domains.push(value.to_lowercase());
+        }
+        _ => {}
+    }
+}
+
+fn check_regexes(rule: &DeterministicRule, condition: &Condition, warnings: &mut Vec<LintWarning>) {
+    let mut patterns: Vec<&str> = Vec::new();
+    for_each_leaf(condition, &mut |leaf| match leaf {
+        LeafCondition::SubjectRegex { value } => patterns.push(value),
+        LeafCondition::HeaderMatch { pattern, .. } => patterns.push(pattern),
+        _ => {}
+    });
+    patterns.dedup();
+
+    for pattern in patterns {
+        match RegexBuilder::new(pattern)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+        {
+            Ok(_) => {}
+            Err(regex::Error::CompiledTooBig(limit)) => {
+                // Still report patterns that the default limit would reject as invalid.
+                let kind = if RegexBuilder::new(pattern).build().is_ok() {
+                    LintKind::SlowRegex
+                } else {
+                    LintKind::InvalidRegex
+                };
+                warnings.push(warning(
+                    kind,
+                    rule,
+                    None,
+                    format!(
+                        "regex '{pattern}' compiles to more than {limit} bytes and is expensive to evaluate"
+                    ),
+                ));
+            }
+            Err(err) => warnings.push(warning(
+                LintKind::InvalidRegex,
+                rule,
+                None,
+                format!("regex '{pattern}' does not compile: {err}"),
+            )),
+        }
+    }
+}
+
+fn check_sender_lists(
+    rule: &DeterministicRule,
+    condition: &Condition,
+    sender_lists: &[SenderList],
+    warnings: &mut Vec<LintWarning>,
+) {
+    let mut seen = HashSet::new();
+    for name in referenced_sender_lists(condition) {
+        let known = sender_lists
+            .iter()
+            .any(|list| list.name.eq_ignore_ascii_case(name));
+        if !known && seen.insert(name.to_lowercase()) {
+            warnings.push(warning(
+                LintKind::UnknownSenderList,
+                rule,
+                None,
+                format!("references sender list '{name}', which does not exist"),
+            ));
+        }
+    }
+}
+
+fn check_reply_template(
+    rule: &DeterministicRule,
+    reply_templates: &[ReplyTemplate],
+    warnings: &mut Vec<LintWarning>,
+) {
+    if rule.action_type != "auto_reply" {
+        return;
+    }
+    let Some(name) = rule.action_parameters_json["template"].as_str() else {
+        return;
+    };
+    let known = reply_templates
+        .iter()
+        .any(|template| template.name.eq_ignore_ascii_case(name.trim()));
+    if !known {
+        warnings.push(warning(
+            LintKind::UnknownReplyTemplate,
+            rule,
+            None,
+            format!("replies with template '{name}', which does not exist"),
+        ));
+    }
+}
+
+/// Replace `in_list` leaves with an OR of the equivalent `sender_email` / `sender_domain`
+/// leaves so lists compare like the conditions they stand for. Unknown lists are kept as-is.
+fn expand_sender_lists(condition: Condition, sender_lists: &[SenderList]) -> Condition {
+    match condition {
+        Condition::Leaf(LeafCondition::InList { list }) => {
+            match sender_lists
+                .iter()
+                .find(|candidate| candidate.name.eq_ignore_ascii_case(&list))
+            {
+                Some(found) => Condition::Logical(LogicalCondition {
+                    op: LogicalOperator::Or,
+                    children: found
+                        .entries
+                        .iter()
+                        .map(|entry| {
+                            let value = entry.clone();
+                            Condition::Leaf(if entry.contains('@') {
+                                LeafCondition::SenderEmail { value }
+                            } else {
+                                LeafCondition::SenderDomain { value }
+                            })
+                        })
+                        .collect(),
+                }),
+                None => Condition::Leaf(LeafCondition::InList { list }),
+            }
+        }
+        Condition::Leaf(leaf) => Condition::Leaf(leaf),
+        Condition::Logical(logical) => Condition::Logical(LogicalCondition {
+            op: logical.op,
+            children: logical
+                .children
+                .into_iter()
+                .map(|child| expand_sender_lists(child, sender_lists))
+                .collect(),
+        }),
+    }
+}
+
+fn check_labels(
+    rule: &DeterministicRule,
+    condition: &Condition,
+    known_labels: &KnownLabels,
+    warnings: &mut Vec<LintWarning>,
+) {
+    let account_id = match rule.scope {
+        RuleScope::Account => rule.scope_ref.as_deref(),
+        _ => None,
+    };
+
+    let mut referenced: Vec<&str> = Vec::new();
+    for_each_leaf(condition, &mut |leaf| {
+        if let LeafCondition::LabelPresent { value } = leaf {
+            referenced.push(value);
+        }
+    });
+    if matches!(rule.action_type.as_str(), "apply_label" | "remove_label")
+        && let Some(label) = rule.action_parameters_json["label"].as_str()
+    {
+        referenced.push(label);
+    }
+
+    let mut seen = HashSet::new();
+    for label_id in referenced {
+        if seen.insert(label_id) && !known_labels.contains(account_id, label_id) {
+            warnings.push(warning(
+                LintKind::UnknownLabel,
+                rule,
+                None,
+                format!("references label '{label_id}', which no synced account has"),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::db::Database;
+    use crate::gmail::OAuthTokens;
+    use crate::labels::NewLabel;
+    use crate::migrations::run_migrations;
+    use crate::rules::schedule::RuleSchedule;
+    use crate::rules::types::{NewDeterministicRule, SafeMode};
+    use chrono::{Duration, Utc};
+    use serde_json::{Value, json};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn rule(
+        id: &str,
+        priority: i64,
+        scope: RuleScope,
+        scope_ref: Option<&str>,
+        conditions_json: Value,
+        action_type: &str,
+        action_parameters_json: Value,
+    ) -> DeterministicRule {
+        let created_at = Utc::now() + Duration::milliseconds(priority);
+        DeterministicRule {
+            id: id.to_string(),
+            org_id: DEFAULT_ORG_ID,
+            user_id: Some(DEFAULT_USER_ID),
+            name: id.to_string(),
+            description: None,
+            scope,
+            scope_ref: scope_ref.map(str::to_string),
+            priority,
+            enabled: true,
+            disabled_reason: None,
+            conditions_json,
+            action_type: action_type.to_string(),
+            action_parameters_json,
+            safe_mode: SafeMode::Default,
+            created_at,
+            updated_at: created_at,
+            active_from: None,
+            active_until: None,
+            schedule: None,
+        }
+    }
+
+    fn global(
+        id: &str,
+        priority: i64,
+        conditions_json: Value,
+        action_type: &str,
+    ) -> DeterministicRule {
+        rule(
+            id,
+            priority,
+            RuleScope::Global,
+            None,
+            conditions_json,
+            action_type,
+            json!({}),
+        )
+    }
+
+    fn kinds(warnings: &[LintWarning]) -> Vec<(LintKind, &str)> {
+        warnings
+            .iter()
+            .map(|w| (w.kind, w.rule_id.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn clean_rule_set_has_no_warnings() {
+        let rules = vec![
+            global(
+                "a",
+                10,
+                json!({"type": "sender_domain", "value": "amazon.com"}),
+                "archive",
+            ),
+            global(
+                "b",
+                20,
+                json!({"type": "subject_contains", "value": "invoice"}),
+                "star",
+            ),
+        ];
+
+        assert!(lint_rules(&rules, &KnownLabels::default(), &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn broader_earlier_rule_shadows_narrower_rule() {
+        let rules = vec![
+            global(
+                "domain",
+                10,
+                json!({"type": "sender_domain", "value": "amazon.com"}),
+                "archive",
+            ),
+            global(
+                "sender",
+                20,
+                json!({"op": "and", "children": [
+                    {"type": "sender_email", "value": "orders@amazon.com"},
+                    {"type": "subject_contains", "value": "shipped"}
+                ]}),
+                "star",
+            ),
+        ];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &[], &[]);
+        assert_eq!(kinds(&warnings), vec![(LintKind::Shadowed, "sender")]);
+        assert_eq!(warnings[0].related_rule_id.as_deref(), Some("domain"));
+    }
+
+    #[test]
+    fn narrower_earlier_rule_does_not_shadow() {
+        let rules = vec![
+            global(
+                "sender",
+                10,
+                json!({"type": "sender_email", "value": "orders@amazon.com"}),
+                "star",
+            ),
+            global(
+                "domain",
+                20,
+                json!({"type": "sender_domain", "value": "amazon.com"}),
+                "archive",
+            ),
+        ];
+
+        assert!(lint_rules(&rules, &KnownLabels::default(), &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn weaker_known_contact_rule_shadows_stronger_one() {
+        let rules = vec![
+            global("any", 10, json!({"type": "known_contact"}), "star"),
+            global(
+                "strong",
+                20,
+                json!({"type": "known_contact", "min_strength": "strong"}),
+                "apply_label",
+            ),
+        ];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &[], &[]);
+        assert_eq!(kinds(&warnings), vec![(LintKind::Shadowed, "strong")]);
+
+        let reversed = vec![
+            global(
+                "strong",
+                10,
+                json!({"type": "known_contact", "min_strength": "strong"}),
+                "star",
+            ),
+            global("any", 20, json!({"type": "known_contact"}), "apply_label"),
+        ];
+        assert!(lint_rules(&reversed, &KnownLabels::default(), &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn account_scoped_rule_does_not_shadow_global_rule() {
+        let condition = json!({"type": "subject_contains", "value": "sale"});
+        let rules = vec![
+            rule(
+                "account",
+                10,
+                RuleScope::Account,
+                Some("acct1"),
+                condition.clone(),
+                "archive",
+                json!({}),
+            ),
+            global("global", 20, condition, "archive"),
+        ];
+
+        assert!(lint_rules(&rules, &KnownLabels::default(), &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn identical_conditions_with_different_actions_conflict() {
+        let condition = json!({"type": "subject_contains", "value": "sale"});
+        let rules = vec![
+            global("first", 10, condition.clone(), "archive"),
+            global("second", 20, condition, "delete"),
+        ];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &[], &[]);
+        assert_eq!(
+            kinds(&warnings),
+            vec![(LintKind::ConflictingActions, "second")]
+        );
+        assert!(warnings[0].message.contains("never fires"));
+    }
+
+    #[test]
+    fn conflict_across_overlapping_scopes_depends_on_context() {
+        let condition = json!({"type": "subject_contains", "value": "sale"});
+        let rules = vec![
+            rule(
+                "account",
+                10,
+                RuleScope::Account,
+                Some("acct1"),
+                condition.clone(),
+                "archive",
+                json!({}),
+            ),
+            rule(
+                "domain",
+                20,
+                RuleScope::Domain,
+                Some("shop.com"),
+                condition,
+                "delete",
+                json!({}),
+            ),
+        ];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &[], &[]);
+        assert_eq!(
+            kinds(&warnings),
+            vec![(LintKind::ConflictingActions, "domain")]
+        );
+        assert!(warnings[0].message.contains("depends on"));
+    }
+
+    #[test]
+    fn rules_for_different_senders_do_not_interact() {
+        let condition = json!({"type": "subject_contains", "value": "sale"});
+        let rules = vec![
+            rule(
+                "a",
+                10,
+                RuleScope::Sender,
+                Some("a@shop.com"),
+                condition.clone(),
+                "archive",
+                json!({}),
+            ),
+            rule(
+                "b",
+                20,
+                RuleScope::Sender,
+                Some("b@shop.com"),
+                condition,
+                "delete",
+                json!({}),
+            ),
+        ];
+
+        assert!(lint_rules(&rules, &KnownLabels::default(), &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn disabled_rules_are_ignored() {
+        let condition = json!({"type": "subject_contains", "value": "sale"});
+        let mut first = global("first", 10, condition.clone(), "archive");
+        first.enabled = false;
+        let rules = vec![first, global("second", 20, condition, "delete")];
+
+        assert!(lint_rules(&rules, &KnownLabels::default(), &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn contradictory_sender_requirements_are_unreachable() {
+        let rules = vec![
+            global(
+                "and",
+                10,
+                json!({"op": "and", "children": [
+                    {"type": "sender_domain", "value": "a.com"},
+                    {"type": "sender_domain", "value": "b.com"}
+                ]}),
+                "archive",
+            ),
+            rule(
+                "scoped",
+                20,
+                RuleScope::Domain,
+                Some("a.com"),
+                json!({"type": "sender_email", "value": "someone@b.com"}),
+                "archive",
+                json!({}),
+            ),
+            rule(
+                "missing_ref",
+                30,
+                RuleScope::Sender,
+                None,
+                json!({"type": "subject_contains", "value": "x"}),
+                "archive",
+                json!({}),
+            ),
+        ];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &[], &[]);
+        assert_eq!(
+            kinds(&warnings),
+            vec![
+                (LintKind::Unreachable, "and"),
+                (LintKind::Unreachable, "scoped"),
+                (LintKind::Unreachable, "missing_ref"),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_and_oversized_regexes_are_reported() {
+        let rules = vec![
+            global(
+                "invalid",
+                10,
+                json!({"type": "subject_regex", "value": "(unclosed"}),
+                "archive",
+            ),
+            global(
+                "slow",
+                20,
+                json!({"type": "header_match", "header": "X-Id", "pattern": r"\w{100}"}),
+                "archive",
+            ),
+        ];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &[], &[]);
+        assert_eq!(
+            kinds(&warnings),
+            vec![
+                (LintKind::InvalidRegex, "invalid"),
+                (LintKind::SlowRegex, "slow"),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_conditions_are_reported() {
+        let rules = vec![global(
+            "bad",
+            10,
+            json!({"op": "not", "children": []}),
+            "archive",
+        )];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &[], &[]);
+        assert_eq!(kinds(&warnings), vec![(LintKind::InvalidCondition, "bad")]);
+    }
+
+    fn label(account_id: &str, provider_label_id: &str) -> Label {
+        Label {
+            id: Uuid::new_v4().to_string(),
+            account_id: account_id.to_string(),
+            provider_label_id: provider_label_id.to_string(),
+            name: provider_label_id.to_string(),
+            label_type: "user".into(),
+            description: None,
+            available_to_classifier: true,
+            message_list_visibility: None,
+            label_list_visibility: None,
+            background_color: None,
+            text_color: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            org_id: DEFAULT_ORG_ID,
+            user_id: DEFAULT_USER_ID,
+        }
+    }
+
+    #[test]
+    fn unknown_labels_are_reported() {
+        let known =
+            KnownLabels::from_labels(&[label("acct1", "Label_1"), label("acct2", "Label_2")]);
+        let rules = vec![
+            global(
+                "known_condition",
+                10,
+                json!({"type": "label_present", "value": "Label_2"}),
+                "archive",
+            ),
+            rule(
+                "account_label",
+                20,
+                RuleScope::Account,
+                Some("acct1"),
+                json!({"type": "subject_contains", "value": "x"}),
+                "apply_label",
+                json!({"label": "Label_2"}),
+            ),
+            rule(
+                "missing_action_label",
+                30,
+                RuleScope::Global,
+                None,
+                json!({"type": "subject_contains", "value": "y"}),
+                "remove_label",
+                json!({"label": "Label_9"}),
+            ),
+        ];
+
+        let warnings = lint_rules(&rules, &known, &[], &[]);
+        assert_eq!(
+            kinds(&warnings),
+            vec![
+                (LintKind::UnknownLabel, "account_label"),
+                (LintKind::UnknownLabel, "missing_action_label"),
+            ]
+        );
+
+        // Without any synced labels the check is skipped entirely.
+        assert!(lint_rules(&rules, &KnownLabels::default(), &[], &[]).is_empty());
+    }
+
+    fn sender_list(name: &str, entries: &[&str]) -> SenderList {
+        SenderList {
+            id: Uuid::new_v4().to_string(),
+            org_id: DEFAULT_ORG_ID,
+            user_id: Some(DEFAULT_USER_ID),
+            name: name.to_string(),
+            description: None,
+            entries: entries.iter().map(|e| e.to_string()).collect(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn sender_lists_are_expanded_before_comparing_conditions() {
+        let lists = vec![sender_list("vips", &["boss@corp.com", "partner.com"])];
+        let rules = vec![
+            global(
+                "vips",
+                10,
+                json!({"type": "in_list", "list": "VIPs"}),
+                "star",
+            ),
+            global(
+                "partner_ceo",
+                20,
+                json!({"type": "sender_email", "value": "ceo@partner.com"}),
+                "archive",
+            ),
+            global(
+                "other",
+                30,
+                json!({"type": "sender_domain", "value": "elsewhere.com"}),
+                "archive",
+            ),
+        ];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &lists, &[]);
+        assert_eq!(kinds(&warnings), vec![(LintKind::Shadowed, "partner_ceo")]);
+    }
+
+    #[test]
+    fn unknown_sender_lists_are_reported() {
+        let rules = vec![global(
+            "missing",
+            10,
+            json!({"op": "or", "children": [
+                {"type": "in_list", "list": "family"},
+                {"type": "in_list", "list": "Family"}
+            ]}),
+            "star",
+        )];
+
+        let warnings = lint_rules(&rules, &KnownLabels::default(), &[], &[]);
+        assert_eq!(
+            kinds(&warnings),
+            vec![(LintKind::UnknownSenderList, "missing")]
+        );
+    }
+
+    #[test]
+    fn unknown_reply_templates_are_reported() {
+        let mut known = global(
+            "known",
+            10,
+            json!({"type": "subject_contains", "value": "pricing"}),
+            "auto_reply",
+        );
+        known.action_parameters_json = json!({"template": "Pricing"});
+        let mut missing = global(
+            "missing",
+            20,
+            json!({"type": "subject_contains", "value": "vacation"}),
+            "auto_reply",
+        );
+        missing.action_parameters_json = json!({"template": "vacation"});
+        let now = Utc::now();
+        let templates = vec![ReplyTemplate {
+            id: Uuid::new_v4().to_string(),
+            org_id: DEFAULT_ORG_ID,
+            user_id: DEFAULT_USER_ID,
+            name: "pricing".to_string(),
+            description: None,
+            subject: None,
+            body: "See our pricing page.".to_string(),
+            body_html: None,
+            slots: vec![],
+            created_at: now,
+            updated_at: now,
+        }];
+
+        let warnings = lint_rules(&[known, missing], &KnownLabels::default(), &[], &templates);
+        assert_eq!(
+            kinds(&warnings),
+            vec![(LintKind::UnknownReplyTemplate, "missing")]
+        );
+    }
+
+    #[test]
+    fn scheduled_rules_do_not_shadow_and_only_conflict_within_the_same_window() {
+        let business_hours = RuleSchedule {
+            days: vec![],
+            start: "09:00".into(),
+            end: "17:00".into(),
+            timezone: "UTC".into(),
+        };
+        let mut during_hours = global(
+            "hours",
+            10,
+            json!({"type": "sender_domain", "value": "example.com"}),
+            "archive",
+        );
+        during_hours.schedule = Some(business_hours.clone());
+        let always = global(
+            "always",
+            20,
+            json!({"type": "sender_domain", "value": "example.com"}),
+            "star",
+        );
+        let mut vacation = global(
+            "vacation",
+            5,
+            json!({"type": "sender_domain", "value": "example.com"}),
+            "forward",
+        );
+        vacation.active_from = Some(Utc::now());
+        vacation.active_until = Some(Utc::now() + Duration::days(7));
+
+        let warnings = lint_rules(
+            &[during_hours.clone(), always.clone(), vacation],
+            &KnownLabels::default(),
+            &[],
+            &[],
+        );
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+
+        let mut also_during_hours = always;
+        also_during_hours.schedule = Some(business_hours);
+        let warnings = lint_rules(
+            &[during_hours, also_during_hours],
+            &KnownLabels::default(),
+            &[],
+            &[],
+        );
+        assert_eq!(
+            kinds(&warnings),
+            vec![(LintKind::ConflictingActions, "always")]
+        );
+    }
+
+    #[test]
+    fn impossible_active_windows_are_unreachable() {
+        let mut ended_before_start = global(
+            "window",
+            10,
+            json!({"type": "subject_contains", "value": "x"}),
+            "archive",
+        );
+        ended_before_start.active_from = Some(Utc::now());
+        ended_before_start.active_until = Some(Utc::now() - Duration::days(1));
+        let mut bad_timezone = global(
+            "schedule",
+            20,
+            json!({"type": "subject_contains", "value": "y"}),
+            "archive",
+        );
+        bad_timezone.schedule = Some(RuleSchedule {
+            days: vec![],
+            start: "09:00".into(),
+            end: "17:00".into(),
+            timezone: "Nowhere/Special".into(),
+        });
+
+        let warnings = lint_rules(
+            &[ended_before_start, bad_timezone],
+            &KnownLabels::default(),
+            &[],
+            &[],
+        );
+        assert_eq!(
+            kinds(&warnings),
+            vec![
+                (LintKind::Unreachable, "window"),
+                (LintKind::Unreachable, "schedule")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn linter_filters_warnings_for_a_single_rule() {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join(format!("db_{}.sqlite", Uuid::new_v4()));
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let rules = DeterministicRuleRepository::new(db.clone());
+        let account_id = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account")
+            .id;
+
+        let labels = LabelRepository::new(db.clone());
+        labels
+            .upsert(NewLabel {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id,
+                provider_label_id: "Label_1".into(),
+                name: "Receipts".into(),
+                label_type: "user".into(),
+                description: None,
+                available_to_classifier: true,
+                message_list_visibility: None,
+                label_list_visibility: None,
+                background_color: None,
+                text_color: None,
+            })
+            .await
+            .expect("upsert label");
+
+        let new_rule = |name: &str, priority: i64, label: &str| NewDeterministicRule {
+            org_id: DEFAULT_ORG_ID,
+            user_id: Some(DEFAULT_USER_ID),
+            name: name.to_string(),
+            description: None,
+            scope: RuleScope::Global,
+            scope_ref: None,
+            priority,
+            enabled: true,
+            disabled_reason: None,
+            conditions_json: json!({"type": "subject_contains", "value": name}),
+            action_type: "apply_label".into(),
+            action_parameters_json: json!({"label": label}),
+            safe_mode: SafeMode::Default,
+            active_from: None,
+            active_until: None,
+            schedule: None,
+        };
+
+        let good = rules
+            .create(new_rule("good", 10, "Label_1"))
+            .await
+            .expect("create good");
+        let bad = rules
+            .create(new_rule("bad", 20, "Label_deleted"))
+            .await
+            .expect("create bad");
+
+        let linter = RuleLinter::new(
+            rules,
+            labels,
+            SenderListRepository::new(db.clone()),
+            ReplyTemplateRepository::new(db.clone()),
+        );
+        let all = linter
+            .lint(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+            .await
+            .expect("lint");
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].rule_id, bad.id);
+        assert_eq!(all[0].kind, LintKind::UnknownLabel);
+
+        let for_good = linter
+            .lint_rule(DEFAULT_ORG_ID, DEFAULT_USER_ID, &good.id)
+            .await
+            .expect("lint good");
+        assert!(for_good.is_empty());
+    }
+}
@@ -1,19 +1,24 @@
 pub mod conditions;
 pub mod deterministic;
+pub mod lint;
 pub mod repositories;
+pub mod schedule;
 pub mod types;
 
 pub use conditions::{
     Condition, ConditionError, EvaluationContext, LeafCondition, LogicalCondition, LogicalOperator,
+    referenced_sender_lists, references_known_contact,
 };
 pub use deterministic::{ExecutorError, RuleExecutor, RuleLoader, RuleLoaderError, RuleMatch};
+pub use lint::{KnownLabels, LintError, LintKind, LintWarning, RuleLinter, lint_rules};
 pub use repositories::{
     DeterministicRuleError, DeterministicRuleRepository, DirectionError, DirectionsRepository,
     LlmRuleError, LlmRuleRepository, RulesChatMessageError, RulesChatMessageRepository,
-    RulesChatSessionError, RulesChatSessionRepository,
+    RulesChatSessionError, RulesChatSessionRepository, SenderListError, SenderListRepository,
 };
+pub use schedule::{RuleSchedule, ScheduleDay, ScheduleError};
 pub use types::{
     DeterministicRule, Direction, LlmRule, NewDeterministicRule, NewDirection, NewLlmRule,
-    NewRulesChatMessage, NewRulesChatSession, RuleScope, RulesChatMessage, RulesChatRole,
-    RulesChatSession, SafeMode,
+    NewRulesChatMessage, NewRulesChatSession, NewSenderList, RuleScope, RulesChatMessage,
+    RulesChatRole, RulesChatSession, SafeMode, SenderList,
 };
@@ -6,15 +6,19 @@ use uuid::Uuid;
 
 use crate::db::{Database, DbError};
 
+use super::conditions::{parse_condition, referenced_sender_lists};
+use super::schedule::RuleSchedule;
 use super::types::{
     DeterministicRule, Direction, LlmRule, NewDeterministicRule, NewDirection, NewLlmRule,
-    NewRulesChatMessage, NewRulesChatSession, RuleScope, RulesChatMessage, RulesChatRole,
-    RulesChatSession, SafeMode,
+    NewRulesChatMessage, NewRulesChatSession, NewSenderList, RuleScope, RulesChatMessage,
+    RulesChatRole, RulesChatSession, SafeMode, SenderList,
 };
 
-const DETERMINISTIC_RULE_COLUMNS: &str = "id, name, description, scope, scope_ref, priority, enabled, disabled_reason, conditions_json, action_type, action_parameters_json, safe_mode, created_at, updated_at, org_id, user_id";
-const LLM_RULE_COLUMNS: &str = "id, name, description, scope, scope_ref, rule_text, enabled, metadata_json, created_at, updated_at, org_id, user_id";
+const DETERMINISTIC_RULE_COLUMNS: &str = "id, name, description, scope, scope_ref, priority, enabled, disabled_reason, conditions_json, action_type, action_parameters_json, safe_mode, created_at, updated_at, org_id, user_id, active_from, active_until, schedule_json";
+const LLM_RULE_COLUMNS: &str = "id, name, description, scope, scope_ref, rule_text, enabled, metadata_json, created_at, updated_at, org_id, user_id, disabled_reason, active_from, active_until, schedule_json";
 const DIRECTION_COLUMNS: &str = "id, content, enabled, created_at, updated_at, org_id, user_id";
+const SENDER_LIST_COLUMNS: &str =
+    "id, name, description, entries_json, created_at, updated_at, org_id, user_id";
 const RULES_CHAT_SESSION_COLUMNS: &str = "id, title, created_at, updated_at, org_id, user_id";
 const RULES_CHAT_MESSAGE_COLUMNS: &str =
     "id, session_id, role, content, created_at, org_id, user_id";
@@ -65,6 +69,22 @@ pub enum DirectionError {
     NotFound(String),
 }
 
+#[derive(Debug, Error)]
+pub enum SenderListError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("datetime parse error: {0}")]
+    DateTimeParse(#[from] chrono::ParseError),
+    #[error("sender list not found: {0}")]
+    NotFound(String),
+    #[error("sender list already exists: {0}")]
+    DuplicateName(String),
+}
+
 #[derive(Debug, Error)]
 pub enum RulesChatSessionError {
     #[error("database error: {0}")]
@@ -111,13 +131,14 @@ impl DeterministicRuleRepository {
         let action_parameters_json = serde_json::to_string(&new_rule.action_parameters_json)?;
         let enabled = new_rule.enabled as i64;
         let scope_ref = normalize_scope_ref(&new_rule.scope, &new_rule.scope_ref);
+        let schedule_json = schedule_to_json(new_rule.schedule.as_ref())?;
         let conn = self.db.connection().await?;
         let mut rows = conn
             .query(
                 &format!(
                     "INSERT INTO deterministic_rules (
-                        id, name, description, scope, scope_ref, priority, enabled, disabled_reason, conditions_json, action_type, action_parameters_json, safe_mode, created_at, updated_at, org_id, user_id
-                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?13, ?14, ?15)
+                        id, name, description, scope, scope_ref, priority, enabled, disabled_reason, conditions_json, action_type, action_parameters_json, safe_mode, created_at, updated_at, org_id, user_id, active_from, active_until, schedule_json
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?13, ?14, ?15, ?16, ?17, ?18)
                     RETURNING {DETERMINISTIC_RULE_COLUMNS}"
                 ),
                 params![
@@ -135,7 +156,10 @@ impl DeterministicRuleRepository {
                     new_rule.safe_mode.as_str(),
                     now,
                     new_rule.org_id,
-                    new_rule.user_id
+                    new_rule.user_id,
+                    new_rule.active_from.map(to_rfc3339),
+                    new_rule.active_until.map(to_rfc3339),
+                    schedule_json
                 ],
             )
             .await?;
@@ -270,6 +294,7 @@ impl DeterministicRuleRepository {
         let action_parameters_json = serde_json::to_string(&updated.action_parameters_json)?;
         let enabled = updated.enabled as i64;
         let scope_ref = normalize_scope_ref(&updated.scope, &updated.scope_ref);
+        let schedule_json = schedule_to_json(updated.schedule.as_ref())?;
         let conn = self.db.connection().await?;
         let mut rows = conn
             .query(
@@ -287,10 +312,13 @@ impl DeterministicRuleRepository {
                          action_parameters_json = ?10,
                          safe_mode = ?11,
                          user_id = ?12,
-                         updated_at = ?13
-                     WHERE id = ?14
-                       AND org_id = ?15
-                       AND (user_id IS NULL OR user_id = ?16)
+                         updated_at = ?13,
+                         active_from = ?14,
+                         active_until = ?15,
+                         schedule_json = ?16
+                     WHERE id = ?17
+                       AND org_id = ?18
+                       AND (user_id IS NULL OR user_id = ?19)
                      RETURNING {DETERMINISTIC_RULE_COLUMNS}"
                 ),
                 params![
@@ -307,6 +335,9 @@ impl DeterministicRuleRepository {
                     updated.safe_mode.as_str(),
                     updated.user_id,
                     now,
+                    updated.active_from.map(to_rfc3339),
+                    updated.active_until.map(to_rfc3339),
+                    schedule_json,
                     id,
                     org_id,
                     user_id
@@ -410,6 +441,44 @@ impl DeterministicRuleRepository {
         }
         Ok(rules)
     }
+
+    /// Find rules whose conditions reference a sender list by name (case-insensitive).
+    /// Rules with conditions that fail to parse are skipped.
+    pub async fn find_rules_referencing_sender_list(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        list_name: &str,
+    ) -> Result<Vec<DeterministicRule>, DeterministicRuleError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {DETERMINISTIC_RULE_COLUMNS}
+                     FROM deterministic_rules
+                     WHERE org_id = ?1
+                       AND (user_id IS NULL OR user_id = ?2)
+                       AND conditions_json LIKE '%\"in_list\"%'
+                     ORDER BY priority ASC, created_at"
+                ),
+                params![org_id, user_id],
+            )
+            .await?;
+
+        let mut rules = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let rule = row_to_deterministic_rule(row)?;
+            let references = parse_condition(&rule.conditions_json).is_ok_and(|condition| {
+                referenced_sender_lists(&condition)
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(list_name))
+            });
+            if references {
+                rules.push(rule);
+            }
+        }
+        Ok(rules)
+    }
 }
 
 fn normalize_scope_ref(scope: &RuleScope, scope_ref: &Option<String>) -> Option<String> {
@@ -436,14 +505,15 @@ impl LlmRuleRepository {
         let now = now_rfc3339();
         let metadata_json = serde_json::to_string(&new_rule.metadata_json)?;
         let enabled = new_rule.enabled as i64;
+        let schedule_json = schedule_to_json(new_rule.schedule.as_ref())?;
 
         let conn = self.db.connection().await?;
         let mut rows = conn
             .query(
                 &format!(
                     "INSERT INTO llm_rules (
-                        id, name, description, scope, scope_ref, rule_text, enabled, metadata_json, created_at, updated_at, org_id, user_id
-                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9, ?10, ?11)
+                        id, name, description, scope, scope_ref, rule_text, enabled, metadata_json, created_at, updated_at, org_id, user_id, disabled_reason, active_from, active_until, schedule_json
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
                     RETURNING {LLM_RULE_COLUMNS}"
                 ),
                 params![
@@ -457,7 +527,11 @@ impl LlmRuleRepository {
                     metadata_json,
                     now,
                     new_rule.org_id,
-                    new_rule.user_id
+                    new_rule.user_id,
+                    new_rule.disabled_reason,
+                    new_rule.active_from.map(to_rfc3339),
+                    new_rule.active_until.map(to_rfc3339),
+                    schedule_json
                 ],
             )
             .await?;
@@ -574,6 +648,7 @@ impl LlmRuleRepository {
         let now = now_rfc3339();
         let metadata_json = serde_json::to_string(&updated.metadata_json)?;
         let enabled = updated.enabled as i64;
+        let schedule_json = schedule_to_json(updated.schedule.as_ref())?;
 
         let conn = self.db.connection().await?;
         let mut rows = conn
@@ -588,10 +663,14 @@ impl LlmRuleRepository {
                          enabled = ?6,
                          metadata_json = ?7,
                          user_id = ?8,
-                         updated_at = ?9
-                     WHERE id = ?10
-                       AND org_id = ?11
-                       AND (user_id IS NULL OR user_id = ?12)
+                         updated_at = ?9,
+                         disabled_reason = ?10,
+                         active_from = ?11,
+                         active_until = ?12,
+                         schedule_json = ?13
+                     WHERE id = ?14
+                       AND org_id = ?15
+                       AND (user_id IS NULL OR user_id = ?16)
                      RETURNING {LLM_RULE_COLUMNS}"
                 ),
                 params![
@@ -604,6 +683,10 @@ impl LlmRuleRepository {
                     metadata_json,
                     updated.user_id,
                     now,
+                    updated.disabled_reason,
+                    updated.active_from.map(to_rfc3339),
+                    updated.active_until.map(to_rfc3339),
+                    schedule_json,
                     id,
                     org_id,
                     user_id
@@ -617,6 +700,38 @@ impl LlmRuleRepository {
         }
     }
 
+    /// Disable a rule and set a reason explaining why it was disabled.
+    pub async fn disable_rule_with_reason(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+        reason: &str,
+    ) -> Result<LlmRule, LlmRuleError> {
+        let now = now_rfc3339();
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "UPDATE llm_rules
+                     SET enabled = 0,
+                         disabled_reason = ?1,
+                         updated_at = ?2
+                     WHERE id = ?3
+                       AND org_id = ?4
+                       AND (user_id IS NULL OR user_id = ?5)
+                     RETURNING {LLM_RULE_COLUMNS}"
+                ),
+                params![reason, now, id, org_id, user_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_llm_rule(row),
+            None => Err(LlmRuleError::NotFound(id.to_string())),
+        }
+    }
+
     pub async fn delete(&self, org_id: i64, user_id: i64, id: &str) -> Result<(), LlmRuleError> {
         let conn = self.db.connection().await?;
         let mut rows = conn
@@ -808,6 +923,223 @@ impl DirectionsRepository {
     }
 }
 
+#[derive(Clone)]
+pub struct SenderListRepository {
+    db: Database,
+}
+
+impl SenderListRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, new_list: NewSenderList) -> Result<SenderList, SenderListError> {
+        let id = Uuid::new_v4().to_string();
+        let now = now_rfc3339();
+        let entries_json =
+            serde_json::to_string(&normalize_sender_list_entries(&new_list.entries))?;
+
+        let conn = self.db.connection().await?;
+        let result = conn
+            .query(
+                &format!(
+                    "INSERT INTO sender_lists (id, name, description, entries_json, created_at, updated_at, org_id, user_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6, ?7)
+                     RETURNING {SENDER_LIST_COLUMNS}"
+                ),
+                params![
+                    id,
+                    new_list.name.trim(),
+                    new_list.description,
+                    entries_json,
+                    now,
+                    new_list.org_id,
+                    new_list.user_id
+                ],
+            )
+            .await;
+
+        let mut rows = result.map_err(|err| sender_list_write_error(err, &new_list.name))?;
+
+        match rows
+            .next()
+            .await
+            .map_err(|err| sender_list_write_error(err, &new_list.name))?
+        {
+            Some(row) => row_to_sender_list(row),
+            None => Err(SenderListError::NotFound("insert failed".into())),
+        }
+    }
+
+    pub async fn get_by_id(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+    ) -> Result<SenderList, SenderListError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {SENDER_LIST_COLUMNS}
+                     FROM sender_lists
+                     WHERE id = ?1
+                       AND org_id = ?2
+                       AND (user_id IS NULL OR user_id = ?3)"
+                ),
+                params![id, org_id, user_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_sender_list(row),
+            None => Err(SenderListError::NotFound(id.to_string())),
+        }
+    }
+
+    /// Lookup a list by name, ignoring case.
+    pub async fn get_by_name(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        name: &str,
+    ) -> Result<SenderList, SenderListError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {SENDER_LIST_COLUMNS}
+                     FROM sender_lists
+                     WHERE LOWER(name) = LOWER(?1)
+                       AND org_id = ?2
+                       AND (user_id IS NULL OR user_id = ?3)
+                     LIMIT 1"
+                ),
+                params![name, org_id, user_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_sender_list(row),
+            None => Err(SenderListError::NotFound(name.to_string())),
+        }
+    }
+
+    pub async fn list_all(
+        &self,
+        org_id: i64,
+        user_id: i64,
+    ) -> Result<Vec<SenderList>, SenderListError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {SENDER_LIST_COLUMNS}
+                     FROM sender_lists
+                     WHERE org_id = ?1 AND (user_id IS NULL OR user_id = ?2)
+                     ORDER BY name"
+                ),
+                params![org_id, user_id],
+            )
+            .await?;
+
+        let mut lists = Vec::new();
+        while let Some(row) = rows.next().await? {
+            lists.push(row_to_sender_list(row)?);
+        }
+        Ok(lists)
+    }
+
+    pub async fn update(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        id: &str,
+        updated: NewSenderList,
+    ) -> Result<SenderList, SenderListError> {
+        let now = now_rfc3339();
+        let entries_json = serde_json::to_string(&normalize_sender_list_entries(&updated.entries))?;
+        let conn = self.db.connection().await?;
+        let result = conn
+            .query(
+                &format!(
+                    "UPDATE sender_lists
+                     SET name = ?1,
+                         description = ?2,
+                         entries_json = ?3,
+                         updated_at = ?4
+                     WHERE id = ?5
+                       AND org_id = ?6
+                       AND (user_id IS NULL OR user_id = ?7)
+                     RETURNING {SENDER_LIST_COLUMNS}"
+                ),
+                params![
+                    updated.name.trim(),
+                    updated.description,
+                    entries_json,
+                    now,
+                    id,
+                    org_id,
+                    user_id
+                ],
+            )
+            .await;
+
+        let mut rows = result.map_err(|err| sender_list_write_error(err, &updated.name))?;
+
+        match rows
+            .next()
+            .await
+            .map_err(|err| sender_list_write_error(err, &updated.name))?
+        {
+            Some(row) => row_to_sender_list(row),
+            None => Err(SenderListError::NotFound(id.to_string())),
+        }
+    }
+
+    pub async fn delete(&self, org_id: i64, user_id: i64, id: &str) -> Result<(), SenderListError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "DELETE FROM sender_lists WHERE id = ?1 AND org_id = ?2 AND (user_id IS NULL OR user_id = ?3) RETURNING id",
+                params![id, org_id, user_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(_) => Ok(()),
+            None => Err(SenderListError::NotFound(id.to_string())),
+        }
+    }
+}
+
+/// Trim and lowercase entries, dropping blanks and duplicates while keeping order.
+fn normalize_sender_list_entries(entries: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for entry in entries {
+        let entry = entry.trim().to_lowercase();
+        if !entry.is_empty() && !normalized.contains(&entry) {
+            normalized.push(entry);
+        }
+    }
+    normalized
+}
+
+/// Map a write error, turning unique name violations into `DuplicateName`.
+fn sender_list_write_error(err: libsql::Error, name: &str) -> SenderListError {
+    if is_unique_violation(&err) {
+        SenderListError::DuplicateName(name.trim().to_string())
+    } else {
+        SenderListError::Sql(err)
+    }
+}
+
+fn is_unique_violation(err: &libsql::Error) -> bool {
+    err.to_string()
+        .to_ascii_lowercase()
+        .contains("unique constraint failed")
+}
+
 #[derive(Clone)]
 pub struct RulesChatSessionRepository {
     db: Database,
@@ -1016,6 +1348,22 @@ fn now_rfc3339() -> String {
     Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
 }
 
+fn to_rfc3339(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_optional_datetime(
+    value: Option<String>,
+) -> Result<Option<DateTime<Utc>>, chrono::ParseError> {
+    value
+        .map(|v| DateTime::parse_from_rfc3339(&v).map(|dt| dt.with_timezone(&Utc)))
+        .transpose()
+}
+
+fn schedule_to_json(schedule: Option<&RuleSchedule>) -> Result<Option<String>, serde_json::Error> {
+    schedule.map(serde_json::to_string).transpose()
+}
+
 fn row_to_deterministic_rule(row: Row) -> Result<DeterministicRule, DeterministicRuleError> {
     let scope: String = row.get(3)?;
     let enabled: i64 = row.get(6)?;
@@ -1027,6 +1375,9 @@ fn row_to_deterministic_rule(row: Row) -> Result<DeterministicRule, Deterministi
     let updated_at: String = row.get(13)?;
     let org_id: i64 = row.get(14)?;
     let user_id: Option<i64> = row.get(15)?;
+    let active_from: Option<String> = row.get(16)?;
+    let active_until: Option<String> = row.get(17)?;
+    let schedule_json: Option<String> = row.get(18)?;
 
     let scope = RuleScope::from_str(&scope)
         .ok_or_else(|| DeterministicRuleError::InvalidScope(scope.clone()))?;
@@ -1046,6 +1397,11 @@ fn row_to_deterministic_rule(row: Row) -> Result<DeterministicRule, Deterministi
         action_type: row.get(9)?,
         action_parameters_json: serde_json::from_str(&action_parameters_json)?,
         safe_mode,
+        active_from: parse_optional_datetime(active_from)?,
+        active_until: parse_optional_datetime(active_until)?,
+        schedule: schedule_json
+            .map(|s| serde_json::from_str(&s))
+            .transpose()?,
         created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
         updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
         org_id,
@@ -1061,6 +1417,10 @@ fn row_to_llm_rule(row: Row) -> Result<LlmRule, LlmRuleError> {
     let updated_at: String = row.get(9)?;
     let org_id: i64 = row.get(10)?;
     let user_id: Option<i64> = row.get(11)?;
+    let disabled_reason: Option<String> = row.get(12)?;
+    let active_from: Option<String> = row.get(13)?;
+    let active_until: Option<String> = row.get(14)?;
+    let schedule_json: Option<String> = row.get(15)?;
 
     let scope =
         RuleScope::from_str(&scope).ok_or_else(|| LlmRuleError::InvalidScope(scope.clone()))?;
@@ -1073,7 +1433,13 @@ fn row_to_llm_rule(row: Row) -> Result<LlmRule, LlmRuleError> {
         scope_ref: row.get(4)?,
         rule_text: row.get(5)?,
         enabled: enabled != 0,
+        disabled_reason,
         metadata_json: serde_json::from_str(&metadata_json)?,
+        active_from: parse_optional_datetime(active_from)?,
+        active_until: parse_optional_datetime(active_until)?,
+        schedule: schedule_json
+            .map(|s| serde_json::from_str(&s))
+            .transpose()?,
         created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
         updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
         org_id,
@@ -1099,6 +1465,25 @@ fn row_to_direction(row: Row) -> Result<Direction, DirectionError> {
     })
 }
 
+fn row_to_sender_list(row: Row) -> Result<SenderList, SenderListError> {
+    let entries_json: String = row.get(3)?;
+    let created_at: String = row.get(4)?;
+    let updated_at: String = row.get(5)?;
+    let org_id: i64 = row.get(6)?;
+    let user_id: Option<i64> = row.get(7)?;
+
+    Ok(SenderList {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        entries: serde_json::from_str(&entries_json)?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+        org_id,
+        user_id,
+    })
+}
+
 fn row_to_rules_chat_session(row: Row) -> Result<RulesChatSession, RulesChatSessionError> {
     let created_at: String = row.get(2)?;
     let updated_at: String = row.get(3)?;
@@ -1167,6 +1552,9 @@ mod tests {
             action_type: "flag".into(),
             action_parameters_json: serde_json::json!({"level": "high"}),
             safe_mode: SafeMode::Default,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         }
     }
 
@@ -1181,6 +1569,10 @@ mod tests {
             rule_text: "Always be concise.".into(),
             enabled: true,
             metadata_json: serde_json::json!({"kind": "concise"}),
+            disabled_reason: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         }
     }
 
@@ -1527,6 +1919,55 @@ mod tests {
         assert!(matches!(err, LlmRuleError::NotFound(_)));
     }
 
+    #[tokio::test]
+    async fn rule_active_windows_roundtrip_and_llm_rules_can_be_disabled() {
+        use crate::rules::schedule::{RuleSchedule, ScheduleDay};
+        use chrono::TimeZone;
+
+        let (db, _dir) = setup_db().await;
+        let schedule = RuleSchedule {
+            days: vec![ScheduleDay::Mon, ScheduleDay::Fri],
+            start: "09:00".into(),
+            end: "17:00".into(),
+            timezone: "Europe/Berlin".into(),
+        };
+        let from = Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap();
+        let until = Utc.with_ymd_and_hms(2024, 7, 15, 0, 0, 0).unwrap();
+
+        let deterministic = DeterministicRuleRepository::new(db.clone());
+        let mut new_rule = sample_new_det_rule(RuleScope::Global, None);
+        new_rule.active_from = Some(from);
+        new_rule.active_until = Some(until);
+        new_rule.schedule = Some(schedule.clone());
+        let created = deterministic.create(new_rule).await.expect("create");
+        assert_eq!(created.active_from, Some(from));
+        assert_eq!(created.active_until, Some(until));
+        assert_eq!(created.schedule.as_ref(), Some(&schedule));
+
+        let llm = LlmRuleRepository::new(db);
+        let mut new_llm_rule = sample_new_llm_rule(RuleScope::Global, None);
+        new_llm_rule.schedule = Some(schedule.clone());
+        let created = llm.create(new_llm_rule.clone()).await.expect("create llm");
+        assert_eq!(created.schedule, Some(schedule));
+        assert_eq!(created.active_from, None);
+
+        new_llm_rule.schedule = None;
+        new_llm_rule.active_until = Some(until);
+        let updated = llm
+            .update(DEFAULT_ORG_ID, DEFAULT_USER_ID, &created.id, new_llm_rule)
+            .await
+            .expect("update llm");
+        assert_eq!(updated.schedule, None);
+        assert_eq!(updated.active_until, Some(until));
+
+        let disabled = llm
+            .disable_rule_with_reason(DEFAULT_ORG_ID, DEFAULT_USER_ID, &created.id, "expired")
+            .await
+            .expect("disable llm");
+        assert!(!disabled.enabled);
+        assert_eq!(disabled.disabled_reason.as_deref(), Some("expired"));
+    }
+
     #[tokio::test]
     async fn llm_rule_list_all_returns_all_rules() {
         let (db, _dir) = setup_db().await;
@@ -2702,4 +3143,108 @@ mod tests {
         assert_eq!(found.len(), 1);
         assert_eq!(found[0].id, created.id);
     }
+
+    fn sample_new_sender_list(name: &str, entries: &[&str]) -> NewSenderList {
+        NewSenderList {
+            org_id: DEFAULT_ORG_ID,
+            user_id: Some(DEFAULT_USER_ID),
+            name: name.into(),
+            description: Some("Important people".into()),
+            entries: entries.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn sender_list_crud_roundtrip() {
+        let (db, _dir) = setup_db().await;
+        let repo = SenderListRepository::new(db);
+
+        let created = repo
+            .create(sample_new_sender_list(
+                " vips ",
+                &[" Boss@Corp.com", "partner.com", "boss@corp.com", ""],
+            ))
+            .await
+            .expect("create list");
+        assert_eq!(created.name, "vips");
+        assert_eq!(created.entries, vec!["boss@corp.com", "partner.com"]);
+
+        let by_name = repo
+            .get_by_name(DEFAULT_ORG_ID, DEFAULT_USER_ID, "VIPS")
+            .await
+            .expect("get by name");
+        assert_eq!(by_name.id, created.id);
+
+        let updated = repo
+            .update(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &created.id,
+                sample_new_sender_list("vips", &["*@corp.com"]),
+            )
+            .await
+            .expect("update list");
+        assert_eq!(updated.entries, vec!["*@corp.com"]);
+
+        let all = repo
+            .list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+            .await
+            .expect("list all");
+        assert_eq!(all.len(), 1);
+
+        repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &created.id)
+            .await
+            .expect("delete list");
+        let err = repo
+            .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &created.id)
+            .await
+            .expect_err("deleted");
+        assert!(matches!(err, SenderListError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn sender_list_names_are_unique_ignoring_case() {
+        let (db, _dir) = setup_db().await;
+        let repo = SenderListRepository::new(db);
+
+        repo.create(sample_new_sender_list("vips", &[]))
+            .await
+            .expect("create list");
+        let err = repo
+            .create(sample_new_sender_list("VIPs", &[]))
+            .await
+            .expect_err("duplicate name");
+        assert!(matches!(err, SenderListError::DuplicateName(_)));
+    }
+
+    #[tokio::test]
+    async fn find_rules_referencing_sender_list_matches_by_name() {
+        let (db, _dir) = setup_db().await;
+        let repo = DeterministicRuleRepository::new(db);
+
+        let mut referencing = sample_new_det_rule(RuleScope::Global, None);
+        referencing.conditions_json = serde_json::json!({
+            "op": "or",
+            "children": [
+                {"type": "in_list", "list": "VIPs"},
+                {"type": "subject_contains", "value": "urgent"}
+            ]
+        });
+        let created = repo.create(referencing).await.expect("create rule");
+
+        let mut other_list = sample_new_det_rule(RuleScope::Global, None);
+        other_list.conditions_json = serde_json::json!({"type": "in_list", "list": "vips2"});
+        repo.create(other_list).await.expect("create other rule");
+
+        repo.create(sample_new_det_rule(RuleScope::Global, None))
+            .await
+            .expect("create unrelated rule");
+
+        let found = repo
+            .find_rules_referencing_sender_list(DEFAULT_ORG_ID, DEFAULT_USER_ID, "vips")
+            .await
+            .expect("find rules");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, created.id);
+    }
 }
@@ -0,0 +1,392 @@
+use chrono::{DateTime, Datelike, NaiveTime, SecondsFormat, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+use ts_rs::TS;
+
+const TIME_FORMAT: &str = "%H:%M";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ScheduleDay {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl From<Weekday> for ScheduleDay {
+    fn from(day: Weekday) -> Self {
+        match day {
+            Weekday::Mon => Self::Mon,
+            Weekday::Tue => Self::Tue,
+            Weekday::Wed => Self::Wed,
+            Weekday::Thu => Self::Thu,
+            Weekday::Fri => Self::Fri,
+            Weekday::Sat => Self::Sat,
+            Weekday::Sun => Self::Sun,
+        }
+    }
+}
+
+/// A recurring weekly window during which a rule is active, e.g. weekdays 09:00–17:00.
+///
+/// `start` and `end` are `HH:MM` wall-clock times in `timezone` (an IANA name). When `end`
+/// is earlier than `start` the window runs overnight and belongs to the day it starts on;
+/// equal times cover the whole day. An empty `days` list means every day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RuleSchedule {
+    #[serde(default)]
+    pub days: Vec<ScheduleDay>,
+    pub start: String,
+    pub end: String,
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ScheduleError {
+    #[error("invalid time '{0}', expected HH:MM")]
+    InvalidTime(String),
+    #[error("unknown timezone '{0}'")]
+    UnknownTimezone(String),
+    #[error("active_until must be later than active_from")]
+    EmptyWindow,
+}
+
+impl RuleSchedule {
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        self.parsed().map(|_| ())
+    }
+
+    /// Whether `now` falls inside the schedule.
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> Result<bool, ScheduleError> {
+        let (start, end, tz) = self.parsed()?;
+        let local = now.with_timezone(&tz);
+        let time = local.time();
+        let today = local.weekday();
+
+        Ok(if start == end {
+            self.includes(today)
+        } else if start < end {
+            self.includes(today) && time >= start && time < end
+        } else {
+            (self.includes(today) && time >= start) || (self.includes(today.pred()) && time < end)
+        })
+    }
+
+    fn includes(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day.into())
+    }
+
+    fn parsed(&self) -> Result<(NaiveTime, NaiveTime, Tz), ScheduleError> {
+        let start = parse_time(&self.start)?;
+        let end = parse_time(&self.end)?;
+        let tz = self
+            .timezone
+            .parse::<Tz>()
+            .map_err(|_| ScheduleError::UnknownTimezone(self.timezone.clone()))?;
+        Ok((start, end, tz))
+    }
+}
+
+pub(crate) fn parse_time(value: &str) -> Result<NaiveTime, ScheduleError> {
+    NaiveTime::parse_from_str(value.trim(), TIME_FORMAT)
+        .map_err(|_| ScheduleError::InvalidTime(value.to_string()))
+}
+
+/// Validate a rule's active window before it is saved.
+pub fn validate_window(
+    active_from: Option<DateTime<Utc>>,
+    active_until: Option<DateTime<Utc>>,
+    schedule: Option<&RuleSchedule>,
+) -> Result<(), ScheduleError> {
+    if let (Some(from), Some(until)) = (active_from, active_until)
+        && until <= from
+    {
+        return Err(ScheduleError::EmptyWindow);
+    }
+    match schedule {
+        Some(schedule) => schedule.validate(),
+        None => Ok(()),
+    }
+}
+
+/// Whether a rule with the given window is active at `now`.
+///
+/// `active_from` is inclusive and `active_until` exclusive. A schedule that fails to parse
+/// never matches, so a broken schedule cannot make a rule fire around the clock.
+pub fn is_active_at(
+    active_from: Option<DateTime<Utc>>,
+    active_until: Option<DateTime<Utc>>,
+    schedule: Option<&RuleSchedule>,
+    now: DateTime<Utc>,
+) -> bool {
+    if active_from.is_some_and(|from| now < from) || is_expired_at(active_until, now) {
+        return false;
+    }
+    match schedule {
+        Some(schedule) => schedule.is_active_at(now).unwrap_or(false),
+        None => true,
+    }
+}
+
+/// Whether a rule's window has ended for good at `now`.
+pub fn is_expired_at(active_until: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    active_until.is_some_and(|until| now >= until)
+}
+
+/// The `disabled_reason` recorded when a rule is auto-disabled after its window ends.
+pub fn expired_reason(active_until: DateTime<Utc>) -> String {
+    format!(
+        "Active window ended at {}",
+        active_until.to_rfc3339_opts(SecondsFormat::Secs, true)
+    )
+}
+
+/// A rule that carries an active window, so both rule kinds share the window checks.
+pub trait ActiveWindow {
+    fn rule_id(&self) -> &str;
+    fn active_from(&self) -> Option<DateTime<Utc>>;
+    fn active_until(&self) -> Option<DateTime<Utc>>;
+    fn schedule(&self) -> Option<&RuleSchedule>;
+
+    fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        is_active_at(
+            self.active_from(),
+            self.active_until(),
+            self.schedule(),
+            now,
+        )
+    }
+
+    fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        is_expired_at(self.active_until(), now)
+    }
+}
+
+/// Keep the rules that are active at `now`.
+///
+/// Rules whose window has ended are dropped and passed to `disable` along with the
+/// `disabled_reason` to record. A failed disable is only logged; the rule is still dropped and
+/// will be retried on the next load.
+pub async fn retain_active<R, F, Fut, E>(
+    rules: Vec<R>,
+    now: DateTime<Utc>,
+    mut disable: F,
+) -> Vec<R>
+where
+    R: ActiveWindow,
+    F: FnMut(String, String) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+    E: std::fmt::Display,
+{
+    let mut active = Vec::with_capacity(rules.len());
+    for rule in rules {
+        if let Some(until) = rule.active_until()
+            && rule.is_expired_at(now)
+        {
+            if let Err(err) = disable(rule.rule_id().to_string(), expired_reason(until)).await {
+                warn!(rule_id = %rule.rule_id(), error = %err, "failed to disable expired rule");
+            }
+            continue;
+        }
+        if rule.is_active_at(now) {
+            active.push(rule);
+        }
+    }
+    active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn schedule(days: &[ScheduleDay], start: &str, end: &str, timezone: &str) -> RuleSchedule {
+        RuleSchedule {
+            days: days.to_vec(),
+            start: start.to_string(),
+            end: end.to_string(),
+            timezone: timezone.to_string(),
+        }
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    const WEEKDAYS: &[ScheduleDay] = &[
+        ScheduleDay::Mon,
+        ScheduleDay::Tue,
+        ScheduleDay::Wed,
+        ScheduleDay::Thu,
+        ScheduleDay::Fri,
+    ];
+
+    #[test]
+    fn weekday_business_hours() {
+        let hours = schedule(WEEKDAYS, "09:00", "17:00", "UTC");
+        // 2024-06-03 is a Monday.
+        assert!(hours.is_active_at(utc(2024, 6, 3, 9, 0)).unwrap());
+        assert!(hours.is_active_at(utc(2024, 6, 3, 16, 59)).unwrap());
+        assert!(!hours.is_active_at(utc(2024, 6, 3, 17, 0)).unwrap());
+        assert!(!hours.is_active_at(utc(2024, 6, 3, 8, 59)).unwrap());
+        assert!(!hours.is_active_at(utc(2024, 6, 8, 12, 0)).unwrap());
+    }
+
+    #[test]
+    fn schedule_uses_configured_timezone() {
+        let hours = schedule(WEEKDAYS, "09:00", "17:00", "America/New_York");
+        // 13:00 UTC is 09:00 EDT; 21:30 UTC is 17:30 EDT.
+        assert!(hours.is_active_at(utc(2024, 6, 3, 13, 0)).unwrap());
+        assert!(!hours.is_active_at(utc(2024, 6, 3, 21, 30)).unwrap());
+        // Friday 23:00 EDT is already Saturday in UTC.
+        assert!(!hours.is_active_at(utc(2024, 6, 8, 3, 0)).unwrap());
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_the_day_it_starts() {
+        let nights = schedule(&[ScheduleDay::Fri], "22:00", "06:00", "UTC");
+        // 2024-06-07 is a Friday.
+        assert!(nights.is_active_at(utc(2024, 6, 7, 23, 0)).unwrap());
+        assert!(nights.is_active_at(utc(2024, 6, 8, 5, 59)).unwrap());
+        assert!(!nights.is_active_at(utc(2024, 6, 8, 6, 0)).unwrap());
+        assert!(!nights.is_active_at(utc(2024, 6, 7, 5, 0)).unwrap());
+    }
+
+    #[test]
+    fn equal_times_cover_the_whole_day_and_empty_days_mean_every_day() {
+        let weekends = schedule(
+            &[ScheduleDay::Sat, ScheduleDay::Sun],
+            "00:00",
+            "00:00",
+            "UTC",
+        );
+        assert!(weekends.is_active_at(utc(2024, 6, 9, 23, 59)).unwrap());
+        assert!(!weekends.is_active_at(utc(2024, 6, 10, 0, 0)).unwrap());
+
+        let daily = schedule(&[], "12:00", "13:00", "UTC");
+        assert!(daily.is_active_at(utc(2024, 6, 8, 12, 30)).unwrap());
+    }
+
+    #[test]
+    fn invalid_schedules_are_rejected() {
+        assert_eq!(
+            schedule(&[], "9am", "17:00", "UTC").validate(),
+            Err(ScheduleError::InvalidTime("9am".into()))
+        );
+        assert_eq!(
+            schedule(&[], "09:00", "17:00", "Mars/Olympus").validate(),
+            Err(ScheduleError::UnknownTimezone("Mars/Olympus".into()))
+        );
+        assert!(!is_active_at(
+            None,
+            None,
+            Some(&schedule(&[], "09:00", "17:00", "Mars/Olympus")),
+            utc(2024, 6, 3, 12, 0)
+        ));
+    }
+
+    #[test]
+    fn active_window_bounds() {
+        let from = utc(2024, 7, 1, 0, 0);
+        let until = utc(2024, 7, 15, 0, 0);
+        assert!(!is_active_at(
+            Some(from),
+            Some(until),
+            None,
+            utc(2024, 6, 30, 23, 59)
+        ));
+        assert!(is_active_at(Some(from), Some(until), None, from));
+        assert!(!is_active_at(Some(from), Some(until), None, until));
+        assert!(is_expired_at(Some(until), until));
+        assert!(!is_expired_at(None, until));
+
+        assert_eq!(
+            validate_window(Some(until), Some(from), None),
+            Err(ScheduleError::EmptyWindow)
+        );
+        assert_eq!(validate_window(Some(from), Some(until), None), Ok(()));
+    }
+
+    #[test]
+    fn schedule_deserializes_with_default_timezone() {
+        let parsed: RuleSchedule =
+            serde_json::from_str(r#"{"days": ["mon", "fri"], "start": "09:00", "end": "17:00"}"#)
+                .unwrap();
+        assert_eq!(parsed.timezone, "UTC");
+        assert_eq!(parsed.days, vec![ScheduleDay::Mon, ScheduleDay::Fri]);
+    }
+
+    struct WindowedRule {
+        id: &'static str,
+        active_from: Option<DateTime<Utc>>,
+        active_until: Option<DateTime<Utc>>,
+    }
+
+    impl ActiveWindow for WindowedRule {
+        fn rule_id(&self) -> &str {
+            self.id
+        }
+
+        fn active_from(&self) -> Option<DateTime<Utc>> {
+            self.active_from
+        }
+
+        fn active_until(&self) -> Option<DateTime<Utc>> {
+            self.active_until
+        }
+
+        fn schedule(&self) -> Option<&RuleSchedule> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn retain_active_disables_expired_rules_and_skips_inactive_ones() {
+        let now = utc(2024, 6, 15, 12, 0);
+        let ended = utc(2024, 6, 1, 0, 0);
+        let rules = vec![
+            WindowedRule {
+                id: "open",
+                active_from: None,
+                active_until: None,
+            },
+            WindowedRule {
+                id: "expired",
+                active_from: None,
+                active_until: Some(ended),
+            },
+            WindowedRule {
+                id: "upcoming",
+                active_from: Some(utc(2024, 7, 1, 0, 0)),
+                active_until: None,
+            },
+        ];
+
+        let mut disabled = Vec::new();
+        let active = retain_active(rules, now, |id, reason| {
+            disabled.push((id, reason));
+            async { Ok::<(), String>(()) }
+        })
+        .await;
+
+        let ids: Vec<_> = active.iter().map(|rule| rule.id).collect();
+        assert_eq!(ids, vec!["open"]);
+        assert_eq!(
+            disabled,
+            vec![("expired".to_string(), expired_reason(ended))]
+        );
+    }
+}
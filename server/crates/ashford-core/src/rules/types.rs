@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use ts_rs::TS;
 
+use super::schedule::{ActiveWindow, RuleSchedule};
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 #[ts(export)]
@@ -111,10 +113,34 @@ pub struct DeterministicRule {
     #[ts(type = "Record<string, unknown>")]
     pub action_parameters_json: Value,
     pub safe_mode: SafeMode,
+    /// The rule only applies from this instant (inclusive).
+    pub active_from: Option<DateTime<Utc>>,
+    /// The rule stops applying at this instant and is then auto-disabled.
+    pub active_until: Option<DateTime<Utc>>,
+    /// Recurring weekly window, checked in addition to active_from/active_until.
+    pub schedule: Option<RuleSchedule>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl ActiveWindow for DeterministicRule {
+    fn rule_id(&self) -> &str {
+        &self.id
+    }
+
+    fn active_from(&self) -> Option<DateTime<Utc>> {
+        self.active_from
+    }
+
+    fn active_until(&self) -> Option<DateTime<Utc>> {
+        self.active_until
+    }
+
+    fn schedule(&self) -> Option<&RuleSchedule> {
+        self.schedule.as_ref()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NewDeterministicRule {
     pub org_id: i64,
@@ -130,6 +156,9 @@ pub struct NewDeterministicRule {
     pub action_type: String,
     pub action_parameters_json: Value,
     pub safe_mode: SafeMode,
+    pub active_from: Option<DateTime<Utc>>,
+    pub active_until: Option<DateTime<Utc>>,
+    pub schedule: Option<RuleSchedule>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
@@ -146,12 +175,37 @@ pub struct LlmRule {
     pub scope_ref: Option<String>,
     pub rule_text: String,
     pub enabled: bool,
+    pub disabled_reason: Option<String>,
     #[ts(type = "Record<string, unknown>")]
     pub metadata_json: Value,
+    /// The rule only applies from this instant (inclusive).
+    pub active_from: Option<DateTime<Utc>>,
+    /// The rule stops applying at this instant and is then auto-disabled.
+    pub active_until: Option<DateTime<Utc>>,
+    /// Recurring weekly window, checked in addition to active_from/active_until.
+    pub schedule: Option<RuleSchedule>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl ActiveWindow for LlmRule {
+    fn rule_id(&self) -> &str {
+        &self.id
+    }
+
+    fn active_from(&self) -> Option<DateTime<Utc>> {
+        self.active_from
+    }
+
+    fn active_until(&self) -> Option<DateTime<Utc>> {
+        self.active_until
+    }
+
+    fn schedule(&self) -> Option<&RuleSchedule> {
+        self.schedule.as_ref()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NewLlmRule {
     pub org_id: i64,
@@ -162,7 +216,39 @@ pub struct NewLlmRule {
     pub scope_ref: Option<String>,
     pub rule_text: String,
     pub enabled: bool,
+    pub disabled_reason: Option<String>,
     pub metadata_json: Value,
+    pub active_from: Option<DateTime<Utc>>,
+    pub active_until: Option<DateTime<Utc>>,
+    pub schedule: Option<RuleSchedule>,
+}
+
+/// A named list of senders that conditions can reference with `in_list`.
+///
+/// Entries containing `@` match like `sender_email` (including `*@domain` wildcards);
+/// anything else is treated as a domain and matches like `sender_domain`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SenderList {
+    pub id: String,
+    #[ts(type = "number")]
+    pub org_id: i64,
+    #[ts(type = "number | null")]
+    pub user_id: Option<i64>,
+    pub name: String,
+    pub description: Option<String>,
+    pub entries: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewSenderList {
+    pub org_id: i64,
+    pub user_id: Option<i64>,
+    pub name: String,
+    pub description: Option<String>,
+    pub entries: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
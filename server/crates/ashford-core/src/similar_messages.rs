@@ -0,0 +1,486 @@
+//! Embedding index of classified messages.
+//!
+//! Each message that reaches the LLM is embedded and stored. Later classifications look up the
+//! most similar earlier messages of the account and show the model what was decided for them
+//! and how that turned out, as precedent beyond the current thread.
+
+use chrono::{SecondsFormat, Utc};
+use libsql::params;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::db::{Database, DbError};
+use crate::decisions::ActionStatus;
+use crate::feedback::FeedbackSource;
+use crate::llm::embedding::cosine_similarity;
+use crate::llm::prompt::{get_body_text, truncate_text};
+use crate::messages::Message;
+
+/// Body characters included in the embedded text.
+const EMBEDDING_BODY_LENGTH: usize = 2_000;
+
+#[derive(Debug, Error)]
+pub enum SimilarMessagesError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("sql error: {0}")]
+    Sql(#[from] libsql::Error),
+}
+
+/// An earlier message similar to the one being classified, and what happened to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimilarMessage {
+    pub message_id: String,
+    /// Cosine similarity to the message being classified.
+    pub similarity: f32,
+    pub sender_email: Option<String>,
+    pub subject: Option<String>,
+    /// Action of the latest decision for the message.
+    pub action_type: Option<String>,
+    /// Status of the action created for that decision.
+    pub action_status: Option<ActionStatus>,
+    /// Set when the user undid or rejected the action.
+    pub feedback_source: Option<FeedbackSource>,
+    /// The action the user wanted instead, when recorded.
+    pub corrected_action_type: Option<String>,
+}
+
+/// Limits for [`MessageEmbeddingRepository::find_similar`].
+#[derive(Debug, Clone, Copy)]
+pub struct SimilarityQuery {
+    pub top_k: usize,
+    pub min_similarity: f32,
+    /// Most recent embeddings compared.
+    pub max_candidates: usize,
+}
+
+/// Text embedded for a message: sender, subject and the start of the body.
+pub fn embedding_text(message: &Message) -> String {
+    let mut lines = Vec::new();
+    if let Some(from) = message.from_email.as_deref() {
+        lines.push(format!("From: {from}"));
+    }
+    if let Some(subject) = message.subject.as_deref() {
+        lines.push(format!("Subject: {subject}"));
+    }
+    match get_body_text(message, EMBEDDING_BODY_LENGTH) {
+        Some(body) => lines.push(body),
+        None => {
+            if let Some(snippet) = message.snippet.as_deref() {
+                lines.push(truncate_text(snippet, EMBEDDING_BODY_LENGTH));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+#[derive(Clone)]
+pub struct MessageEmbeddingRepository {
+    db: Database,
+}
+
+impl MessageEmbeddingRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Store the embedding of a message, replacing any earlier one.
+    pub async fn upsert(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        message_id: &str,
+        model: &str,
+        vector: &[f32],
+    ) -> Result<(), SimilarMessagesError> {
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let conn = self.db.connection().await?;
+        conn.execute(
+            "INSERT INTO message_embeddings (message_id, org_id, user_id, account_id, model, dimensions, vector, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)
+             ON CONFLICT(message_id) DO UPDATE SET
+                model = excluded.model,
+                dimensions = excluded.dimensions,
+                vector = excluded.vector,
+                updated_at = excluded.updated_at",
+            params![
+                message_id,
+                org_id,
+                user_id,
+                account_id,
+                model,
+                vector.len() as i64,
+                encode_vector(vector),
+                now
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn count(&self, org_id: i64, user_id: i64) -> Result<i64, SimilarMessagesError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "SELECT COUNT(*) FROM message_embeddings WHERE org_id = ?1 AND user_id = ?2",
+                params![org_id, user_id],
+            )
+            .await?;
+        match rows.next().await? {
+            Some(row) => Ok(row.get(0)?),
+            None => Ok(0),
+        }
+    }
+
+    /// The decided messages of the account most similar to `vector`, most similar first.
+    ///
+    /// Only embeddings from `model` are compared, and `exclude_message_id` (the message being
+    /// classified) is skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn find_similar(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        model: &str,
+        vector: &[f32],
+        exclude_message_id: &str,
+        query: SimilarityQuery,
+    ) -> Result<Vec<SimilarMessage>, SimilarMessagesError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                "SELECT e.message_id, e.vector FROM message_embeddings e
+                 WHERE e.org_id = ?1 AND e.user_id = ?2 AND e.account_id = ?3 AND e.model = ?4
+                   AND e.message_id != ?5
+                   AND EXISTS (
+                     SELECT 1 FROM decisions d
+                     WHERE d.org_id = e.org_id AND d.user_id = e.user_id AND d.message_id = e.message_id
+                   )
+                 ORDER BY e.created_at DESC
+                 LIMIT ?6",
+                params![
+                    org_id,
+                    user_id,
+                    account_id,
+                    model,
+                    exclude_message_id,
+                    query.max_candidates as i64
+                ],
+            )
+            .await?;
+
+        let mut scored = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let message_id: String = row.get(0)?;
+            let stored: Vec<u8> = row.get(1)?;
+            let similarity = cosine_similarity(vector, &decode_vector(&stored));
+            if similarity >= query.min_similarity {
+                scored.push((similarity, message_id));
+            }
+        }
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        scored.truncate(query.top_k);
+
+        let mut similar = Vec::with_capacity(scored.len());
+        for (similarity, message_id) in scored {
+            let mut rows = conn
+                .query(
+                    "SELECT m.from_email, m.subject, d.action_type, a.status, f.source, f.corrected_action_type
+                     FROM messages m
+                     JOIN decisions d ON d.id = (
+                       SELECT id FROM decisions
+                       WHERE org_id = ?1 AND user_id = ?2 AND message_id = m.id
+                       ORDER BY created_at DESC LIMIT 1
+                     )
+                     LEFT JOIN actions a ON a.id = (
+                       SELECT id FROM actions WHERE decision_id = d.id ORDER BY created_at ASC LIMIT 1
+                     )
+                     LEFT JOIN classification_feedback f ON f.id = (
+                       SELECT id FROM classification_feedback
+                       WHERE org_id = ?1 AND user_id = ?2 AND message_id = m.id
+                       ORDER BY created_at DESC LIMIT 1
+                     )
+                     WHERE m.org_id = ?1 AND m.user_id = ?2 AND m.id = ?3",
+                    params![org_id, user_id, message_id.as_str()],
+                )
+                .await?;
+            let Some(row) = rows.next().await? else {
+                continue;
+            };
+            let status: Option<String> = row.get(3)?;
+            let source: Option<String> = row.get(4)?;
+            similar.push(SimilarMessage {
+                message_id,
+                similarity,
+                sender_email: row.get(0)?,
+                subject: row.get(1)?,
+                action_type: row.get(2)?,
+                action_status: status.as_deref().and_then(ActionStatus::from_str),
+                feedback_source: source.and_then(|source| source.parse().ok()),
+                corrected_action_type: row.get(5)?,
+            });
+        }
+        Ok(similar)
+    }
+}
+
+/// Little-endian `f32`s, as stored in `message_embeddings.vector`.
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountConfig, AccountRepository, PubsubConfig};
+    use crate::constants::{DEFAULT_ORG_ID, DEFAULT_USER_ID};
+    use crate::decisions::{
+        ActionRepository, DecisionRepository, DecisionSource, NewAction, NewDecision,
+    };
+    use crate::feedback::FeedbackRepository;
+    use crate::gmail::OAuthTokens;
+    use crate::llm::HashingEmbedder;
+    use crate::messages::{MessageRepository, NewMessage};
+    use crate::migrations::run_migrations;
+    use crate::threads::ThreadRepository;
+    use chrono::Duration;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        db: Database,
+        account_id: String,
+        thread_id: String,
+        _dir: TempDir,
+    }
+
+    async fn setup() -> Fixture {
+        let dir = TempDir::new().expect("temp dir");
+        let db = Database::new(&dir.path().join("db.sqlite"))
+            .await
+            .expect("create db");
+        run_migrations(&db).await.expect("migrations");
+
+        let account_id = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account")
+            .id;
+        let thread_id = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thread-1",
+                None,
+                None,
+                Some(Utc::now()),
+                json!({}),
+            )
+            .await
+            .expect("create thread")
+            .id;
+
+        Fixture {
+            db,
+            account_id,
+            thread_id,
+            _dir: dir,
+        }
+    }
+
+    async fn seed_message(fx: &Fixture, provider_id: &str, subject: &str) -> Message {
+        MessageRepository::new(fx.db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fx.account_id.clone(),
+                thread_id: fx.thread_id.clone(),
+                provider_message_id: provider_id.to_string(),
+                from_email: Some("billing@acme.example".into()),
+                from_name: None,
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                subject: Some(subject.to_string()),
+                snippet: None,
+                received_at: Some(Utc::now()),
+                internal_date: Some(Utc::now()),
+                labels: vec!["INBOX".into()],
+                headers: vec![],
+                body_plain: None,
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("create message")
+    }
+
+    async fn decide(fx: &Fixture, message: &Message, action_type: &str) -> crate::Action {
+        let decision = DecisionRepository::new(fx.db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fx.account_id.clone(),
+                message_id: message.id.clone(),
+                source: DecisionSource::Llm,
+                decision_json: json!({}),
+                action_type: Some(action_type.into()),
+                confidence: Some(0.9),
+                needs_approval: false,
+                rationale: None,
+                telemetry_json: json!({}),
+            })
+            .await
+            .expect("create decision");
+        ActionRepository::new(fx.db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: fx.account_id.clone(),
+                message_id: message.id.clone(),
+                decision_id: Some(decision.id),
+                action_type: action_type.into(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("create action")
+    }
+
+    #[test]
+    fn vectors_round_trip_through_bytes() {
+        let vector = vec![0.25, -1.5, 3.0];
+        assert_eq!(encode_vector(&vector).len(), 12);
+        assert_eq!(decode_vector(&encode_vector(&vector)), vector);
+    }
+
+    #[tokio::test]
+    async fn find_similar_returns_decided_neighbours_with_outcomes() {
+        let fx = setup().await;
+        let repo = MessageEmbeddingRepository::new(fx.db.clone());
+        let embedder = HashingEmbedder::new(256);
+        let model = "local-hash-256";
+        let index = |message: Message| {
+            let repo = repo.clone();
+            let vector = embedder.vector(&embedding_text(&message));
+            async move {
+                repo.upsert(
+                    DEFAULT_ORG_ID,
+                    DEFAULT_USER_ID,
+                    &message.account_id,
+                    &message.id,
+                    model,
+                    &vector,
+                )
+                .await
+                .expect("upsert");
+                message
+            }
+        };
+
+        let archived =
+            index(seed_message(&fx, "m1", "Your Acme invoice for March is ready").await).await;
+        decide(&fx, &archived, "archive").await;
+        let undone =
+            index(seed_message(&fx, "m2", "Your Acme invoice for April is ready").await).await;
+        let action = decide(&fx, &undone, "delete").await;
+        FeedbackRepository::new(fx.db.clone())
+            .record_from_action(&action, FeedbackSource::Undo)
+            .await
+            .expect("record feedback");
+        let unrelated =
+            index(seed_message(&fx, "m3", "Team offsite agenda and travel plans").await).await;
+        decide(&fx, &unrelated, "apply_label").await;
+        // Embedded but never decided, e.g. classification failed
+        index(seed_message(&fx, "m4", "Your Acme invoice for May is ready").await).await;
+
+        let current =
+            index(seed_message(&fx, "m5", "Your Acme invoice for June is ready").await).await;
+        let vector = embedder.vector(&embedding_text(&current));
+        let similar = repo
+            .find_similar(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &fx.account_id,
+                model,
+                &vector,
+                &current.id,
+                SimilarityQuery {
+                    top_k: 5,
+                    min_similarity: 0.5,
+                    max_candidates: 100,
+                },
+            )
+            .await
+            .expect("find similar");
+
+        let ids: Vec<&str> = similar.iter().map(|s| s.message_id.as_str()).collect();
+        assert_eq!(ids.len(), 2, "got {ids:?}");
+        assert!(ids.contains(&archived.id.as_str()));
+        assert!(ids.contains(&undone.id.as_str()));
+        assert!(similar[0].similarity >= similar[1].similarity);
+
+        let undone_hit = similar.iter().find(|s| s.message_id == undone.id).unwrap();
+        assert_eq!(undone_hit.action_type.as_deref(), Some("delete"));
+        assert_eq!(undone_hit.action_status, Some(ActionStatus::Queued));
+        assert_eq!(undone_hit.feedback_source, Some(FeedbackSource::Undo));
+        assert_eq!(
+            undone_hit.subject.as_deref(),
+            Some("Your Acme invoice for April is ready")
+        );
+
+        let other_model = repo
+            .find_similar(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &fx.account_id,
+                "openai::text-embedding-3-small",
+                &vector,
+                &current.id,
+                SimilarityQuery {
+                    top_k: 5,
+                    min_similarity: 0.0,
+                    max_candidates: 100,
+                },
+            )
+            .await
+            .expect("find similar");
+        assert!(other_model.is_empty());
+        assert_eq!(
+            repo.count(DEFAULT_ORG_ID, DEFAULT_USER_ID).await.unwrap(),
+            5
+        );
+    }
+}
@@ -1,12 +1,17 @@
 use chrono::{DateTime, SecondsFormat, Utc};
 use libsql::{Row, params};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
+use ts_rs::TS;
 use uuid::Uuid;
 
 use crate::db::{Database, DbError};
 
 const THREAD_COLUMNS: &str = "id, account_id, provider_thread_id, subject, snippet, last_message_at, metadata_json, raw_json, created_at, updated_at, org_id, user_id";
+const MUTED_THREAD_COLUMNS: &str =
+    "id, account_id, thread_id, provider_thread_id, action_id, created_at, org_id, user_id";
+const THREAD_SUMMARY_COLUMNS: &str = "thread_id, account_id, summary, participants_json, open_questions_json, commitments_json, message_count, last_message_id, model, llm_call_id, created_at, updated_at, org_id, user_id";
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Thread {
@@ -24,6 +29,61 @@ pub struct Thread {
     pub user_id: i64,
 }
 
+/// A thread muted by a `mute_thread` action. Messages that later arrive in it are
+/// archived during ingest instead of being classified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutedThread {
+    pub id: String,
+    pub account_id: String,
+    pub thread_id: Option<String>,
+    pub provider_thread_id: String,
+    /// The `mute_thread` action that created the mute.
+    pub action_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub org_id: i64,
+    pub user_id: i64,
+}
+
+/// Rolling LLM summary of a thread. Each update folds the messages that arrived since
+/// `last_message_id` into the previous summary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ThreadSummary {
+    pub thread_id: String,
+    pub account_id: String,
+    pub summary: String,
+    pub participants: Vec<String>,
+    pub open_questions: Vec<String>,
+    pub commitments: Vec<String>,
+    /// Messages of the thread covered by the summary.
+    #[ts(type = "number")]
+    pub message_count: i64,
+    /// The newest message covered by the summary.
+    pub last_message_id: String,
+    pub model: String,
+    pub llm_call_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[ts(type = "number")]
+    pub org_id: i64,
+    #[ts(type = "number")]
+    pub user_id: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewThreadSummary {
+    pub thread_id: String,
+    pub account_id: String,
+    pub summary: String,
+    pub participants: Vec<String>,
+    pub open_questions: Vec<String>,
+    pub commitments: Vec<String>,
+    pub message_count: i64,
+    pub last_message_id: String,
+    pub model: String,
+    pub llm_call_id: Option<String>,
+}
+
 #[derive(Debug, Error)]
 pub enum ThreadError {
     #[error("database error: {0}")]
@@ -192,6 +252,264 @@ impl ThreadRepository {
     }
 }
 
+#[derive(Clone)]
+pub struct MutedThreadRepository {
+    db: Database,
+}
+
+impl MutedThreadRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Mutes a thread. Muting an already-muted thread keeps the original record but points it
+    /// at the latest `mute_thread` action.
+    pub async fn mute(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        provider_thread_id: &str,
+        thread_id: Option<&str>,
+        action_id: Option<&str>,
+    ) -> Result<MutedThread, ThreadError> {
+        let id = Uuid::new_v4().to_string();
+        let now = now_rfc3339();
+
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "INSERT INTO muted_threads (id, account_id, thread_id, provider_thread_id, action_id, created_at, org_id, user_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                     ON CONFLICT(account_id, provider_thread_id) DO UPDATE SET
+                        thread_id = COALESCE(excluded.thread_id, muted_threads.thread_id),
+                        action_id = COALESCE(excluded.action_id, muted_threads.action_id)
+                     RETURNING {MUTED_THREAD_COLUMNS}"
+                ),
+                params![
+                    id,
+                    account_id,
+                    thread_id,
+                    provider_thread_id,
+                    action_id,
+                    now,
+                    org_id,
+                    user_id
+                ],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_muted_thread(row),
+            None => Err(ThreadError::NotFound(provider_thread_id.to_string())),
+        }
+    }
+
+    /// Returns the mute for a thread, if it is muted.
+    pub async fn get(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        provider_thread_id: &str,
+    ) -> Result<Option<MutedThread>, ThreadError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {MUTED_THREAD_COLUMNS}
+                     FROM muted_threads
+                     WHERE org_id = ?1 AND user_id = ?2 AND account_id = ?3 AND provider_thread_id = ?4"
+                ),
+                params![org_id, user_id, account_id, provider_thread_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(row_to_muted_thread(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the mute for a thread. Returns whether a mute existed.
+    pub async fn unmute(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        account_id: &str,
+        provider_thread_id: &str,
+    ) -> Result<bool, ThreadError> {
+        let conn = self.db.connection().await?;
+        let affected = conn
+            .execute(
+                "DELETE FROM muted_threads
+                 WHERE org_id = ?1 AND user_id = ?2 AND account_id = ?3 AND provider_thread_id = ?4",
+                params![org_id, user_id, account_id, provider_thread_id],
+            )
+            .await?;
+        Ok(affected > 0)
+    }
+}
+
+#[derive(Clone)]
+pub struct ThreadSummaryRepository {
+    db: Database,
+}
+
+impl ThreadSummaryRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Stores the summary of a thread, replacing the previous one.
+    pub async fn upsert(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        summary: NewThreadSummary,
+    ) -> Result<ThreadSummary, ThreadError> {
+        let now = now_rfc3339();
+        let participants_json = serde_json::to_string(&summary.participants)?;
+        let open_questions_json = serde_json::to_string(&summary.open_questions)?;
+        let commitments_json = serde_json::to_string(&summary.commitments)?;
+
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "INSERT INTO thread_summaries (thread_id, account_id, summary, participants_json, open_questions_json, commitments_json, message_count, last_message_id, model, llm_call_id, created_at, updated_at, org_id, user_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11, ?12, ?13)
+                     ON CONFLICT(thread_id) DO UPDATE SET
+                        summary = excluded.summary,
+                        participants_json = excluded.participants_json,
+                        open_questions_json = excluded.open_questions_json,
+                        commitments_json = excluded.commitments_json,
+                        message_count = excluded.message_count,
+                        last_message_id = excluded.last_message_id,
+                        model = excluded.model,
+                        llm_call_id = excluded.llm_call_id,
+                        updated_at = excluded.updated_at
+                     WHERE thread_summaries.org_id = excluded.org_id AND thread_summaries.user_id = excluded.user_id
+                     RETURNING {THREAD_SUMMARY_COLUMNS}"
+                ),
+                params![
+                    summary.thread_id.clone(),
+                    summary.account_id,
+                    summary.summary,
+                    participants_json,
+                    open_questions_json,
+                    commitments_json,
+                    summary.message_count,
+                    summary.last_message_id,
+                    summary.model,
+                    summary.llm_call_id,
+                    now,
+                    org_id,
+                    user_id
+                ],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => row_to_thread_summary(row),
+            None => Err(ThreadError::NotFound(summary.thread_id)),
+        }
+    }
+
+    /// Returns the summary of a thread, if one has been generated.
+    pub async fn get(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        thread_id: &str,
+    ) -> Result<Option<ThreadSummary>, ThreadError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {THREAD_SUMMARY_COLUMNS}
+                     FROM thread_summaries
+                     WHERE org_id = ?1 AND user_id = ?2 AND thread_id = ?3"
+                ),
+                params![org_id, user_id, thread_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(row_to_thread_summary(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the summary of the thread a message belongs to, if one has been generated.
+    pub async fn get_for_message(
+        &self,
+        org_id: i64,
+        user_id: i64,
+        message_id: &str,
+    ) -> Result<Option<ThreadSummary>, ThreadError> {
+        let conn = self.db.connection().await?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {THREAD_SUMMARY_COLUMNS}
+                     FROM thread_summaries
+                     WHERE org_id = ?1 AND user_id = ?2 AND thread_id = (
+                        SELECT thread_id FROM messages WHERE org_id = ?1 AND user_id = ?2 AND id = ?3
+                     )"
+                ),
+                params![org_id, user_id, message_id],
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(row_to_thread_summary(row)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn row_to_thread_summary(row: Row) -> Result<ThreadSummary, ThreadError> {
+    let participants_json: String = row.get(3)?;
+    let open_questions_json: String = row.get(4)?;
+    let commitments_json: String = row.get(5)?;
+    let created_at: String = row.get(10)?;
+    let updated_at: String = row.get(11)?;
+
+    Ok(ThreadSummary {
+        thread_id: row.get(0)?,
+        account_id: row.get(1)?,
+        summary: row.get(2)?,
+        participants: serde_json::from_str(&participants_json)?,
+        open_questions: serde_json::from_str(&open_questions_json)?,
+        commitments: serde_json::from_str(&commitments_json)?,
+        message_count: row.get(6)?,
+        last_message_id: row.get(7)?,
+        model: row.get(8)?,
+        llm_call_id: row.get(9)?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+        org_id: row.get(12)?,
+        user_id: row.get(13)?,
+    })
+}
+
+fn row_to_muted_thread(row: Row) -> Result<MutedThread, ThreadError> {
+    let created_at: String = row.get(5)?;
+
+    Ok(MutedThread {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        thread_id: row.get(2)?,
+        provider_thread_id: row.get(3)?,
+        action_id: row.get(4)?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+        org_id: row.get(6)?,
+        user_id: row.get(7)?,
+    })
+}
+
 fn row_to_thread(row: Row) -> Result<Thread, ThreadError> {
     let last_message_at: Option<String> = row.get(5)?;
     let metadata_json: String = row.get(6)?;
@@ -449,4 +767,146 @@ mod tests {
             .expect_err("update with wrong user should fail");
         assert!(matches!(update_wrong_user, ThreadError::NotFound(_)));
     }
+
+    #[tokio::test]
+    async fn mute_get_and_unmute_thread() {
+        let (_repo, db, _dir) = setup_repo().await;
+        let account_id = seed_account(&db).await;
+        let muted = MutedThreadRepository::new(db.clone());
+
+        assert!(
+            muted
+                .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, "thread1")
+                .await
+                .expect("get")
+                .is_none()
+        );
+
+        let first = muted
+            .mute(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thread1",
+                None,
+                Some("action-1"),
+            )
+            .await
+            .expect("mute");
+        let again = muted
+            .mute(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thread1",
+                None,
+                Some("action-2"),
+            )
+            .await
+            .expect("mute again");
+        assert_eq!(first.id, again.id, "re-muting should not create a new row");
+        assert_eq!(again.action_id.as_deref(), Some("action-2"));
+
+        let fetched = muted
+            .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, "thread1")
+            .await
+            .expect("get")
+            .expect("thread is muted");
+        assert_eq!(fetched.provider_thread_id, "thread1");
+        assert!(
+            muted
+                .get(DEFAULT_ORG_ID, DEFAULT_USER_ID + 1, &account_id, "thread1")
+                .await
+                .expect("get other user")
+                .is_none()
+        );
+
+        assert!(
+            muted
+                .unmute(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, "thread1")
+                .await
+                .expect("unmute")
+        );
+        assert!(
+            !muted
+                .unmute(DEFAULT_ORG_ID, DEFAULT_USER_ID, &account_id, "thread1")
+                .await
+                .expect("unmute again")
+        );
+    }
+
+    #[tokio::test]
+    async fn thread_summary_upsert_replaces_previous_summary() {
+        let (repo, db, _dir) = setup_repo().await;
+        let account_id = seed_account(&db).await;
+        let thread = repo
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account_id,
+                "thread1",
+                Some("Plans".into()),
+                None,
+                None,
+                serde_json::json!({}),
+            )
+            .await
+            .expect("thread");
+        let summaries = ThreadSummaryRepository::new(db.clone());
+        assert!(
+            summaries
+                .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &thread.id)
+                .await
+                .expect("get")
+                .is_none()
+        );
+
+        let new_summary = |summary: &str, count: i64| NewThreadSummary {
+            thread_id: thread.id.clone(),
+            account_id: account_id.clone(),
+            summary: summary.to_string(),
+            participants: vec!["alice@example.com".into()],
+            open_questions: vec!["Which date?".into()],
+            commitments: Vec::new(),
+            message_count: count,
+            last_message_id: format!("msg-{count}"),
+            model: "test-model".into(),
+            llm_call_id: None,
+        };
+        let first = summaries
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                new_summary("Planning a meeting", 2),
+            )
+            .await
+            .expect("insert");
+        let second = summaries
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                new_summary("Meeting set for Friday", 3),
+            )
+            .await
+            .expect("update");
+        assert_eq!(second.created_at, first.created_at);
+        assert_eq!(second.message_count, 3);
+
+        let fetched = summaries
+            .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &thread.id)
+            .await
+            .expect("get")
+            .expect("summary exists");
+        assert_eq!(fetched.summary, "Meeting set for Friday");
+        assert_eq!(fetched.last_message_id, "msg-3");
+        assert_eq!(fetched.participants, vec!["alice@example.com"]);
+        assert_eq!(fetched.open_questions, vec!["Which date?"]);
+        assert!(
+            summaries
+                .get(DEFAULT_ORG_ID, DEFAULT_USER_ID + 1, &thread.id)
+                .await
+                .expect("get other user")
+                .is_none()
+        );
+    }
 }
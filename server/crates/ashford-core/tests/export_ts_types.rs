@@ -20,18 +20,49 @@ fn export_typescript_types() {
     ashford_core::Decision::export_all().expect("Decision");
     ashford_core::Action::export_all().expect("Action");
     ashford_core::ActionLink::export_all().expect("ActionLink");
+    ashford_core::PolicyEffect::export_all().expect("PolicyEffect");
+    ashford_core::PolicyClause::export_all().expect("PolicyClause");
 
     // Rule types
     ashford_core::RuleScope::export_all().expect("RuleScope");
     ashford_core::SafeMode::export_all().expect("SafeMode");
     ashford_core::DeterministicRule::export_all().expect("DeterministicRule");
     ashford_core::LlmRule::export_all().expect("LlmRule");
+    ashford_core::SenderList::export_all().expect("SenderList");
+    ashford_core::RuleSchedule::export_all().expect("RuleSchedule");
+    ashford_core::ScheduleDay::export_all().expect("ScheduleDay");
 
     // Condition types (from rules module)
     ashford_core::rules::LogicalOperator::export_all().expect("LogicalOperator");
     ashford_core::rules::LogicalCondition::export_all().expect("LogicalCondition");
     ashford_core::rules::LeafCondition::export_all().expect("LeafCondition");
 
+    // Rule lint types
+    ashford_core::LintKind::export_all().expect("LintKind");
+    ashford_core::LintWarning::export_all().expect("LintWarning");
+
+    // Contact types
+    ashford_core::ContactStrength::export_all().expect("ContactStrength");
+
+    // Feedback types
+    ashford_core::FeedbackSource::export_all().expect("FeedbackSource");
+    ashford_core::ClassificationFeedback::export_all().expect("ClassificationFeedback");
+
+    // Circuit breaker types
+    ashford_core::CircuitBreakerScope::export_all().expect("CircuitBreakerScope");
+    ashford_core::CircuitBreakerTrip::export_all().expect("CircuitBreakerTrip");
+    ashford_core::BurstReport::export_all().expect("BurstReport");
+
+    // Bulk undo types
+    ashford_core::BulkUndoPreview::export_all().expect("BulkUndoPreview");
+    ashford_core::BulkUndoProgress::export_all().expect("BulkUndoProgress");
+
+    // Reply template types
+    ashford_core::ReplyTemplate::export_all().expect("ReplyTemplate");
+
+    // LLM spend types
+    ashford_core::SpendReport::export_all().expect("SpendReport");
+
     // Account types
     ashford_core::SyncStatus::export_all().expect("SyncStatus");
     ashford_core::AccountState::export_all().expect("AccountState");
@@ -33,6 +33,10 @@ async fn openai_completion_logs_and_returns_content() -> Result<(), Box<dyn std:
         model: integration_model(),
         temperature: 0.0,
         max_output_tokens: 32,
+        base_url: None,
+        api_key: None,
+        headers: Default::default(),
+        supports_tools: true,
     };
 
     let client = GenaiLLMClient::new(db.clone(), model_config);
@@ -51,6 +55,7 @@ async fn openai_completion_logs_and_returns_content() -> Result<(), Box<dyn std:
         temperature: 0.0,
         max_tokens: 8,
         json_mode: false,
+        model: None,
     };
 
     let context = LlmCallContext::new("llm_integration");
@@ -1,8 +1,8 @@
 use ashford_core::gmail::types::Header;
 use ashford_core::llm::{
     ActionType, ChatRole, DECISION_TOOL_NAME, DecisionDetails, DecisionOutput, DecisionParseError,
-    Explanations, MessageRef, PromptBuilder, TelemetryPlaceholder, ToolCallResult, UndoHint,
-    build_decision_tool,
+    Explanations, MessageRef, PromptBuilder, PromptContext, TelemetryPlaceholder, ToolCallResult,
+    UndoHint, build_decision_tool,
 };
 use ashford_core::messages::{Mailbox, Message};
 use ashford_core::rules::types::{Direction, LlmRule, RuleScope};
@@ -72,33 +72,40 @@ fn sample_decision() -> DecisionOutput {
 #[test]
 fn prompt_builder_includes_layers_and_task_directive() {
     let builder = PromptBuilder::new();
+    let directions = [Direction {
+        id: "d1".into(),
+        org_id: 1,
+        user_id: None,
+        content: "Never delete newsletters".into(),
+        enabled: true,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }];
+    let llm_rules = [LlmRule {
+        id: "r1".into(),
+        org_id: 1,
+        user_id: None,
+        name: "Newsletter rule".into(),
+        description: Some("Handle newsletters safely".into()),
+        scope: RuleScope::Global,
+        scope_ref: None,
+        rule_text: "Archive newsletters unless urgent".into(),
+        enabled: true,
+        metadata_json: serde_json::json!({}),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        disabled_reason: None,
+        active_from: None,
+        active_until: None,
+        schedule: None,
+    }];
     let prompt = builder.build(
         &sample_message(),
-        &[Direction {
-            id: "d1".into(),
-            org_id: 1,
-            user_id: None,
-            content: "Never delete newsletters".into(),
-            enabled: true,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-        }],
-        &[LlmRule {
-            id: "r1".into(),
-            org_id: 1,
-            user_id: None,
-            name: "Newsletter rule".into(),
-            description: Some("Handle newsletters safely".into()),
-            scope: RuleScope::Global,
-            scope_ref: None,
-            rule_text: "Archive newsletters unless urgent".into(),
-            enabled: true,
-            metadata_json: serde_json::json!({}),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-        }],
-        None,
-        &[], // No available labels for this test
+        &PromptContext {
+            directions: &directions,
+            llm_rules: &llm_rules,
+            ..Default::default()
+        },
     );
 
     assert_eq!(prompt.len(), 2);
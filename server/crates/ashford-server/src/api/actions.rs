@@ -4,6 +4,8 @@
 //! - GET /api/actions - List actions with filtering and pagination
 //! - GET /api/actions/:id - Get action detail
 //! - POST /api/actions/:id/undo - Queue an undo action
+//! - POST /api/actions/:id/reject - Reject an action awaiting approval
+//! - POST /api/actions/:id/cancel - Cancel a delayed action before it runs
 
 use axum::{
     Json, Router,
@@ -19,8 +21,9 @@ use serde_json::json;
 use ashford_core::decisions::ActionLinkRepository;
 use ashford_core::{
     ActionDetail, ActionLinkRelationType, ActionListFilter, ActionListItem, ActionRepository,
-    ActionStatus, DEFAULT_ORG_ID, DEFAULT_USER_ID, JOB_TYPE_ACTION_GMAIL, JobQueue, NewAction,
-    NewActionLink, PaginatedResponse, UndoActionResponse,
+    ActionStatus, DEFAULT_ORG_ID, DEFAULT_USER_ID, FeedbackRepository, FeedbackSource,
+    JOB_TYPE_ACTION_GMAIL, JobQueue, NewAction, NewActionLink, PaginatedResponse,
+    ThreadSummaryRepository, UndoActionResponse,
 };
 
 use crate::AppState;
@@ -31,6 +34,8 @@ pub fn router() -> Router<AppState> {
         .route("/", get(list_actions))
         .route("/{id}", get(get_action))
         .route("/{id}/undo", post(undo_action))
+        .route("/{id}/reject", post(reject_action))
+        .route("/{id}/cancel", post(cancel_action))
 }
 
 /// Error response for API errors.
@@ -212,6 +217,16 @@ async fn get_action(State(state): State<AppState>, Path(id): Path<String>) -> im
             let can_undo = row.can_undo();
             let gmail_link = row.gmail_link();
             let has_been_undone = row.has_been_undone();
+            let thread_summary = match ThreadSummaryRepository::new(state.db.clone())
+                .get_for_message(DEFAULT_ORG_ID, DEFAULT_USER_ID, &row.action.message_id)
+                .await
+            {
+                Ok(summary) => summary,
+                Err(e) => {
+                    tracing::warn!("Failed to load thread summary for action {}: {}", id, e);
+                    None
+                }
+            };
 
             let detail = ActionDetail {
                 id: row.action.id,
@@ -240,6 +255,7 @@ async fn get_action(State(state): State<AppState>, Path(id): Path<String>) -> im
                 gmail_link,
                 has_been_undone,
                 undo_action_id: row.undo_action_id,
+                thread_summary,
             };
             (StatusCode::OK, Json(detail)).into_response()
         }
@@ -267,7 +283,10 @@ async fn get_action(State(state): State<AppState>, Path(id): Path<String>) -> im
 /// 1. Action must be in Completed status
 /// 2. Action must have undo_hint_json with inverse_action
 /// 3. Action must not have already been undone
-async fn undo_action(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+pub(super) async fn undo_action(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
     let action_repo = ActionRepository::new(state.db.clone());
     let link_repo = ActionLinkRepository::new(state.db.clone());
     let queue = JobQueue::new(state.db.clone());
@@ -442,6 +461,185 @@ async fn undo_action(State(state): State<AppState>, Path(id): Path<String>) -> i
     (StatusCode::OK, Json(response)).into_response()
 }
 
+/// POST /api/actions/:id/reject
+///
+/// Reject an action that is awaiting approval. The action will not be executed and the
+/// rejection is recorded as classifier feedback.
+async fn reject_action(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let action_repo = ActionRepository::new(state.db.clone());
+
+    let action = match action_repo
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id)
+        .await
+    {
+        Ok(action) => action,
+        Err(ashford_core::ActionError::NotFound(_)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!("Action not found: {}", id))),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get action {}: {}", id, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!("Failed to get action: {}", e))),
+            )
+                .into_response();
+        }
+    };
+
+    if action.status != ActionStatus::ApprovedPending {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(format!(
+                "Cannot reject action with status: {:?}",
+                action.status
+            ))),
+        )
+            .into_response();
+    }
+
+    let rejected = match action_repo
+        .update_status(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &id,
+            ActionStatus::Rejected,
+            Some("Rejected by user".into()),
+            None,
+        )
+        .await
+    {
+        Ok(action) => action,
+        Err(ashford_core::ActionError::InvalidStatusTransition { from, .. }) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::bad_request(format!(
+                    "Cannot reject action with status: {:?}",
+                    from
+                ))),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to reject action {}: {}", id, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to reject action: {}",
+                    e
+                ))),
+            )
+                .into_response();
+        }
+    };
+
+    if let Err(e) = FeedbackRepository::new(state.db.clone())
+        .record_from_action(&rejected, FeedbackSource::Rejection)
+        .await
+    {
+        tracing::warn!("Failed to record rejection feedback for {}: {}", id, e);
+    }
+
+    (StatusCode::OK, Json(rejected)).into_response()
+}
+
+/// POST /api/actions/:id/cancel
+///
+/// Cancel an action in `pending_execution` before its delay has passed. Its scheduled job is
+/// canceled too, so the Gmail mutation or send never happens.
+async fn cancel_action(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let action_repo = ActionRepository::new(state.db.clone());
+
+    let action = match action_repo
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id)
+        .await
+    {
+        Ok(action) => action,
+        Err(ashford_core::ActionError::NotFound(_)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!("Action not found: {}", id))),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get action {}: {}", id, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!("Failed to get action: {}", e))),
+            )
+                .into_response();
+        }
+    };
+
+    if action.status != ActionStatus::PendingExecution {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(format!(
+                "Cannot cancel action with status: {:?}",
+                action.status
+            ))),
+        )
+            .into_response();
+    }
+
+    let canceled = match action_repo
+        .update_status(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &id,
+            ActionStatus::Canceled,
+            Some("Canceled by user".into()),
+            None,
+        )
+        .await
+    {
+        Ok(action) => action,
+        Err(ashford_core::ActionError::InvalidStatusTransition { from, .. }) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::bad_request(format!(
+                    "Cannot cancel action with status: {:?}",
+                    from
+                ))),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to cancel action {}: {}", id, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to cancel action: {}",
+                    e
+                ))),
+            )
+                .into_response();
+        }
+    };
+
+    // The job would skip the canceled action anyway; this keeps the queue tidy
+    let queue = JobQueue::new(state.db.clone());
+    let key = format!(
+        "{}:{}:{}:{}",
+        JOB_TYPE_ACTION_GMAIL, canceled.account_id, canceled.message_id, canceled.id
+    );
+    match queue.find_by_idempotency_key(&key).await {
+        Ok(Some(job)) => {
+            if let Err(e) = queue.cancel(&job.id).await {
+                tracing::warn!("Failed to cancel job {} for action {}: {}", job.id, id, e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to find job for action {}: {}", id, e),
+    }
+
+    (StatusCode::OK, Json(canceled)).into_response()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -560,7 +758,7 @@ mod tests {
 
         conn.execute(
             "INSERT INTO messages (id, account_id, thread_id, provider_message_id, from_email, from_name, to_json, cc_json, bcc_json, subject, snippet, received_at, internal_date, labels_json, headers_json, body_plain, body_html, raw_json, created_at, updated_at, org_id, user_id)
-             VALUES (?1, ?2, ?3, 'prov-msg', 'sender@example.com', 'Sender', '[]', '[]', '[]', 'Subject', 'Snippet', ?4, ?4, '[]', '{}', NULL, NULL, '{}', ?4, ?4, 1, 1)",
+             VALUES (?1, ?2, ?3, 'prov-msg', 'sender@example.com', 'Sender', '[]', '[]', '[]', 'Subject', 'Snippet', ?4, ?4, '[]', '[]', NULL, NULL, '{}', ?4, ?4, 1, 1)",
             params![message_id.clone(), account_id.clone(), thread_id.clone(), now.clone()],
         )
         .await
@@ -723,4 +921,200 @@ mod tests {
         let job_count: i64 = job_rows.next().await.unwrap().unwrap().get(0).unwrap();
         assert_eq!(job_count, 0, "no jobs should be enqueued when link fails");
     }
+
+    #[tokio::test]
+    async fn undo_action_leaves_feedback_until_the_undo_runs() {
+        let (db, _dir) = setup_db().await;
+        let (account_id, message_id) = seed_message(&db).await;
+        let decision = ashford_core::DecisionRepository::new(db.clone())
+            .create(ashford_core::NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.clone(),
+                message_id: message_id.clone(),
+                source: ashford_core::DecisionSource::Llm,
+                decision_json: json!({}),
+                action_type: Some("archive".into()),
+                confidence: Some(0.9),
+                needs_approval: false,
+                rationale: None,
+                telemetry_json: json!({}),
+            })
+            .await
+            .expect("create decision");
+        let repo = ActionRepository::new(db.clone());
+        let action = repo
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.clone(),
+                message_id: message_id.clone(),
+                decision_id: Some(decision.id),
+                action_type: "archive".to_string(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("create action");
+        repo.mark_executing(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+            .await
+            .expect("mark executing");
+        repo.mark_completed_with_undo_hint(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &action.id,
+            json!({"inverse_action": "apply_label", "inverse_parameters": {"label": "INBOX"}}),
+        )
+        .await
+        .expect("complete action");
+
+        let state = crate::AppState { db: db.clone() };
+        let response = undo_action(State(state), Path(action.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let feedback = FeedbackRepository::new(db.clone())
+            .list_recent(DEFAULT_ORG_ID, DEFAULT_USER_ID, 10, 0)
+            .await
+            .expect("list feedback");
+        assert!(
+            feedback.is_empty(),
+            "feedback is recorded once the undo completes, not when it is requested"
+        );
+    }
+
+    #[tokio::test]
+    async fn reject_action_rejects_pending_action_and_records_feedback() {
+        let (db, _dir) = setup_db().await;
+        let (account_id, message_id) = seed_message(&db).await;
+        let decision = ashford_core::DecisionRepository::new(db.clone())
+            .create(ashford_core::NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.clone(),
+                message_id: message_id.clone(),
+                source: ashford_core::DecisionSource::Llm,
+                decision_json: json!({}),
+                action_type: Some("delete".into()),
+                confidence: Some(0.8),
+                needs_approval: true,
+                rationale: None,
+                telemetry_json: json!({}),
+            })
+            .await
+            .expect("create decision");
+        let action = ActionRepository::new(db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account_id.clone(),
+                message_id: message_id.clone(),
+                decision_id: Some(decision.id),
+                action_type: "delete".to_string(),
+                parameters_json: json!({}),
+                status: ActionStatus::ApprovedPending,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("create action");
+
+        let state = crate::AppState { db: db.clone() };
+        let response = reject_action(State(state.clone()), Path(action.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).expect("json body");
+        assert_eq!(body["status"], json!("rejected"));
+
+        let feedback = FeedbackRepository::new(db.clone())
+            .list_recent(DEFAULT_ORG_ID, DEFAULT_USER_ID, 10, 0)
+            .await
+            .expect("list feedback");
+        assert_eq!(feedback.len(), 1);
+        assert_eq!(feedback[0].action_id, action.id);
+        assert_eq!(feedback[0].source, FeedbackSource::Rejection);
+        assert_eq!(feedback[0].wrong_action_type, "delete");
+        assert_eq!(
+            feedback[0].sender_email.as_deref(),
+            Some("sender@example.com")
+        );
+
+        let response = reject_action(State(state), Path(action.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn cancel_action_cancels_pending_execution_and_its_job() {
+        let (db, _dir) = setup_db().await;
+        let (account_id, message_id) = seed_message(&db).await;
+        let repo = ActionRepository::new(db.clone());
+        let new_action = |status: ActionStatus| NewAction {
+            org_id: DEFAULT_ORG_ID,
+            user_id: DEFAULT_USER_ID,
+            account_id: account_id.clone(),
+            message_id: message_id.clone(),
+            decision_id: None,
+            action_type: "delete".to_string(),
+            parameters_json: json!({}),
+            status,
+            error_message: None,
+            executed_at: None,
+            undo_hint_json: json!({}),
+            trace_id: None,
+        };
+        let pending = repo
+            .create(new_action(ActionStatus::PendingExecution))
+            .await
+            .expect("create pending action");
+        let queued = repo
+            .create(new_action(ActionStatus::Queued))
+            .await
+            .expect("create queued action");
+        let queue = JobQueue::new(db.clone());
+        let job_id = queue
+            .enqueue_scheduled(
+                JOB_TYPE_ACTION_GMAIL,
+                json!({}),
+                Some(format!(
+                    "{JOB_TYPE_ACTION_GMAIL}:{account_id}:{message_id}:{}",
+                    pending.id
+                )),
+                0,
+                Utc::now() + Duration::minutes(30),
+            )
+            .await
+            .expect("enqueue");
+
+        let state = crate::AppState { db: db.clone() };
+        let response = cancel_action(State(state.clone()), Path(pending.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).expect("json body");
+        assert_eq!(body["status"], json!("canceled"));
+        let job = queue.fetch_job(&job_id).await.expect("fetch job");
+        assert_eq!(job.state, ashford_core::JobState::Canceled);
+
+        // Only delayed actions can be canceled
+        let response = cancel_action(State(state), Path(queued.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
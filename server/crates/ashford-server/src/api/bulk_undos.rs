@@ -0,0 +1,497 @@
+//! Bulk undo API endpoints.
+//!
+//! Provides:
+//! - GET /api/bulk-undos - List bulk undos
+//! - POST /api/bulk-undos/preview - Show which actions a filter selects and which can be undone
+//! - POST /api/bulk-undos - Enqueue undo jobs for every undoable action a filter selects
+//! - GET /api/bulk-undos/:id - Get a bulk undo with the progress of its undo jobs
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+use serde::Serialize;
+use serde_json::json;
+
+use ashford_core::{
+    BulkUndo, BulkUndoError, BulkUndoFilter, BulkUndoRepository, DEFAULT_ORG_ID, DEFAULT_USER_ID,
+    JOB_TYPE_UNDO_ACTION, JobQueue, queue::QueueError,
+};
+
+use crate::AppState;
+
+/// Create the bulk undo API router.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_bulk_undos).post(create_bulk_undo))
+        .route("/preview", post(preview_bulk_undo))
+        .route("/{id}", get(get_bulk_undo))
+}
+
+/// Error response for API errors.
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    message: String,
+}
+
+impl ApiError {
+    fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new("not_found", message)
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new("bad_request", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new("internal_error", message)
+    }
+}
+
+/// Map a repository error to a response.
+fn bulk_undo_error(context: &str, id: &str, error: BulkUndoError) -> axum::response::Response {
+    match error {
+        BulkUndoError::NotFound(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!("Bulk undo not found: {}", id))),
+        )
+            .into_response(),
+        e @ (BulkUndoError::TooManyActions(_)
+        | BulkUndoError::InvalidWindow
+        | BulkUndoError::UntilRequired) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(e.to_string())),
+        )
+            .into_response(),
+        e => {
+            tracing::error!("Failed to {} {}: {}", context, id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!("Failed to {}: {}", context, e))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// GET /api/bulk-undos
+///
+/// List bulk undos, newest first.
+async fn list_bulk_undos(State(state): State<AppState>) -> impl IntoResponse {
+    let repo = BulkUndoRepository::new(state.db.clone());
+
+    match repo.list(DEFAULT_ORG_ID, DEFAULT_USER_ID).await {
+        Ok(bulk_undos) => (StatusCode::OK, Json(bulk_undos)).into_response(),
+        Err(e) => bulk_undo_error("list bulk undos", "", e),
+    }
+}
+
+/// POST /api/bulk-undos/preview
+///
+/// Show the actions a filter selects, split into those that can be undone and those that
+/// cannot, with the reason, and the `until` the window ended at. Nothing is enqueued.
+async fn preview_bulk_undo(
+    State(state): State<AppState>,
+    Json(filter): Json<BulkUndoFilter>,
+) -> impl IntoResponse {
+    let repo = BulkUndoRepository::new(state.db.clone());
+
+    match repo.preview(DEFAULT_ORG_ID, DEFAULT_USER_ID, &filter).await {
+        Ok(preview) => (StatusCode::OK, Json(preview)).into_response(),
+        Err(e) => bulk_undo_error("preview bulk undo", "", e),
+    }
+}
+
+/// POST /api/bulk-undos
+///
+/// Record a bulk undo and enqueue an `undo.action` job for each undoable action the filter
+/// selects. `until` is required, normally the one the preview returned, so actions created
+/// after the preview are left alone. Returns the bulk undo with its initial progress.
+async fn create_bulk_undo(
+    State(state): State<AppState>,
+    Json(filter): Json<BulkUndoFilter>,
+) -> impl IntoResponse {
+    let repo = BulkUndoRepository::new(state.db.clone());
+
+    let preview = match repo.preview(DEFAULT_ORG_ID, DEFAULT_USER_ID, &filter).await {
+        Ok(preview) => preview,
+        Err(e) => return bulk_undo_error("preview bulk undo", "", e),
+    };
+    let bulk_undo = match repo
+        .create(DEFAULT_ORG_ID, DEFAULT_USER_ID, &filter, &preview)
+        .await
+    {
+        Ok(bulk_undo) => bulk_undo,
+        Err(e) => return bulk_undo_error("create bulk undo", "", e),
+    };
+
+    let queue = JobQueue::new(state.db.clone());
+    for candidate in &preview.undoable {
+        let payload = json!({
+            "account_id": candidate.account_id,
+            "original_action_id": candidate.action_id,
+        });
+        match queue
+            .enqueue(
+                JOB_TYPE_UNDO_ACTION,
+                payload,
+                Some(bulk_undo.job_idempotency_key(&candidate.action_id)),
+                0,
+            )
+            .await
+        {
+            Ok(_) | Err(QueueError::DuplicateIdempotency { .. }) => {}
+            Err(e) => {
+                // Actions left without a job show up as failed in the progress
+                tracing::error!(
+                    "Failed to enqueue undo job for {} in bulk undo {}: {}",
+                    candidate.action_id,
+                    bulk_undo.id,
+                    e
+                );
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::internal(format!(
+                        "Failed to enqueue undo job: {}",
+                        e
+                    ))),
+                )
+                    .into_response();
+            }
+        }
+    }
+
+    tracing::info!(
+        bulk_undo_id = %bulk_undo.id,
+        actions = bulk_undo.action_ids.len(),
+        skipped = bulk_undo.skipped.len(),
+        "bulk undo enqueued"
+    );
+    progress_response(&repo, bulk_undo, StatusCode::CREATED).await
+}
+
+/// GET /api/bulk-undos/:id
+///
+/// Get a bulk undo with the state of each action's undo.
+async fn get_bulk_undo(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let repo = BulkUndoRepository::new(state.db.clone());
+
+    match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(bulk_undo) => progress_response(&repo, bulk_undo, StatusCode::OK).await,
+        Err(e) => bulk_undo_error("get bulk undo", &id, e),
+    }
+}
+
+async fn progress_response(
+    repo: &BulkUndoRepository,
+    bulk_undo: BulkUndo,
+    status: StatusCode,
+) -> axum::response::Response {
+    let id = bulk_undo.id.clone();
+    match repo.progress(bulk_undo).await {
+        Ok(progress) => (status, Json(progress)).into_response(),
+        Err(e) => bulk_undo_error("load bulk undo progress", &id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ashford_core::accounts::{AccountConfig, PubsubConfig};
+    use ashford_core::{
+        AccountRepository, ActionRepository, ActionStatus, BulkUndoSkipReason, Database,
+        DecisionRepository, DecisionSource, MessageRepository, NewAction, NewDecision, NewMessage,
+        OAuthTokens, ThreadRepository, migrations::run_migrations,
+    };
+    use axum::body::to_bytes;
+    use chrono::{Duration, Utc};
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("test.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    async fn response_json(response: axum::response::Response) -> serde_json::Value {
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        serde_json::from_slice(&body).expect("json body")
+    }
+
+    /// Create a completed action chosen by deterministic rule `rule_id`.
+    async fn completed_action(
+        db: &Database,
+        rule_id: &str,
+        undo_hint: serde_json::Value,
+    ) -> String {
+        let account = match AccountRepository::new(db.clone())
+            .list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+            .await
+            .expect("list accounts")
+            .into_iter()
+            .next()
+        {
+            Some(account) => account,
+            None => AccountRepository::new(db.clone())
+                .create(
+                    DEFAULT_ORG_ID,
+                    DEFAULT_USER_ID,
+                    "user@example.com",
+                    None,
+                    AccountConfig {
+                        client_id: "client".into(),
+                        client_secret: "secret".into(),
+                        oauth: OAuthTokens {
+                            access_token: "access".into(),
+                            refresh_token: "refresh".into(),
+                            expires_at: Utc::now() + Duration::hours(1),
+                        },
+                        pubsub: PubsubConfig::default(),
+                    },
+                )
+                .await
+                .expect("create account"),
+        };
+        let thread = ThreadRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &account.id,
+                "thr-1",
+                None,
+                None,
+                None,
+                json!({}),
+            )
+            .await
+            .expect("thread");
+        let message = MessageRepository::new(db.clone())
+            .upsert(NewMessage {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account.id.clone(),
+                thread_id: thread.id,
+                provider_message_id: "msg-1".into(),
+                from_email: None,
+                from_name: None,
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                subject: None,
+                snippet: None,
+                received_at: None,
+                internal_date: None,
+                labels: vec![],
+                headers: vec![],
+                body_plain: None,
+                body_html: None,
+                raw_json: json!({}),
+            })
+            .await
+            .expect("message");
+        let decision = DecisionRepository::new(db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account.id.clone(),
+                message_id: message.id.clone(),
+                source: DecisionSource::Deterministic,
+                decision_json: json!({}),
+                action_type: Some("archive".into()),
+                confidence: Some(1.0),
+                needs_approval: false,
+                rationale: None,
+                telemetry_json: json!({"rule": {"id": rule_id}}),
+            })
+            .await
+            .expect("decision");
+        let repo = ActionRepository::new(db.clone());
+        let action = repo
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: account.id,
+                message_id: message.id,
+                decision_id: Some(decision.id),
+                action_type: "archive".into(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("action");
+        repo.mark_executing(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id)
+            .await
+            .expect("executing");
+        repo.mark_completed_with_undo_hint(DEFAULT_ORG_ID, DEFAULT_USER_ID, &action.id, undo_hint)
+            .await
+            .expect("completed");
+        action.id
+    }
+
+    #[tokio::test]
+    async fn create_enqueues_undo_jobs_for_undoable_actions() {
+        let (db, _dir) = setup_db().await;
+        let undoable = completed_action(&db, "bad", json!({"inverse_action": "apply_label"})).await;
+        let unsupported = completed_action(&db, "bad", json!({})).await;
+        completed_action(&db, "good", json!({"inverse_action": "apply_label"})).await;
+        let state = AppState { db: db.clone() };
+        let filter = BulkUndoFilter {
+            rule_id: Some("bad".into()),
+            source: None,
+            account_id: None,
+            action_types: vec![],
+            since: Utc::now() - Duration::hours(1),
+            until: None,
+        };
+
+        let response = preview_bulk_undo(State(state.clone()), Json(filter.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_json(response).await;
+        assert_eq!(body["undoable"][0]["action_id"], json!(undoable));
+        assert_eq!(body["not_undoable"][0]["action_id"], json!(unsupported));
+        assert_eq!(body["not_undoable"][0]["reason"], "not_supported");
+
+        let until = serde_json::from_value(body["until"].clone()).expect("until");
+
+        // Without the preview's until the window would include actions created since
+        let response = create_bulk_undo(State(state.clone()), Json(filter.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let later = completed_action(&db, "bad", json!({"inverse_action": "apply_label"})).await;
+
+        let filter = BulkUndoFilter {
+            until: Some(until),
+            ..filter
+        };
+        let response = create_bulk_undo(State(state.clone()), Json(filter.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response_json(response).await;
+        assert_eq!(body["total"], 1);
+        assert_eq!(body["pending"], 1);
+        assert_eq!(
+            body["bulk_undo"]["action_ids"],
+            json!([undoable]),
+            "{later} was created after the preview"
+        );
+        assert_eq!(
+            body["bulk_undo"]["skipped"][0]["action_id"],
+            json!(unsupported)
+        );
+        let id = body["bulk_undo"]["id"].as_str().expect("id").to_string();
+
+        let job = JobQueue::new(db.clone())
+            .find_by_idempotency_key(&format!("undo.action:bulk:{id}:{undoable}"))
+            .await
+            .expect("find job")
+            .expect("undo job");
+        assert_eq!(job.job_type, JOB_TYPE_UNDO_ACTION);
+        assert_eq!(job.payload["original_action_id"], json!(undoable));
+
+        let response = get_bulk_undo(State(state.clone()), Path(id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = get_bulk_undo(State(state.clone()), Path("missing".into()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = preview_bulk_undo(
+            State(state),
+            Json(BulkUndoFilter {
+                until: Some(Utc::now() - Duration::hours(2)),
+                ..filter
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn preview_skips_actions_undone_through_the_actions_api() {
+        let (db, _dir) = setup_db().await;
+        let original = completed_action(
+            &db,
+            "bad",
+            json!({"inverse_action": "apply_label", "inverse_parameters": {"label": "INBOX"}}),
+        )
+        .await;
+        let state = AppState { db: db.clone() };
+
+        let response =
+            crate::api::actions::undo_action(State(state.clone()), Path(original.clone()))
+                .await
+                .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let undo_id = response_json(response).await["undo_action_id"]
+            .as_str()
+            .expect("undo action id")
+            .to_string();
+
+        // No rule filter, so the API's plain `apply_label` undo action would match too
+        let filter = BulkUndoFilter {
+            rule_id: None,
+            source: None,
+            account_id: None,
+            action_types: vec![],
+            since: Utc::now() - Duration::hours(1),
+            until: None,
+        };
+        let repo = BulkUndoRepository::new(db.clone());
+        let preview = repo
+            .preview(DEFAULT_ORG_ID, DEFAULT_USER_ID, &filter)
+            .await
+            .expect("preview");
+        assert!(preview.undoable.is_empty());
+        assert_eq!(preview.not_undoable.len(), 1);
+        assert_eq!(preview.not_undoable[0].action_id, original);
+        assert_eq!(
+            preview.not_undoable[0].reason,
+            BulkUndoSkipReason::UndoInProgress
+        );
+
+        let actions = ActionRepository::new(db.clone());
+        actions
+            .mark_executing(DEFAULT_ORG_ID, DEFAULT_USER_ID, &undo_id)
+            .await
+            .expect("executing");
+        actions
+            .mark_completed(DEFAULT_ORG_ID, DEFAULT_USER_ID, &undo_id)
+            .await
+            .expect("completed");
+        let preview = repo
+            .preview(DEFAULT_ORG_ID, DEFAULT_USER_ID, &filter)
+            .await
+            .expect("preview");
+        assert_eq!(preview.not_undoable.len(), 1);
+        assert_eq!(
+            preview.not_undoable[0].reason,
+            BulkUndoSkipReason::AlreadyUndone
+        );
+    }
+}
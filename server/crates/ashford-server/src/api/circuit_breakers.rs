@@ -0,0 +1,269 @@
+//! Circuit breaker API endpoints.
+//!
+//! Provides:
+//! - GET /api/circuit-breakers - List open trips, or all trips with `?include_reset=true`
+//! - GET /api/circuit-breakers/:id - Get a trip with its burst report
+//! - POST /api/circuit-breakers/:id/reset - Reset an open trip and return its burst report
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+
+use ashford_core::{
+    CircuitBreakerError, CircuitBreakerRepository, CircuitBreakerTrip, DEFAULT_ORG_ID,
+    DEFAULT_USER_ID,
+};
+
+use crate::AppState;
+
+/// Create the circuit breaker API router.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_trips))
+        .route("/{id}", get(get_trip))
+        .route("/{id}/reset", post(reset_trip))
+}
+
+/// Error response for API errors.
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    message: String,
+}
+
+impl ApiError {
+    fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new("not_found", message)
+    }
+
+    fn conflict(message: impl Into<String>) -> Self {
+        Self::new("conflict", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new("internal_error", message)
+    }
+}
+
+/// Map a repository error to a response.
+fn trip_error(context: &str, id: &str, error: CircuitBreakerError) -> axum::response::Response {
+    match error {
+        CircuitBreakerError::NotFound(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Circuit breaker trip not found: {}",
+                id
+            ))),
+        )
+            .into_response(),
+        CircuitBreakerError::AlreadyReset(_) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "Circuit breaker trip already reset: {}",
+                id
+            ))),
+        )
+            .into_response(),
+        e => {
+            tracing::error!("Failed to {} {}: {}", context, id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!("Failed to {}: {}", context, e))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Query parameters for listing trips.
+#[derive(Debug, Deserialize)]
+pub struct TripListQuery {
+    /// Include trips that were already reset.
+    #[serde(default)]
+    pub include_reset: bool,
+}
+
+/// GET /api/circuit-breakers
+///
+/// List trips, newest first.
+async fn list_trips(
+    State(state): State<AppState>,
+    Query(query): Query<TripListQuery>,
+) -> impl IntoResponse {
+    let repo = CircuitBreakerRepository::new(state.db.clone());
+
+    match repo
+        .list(DEFAULT_ORG_ID, DEFAULT_USER_ID, query.include_reset)
+        .await
+    {
+        Ok(trips) => (StatusCode::OK, Json(trips)).into_response(),
+        Err(e) => trip_error("list circuit breaker trips", "", e),
+    }
+}
+
+/// GET /api/circuit-breakers/:id
+///
+/// Get a trip with the actions that ran during its burst and those it has held.
+async fn get_trip(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let repo = CircuitBreakerRepository::new(state.db.clone());
+
+    match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(trip) => report_response(&repo, &trip, StatusCode::OK).await,
+        Err(e) => trip_error("get circuit breaker trip", &id, e),
+    }
+}
+
+/// POST /api/circuit-breakers/:id/reset
+///
+/// Reset an open trip so its account or rule runs automatically again. Actions already
+/// held for approval stay there.
+async fn reset_trip(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let repo = CircuitBreakerRepository::new(state.db.clone());
+
+    match repo.reset(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(trip) => {
+            tracing::info!(trip_id = %trip.id, "circuit breaker reset");
+            report_response(&repo, &trip, StatusCode::OK).await
+        }
+        Err(e) => trip_error("reset circuit breaker trip", &id, e),
+    }
+}
+
+async fn report_response(
+    repo: &CircuitBreakerRepository,
+    trip: &CircuitBreakerTrip,
+    status: StatusCode,
+) -> axum::response::Response {
+    match repo.report(DEFAULT_ORG_ID, DEFAULT_USER_ID, trip).await {
+        Ok(report) => (status, Json(report)).into_response(),
+        Err(e) => trip_error("report circuit breaker trip", &trip.id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ashford_core::accounts::{AccountConfig, PubsubConfig};
+    use ashford_core::{
+        AccountRepository, CircuitBreakerConfig, CircuitBreakerWindow, Database, OAuthTokens,
+        TrackedAction, migrations::run_migrations,
+    };
+    use axum::body::to_bytes;
+    use chrono::{Duration, Utc};
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("test.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    async fn response_json(response: axum::response::Response) -> serde_json::Value {
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        serde_json::from_slice(&body).expect("json body")
+    }
+
+    #[tokio::test]
+    async fn reset_closes_trip_and_returns_report() {
+        let (db, _dir) = setup_db().await;
+        let account = AccountRepository::new(db.clone())
+            .create(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                "user@example.com",
+                None,
+                AccountConfig {
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    oauth: OAuthTokens {
+                        access_token: "access".into(),
+                        refresh_token: "refresh".into(),
+                        expires_at: Utc::now() + Duration::hours(1),
+                    },
+                    pubsub: PubsubConfig::default(),
+                },
+            )
+            .await
+            .expect("create account");
+        let config = CircuitBreakerConfig {
+            windows: vec![CircuitBreakerWindow {
+                seconds: 60,
+                max_per_account: 0,
+                max_per_rule: 0,
+            }],
+            ..Default::default()
+        };
+        let hold = CircuitBreakerRepository::new(db.clone())
+            .check(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                &config,
+                &TrackedAction {
+                    account_id: &account.id,
+                    action_type: "trash",
+                    rule: None,
+                },
+            )
+            .await
+            .expect("check")
+            .expect("tripped");
+        let state = AppState { db };
+
+        let response = list_trips(
+            State(state.clone()),
+            Query(TripListQuery {
+                include_reset: false,
+            }),
+        )
+        .await
+        .into_response();
+        let body = response_json(response).await;
+        assert_eq!(body.as_array().expect("array").len(), 1);
+
+        let response = reset_trip(State(state.clone()), Path(hold.trip.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_json(response).await;
+        assert!(body["trip"]["reset_at"].is_string());
+        assert_eq!(body["executed"], serde_json::json!([]));
+        assert_eq!(body["held"], serde_json::json!([]));
+
+        let response = reset_trip(State(state.clone()), Path(hold.trip.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = list_trips(
+            State(state.clone()),
+            Query(TripListQuery {
+                include_reset: false,
+            }),
+        )
+        .await
+        .into_response();
+        let body = response_json(response).await;
+        assert_eq!(body, serde_json::json!([]));
+
+        let response = reset_trip(State(state), Path("missing".into()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
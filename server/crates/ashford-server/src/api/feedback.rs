@@ -0,0 +1,422 @@
+//! Classifier feedback API endpoints.
+//!
+//! Provides:
+//! - GET /api/feedback - List recorded corrections with pagination
+//! - GET /api/feedback/:id - Get a correction
+//! - PATCH /api/feedback/:id - Set or clear the corrected action
+//! - DELETE /api/feedback/:id - Delete a correction
+//! - POST /api/feedback/prune - Delete corrections older than a number of days
+
+use std::str::FromStr;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use ashford_core::llm::ActionType;
+use ashford_core::{
+    ClassificationFeedback, DEFAULT_ORG_ID, DEFAULT_USER_ID, FeedbackError, FeedbackRepository,
+    PaginatedResponse,
+};
+
+use crate::AppState;
+
+/// Create the feedback API router.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_feedback))
+        .route("/prune", post(prune_feedback))
+        .route(
+            "/{id}",
+            get(get_feedback)
+                .patch(update_feedback)
+                .delete(delete_feedback),
+        )
+}
+
+/// Error response for API errors.
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    message: String,
+}
+
+impl ApiError {
+    fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new("not_found", message)
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new("bad_request", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new("internal_error", message)
+    }
+}
+
+fn feedback_error_response(
+    id: &str,
+    context: &str,
+    err: FeedbackError,
+) -> axum::response::Response {
+    match err {
+        FeedbackError::NotFound(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!("Feedback not found: {}", id))),
+        )
+            .into_response(),
+        e => {
+            tracing::error!("Failed to {} feedback {}: {}", context, id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to {} feedback: {}",
+                    context, e
+                ))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Query parameters for listing feedback.
+#[derive(Debug, Deserialize)]
+pub struct FeedbackListQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// GET /api/feedback
+///
+/// List recorded corrections, newest first.
+async fn list_feedback(
+    State(state): State<AppState>,
+    Query(query): Query<FeedbackListQuery>,
+) -> impl IntoResponse {
+    let repo = FeedbackRepository::new(state.db.clone());
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let items = match repo
+        .list_recent(DEFAULT_ORG_ID, DEFAULT_USER_ID, limit, offset)
+        .await
+    {
+        Ok(items) => items,
+        Err(e) => return feedback_error_response("list", "list", e),
+    };
+    let total = match repo.count(DEFAULT_ORG_ID, DEFAULT_USER_ID).await {
+        Ok(total) => total,
+        Err(e) => return feedback_error_response("list", "count", e),
+    };
+
+    (
+        StatusCode::OK,
+        Json(PaginatedResponse::<ClassificationFeedback>::new(
+            items, total, limit, offset,
+        )),
+    )
+        .into_response()
+}
+
+/// GET /api/feedback/:id
+async fn get_feedback(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let repo = FeedbackRepository::new(state.db.clone());
+    match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(feedback) => (StatusCode::OK, Json(feedback)).into_response(),
+        Err(e) => feedback_error_response(&id, "fetch", e),
+    }
+}
+
+/// Request body for recording what the classifier should have done.
+/// Omitting `corrected_action_type` (or sending null) clears the correction.
+#[derive(Debug, Deserialize)]
+pub struct UpdateFeedbackRequest {
+    pub corrected_action_type: Option<String>,
+    pub corrected_parameters: Option<Value>,
+}
+
+/// PATCH /api/feedback/:id
+async fn update_feedback(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateFeedbackRequest>,
+) -> impl IntoResponse {
+    let action_type = body
+        .corrected_action_type
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    if let Some(action_type) = action_type
+        && ActionType::from_str(action_type).is_err()
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(format!(
+                "Unknown action type: {}",
+                action_type
+            ))),
+        )
+            .into_response();
+    }
+    if body
+        .corrected_parameters
+        .as_ref()
+        .is_some_and(|params| !params.is_object())
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(
+                "corrected_parameters must be a JSON object",
+            )),
+        )
+            .into_response();
+    }
+
+    // Parameters only make sense alongside an action
+    let parameters = action_type.map(|_| body.corrected_parameters.unwrap_or_else(|| json!({})));
+
+    let repo = FeedbackRepository::new(state.db.clone());
+    match repo
+        .set_correction(
+            DEFAULT_ORG_ID,
+            DEFAULT_USER_ID,
+            &id,
+            action_type,
+            parameters.as_ref(),
+        )
+        .await
+    {
+        Ok(feedback) => (StatusCode::OK, Json(feedback)).into_response(),
+        Err(e) => feedback_error_response(&id, "update", e),
+    }
+}
+
+/// DELETE /api/feedback/:id
+async fn delete_feedback(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let repo = FeedbackRepository::new(state.db.clone());
+    match repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => feedback_error_response(&id, "delete", e),
+    }
+}
+
+/// Request body for pruning old corrections.
+#[derive(Debug, Deserialize)]
+pub struct PruneFeedbackRequest {
+    pub older_than_days: i64,
+}
+
+/// POST /api/feedback/prune
+///
+/// Delete corrections recorded more than `older_than_days` days ago.
+async fn prune_feedback(
+    State(state): State<AppState>,
+    Json(body): Json<PruneFeedbackRequest>,
+) -> impl IntoResponse {
+    if body.older_than_days < 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(
+                "older_than_days must not be negative",
+            )),
+        )
+            .into_response();
+    }
+
+    let cutoff = Utc::now() - Duration::days(body.older_than_days);
+    let repo = FeedbackRepository::new(state.db.clone());
+    match repo
+        .prune_older_than(DEFAULT_ORG_ID, DEFAULT_USER_ID, cutoff)
+        .await
+    {
+        Ok(deleted) => (StatusCode::OK, Json(json!({ "deleted": deleted }))).into_response(),
+        Err(e) => feedback_error_response("prune", "prune", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ashford_core::{
+        ActionRepository, ActionStatus, Database, DecisionRepository, DecisionSource,
+        FeedbackSource, NewAction, NewDecision, migrations::run_migrations,
+    };
+    use axum::body::to_bytes;
+    use libsql::params;
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("test.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    /// Seed a message with an undone LLM action and record it as feedback.
+    async fn seed_feedback(db: &Database) -> ClassificationFeedback {
+        let now = Utc::now().to_rfc3339();
+        let conn = db.connection().await.expect("conn");
+        conn.execute(
+            "INSERT INTO accounts (id, provider, email, display_name, config_json, state_json, created_at, updated_at, org_id, user_id)
+             VALUES ('acc-test', 'gmail', 'user@example.com', 'User', '{}', '{}', ?1, ?1, 1, 1)",
+            params![now.clone()],
+        )
+        .await
+        .expect("insert account");
+        conn.execute(
+            "INSERT INTO threads (id, account_id, provider_thread_id, subject, snippet, last_message_at, metadata_json, raw_json, created_at, updated_at, org_id, user_id)
+             VALUES ('thr-test', 'acc-test', 'prov-thread', 'Subject', 'Snippet', ?1, '{}', '{}', ?1, ?1, 1, 1)",
+            params![now.clone()],
+        )
+        .await
+        .expect("insert thread");
+        conn.execute(
+            "INSERT INTO messages (id, account_id, thread_id, provider_message_id, from_email, from_name, to_json, cc_json, bcc_json, subject, snippet, received_at, internal_date, labels_json, headers_json, body_plain, body_html, raw_json, created_at, updated_at, org_id, user_id)
+             VALUES ('msg-test', 'acc-test', 'thr-test', 'prov-msg', 'sender@example.com', 'Sender', '[]', '[]', '[]', 'Subject', 'Snippet', ?1, ?1, '[]', '[]', NULL, NULL, '{}', ?1, ?1, 1, 1)",
+            params![now],
+        )
+        .await
+        .expect("insert message");
+
+        let decision = DecisionRepository::new(db.clone())
+            .create(NewDecision {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: "acc-test".into(),
+                message_id: "msg-test".into(),
+                source: DecisionSource::Llm,
+                decision_json: json!({}),
+                action_type: Some("archive".into()),
+                confidence: Some(0.9),
+                needs_approval: false,
+                rationale: None,
+                telemetry_json: json!({}),
+            })
+            .await
+            .expect("create decision");
+        let action = ActionRepository::new(db.clone())
+            .create(NewAction {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                account_id: "acc-test".into(),
+                message_id: "msg-test".into(),
+                decision_id: Some(decision.id),
+                action_type: "archive".into(),
+                parameters_json: json!({}),
+                status: ActionStatus::Queued,
+                error_message: None,
+                executed_at: None,
+                undo_hint_json: json!({}),
+                trace_id: None,
+            })
+            .await
+            .expect("create action");
+
+        FeedbackRepository::new(db.clone())
+            .record_from_action(&action, FeedbackSource::Undo)
+            .await
+            .expect("record feedback")
+            .expect("feedback")
+    }
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        serde_json::from_slice(&bytes).expect("json body")
+    }
+
+    #[tokio::test]
+    async fn list_and_update_feedback() {
+        let (db, _dir) = setup_db().await;
+        let feedback = seed_feedback(&db).await;
+        let state = AppState { db: db.clone() };
+
+        let response = list_feedback(
+            State(state.clone()),
+            Query(FeedbackListQuery {
+                limit: None,
+                offset: None,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["total"], json!(1));
+        assert_eq!(body["items"][0]["id"], json!(feedback.id));
+        assert_eq!(body["items"][0]["source"], json!("undo"));
+
+        let response = update_feedback(
+            State(state.clone()),
+            Path(feedback.id.clone()),
+            Json(UpdateFeedbackRequest {
+                corrected_action_type: Some("explode".into()),
+                corrected_parameters: None,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = update_feedback(
+            State(state),
+            Path(feedback.id.clone()),
+            Json(UpdateFeedbackRequest {
+                corrected_action_type: Some("apply_label".into()),
+                corrected_parameters: Some(json!({"label": "Work"})),
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["corrected_action_type"], json!("apply_label"));
+        assert_eq!(body["corrected_parameters_json"], json!({"label": "Work"}));
+    }
+
+    #[tokio::test]
+    async fn delete_and_prune_feedback() {
+        let (db, _dir) = setup_db().await;
+        let feedback = seed_feedback(&db).await;
+        let state = AppState { db: db.clone() };
+
+        let response = prune_feedback(
+            State(state.clone()),
+            Json(PruneFeedbackRequest { older_than_days: 7 }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await, json!({"deleted": 0}));
+
+        let response = delete_feedback(State(state.clone()), Path(feedback.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = get_feedback(State(state), Path(feedback.id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
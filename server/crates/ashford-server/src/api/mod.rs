@@ -3,14 +3,28 @@
 //! This module provides REST API endpoints for:
 //! - Accounts listing
 //! - Actions history and management
+//! - Bulk undo of actions selected by rule, source, account, type and time window
+//! - Circuit breaker trips, burst reports and manual reset
 //! - Rules configuration (deterministic and LLM rules)
 //! - Labels listing
+//! - Reply templates used by auto_reply actions
+//! - Classifier feedback review and pruning
+//! - Safety policy clauses
+//! - LLM spend and budget status
+//! - Threads and their rolling summaries
 //! - Settings (future)
 
 pub mod accounts;
 pub mod actions;
+pub mod bulk_undos;
+pub mod circuit_breakers;
+pub mod feedback;
 pub mod labels;
+pub mod policy;
+pub mod reply_templates;
 pub mod rules;
+pub mod spend;
+pub mod threads;
 
 use axum::Router;
 
@@ -21,6 +35,13 @@ pub fn router(_state: AppState) -> Router<AppState> {
     Router::new()
         .nest("/accounts", accounts::router())
         .nest("/actions", actions::router())
+        .nest("/bulk-undos", bulk_undos::router())
+        .nest("/circuit-breakers", circuit_breakers::router())
+        .nest("/feedback", feedback::router())
         .nest("/labels", labels::router())
+        .nest("/policy", policy::router())
+        .nest("/reply-templates", reply_templates::router())
         .nest("/rules", rules::router())
+        .nest("/spend", spend::router())
+        .nest("/threads", threads::router())
 }
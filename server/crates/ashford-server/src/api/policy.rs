@@ -0,0 +1,405 @@
+//! Safety policy API endpoints.
+//!
+//! Provides:
+//! - GET /api/policy/clauses - List policy clauses
+//! - GET /api/policy/clauses/:id - Get a policy clause by ID
+//! - POST /api/policy/clauses - Create a policy clause
+//! - PATCH /api/policy/clauses/:id - Update a policy clause
+//! - DELETE /api/policy/clauses/:id - Delete a policy clause
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, patch, post},
+};
+use serde::{Deserialize, Serialize};
+
+use ashford_core::accounts::AccountError;
+use ashford_core::{
+    AccountRepository, DEFAULT_ORG_ID, DEFAULT_USER_ID, NewPolicyClause, PolicyClauseError,
+    PolicyClauseRepository, PolicyEffect,
+};
+
+use super::rules::nullable;
+use crate::AppState;
+
+/// Create the policy API router.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/clauses", get(list_policy_clauses))
+        .route("/clauses", post(create_policy_clause))
+        .route("/clauses/{id}", get(get_policy_clause))
+        .route("/clauses/{id}", patch(update_policy_clause))
+        .route("/clauses/{id}", delete(delete_policy_clause))
+}
+
+/// Error response for API errors.
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    message: String,
+}
+
+impl ApiError {
+    fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new("not_found", message)
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new("bad_request", message)
+    }
+
+    fn conflict(message: impl Into<String>) -> Self {
+        Self::new("conflict", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new("internal_error", message)
+    }
+}
+
+/// Map a repository error to a response.
+fn clause_error(context: &str, id: &str, error: PolicyClauseError) -> axum::response::Response {
+    match error {
+        PolicyClauseError::NotFound(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Policy clause not found: {}",
+                id
+            ))),
+        )
+            .into_response(),
+        PolicyClauseError::DuplicateName(name) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "Policy clause already exists: {}",
+                name
+            ))),
+        )
+            .into_response(),
+        PolicyClauseError::Invalid(message) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(message)),
+        )
+            .into_response(),
+        e => {
+            tracing::error!("Failed to {} {}: {}", context, id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!("Failed to {}: {}", context, e))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Reject clauses scoped to an account that does not exist.
+/// Returns `Err` with a ready-made error response.
+async fn check_account(
+    state: &AppState,
+    account_id: Option<&str>,
+) -> Result<(), axum::response::Response> {
+    let Some(account_id) = account_id else {
+        return Ok(());
+    };
+    match AccountRepository::new(state.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, account_id)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(AccountError::NotFound(_)) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(format!(
+                "Account not found: {}",
+                account_id
+            ))),
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!("Failed to load account {}: {}", account_id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!("Failed to load account: {}", e))),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// GET /api/policy/clauses
+///
+/// List all policy clauses, sorted by name.
+async fn list_policy_clauses(State(state): State<AppState>) -> impl IntoResponse {
+    let repo = PolicyClauseRepository::new(state.db.clone());
+
+    match repo.list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID).await {
+        Ok(clauses) => (StatusCode::OK, Json(clauses)).into_response(),
+        Err(e) => clause_error("list policy clauses", "", e),
+    }
+}
+
+/// GET /api/policy/clauses/:id
+///
+/// Get a single policy clause by ID.
+async fn get_policy_clause(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let repo = PolicyClauseRepository::new(state.db.clone());
+
+    match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(clause) => (StatusCode::OK, Json(clause)).into_response(),
+        Err(e) => clause_error("get policy clause", &id, e),
+    }
+}
+
+/// Request body for creating a policy clause.
+#[derive(Debug, Deserialize)]
+pub struct CreatePolicyClauseRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Limits the clause to one account. Omit for a global clause.
+    pub account_id: Option<String>,
+    pub effect: PolicyEffect,
+    /// Required for `min_confidence` clauses.
+    pub min_confidence: Option<f64>,
+    pub action_types: Option<Vec<String>>,
+    pub senders: Option<Vec<String>>,
+    pub labels: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+/// POST /api/policy/clauses
+///
+/// Create a new policy clause.
+async fn create_policy_clause(
+    State(state): State<AppState>,
+    Json(body): Json<CreatePolicyClauseRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = check_account(&state, body.account_id.as_deref()).await {
+        return response;
+    }
+
+    let new_clause = NewPolicyClause {
+        org_id: DEFAULT_ORG_ID,
+        user_id: Some(DEFAULT_USER_ID),
+        name: body.name,
+        description: body.description,
+        account_id: body.account_id,
+        effect: body.effect,
+        min_confidence: body.min_confidence,
+        action_types: body.action_types.unwrap_or_default(),
+        senders: body.senders.unwrap_or_default(),
+        labels: body.labels.unwrap_or_default(),
+        enabled: body.enabled.unwrap_or(true),
+    };
+
+    let repo = PolicyClauseRepository::new(state.db.clone());
+
+    match repo.create(new_clause).await {
+        Ok(clause) => (StatusCode::CREATED, Json(clause)).into_response(),
+        Err(e) => clause_error("create policy clause", "", e),
+    }
+}
+
+/// Request body for updating a policy clause.
+/// All fields are optional for partial updates. List fields replace the whole list.
+#[derive(Debug, Deserialize)]
+pub struct UpdatePolicyClauseRequest {
+    pub name: Option<String>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub description: Option<Option<String>>,
+    /// Send null to make the clause global.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub account_id: Option<Option<String>>,
+    pub effect: Option<PolicyEffect>,
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub min_confidence: Option<Option<f64>>,
+    pub action_types: Option<Vec<String>>,
+    pub senders: Option<Vec<String>>,
+    pub labels: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+/// PATCH /api/policy/clauses/:id
+///
+/// Update an existing policy clause.
+async fn update_policy_clause(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdatePolicyClauseRequest>,
+) -> impl IntoResponse {
+    let repo = PolicyClauseRepository::new(state.db.clone());
+
+    let existing = match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(clause) => clause,
+        Err(e) => return clause_error("fetch policy clause", &id, e),
+    };
+
+    let account_id = nullable::merge(body.account_id, existing.account_id);
+    if let Err(response) = check_account(&state, account_id.as_deref()).await {
+        return response;
+    }
+
+    let updated_clause = NewPolicyClause {
+        org_id: existing.org_id,
+        user_id: existing.user_id,
+        name: body.name.unwrap_or(existing.name),
+        description: nullable::merge(body.description, existing.description),
+        account_id,
+        effect: body.effect.unwrap_or(existing.effect),
+        min_confidence: nullable::merge(body.min_confidence, existing.min_confidence),
+        action_types: body.action_types.unwrap_or(existing.action_types),
+        senders: body.senders.unwrap_or(existing.senders),
+        labels: body.labels.unwrap_or(existing.labels),
+        enabled: body.enabled.unwrap_or(existing.enabled),
+    };
+
+    match repo
+        .update(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id, updated_clause)
+        .await
+    {
+        Ok(clause) => (StatusCode::OK, Json(clause)).into_response(),
+        Err(e) => clause_error("update policy clause", &id, e),
+    }
+}
+
+/// DELETE /api/policy/clauses/:id
+///
+/// Delete a policy clause.
+async fn delete_policy_clause(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let repo = PolicyClauseRepository::new(state.db.clone());
+
+    match repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => clause_error("delete policy clause", &id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ashford_core::{Database, PolicyClause, migrations::run_migrations};
+    use axum::body::to_bytes;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("test.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    async fn response_json(response: axum::response::Response) -> serde_json::Value {
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        serde_json::from_slice(&body).expect("json body")
+    }
+
+    async fn create(state: &AppState, body: serde_json::Value) -> axum::response::Response {
+        let request: CreatePolicyClauseRequest = serde_json::from_value(body).expect("request");
+        create_policy_clause(State(state.clone()), Json(request))
+            .await
+            .into_response()
+    }
+
+    #[tokio::test]
+    async fn create_and_update_policy_clause() {
+        let (db, _dir) = setup_db().await;
+        let state = AppState { db };
+
+        let response = create(
+            &state,
+            json!({
+                "name": "protect finance",
+                "effect": "require_approval",
+                "action_types": ["trash", "delete"],
+                "labels": ["Finance"]
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let clause: PolicyClause =
+            serde_json::from_value(response_json(response).await).expect("clause");
+        assert_eq!(clause.effect, PolicyEffect::RequireApproval);
+        assert!(clause.enabled);
+
+        let body: UpdatePolicyClauseRequest =
+            serde_json::from_value(json!({"effect": "min_confidence", "min_confidence": 0.9}))
+                .expect("request");
+        let response =
+            update_policy_clause(State(state.clone()), Path(clause.id.clone()), Json(body))
+                .await
+                .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated: PolicyClause =
+            serde_json::from_value(response_json(response).await).expect("clause");
+        assert_eq!(updated.effect, PolicyEffect::MinConfidence);
+        assert_eq!(updated.min_confidence, Some(0.9));
+        assert_eq!(updated.labels, vec!["Finance"]);
+
+        let response = list_policy_clauses(State(state.clone()))
+            .await
+            .into_response();
+        assert_eq!(
+            response_json(response).await.as_array().map(Vec::len),
+            Some(1)
+        );
+
+        let response = delete_policy_clause(State(state.clone()), Path(clause.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = get_policy_clause(State(state), Path(clause.id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn invalid_policy_clauses_are_rejected() {
+        let (db, _dir) = setup_db().await;
+        let state = AppState { db };
+
+        let response = create(
+            &state,
+            json!({"name": "strict", "effect": "min_confidence"}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = create(
+            &state,
+            json!({"name": "scoped", "effect": "skip_approval", "account_id": "missing"}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response_json(response).await["message"],
+            "Account not found: missing"
+        );
+
+        let body = json!({"name": "trusted", "effect": "skip_approval"});
+        assert_eq!(
+            create(&state, body.clone()).await.status(),
+            StatusCode::CREATED
+        );
+        assert_eq!(create(&state, body).await.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,344 @@
+//! Reply template API endpoints.
+//!
+//! Provides:
+//! - GET /api/reply-templates - List reply templates
+//! - GET /api/reply-templates/:id - Get a reply template by ID
+//! - POST /api/reply-templates - Create a reply template
+//! - PATCH /api/reply-templates/:id - Update a reply template
+//! - DELETE /api/reply-templates/:id - Delete a reply template
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, patch, post},
+};
+use serde::{Deserialize, Serialize};
+
+use ashford_core::{
+    DEFAULT_ORG_ID, DEFAULT_USER_ID, NewReplyTemplate, ReplyTemplateError, ReplyTemplateRepository,
+    TemplateSlot,
+};
+
+use super::rules::nullable;
+use crate::AppState;
+
+/// Create the reply templates API router.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_reply_templates))
+        .route("/", post(create_reply_template))
+        .route("/{id}", get(get_reply_template))
+        .route("/{id}", patch(update_reply_template))
+        .route("/{id}", delete(delete_reply_template))
+}
+
+/// Error response for API errors.
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    message: String,
+}
+
+impl ApiError {
+    fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new("not_found", message)
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new("bad_request", message)
+    }
+
+    fn conflict(message: impl Into<String>) -> Self {
+        Self::new("conflict", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new("internal_error", message)
+    }
+}
+
+/// Map a repository error to a response.
+fn template_error(context: &str, id: &str, error: ReplyTemplateError) -> axum::response::Response {
+    match error {
+        ReplyTemplateError::NotFound(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Reply template not found: {}",
+                id
+            ))),
+        )
+            .into_response(),
+        ReplyTemplateError::DuplicateName(name) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "Reply template already exists: {}",
+                name
+            ))),
+        )
+            .into_response(),
+        ReplyTemplateError::Invalid(message) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(message)),
+        )
+            .into_response(),
+        e => {
+            tracing::error!("Failed to {} {}: {}", context, id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!("Failed to {}: {}", context, e))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// GET /api/reply-templates
+///
+/// List all reply templates, sorted by name.
+async fn list_reply_templates(State(state): State<AppState>) -> impl IntoResponse {
+    let repo = ReplyTemplateRepository::new(state.db.clone());
+
+    match repo.list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID).await {
+        Ok(templates) => (StatusCode::OK, Json(templates)).into_response(),
+        Err(e) => template_error("list reply templates", "", e),
+    }
+}
+
+/// GET /api/reply-templates/:id
+///
+/// Get a single reply template by ID.
+async fn get_reply_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let repo = ReplyTemplateRepository::new(state.db.clone());
+
+    match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(template) => (StatusCode::OK, Json(template)).into_response(),
+        Err(e) => template_error("get reply template", &id, e),
+    }
+}
+
+/// Request body for creating a reply template.
+#[derive(Debug, Deserialize)]
+pub struct CreateReplyTemplateRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Omit to reply with `Re: <original subject>`.
+    pub subject: Option<String>,
+    pub body: String,
+    /// Omit to derive the HTML part from `body`.
+    pub body_html: Option<String>,
+    pub slots: Option<Vec<TemplateSlot>>,
+}
+
+/// POST /api/reply-templates
+///
+/// Create a new reply template.
+async fn create_reply_template(
+    State(state): State<AppState>,
+    Json(body): Json<CreateReplyTemplateRequest>,
+) -> impl IntoResponse {
+    let new_template = NewReplyTemplate {
+        org_id: DEFAULT_ORG_ID,
+        user_id: DEFAULT_USER_ID,
+        name: body.name,
+        description: body.description,
+        subject: body.subject,
+        body: body.body,
+        body_html: body.body_html,
+        slots: body.slots.unwrap_or_default(),
+    };
+
+    let repo = ReplyTemplateRepository::new(state.db.clone());
+
+    match repo.create(new_template).await {
+        Ok(template) => (StatusCode::CREATED, Json(template)).into_response(),
+        Err(e) => template_error("create reply template", "", e),
+    }
+}
+
+/// Request body for updating a reply template.
+/// All fields are optional for partial updates. `slots` replaces the whole list.
+#[derive(Debug, Deserialize)]
+pub struct UpdateReplyTemplateRequest {
+    pub name: Option<String>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub description: Option<Option<String>>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub subject: Option<Option<String>>,
+    pub body: Option<String>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub body_html: Option<Option<String>>,
+    pub slots: Option<Vec<TemplateSlot>>,
+}
+
+/// PATCH /api/reply-templates/:id
+///
+/// Update an existing reply template.
+async fn update_reply_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateReplyTemplateRequest>,
+) -> impl IntoResponse {
+    let repo = ReplyTemplateRepository::new(state.db.clone());
+
+    let existing = match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(template) => template,
+        Err(e) => return template_error("fetch reply template", &id, e),
+    };
+
+    let updated_template = NewReplyTemplate {
+        org_id: existing.org_id,
+        user_id: existing.user_id,
+        name: body.name.unwrap_or(existing.name),
+        description: nullable::merge(body.description, existing.description),
+        subject: nullable::merge(body.subject, existing.subject),
+        body: body.body.unwrap_or(existing.body),
+        body_html: nullable::merge(body.body_html, existing.body_html),
+        slots: body.slots.unwrap_or(existing.slots),
+    };
+
+    match repo
+        .update(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id, updated_template)
+        .await
+    {
+        Ok(template) => (StatusCode::OK, Json(template)).into_response(),
+        Err(e) => template_error("update reply template", &id, e),
+    }
+}
+
+/// DELETE /api/reply-templates/:id
+///
+/// Delete a reply template.
+async fn delete_reply_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let repo = ReplyTemplateRepository::new(state.db.clone());
+
+    match repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => template_error("delete reply template", &id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ashford_core::{Database, ReplyTemplate, migrations::run_migrations};
+    use axum::body::to_bytes;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("test.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    async fn response_json(response: axum::response::Response) -> serde_json::Value {
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        serde_json::from_slice(&body).expect("json body")
+    }
+
+    async fn create(state: &AppState, body: serde_json::Value) -> axum::response::Response {
+        let request: CreateReplyTemplateRequest = serde_json::from_value(body).expect("request");
+        create_reply_template(State(state.clone()), Json(request))
+            .await
+            .into_response()
+    }
+
+    #[tokio::test]
+    async fn create_update_and_delete_reply_template() {
+        let (db, _dir) = setup_db().await;
+        let state = AppState { db };
+
+        let response = create(
+            &state,
+            json!({
+                "name": "pricing",
+                "subject": "Pricing for {{sender_first_name}}",
+                "body": "Our plans start at {{price}}.",
+                "slots": [{"name": "price", "required": true}]
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let template: ReplyTemplate =
+            serde_json::from_value(response_json(response).await).expect("template");
+        assert_eq!(template.slots.len(), 1);
+        assert!(template.slots[0].required);
+
+        let body: UpdateReplyTemplateRequest =
+            serde_json::from_value(json!({"subject": null, "description": "Plan prices"}))
+                .expect("request");
+        let response =
+            update_reply_template(State(state.clone()), Path(template.id.clone()), Json(body))
+                .await
+                .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated: ReplyTemplate =
+            serde_json::from_value(response_json(response).await).expect("template");
+        assert_eq!(updated.subject, None);
+        assert_eq!(updated.description.as_deref(), Some("Plan prices"));
+        assert_eq!(updated.body, "Our plans start at {{price}}.");
+
+        let response = delete_reply_template(State(state.clone()), Path(template.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = get_reply_template(State(state), Path(template.id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn invalid_reply_templates_are_rejected() {
+        let (db, _dir) = setup_db().await;
+        let state = AppState { db };
+
+        let response = create(
+            &state,
+            json!({"name": "broken", "body": "Hi {{#if sender_name}}there"}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = create(
+            &state,
+            json!({"name": "undeclared", "body": "See you {{meeting_time}}"}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response_json(response).await["message"],
+            "body: unknown variable 'meeting_time'; declare it as a slot"
+        );
+
+        let body = json!({"name": "thanks", "body": "Thanks, {{sender_name}}!"});
+        assert_eq!(
+            create(&state, body.clone()).await.status(),
+            StatusCode::CREATED
+        );
+        let body = json!({"name": "Thanks", "body": "Thank you!"});
+        assert_eq!(create(&state, body).await.status(), StatusCode::CONFLICT);
+    }
+}
@@ -6,11 +6,17 @@
 //! - POST /api/rules/deterministic - Create a deterministic rule
 //! - PATCH /api/rules/deterministic/:id - Update a deterministic rule
 //! - DELETE /api/rules/deterministic/:id - Delete a deterministic rule
+//! - GET /api/rules/lint - Lint all enabled deterministic rules
 //! - GET /api/rules/llm - List LLM rules
 //! - GET /api/rules/llm/:id - Get an LLM rule by ID
 //! - POST /api/rules/llm - Create an LLM rule
 //! - PATCH /api/rules/llm/:id - Update an LLM rule
 //! - DELETE /api/rules/llm/:id - Delete an LLM rule
+//! - GET /api/rules/sender-lists - List sender lists
+//! - GET /api/rules/sender-lists/:id - Get a sender list by ID
+//! - POST /api/rules/sender-lists - Create a sender list
+//! - PATCH /api/rules/sender-lists/:id - Update a sender list
+//! - DELETE /api/rules/sender-lists/:id - Delete a sender list
 
 use axum::{
     Json, Router,
@@ -19,12 +25,16 @@ use axum::{
     response::IntoResponse,
     routing::{delete, get, patch, post},
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use ashford_core::rules::schedule::validate_window;
 use ashford_core::{
-    DEFAULT_ORG_ID, DEFAULT_USER_ID, DeterministicRuleError, DeterministicRuleRepository,
-    LlmRuleError, LlmRuleRepository, NewDeterministicRule, NewLlmRule, RuleScope, SafeMode,
+    DEFAULT_ORG_ID, DEFAULT_USER_ID, DeterministicRule, DeterministicRuleError,
+    DeterministicRuleRepository, LabelRepository, LintWarning, LlmRuleError, LlmRuleRepository,
+    NewDeterministicRule, NewLlmRule, NewSenderList, ReplyTemplateRepository, RuleLinter,
+    RuleSchedule, RuleScope, SafeMode, SenderListError, SenderListRepository,
 };
 
 use crate::AppState;
@@ -42,12 +52,19 @@ pub fn router() -> Router<AppState> {
         .route("/deterministic/{id}", get(get_deterministic_rule))
         .route("/deterministic/{id}", patch(update_deterministic_rule))
         .route("/deterministic/{id}", delete(delete_deterministic_rule))
+        .route("/lint", get(lint_deterministic_rules))
         // LLM rules
         .route("/llm", get(list_llm_rules))
         .route("/llm", post(create_llm_rule))
         .route("/llm/{id}", get(get_llm_rule))
         .route("/llm/{id}", patch(update_llm_rule))
         .route("/llm/{id}", delete(delete_llm_rule))
+        // Sender lists
+        .route("/sender-lists", get(list_sender_lists))
+        .route("/sender-lists", post(create_sender_list))
+        .route("/sender-lists/{id}", get(get_sender_list))
+        .route("/sender-lists/{id}", patch(update_sender_list))
+        .route("/sender-lists/{id}", delete(delete_sender_list))
 }
 
 /// Error response for API errors.
@@ -73,6 +90,10 @@ impl ApiError {
         Self::new("bad_request", message)
     }
 
+    fn conflict(message: impl Into<String>) -> Self {
+        Self::new("conflict", message)
+    }
+
     fn internal(message: impl Into<String>) -> Self {
         Self::new("internal_error", message)
     }
@@ -148,6 +169,12 @@ pub struct CreateDeterministicRuleRequest {
     pub action_type: String,
     pub action_parameters_json: Option<Value>,
     pub safe_mode: Option<SafeMode>,
+    /// The rule only applies from this instant.
+    pub active_from: Option<DateTime<Utc>>,
+    /// The rule stops applying at this instant and is then auto-disabled.
+    pub active_until: Option<DateTime<Utc>>,
+    /// Recurring weekly window, e.g. weekdays 09:00-17:00 in a timezone.
+    pub schedule: Option<RuleSchedule>,
 }
 
 /// POST /api/rules/deterministic
@@ -183,6 +210,14 @@ async fn create_deterministic_rule(
             .into_response();
     }
 
+    if let Err(e) = validate_window(body.active_from, body.active_until, body.schedule.as_ref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(e.to_string())),
+        )
+            .into_response();
+    }
+
     let scope = body.scope.unwrap_or(RuleScope::Global);
     // Clear scope_ref if scope is Global (it would be meaningless)
     let scope_ref = if scope == RuleScope::Global {
@@ -207,12 +242,25 @@ async fn create_deterministic_rule(
             .action_parameters_json
             .unwrap_or(Value::Object(Default::default())),
         safe_mode: body.safe_mode.unwrap_or(SafeMode::Default),
+        active_from: body.active_from,
+        active_until: body.active_until,
+        schedule: body.schedule,
     };
 
     let repo = DeterministicRuleRepository::new(state.db.clone());
 
     match repo.create(new_rule).await {
-        Ok(rule) => (StatusCode::CREATED, Json(rule)).into_response(),
+        Ok(rule) => {
+            let lint_warnings = lint_warnings_for_rule(&state, &rule.id).await;
+            (
+                StatusCode::CREATED,
+                Json(DeterministicRuleWithWarnings {
+                    rule,
+                    lint_warnings,
+                }),
+            )
+                .into_response()
+        }
         Err(e) => {
             tracing::error!("Failed to create deterministic rule: {}", e);
             (
@@ -231,7 +279,7 @@ async fn create_deterministic_rule(
 /// `None` = field not present (keep existing value)
 /// `Some(None)` = field explicitly set to null (clear the value)
 /// `Some(Some(T))` = field explicitly set to a value
-mod nullable {
+pub(crate) mod nullable {
     use serde::{Deserialize, Deserializer};
 
     /// Deserialize an optional nullable field.
@@ -246,6 +294,14 @@ mod nullable {
         // Then the inner Option handles null vs value
         Ok(Some(Option::deserialize(deserializer)?))
     }
+
+    /// Apply a deserialized nullable field to the existing value.
+    pub fn merge<T>(update: Option<Option<T>>, existing: Option<T>) -> Option<T> {
+        match update {
+            None => existing,
+            Some(value) => value,
+        }
+    }
 }
 
 /// Request body for updating a deterministic rule.
@@ -274,6 +330,15 @@ pub struct UpdateDeterministicRuleRequest {
     pub action_type: Option<String>,
     pub action_parameters_json: Option<Value>,
     pub safe_mode: Option<SafeMode>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub active_from: Option<Option<DateTime<Utc>>>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub active_until: Option<Option<DateTime<Utc>>>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub schedule: Option<Option<RuleSchedule>>,
 }
 
 /// PATCH /api/rules/deterministic/:id
@@ -343,6 +408,17 @@ async fn update_deterministic_rule(
         }
     };
 
+    let active_from = nullable::merge(body.active_from, existing.active_from);
+    let active_until = nullable::merge(body.active_until, existing.active_until);
+    let schedule = nullable::merge(body.schedule, existing.schedule);
+    if let Err(e) = validate_window(active_from, active_until, schedule.as_ref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(e.to_string())),
+        )
+            .into_response();
+    }
+
     let updated_rule = NewDeterministicRule {
         org_id: existing.org_id,
         user_id: existing.user_id,
@@ -359,13 +435,26 @@ async fn update_deterministic_rule(
             .action_parameters_json
             .unwrap_or(existing.action_parameters_json),
         safe_mode: body.safe_mode.unwrap_or(existing.safe_mode),
+        active_from,
+        active_until,
+        schedule,
     };
 
     match repo
         .update(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id, updated_rule)
         .await
     {
-        Ok(rule) => (StatusCode::OK, Json(rule)).into_response(),
+        Ok(rule) => {
+            let lint_warnings = lint_warnings_for_rule(&state, &rule.id).await;
+            (
+                StatusCode::OK,
+                Json(DeterministicRuleWithWarnings {
+                    rule,
+                    lint_warnings,
+                }),
+            )
+                .into_response()
+        }
         Err(DeterministicRuleError::NotFound(_)) => (
             StatusCode::NOT_FOUND,
             Json(ApiError::not_found(format!(
@@ -421,6 +510,67 @@ async fn delete_deterministic_rule(
     }
 }
 
+/// Deterministic rule returned from create and update, along with any lint warnings
+/// that involve it. The rule fields are flattened so the shape stays compatible with
+/// `DeterministicRule`.
+#[derive(Debug, Serialize)]
+pub struct DeterministicRuleWithWarnings {
+    #[serde(flatten)]
+    pub rule: DeterministicRule,
+    pub lint_warnings: Vec<LintWarning>,
+}
+
+/// Response body for the lint endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LintRulesResponse {
+    pub warnings: Vec<LintWarning>,
+}
+
+fn rule_linter(state: &AppState) -> RuleLinter {
+    RuleLinter::new(
+        DeterministicRuleRepository::new(state.db.clone()),
+        LabelRepository::new(state.db.clone()),
+        SenderListRepository::new(state.db.clone()),
+        ReplyTemplateRepository::new(state.db.clone()),
+    )
+}
+
+/// Lint warnings involving a rule that was just saved. Linting never blocks a save, so
+/// failures are logged and reported as no warnings.
+async fn lint_warnings_for_rule(state: &AppState, rule_id: &str) -> Vec<LintWarning> {
+    match rule_linter(state)
+        .lint_rule(DEFAULT_ORG_ID, DEFAULT_USER_ID, rule_id)
+        .await
+    {
+        Ok(warnings) => warnings,
+        Err(e) => {
+            tracing::warn!("Failed to lint deterministic rule {}: {}", rule_id, e);
+            Vec::new()
+        }
+    }
+}
+
+/// GET /api/rules/lint
+///
+/// Run static analysis over all enabled deterministic rules and report shadowed,
+/// conflicting and unreachable rules, bad regexes and unknown label references.
+async fn lint_deterministic_rules(State(state): State<AppState>) -> impl IntoResponse {
+    match rule_linter(&state)
+        .lint(DEFAULT_ORG_ID, DEFAULT_USER_ID)
+        .await
+    {
+        Ok(warnings) => (StatusCode::OK, Json(LintRulesResponse { warnings })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to lint deterministic rules: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal("Failed to lint deterministic rules")),
+            )
+                .into_response()
+        }
+    }
+}
+
 /// Request body for swapping priorities between two deterministic rules.
 #[derive(Debug, Deserialize)]
 pub struct SwapPrioritiesRequest {
@@ -713,6 +863,12 @@ pub struct CreateLlmRuleRequest {
     pub rule_text: String,
     pub enabled: Option<bool>,
     pub metadata_json: Option<Value>,
+    /// The rule only applies from this instant.
+    pub active_from: Option<DateTime<Utc>>,
+    /// The rule stops applying at this instant and is then auto-disabled.
+    pub active_until: Option<DateTime<Utc>>,
+    /// Recurring weekly window, e.g. weekdays 09:00-17:00 in a timezone.
+    pub schedule: Option<RuleSchedule>,
 }
 
 /// POST /api/rules/llm
@@ -747,6 +903,14 @@ async fn create_llm_rule(
         body.scope_ref
     };
 
+    if let Err(e) = validate_window(body.active_from, body.active_until, body.schedule.as_ref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(e.to_string())),
+        )
+            .into_response();
+    }
+
     let new_rule = NewLlmRule {
         org_id: DEFAULT_ORG_ID,
         user_id: Some(DEFAULT_USER_ID),
@@ -759,6 +923,10 @@ async fn create_llm_rule(
         metadata_json: body
             .metadata_json
             .unwrap_or(Value::Object(Default::default())),
+        disabled_reason: None,
+        active_from: body.active_from,
+        active_until: body.active_until,
+        schedule: body.schedule,
     };
 
     let repo = LlmRuleRepository::new(state.db.clone());
@@ -797,8 +965,18 @@ pub struct UpdateLlmRuleRequest {
     #[serde(default, deserialize_with = "nullable::deserialize")]
     pub scope_ref: Option<Option<String>>,
     pub rule_text: Option<String>,
+    /// Re-enabling a rule clears its disabled_reason.
     pub enabled: Option<bool>,
     pub metadata_json: Option<Value>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub active_from: Option<Option<DateTime<Utc>>>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub active_until: Option<Option<DateTime<Utc>>>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub schedule: Option<Option<RuleSchedule>>,
 }
 
 /// PATCH /api/rules/llm/:id
@@ -858,6 +1036,22 @@ async fn update_llm_rule(
         }
     };
 
+    let active_from = nullable::merge(body.active_from, existing.active_from);
+    let active_until = nullable::merge(body.active_until, existing.active_until);
+    let schedule = nullable::merge(body.schedule, existing.schedule);
+    if let Err(e) = validate_window(active_from, active_until, schedule.as_ref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request(e.to_string())),
+        )
+            .into_response();
+    }
+
+    let disabled_reason = match body.enabled {
+        Some(true) => None,
+        _ => existing.disabled_reason,
+    };
+
     let updated_rule = NewLlmRule {
         org_id: existing.org_id,
         user_id: existing.user_id,
@@ -867,7 +1061,11 @@ async fn update_llm_rule(
         scope_ref,
         rule_text: body.rule_text.unwrap_or(existing.rule_text),
         enabled: body.enabled.unwrap_or(existing.enabled),
+        disabled_reason,
         metadata_json: body.metadata_json.unwrap_or(existing.metadata_json),
+        active_from,
+        active_until,
+        schedule,
     };
 
     match repo
@@ -924,10 +1122,355 @@ async fn delete_llm_rule(
     }
 }
 
+// ============================================================================
+// Sender Lists Endpoints
+// ============================================================================
+
+/// GET /api/rules/sender-lists
+///
+/// List all sender lists, sorted by name.
+async fn list_sender_lists(State(state): State<AppState>) -> impl IntoResponse {
+    let repo = SenderListRepository::new(state.db.clone());
+
+    match repo.list_all(DEFAULT_ORG_ID, DEFAULT_USER_ID).await {
+        Ok(lists) => (StatusCode::OK, Json(lists)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list sender lists: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal("Failed to list sender lists")),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// GET /api/rules/sender-lists/:id
+///
+/// Get a single sender list by ID.
+async fn get_sender_list(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let repo = SenderListRepository::new(state.db.clone());
+
+    match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(list) => (StatusCode::OK, Json(list)).into_response(),
+        Err(SenderListError::NotFound(_)) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Sender list not found: {}",
+                id
+            ))),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get sender list {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to get sender list: {}",
+                    e
+                ))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Request body for creating a sender list.
+#[derive(Debug, Deserialize)]
+pub struct CreateSenderListRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Email addresses, `*@domain` wildcards, or bare domains. Defaults to empty.
+    pub entries: Option<Vec<String>>,
+}
+
+/// POST /api/rules/sender-lists
+///
+/// Create a new sender list.
+async fn create_sender_list(
+    State(state): State<AppState>,
+    Json(body): Json<CreateSenderListRequest>,
+) -> impl IntoResponse {
+    if body.name.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::bad_request("Name is required")),
+        )
+            .into_response();
+    }
+
+    let new_list = NewSenderList {
+        org_id: DEFAULT_ORG_ID,
+        user_id: Some(DEFAULT_USER_ID),
+        name: body.name,
+        description: body.description,
+        entries: body.entries.unwrap_or_default(),
+    };
+
+    let repo = SenderListRepository::new(state.db.clone());
+
+    match repo.create(new_list).await {
+        Ok(list) => (StatusCode::CREATED, Json(list)).into_response(),
+        Err(SenderListError::DuplicateName(name)) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "Sender list already exists: {}",
+                name
+            ))),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to create sender list: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to create sender list: {}",
+                    e
+                ))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Request body for updating a sender list.
+/// All fields are optional for partial updates. `entries` replaces the whole list.
+#[derive(Debug, Deserialize)]
+pub struct UpdateSenderListRequest {
+    pub name: Option<String>,
+    /// Can be cleared by sending null.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub description: Option<Option<String>>,
+    pub entries: Option<Vec<String>>,
+}
+
+/// Names of rules whose conditions reference a sender list, for conflict responses.
+/// Returns `Err` with a ready-made error response when the lookup fails.
+async fn rules_referencing_sender_list(
+    state: &AppState,
+    list_name: &str,
+) -> Result<Vec<String>, axum::response::Response> {
+    let rule_repo = DeterministicRuleRepository::new(state.db.clone());
+    match rule_repo
+        .find_rules_referencing_sender_list(DEFAULT_ORG_ID, DEFAULT_USER_ID, list_name)
+        .await
+    {
+        Ok(rules) => Ok(rules.into_iter().map(|rule| rule.name).collect()),
+        Err(e) => {
+            tracing::error!(
+                "Failed to find rules referencing sender list {}: {}",
+                list_name,
+                e
+            );
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to find rules referencing sender list: {}",
+                    e
+                ))),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// PATCH /api/rules/sender-lists/:id
+///
+/// Update an existing sender list. Changes apply to every rule that references the list.
+/// Renaming a list that rules still reference is rejected, since those rules would stop
+/// matching.
+async fn update_sender_list(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateSenderListRequest>,
+) -> impl IntoResponse {
+    let repo = SenderListRepository::new(state.db.clone());
+
+    let existing = match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(list) => list,
+        Err(SenderListError::NotFound(_)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Sender list not found: {}",
+                    id
+                ))),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch sender list {}: {}", id, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to fetch sender list: {}",
+                    e
+                ))),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(name) = &body.name {
+        if name.trim().is_empty() {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::bad_request("Name cannot be empty")),
+            )
+                .into_response();
+        }
+
+        if !name.trim().eq_ignore_ascii_case(&existing.name) {
+            let referencing = match rules_referencing_sender_list(&state, &existing.name).await {
+                Ok(names) => names,
+                Err(response) => return response,
+            };
+            if !referencing.is_empty() {
+                return (
+                    StatusCode::CONFLICT,
+                    Json(ApiError::conflict(format!(
+                        "Sender list '{}' is referenced by rules: {}",
+                        existing.name,
+                        referencing.join(", ")
+                    ))),
+                )
+                    .into_response();
+            }
+        }
+    }
+
+    let description = match body.description {
+        None => existing.description,
+        Some(None) => None,
+        Some(Some(v)) => Some(v),
+    };
+
+    let updated_list = NewSenderList {
+        org_id: existing.org_id,
+        user_id: existing.user_id,
+        name: body.name.unwrap_or(existing.name),
+        description,
+        entries: body.entries.unwrap_or(existing.entries),
+    };
+
+    match repo
+        .update(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id, updated_list)
+        .await
+    {
+        Ok(list) => (StatusCode::OK, Json(list)).into_response(),
+        Err(SenderListError::NotFound(_)) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Sender list not found: {}",
+                id
+            ))),
+        )
+            .into_response(),
+        Err(SenderListError::DuplicateName(name)) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "Sender list already exists: {}",
+                name
+            ))),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to update sender list {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to update sender list: {}",
+                    e
+                ))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// DELETE /api/rules/sender-lists/:id
+///
+/// Delete a sender list. Lists still referenced by rules cannot be deleted.
+async fn delete_sender_list(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let repo = SenderListRepository::new(state.db.clone());
+
+    let existing = match repo.get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(list) => list,
+        Err(SenderListError::NotFound(_)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Sender list not found: {}",
+                    id
+                ))),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch sender list {}: {}", id, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to fetch sender list: {}",
+                    e
+                ))),
+            )
+                .into_response();
+        }
+    };
+
+    let referencing = match rules_referencing_sender_list(&state, &existing.name).await {
+        Ok(names) => names,
+        Err(response) => return response,
+    };
+    if !referencing.is_empty() {
+        return (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "Sender list '{}' is referenced by rules: {}",
+                existing.name,
+                referencing.join(", ")
+            ))),
+        )
+            .into_response();
+    }
+
+    match repo.delete(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(SenderListError::NotFound(_)) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Sender list not found: {}",
+                id
+            ))),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to delete sender list {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to delete sender list: {}",
+                    e
+                ))),
+            )
+                .into_response()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ashford_core::{Database, DeterministicRule, LlmRule, migrations::run_migrations};
+    use ashford_core::{
+        Database, DeterministicRule, LlmRule, SenderList, migrations::run_migrations,
+    };
     use axum::body::to_bytes;
     use serde_json::json;
     use tempfile::TempDir;
@@ -971,6 +1514,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: Some(json!({})),
             safe_mode: Some(SafeMode::Default),
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = create_deterministic_rule(State(state), Json(request))
@@ -1003,6 +1549,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = create_deterministic_rule(State(state), Json(request))
@@ -1029,6 +1578,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_deterministic_rule(State(state.clone()), Json(create_request))
@@ -1052,6 +1604,9 @@ mod tests {
             action_type: None,
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let update_response =
@@ -1089,6 +1644,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_deterministic_rule(State(state.clone()), Json(create_request))
@@ -1154,6 +1712,9 @@ mod tests {
             rule_text: "Archive all newsletters".to_string(),
             enabled: Some(true),
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = create_llm_rule(State(state), Json(request))
@@ -1183,6 +1744,9 @@ mod tests {
             rule_text: "".to_string(),
             enabled: None,
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = create_llm_rule(State(state), Json(request))
@@ -1206,6 +1770,9 @@ mod tests {
             rule_text: "Original rule text".to_string(),
             enabled: Some(true),
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_llm_rule(State(state.clone()), Json(create_request))
@@ -1225,6 +1792,9 @@ mod tests {
             rule_text: Some("Updated rule text".to_string()),
             enabled: None,
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let update_response =
@@ -1255,6 +1825,9 @@ mod tests {
             rule_text: "Some rule text".to_string(),
             enabled: None,
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_llm_rule(State(state.clone()), Json(create_request))
@@ -1301,6 +1874,9 @@ mod tests {
                 action_type: "archive".to_string(),
                 action_parameters_json: None,
                 safe_mode: None,
+                active_from: None,
+                active_until: None,
+                schedule: None,
             };
             create_deterministic_rule(State(state.clone()), Json(request)).await;
         }
@@ -1350,6 +1926,9 @@ mod tests {
             action_type: None,
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = update_deterministic_rule(
@@ -1379,6 +1958,9 @@ mod tests {
             action_type: "".to_string(), // Empty action_type
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = create_deterministic_rule(State(state), Json(request))
@@ -1404,6 +1986,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = create_deterministic_rule(State(state), Json(request))
@@ -1426,6 +2011,9 @@ mod tests {
             rule_text: "Some rule text".to_string(),
             enabled: None,
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = create_llm_rule(State(state), Json(request))
@@ -1460,6 +2048,9 @@ mod tests {
             rule_text: None,
             enabled: None,
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let response = update_llm_rule(
@@ -1502,6 +2093,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_deterministic_rule(State(state.clone()), Json(create_request))
@@ -1541,6 +2135,9 @@ mod tests {
             rule_text: "Archive newsletters".to_string(),
             enabled: Some(true),
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_llm_rule(State(state.clone()), Json(create_request))
@@ -1583,6 +2180,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_deterministic_rule(State(state.clone()), Json(create_request))
@@ -1607,6 +2207,9 @@ mod tests {
             action_type: None,
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let update_response =
@@ -1636,6 +2239,9 @@ mod tests {
             rule_text: "Some rule text".to_string(),
             enabled: Some(true),
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_llm_rule(State(state.clone()), Json(create_request))
@@ -1656,6 +2262,9 @@ mod tests {
             rule_text: None,
             enabled: Some(false),
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let update_response =
@@ -1671,6 +2280,84 @@ mod tests {
         assert!(!updated.enabled);
     }
 
+    #[tokio::test]
+    async fn create_deterministic_rule_rejects_invalid_active_window() {
+        let (db, _dir) = setup_db().await;
+        let state = crate::AppState { db: db.clone() };
+
+        let request: CreateDeterministicRuleRequest = serde_json::from_value(json!({
+            "name": "Office hours",
+            "conditions_json": {"type": "sender_domain", "value": "example.com"},
+            "action_type": "archive",
+            "schedule": {"days": ["mon"], "start": "9am", "end": "17:00"}
+        }))
+        .expect("request");
+        let response = create_deterministic_rule(State(state.clone()), Json(request))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let request: CreateDeterministicRuleRequest = serde_json::from_value(json!({
+            "name": "Vacation",
+            "conditions_json": {"type": "sender_domain", "value": "example.com"},
+            "action_type": "archive",
+            "active_from": "2024-07-15T00:00:00Z",
+            "active_until": "2024-07-01T00:00:00Z"
+        }))
+        .expect("request");
+        let response = create_deterministic_rule(State(state), Json(request))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn update_llm_rule_sets_schedule_and_reenabling_clears_disabled_reason() {
+        let (db, _dir) = setup_db().await;
+        let state = crate::AppState { db: db.clone() };
+        let repo = LlmRuleRepository::new(db);
+
+        let request: CreateLlmRuleRequest = serde_json::from_value(json!({
+            "name": "Conference",
+            "rule_text": "Keep conference mail in the inbox",
+            "active_until": "2024-07-01T00:00:00Z"
+        }))
+        .expect("request");
+        let response = create_llm_rule(State(state.clone()), Json(request))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let created: LlmRule = serde_json::from_slice(&body_bytes).expect("json body");
+        repo.disable_rule_with_reason(DEFAULT_ORG_ID, DEFAULT_USER_ID, &created.id, "expired")
+            .await
+            .expect("disable");
+
+        let request: UpdateLlmRuleRequest = serde_json::from_value(json!({
+            "enabled": true,
+            "active_until": null,
+            "schedule": {"days": ["mon", "tue", "wed", "thu", "fri"], "start": "09:00", "end": "17:00", "timezone": "America/New_York"}
+        }))
+        .expect("request");
+        let response = update_llm_rule(State(state), Path(created.id.clone()), Json(request))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let updated: LlmRule = serde_json::from_slice(&body_bytes).expect("json body");
+        assert!(updated.enabled);
+        assert_eq!(updated.disabled_reason, None);
+        assert_eq!(updated.active_until, None);
+        assert_eq!(
+            updated.schedule.map(|s| s.timezone),
+            Some("America/New_York".to_string())
+        );
+    }
+
     // ========================================================================
     // Task 20: Tests for nullable fields - clearing optional fields with null
     // ========================================================================
@@ -1794,6 +2481,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_deterministic_rule(State(state.clone()), Json(create_request))
@@ -1844,6 +2534,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_deterministic_rule(State(state.clone()), Json(create_request))
@@ -1898,6 +2591,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_deterministic_rule(State(state.clone()), Json(create_request))
@@ -1953,6 +2649,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_deterministic_rule(State(state.clone()), Json(create_request))
@@ -2007,6 +2706,9 @@ mod tests {
             rule_text: "Archive newsletters".to_string(),
             enabled: None,
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_llm_rule(State(state.clone()), Json(create_request))
@@ -2056,6 +2758,9 @@ mod tests {
             rule_text: "Process emails".to_string(),
             enabled: None,
             metadata_json: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
 
         let create_response = create_llm_rule(State(state.clone()), Json(create_request))
@@ -2279,6 +2984,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
         let response_a = create_deterministic_rule(State(state.clone()), Json(create_request_a))
             .await
@@ -2298,6 +3006,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
         let response_b = create_deterministic_rule(State(state.clone()), Json(create_request_b))
             .await
@@ -2367,6 +3078,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
         let response_b = create_deterministic_rule(State(state.clone()), Json(create_request_b))
             .await
@@ -2405,6 +3119,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
         let response_a = create_deterministic_rule(State(state.clone()), Json(create_request_a))
             .await
@@ -2479,6 +3196,9 @@ mod tests {
             action_type: "archive".to_string(),
             action_parameters_json: None,
             safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
         };
         let response = create_deterministic_rule(State(state.clone()), Json(create_request))
             .await
@@ -2519,6 +3239,9 @@ mod tests {
                 action_type: "archive".to_string(),
                 action_parameters_json: None,
                 safe_mode: None,
+                active_from: None,
+                active_until: None,
+                schedule: None,
             };
             let response = create_deterministic_rule(State(state.clone()), Json(create_request))
                 .await
@@ -2557,4 +3280,220 @@ mod tests {
         assert_eq!(rules[2].name, "Rule 1");
         assert_eq!(rules[2].priority, 30);
     }
+
+    fn lint_test_request(name: &str, action_type: &str) -> CreateDeterministicRuleRequest {
+        CreateDeterministicRuleRequest {
+            name: name.to_string(),
+            description: None,
+            scope: Some(RuleScope::Global),
+            scope_ref: None,
+            priority: None,
+            enabled: Some(true),
+            conditions_json: json!({"type": "subject_contains", "value": "sale"}),
+            action_type: action_type.to_string(),
+            action_parameters_json: None,
+            safe_mode: None,
+            active_from: None,
+            active_until: None,
+            schedule: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_deterministic_rule_returns_lint_warnings() {
+        let (db, _dir) = setup_db().await;
+        let state = crate::AppState { db: db.clone() };
+
+        let first = create_deterministic_rule(
+            State(state.clone()),
+            Json(lint_test_request("Archive sales", "archive")),
+        )
+        .await
+        .into_response();
+        assert_eq!(first.status(), StatusCode::CREATED);
+        let body_bytes = to_bytes(first.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let body: Value = serde_json::from_slice(&body_bytes).expect("json body");
+        assert_eq!(body["lint_warnings"], json!([]));
+        let first_id = body["id"].as_str().expect("id").to_string();
+
+        let second = create_deterministic_rule(
+            State(state),
+            Json(lint_test_request("Delete sales", "delete")),
+        )
+        .await
+        .into_response();
+        assert_eq!(second.status(), StatusCode::CREATED);
+        let body_bytes = to_bytes(second.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+
+        // The rule fields are still readable as a plain DeterministicRule.
+        let rule: DeterministicRule = serde_json::from_slice(&body_bytes).expect("rule body");
+        assert_eq!(rule.name, "Delete sales");
+
+        let body: Value = serde_json::from_slice(&body_bytes).expect("json body");
+        let warnings: Vec<LintWarning> =
+            serde_json::from_value(body["lint_warnings"].clone()).expect("warnings");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, ashford_core::LintKind::ConflictingActions);
+        assert_eq!(warnings[0].rule_id, rule.id);
+        assert_eq!(
+            warnings[0].related_rule_id.as_deref(),
+            Some(first_id.as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn lint_deterministic_rules_reports_all_warnings() {
+        let (db, _dir) = setup_db().await;
+        let state = crate::AppState { db: db.clone() };
+
+        let mut invalid = lint_test_request("Bad regex", "archive");
+        invalid.conditions_json = json!({"type": "subject_regex", "value": "(unclosed"});
+        let created = create_deterministic_rule(State(state.clone()), Json(invalid))
+            .await
+            .into_response();
+        assert_eq!(created.status(), StatusCode::CREATED);
+
+        let response = lint_deterministic_rules(State(state)).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let body: LintRulesResponse = serde_json::from_slice(&body_bytes).expect("json body");
+        assert_eq!(body.warnings.len(), 1);
+        assert_eq!(body.warnings[0].kind, ashford_core::LintKind::InvalidRegex);
+        assert_eq!(body.warnings[0].rule_name, "Bad regex");
+    }
+
+    async fn create_test_sender_list(state: &crate::AppState, name: &str) -> SenderList {
+        let response = create_sender_list(
+            State(state.clone()),
+            Json(CreateSenderListRequest {
+                name: name.to_string(),
+                description: None,
+                entries: Some(vec!["Boss@Corp.com".to_string(), "partner.com".to_string()]),
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        serde_json::from_slice(&body_bytes).expect("json body")
+    }
+
+    #[tokio::test]
+    async fn create_sender_list_normalizes_entries_and_rejects_duplicates() {
+        let (db, _dir) = setup_db().await;
+        let state = crate::AppState { db: db.clone() };
+
+        let list = create_test_sender_list(&state, "vips").await;
+        assert_eq!(list.entries, vec!["boss@corp.com", "partner.com"]);
+
+        let response = create_sender_list(
+            State(state),
+            Json(CreateSenderListRequest {
+                name: "VIPS".to_string(),
+                description: None,
+                entries: None,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn update_sender_list_replaces_entries() {
+        let (db, _dir) = setup_db().await;
+        let state = crate::AppState { db: db.clone() };
+        let list = create_test_sender_list(&state, "vips").await;
+
+        let body: UpdateSenderListRequest =
+            serde_json::from_value(json!({"entries": ["*@family.org"], "description": "Family"}))
+                .expect("request");
+        let response = update_sender_list(State(state), Path(list.id.clone()), Json(body))
+            .await
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let updated: SenderList = serde_json::from_slice(&body_bytes).expect("json body");
+        assert_eq!(updated.name, "vips");
+        assert_eq!(updated.entries, vec!["*@family.org"]);
+        assert_eq!(updated.description.as_deref(), Some("Family"));
+    }
+
+    #[tokio::test]
+    async fn referenced_sender_list_cannot_be_deleted_or_renamed() {
+        let (db, _dir) = setup_db().await;
+        let state = crate::AppState { db: db.clone() };
+        let list = create_test_sender_list(&state, "vips").await;
+
+        let mut rule_request = lint_test_request("Star VIPs", "star");
+        rule_request.conditions_json = json!({"type": "in_list", "list": "vips"});
+        let response = create_deterministic_rule(State(state.clone()), Json(rule_request))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let body: Value = serde_json::from_slice(&body_bytes).expect("json body");
+        assert_eq!(body["lint_warnings"], json!([]));
+        let rule_id = body["id"].as_str().expect("id").to_string();
+
+        let rename: UpdateSenderListRequest =
+            serde_json::from_value(json!({"name": "important"})).expect("request");
+        let response =
+            update_sender_list(State(state.clone()), Path(list.id.clone()), Json(rename))
+                .await
+                .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = delete_sender_list(State(state.clone()), Path(list.id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let body: Value = serde_json::from_slice(&body_bytes).expect("json body");
+        assert!(body["message"].as_str().unwrap().contains("Star VIPs"));
+
+        let response = delete_deterministic_rule(State(state.clone()), Path(rule_id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = delete_sender_list(State(state), Path(list.id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn create_rule_with_unknown_sender_list_warns() {
+        let (db, _dir) = setup_db().await;
+        let state = crate::AppState { db: db.clone() };
+
+        let mut rule_request = lint_test_request("Star family", "star");
+        rule_request.conditions_json = json!({"type": "in_list", "list": "family"});
+        let response = create_deterministic_rule(State(state), Json(rule_request))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let body: Value = serde_json::from_slice(&body_bytes).expect("json body");
+        assert_eq!(body["lint_warnings"][0]["kind"], "unknown_sender_list");
+    }
 }
@@ -0,0 +1,138 @@
+//! LLM spend API endpoints.
+//!
+//! Provides:
+//! - GET /api/spend - Daily and monthly LLM spend by feature and account, plus exceeded budgets
+
+use axum::{Extension, Json, Router, http::StatusCode, response::IntoResponse, routing::get};
+use chrono::Utc;
+use serde::Serialize;
+
+use ashford_core::{DEFAULT_ORG_ID, DEFAULT_USER_ID, SpendTracker};
+
+use crate::AppState;
+
+/// Create the spend API router.
+///
+/// Handlers read the [`SpendTracker`] from a request extension so pricing and budgets
+/// come from the loaded configuration.
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(get_spend))
+}
+
+/// Error response for API errors.
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    message: String,
+}
+
+impl ApiError {
+    fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+        }
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new("internal_error", message)
+    }
+}
+
+/// GET /api/spend
+///
+/// Spend for the current UTC day and month, with every budget limit currently exceeded.
+async fn get_spend(Extension(spend): Extension<SpendTracker>) -> impl IntoResponse {
+    match spend
+        .report(DEFAULT_ORG_ID, DEFAULT_USER_ID, Utc::now())
+        .await
+    {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load LLM spend: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal(format!(
+                    "Failed to load LLM spend: {}",
+                    e
+                ))),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ashford_core::{
+        BudgetConfig, Database, LlmCallContext, LlmCallRepository, ModelPricing, NewLlmCall,
+        PricingConfig, migrations::run_migrations,
+    };
+    use axum::body::to_bytes;
+    use serde_json::Value;
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("test.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    #[tokio::test]
+    async fn get_spend_reports_cost_and_exceeded_budgets() {
+        let (db, _dir) = setup_db().await;
+        let mut context = LlmCallContext::new("classify");
+        context.account_id = Some("acc-test".into());
+        LlmCallRepository::new(db.clone())
+            .create(NewLlmCall {
+                org_id: DEFAULT_ORG_ID,
+                user_id: DEFAULT_USER_ID,
+                context,
+                model: "openai::gpt-4o-mini".into(),
+                request_json: serde_json::json!({}),
+                response_json: None,
+                input_tokens: Some(2_000_000),
+                output_tokens: Some(500_000),
+                latency_ms: Some(300),
+                error: None,
+                trace_id: None,
+            })
+            .await
+            .expect("log call");
+
+        let pricing = PricingConfig {
+            models: vec![ModelPricing {
+                model: "openai::gpt-4o-mini".into(),
+                input_per_million: 0.5,
+                output_per_million: 2.0,
+            }],
+        };
+        let budget = BudgetConfig {
+            monthly_usd: Some(1.5),
+            ..Default::default()
+        };
+        let spend = SpendTracker::new(db, pricing, budget);
+
+        let response = get_spend(Extension(spend)).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        let body: Value = serde_json::from_slice(&bytes).expect("json body");
+
+        assert_eq!(body["daily"]["total"]["calls"], 1);
+        assert_eq!(body["daily"]["total"]["cost_usd"], 2.0);
+        assert_eq!(body["monthly"]["by_feature"]["classify"]["cost_usd"], 2.0);
+        assert_eq!(
+            body["monthly"]["by_account"]["acc-test"]["input_tokens"],
+            2_000_000
+        );
+        let exceeded = body["exceeded"].as_array().expect("exceeded");
+        assert_eq!(exceeded.len(), 1);
+        assert_eq!(exceeded[0]["window"], "monthly");
+        assert_eq!(exceeded[0]["limit_usd"], 1.5);
+    }
+}
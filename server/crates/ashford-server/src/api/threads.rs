@@ -0,0 +1,218 @@
+//! Threads API endpoints.
+//!
+//! Provides:
+//! - GET /api/threads/:id - Get a thread with its rolling summary
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+};
+use serde::Serialize;
+
+use ashford_core::{
+    DEFAULT_ORG_ID, DEFAULT_USER_ID, MessageRepository, ThreadDetail, ThreadError,
+    ThreadRepository, ThreadSummaryRepository,
+};
+
+use crate::AppState;
+
+/// Create the threads API router.
+pub fn router() -> Router<AppState> {
+    Router::new().route("/{id}", get(get_thread))
+}
+
+/// Error response for API errors.
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    message: String,
+}
+
+impl ApiError {
+    fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new("not_found", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new("internal_error", message)
+    }
+}
+
+fn internal_error(
+    context: &str,
+    id: &str,
+    error: impl std::fmt::Display,
+) -> axum::response::Response {
+    tracing::error!("Failed to {} for thread {}: {}", context, id, error);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiError::internal(format!(
+            "Failed to {}: {}",
+            context, error
+        ))),
+    )
+        .into_response()
+}
+
+/// GET /api/threads/:id
+///
+/// Get a thread by its internal ID, with its rolling summary if one has been generated.
+async fn get_thread(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let thread = match ThreadRepository::new(state.db.clone())
+        .get_by_id(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id)
+        .await
+    {
+        Ok(thread) => thread,
+        Err(ThreadError::NotFound(_)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!("Thread not found: {}", id))),
+            )
+                .into_response();
+        }
+        Err(e) => return internal_error("get thread", &id, e),
+    };
+
+    let message_count = match MessageRepository::new(state.db.clone())
+        .count_by_thread(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id)
+        .await
+    {
+        Ok(count) => count,
+        Err(e) => return internal_error("count thread messages", &id, e),
+    };
+
+    let summary = match ThreadSummaryRepository::new(state.db.clone())
+        .get(DEFAULT_ORG_ID, DEFAULT_USER_ID, &id)
+        .await
+    {
+        Ok(summary) => summary,
+        Err(e) => return internal_error("get thread summary", &id, e),
+    };
+
+    let detail = ThreadDetail {
+        id: thread.id,
+        account_id: thread.account_id,
+        provider_thread_id: thread.provider_thread_id,
+        subject: thread.subject,
+        snippet: thread.snippet,
+        last_message_at: thread.last_message_at,
+        message_count,
+        summary,
+    };
+    (StatusCode::OK, Json(detail)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ashford_core::{Database, NewThreadSummary, migrations::run_migrations};
+    use axum::body::to_bytes;
+    use chrono::Utc;
+    use libsql::params;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("test.sqlite");
+        let db = Database::new(&db_path).await.expect("create db");
+        run_migrations(&db).await.expect("migrations");
+        (db, dir)
+    }
+
+    async fn seed_thread(db: &Database) -> String {
+        let now = Utc::now().to_rfc3339();
+        let conn = db.connection().await.expect("conn");
+        conn.execute(
+            "INSERT INTO accounts (id, provider, email, display_name, config_json, state_json, created_at, updated_at, org_id, user_id)
+             VALUES ('acc-test', 'gmail', 'user@example.com', 'User', '{}', '{}', ?1, ?1, 1, 1)",
+            params![now.clone()],
+        )
+        .await
+        .expect("insert account");
+        conn.execute(
+            "INSERT INTO threads (id, account_id, provider_thread_id, subject, snippet, last_message_at, metadata_json, raw_json, created_at, updated_at, org_id, user_id)
+             VALUES ('thr-test', 'acc-test', 'prov-thread', 'Subject', 'Snippet', ?1, '{}', '{}', ?1, ?1, 1, 1)",
+            params![now.clone()],
+        )
+        .await
+        .expect("insert thread");
+        conn.execute(
+            "INSERT INTO messages (id, account_id, thread_id, provider_message_id, from_email, from_name, to_json, cc_json, bcc_json, subject, snippet, received_at, internal_date, labels_json, headers_json, body_plain, body_html, raw_json, created_at, updated_at, org_id, user_id)
+             VALUES ('msg-test', 'acc-test', 'thr-test', 'prov-msg', 'sender@example.com', 'Sender', '[]', '[]', '[]', 'Subject', 'Snippet', ?1, ?1, '[]', '[]', NULL, NULL, '{}', ?1, ?1, 1, 1)",
+            params![now],
+        )
+        .await
+        .expect("insert message");
+        "thr-test".to_string()
+    }
+
+    async fn get_json(db: &Database, id: &str) -> (StatusCode, serde_json::Value) {
+        let state = crate::AppState { db: db.clone() };
+        let response = get_thread(State(state), Path(id.to_string()))
+            .await
+            .into_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body bytes");
+        (status, serde_json::from_slice(&body).expect("json body"))
+    }
+
+    #[tokio::test]
+    async fn get_thread_returns_summary_when_generated() {
+        let (db, _dir) = setup_db().await;
+        let thread_id = seed_thread(&db).await;
+
+        let (status, body) = get_json(&db, &thread_id).await;
+        assert_eq!(status, StatusCode::OK, "body: {}", body);
+        assert_eq!(body["message_count"], json!(1));
+        assert!(body["summary"].is_null());
+
+        ThreadSummaryRepository::new(db.clone())
+            .upsert(
+                DEFAULT_ORG_ID,
+                DEFAULT_USER_ID,
+                NewThreadSummary {
+                    thread_id: thread_id.clone(),
+                    account_id: "acc-test".into(),
+                    summary: "Sender wants a reply.".into(),
+                    participants: vec!["sender@example.com".into()],
+                    open_questions: vec![],
+                    commitments: vec!["User replies by Monday".into()],
+                    message_count: 1,
+                    last_message_id: "msg-test".into(),
+                    model: "test-model".into(),
+                    llm_call_id: None,
+                },
+            )
+            .await
+            .expect("store summary");
+
+        let (status, body) = get_json(&db, &thread_id).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["summary"]["summary"], json!("Sender wants a reply."));
+        assert_eq!(
+            body["summary"]["commitments"],
+            json!(["User replies by Monday"])
+        );
+    }
+
+    #[tokio::test]
+    async fn get_thread_returns_not_found_for_unknown_thread() {
+        let (db, _dir) = setup_db().await;
+        let (status, body) = get_json(&db, "missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], json!("not_found"));
+    }
+}
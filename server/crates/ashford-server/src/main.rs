@@ -5,10 +5,10 @@ use std::{env, net::SocketAddr};
 
 use ashford_core::pubsub_listener::run_pubsub_supervisor;
 use ashford_core::{
-    Config, Database, GenaiLLMClient, JobDispatcher, JobQueue, WorkerConfig, init_telemetry,
-    migrations, run_worker,
+    Config, Database, GenaiLLMClient, JobDispatcher, JobQueue, SpendTracker, WorkerConfig,
+    init_telemetry, migrations, run_worker, schedule_digests,
 };
-use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
+use axum::{Extension, Json, Router, extract::State, http::StatusCode, routing::get};
 use serde::Serialize;
 use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
@@ -29,14 +29,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     migrations::run_migrations(&db).await?;
 
     let queue = JobQueue::new(db.clone());
-    let llm_client = Arc::new(GenaiLLMClient::new(db.clone(), config.model.clone()));
+    let llm_client = Arc::new(
+        GenaiLLMClient::new(db.clone(), config.model.clone())
+            .with_pricing(config.pricing.clone())
+            .with_routing(&config.routing),
+    );
     let dispatcher = JobDispatcher::new(
         db.clone(),
         reqwest::Client::new(),
         llm_client,
         config.policy.clone(),
     )
-    .with_gmail_config(config.gmail.clone());
+    .with_gmail_config(config.gmail.clone())
+    .with_decision_cache_config(config.decision_cache.clone())
+    .with_pricing_config(config.pricing.clone())
+    .with_budget_config(config.budget.clone())
+    .with_routing_config(config.routing.clone())
+    .with_direction_check_config(config.direction_check.clone())
+    .with_redaction_config(config.redaction.clone())
+    .with_similar_messages_config(config.similar_messages.clone())
+    .with_backfill_config(config.backfill.clone())
+    .with_thread_summary_config(config.thread_summaries.clone())
+    .with_digest_config(config.digest.clone())
+    .with_circuit_breaker_config(config.circuit_breaker.clone())
+    .with_approval_config(config.approval.clone())
+    .with_execution_delay_config(config.execution_delay)
+    .with_discord_config(config.discord.clone());
+    if let Err(err) = schedule_digests(&dispatcher, chrono::Utc::now()).await {
+        warn!("failed to schedule digests: {err}");
+    }
     let shutdown = CancellationToken::new();
     let worker_shutdown = shutdown.child_token();
     let worker_handle = tokio::spawn(run_worker(
@@ -52,7 +73,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ));
 
     let state = AppState { db: db.clone() };
-    let app = router(state);
+    let spend = SpendTracker::new(db.clone(), config.pricing.clone(), config.budget.clone());
+    let app = router(state, spend);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.app.port));
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -74,10 +96,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn router(state: AppState) -> Router {
+fn router(state: AppState, spend: SpendTracker) -> Router {
     Router::new()
         .route("/healthz", get(healthz))
         .nest("/api", api::router(state.clone()))
+        .layer(Extension(spend))
         .with_state(state)
 }
 
@@ -0,0 +1,18 @@
+-- Named sender lists referenced by the in_list rule condition
+CREATE TABLE sender_lists (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  description TEXT,
+  entries_json TEXT NOT NULL DEFAULT '[]',
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER
+);
+
+-- List names are unique per org/user, ignoring case
+CREATE UNIQUE INDEX sender_lists_org_user_name_uidx
+  ON sender_lists(org_id, COALESCE(user_id, 0), LOWER(name));
+
+-- Standard org/user index for multi-tenancy
+CREATE INDEX sender_lists_org_user_idx ON sender_lists(org_id, user_id);
@@ -0,0 +1,11 @@
+-- Optional active windows and recurring schedules for rules
+ALTER TABLE deterministic_rules ADD COLUMN active_from TEXT;
+ALTER TABLE deterministic_rules ADD COLUMN active_until TEXT;
+ALTER TABLE deterministic_rules ADD COLUMN schedule_json TEXT;
+
+ALTER TABLE llm_rules ADD COLUMN active_from TEXT;
+ALTER TABLE llm_rules ADD COLUMN active_until TEXT;
+ALTER TABLE llm_rules ADD COLUMN schedule_json TEXT;
+
+-- LLM rules are auto-disabled when their window ends, so they need a reason too
+ALTER TABLE llm_rules ADD COLUMN disabled_reason TEXT;
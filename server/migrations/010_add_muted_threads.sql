@@ -0,0 +1,19 @@
+-- Threads muted by a mute_thread action; new messages in them are auto-archived on ingest
+CREATE TABLE muted_threads (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  thread_id TEXT,
+  provider_thread_id TEXT NOT NULL,
+  action_id TEXT,
+  created_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1,
+  FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+-- A thread is muted at most once per account
+CREATE UNIQUE INDEX muted_threads_account_thread_uidx
+  ON muted_threads(account_id, provider_thread_id);
+
+-- Standard org/user index for multi-tenancy
+CREATE INDEX muted_threads_org_user_idx ON muted_threads(org_id, user_id);
@@ -0,0 +1,23 @@
+-- Known correspondents derived from mail the account has sent
+CREATE TABLE contacts (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  email TEXT NOT NULL,
+  name TEXT,
+  sent_count INTEGER NOT NULL DEFAULT 0,
+  reply_count INTEGER NOT NULL DEFAULT 0,
+  received_count INTEGER NOT NULL DEFAULT 0,
+  last_sent_at TEXT,
+  last_interaction_at TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1,
+  FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+-- Emails are stored lowercased, one contact per account and address
+CREATE UNIQUE INDEX contacts_account_email_uidx ON contacts(account_id, email);
+
+-- Standard org/user index for multi-tenancy
+CREATE INDEX contacts_org_user_idx ON contacts(org_id, user_id);
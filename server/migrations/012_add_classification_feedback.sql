@@ -0,0 +1,34 @@
+-- Corrections learned from undone and rejected actions, used as few-shot examples
+CREATE TABLE classification_feedback (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  message_id TEXT NOT NULL,
+  action_id TEXT NOT NULL,
+  decision_id TEXT,
+  source TEXT NOT NULL CHECK (source IN ('undo', 'rejection')),
+  sender_email TEXT,
+  sender_domain TEXT,
+  subject TEXT,
+  snippet TEXT,
+  wrong_action_type TEXT NOT NULL,
+  wrong_parameters_json TEXT NOT NULL DEFAULT '{}',
+  corrected_action_type TEXT,
+  corrected_parameters_json TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1,
+  FOREIGN KEY (account_id) REFERENCES accounts(id),
+  FOREIGN KEY (message_id) REFERENCES messages(id),
+  FOREIGN KEY (action_id) REFERENCES actions(id)
+);
+
+-- One correction per action, so recording is idempotent
+CREATE UNIQUE INDEX classification_feedback_action_uidx ON classification_feedback(action_id);
+
+CREATE INDEX classification_feedback_sender_idx
+  ON classification_feedback(org_id, user_id, sender_email);
+CREATE INDEX classification_feedback_domain_idx
+  ON classification_feedback(org_id, user_id, sender_domain);
+CREATE INDEX classification_feedback_created_idx
+  ON classification_feedback(org_id, user_id, created_at);
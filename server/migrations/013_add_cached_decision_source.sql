@@ -0,0 +1,47 @@
+-- Allow decisions reused from the sender-level decision cache (source = 'cached').
+-- SQLite cannot alter a CHECK constraint, so the table is rebuilt. Foreign keys from
+-- actions are deferred: dropping the old table orphans them until the rows are copied back.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMP TABLE decisions_backup AS SELECT * FROM decisions;
+
+DROP TABLE decisions;
+
+CREATE TABLE decisions (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  message_id TEXT NOT NULL,
+  source TEXT NOT NULL CHECK (source IN ('llm','deterministic','cached')),
+  decision_json TEXT NOT NULL,
+  action_type TEXT,
+  confidence REAL,
+  needs_approval INTEGER NOT NULL DEFAULT 0,
+  rationale TEXT,
+  telemetry_json TEXT NOT NULL DEFAULT '{}',
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1,
+  FOREIGN KEY (account_id) REFERENCES accounts(id),
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+INSERT INTO decisions (
+  id, account_id, message_id, source, decision_json, action_type, confidence,
+  needs_approval, rationale, telemetry_json, created_at, updated_at, org_id, user_id
+)
+SELECT
+  id, account_id, message_id, source, decision_json, action_type, confidence,
+  needs_approval, rationale, telemetry_json, created_at, updated_at, org_id, user_id
+FROM decisions_backup;
+
+DROP TABLE decisions_backup;
+
+CREATE INDEX decisions_message_idx
+  ON decisions(message_id);
+
+CREATE INDEX decisions_created_idx
+  ON decisions(created_at);
+
+CREATE INDEX decisions_org_user_idx
+  ON decisions(org_id, user_id);
@@ -0,0 +1,17 @@
+-- Embeddings of classified messages, searched for similar past messages during classification
+CREATE TABLE message_embeddings (
+  message_id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  model TEXT NOT NULL,
+  dimensions INTEGER NOT NULL,
+  vector BLOB NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1,
+  FOREIGN KEY (account_id) REFERENCES accounts(id),
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+CREATE INDEX message_embeddings_account_model_idx
+  ON message_embeddings(org_id, user_id, account_id, model, created_at);
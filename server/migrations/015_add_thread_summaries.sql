@@ -0,0 +1,22 @@
+-- Rolling LLM summaries of threads, updated as new messages arrive
+CREATE TABLE thread_summaries (
+  thread_id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  summary TEXT NOT NULL,
+  participants_json TEXT NOT NULL DEFAULT '[]',
+  open_questions_json TEXT NOT NULL DEFAULT '[]',
+  commitments_json TEXT NOT NULL DEFAULT '[]',
+  message_count INTEGER NOT NULL,
+  last_message_id TEXT NOT NULL,
+  model TEXT NOT NULL,
+  llm_call_id TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1,
+  FOREIGN KEY (account_id) REFERENCES accounts(id),
+  FOREIGN KEY (thread_id) REFERENCES threads(id)
+);
+
+CREATE INDEX thread_summaries_account_idx
+  ON thread_summaries(org_id, user_id, account_id, updated_at);
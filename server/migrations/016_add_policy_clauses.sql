@@ -0,0 +1,25 @@
+-- Safety policy clauses applied by the SafetyEnforcer on top of the [policy] config
+CREATE TABLE policy_clauses (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  description TEXT,
+  account_id TEXT,
+  effect TEXT NOT NULL,
+  min_confidence REAL,
+  action_types_json TEXT NOT NULL DEFAULT '[]',
+  senders_json TEXT NOT NULL DEFAULT '[]',
+  labels_json TEXT NOT NULL DEFAULT '[]',
+  enabled INTEGER NOT NULL DEFAULT 1,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER,
+  FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+-- Clause names are unique per org/user, ignoring case
+CREATE UNIQUE INDEX policy_clauses_org_user_name_uidx
+  ON policy_clauses(org_id, COALESCE(user_id, 0), LOWER(name));
+
+-- Standard org/user index for multi-tenancy
+CREATE INDEX policy_clauses_org_user_idx ON policy_clauses(org_id, user_id);
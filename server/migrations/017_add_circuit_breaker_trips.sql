@@ -0,0 +1,26 @@
+-- Automation circuit breaker trips. A trip without reset_at is open and holds new
+-- tracked actions of its account (or rule) for approval until it is reset by hand.
+CREATE TABLE circuit_breaker_trips (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  scope TEXT NOT NULL,
+  rule_id TEXT,
+  rule_name TEXT,
+  action_types_json TEXT NOT NULL DEFAULT '[]',
+  window_seconds INTEGER NOT NULL,
+  threshold INTEGER NOT NULL,
+  action_count INTEGER NOT NULL,
+  tripped_at TEXT NOT NULL,
+  reset_at TEXT,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1,
+  FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+-- At most one open trip per account or rule
+CREATE UNIQUE INDEX circuit_breaker_trips_open_uidx
+  ON circuit_breaker_trips(org_id, user_id, account_id, scope, COALESCE(rule_id, ''))
+  WHERE reset_at IS NULL;
+
+CREATE INDEX circuit_breaker_trips_org_user_tripped_idx
+  ON circuit_breaker_trips(org_id, user_id, tripped_at);
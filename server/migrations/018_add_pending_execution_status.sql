@@ -0,0 +1,54 @@
+-- Allow actions delayed before execution (status = 'pending_execution').
+-- SQLite cannot alter a CHECK constraint, so the table is rebuilt. Foreign keys from
+-- action_links and classification_feedback are deferred until the rows are copied back.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMP TABLE actions_backup AS SELECT * FROM actions;
+
+DROP TABLE actions;
+
+CREATE TABLE actions (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  message_id TEXT NOT NULL,
+  decision_id TEXT,
+  action_type TEXT NOT NULL,
+  parameters_json TEXT NOT NULL,
+  status TEXT NOT NULL CHECK (
+    status IN (
+      'queued','executing','completed','failed',
+      'canceled','rejected','approved_pending','pending_execution'
+    )
+  ),
+  error_message TEXT,
+  executed_at TEXT,
+  undo_hint_json TEXT NOT NULL DEFAULT '{}',
+  trace_id TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1,
+  FOREIGN KEY (account_id) REFERENCES accounts(id),
+  FOREIGN KEY (message_id) REFERENCES messages(id),
+  FOREIGN KEY (decision_id) REFERENCES decisions(id)
+);
+
+INSERT INTO actions (
+  id, account_id, message_id, decision_id, action_type, parameters_json, status,
+  error_message, executed_at, undo_hint_json, trace_id, created_at, updated_at, org_id, user_id
+)
+SELECT
+  id, account_id, message_id, decision_id, action_type, parameters_json, status,
+  error_message, executed_at, undo_hint_json, trace_id, created_at, updated_at, org_id, user_id
+FROM actions_backup;
+
+DROP TABLE actions_backup;
+
+CREATE INDEX actions_message_idx
+  ON actions(message_id, created_at);
+
+CREATE INDEX actions_status_idx
+  ON actions(status, created_at);
+
+CREATE INDEX actions_org_user_idx
+  ON actions(org_id, user_id);
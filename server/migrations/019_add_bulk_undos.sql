@@ -0,0 +1,15 @@
+-- Bulk undo requests. The undoable actions selected by the filter are stored so progress
+-- can be tracked through their undo.action jobs; skipped_json records why the others
+-- were left alone.
+CREATE TABLE bulk_undos (
+  id TEXT PRIMARY KEY,
+  filter_json TEXT NOT NULL,
+  action_ids_json TEXT NOT NULL DEFAULT '[]',
+  skipped_json TEXT NOT NULL DEFAULT '[]',
+  created_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE INDEX bulk_undos_org_user_created_idx
+  ON bulk_undos(org_id, user_id, created_at);
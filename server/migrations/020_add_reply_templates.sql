@@ -0,0 +1,23 @@
+-- Stored reply templates used by auto_reply actions. subject, body and body_html hold
+-- templates with {{variable}} placeholders and {{#if variable}} blocks; slots_json declares
+-- the values a rule or the LLM fills in.
+CREATE TABLE reply_templates (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  description TEXT,
+  subject TEXT,
+  body TEXT NOT NULL,
+  body_html TEXT,
+  slots_json TEXT NOT NULL DEFAULT '[]',
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  org_id INTEGER NOT NULL DEFAULT 1,
+  user_id INTEGER NOT NULL DEFAULT 1
+);
+
+-- Rules and the LLM refer to templates by name
+CREATE UNIQUE INDEX reply_templates_org_user_name_uidx
+  ON reply_templates(org_id, COALESCE(user_id, 0), LOWER(name));
+
+-- Standard org/user index for multi-tenancy
+CREATE INDEX reply_templates_org_user_idx ON reply_templates(org_id, user_id);
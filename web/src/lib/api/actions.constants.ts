@@ -16,7 +16,8 @@ export const ACTION_STATUSES = [
 	'failed',
 	'canceled',
 	'rejected',
-	'approved_pending'
+	'approved_pending',
+	'pending_execution'
 ] as const satisfies readonly ActionStatus[];
 
 /**
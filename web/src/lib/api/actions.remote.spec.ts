@@ -14,6 +14,7 @@
 import { describe, it, expect, vi, afterEach } from 'vitest';
 import { get, post, buildQueryString } from './client';
 import type {
+	Action,
 	ActionListItem,
 	ActionDetail,
 	PaginatedResponse,
@@ -193,7 +194,8 @@ describe('Actions API Logic', () => {
 				can_undo: false,
 				gmail_link: null,
 				has_been_undone: false,
-				undo_action_id: null
+				undo_action_id: null,
+				thread_summary: null
 			};
 
 			originalFetch = globalThis.fetch;
@@ -238,5 +240,43 @@ describe('Actions API Logic', () => {
 				})
 			);
 		});
+
+		it('should call POST /api/actions/{id}/cancel for cancel action', async () => {
+			const mockAction: Action = {
+				id: 'action-123',
+				org_id: 1,
+				user_id: 1,
+				account_id: 'account-1',
+				message_id: 'message-1',
+				decision_id: null,
+				action_type: 'delete',
+				parameters_json: {},
+				status: 'canceled',
+				error_message: 'Canceled by user',
+				executed_at: null,
+				undo_hint_json: {},
+				trace_id: null,
+				created_at: '2024-01-01T00:00:00Z',
+				updated_at: '2024-01-01T00:00:00Z'
+			};
+
+			originalFetch = globalThis.fetch;
+			globalThis.fetch = vi.fn().mockResolvedValue({
+				ok: true,
+				status: 200,
+				headers: new Headers({ 'content-length': '100' }),
+				json: () => Promise.resolve(mockAction)
+			});
+
+			const result = await post<Action>('/api/actions/action-123/cancel');
+
+			expect(result).toEqual(mockAction);
+			expect(fetch).toHaveBeenCalledWith(
+				'http://test-backend:8080/api/actions/action-123/cancel',
+				expect.objectContaining({
+					method: 'POST'
+				})
+			);
+		});
 	});
 });
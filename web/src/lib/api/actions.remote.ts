@@ -7,6 +7,7 @@ import { query, command } from '$app/server';
 import * as v from 'valibot';
 import { get, post, buildQueryString } from './client';
 import type {
+	Action,
 	ActionListItem,
 	ActionDetail,
 	PaginatedResponse,
@@ -62,6 +63,15 @@ const undoActionInputSchema = v.object({
 
 type UndoActionInput = v.InferOutput<typeof undoActionInputSchema>;
 
+/**
+ * Schema for canceling a delayed action.
+ */
+const cancelActionInputSchema = v.object({
+	actionId: v.pipe(v.string(), v.minLength(1))
+});
+
+type CancelActionInput = v.InferOutput<typeof cancelActionInputSchema>;
+
 // ============================================================================
 // Query Functions (Read Operations)
 // ============================================================================
@@ -152,3 +162,25 @@ export const undoAction = command(
 		return post<UndoActionResponse>(`/api/actions/${input.actionId}/undo`);
 	}
 );
+
+/**
+ * Cancels an action that is waiting out its execution delay.
+ *
+ * @example
+ * ```svelte
+ * <script lang="ts">
+ *   import { cancelAction } from '$lib/api/actions.remote';
+ *
+ *   async function handleCancel(actionId: string) {
+ *     const canceled = await cancelAction({ actionId });
+ *     console.log('Status:', canceled.status);
+ *   }
+ * </script>
+ * ```
+ */
+export const cancelAction = command(
+	cancelActionInputSchema,
+	async (input: CancelActionInput): Promise<Action> => {
+		return post<Action>(`/api/actions/${input.actionId}/cancel`);
+	}
+);
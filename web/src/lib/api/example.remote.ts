@@ -30,7 +30,8 @@ const listActionsInputSchema = v.object({
 			'failed',
 			'canceled',
 			'rejected',
-			'approved_pending'
+			'approved_pending',
+			'pending_execution'
 		] as const satisfies readonly ActionStatus[])
 	),
 	/** Filter by account ID */
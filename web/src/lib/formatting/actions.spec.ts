@@ -128,6 +128,7 @@ describe('Shared Action Formatting Functions', () => {
 			expect(getStatusVariant('queued')).toBe('secondary');
 			expect(getStatusVariant('executing')).toBe('secondary');
 			expect(getStatusVariant('approved_pending')).toBe('secondary');
+			expect(getStatusVariant('pending_execution')).toBe('secondary');
 		});
 
 		it('should return destructive for error/terminal statuses', () => {
@@ -146,6 +147,10 @@ describe('Shared Action Formatting Functions', () => {
 			expect(getStatusLabel('approved_pending')).toBe('Pending Approval');
 		});
 
+		it('should return "Pending Execution" for pending_execution', () => {
+			expect(getStatusLabel('pending_execution')).toBe('Pending Execution');
+		});
+
 		it('should capitalize single-word statuses', () => {
 			expect(getStatusLabel('completed')).toBe('Completed');
 			expect(getStatusLabel('queued')).toBe('Queued');
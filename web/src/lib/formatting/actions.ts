@@ -79,6 +79,7 @@ export function getStatusVariant(
 		case 'queued':
 		case 'executing':
 		case 'approved_pending':
+		case 'pending_execution':
 			return 'secondary';
 		case 'failed':
 		case 'canceled':
@@ -96,6 +97,8 @@ export function getStatusLabel(status: ActionStatus): string {
 	switch (status) {
 		case 'approved_pending':
 			return 'Pending Approval';
+		case 'pending_execution':
+			return 'Pending Execution';
 		default:
 			return status.charAt(0).toUpperCase() + status.slice(1);
 	}
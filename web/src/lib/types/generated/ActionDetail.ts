@@ -1,6 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { ActionStatus } from "./ActionStatus";
 import type { Decision } from "./Decision";
+import type { ThreadSummary } from "./ThreadSummary";
 
 /**
  * Detailed action information including decision and message data.
@@ -50,4 +51,8 @@ has_been_undone: boolean,
 /**
  * If undone, the ID of the undo action
  */
-undo_action_id: string | null, };
+undo_action_id: string | null, 
+/**
+ * Rolling summary of the message's thread, if one has been generated
+ */
+thread_summary: ThreadSummary | null, };
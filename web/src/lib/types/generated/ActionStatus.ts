@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type ActionStatus = "queued" | "executing" | "completed" | "failed" | "canceled" | "rejected" | "approved_pending";
+export type ActionStatus = "queued" | "executing" | "completed" | "failed" | "canceled" | "rejected" | "approved_pending" | "pending_execution";
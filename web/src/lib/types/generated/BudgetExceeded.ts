@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SpendWindow } from "./SpendWindow";
+
+/**
+ * A budget limit that has been reached.
+ */
+export type BudgetExceeded = { window: SpendWindow, 
+/**
+ * Feature the limit applies to; `None` for limits across all features.
+ */
+feature: string | null, 
+/**
+ * Account the limit applies to; `None` for limits across all accounts.
+ */
+account_id: string | null, limit_usd: number, spent_usd: number, };
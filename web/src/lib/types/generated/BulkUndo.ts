@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BulkUndoFilter } from "./BulkUndoFilter";
+import type { BulkUndoSkip } from "./BulkUndoSkip";
+
+/**
+ * A stored bulk undo request.
+ */
+export type BulkUndo = { id: string, org_id: number, user_id: number, filter: BulkUndoFilter, 
+/**
+ * Actions an undo job was enqueued for.
+ */
+action_ids: Array<string>, 
+/**
+ * Actions the filter selected but that could not be undone.
+ */
+skipped: Array<BulkUndoSkip>, created_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An action the filter selected that can be undone.
+ */
+export type BulkUndoCandidate = { action_id: string, account_id: string, action_type: string, created_at: string, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DecisionSource } from "./DecisionSource";
+
+/**
+ * Which actions to undo. Unset fields match everything; `action_types` empty matches
+ * every type.
+ */
+export type BulkUndoFilter = { 
+/**
+ * Deterministic rule that chose the actions.
+ */
+rule_id: string | null, source: DecisionSource | null, account_id: string | null, action_types: Array<string>, 
+/**
+ * Start of the window, on action creation time.
+ */
+since: string, 
+/**
+ * End of the window; defaults to now when previewing. Required to create a bulk undo.
+ */
+until: string | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BulkUndoItemStatus } from "./BulkUndoItemStatus";
+
+/**
+ * Progress of one action's undo.
+ */
+export type BulkUndoItem = { action_id: string, status: BulkUndoItemStatus, 
+/**
+ * The `undo_*` action recording the revert, once the job created it.
+ */
+undo_action_id: string | null, error: string | null, };
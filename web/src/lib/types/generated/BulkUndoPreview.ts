@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BulkUndoCandidate } from "./BulkUndoCandidate";
+import type { BulkUndoSkip } from "./BulkUndoSkip";
+
+/**
+ * The actions a filter selects, split by whether they can be undone. Oldest first.
+ */
+export type BulkUndoPreview = { 
+/**
+ * End of the window the preview used. Creating the bulk undo with this `until` covers
+ * the same actions, not ones created since.
+ */
+until: string, undoable: Array<BulkUndoCandidate>, not_undoable: Array<BulkUndoSkip>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BulkUndo } from "./BulkUndo";
+import type { BulkUndoItem } from "./BulkUndoItem";
+
+/**
+ * A bulk undo with the state of each of its undo jobs.
+ */
+export type BulkUndoProgress = { bulk_undo: BulkUndo, total: number, pending: number, completed: number, failed: number, items: Array<BulkUndoItem>, };
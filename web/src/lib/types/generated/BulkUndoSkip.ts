@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BulkUndoSkipReason } from "./BulkUndoSkipReason";
+
+/**
+ * An action the filter selected that was left alone.
+ */
+export type BulkUndoSkip = { action_id: string, action_type: string, reason: BulkUndoSkipReason, 
+/**
+ * Human readable explanation, e.g. `not completed (status failed)`.
+ */
+detail: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Why a selected action cannot be undone.
+ */
+export type BulkUndoSkipReason = "not_completed" | "not_supported" | "irreversible" | "already_undone" | "undo_attempted" | "undo_in_progress";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActionStatus } from "./ActionStatus";
+
+/**
+ * An action listed in a burst report.
+ */
+export type BurstAction = { action_id: string, message_id: string, subject: string | null, from_email: string | null, action_type: string, status: ActionStatus, rule_name: string | null, created_at: string, };
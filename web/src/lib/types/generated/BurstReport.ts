@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BurstAction } from "./BurstAction";
+import type { CircuitBreakerTrip } from "./CircuitBreakerTrip";
+
+/**
+ * What ran during the burst that tripped a breaker, and what it has held since.
+ */
+export type BurstReport = { trip: CircuitBreakerTrip, 
+/**
+ * Tracked actions run automatically in the exceeded window, oldest first.
+ */
+executed: Array<BurstAction>, 
+/**
+ * Actions sent to approval because the breaker was open, oldest first.
+ */
+held: Array<BurstAction>, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CircuitBreakerScope } from "./CircuitBreakerScope";
+
+/**
+ * A recorded trip. It stays open, holding actions for approval, until `reset_at` is set.
+ */
+export type CircuitBreakerTrip = { id: string, org_id: number, user_id: number, account_id: string, scope: CircuitBreakerScope, 
+/**
+ * The rule that was paused, for rule trips.
+ */
+rule_id: string | null, rule_name: string | null, 
+/**
+ * Action types tracked when the breaker tripped.
+ */
+action_types: Array<string>, 
+/**
+ * The window that was exceeded.
+ */
+window_seconds: number, threshold: number, 
+/**
+ * Actions that had run within the window, including the one that tripped it.
+ */
+action_count: number, tripped_at: string, reset_at: string | null, };
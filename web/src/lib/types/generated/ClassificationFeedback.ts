@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FeedbackSource } from "./FeedbackSource";
+
+/**
+ * A correction to a classifier decision, kept as a few-shot example for later prompts.
+ *
+ * Stores the message features the decision was based on, the action that turned out to be
+ * wrong and, when known, the action the user wanted instead.
+ */
+export type ClassificationFeedback = { id: string, org_id: number, user_id: number, account_id: string, message_id: string, 
+/**
+ * The undone or rejected action.
+ */
+action_id: string, decision_id: string | null, source: FeedbackSource, sender_email: string | null, sender_domain: string | null, subject: string | null, snippet: string | null, wrong_action_type: string, wrong_parameters_json: Record<string, unknown>, corrected_action_type: string | null, corrected_parameters_json: Record<string, unknown> | null, created_at: string, updated_at: string, };
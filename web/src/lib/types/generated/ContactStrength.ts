@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How strong the relationship with a contact is, derived from how often the account
+ * has written to and replied to them.
+ */
+export type ContactStrength = "weak" | "moderate" | "strong";
@@ -1,5 +1,18 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RuleSchedule } from "./RuleSchedule";
 import type { RuleScope } from "./RuleScope";
 import type { SafeMode } from "./SafeMode";
 
-export type DeterministicRule = { id: string, org_id: number, user_id: number | null, name: string, description: string | null, scope: RuleScope, scope_ref: string | null, priority: number, enabled: boolean, disabled_reason: string | null, conditions_json: Record<string, unknown>, action_type: string, action_parameters_json: Record<string, unknown>, safe_mode: SafeMode, created_at: string, updated_at: string, };
+export type DeterministicRule = { id: string, org_id: number, user_id: number | null, name: string, description: string | null, scope: RuleScope, scope_ref: string | null, priority: number, enabled: boolean, disabled_reason: string | null, conditions_json: Record<string, unknown>, action_type: string, action_parameters_json: Record<string, unknown>, safe_mode: SafeMode, 
+/**
+ * The rule only applies from this instant (inclusive).
+ */
+active_from: string | null, 
+/**
+ * The rule stops applying at this instant and is then auto-disabled.
+ */
+active_until: string | null, 
+/**
+ * Recurring weekly window, checked in addition to active_from/active_until.
+ */
+schedule: RuleSchedule | null, created_at: string, updated_at: string, };
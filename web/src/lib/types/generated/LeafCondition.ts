@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContactStrength } from "./ContactStrength";
 
-export type LeafCondition = { "type": "sender_email", value: string, } | { "type": "sender_domain", value: string, } | { "type": "subject_contains", value: string, } | { "type": "subject_regex", value: string, } | { "type": "header_match", header: string, pattern: string, } | { "type": "label_present", value: string, };
+export type LeafCondition = { "type": "sender_email", value: string, } | { "type": "sender_domain", value: string, } | { "type": "subject_contains", value: string, } | { "type": "subject_regex", value: string, } | { "type": "header_match", header: string, pattern: string, } | { "type": "label_present", value: string, } | { "type": "in_list", list: string, } | { "type": "known_contact", min_strength?: ContactStrength, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type LintKind = "invalid_condition" | "shadowed" | "conflicting_actions" | "unreachable" | "invalid_regex" | "slow_regex" | "unknown_label" | "unknown_sender_list" | "unknown_reply_template";
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LintKind } from "./LintKind";
+
+export type LintWarning = { kind: LintKind, rule_id: string, rule_name: string, 
+/**
+ * The other rule involved, for shadowing and conflict warnings.
+ */
+related_rule_id: string | null, message: string, };
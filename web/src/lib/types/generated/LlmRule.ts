@@ -1,4 +1,17 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RuleSchedule } from "./RuleSchedule";
 import type { RuleScope } from "./RuleScope";
 
-export type LlmRule = { id: string, org_id: number, user_id: number | null, name: string, description: string | null, scope: RuleScope, scope_ref: string | null, rule_text: string, enabled: boolean, metadata_json: Record<string, unknown>, created_at: string, updated_at: string, };
+export type LlmRule = { id: string, org_id: number, user_id: number | null, name: string, description: string | null, scope: RuleScope, scope_ref: string | null, rule_text: string, enabled: boolean, disabled_reason: string | null, metadata_json: Record<string, unknown>, 
+/**
+ * The rule only applies from this instant (inclusive).
+ */
+active_from: string | null, 
+/**
+ * The rule stops applying at this instant and is then auto-disabled.
+ */
+active_until: string | null, 
+/**
+ * Recurring weekly window, checked in addition to active_from/active_until.
+ */
+schedule: RuleSchedule | null, created_at: string, updated_at: string, };
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PolicyEffect } from "./PolicyEffect";
+
+/**
+ * A stored safety policy clause, applied by the `SafetyEnforcer` on top of `PolicyConfig`.
+ *
+ * A clause matches a decision when every non-empty filter matches: its account, one of
+ * its action types, one of its senders, and one of its labels.
+ */
+export type PolicyClause = { id: string, org_id: number, user_id: number | null, name: string, description: string | null, 
+/**
+ * Limits the clause to one account. Account clauses take precedence over global
+ * clauses when both set a confidence threshold.
+ */
+account_id: string | null, effect: PolicyEffect, 
+/**
+ * Threshold used by `min_confidence` clauses.
+ */
+min_confidence: number | null, 
+/**
+ * Action types (snake_case) the clause covers. Empty covers every action.
+ */
+action_types: Array<string>, 
+/**
+ * Sender addresses, `*@domain` wildcards, or bare domains. Empty covers every sender.
+ */
+senders: Array<string>, 
+/**
+ * Label names or IDs the message must carry. Empty covers every message.
+ */
+labels: Array<string>, enabled: boolean, created_at: string, updated_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a policy clause does to the decisions it matches.
+ */
+export type PolicyEffect = "require_approval" | "skip_approval" | "min_confidence" | "protect";
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TemplateSlot } from "./TemplateSlot";
+
+export type ReplyTemplate = { id: string, org_id: number, user_id: number, 
+/**
+ * Unique, case-insensitive; what rules and the LLM refer to.
+ */
+name: string, 
+/**
+ * Tells the LLM when the template fits.
+ */
+description: string | null, 
+/**
+ * Subject template. Without one, replies use `Re: <original subject>`.
+ */
+subject: string | null, 
+/**
+ * Plain text body template.
+ */
+body: string, 
+/**
+ * HTML body template. Without one, the HTML part is the escaped plain text.
+ */
+body_html: string | null, slots: Array<TemplateSlot>, created_at: string, updated_at: string, };
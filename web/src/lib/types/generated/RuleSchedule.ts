@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScheduleDay } from "./ScheduleDay";
+
+/**
+ * A recurring weekly window during which a rule is active, e.g. weekdays 09:00–17:00.
+ *
+ * `start` and `end` are `HH:MM` wall-clock times in `timezone` (an IANA name). When `end`
+ * is earlier than `start` the window runs overnight and belongs to the day it starts on;
+ * equal times cover the whole day. An empty `days` list means every day.
+ */
+export type RuleSchedule = { days: Array<ScheduleDay>, start: string, end: string, timezone: string, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A named list of senders that conditions can reference with `in_list`.
+ *
+ * Entries containing `@` match like `sender_email` (including `*@domain` wildcards);
+ * anything else is treated as a domain and matches like `sender_domain`.
+ */
+export type SenderList = { id: string, org_id: number, user_id: number | null, name: string, description: string | null, entries: Array<string>, created_at: string, updated_at: string, };
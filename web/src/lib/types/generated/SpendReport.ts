@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetExceeded } from "./BudgetExceeded";
+import type { SpendSummary } from "./SpendSummary";
+
+/**
+ * Daily and monthly spend plus every budget limit currently exceeded.
+ */
+export type SpendReport = { daily: SpendSummary, monthly: SpendSummary, exceeded: Array<BudgetExceeded>, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SpendTotals } from "./SpendTotals";
+import type { SpendWindow } from "./SpendWindow";
+
+/**
+ * Spend within one window, broken down by feature and by account.
+ */
+export type SpendSummary = { window: SpendWindow, since: string, total: SpendTotals, by_feature: { [key in string]?: SpendTotals }, 
+/**
+ * Calls made outside an account (e.g. the rules assistant) only appear in the total.
+ */
+by_account: { [key in string]?: SpendTotals }, 
+/**
+ * Models with calls in the window but no configured price; their cost counts as zero.
+ */
+unpriced_models: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Call count, tokens and cost for a group of LLM calls.
+ */
+export type SpendTotals = { calls: number, input_tokens: number, output_tokens: number, cost_usd: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Budget window. Both windows start at midnight UTC.
+ */
+export type SpendWindow = "daily" | "monthly";
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A value the template leaves for a rule or the LLM to fill.
+ */
+export type TemplateSlot = { name: string, 
+/**
+ * Tells the LLM what to put in the slot.
+ */
+description: string | null, 
+/**
+ * Rendering fails when a required slot is missing or blank.
+ */
+required: boolean, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ThreadSummary } from "./ThreadSummary";
+
+/**
+ * A thread with its rolling summary.
+ */
+export type ThreadDetail = { id: string, account_id: string, provider_thread_id: string, subject: string | null, snippet: string | null, last_message_at: string | null, 
+/**
+ * Messages of the thread stored locally
+ */
+message_count: number, 
+/**
+ * The summary, if one has been generated
+ */
+summary: ThreadSummary | null, };
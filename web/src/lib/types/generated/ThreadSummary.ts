@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Rolling LLM summary of a thread. Each update folds the messages that arrived since
+ * `last_message_id` into the previous summary.
+ */
+export type ThreadSummary = { thread_id: string, account_id: string, summary: string, participants: Array<string>, open_questions: Array<string>, commitments: Array<string>, 
+/**
+ * Messages of the thread covered by the summary.
+ */
+message_count: number, 
+/**
+ * The newest message covered by the summary.
+ */
+last_message_id: string, model: string, llm_call_id: string | null, created_at: string, updated_at: string, org_id: number, user_id: number, };
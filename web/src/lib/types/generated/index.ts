@@ -9,22 +9,54 @@ export type { ActionLink } from './ActionLink';
 export type { ActionLinkRelationType } from './ActionLinkRelationType';
 export type { ActionListItem } from './ActionListItem';
 export type { ActionStatus } from './ActionStatus';
+export type { BudgetExceeded } from './BudgetExceeded';
+export type { BulkUndo } from './BulkUndo';
+export type { BulkUndoCandidate } from './BulkUndoCandidate';
+export type { BulkUndoFilter } from './BulkUndoFilter';
+export type { BulkUndoItem } from './BulkUndoItem';
+export type { BulkUndoItemStatus } from './BulkUndoItemStatus';
+export type { BulkUndoPreview } from './BulkUndoPreview';
+export type { BulkUndoProgress } from './BulkUndoProgress';
+export type { BulkUndoSkip } from './BulkUndoSkip';
+export type { BulkUndoSkipReason } from './BulkUndoSkipReason';
+export type { BurstAction } from './BurstAction';
+export type { BurstReport } from './BurstReport';
+export type { CircuitBreakerScope } from './CircuitBreakerScope';
+export type { CircuitBreakerTrip } from './CircuitBreakerTrip';
+export type { ClassificationFeedback } from './ClassificationFeedback';
+export type { ContactStrength } from './ContactStrength';
 export type { Decision } from './Decision';
 export type { DecisionSource } from './DecisionSource';
 export type { DeterministicRule } from './DeterministicRule';
+export type { FeedbackSource } from './FeedbackSource';
 export type { Header } from './Header';
 export type { LabelColors } from './LabelColors';
 export type { LabelSummary } from './LabelSummary';
 export type { LeafCondition } from './LeafCondition';
+export type { LintKind } from './LintKind';
+export type { LintWarning } from './LintWarning';
 export type { LlmRule } from './LlmRule';
 export type { LogicalCondition } from './LogicalCondition';
 export type { LogicalOperator } from './LogicalOperator';
 export type { Mailbox } from './Mailbox';
 export type { MessageSummary } from './MessageSummary';
 export type { PaginatedResponse } from './PaginatedResponse';
+export type { PolicyClause } from './PolicyClause';
+export type { PolicyEffect } from './PolicyEffect';
+export type { ReplyTemplate } from './ReplyTemplate';
+export type { RuleSchedule } from './RuleSchedule';
 export type { RuleScope } from './RuleScope';
 export type { SafeMode } from './SafeMode';
+export type { ScheduleDay } from './ScheduleDay';
+export type { SenderList } from './SenderList';
+export type { SpendReport } from './SpendReport';
+export type { SpendSummary } from './SpendSummary';
+export type { SpendTotals } from './SpendTotals';
+export type { SpendWindow } from './SpendWindow';
 export type { SyncStatus } from './SyncStatus';
+export type { TemplateSlot } from './TemplateSlot';
+export type { ThreadDetail } from './ThreadDetail';
+export type { ThreadSummary } from './ThreadSummary';
 export type { UndoActionResponse } from './UndoActionResponse';
 
 // Condition is an untagged enum in Rust that ts-rs doesn't handle well,